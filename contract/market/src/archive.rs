@@ -0,0 +1,28 @@
+// Retiring a settled market. Once the grace period after settlement has passed, the market
+// commits to its final state, pays out anything still unclaimed, reports the commitment to
+// the registry and closes its chain.
+// The state can be exported during the grace period and checked against the commitment
+// after the chain is gone.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// How long holders have to claim after the market settles; what they leave is then sent to them
+pub const ARCHIVE_GRACE_PERIOD: u64 = 30 * 86_400_000_000;
+
+// Domain separator so a snapshot hash can't be confused with any other digest
+const SNAPSHOT_DOMAIN: &[u8] = b"oddsstream-snapshot-v1";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ArchiveRecord {
+    pub archived_at: u64,
+    pub snapshot_hash: [u8; 32],
+}
+
+// Positions and the full audit trail are both certified through their roots, so committing
+// to those commits to everything an export carries
+pub fn snapshot_hash(market_id: &str, position_root: &[u8; 32], audit_head: &[u8; 32], audit_length: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SNAPSHOT_DOMAIN);
+    hasher.update(bcs::to_bytes(&(market_id, position_root, audit_head, audit_length)).expect("snapshot is serializable"));
+    hasher.finalize().into()
+}
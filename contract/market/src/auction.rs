@@ -0,0 +1,82 @@
+// Call auction: orders are collected for a period, then all execute at one price per side
+use crate::{Order, OrderSide};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AuctionConfig {
+    // Micros the opening auction collects orders for; 0 opens straight into continuous trading
+    pub opening_duration: u64,
+    // Collect orders while the circuit breaker is tripped and uncross them when the halt ends,
+    // instead of rejecting them
+    pub reopen_after_halt: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CallAuction {
+    // Micros timestamp after which the auction may be uncrossed
+    pub ends_at: u64,
+    // User chain -> orders collected, in arrival order
+    pub orders: BTreeMap<ChainId, Vec<Order>>,
+}
+
+impl CallAuction {
+    pub fn new(ends_at: u64) -> Self {
+        Self { ends_at, orders: BTreeMap::new() }
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        now >= self.ends_at
+    }
+
+    pub fn collect(&mut self, user_chain_id: ChainId, orders: Vec<Order>) {
+        self.orders.entry(user_chain_id).or_default().extend(orders);
+    }
+
+    pub fn find(&self, user_chain_id: ChainId, order_id: u64) -> Option<&Order> {
+        self.orders.get(&user_chain_id)?.iter().find(|order| order.id == order_id)
+    }
+
+    // Swaps in `order` for the queued order with the same id, keeping its place in the queue
+    pub fn replace(&mut self, user_chain_id: ChainId, order: Order) -> bool {
+        let queued = self.orders.get_mut(&user_chain_id).and_then(|orders| orders.iter_mut().find(|queued| queued.id == order.id));
+        match queued {
+            Some(queued) => {
+                *queued = order;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn order_count(&self) -> usize {
+        self.orders.values().map(Vec::len).sum()
+    }
+
+    // Shares requested on the YES and NO side
+    pub fn demand(&self) -> (Amount, Amount) {
+        let mut yes = Amount::zero();
+        let mut no = Amount::zero();
+        for order in self.orders.values().flatten() {
+            match order.side {
+                OrderSide::BuyYes => yes += order.amount,
+                OrderSide::BuyNo => no += order.amount,
+            }
+        }
+        (yes, no)
+    }
+
+    // YES and NO prices once every collected share has joined the pools. Nobody can trade
+    // ahead of the rest, so there is nothing to snipe; until the end this is only indicative.
+    pub fn clearing_prices(&self, pool_yes: Amount, pool_no: Amount) -> (f64, f64) {
+        let (yes_demand, no_demand) = self.demand();
+        let yes = pool_yes + yes_demand;
+        let no = pool_no + no_demand;
+        let total = yes + no;
+        if total == Amount::zero() {
+            return (0.5, 0.5);
+        }
+        ((no / total).into(), (yes / total).into())
+    }
+}
@@ -0,0 +1,100 @@
+// Hash-chained audit trail of every state transition that moves positions or funds
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+
+// Event stream carrying the full log; state only keeps the most recent records
+pub const AUDIT_STREAM: &[u8] = b"audit";
+
+pub const MAX_AUDIT_RECORDS: usize = 10_000;
+
+// Domain separator so an audit hash can't be confused with any other digest
+const AUDIT_DOMAIN: &[u8] = b"oddsstream-audit-v1";
+
+// BCS has no floats, so everything here is exact
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AuditEvent {
+    OrdersAccepted {
+        user_chain_id: ChainId,
+        nonce: u64,
+        order_count: u64,
+    },
+    Fill {
+        user_chain_id: ChainId,
+        buy_yes: bool,
+        size: Amount,
+        // What the shares cost, before fees
+        cost: Amount,
+    },
+    Resolved {
+        outcome: bool,
+    },
+    Payout {
+        user_chain_id: ChainId,
+        amount: Amount,
+    },
+    StageResolved {
+        stage: u32,
+        outcome: bool,
+    },
+    // A queued order was replaced in place; it keeps its id
+    OrderAmended {
+        user_chain_id: ChainId,
+        order_id: u64,
+        amount: Amount,
+        max_price: Option<Amount>,
+    },
+    // The oracle missed its deadline; the fallback committee may now resolve
+    OracleEscalated {
+        member_count: u32,
+    },
+    // Nobody resolved before the refund deadline; positions are refunded
+    Voided,
+    // Yield collected from the market's yield source and credited to providers or the treasury
+    YieldHarvested {
+        earned: Amount,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditRecord {
+    // Contiguous from 1
+    pub sequence: u64,
+    pub timestamp: u64,
+    pub event: AuditEvent,
+    pub prev_hash: [u8; 32],
+    pub hash: [u8; 32],
+}
+
+// The log's head is part of the market's state, so it is certified by the
+// validators with every block; an exported log is checked against it
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AuditLog {
+    pub head: [u8; 32],
+    pub length: u64,
+    pub recent: VecDeque<AuditRecord>,
+}
+
+pub fn record_hash(prev_hash: &[u8; 32], sequence: u64, timestamp: u64, event: &AuditEvent) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(AUDIT_DOMAIN);
+    hasher.update(prev_hash);
+    hasher.update(bcs::to_bytes(&(sequence, timestamp, event)).expect("audit event is serializable"));
+    hasher.finalize().into()
+}
+
+impl AuditLog {
+    pub fn append(&mut self, event: AuditEvent, timestamp: u64) -> AuditRecord {
+        let sequence = self.length + 1;
+        let hash = record_hash(&self.head, sequence, timestamp, &event);
+        let record = AuditRecord { sequence, timestamp, event, prev_hash: self.head, hash };
+        self.head = hash;
+        self.length = sequence;
+        if self.recent.len() == MAX_AUDIT_RECORDS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(record.clone());
+        record
+    }
+}
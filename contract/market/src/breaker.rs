@@ -0,0 +1,65 @@
+// Circuit breaker: halts trading after an extreme odds move, such as an oracle-driven flash move
+use serde::{Deserialize, Serialize};
+
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BreakerConfig {
+    // Largest swing of the YES probability allowed within one window, in basis points; 0 disables the breaker
+    pub max_move_bps: u32,
+    // Length of the measurement window in blocks
+    pub window_blocks: u64,
+    // Micros new orders are rejected for once the breaker trips
+    pub cooldown: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CircuitBreaker {
+    pub config: BreakerConfig,
+    // Block the current window opened at, and the YES probability at that point
+    window_start: Option<u64>,
+    reference_odds: f64,
+    // Micros timestamp until which orders are rejected; 0 when trading normally
+    pub halted_until: u64,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    pub fn is_halted(&self, now: u64) -> bool {
+        now < self.halted_until
+    }
+
+    // YES probability the open window measures moves from; `None` until a fill opens one
+    pub fn reference_odds(&self) -> Option<f64> {
+        self.window_start.map(|_| self.reference_odds)
+    }
+
+    // Start a new window at `yes_odds` if the current one has run its course
+    pub fn roll_window(&mut self, block: u64, yes_odds: f64) {
+        let expired = self
+            .window_start
+            .is_none_or(|start| block >= start.saturating_add(self.config.window_blocks));
+        if expired {
+            self.window_start = Some(block);
+            self.reference_odds = yes_odds;
+        }
+    }
+
+    // Called after every fill; trips the breaker if the odds left the allowed band
+    pub fn check(&mut self, yes_odds: f64, now: u64) -> bool {
+        if self.config.max_move_bps == 0 {
+            return false;
+        }
+        let max_move = f64::from(self.config.max_move_bps) / BPS_DENOMINATOR;
+        if (yes_odds - self.reference_odds).abs() <= max_move {
+            return false;
+        }
+        self.halted_until = now.saturating_add(self.config.cooldown);
+        // Measure afresh from wherever the odds settled once trading resumes
+        self.window_start = None;
+        true
+    }
+}
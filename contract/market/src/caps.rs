@@ -0,0 +1,39 @@
+// Open-interest caps: keep thin markets too small to be worth manipulating the oracle over
+use crate::validation::RejectionReason;
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MarketCaps {
+    // Most the YES and NO pools may hold between them; `None` is uncapped
+    pub max_pool: Option<Amount>,
+    // Most a single user chain may have paid for its shares; `None` is uncapped
+    pub max_user_exposure: Option<Amount>,
+}
+
+impl MarketCaps {
+    // What orders can still add to the pools
+    pub fn remaining_pool(&self, pool: Amount) -> Option<Amount> {
+        self.max_pool.map(|cap| cap.saturating_sub(pool))
+    }
+
+    // What a user chain that has paid `exposure` so far can still spend
+    pub fn remaining_exposure(&self, exposure: Amount) -> Option<Amount> {
+        self.max_user_exposure.map(|cap| cap.saturating_sub(exposure))
+    }
+
+    // Whether an order adding `shares` to the pools at `cost` to a user already exposed by `exposure` fits
+    pub fn check(&self, pool: Amount, shares: Amount, exposure: Amount, cost: Amount) -> Result<(), RejectionReason> {
+        if let Some(remaining) = self.remaining_pool(pool) {
+            if shares > remaining {
+                return Err(RejectionReason::PoolCapReached { remaining });
+            }
+        }
+        if let Some(remaining) = self.remaining_exposure(exposure) {
+            if cost > remaining {
+                return Err(RejectionReason::ExposureCapReached { remaining });
+            }
+        }
+        Ok(())
+    }
+}
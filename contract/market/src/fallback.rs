@@ -0,0 +1,37 @@
+// Deadlines for an oracle that never reports: past `resolution_time + escalate_after` the
+// committee may resolve in its place, and past `resolution_time + refund_after` the market
+// is voided and every position refunded
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FallbackPolicy {
+    // Micros past the resolution time before a committee may resolve instead of the
+    // configured oracle; `None` never escalates
+    pub escalate_after: Option<u64>,
+    // Members of the fallback committee
+    pub committee_size: u32,
+    // Micros past the resolution time before an unresolved market is voided; `None` waits forever
+    pub refund_after: Option<u64>,
+}
+
+impl FallbackPolicy {
+    // A committee needs members, and escalation is pointless once refunds have started
+    pub fn is_valid(&self) -> bool {
+        let escalation_ok = self.escalate_after.is_none() || self.committee_size > 0;
+        let order_ok = match (self.escalate_after, self.refund_after) {
+            (Some(escalate), Some(refund)) => escalate < refund,
+            _ => true,
+        };
+        escalation_ok && order_ok
+    }
+
+    // Micros timestamp the fallback committee may resolve from
+    pub fn escalates_at(&self, resolution_time: u64) -> Option<u64> {
+        self.escalate_after.map(|after| resolution_time.saturating_add(after))
+    }
+
+    // Micros timestamp an unresolved market is voided at
+    pub fn refunds_at(&self, resolution_time: u64) -> Option<u64> {
+        self.refund_after.map(|after| resolution_time.saturating_add(after))
+    }
+}
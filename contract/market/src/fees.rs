@@ -0,0 +1,109 @@
+// Trading fee configuration, fixed at market creation
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const BPS_DENOMINATOR: u128 = 10_000;
+// Fee tiers go by what a user chain has paid for shares over this many days
+pub const VOLUME_WINDOW_DAYS: u64 = 30;
+const MICROS_PER_DAY: u64 = 86_400 * 1_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FeeSchedule {
+    // Fee charged on the cost of every filled order
+    pub taker_fee_bps: u32,
+    // Portion of the taker fee paid to the referrer of the order, if any
+    pub referral_share_bps: u32,
+    // Portion of the taker fee set aside to pay the market's oracle
+    pub oracle_share_bps: u32,
+    // Portion of the taker fee owed to the protocol treasury
+    pub protocol_share_bps: u32,
+    // Micros after a deposit during which the provider's liquidity is locked
+    pub lp_cooldown: u64,
+    // If non-zero, liquidity can leave during the cooldown by paying this fee to the pool
+    pub early_exit_fee_bps: u32,
+    // Fee on fills against resting orders; every fill is a taker until the order book lands
+    pub maker_fee_bps: u32,
+    // Discounts by trailing volume, ascending by `min_volume`; the base rates apply below the first
+    pub tiers: Vec<FeeTier>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FeeTier {
+    pub min_volume: Amount,
+    pub taker_fee_bps: u32,
+    pub maker_fee_bps: u32,
+}
+
+// A user chain's spending per day, kept for the trailing window only
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct VolumeHistory {
+    daily: BTreeMap<u64, Amount>,
+}
+
+impl VolumeHistory {
+    pub fn record(&mut self, now: u64, amount: Amount) {
+        let today = now / MICROS_PER_DAY;
+        *self.daily.entry(today).or_insert(Amount::zero()) += amount;
+        self.daily = self.daily.split_off(&today.saturating_sub(VOLUME_WINDOW_DAYS - 1));
+    }
+
+    pub fn trailing(&self, now: u64) -> Amount {
+        let today = now / MICROS_PER_DAY;
+        self.daily
+            .range(today.saturating_sub(VOLUME_WINDOW_DAYS - 1)..)
+            .fold(Amount::zero(), |sum, (_, amount)| sum + *amount)
+    }
+}
+
+pub enum WithdrawalCheck {
+    Free,
+    EarlyExit(Amount),
+    Locked,
+}
+
+impl FeeSchedule {
+    // Index of the highest tier `volume` reaches, if any
+    pub fn tier_index(&self, volume: Amount) -> Option<usize> {
+        self.tiers.iter().rposition(|tier| volume >= tier.min_volume)
+    }
+
+    // Taker and maker rates for a user chain with trailing `volume`
+    pub fn rates(&self, volume: Amount) -> (u32, u32) {
+        match self.tier_index(volume) {
+            Some(index) => (self.tiers[index].taker_fee_bps, self.tiers[index].maker_fee_bps),
+            None => (self.taker_fee_bps, self.maker_fee_bps),
+        }
+    }
+
+    pub fn taker_fee(&self, cost: Amount, volume: Amount) -> Amount {
+        apply_bps(cost, self.rates(volume).0)
+    }
+
+    pub fn referral_cut(&self, fee: Amount) -> Amount {
+        apply_bps(fee, self.referral_share_bps)
+    }
+
+    pub fn oracle_cut(&self, fee: Amount) -> Amount {
+        apply_bps(fee, self.oracle_share_bps)
+    }
+
+    pub fn protocol_cut(&self, fee: Amount) -> Amount {
+        apply_bps(fee, self.protocol_share_bps)
+    }
+    
+    // Guards against just-in-time liquidity pulled right before resolution
+    pub fn check_withdrawal(&self, value: Amount, deposited_at: u64, now: u64) -> WithdrawalCheck {
+        if now >= deposited_at.saturating_add(self.lp_cooldown) {
+            WithdrawalCheck::Free
+        } else if self.early_exit_fee_bps > 0 {
+            WithdrawalCheck::EarlyExit(apply_bps(value, self.early_exit_fee_bps))
+        } else {
+            WithdrawalCheck::Locked
+        }
+    }
+}
+
+pub fn apply_bps(amount: Amount, bps: u32) -> Amount {
+    Amount::from_attos(u128::from(amount) * u128::from(bps) / BPS_DENOMINATOR)
+}
@@ -0,0 +1,876 @@
+// Read-only GraphQL extension served by each market chain
+use crate::lifecycle::MarketStatus;
+use crate::saga::{LegVote, SagaStatus};
+use crate::transfers::TransferId;
+use crate::{merkle, EvidenceKind, MarketState, OracleType};
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use linera_sdk::base::{Amount, ChainId};
+use std::sync::Arc;
+
+pub type MarketSchema = Schema<MarketQueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(state: Arc<MarketState>) -> MarketSchema {
+    Schema::build(MarketQueryRoot { state }, EmptyMutation, EmptySubscription).finish()
+}
+
+pub struct MarketQueryRoot {
+    state: Arc<MarketState>,
+}
+
+// On-chain half of the market metrics; volatility comes from the indexer's odds history
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct MarketStats {
+    pub market_id: String,
+    // Liquidity standing behind each side of the pool
+    pub yes_depth: String,
+    pub no_depth: String,
+    pub trade_count: u64,
+    pub traded_volume: String,
+    pub average_trade_size: f64,
+    pub last_trade_at: Option<u64>,
+    // What orders can still add to the pools; `None` when the pool is uncapped
+    pub remaining_pool_capacity: Option<String>,
+    pub max_user_exposure: Option<String>,
+    // What the queried owner can still spend; `None` when uncapped or no owner was given
+    pub remaining_user_exposure: Option<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct FeeScheduleInfo {
+    pub taker_fee_bps: u32,
+    pub referral_share_bps: u32,
+    pub oracle_share_bps: u32,
+    pub protocol_share_bps: u32,
+    pub lp_cooldown_secs: u64,
+    pub early_exit_fee_bps: u32,
+    pub maker_fee_bps: u32,
+    pub tiers: Vec<FeeTierInfo>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct FeeTierInfo {
+    // Trailing 30-day volume from which the tier applies
+    pub min_volume: String,
+    pub taker_fee_bps: u32,
+    pub maker_fee_bps: u32,
+}
+
+// The rates one user chain trades at, given its trailing volume
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct UserFeeTierInfo {
+    pub trailing_volume: String,
+    // Index into the schedule's tiers; null at the base rates
+    pub tier: Option<u32>,
+    pub taker_fee_bps: u32,
+    pub maker_fee_bps: u32,
+    // Volume at which the next tier starts; null at the top
+    pub next_tier_volume: Option<String>,
+}
+
+// Settings fixed when the market was created, for clients that display a market in full
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct MarketConfigInfo {
+    pub market_id: String,
+    pub description: String,
+    // Pricing curve; every market prices off its YES and NO pools as a constant product
+    pub amm_kind: String,
+    // "FAST_TEE", "COMMITTEE" or "HYBRID"
+    pub oracle_kind: String,
+    // Hex key the TEE oracle signs with; only for FAST_TEE
+    pub oracle_public_key: Option<String>,
+    // Only for COMMITTEE
+    pub committee_size: Option<u32>,
+    pub registry_chain: String,
+    pub settlement_token: Option<String>,
+    pub allowlist: Option<String>,
+    // Set for conditional markets
+    pub parent_market_id: Option<String>,
+    pub activates_on: Option<bool>,
+    pub stage_count: u32,
+    pub opening_auction_secs: u64,
+    pub reopen_after_halt: bool,
+    pub max_pool: Option<String>,
+    pub max_user_exposure: Option<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct CircuitBreakerInfo {
+    // 0 when the market has no breaker
+    pub max_move_bps: u32,
+    pub window_blocks: u64,
+    pub cooldown_secs: u64,
+    // Micros timestamp trading resumes at, while halted
+    pub halted_until: Option<u64>,
+}
+
+// Everything a batch is checked against, read in one go so a client can replay it locally
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct SimulationStateInfo {
+    pub status: String,
+    // Whether a batch would get past the status and condition checks
+    pub accepting: bool,
+    // Orders queue for a call auction rather than filling
+    pub in_auction: bool,
+    pub protocol_paused: bool,
+    pub halted_until: Option<u64>,
+    // A halted market reopens through an auction instead of refusing orders
+    pub reopen_after_halt: bool,
+    pub pool_yes: String,
+    pub pool_no: String,
+    pub yes_odds: f64,
+    pub no_odds: f64,
+    pub taker_fee_bps: u32,
+    pub remaining_pool_capacity: Option<String>,
+    // Only when an owner was given
+    pub remaining_user_exposure: Option<String>,
+    // 0 when the market has no breaker
+    pub breaker_max_move_bps: u32,
+    pub breaker_cooldown_secs: u64,
+    // Null until a fill opens a breaker window; the next fill then measures from its own odds
+    pub breaker_reference_odds: Option<f64>,
+}
+
+// Where idle pool funds earn yield and what they have earned
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct YieldInfo {
+    pub source: String,
+    pub deposit_bps: u32,
+    // "Providers" or "Treasury"
+    pub beneficiary: String,
+    // Principal the source holds now; 0 once trading has stopped
+    pub deposited: String,
+    pub harvested: String,
+}
+
+// Orders queued in a call auction and the prices they would execute at right now
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct AuctionInfo {
+    pub ends_at: u64,
+    pub order_count: u64,
+    pub yes_demand: String,
+    pub no_demand: String,
+    pub indicative_yes_price: f64,
+    pub indicative_no_price: f64,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub timestamp: u64,
+    // The `AuditEvent` as JSON; clients hash its BCS encoding
+    pub event: String,
+    // Hex-encoded sha256 chain links
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct AuditHead {
+    pub length: u64,
+    pub hash: String,
+}
+
+// One market of an atomic batch and how it voted
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct SagaLegInfo {
+    pub market_chain: String,
+    pub order_count: u32,
+    // PENDING, RESERVED or REFUSED
+    pub vote: String,
+    // Set once reserved; before fees
+    pub total_cost: Option<String>,
+}
+
+// An atomic batch coordinated by this chain
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct SagaInfo {
+    pub saga_id: u64,
+    // RESERVING, COMMITTED or ABORTED
+    pub status: String,
+    pub created_at: u64,
+    pub legs: Vec<SagaLegInfo>,
+    // Orders refused, and why, by the markets that refused
+    pub rejections: Vec<String>,
+}
+
+// A transfer this market issued; the receiving chain applies `sequence` once
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct IssuedTransferInfo {
+    pub sequence: u64,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub token: Option<String>,
+    pub issued_at: u64,
+}
+
+// A confirmation or payout the receiving chain hasn't acknowledged
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct OutboxMessageInfo {
+    pub outbox_id: u64,
+    pub destination: String,
+    pub kind: String,
+    pub attempts: u32,
+    pub first_sent_at: u64,
+    pub last_sent_at: u64,
+    // `None` once automatic retries are used up; only `RetryMessage` resends it then
+    pub next_retry_at: Option<u64>,
+}
+
+// Who signs resolutions now; unlike `MarketConfigInfo` this changes as FastTee keys rotate
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct OracleInfo {
+    pub oracle_kind: String,
+    // Hex key resolutions are signed with
+    pub public_key: Option<String>,
+    // Set during a rotation's overlap window, when either key is accepted
+    pub next_public_key: Option<String>,
+    pub rotation_announced_at: Option<u64>,
+    pub rotation_completes_at: Option<u64>,
+    pub guardian_key: Option<String>,
+    pub escalated: bool,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct StatusChangeEntry {
+    pub status: String,
+    // Set once resolved
+    pub outcome: Option<bool>,
+    pub at: u64,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TimerEntry {
+    pub kind: String,
+    pub at: u64,
+}
+
+// Recorded status; transitions due to the clock are recorded when the next message arrives,
+// so clients compare `pending_timers` with the current time themselves
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct LifecycleInfo {
+    pub status: String,
+    // Orders are refused from here on
+    pub locks_at: u64,
+    pub resolution_time: u64,
+    pub history: Vec<StatusChangeEntry>,
+    // Deadlines still to fire, in firing order
+    pub pending_timers: Vec<TimerEntry>,
+    // Set once the chain is retired; the hex commitment the registry keeps
+    pub archived_at: Option<u64>,
+    pub snapshot_hash: Option<String>,
+    // Governance's emergency pause is on; only cancels and claims are accepted
+    pub protocol_paused: bool,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct LpPositionInfo {
+    pub shares: String,
+    // The shares' slice of the pools now, fees earned included
+    pub value: String,
+    pub last_deposit_at: u64,
+    // Harvested yield credited to the provider and not yet claimed
+    pub yield_earned: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct StageEntry {
+    pub index: u32,
+    pub name: String,
+    pub share_bps: u32,
+    // `None` until the stage settles
+    pub outcome: Option<bool>,
+    pub resolved_at: Option<u64>,
+    pub paid_out: String,
+}
+
+// Everything needed to check one position against `root` without trusting this service
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct PositionProofInfo {
+    pub owner: String,
+    pub yes_shares: String,
+    pub no_shares: String,
+    pub cost_basis: String,
+    pub claimed: bool,
+    pub index: u64,
+    pub leaf_count: u64,
+    // Hex, bottom-up
+    pub siblings: Vec<String>,
+    pub root: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TradeEntry {
+    pub market_id: String,
+    pub sequence: u64,
+    pub price: f64,
+    pub size: String,
+    // "Yes" or "No", matching the SDK's OrderSide: the side the taker bought
+    pub aggressor: String,
+    pub timestamp: u64,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct EvidenceEntry {
+    pub kind: String,
+    // Hex-encoded sha256 of the off-chain content
+    pub content_hash: String,
+    pub uri: String,
+    pub submitter: String,
+    pub submitted_at: u64,
+}
+
+#[Object]
+impl MarketQueryRoot {
+    async fn market_config(&self, market_id: String) -> Option<MarketConfigInfo> {
+        let state = &self.state;
+        if state.market_id != market_id {
+            return None;
+        }
+        let (oracle_kind, oracle_public_key, committee_size) = match &state.oracle_type {
+            OracleType::FastTee { public_key } => ("FAST_TEE", Some(public_key.clone()), None),
+            OracleType::Committee { member_count } => ("COMMITTEE", None, Some(*member_count)),
+            OracleType::Hybrid => ("HYBRID", None, None),
+        };
+        Some(MarketConfigInfo {
+            market_id: state.market_id.clone(),
+            description: state.description.clone(),
+            amm_kind: "CONSTANT_PRODUCT".to_string(),
+            oracle_kind: oracle_kind.to_string(),
+            oracle_public_key,
+            committee_size,
+            registry_chain: state.registry_chain.to_string(),
+            settlement_token: state.settlement_token.map(|token| token.to_string()),
+            allowlist: state.allowlist.map(|allowlist| allowlist.to_string()),
+            parent_market_id: state.condition.as_ref().map(|condition| condition.parent_market_id.clone()),
+            activates_on: state.condition.as_ref().map(|condition| condition.activates_on),
+            stage_count: state.stages.len() as u32,
+            opening_auction_secs: state.auction_config.opening_duration / 1_000_000,
+            reopen_after_halt: state.auction_config.reopen_after_halt,
+            max_pool: state.caps.max_pool.map(|amount| amount.to_string()),
+            max_user_exposure: state.caps.max_user_exposure.map(|amount| amount.to_string()),
+        })
+    }
+
+    async fn oracle_info(&self, market_id: String) -> Option<OracleInfo> {
+        let state = &self.state;
+        if state.market_id != market_id {
+            return None;
+        }
+        let (oracle_kind, public_key) = match &state.oracle_type {
+            OracleType::FastTee { public_key } => ("FAST_TEE", Some(public_key.clone())),
+            OracleType::Committee { .. } => ("COMMITTEE", None),
+            OracleType::Hybrid => ("HYBRID", None),
+        };
+        let rotation = state.oracle_key_rotation.as_ref();
+        Some(OracleInfo {
+            oracle_kind: oracle_kind.to_string(),
+            public_key,
+            next_public_key: rotation.map(|rotation| rotation.new_key.clone()),
+            rotation_announced_at: rotation.map(|rotation| rotation.announced_at),
+            rotation_completes_at: rotation.map(|rotation| rotation.completes_at),
+            guardian_key: state.oracle_guardian.clone(),
+            escalated: state.oracle_escalated,
+        })
+    }
+
+    // Answered by the user chain that coordinates the saga
+    async fn saga(&self, saga_id: u64) -> Option<SagaInfo> {
+        let saga = self.state.sagas.get(&saga_id)?;
+        let legs = saga
+            .legs
+            .iter()
+            .zip(&saga.votes)
+            .map(|(leg, vote)| SagaLegInfo {
+                market_chain: leg.market_chain.to_string(),
+                order_count: leg.orders.len() as u32,
+                vote: match vote {
+                    LegVote::Pending => "PENDING",
+                    LegVote::Reserved { .. } => "RESERVED",
+                    LegVote::Refused => "REFUSED",
+                }
+                .to_string(),
+                total_cost: match vote {
+                    LegVote::Reserved { total_cost } => Some(total_cost.to_string()),
+                    _ => None,
+                },
+            })
+            .collect();
+        Some(SagaInfo {
+            saga_id,
+            status: match saga.status {
+                SagaStatus::Reserving => "RESERVING",
+                SagaStatus::Committed => "COMMITTED",
+                SagaStatus::Aborted => "ABORTED",
+            }
+            .to_string(),
+            created_at: saga.created_at,
+            legs,
+            rejections: saga
+                .rejections
+                .iter()
+                .map(|rejection| format!("order {}: {:?}", rejection.order_id, rejection.reason))
+                .collect(),
+        })
+    }
+
+    // Transfers the market issued, oldest first, optionally only those paying or charging `chain_id`
+    async fn issued_transfers(&self, market_id: String, chain_id: Option<String>) -> Vec<IssuedTransferInfo> {
+        if self.state.market_id != market_id {
+            return Vec::new();
+        }
+        self.state
+            .transfers
+            .issued
+            .iter()
+            .filter(|(_, transfer)| {
+                chain_id.as_ref().map_or(true, |chain_id| {
+                    transfer.from.to_string() == *chain_id || transfer.to.to_string() == *chain_id
+                })
+            })
+            .map(|(sequence, transfer)| IssuedTransferInfo {
+                sequence: *sequence,
+                from: transfer.from.to_string(),
+                to: transfer.to.to_string(),
+                amount: transfer.amount.to_string(),
+                token: transfer.token.map(|token| token.to_string()),
+                issued_at: transfer.issued_at,
+            })
+            .collect()
+    }
+
+    // Oldest sequence `issuedTransfers` still returns; older transfers were dropped from the ledger
+    async fn transfers_retained_from(&self, market_id: String) -> Option<u64> {
+        (self.state.market_id == market_id).then(|| self.state.transfers.retained_from())
+    }
+
+    // Whether this chain has applied the transfer; answered by whichever chain is queried
+    async fn transfer_applied(&self, issuer: String, sequence: u64) -> bool {
+        let Ok(issuer) = issuer.parse::<ChainId>() else {
+            return false;
+        };
+        self.state.transfers.is_applied(TransferId { issuer, sequence })
+    }
+
+    // Unacknowledged messages sent at least `min_attempts` times (default 2: resent at least once)
+    async fn stuck_messages(&self, market_id: String, min_attempts: Option<u32>) -> Vec<OutboxMessageInfo> {
+        if self.state.market_id != market_id {
+            return Vec::new();
+        }
+        let min_attempts = min_attempts.unwrap_or(2);
+        self.state
+            .outbox
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.attempts >= min_attempts)
+            .map(|(id, entry)| OutboxMessageInfo {
+                outbox_id: *id,
+                destination: entry.destination.to_string(),
+                kind: entry.kind.clone(),
+                attempts: entry.attempts,
+                first_sent_at: entry.first_sent_at,
+                last_sent_at: entry.last_sent_at,
+                next_retry_at: entry.next_retry_at(),
+            })
+            .collect()
+    }
+
+    async fn fee_schedule(&self, market_id: String) -> Option<FeeScheduleInfo> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let schedule = &self.state.fee_schedule;
+        Some(FeeScheduleInfo {
+            taker_fee_bps: schedule.taker_fee_bps,
+            referral_share_bps: schedule.referral_share_bps,
+            oracle_share_bps: schedule.oracle_share_bps,
+            protocol_share_bps: schedule.protocol_share_bps,
+            lp_cooldown_secs: schedule.lp_cooldown / 1_000_000,
+            early_exit_fee_bps: schedule.early_exit_fee_bps,
+            maker_fee_bps: schedule.maker_fee_bps,
+            tiers: schedule
+                .tiers
+                .iter()
+                .map(|tier| FeeTierInfo {
+                    min_volume: tier.min_volume.to_string(),
+                    taker_fee_bps: tier.taker_fee_bps,
+                    maker_fee_bps: tier.maker_fee_bps,
+                })
+                .collect(),
+        })
+    }
+
+    // `now` is the caller's clock in micros; the window is counted in whole days up to it
+    async fn fee_tier(&self, market_id: String, owner: String, now: u64) -> Option<UserFeeTierInfo> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let owner: ChainId = owner.parse().ok()?;
+        let schedule = &self.state.fee_schedule;
+        let volume = self.state.user_volume.get(&owner).map_or(Amount::zero(), |history| history.trailing(now));
+        let tier = schedule.tier_index(volume);
+        let (taker_fee_bps, maker_fee_bps) = schedule.rates(volume);
+        let next = tier.map_or(0, |index| index + 1);
+        Some(UserFeeTierInfo {
+            trailing_volume: volume.to_string(),
+            tier: tier.map(|index| index as u32),
+            taker_fee_bps,
+            maker_fee_bps,
+            next_tier_volume: schedule.tiers.get(next).map(|tier| tier.min_volume.to_string()),
+        })
+    }
+
+    // Application ID of the fungible token the market settles in; null for the native token
+    async fn settlement_token(&self, market_id: String) -> Option<String> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        self.state.settlement_token.map(|token| token.to_string())
+    }
+
+    async fn circuit_breaker(&self, market_id: String) -> Option<CircuitBreakerInfo> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let breaker = &self.state.circuit_breaker;
+        Some(CircuitBreakerInfo {
+            max_move_bps: breaker.config.max_move_bps,
+            window_blocks: breaker.config.window_blocks,
+            cooldown_secs: breaker.config.cooldown / 1_000_000,
+            halted_until: (breaker.halted_until > 0).then_some(breaker.halted_until),
+        })
+    }
+
+    // With `now`, the taker fee is the owner's tier rate rather than the base rate
+    async fn simulation_state(&self, market_id: String, owner: Option<String>, now: Option<u64>) -> Option<SimulationStateInfo> {
+        let state = &self.state;
+        if state.market_id != market_id {
+            return None;
+        }
+        let accepting = match state.status {
+            MarketStatus::Open => true,
+            MarketStatus::Created => state.auction.is_some(),
+            _ => false,
+        };
+        let owner: Option<ChainId> = owner.and_then(|owner| owner.parse().ok());
+        let remaining_user_exposure = owner.and_then(|owner| {
+            let exposure = state.positions.get(&owner).map(|position| position.cost_basis).unwrap_or_default();
+            state.caps.remaining_exposure(exposure)
+        });
+        let volume = owner
+            .zip(now)
+            .and_then(|(owner, now)| Some(state.user_volume.get(&owner)?.trailing(now)))
+            .unwrap_or_default();
+        let breaker = &state.circuit_breaker;
+        Some(SimulationStateInfo {
+            status: state.status.name().to_string(),
+            accepting: accepting && state.condition_met() != Some(false),
+            in_auction: state.auction.is_some(),
+            protocol_paused: state.protocol_paused,
+            halted_until: (breaker.halted_until > 0).then_some(breaker.halted_until),
+            reopen_after_halt: state.auction_config.reopen_after_halt,
+            pool_yes: state.pool_yes.to_string(),
+            pool_no: state.pool_no.to_string(),
+            yes_odds: state.yes_odds,
+            no_odds: state.no_odds,
+            taker_fee_bps: state.fee_schedule.rates(volume).0,
+            remaining_pool_capacity: state.caps.remaining_pool(state.pool_yes + state.pool_no).map(|amount| amount.to_string()),
+            remaining_user_exposure: remaining_user_exposure.map(|amount| amount.to_string()),
+            breaker_max_move_bps: breaker.config.max_move_bps,
+            breaker_cooldown_secs: breaker.config.cooldown / 1_000_000,
+            breaker_reference_odds: breaker.reference_odds(),
+        })
+    }
+
+    // Null while the market trades continuously
+    async fn auction(&self, market_id: String) -> Option<AuctionInfo> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let auction = self.state.auction.as_ref()?;
+        let (yes_demand, no_demand) = auction.demand();
+        let (indicative_yes_price, indicative_no_price) =
+            auction.clearing_prices(self.state.pool_yes, self.state.pool_no);
+        Some(AuctionInfo {
+            ends_at: auction.ends_at,
+            order_count: auction.order_count() as u64,
+            yes_demand: yes_demand.to_string(),
+            no_demand: no_demand.to_string(),
+            indicative_yes_price,
+            indicative_no_price,
+        })
+    }
+
+    // Null for markets that keep all their funds on the market chain
+    async fn yield_status(&self, market_id: String) -> Option<YieldInfo> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let config = &self.state.yield_config;
+        let position = &self.state.yield_position;
+        Some(YieldInfo {
+            source: config.source?.to_string(),
+            deposit_bps: config.deposit_bps,
+            beneficiary: format!("{:?}", config.beneficiary),
+            deposited: position.deposited.to_string(),
+            harvested: position.harvested.to_string(),
+        })
+    }
+
+    // Application ID of the compliance allowlist orders are checked against; null if ungated
+    async fn allowlist(&self, market_id: String) -> Option<String> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        self.state.allowlist.map(|allowlist| allowlist.to_string())
+    }
+
+    async fn resolution_evidence(&self, market_id: String) -> Vec<EvidenceEntry> {
+        if self.state.market_id != market_id {
+            return Vec::new();
+        }
+        self.state
+            .evidence
+            .iter()
+            .map(|evidence| EvidenceEntry {
+                kind: match evidence.kind {
+                    EvidenceKind::ResolutionSource => "RESOLUTION_SOURCE",
+                    EvidenceKind::DisputeEvidence => "DISPUTE_EVIDENCE",
+                    EvidenceKind::OracleObservation => "ORACLE_OBSERVATION",
+                }
+                .to_string(),
+                content_hash: hex::encode(evidence.content_hash),
+                uri: evidence.uri.clone(),
+                submitter: evidence.submitter.to_string(),
+                submitted_at: evidence.submitted_at,
+            })
+            .collect()
+    }
+
+    // Fills after `after_sequence`, oldest first; only the last `MAX_RECENT_TRADES` are kept
+    async fn recent_trades(&self, market_id: String, after_sequence: Option<u64>) -> Vec<TradeEntry> {
+        if self.state.market_id != market_id {
+            return Vec::new();
+        }
+        let after = after_sequence.unwrap_or(0);
+        self.state
+            .recent_trades
+            .iter()
+            .filter(|trade| trade.sequence > after)
+            .map(|trade| TradeEntry {
+                market_id: market_id.clone(),
+                sequence: trade.sequence,
+                price: trade.price,
+                size: trade.size.to_string(),
+                aggressor: if trade.buy_yes { "Yes" } else { "No" }.to_string(),
+                timestamp: trade.timestamp,
+            })
+            .collect()
+    }
+
+    // Records after `after_sequence`, oldest first; older ones are only on the audit event stream
+    async fn audit_log(&self, market_id: String, after_sequence: Option<u64>, limit: Option<usize>) -> Vec<AuditEntry> {
+        if self.state.market_id != market_id {
+            return Vec::new();
+        }
+        let after = after_sequence.unwrap_or(0);
+        self.state
+            .audit_log
+            .recent
+            .iter()
+            .filter(|record| record.sequence > after)
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|record| AuditEntry {
+                sequence: record.sequence,
+                timestamp: record.timestamp,
+                event: serde_json::to_string(&record.event).unwrap_or_default(),
+                prev_hash: hex::encode(record.prev_hash),
+                hash: hex::encode(record.hash),
+            })
+            .collect()
+    }
+
+    async fn audit_head(&self, market_id: String) -> Option<AuditHead> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        Some(AuditHead {
+            length: self.state.audit_log.length,
+            hash: hex::encode(self.state.audit_log.head),
+        })
+    }
+
+    async fn lifecycle(&self, market_id: String) -> Option<LifecycleInfo> {
+        let state = &self.state;
+        if state.market_id != market_id {
+            return None;
+        }
+        let history = state
+            .status_history
+            .iter()
+            .map(|change| StatusChangeEntry {
+                status: change.status.name().to_string(),
+                outcome: match change.status {
+                    MarketStatus::Resolved(outcome) => Some(outcome),
+                    _ => None,
+                },
+                at: change.at,
+            })
+            .collect();
+        Some(LifecycleInfo {
+            status: state.status.name().to_string(),
+            locks_at: state.locks_at,
+            resolution_time: state.resolution_time,
+            history,
+            pending_timers: state
+                .timers
+                .pending()
+                .map(|timer| TimerEntry { kind: format!("{:?}", timer.kind), at: timer.at })
+                .collect(),
+            archived_at: state.archive.map(|archive| archive.archived_at),
+            snapshot_hash: state.archive.map(|archive| hex::encode(archive.snapshot_hash)),
+            protocol_paused: state.protocol_paused,
+        })
+    }
+
+    async fn lp_position(&self, market_id: String, provider: String) -> Option<LpPositionInfo> {
+        let state = &self.state;
+        if state.market_id != market_id {
+            return None;
+        }
+        let provider = provider.parse().ok()?;
+        let position = state.lp_positions.get(&provider)?;
+        let pool_total = state.pool_yes + state.pool_no;
+        let value = if state.total_lp_shares == Amount::zero() {
+            Amount::zero()
+        } else {
+            Amount::from_attos(
+                u128::from(position.shares) * u128::from(pool_total) / u128::from(state.total_lp_shares),
+            )
+        };
+        Some(LpPositionInfo {
+            shares: position.shares.to_string(),
+            value: value.to_string(),
+            last_deposit_at: position.last_deposit_at,
+            yield_earned: state.lp_yield_earnings.get(&provider).copied().unwrap_or(Amount::zero()).to_string(),
+        })
+    }
+
+    // Stage schedule of a staged market, in settlement order; empty for single-shot markets
+    async fn stages(&self, market_id: String) -> Option<Vec<StageEntry>> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let stages = self
+            .state
+            .stages
+            .iter()
+            .enumerate()
+            .map(|(index, stage)| StageEntry {
+                index: index as u32,
+                name: stage.spec.name.clone(),
+                share_bps: stage.spec.share_bps,
+                outcome: stage.outcome,
+                resolved_at: stage.outcome.map(|_| stage.resolved_at),
+                paid_out: stage.paid_out.to_string(),
+            })
+            .collect();
+        Some(stages)
+    }
+
+    // Stage winnings credited to `owner` and not yet claimed
+    async fn stage_credit(&self, market_id: String, owner: String) -> Option<String> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let owner = owner.parse().ok()?;
+        let earned = self.state.stage_earnings.get(&owner).copied().unwrap_or_default();
+        let claimed = self.state.stage_claimed.get(&owner).copied().unwrap_or_default();
+        Some(earned.saturating_sub(claimed).to_string())
+    }
+
+    // Fingerprint of the wire schema this market was built against
+    async fn schema_hash(&self, market_id: String) -> Option<String> {
+        (self.state.market_id == market_id).then(oddsstream_schema::schema_hash_hex)
+    }
+
+    async fn position_root(&self, market_id: String) -> Option<String> {
+        (self.state.market_id == market_id).then(|| hex::encode(self.state.position_root))
+    }
+
+    async fn position_proof(&self, market_id: String, owner: String) -> Option<PositionProofInfo> {
+        if self.state.market_id != market_id {
+            return None;
+        }
+        let owner = owner.parse().ok()?;
+        let position = self.state.positions.get(&owner)?;
+        let proof = merkle::position_proof(&self.state.positions, owner)?;
+        Some(PositionProofInfo {
+            owner: owner.to_string(),
+            yes_shares: position.yes_shares.to_string(),
+            no_shares: position.no_shares.to_string(),
+            cost_basis: position.cost_basis.to_string(),
+            claimed: position.claimed,
+            index: proof.index as u64,
+            leaf_count: proof.leaf_count as u64,
+            siblings: proof.siblings.iter().map(hex::encode).collect(),
+            root: hex::encode(self.state.position_root),
+        })
+    }
+
+    async fn market_stats(&self, market_id: String, owner: Option<String>) -> Option<MarketStats> {
+        let state = &self.state;
+        if state.market_id != market_id {
+            return None;
+        }
+
+        let average_trade_size = if state.trade_count > 0 {
+            f64::from(state.traded_volume) / state.trade_count as f64
+        } else {
+            0.0
+        };
+
+        let caps = &state.caps;
+        let remaining_user_exposure = owner.and_then(|owner| owner.parse().ok()).and_then(|owner| {
+            let exposure = state.positions.get(&owner).map(|position| position.cost_basis).unwrap_or_default();
+            caps.remaining_exposure(exposure)
+        });
+
+        Some(MarketStats {
+            market_id,
+            yes_depth: state.pool_yes.to_string(),
+            no_depth: state.pool_no.to_string(),
+            trade_count: state.trade_count,
+            traded_volume: state.traded_volume.to_string(),
+            average_trade_size,
+            last_trade_at: (state.last_trade_at > 0).then_some(state.last_trade_at),
+            remaining_pool_capacity: caps.remaining_pool(state.pool_yes + state.pool_no).map(|amount| amount.to_string()),
+            max_user_exposure: caps.max_user_exposure.map(|amount| amount.to_string()),
+            remaining_user_exposure: remaining_user_exposure.map(|amount| amount.to_string()),
+        })
+    }
+}
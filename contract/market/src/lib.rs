@@ -1,19 +1,360 @@
-use linera_sdk::{base::Amount, contract::system_api};
+use linera_sdk::{base::{Amount, ApplicationId, StreamUpdate}, contract::system_api};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+pub mod archive;
+pub mod auction;
+pub mod audit;
+pub mod breaker;
+pub mod caps;
+pub mod fallback;
+pub mod fees;
+pub mod graphql;
+pub mod lifecycle;
+pub mod merkle;
+pub mod multisig;
+pub mod oracle_keys;
+pub mod outbox;
+pub mod parlay;
+pub mod saga;
+pub mod signing;
+pub mod stages;
+pub mod timers;
+pub mod transfers;
+pub mod validation;
+pub mod yield_source;
+
+use archive::{ArchiveRecord, ARCHIVE_GRACE_PERIOD};
+use auction::{AuctionConfig, CallAuction};
+use audit::{AuditEvent, AuditLog, AUDIT_STREAM};
+use breaker::{BreakerConfig, CircuitBreaker};
+use caps::MarketCaps;
+use fallback::FallbackPolicy;
+use fees::{FeeSchedule, VolumeHistory, WithdrawalCheck};
+use lifecycle::{MarketStatus, StatusChange};
+use multisig::{MultisigPolicy, SingleSignerWindow};
+use oracle_keys::KeyRotation;
+use outbox::Outbox;
+use parlay::{Parlay, ParlayLeg, ParlayStatus, ParlayWatcher, MAX_PARLAY_LEGS, MAX_PARLAY_WATCHERS, MAX_WATCHERS_PER_COORDINATOR};
+use saga::{AtomicLeg, LegVote, Reservation, Saga, SagaStatus, RESERVATION_TTL, SAGA_TIMEOUT};
+use signing::{OrderSignature, RelayerFee};
+use stages::{Stage, StageSpec};
+use timers::{Timer, TimerKind, TimerRegistry};
+use transfers::{Admission, IssuedTransfer, TokenCall, TransferId, TransferLedger};
+use validation::{OrderRejection, RejectionReason};
+use yield_source::{YieldBeneficiary, YieldConfig, YieldPosition, YieldSourceCall};
 
 #[derive(Serialize, Deserialize)]
 pub struct MarketState {
     pub market_id: String,
     pub description: String,
     pub status: MarketStatus,
+    // Every transition so far, oldest first
+    pub status_history: Vec<StatusChange>,
+    // Micros timestamp trading stops at, ahead of `resolution_time`
+    pub locks_at: u64,
+    // Stages that each release part of the payout before final resolution; empty for single-shot markets
+    pub stages: Vec<Stage>,
+    // User chain -> stage winnings credited so far, and how much of that has been claimed
+    pub stage_earnings: BTreeMap<ChainId, Amount>,
+    pub stage_claimed: BTreeMap<ChainId, Amount>,
     pub pool_yes: Amount,
     pub pool_no: Amount,
     pub yes_odds: f64,
     pub no_odds: f64,
     pub oracle_type: OracleType,
     pub resolution_time: u64,
+    // What happens if the oracle never reports
+    pub fallback: FallbackPolicy,
+    // Set once the fallback committee may resolve in place of `oracle_type`
+    pub oracle_escalated: bool,
+    // Hex key governance may announce FastTee key rotations with in place of the current key
+    pub oracle_guardian: Option<String>,
+    // Rotation still in its overlap window, and the newest announcement applied so far
+    pub oracle_key_rotation: Option<KeyRotation>,
+    pub last_key_rotation_at: u64,
+    // Lock, resolution, auction, fallback, archival and key rotation deadlines still to fire
+    pub timers: TimerRegistry,
+    pub fee_schedule: FeeSchedule,
+    // Per user chain, what it paid for shares each day of the fee-tier window
+    pub user_volume: BTreeMap<ChainId, VolumeHistory>,
+    // Compliance allowlist that must approve a user chain before its orders are accepted
+    pub allowlist: Option<ApplicationId>,
+    // Rejects orders for a cooldown after an extreme odds move
+    pub circuit_breaker: CircuitBreaker,
+    pub auction_config: AuctionConfig,
+    // Limits on pool size and per-user exposure, enforced as orders fill
+    pub caps: MarketCaps,
+    // Call auction collecting orders at open or after a halt; `None` during continuous trading
+    pub auction: Option<CallAuction>,
+    // Registry that created this market; receives trade reports for the leaderboard
+    pub registry_chain: ChainId,
+    // Fungible token application stakes and payouts are denominated in; `None` settles in the native token
+    pub settlement_token: Option<ApplicationId>,
+    // Referral code -> chain credited with a share of the fees on referred orders
+    pub referral_codes: BTreeMap<String, ChainId>,
+    // Referrer chain -> fees accrued and not yet claimed
+    pub referral_earnings: BTreeMap<ChainId, Amount>,
+    // Taker fee shares owed to the oracle and the protocol treasury
+    pub oracle_fees: Amount,
+    pub protocol_fees: Amount,
+    // User chain -> shares held on each side
+    pub positions: BTreeMap<ChainId, Position>,
+    // Merkle root over `positions`, refreshed whenever a position changes
+    pub position_root: [u8; 32],
+    // (User chain, sub-account) -> the same shares broken down for attribution
+    pub subaccount_positions: BTreeMap<(ChainId, String), Position>,
+    // User chain -> highest batch nonce accepted
+    pub last_nonces: BTreeMap<ChainId, u64>,
+    // User chain -> ed25519 key allowed to sign orders relayed on its behalf
+    pub signer_keys: BTreeMap<ChainId, Vec<u8>>,
+    // (User chain, session public key) -> scoped key granted to an agent
+    pub session_keys: BTreeMap<(ChainId, Vec<u8>), SessionKey>,
+    // User chain -> co-signing policy for large batches
+    pub multisig_policies: BTreeMap<ChainId, MultisigPolicy>,
+    // User chain -> what it traded without co-signatures within the policy window
+    pub single_signer_notional: BTreeMap<ChainId, SingleSignerWindow>,
+    // User chain -> micros timestamp its self-exclusion ends; no orders are taken from it before then
+    pub self_exclusions: BTreeMap<ChainId, u64>,
+    // Running trade statistics served to the stats query
+    pub trade_count: u64,
+    pub traded_volume: Amount,
+    // Micros timestamp of the most recent fill; 0 before the first trade
+    pub last_trade_at: u64,
+    // Hashes of off-chain resolution sources, dispute evidence and oracle observations
+    pub evidence: Vec<Evidence>,
+    // Provider chain -> pool shares from liquidity deposits
+    pub lp_positions: BTreeMap<ChainId, LpPosition>,
+    pub total_lp_shares: Amount,
+    // Where idle pool funds earn yield while the market trades, and what the source holds for it
+    pub yield_config: YieldConfig,
+    pub yield_position: YieldPosition,
+    // Provider chain -> harvested yield credited and not yet claimed
+    pub lp_yield_earnings: BTreeMap<ChainId, Amount>,
+    // Parlays coordinated by this market, by ID
+    pub parlays: BTreeMap<u64, Parlay>,
+    pub next_parlay_id: u64,
+    // Coordinators to notify when this market resolves
+    pub parlay_watchers: Vec<ParlayWatcher>,
+    // Funds parlay winnings: what funders paid in plus the stakes of lost parlays,
+    // less the winnings paid out or set aside
+    pub parlay_reserve: Amount,
+    // Winnings set aside for parlays whose legs are all quoted
+    pub parlay_committed: Amount,
+    // Funder chain -> what it paid into the reserve; what is left at archive is split by it
+    pub parlay_funders: BTreeMap<ChainId, Amount>,
+    // Set for conditional markets: only settles if the parent resolves to `activates_on`
+    pub condition: Option<MarketCondition>,
+    pub parent_outcome: Option<bool>,
+    // Conditional markets to notify when this market settles, each once; at most MAX_RESOLUTION_WATCHERS
+    pub resolution_watchers: Vec<ChainId>,
+    // Hash chain over orders, fills, resolution and payouts
+    pub audit_log: AuditLog,
+    // Set once the market has committed to its final state and retired its chain
+    pub archive: Option<ArchiveRecord>,
+    // Mirrors the registry's emergency pause: new orders, liquidity and parlays are refused
+    pub protocol_paused: bool,
+    // Confirmations and payouts sent to user chains and not yet acknowledged
+    pub outbox: Outbox,
+    // Transfers issued by this chain, and those applied here, by ID
+    pub transfers: TransferLedger,
+    // Atomic batches coordinated by this chain, when it is a user chain, by ID
+    pub sagas: BTreeMap<u64, Saga>,
+    pub next_saga_id: u64,
+    // (User chain, saga ID) -> orders reserved for another chain's atomic batch
+    pub reservations: BTreeMap<(ChainId, u64), Reservation>,
+    // Most recent fills, oldest first, for the service to relay as the trade tape
+    pub recent_trades: VecDeque<TradeEvent>,
+    pub next_trade_sequence: u64,
+}
+
+// Event stream the service subscribes to for time & sales
+pub const TRADE_STREAM: &[u8] = b"trades";
+
+// Fills kept in state so a relay that falls behind can catch up by sequence
+pub const MAX_RECENT_TRADES: usize = 1_000;
+
+// One fill, emitted on `TRADE_STREAM` as it happens
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TradeEvent {
+    pub sequence: u64,
+    pub user_chain_id: ChainId,
+    // Side the taker bought; every fill on the AMM is taker-initiated
+    pub buy_yes: bool,
+    // Odds the order filled at
+    pub price: f64,
+    pub size: Amount,
+    pub timestamp: u64,
+}
+
+// The registry's pause broadcast, read from its `PAUSE_STREAM`
+pub const PAUSE_STREAM: &[u8] = b"pause";
+
+// Mirrors the registry's pause event; the latest one read wins
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PauseUpdate {
+    pub paused: bool,
+    pub reason: String,
+    pub at: u64,
+}
+
+// Call interface a compliance allowlist application must implement; it is
+// called synchronously, so it has to be registered on the market chain
+#[derive(Serialize, Deserialize)]
+pub enum AllowlistCall {
+    IsApproved { user_chain_id: ChainId },
+}
+
+// Mirrors the user chain's message enum; lets its loss limit net payouts against stakes
+#[derive(Serialize, Deserialize)]
+pub enum UserMessage {
+    PayoutCredited {
+        amount: Amount,
+        // The payout's `Transfer`; a user chain counts each one once
+        transfer_id: TransferId,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MarketCondition {
+    pub parent_market_id: String,
+    pub parent_chain: ChainId,
+    pub activates_on: bool,
+}
+
+// Mirrors the registry's instantiation argument
+#[derive(Serialize, Deserialize)]
+pub struct MarketArgs {
+    pub market_id: String,
+    pub description: String,
+    pub oracle_type: OracleType,
+    pub resolution_time: u64,
+    // Micros before `resolution_time` during which orders are refused
+    pub lock_period: u64,
+    pub stages: Vec<StageSpec>,
+    pub fallback: FallbackPolicy,
+    pub fee_schedule: FeeSchedule,
+    pub amm: AmmParams,
+    pub registry_chain: ChainId,
+    pub condition: Option<MarketCondition>,
+    pub settlement_token: Option<ApplicationId>,
+    pub circuit_breaker: BreakerConfig,
+    pub auction: AuctionConfig,
+    pub caps: MarketCaps,
+    pub yield_config: YieldConfig,
+    pub allowlist: Option<ApplicationId>,
+    pub oracle_guardian: Option<String>,
+    // Created while the protocol was paused
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AmmParams {
+    pub initial_liquidity: Amount,
+    pub initial_yes_odds_bps: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LpPosition {
+    pub shares: Amount,
+    // Every deposit restarts the withdrawal cooldown
+    pub last_deposit_at: u64,
+}
+
+// Evidence beyond this count is rejected to keep market state bounded
+pub const MAX_EVIDENCE: usize = 256;
+// No one chain may take more than this many of those slots
+pub const MAX_EVIDENCE_PER_SUBMITTER: usize = 8;
+// Likewise for watchers; any chain may ask to watch, not just conditional markets
+pub const MAX_RESOLUTION_WATCHERS: usize = 256;
+// Markets one session key may be granted on, each of which hears about every spend
+pub const MAX_SESSION_KEY_MARKETS: usize = 64;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EvidenceKind {
+    ResolutionSource,
+    DisputeEvidence,
+    OracleObservation,
+}
+
+// Only the hash lives on-chain; `uri` points at the off-chain content it commits to
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Evidence {
+    pub kind: EvidenceKind,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    pub submitter: ChainId,
+    pub submitted_at: u64,
+}
+
+// Restricted key an agent can sign orders with; a grant only reaches the
+// markets it is allowed to trade. The cap is per key: the user chain's own
+// market app adds up what every market reports and sends them the total.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionKey {
+    pub spend_cap: Amount,
+    pub spent: Amount,
+    pub expires_at: u64,
+    // Market chains the key was granted on
+    pub markets: Vec<ChainId>,
+}
+
+// What a batch signed by a session key will count against its cap once accepted
+pub struct SessionCharge {
+    pub public_key: Vec<u8>,
+    pub amount: Amount,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Position {
+    pub yes_shares: Amount,
+    pub no_shares: Amount,
+    // Paid for the shares, excluding fees
+    pub cost_basis: Amount,
+    pub claimed: bool,
+}
+
+// Mirrors the registry's message enum; variant order must match
+#[derive(Serialize, Deserialize)]
+pub enum RegistryMessage {
+    TradesExecuted {
+        user_chain_id: ChainId,
+        volume: Amount,
+        trades: u64,
+    },
+    MarketSettled {
+        outcome: bool,
+        results: Vec<TraderResult>,
+    },
+    SetLeaderboardPrivacy {
+        user_chain_id: ChainId,
+        hidden: bool,
+    },
+    DisputeFiled {
+        submitter: ChainId,
+        uri: String,
+    },
+    ProtocolFeesCollected {
+        amount: Amount,
+        token: Option<ApplicationId>,
+    },
+    MarketCancelled,
+    MarketArchived {
+        snapshot_hash: [u8; 32],
+    },
+    OracleUpdated {
+        oracle_type: OracleType,
+    },
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TraderResult {
+    pub user_chain_id: ChainId,
+    pub cost: Amount,
+    pub payout: Amount,
+}
+
+// The SDK mirrors this enum; the variant index is the BCS tag, so only ever append variants
 #[derive(Serialize, Deserialize)]
 pub enum MarketMessage {
     // Batched orders from user chain
@@ -21,79 +362,2101 @@ pub enum MarketMessage {
         user_chain_id: ChainId,
         orders: Vec<Order>,
         nonce: u64,
+        signature: Option<OrderSignature>,
+        // Set when a third party submits the batch on the user's behalf
+        relayer_fee: Option<RelayerFee>,
+        // Extra signatures over the same digest for multisig user chains
+        cosignatures: Vec<OrderSignature>,
+    },
+    // Installs a user chain's first multisig policy; later changes go through `UpdateMultisigPolicy`
+    SetMultisigPolicy {
+        user_chain_id: ChainId,
+        policy: Option<MultisigPolicy>,
+    },
+    // Registers the key a user chain signs its orders with
+    RegisterSigner {
+        user_chain_id: ChainId,
+        public_key: Vec<u8>,
+    },
+    // Grants an agent key limited trading rights for this market. `markets` lists every market
+    // chain the grant covers; the user chain sends the grant to its own market app as well,
+    // which keeps the key's spend across all of them.
+    GrantSessionKey {
+        user_chain_id: ChainId,
+        public_key: Vec<u8>,
+        spend_cap: Amount,
+        expires_at: u64,
+        markets: Vec<ChainId>,
+    },
+    RevokeSessionKey {
+        user_chain_id: ChainId,
+        public_key: Vec<u8>,
+    },
+    // Claims a referral code for the sending chain
+    RegisterReferralCode {
+        code: String,
+        referrer: ChainId,
+    },
+    ClaimReferralEarnings {
+        referrer: ChainId,
+    },
+    // Attaches a hash of off-chain evidence to the market; the sender is recorded as submitter
+    AttachEvidence {
+        kind: EvidenceKind,
+        content_hash: [u8; 32],
+        uri: String,
+    },
+    // Deposits liquidity split across both pools at the current odds
+    AddLiquidity {
+        provider: ChainId,
+        amount: Amount,
+    },
+    // Burns LP shares for their slice of the pools, subject to the fee schedule's cooldown
+    RemoveLiquidity {
+        provider: ChainId,
+        shares: Amount,
+    },
+    // Multi-market order; the receiving market coordinates it
+    PlaceParlay {
+        user_chain_id: ChainId,
+        stake: Amount,
+        legs: Vec<ParlayLeg>,
+    },
+    // Coordinator -> leg market: lock odds and watch for resolution
+    ParlayLegQuote {
+        parlay_id: u64,
+        leg_index: usize,
+        outcome: bool,
+    },
+    // Leg market -> coordinator
+    ParlayLegQuoted {
+        parlay_id: u64,
+        leg_index: usize,
+        odds: f64,
+    },
+    ParlayLegRejected {
+        parlay_id: u64,
+    },
+    ParlayLegResolved {
+        parlay_id: u64,
+        leg_index: usize,
+        won: bool,
+    },
+    // Fires the timers block time has passed, e.g. uncrossing an ended call auction on an idle market; anyone may send it
+    UncrossAuction,
+    // Conditional market -> parent: report your resolution to me
+    WatchResolution,
+    // Parent -> conditional market
+    ParentResolved {
+        outcome: bool,
     },
     // Resolution from oracle
     Resolution {
         outcome: bool,
+        // Micros timestamp the oracle signed alongside the outcome
+        timestamp: u64,
         signature: Vec<u8>,
         oracle_type: OracleType,
     },
-    // Funds transfer
+    // Winnings claim from a user chain after resolution
+    Claim {
+        user_chain_id: ChainId,
+    },
+    // Funds transfer, in the market's settlement token
     Transfer {
         from: ChainId,
         to: ChainId,
         amount: Amount,
+        // Fungible token application to move; `None` is the chain's native token
+        token: Option<ApplicationId>,
+        // Applied once by the receiving chain however many times it arrives
+        transfer_id: TransferId,
+    },
+    // Market -> user chain: what a batch filled for and why the rest of it did not
+    BatchConfirmed {
+        user_chain_id: ChainId,
+        order_ids: Vec<u64>,
+        total_cost: Amount,
+        rejections: Vec<OrderRejection>,
+    },
+    // Oracle result for one stage of a staged market; releases that stage's share of the payout
+    StageResolution {
+        stage: u32,
+        outcome: bool,
+        timestamp: u64,
+        signature: Vec<u8>,
+        oracle_type: OracleType,
+    },
+    // Cancel-and-replace of an order queued in a call auction, applied in one step. The
+    // replacement keeps the order's id, side and attribution, so whatever tracks the id
+    // (retries, confirmations, the audit log) follows it through amendments.
+    AmendOrder {
+        user_chain_id: ChainId,
+        order_id: u64,
+        amount: Amount,
+        max_price: Option<Amount>,
+        nonce: u64,
+        signature: Option<OrderSignature>,
+    },
+    // Collects the yield accrued so far without touching the principal; anyone may send it
+    HarvestYield,
+    ClaimYield {
+        provider: ChainId,
+    },
+    // Sends the protocol fees accrued so far to the registry's treasury; anyone may send it
+    SweepProtocolFees,
+    // A FastTee oracle's next key, signed by its current key or the guardian; anyone may relay it
+    RotateOracleKey {
+        new_key: String,
+        announced_at: u64,
+        // Micros both keys are accepted for
+        overlap: u64,
+        signature: Vec<u8>,
+    },
+    // A message whose delivery the sender tracks; the receiving chain acknowledges it, then handles `payload`
+    Tracked {
+        outbox_id: u64,
+        payload: Vec<u8>,
+    },
+    // Receiving chain -> sender: a tracked message arrived
+    Delivered {
+        outbox_id: u64,
+    },
+    // Resends an unacknowledged outbox message now, even one out of automatic retries; only its
+    // recipient or the market chain itself may ask
+    RetryMessage {
+        outbox_id: u64,
+    },
+    // User chain -> its own market app: fill all of these orders or none of them
+    SubmitAtomic {
+        legs: Vec<AtomicLeg>,
+    },
+    // Coordinator -> leg market: hold these orders at the current prices
+    ReserveOrders {
+        saga_id: u64,
+        user_chain_id: ChainId,
+        orders: Vec<Order>,
+    },
+    // Leg market -> coordinator; `total_cost` is before fees
+    OrdersReserved {
+        saga_id: u64,
+        total_cost: Amount,
+    },
+    ReservationRejected {
+        saga_id: u64,
+        rejections: Vec<OrderRejection>,
+    },
+    // Coordinator -> leg market: fill the reserved orders at their reserved prices
+    CommitReservation {
+        saga_id: u64,
+    },
+    AbortReservation {
+        saga_id: u64,
+    },
+    // Bankrolls the market's parlays; funders share the reserve left when the market is archived
+    FundParlayReserve {
+        funder: ChainId,
+        amount: Amount,
+    },
+    // Replaces (or with `None`, removes) an installed multisig policy, approved by its own quorum
+    UpdateMultisigPolicy {
+        user_chain_id: ChainId,
+        policy: Option<MultisigPolicy>,
+        nonce: u64,
+        signatures: Vec<OrderSignature>,
+    },
+    // User chain -> its own market app: lets `market_chain` charge up to `amount` more for
+    // orders, liquidity or parlays this chain sends it. Charges beyond what was approved are refused.
+    ApprovePayments {
+        market_chain: ChainId,
+        amount: Amount,
+    },
+    // User chain -> markets it trades on and its own market app: refuse its orders until
+    // `until`, however they are sent. Like the user app's exclusion it only ever extends.
+    SelfExclude {
+        user_chain_id: ChainId,
+        until: u64,
+    },
+    // Registry -> market: resolve through `oracle_type` from now on; acknowledged with `OracleUpdated`
+    UpdateOracle {
+        oracle_type: OracleType,
+    },
+    // Parent -> conditional market: the parent was voided, so the child never activates
+    ParentCancelled,
+    // Market -> user chain's own market app: an accepted batch signed by this session key
+    SessionSpent {
+        public_key: Vec<u8>,
+        amount: Amount,
+    },
+    // User chain -> each market a session key was granted on: what the key has spent on all of them
+    SyncSessionSpend {
+        public_key: Vec<u8>,
+        spent: Amount,
     },
 }
 
 impl Contract for MarketApplication {
     type Message = MarketMessage;
+    type InstantiationArgument = MarketArgs;
+    
+    async fn instantiate(&mut self, args: MarketArgs) {
+        self.market_id = args.market_id;
+        self.description = args.description;
+        self.oracle_type = args.oracle_type;
+        self.resolution_time = args.resolution_time;
+        self.locks_at = args.resolution_time.saturating_sub(args.lock_period);
+        // The registry validates the schedule; one that would pay out more than the pool is dropped
+        if stages::is_valid_schedule(&args.stages) {
+            self.stages = args.stages.into_iter().map(Stage::new).collect();
+        }
+        if args.fallback.is_valid() {
+            self.fallback = args.fallback;
+        }
+        if args.yield_config.is_valid() {
+            self.yield_config = args.yield_config;
+        }
+        self.fee_schedule = args.fee_schedule;
+        self.registry_chain = args.registry_chain;
+        self.settlement_token = args.settlement_token;
+        self.circuit_breaker = CircuitBreaker::new(args.circuit_breaker);
+        let now = system_api::current_system_time().micros();
+        self.status_history.push(StatusChange { status: MarketStatus::Created, at: now });
+        // With an opening auction the market opens when it uncrosses
+        if args.auction.opening_duration > 0 {
+            self.start_auction(now + args.auction.opening_duration);
+        } else {
+            self.transition(MarketStatus::Open, now);
+        }
+        self.auction_config = args.auction;
+        if self.locks_at < self.resolution_time {
+            self.timers.schedule(self.locks_at, TimerKind::Lock);
+        }
+        self.timers.schedule(self.resolution_time, TimerKind::Resolve);
+        if let Some(at) = self.fallback.escalates_at(self.resolution_time) {
+            self.timers.schedule(at, TimerKind::EscalateOracle);
+        }
+        if let Some(at) = self.fallback.refunds_at(self.resolution_time) {
+            self.timers.schedule(at, TimerKind::VoidUnresolved);
+        }
+        self.caps = args.caps;
+        self.allowlist = args.allowlist;
+        self.oracle_guardian = args.oracle_guardian;
+        // Later flips of the switch arrive as registry events
+        self.protocol_paused = args.paused;
+        system_api::subscribe_to_events(self.registry_chain, PAUSE_STREAM);
+        
+        // Seed the pools so the market opens at the requested odds
+        let yes_bps = match args.amm.initial_yes_odds_bps {
+            0 => 5_000,
+            bps => bps.min(9_999),
+        };
+        self.pool_no = fees::apply_bps(args.amm.initial_liquidity, yes_bps);
+        self.pool_yes = args.amm.initial_liquidity.saturating_sub(self.pool_no);
+        self.update_odds();
+        
+        if let Some(condition) = &args.condition {
+            self.send_message(condition.parent_chain, MarketMessage::WatchResolution);
+        }
+        self.condition = args.condition;
+    }
     
     async fn execute_message(&mut self, message: Self::Message) {
+        // Every message brings the market up to block time before it is handled
+        self.run_timers(system_api::current_system_time().micros());
+        // The block that retires the chain handles nothing else
+        if self.archive.is_some() {
+            return;
+        }
         match message {
-            MarketMessage::BatchedOrders { user_chain_id, orders, nonce } => {
+            MarketMessage::BatchedOrders {
+                user_chain_id,
+                orders,
+                nonce,
+                signature,
+                relayer_fee,
+                cosignatures,
+            } => {
+                // Until the batch is authenticated, rejections go back to whoever sent it
+                let origin = self.message_origin();
+                let filled_at = system_api::current_system_time().micros();
+                // Only an open market trades; before opening, orders can only join the opening auction
+                let accepting = match self.status {
+                    MarketStatus::Open => true,
+                    MarketStatus::Created => self.auction.is_some(),
+                    _ => false,
+                };
+                if !accepting || self.condition_met() == Some(false) {
+                    self.reject_batch(origin, user_chain_id, &orders, RejectionReason::MarketClosed);
+                    return;
+                }
+                if self.protocol_paused {
+                    self.reject_batch(origin, user_chain_id, &orders, RejectionReason::ProtocolPaused);
+                    return;
+                }
+                if let Some(until) = self.excluded_until(user_chain_id, filled_at) {
+                    self.reject_batch(origin, user_chain_id, &orders, RejectionReason::SelfExcluded { until });
+                    return;
+                }
+                
+                // Orders not sent by the user chain itself need a signature from its registered key
+                let digest = signing::order_digest(
+                    user_chain_id,
+                    self.chain_id(),
+                    nonce,
+                    &orders,
+                    relayer_fee.as_ref(),
+                );
+                // The fee is only payable to the chain that actually relayed the batch
+                if relayer_fee.as_ref().is_some_and(|fee| fee.relayer != origin) {
+                    self.reject_batch(origin, user_chain_id, &orders, RejectionReason::Unauthorized);
+                    return;
+                }
+                // A session key's spend is only charged once the batch is accepted, so a
+                // replayed batch bounced by the nonce check costs the key nothing
+                let session_charge = match self.authorize_batch(
+                    user_chain_id,
+                    origin,
+                    &digest,
+                    signature.as_ref(),
+                    &orders,
+                    relayer_fee.as_ref(),
+                ) {
+                    Ok(charge) => charge,
+                    Err(reason) => {
+                        self.reject_batch(origin, user_chain_id, &orders, reason);
+                        return;
+                    }
+                };
+                let single_signer = match self.meets_multisig(
+                    user_chain_id,
+                    &digest,
+                    signature.iter().chain(&cosignatures),
+                    &orders,
+                    filled_at,
+                ) {
+                    Ok(notional) => notional,
+                    Err(reason) => {
+                        self.reject_batch(origin, user_chain_id, &orders, reason);
+                        return;
+                    }
+                };
+                // Only the user app checks sub-account names and debits them, and it sends its
+                // batches unsigned from the user chain; anywhere else a name is just a label
+                let via_user_app = origin == user_chain_id && signature.is_none();
+                if !via_user_app && orders.iter().any(|order| order.subaccount.is_some()) {
+                    self.reject_batch(origin, user_chain_id, &orders, RejectionReason::UnknownSubaccount);
+                    return;
+                }
+                if !self.is_approved(user_chain_id) {
+                    self.reject_batch(user_chain_id, user_chain_id, &orders, RejectionReason::NotApproved);
+                    return;
+                }
+                
                 // Verify nonce to prevent replay attacks
-                self.verify_nonce(user_chain_id, nonce);
+                if let Err(reason) = self.verify_nonce(user_chain_id, nonce) {
+                    self.reject_batch(user_chain_id, user_chain_id, &orders, reason);
+                    return;
+                }
+                
+                // A halt reopens through an auction when the market is configured for it
+                if self.circuit_breaker.is_halted(filled_at)
+                    && self.auction_config.reopen_after_halt
+                    && self.auction.is_none()
+                {
+                    self.start_auction(self.circuit_breaker.halted_until);
+                }
+                let accepted = AuditEvent::OrdersAccepted { user_chain_id, nonce, order_count: orders.len() as u64 };
+                // During an auction orders only queue; they are paid for when it uncrosses
+                if let Some(auction) = self.auction.as_mut() {
+                    auction.collect(user_chain_id, orders);
+                    self.audit(accepted, filled_at);
+                    self.charge_authorization(user_chain_id, session_charge, single_signer, filled_at);
+                    self.pay_relayer(user_chain_id, relayer_fee);
+                    return;
+                }
+                if self.circuit_breaker.is_halted(filled_at) {
+                    let halted = RejectionReason::Halted { until: self.circuit_breaker.halted_until };
+                    self.reject_batch(user_chain_id, user_chain_id, &orders, halted);
+                    return;
+                }
+                self.charge_authorization(user_chain_id, session_charge, single_signer, filled_at);
+                self.circuit_breaker
+                    .roll_window(system_api::current_block_height().into(), self.yes_odds);
+                self.audit(accepted, filled_at);
                 
                 let mut total_cost = Amount::zero();
                 let mut processed_orders = Vec::new();
+                let mut rejections = Vec::new();
                 
                 // Process each order in the batch
-                for order in orders {
-                    match order.side {
-                        OrderSide::BuyYes => {
-                            let cost = self.calculate_cost(order.amount, self.yes_odds);
-                            total_cost += cost;
-                            self.pool_yes += order.amount;
-                        }
-                        OrderSide::BuyNo => {
-                            let cost = self.calculate_cost(order.amount, self.no_odds);
-                            total_cost += cost;
-                            self.pool_no += order.amount;
-                        }
+                let mut orders = orders.into_iter();
+                for order in orders.by_ref() {
+                    let price = match order.side {
+                        OrderSide::BuyYes => self.yes_odds,
+                        OrderSide::BuyNo => self.no_odds,
+                    };
+                    if let Err(reason) = validation::check_price(&order, price)
+                        .and_then(|()| self.check_caps(user_chain_id, &order, price))
+                    {
+                        rejections.push(OrderRejection { order_id: order.id, reason });
+                        continue;
                     }
+                    total_cost += self.fill_order(user_chain_id, &order, price, filled_at);
                     processed_orders.push(order.id);
                     
                     // Update odds after each order
                     self.update_odds();
+                    
+                    // Orders after the one that tripped the breaker are rejected unfilled
+                    if self.circuit_breaker.check(self.yes_odds, filled_at) {
+                        break;
+                    }
                 }
+                let halted = RejectionReason::Halted { until: self.circuit_breaker.halted_until };
+                rejections.extend(validation::reject_all(&orders.collect::<Vec<_>>(), halted));
                 
-                // Send payment request to user's chain
-                let payment_msg = MarketMessage::Transfer {
-                    from: user_chain_id,
-                    to: self.chain_id(),
-                    amount: total_cost,
+                self.settle_fills(user_chain_id, processed_orders, total_cost, relayer_fee, filled_at, rejections);
+                self.rebalance_yield();
+            }
+            
+            MarketMessage::AmendOrder { user_chain_id, order_id, amount, max_price, nonce, signature } => {
+                let origin = self.message_origin();
+                let now = system_api::current_system_time().micros();
+                // Same gate as a new batch: once trading stops the outcome may be known
+                let accepting = match self.status {
+                    MarketStatus::Open => true,
+                    MarketStatus::Created => self.auction.is_some(),
+                    _ => false,
                 };
+                if !accepting || self.condition_met() == Some(false) {
+                    self.reject_amendment(origin, user_chain_id, order_id, RejectionReason::MarketClosed);
+                    return;
+                }
+                // Only queued orders can change; anything else has already filled or was never placed
+                let Some(original) = self
+                    .auction
+                    .as_ref()
+                    .and_then(|auction| auction.find(user_chain_id, order_id))
+                    .cloned()
+                else {
+                    self.reject_amendment(origin, user_chain_id, order_id, RejectionReason::OrderNotFound);
+                    return;
+                };
+                // While paused an amendment may only shrink the order, a partial cancel
+                if self.protocol_paused && amount > original.amount {
+                    self.reject_amendment(origin, user_chain_id, order_id, RejectionReason::ProtocolPaused);
+                    return;
+                }
+                // Likewise while self-excluded
+                if let Some(until) = self.excluded_until(user_chain_id, now).filter(|_| amount > original.amount) {
+                    self.reject_amendment(origin, user_chain_id, order_id, RejectionReason::SelfExcluded { until });
+                    return;
+                }
+                let replacement = Order { amount, max_price, ..original };
                 
-                self.send_message(user_chain_id, payment_msg);
+                let digest = signing::amend_digest(user_chain_id, self.chain_id(), nonce, order_id, amount, max_price);
+                let replacements = std::slice::from_ref(&replacement);
+                let session_charge = match self.authorize_batch(user_chain_id, origin, &digest, signature.as_ref(), replacements, None) {
+                    Ok(charge) => charge,
+                    Err(reason) => {
+                        self.reject_amendment(origin, user_chain_id, order_id, reason);
+                        return;
+                    }
+                };
+                let single_signer = match self.meets_multisig(user_chain_id, &digest, signature.iter(), replacements, now) {
+                    Ok(notional) => notional,
+                    Err(reason) => {
+                        self.reject_amendment(origin, user_chain_id, order_id, reason);
+                        return;
+                    }
+                };
+                if let Err(reason) = self.verify_nonce(user_chain_id, nonce) {
+                    self.reject_amendment(user_chain_id, user_chain_id, order_id, reason);
+                    return;
+                }
+                self.charge_authorization(user_chain_id, session_charge, single_signer, now);
                 
-                // Send confirmation back
-                let confirm_msg = MarketMessage::BatchConfirmed {
-                    user_chain_id,
-                    order_ids: processed_orders,
-                    total_cost,
+                if let Some(auction) = self.auction.as_mut() {
+                    auction.replace(user_chain_id, replacement);
+                }
+                self.audit(AuditEvent::OrderAmended { user_chain_id, order_id, amount, max_price }, now);
+            }
+            
+            // Timers already ran; this message exists to wake an idle market
+            MarketMessage::UncrossAuction => {}
+            
+            // Executed by the market app on the chain the transfer was sent to. Payouts were
+            // already made on the paying market's chain and only arrive as receipts; a charge
+            // on this chain is paid once, and only within what the chain approved for it.
+            MarketMessage::Transfer { from, to, amount, token, transfer_id } => {
+                let origin = self.message_origin();
+                if self.transfers.admit(self.chain_id(), origin, from, amount, transfer_id) == Admission::Debit {
+                    self.move_funds(to, amount, token);
+                }
+            }
+            
+            MarketMessage::ApprovePayments { market_chain, amount } => {
+                // Only the user chain itself decides what markets may charge it
+                if self.message_origin() == self.chain_id() {
+                    self.transfers.approve(market_chain, amount);
+                }
+            }
+            
+            MarketMessage::SelfExclude { user_chain_id, until } => {
+                if self.message_origin() == user_chain_id {
+                    let excluded_until = self.self_exclusions.entry(user_chain_id).or_insert(0);
+                    *excluded_until = (*excluded_until).max(until);
+                }
+            }
+            
+            MarketMessage::SubmitAtomic { legs } => {
+                // Only the user chain itself may start a saga on its own market app
+                if self.message_origin() != self.chain_id() || !saga::is_valid_plan(&legs) {
+                    return;
+                }
+                let now = system_api::current_system_time().micros();
+                if self.excluded_until(self.chain_id(), now).is_some() {
+                    return;
+                }
+                let saga_id = self.next_saga_id;
+                self.next_saga_id += 1;
+                let user_chain_id = self.chain_id();
+                for leg in &legs {
+                    // Each leg charges for its orders once it commits
+                    let notional = leg.orders.iter().fold(Amount::zero(), |sum, order| sum + order.amount);
+                    self.transfers.approve(leg.market_chain, notional);
+                    let reserve = MarketMessage::ReserveOrders { saga_id, user_chain_id, orders: leg.orders.clone() };
+                    self.send_message(leg.market_chain, reserve);
+                }
+                self.sagas.insert(saga_id, Saga::new(legs, now));
+                self.timers.schedule(now + SAGA_TIMEOUT, TimerKind::AbortSaga);
+            }
+            
+            MarketMessage::ReserveOrders { saga_id, user_chain_id, orders } => {
+                // The user chain coordinates its own sagas; nobody else can reserve for it
+                if self.message_origin() != user_chain_id || self.reservations.contains_key(&(user_chain_id, saga_id)) {
+                    return;
+                }
+                let now = system_api::current_system_time().micros();
+                // Atomic batches only fill in continuous trading, never through an auction
+                let refusal = if self.status != MarketStatus::Open
+                    || self.auction.is_some()
+                    || self.condition_met() == Some(false)
+                {
+                    Some(RejectionReason::MarketClosed)
+                } else if self.protocol_paused {
+                    Some(RejectionReason::ProtocolPaused)
+                } else if self.circuit_breaker.is_halted(now) {
+                    Some(RejectionReason::Halted { until: self.circuit_breaker.halted_until })
+                } else if let Some(until) = self.excluded_until(user_chain_id, now) {
+                    Some(RejectionReason::SelfExcluded { until })
+                } else if !self.is_approved(user_chain_id) {
+                    Some(RejectionReason::NotApproved)
+                } else if orders.iter().any(|order| order.subaccount.is_some()) {
+                    // Atomic batches don't go through the user app, so nothing checked the names
+                    Some(RejectionReason::UnknownSubaccount)
+                } else {
+                    self.within_single_signer_window(user_chain_id, &orders, now).err()
+                };
+                // A reservation must lapse before the market locks, or it would fill after trading closed
+                let refusal = refusal.or_else(|| (now + RESERVATION_TTL > self.locks_at).then_some(RejectionReason::MarketClosed));
+                if let Some(reason) = refusal {
+                    let rejections = validation::reject_all(&orders, reason);
+                    self.send_message(user_chain_id, MarketMessage::ReservationRejected { saga_id, rejections });
+                    return;
+                }
+                
+                let mut reserved = Vec::new();
+                let mut rejections = Vec::new();
+                let mut total_cost = Amount::zero();
+                for order in orders {
+                    let price = match order.side {
+                        OrderSide::BuyYes => self.yes_odds,
+                        OrderSide::BuyNo => self.no_odds,
+                    };
+                    match validation::check_price(&order, price).and_then(|()| self.check_caps(user_chain_id, &order, price)) {
+                        Ok(()) => {
+                            total_cost += self.calculate_cost(order.amount, price);
+                            reserved.push((order, price));
+                        }
+                        Err(reason) => rejections.push(OrderRejection { order_id: order.id, reason }),
+                    }
+                }
+                // One refused order refuses the whole leg; the batch is all or nothing
+                if !rejections.is_empty() {
+                    self.send_message(user_chain_id, MarketMessage::ReservationRejected { saga_id, rejections });
+                    return;
+                }
+                let expires_at = now + RESERVATION_TTL;
+                self.reservations.insert((user_chain_id, saga_id), Reservation { orders: reserved, expires_at });
+                self.timers.schedule(expires_at, TimerKind::ExpireReservations);
+                self.send_message(user_chain_id, MarketMessage::OrdersReserved { saga_id, total_cost });
+            }
+            
+            MarketMessage::OrdersReserved { saga_id, total_cost } => {
+                let market_chain = self.message_origin();
+                let Some(saga) = self.sagas.get_mut(&saga_id) else {
+                    return;
+                };
+                let Some(leg) = saga.leg_of(market_chain) else {
+                    return;
+                };
+                // A vote arriving after an abort is answered by the abort already on its way
+                if saga.status != SagaStatus::Reserving {
+                    return;
+                }
+                saga.votes[leg] = LegVote::Reserved { total_cost };
+                if saga.all_reserved() {
+                    saga.status = SagaStatus::Committed;
+                    let chains: Vec<ChainId> = saga.legs.iter().map(|leg| leg.market_chain).collect();
+                    for market_chain in chains {
+                        self.send_message(market_chain, MarketMessage::CommitReservation { saga_id });
+                    }
+                }
+            }
+            
+            MarketMessage::ReservationRejected { saga_id, rejections } => {
+                let market_chain = self.message_origin();
+                let Some(saga) = self.sagas.get_mut(&saga_id) else {
+                    return;
+                };
+                let Some(leg) = saga.leg_of(market_chain) else {
+                    return;
+                };
+                saga.votes[leg] = LegVote::Refused;
+                // Kept even after a commit: a reservation that lapsed first is the one way a saga ends partial
+                saga.rejections.extend(rejections);
+                if saga.status == SagaStatus::Reserving {
+                    self.abort_saga(saga_id);
+                }
+            }
+            
+            MarketMessage::CommitReservation { saga_id } => {
+                let user_chain_id = self.message_origin();
+                // Lapsed reservations were already reported back as `ReservationExpired`
+                let Some(reservation) = self.reservations.remove(&(user_chain_id, saga_id)) else {
+                    return;
+                };
+                let filled_at = system_api::current_system_time().micros();
+                // Leaving `Open` already dropped every reservation; anything else that
+                // stopped trading since, or other fills that used up the caps, refuses the leg
+                let refusal = if self.condition_met() == Some(false) {
+                    Some(RejectionReason::MarketClosed)
+                } else if self.protocol_paused {
+                    Some(RejectionReason::ProtocolPaused)
+                } else if self.circuit_breaker.is_halted(filled_at) {
+                    Some(RejectionReason::Halted { until: self.circuit_breaker.halted_until })
+                } else {
+                    None
+                };
+                let orders: Vec<Order> = reservation.orders.iter().map(|(order, _)| order.clone()).collect();
+                // Other fills may have used up the single-signer window since the reservation
+                let single_signer = self.within_single_signer_window(user_chain_id, &orders, filled_at);
+                let refusal = refusal.or_else(|| single_signer.clone().err());
+                let rejections = match refusal {
+                    Some(reason) => validation::reject_all(&orders, reason),
+                    None => self.check_reserved_caps(user_chain_id, &reservation.orders).err().into_iter().collect(),
+                };
+                if !rejections.is_empty() {
+                    self.send_message(user_chain_id, MarketMessage::ReservationRejected { saga_id, rejections });
+                    return;
+                }
+                self.charge_authorization(user_chain_id, None, single_signer.unwrap_or(Amount::zero()), filled_at);
+                let mut total_cost = Amount::zero();
+                let mut filled = Vec::new();
+                for (order, price) in &reservation.orders {
+                    total_cost += self.fill_order(user_chain_id, order, *price, filled_at);
+                    filled.push(order.id);
+                }
+                self.update_odds();
+                // A commit fills regardless, but may still halt trading for everyone after it
+                self.circuit_breaker.check(self.yes_odds, filled_at);
+                self.settle_fills(user_chain_id, filled, total_cost, None, filled_at, Vec::new());
+                self.rebalance_yield();
+            }
+            
+            MarketMessage::AbortReservation { saga_id } => {
+                let user_chain_id = self.message_origin();
+                self.reservations.remove(&(user_chain_id, saga_id));
+            }
+            
+            MarketMessage::Tracked { outbox_id, payload } => {
+                // Acknowledged even when it can't be decoded, so a bad message isn't resent forever
+                self.send_message(self.message_origin(), MarketMessage::Delivered { outbox_id });
+                if let Ok(message) = bcs::from_bytes::<MarketMessage>(&payload) {
+                    Box::pin(self.execute_message(message)).await;
+                }
+            }
+            
+            MarketMessage::Delivered { outbox_id } => {
+                let origin = self.message_origin();
+                self.outbox.acknowledge(origin, outbox_id);
+            }
+            
+            MarketMessage::RetryMessage { outbox_id } => {
+                // Only the recipient or this chain may spend the entry's attempts
+                let origin = self.message_origin();
+                let Some(entry) = self.outbox.entries.get(&outbox_id) else {
+                    return;
                 };
-                self.send_message(user_chain_id, confirm_msg);
+                if origin != entry.destination && origin != self.chain_id() {
+                    return;
+                }
+                let now = system_api::current_system_time().micros();
+                self.resend(outbox_id, now);
             }
             
-            MarketMessage::Resolution { outcome, signature, oracle_type } => {
-                self.verify_oracle_signature(outcome, signature, oracle_type);
-                self.status = MarketStatus::Resolved(outcome);
+            MarketMessage::Resolution { outcome, timestamp, signature, oracle_type } => {
+                // Conditional markets settle only once the parent has activated them
+                if self.condition_met() != Some(true) {
+                    return;
+                }
+                let now = system_api::current_system_time().micros();
+                // Past its deadline the configured oracle shares the job with the fallback committee
+                if !self.accepts_oracle(&oracle_type) {
+                    return;
+                }
+                let message = oracle_keys::resolution_message(&self.market_id, outcome, timestamp);
+                if self.verify_oracle_signature(&oracle_type, &message, &signature).is_err() {
+                    return;
+                }
+                // Resolving early skips the rest of the schedule; a settled market can't be resolved again
+                if self.status != MarketStatus::Resolving && !self.transition(MarketStatus::Resolving, now) {
+                    return;
+                }
+                self.transition(MarketStatus::Resolved(outcome), now);
+                self.audit(AuditEvent::Resolved { outcome }, now);
                 self.distribute_winnings();
+                
+                // Report every trader's result so the registry can rank them
+                let results = self
+                    .positions
+                    .iter()
+                    .map(|(user_chain_id, position)| TraderResult {
+                        user_chain_id: *user_chain_id,
+                        cost: position.cost_basis,
+                        payout: self.payout_of(position, outcome)
+                            + self.stage_earnings.get(user_chain_id).copied().unwrap_or(Amount::zero()),
+                    })
+                    .collect();
+                self.send_message(self.registry_chain, RegistryMessage::MarketSettled { outcome, results });
+                
+                for watcher in std::mem::take(&mut self.parlay_watchers) {
+                    let resolved_msg = MarketMessage::ParlayLegResolved {
+                        parlay_id: watcher.parlay_id,
+                        leg_index: watcher.leg_index,
+                        won: watcher.outcome == outcome,
+                    };
+                    self.send_message(watcher.coordinator, resolved_msg);
+                }
             }
             
-            _ => {}
+            MarketMessage::StageResolution { stage, outcome, timestamp, signature, oracle_type } => {
+                // Stages settle while the market is live, and only once a conditional market is activated
+                if self.status.is_final() || self.condition_met() != Some(true) {
+                    return;
+                }
+                let Some(index) = usize::try_from(stage).ok().filter(|index| *index < self.stages.len()) else {
+                    return;
+                };
+                if self.stages[index].outcome.is_some() {
+                    return;
+                }
+                if !self.accepts_oracle(&oracle_type) {
+                    return;
+                }
+                // Stage signatures cover the stage index, so they can't stand in for a final resolution
+                let message = oracle_keys::stage_message(&self.market_id, stage, outcome, timestamp);
+                if self.verify_oracle_signature(&oracle_type, &message, &signature).is_err() {
+                    return;
+                }
+                self.settle_stage(index, outcome, system_api::current_system_time().micros());
+            }
+            
+            MarketMessage::RegisterSigner { user_chain_id, public_key } => {
+                // Only the user chain itself may choose its signing key
+                if self.message_origin() == user_chain_id {
+                    self.signer_keys.insert(user_chain_id, public_key);
+                }
+            }
+            
+            MarketMessage::GrantSessionKey { user_chain_id, public_key, spend_cap, expires_at, markets } => {
+                if self.message_origin() == user_chain_id && markets.len() <= MAX_SESSION_KEY_MARKETS {
+                    let key = SessionKey { spend_cap, spent: Amount::zero(), expires_at, markets };
+                    self.session_keys.insert((user_chain_id, public_key), key);
+                }
+            }
+            
+            MarketMessage::RevokeSessionKey { user_chain_id, public_key } => {
+                if self.message_origin() == user_chain_id {
+                    self.session_keys.remove(&(user_chain_id, public_key));
+                }
+            }
+            
+            MarketMessage::SetMultisigPolicy { user_chain_id, policy } => {
+                // A single key holder could otherwise lift the policy that binds them
+                if self.message_origin() != user_chain_id || self.multisig_policies.contains_key(&user_chain_id) {
+                    return;
+                }
+                if let Some(policy) = policy.filter(MultisigPolicy::is_valid) {
+                    self.multisig_policies.insert(user_chain_id, policy);
+                }
+            }
+            
+            MarketMessage::UpdateMultisigPolicy { user_chain_id, policy, nonce, signatures } => {
+                if self.message_origin() != user_chain_id {
+                    return;
+                }
+                let Some(current) = self.multisig_policies.get(&user_chain_id) else {
+                    return;
+                };
+                if policy.as_ref().is_some_and(|policy| !policy.is_valid()) {
+                    return;
+                }
+                let digest = multisig::policy_digest(user_chain_id, self.chain_id(), nonce, policy.as_ref());
+                if current.approvals(&digest, signatures.iter()) < current.required {
+                    return;
+                }
+                if self.verify_nonce(user_chain_id, nonce).is_err() {
+                    return;
+                }
+                match policy {
+                    Some(policy) => self.multisig_policies.insert(user_chain_id, policy),
+                    None => self.multisig_policies.remove(&user_chain_id),
+                };
+                self.single_signer_notional.remove(&user_chain_id);
+            }
+            
+            MarketMessage::RegisterReferralCode { code, referrer } => {
+                // First come, first served; a chain can only register codes for itself
+                if self.message_origin() == referrer && !self.referral_codes.contains_key(&code) {
+                    self.referral_codes.insert(code, referrer);
+                }
+            }
+            
+            MarketMessage::HarvestYield => {
+                if self.status == MarketStatus::Open {
+                    self.harvest_yield(system_api::current_system_time().micros());
+                }
+            }
+            
+            MarketMessage::ClaimYield { provider } => {
+                if let Some(earned) = self.lp_yield_earnings.remove(&provider) {
+                    self.pay(provider, earned);
+                }
+            }
+            
+            MarketMessage::SweepProtocolFees => self.sweep_protocol_fees(),
+            
+            MarketMessage::RotateOracleKey { new_key, announced_at, overlap, signature } => {
+                let OracleType::FastTee { public_key } = &self.oracle_type else {
+                    return;
+                };
+                let digest = oracle_keys::rotation_digest(public_key, &new_key, announced_at, overlap);
+                let by_guardian = self
+                    .oracle_guardian
+                    .as_deref()
+                    .is_some_and(|guardian| oracle_keys::verify_signature(guardian, &digest, &signature));
+                // One rotation at a time; only the guardian may cut across one, e.g. when the new key leaks too
+                let by_oracle = self.oracle_key_rotation.is_none()
+                    && oracle_keys::verify_signature(public_key, &digest, &signature);
+                // Applied in order, so a replayed announcement can't roll the key back
+                if !(by_guardian || by_oracle) || announced_at <= self.last_key_rotation_at {
+                    return;
+                }
+                let now = system_api::current_system_time().micros();
+                self.last_key_rotation_at = announced_at;
+                self.timers.cancel(TimerKind::CompleteKeyRotation);
+                self.oracle_key_rotation = Some(KeyRotation { new_key, announced_at, completes_at: now + overlap });
+                self.timers.schedule(now + overlap, TimerKind::CompleteKeyRotation);
+                // Without an overlap the old key is dropped in this block
+                self.run_timers(now);
+            }
+            
+            MarketMessage::UpdateOracle { oracle_type } => {
+                // Only the registry that created the market may move it, and only before it settles
+                if self.message_origin() != self.registry_chain || self.status.is_final() {
+                    return;
+                }
+                self.oracle_type = oracle_type.clone();
+                // A rotation the old oracle announced means nothing for the new one
+                self.oracle_key_rotation = None;
+                self.timers.cancel(TimerKind::CompleteKeyRotation);
+                self.send_message(self.registry_chain, RegistryMessage::OracleUpdated { oracle_type });
+            }
+            
+            MarketMessage::ClaimReferralEarnings { referrer } => {
+                if let Some(earned) = self.referral_earnings.remove(&referrer) {
+                    self.pay(referrer, earned);
+                }
+            }
+            
+            MarketMessage::AttachEvidence { kind, content_hash, uri } => {
+                // Anyone may attach evidence; auditors judge it by its submitter. The
+                // per-submitter cap keeps one chain from crowding out the disputants.
+                let submitter = self.message_origin();
+                let submitted = self.evidence.iter().filter(|e| e.submitter == submitter).count();
+                if self.evidence.len() < MAX_EVIDENCE && submitted < MAX_EVIDENCE_PER_SUBMITTER {
+                    // Disputes also go to the registry's public activity feed
+                    if kind == EvidenceKind::DisputeEvidence {
+                        let report = RegistryMessage::DisputeFiled { submitter, uri: uri.clone() };
+                        self.send_message(self.registry_chain, report);
+                    }
+                    self.evidence.push(Evidence {
+                        kind,
+                        content_hash,
+                        uri,
+                        submitter,
+                        submitted_at: system_api::current_system_time().micros(),
+                    });
+                }
+            }
+            
+            MarketMessage::AddLiquidity { provider, amount } => {
+                if self.message_origin() != provider || matches!(self.status, MarketStatus::Resolved(_)) {
+                    return;
+                }
+                if self.protocol_paused {
+                    return;
+                }
+                let pool_total = self.pool_yes + self.pool_no;
+                let minted = if self.total_lp_shares == Amount::zero() || pool_total == Amount::zero() {
+                    amount
+                } else {
+                    Amount::from_attos(
+                        u128::from(amount) * u128::from(self.total_lp_shares) / u128::from(pool_total),
+                    )
+                };
+                self.add_to_pools(amount);
+                self.total_lp_shares += minted;
+                let position = self.lp_positions.entry(provider).or_default();
+                position.shares += minted;
+                position.last_deposit_at = system_api::current_system_time().micros();
+                self.rebalance_yield();
+                
+                let payment_msg = self.transfer(provider, self.chain_id(), amount);
+                self.send_message(provider, payment_msg);
+            }
+            
+            MarketMessage::RemoveLiquidity { provider, shares } => {
+                if self.message_origin() != provider || matches!(self.status, MarketStatus::Resolved(_)) {
+                    return;
+                }
+                let Some(position) = self.lp_positions.get(&provider) else {
+                    return;
+                };
+                if shares > position.shares || self.total_lp_shares == Amount::zero() {
+                    return;
+                }
+                let value = Amount::from_attos(
+                    u128::from(self.pool_yes + self.pool_no) * u128::from(shares)
+                        / u128::from(self.total_lp_shares),
+                );
+                let now = system_api::current_system_time().micros();
+                // The early-exit fee stays in the pools for the remaining providers
+                let fee = match self.fee_schedule.check_withdrawal(value, position.last_deposit_at, now) {
+                    WithdrawalCheck::Free => Amount::zero(),
+                    WithdrawalCheck::EarlyExit(fee) => fee,
+                    WithdrawalCheck::Locked => return,
+                };
+                let payout = value.saturating_sub(fee);
+                
+                self.remove_from_pools(payout);
+                self.total_lp_shares = self.total_lp_shares.saturating_sub(shares);
+                let position = self.lp_positions.get_mut(&provider).unwrap();
+                position.shares = position.shares.saturating_sub(shares);
+                if position.shares == Amount::zero() {
+                    self.lp_positions.remove(&provider);
+                }
+                // Brings back enough principal to cover the payout
+                self.rebalance_yield();
+                
+                self.pay(provider, payout);
+            }
+            
+            MarketMessage::PlaceParlay { user_chain_id, stake, legs } => {
+                if self.message_origin() != user_chain_id || legs.len() < 2 || legs.len() > MAX_PARLAY_LEGS {
+                    return;
+                }
+                if self.protocol_paused || !self.is_approved(user_chain_id) {
+                    return;
+                }
+                // Only a live market coordinates, and only parlays it is a leg of; leg markets
+                // take quote requests on that basis
+                if self.status != MarketStatus::Open || !legs.iter().any(|leg| leg.market_chain == self.chain_id()) {
+                    return;
+                }
+                let parlay_id = self.next_parlay_id;
+                self.next_parlay_id += 1;
+                self.parlays.insert(parlay_id, Parlay::new(user_chain_id, stake, legs.clone()));
+                
+                let payment_msg = self.transfer(user_chain_id, self.chain_id(), stake);
+                self.send_message(user_chain_id, payment_msg);
+                
+                // This market's own leg goes through the same message path as the others
+                for (leg_index, leg) in legs.into_iter().enumerate() {
+                    let quote_msg = MarketMessage::ParlayLegQuote { parlay_id, leg_index, outcome: leg.outcome };
+                    self.send_message(leg.market_chain, quote_msg);
+                }
+            }
+            
+            MarketMessage::ParlayLegQuote { parlay_id, leg_index, outcome } => {
+                let coordinator = self.message_origin();
+                // Quoting a market that no longer trades would hand out a known outcome, and
+                // every watcher is one more message when this market resolves
+                let held = self.parlay_watchers.iter().filter(|watcher| watcher.coordinator == coordinator).count();
+                let repeated = self.parlay_watchers.iter().any(|watcher| {
+                    watcher.coordinator == coordinator && watcher.parlay_id == parlay_id && watcher.leg_index == leg_index
+                });
+                if self.status != MarketStatus::Open
+                    || repeated
+                    || held >= MAX_WATCHERS_PER_COORDINATOR
+                    || self.parlay_watchers.len() >= MAX_PARLAY_WATCHERS
+                {
+                    self.send_message(coordinator, MarketMessage::ParlayLegRejected { parlay_id });
+                    return;
+                }
+                let odds = if outcome { self.yes_odds } else { self.no_odds };
+                self.parlay_watchers.push(ParlayWatcher { coordinator, parlay_id, leg_index, outcome });
+                self.send_message(coordinator, MarketMessage::ParlayLegQuoted { parlay_id, leg_index, odds });
+            }
+            
+            MarketMessage::ParlayLegQuoted { parlay_id, leg_index, odds } => {
+                if let Some(parlay) = self.parlay_leg_from(parlay_id, leg_index) {
+                    parlay.leg_odds[leg_index] = Some(odds);
+                    self.set_aside_winnings(parlay_id);
+                    self.settle_parlay(parlay_id);
+                }
+            }
+            
+            MarketMessage::ParlayLegResolved { parlay_id, leg_index, won } => {
+                if let Some(parlay) = self.parlay_leg_from(parlay_id, leg_index) {
+                    parlay.leg_results[leg_index] = Some(won);
+                    self.settle_parlay(parlay_id);
+                }
+            }
+            
+            MarketMessage::ParlayLegRejected { parlay_id } => {
+                let origin = self.message_origin();
+                let Some(parlay) = self.parlays.get(&parlay_id) else {
+                    return;
+                };
+                if !parlay.legs.iter().any(|leg| leg.market_chain == origin) {
+                    return;
+                }
+                self.void_parlay(parlay_id);
+            }
+            
+            MarketMessage::FundParlayReserve { funder, amount } => {
+                if self.message_origin() != funder || self.status.is_final() || self.protocol_paused {
+                    return;
+                }
+                self.parlay_reserve += amount;
+                *self.parlay_funders.entry(funder).or_default() += amount;
+                let payment_msg = self.transfer(funder, self.chain_id(), amount);
+                self.send_message(funder, payment_msg);
+            }
+            
+            MarketMessage::WatchResolution => {
+                let watcher = self.message_origin();
+                if let Some(settled) = self.settlement_notice() {
+                    self.send_message(watcher, settled);
+                } else if !self.resolution_watchers.contains(&watcher)
+                    && self.resolution_watchers.len() < MAX_RESOLUTION_WATCHERS
+                {
+                    self.resolution_watchers.push(watcher);
+                }
+            }
+            
+            MarketMessage::ParentResolved { outcome } => {
+                let is_parent = self.condition.as_ref().is_some_and(|c| c.parent_chain == self.message_origin());
+                if is_parent && self.parent_outcome.is_none() {
+                    self.parent_outcome = Some(outcome);
+                    if self.condition_met() == Some(false) {
+                        self.transition(MarketStatus::Cancelled, system_api::current_system_time().micros());
+                    }
+                }
+            }
+            
+            MarketMessage::SessionSpent { public_key, amount } => {
+                // Kept by the user chain's own market app, from the markets the key was granted on
+                let origin = self.message_origin();
+                let user_chain_id = self.chain_id();
+                let Some(session) = self.session_keys.get_mut(&(user_chain_id, public_key.clone())) else {
+                    return;
+                };
+                if !session.markets.contains(&origin) {
+                    return;
+                }
+                session.spent += amount;
+                let (spent, markets) = (session.spent, session.markets.clone());
+                for market in markets {
+                    self.send_message(market, MarketMessage::SyncSessionSpend { public_key: public_key.clone(), spent });
+                }
+            }
+            
+            MarketMessage::SyncSessionSpend { public_key, spent } => {
+                // Only the user chain speaks for its keys; batches this market accepted since
+                // the total was sent are in flight to it, so the larger count wins
+                let user_chain_id = self.message_origin();
+                if let Some(session) = self.session_keys.get_mut(&(user_chain_id, public_key)) {
+                    session.spent = session.spent.max(spent);
+                }
+            }
+            
+            MarketMessage::ParentCancelled => {
+                // Without this the child would wait on the parent forever, its funds locked
+                let is_parent = self.condition.as_ref().is_some_and(|c| c.parent_chain == self.message_origin());
+                if is_parent && self.parent_outcome.is_none() {
+                    self.transition(MarketStatus::Cancelled, system_api::current_system_time().micros());
+                }
+            }
+            
+            MarketMessage::Claim { user_chain_id } => {
+                // A voided market refunds what was paid for the shares (fees are kept), less stage winnings already claimed
+                if self.status == MarketStatus::Cancelled || self.condition_met() == Some(false) {
+                    if let Some(refund) = self.take_refund(user_chain_id) {
+                        self.pay_out(user_chain_id, refund);
+                        let now = system_api::current_system_time().micros();
+                        self.audit(AuditEvent::Payout { user_chain_id, amount: refund }, now);
+                    }
+                    return;
+                }
+                // Stage winnings are claimable as soon as their stage settles
+                if let Some(credit) = self.take_stage_credit(user_chain_id) {
+                    self.pay_out(user_chain_id, credit);
+                    let now = system_api::current_system_time().micros();
+                    self.audit(AuditEvent::Payout { user_chain_id, amount: credit }, now);
+                }
+                // Only resolved markets pay out, and each position is paid once
+                if let MarketStatus::Resolved(outcome) = self.status {
+                    if let Some(payout) = self.take_payout(user_chain_id, outcome) {
+                        self.pay_out(user_chain_id, payout);
+                        let now = system_api::current_system_time().micros();
+                        self.audit(AuditEvent::Payout { user_chain_id, amount: payout }, now);
+                    }
+                }
+            }
+            
+            _ => {}
+        }
+    }
+    
+    // Only the registry's pause stream is subscribed to; anything else is ignored
+    async fn process_streams(&mut self, updates: Vec<StreamUpdate>) {
+        for update in updates {
+            if update.chain_id != self.registry_chain || update.stream_name != PAUSE_STREAM {
+                continue;
+            }
+            for index in update.new_indices() {
+                let event: PauseUpdate = system_api::read_event(update.chain_id, PAUSE_STREAM, index);
+                self.protocol_paused = event.paused;
+            }
+        }
+    }
+    
+    fn excluded_until(&self, user_chain_id: ChainId, now: u64) -> Option<u64> {
+        self.self_exclusions.get(&user_chain_id).copied().filter(|until| now < *until)
+    }
+    
+    // Markets without an allowlist accept every user chain
+    fn is_approved(&mut self, user_chain_id: ChainId) -> bool {
+        match self.allowlist {
+            Some(allowlist) => self.call_application(allowlist, &AllowlistCall::IsApproved { user_chain_id }),
+            None => true,
+        }
+    }
+    
+    // Accepts batches sent by the user chain itself, signed by its registered key,
+    // or signed by an unexpired session key with enough cap left
+    fn authorize_batch(
+        &self,
+        user_chain_id: ChainId,
+        origin: ChainId,
+        digest: &[u8; 32],
+        signature: Option<&OrderSignature>,
+        orders: &[Order],
+        relayer_fee: Option<&RelayerFee>,
+    ) -> Result<Option<SessionCharge>, RejectionReason> {
+        let Some(sig) = signature else {
+            return if origin == user_chain_id { Ok(None) } else { Err(RejectionReason::Unauthorized) };
+        };
+        if !signing::verify_order_signature(digest, sig) {
+            return Err(RejectionReason::Unauthorized);
+        }
+        if origin == user_chain_id || self.signer_keys.get(&user_chain_id) == Some(&sig.public_key) {
+            return Ok(None);
+        }
+        
+        let now = system_api::current_system_time().micros();
+        let Some(session) = self.session_keys.get(&(user_chain_id, sig.public_key.clone())) else {
+            return Err(RejectionReason::Unauthorized);
+        };
+        if now >= session.expires_at {
+            return Err(RejectionReason::Unauthorized);
+        }
+        // Spend is counted on notional, an upper bound on what the orders can cost, plus
+        // whatever the key signed over to a relayer
+        let notional = orders.iter().fold(Amount::zero(), |sum, order| sum + order.amount)
+            + relayer_fee.map_or(Amount::zero(), |fee| fee.amount);
+        if session.spent + notional > session.spend_cap {
+            return Err(RejectionReason::InsufficientFunds {
+                required: notional,
+                available: session.spend_cap.saturating_sub(session.spent),
+            });
+        }
+        Ok(Some(SessionCharge { public_key: sig.public_key.clone(), amount: notional }))
+    }
+    
+    // Batch nonces must increase per user chain, so a captured batch can't be replayed
+    fn verify_nonce(&mut self, user_chain_id: ChainId, nonce: u64) -> Result<(), RejectionReason> {
+        if let Some(&last_accepted) = self.last_nonces.get(&user_chain_id) {
+            if nonce <= last_accepted {
+                return Err(RejectionReason::StaleNonce { last_accepted });
+            }
+        }
+        self.last_nonces.insert(user_chain_id, nonce);
+        Ok(())
+    }
+    
+    // Reports every order of a batch as rejected for `reason`, without filling any
+    fn reject_batch(&mut self, to: ChainId, user_chain_id: ChainId, orders: &[Order], reason: RejectionReason) {
+        let confirm_msg = MarketMessage::BatchConfirmed {
+            user_chain_id,
+            order_ids: Vec::new(),
+            total_cost: Amount::zero(),
+            rejections: validation::reject_all(orders, reason),
+        };
+        self.send_tracked(to, "BatchConfirmed", confirm_msg);
+    }
+    
+    // Reports a refused amendment; the original order stays queued as it was
+    fn reject_amendment(&mut self, to: ChainId, user_chain_id: ChainId, order_id: u64, reason: RejectionReason) {
+        let confirm_msg = MarketMessage::BatchConfirmed {
+            user_chain_id,
+            order_ids: Vec::new(),
+            total_cost: Amount::zero(),
+            rejections: vec![OrderRejection { order_id, reason }],
+        };
+        self.send_tracked(to, "BatchConfirmed", confirm_msg);
+    }
+    
+    // Past the multisig threshold within the window, batches need enough distinct policy
+    // signers. Returns the notional to count against the window once the batch is
+    // accepted: the batch's own, unless it carried a quorum.
+    fn meets_multisig<'a>(
+        &self,
+        user_chain_id: ChainId,
+        digest: &[u8; 32],
+        signatures: impl Iterator<Item = &'a OrderSignature>,
+        orders: &[Order],
+        now: u64,
+    ) -> Result<Amount, RejectionReason> {
+        let within_window = self.within_single_signer_window(user_chain_id, orders, now);
+        let Some(policy) = self.multisig_policies.get(&user_chain_id) else {
+            return within_window;
+        };
+        if within_window.is_err() && policy.approvals(digest, signatures) >= policy.required {
+            return Ok(Amount::zero());
+        }
+        within_window
+    }
+    
+    // The policy check for batches nobody can cosign, like atomic legs: they only fit
+    // within what one key may trade alone
+    fn within_single_signer_window(&self, user_chain_id: ChainId, orders: &[Order], now: u64) -> Result<Amount, RejectionReason> {
+        let Some(policy) = self.multisig_policies.get(&user_chain_id) else {
+            return Ok(Amount::zero());
+        };
+        let notional = orders.iter().fold(Amount::zero(), |sum, order| sum + order.amount);
+        let traded = self
+            .single_signer_notional
+            .get(&user_chain_id)
+            .map_or(Amount::zero(), |window| window.trailing(now));
+        if traded + notional <= policy.threshold_amount {
+            Ok(notional)
+        } else {
+            Err(RejectionReason::Unauthorized)
+        }
+    }
+    
+    // Counts an accepted batch's authorization: the session key's spend and the single-signer window
+    fn charge_authorization(&mut self, user_chain_id: ChainId, session_charge: Option<SessionCharge>, single_signer: Amount, now: u64) {
+        if single_signer > Amount::zero() {
+            self.single_signer_notional.entry(user_chain_id).or_default().record(now, single_signer);
+        }
+        let Some(charge) = session_charge else {
+            return;
+        };
+        let Some(session) = self.session_keys.get_mut(&(user_chain_id, charge.public_key.clone())) else {
+            return;
+        };
+        session.spent += charge.amount;
+        // The user chain adds it to the key's spend on its other markets
+        let report = MarketMessage::SessionSpent { public_key: charge.public_key, amount: charge.amount };
+        self.send_message(user_chain_id, report);
+    }
+    
+    // Taker fee on an order's cost; credits the referrer's cut when the code is valid
+    fn charge_fee(&mut self, cost: Amount, referral_code: Option<&str>, user_chain_id: ChainId, now: u64) -> Amount {
+        let volume = self.user_volume.get(&user_chain_id).map_or(Amount::zero(), |history| history.trailing(now));
+        let fee = self.fee_schedule.taker_fee(cost, volume);
+        let referrer = referral_code.and_then(|code| self.referral_codes.get(code)).copied();
+        // Self-referrals would just be a fee discount
+        if let Some(referrer) = referrer.filter(|referrer| *referrer != user_chain_id) {
+            let cut = self.fee_schedule.referral_cut(fee);
+            *self.referral_earnings.entry(referrer).or_insert(Amount::zero()) += cut;
+        }
+        self.oracle_fees += self.fee_schedule.oracle_cut(fee);
+        self.protocol_fees += self.fee_schedule.protocol_cut(fee);
+        fee
+    }
+    
+    // Winning shares' pro-rata share of the whole pool; marks the position claimed
+    fn take_payout(&mut self, user_chain_id: ChainId, outcome: bool) -> Option<Amount> {
+        let position = self.positions.get(&user_chain_id)?;
+        let payout = self.payout_of(position, outcome);
+        if position.claimed || payout == Amount::zero() {
+            return None;
+        }
+        self.positions.get_mut(&user_chain_id)?.claimed = true;
+        self.position_root = merkle::position_root(&self.positions);
+        Some(payout)
+    }
+    
+    // What was paid for the shares less stage winnings already claimed out of them
+    fn take_refund(&mut self, user_chain_id: ChainId) -> Option<Amount> {
+        let claimed = self.stage_claimed.get(&user_chain_id).copied().unwrap_or(Amount::zero());
+        let position = self.positions.get_mut(&user_chain_id)?;
+        let refund = position.cost_basis.saturating_sub(claimed);
+        if position.claimed || refund == Amount::zero() {
+            return None;
+        }
+        position.claimed = true;
+        self.position_root = merkle::position_root(&self.positions);
+        Some(refund)
+    }
+    
+    // Stage winnings credited but not yet claimed; marks them claimed
+    fn take_stage_credit(&mut self, user_chain_id: ChainId) -> Option<Amount> {
+        let earned = self.stage_earnings.get(&user_chain_id).copied()?;
+        let claimed = self.stage_claimed.entry(user_chain_id).or_insert(Amount::zero());
+        let credit = earned.saturating_sub(*claimed);
+        if credit == Amount::zero() {
+            return None;
+        }
+        *claimed = earned;
+        Some(credit)
+    }
+    
+    // Credits every holder of the stage's winning side its share of what the market would pay
+    // if it resolved that way now
+    fn settle_stage(&mut self, index: usize, outcome: bool, now: u64) {
+        let share_bps = self.stages[index].spec.share_bps;
+        let credits: Vec<(ChainId, Amount)> = self
+            .positions
+            .iter()
+            .map(|(user_chain_id, position)| {
+                (*user_chain_id, fees::apply_bps(self.full_payout_of(position, outcome), share_bps))
+            })
+            .filter(|(_, credit)| *credit > Amount::zero())
+            .collect();
+        let mut paid_out = Amount::zero();
+        for (user_chain_id, credit) in credits {
+            *self.stage_earnings.entry(user_chain_id).or_insert(Amount::zero()) += credit;
+            paid_out += credit;
+        }
+        let stage = &mut self.stages[index];
+        stage.outcome = Some(outcome);
+        stage.resolved_at = now;
+        stage.paid_out = paid_out;
+        self.audit(AuditEvent::StageResolved { stage: index as u32, outcome }, now);
+    }
+    
+    // What a conditional market watching this one is told once it settles
+    fn settlement_notice(&self) -> Option<MarketMessage> {
+        match self.status {
+            MarketStatus::Resolved(outcome) => Some(MarketMessage::ParentResolved { outcome }),
+            MarketStatus::Cancelled => Some(MarketMessage::ParentCancelled),
+            _ => None,
+        }
+    }
+    
+    // Some(true) if the market may settle, Some(false) if voided, None while the parent is open
+    fn condition_met(&self) -> Option<bool> {
+        match &self.condition {
+            None => Some(true),
+            Some(condition) => self.parent_outcome.map(|outcome| outcome == condition.activates_on),
+        }
+    }
+    
+    // Final payout: whatever share of the full payout settled stages have not already released
+    fn payout_of(&self, position: &Position, outcome: bool) -> Amount {
+        let remaining_bps = stages::FULL_PAYOUT_BPS.saturating_sub(stages::released_bps(&self.stages));
+        fees::apply_bps(self.full_payout_of(position, outcome), remaining_bps)
+    }
+    
+    fn full_payout_of(&self, position: &Position, outcome: bool) -> Amount {
+        let total = self.pool_yes + self.pool_no;
+        let winning_pool = if outcome { self.pool_yes } else { self.pool_no };
+        let shares = if outcome { position.yes_shares } else { position.no_shares };
+        if winning_pool == Amount::zero() {
+            return Amount::zero();
+        }
+        Amount::from_attos(u128::from(total) * u128::from(shares) / u128::from(winning_pool))
+    }
+    
+    // The parlay, if the message came from the chain of its `leg_index`-th leg
+    fn parlay_leg_from(&mut self, parlay_id: u64, leg_index: usize) -> Option<&mut Parlay> {
+        let origin = self.message_origin();
+        self.parlays
+            .get_mut(&parlay_id)
+            .filter(|parlay| parlay.legs.get(leg_index).is_some_and(|leg| leg.market_chain == origin))
+    }
+    
+    // Once every leg is quoted, sets the parlay's winnings aside from the reserve; a parlay
+    // the reserve can't cover is voided rather than paid short
+    fn set_aside_winnings(&mut self, parlay_id: u64) {
+        let Some(parlay) = self.parlays.get_mut(&parlay_id) else {
+            return;
+        };
+        if parlay.settled || parlay.winnings.is_some() {
+            return;
+        }
+        let Some(winnings) = parlay.quoted_winnings() else {
+            return;
+        };
+        if winnings > self.parlay_reserve {
+            self.void_parlay(parlay_id);
+            return;
+        }
+        self.parlay_reserve = self.parlay_reserve.saturating_sub(winnings);
+        self.parlay_committed += winnings;
+        parlay.winnings = Some(winnings);
+    }
+    
+    // Refunds the stake and releases whatever was set aside for the parlay
+    fn void_parlay(&mut self, parlay_id: u64) {
+        let Some(parlay) = self.parlays.get_mut(&parlay_id) else {
+            return;
+        };
+        if parlay.settled {
+            return;
+        }
+        parlay.settled = true;
+        let (user_chain_id, stake) = (parlay.user_chain_id, parlay.stake);
+        let winnings = parlay.winnings.take().unwrap_or(Amount::zero());
+        self.parlay_committed = self.parlay_committed.saturating_sub(winnings);
+        self.parlay_reserve += winnings;
+        self.pay(user_chain_id, stake);
+    }
+    
+    fn settle_parlay(&mut self, parlay_id: u64) {
+        let Some(parlay) = self.parlays.get_mut(&parlay_id) else {
+            return;
+        };
+        if parlay.settled {
+            return;
+        }
+        let (user_chain_id, stake) = (parlay.user_chain_id, parlay.stake);
+        let winnings = parlay.winnings.unwrap_or(Amount::zero());
+        match parlay.status() {
+            ParlayStatus::Pending => {}
+            ParlayStatus::Lost => {
+                parlay.settled = true;
+                self.parlay_committed = self.parlay_committed.saturating_sub(winnings);
+                self.parlay_reserve += stake + winnings;
+            }
+            ParlayStatus::Won(payout) => {
+                parlay.settled = true;
+                self.parlay_committed = self.parlay_committed.saturating_sub(winnings);
+                self.pay(user_chain_id, payout);
+                let now = system_api::current_system_time().micros();
+                self.audit(AuditEvent::Payout { user_chain_id, amount: payout }, now);
+            }
+        }
+    }
+    
+    // Voids parlays still waiting on other markets, then splits the reserve among its funders
+    fn wind_down_parlays(&mut self) {
+        let open: Vec<u64> = self
+            .parlays
+            .iter()
+            .filter(|(_, parlay)| !parlay.settled)
+            .map(|(parlay_id, _)| *parlay_id)
+            .collect();
+        for parlay_id in open {
+            self.void_parlay(parlay_id);
+        }
+        let reserve = std::mem::replace(&mut self.parlay_reserve, Amount::zero());
+        let funders = std::mem::take(&mut self.parlay_funders);
+        let funded = funders.values().fold(Amount::zero(), |sum, amount| sum + *amount);
+        // Without funders the reserve only holds lost stakes, which nobody is owed
+        if funded == Amount::zero() {
+            self.protocol_fees += reserve;
+            return;
+        }
+        for (funder, contribution) in funders {
+            let share = Amount::from_attos(u128::from(reserve) * u128::from(contribution) / u128::from(funded));
+            self.pay(funder, share);
+        }
+    }
+    
+    // Liquidity moves both pools in proportion so the odds are unchanged
+    fn add_to_pools(&mut self, amount: Amount) {
+        let total = self.pool_yes + self.pool_no;
+        let yes_part = if total == Amount::zero() {
+            Amount::from_attos(u128::from(amount) / 2)
+        } else {
+            Amount::from_attos(u128::from(amount) * u128::from(self.pool_yes) / u128::from(total))
+        };
+        self.pool_yes += yes_part;
+        self.pool_no += amount.saturating_sub(yes_part);
+    }
+    
+    fn remove_from_pools(&mut self, amount: Amount) {
+        let total = self.pool_yes + self.pool_no;
+        if total == Amount::zero() {
+            return;
+        }
+        let yes_part = Amount::from_attos(u128::from(amount) * u128::from(self.pool_yes) / u128::from(total));
+        self.pool_yes = self.pool_yes.saturating_sub(yes_part);
+        self.pool_no = self.pool_no.saturating_sub(amount.saturating_sub(yes_part));
+    }
+    
+    // Whether `order` fits under the pool and exposure caps if filled at `price`
+    fn check_caps(&self, user_chain_id: ChainId, order: &Order, price: f64) -> Result<(), RejectionReason> {
+        let exposure = self.positions.get(&user_chain_id).map_or(Amount::zero(), |position| position.cost_basis);
+        let cost = self.calculate_cost(order.amount, price);
+        self.caps.check(self.pool_yes + self.pool_no, order.amount, exposure, cost)
+    }
+    
+    // Fills one order at `price` and moves the pools; returns what the user owes, fees included
+    fn fill_order(&mut self, user_chain_id: ChainId, order: &Order, price: f64, filled_at: u64) -> Amount {
+        let buy_yes = matches!(order.side, OrderSide::BuyYes);
+        let cost = self.calculate_cost(order.amount, price);
+        // The fill counts towards the next order's tier, not its own
+        let fee = self.charge_fee(cost, order.referral_code.as_deref(), user_chain_id, filled_at);
+        self.user_volume.entry(user_chain_id).or_default().record(filled_at, cost);
+        if buy_yes {
+            self.pool_yes += order.amount;
+        } else {
+            self.pool_no += order.amount;
+        }
+        let position = self.positions.entry(user_chain_id).or_default();
+        if buy_yes {
+            position.yes_shares += order.amount;
+        } else {
+            position.no_shares += order.amount;
+        }
+        position.cost_basis += cost;
+        let subaccount = order.subaccount.clone().unwrap_or_else(|| "main".to_string());
+        let sub_position = self.subaccount_positions.entry((user_chain_id, subaccount)).or_default();
+        if buy_yes {
+            sub_position.yes_shares += order.amount;
+        } else {
+            sub_position.no_shares += order.amount;
+        }
+        self.trade_count += 1;
+        self.traded_volume += order.amount;
+        self.audit(AuditEvent::Fill { user_chain_id, buy_yes, size: order.amount, cost }, filled_at);
+        self.record_trade(TradeEvent {
+            sequence: 0,
+            user_chain_id,
+            buy_yes,
+            price,
+            size: order.amount,
+            timestamp: filled_at,
+        });
+        cost + fee
+    }
+    
+    // Reports the fills, collects payment and confirms them to the user chain
+    fn settle_fills(
+        &mut self,
+        user_chain_id: ChainId,
+        order_ids: Vec<u64>,
+        total_cost: Amount,
+        relayer_fee: Option<RelayerFee>,
+        filled_at: u64,
+        rejections: Vec<OrderRejection>,
+    ) {
+        if !order_ids.is_empty() {
+            self.last_trade_at = filled_at;
+            self.position_root = merkle::position_root(&self.positions);
+            let report = RegistryMessage::TradesExecuted {
+                user_chain_id,
+                volume: total_cost,
+                trades: order_ids.len() as u64,
+            };
+            self.send_message(self.registry_chain, report);
+        }
+        
+        // Send payment request to user's chain
+        let payment_msg = self.transfer(user_chain_id, self.chain_id(), total_cost);
+        self.send_message(user_chain_id, payment_msg);
+        
+        self.pay_relayer(user_chain_id, relayer_fee);
+        
+        // Send confirmation back
+        let confirm_msg = MarketMessage::BatchConfirmed {
+            user_chain_id,
+            order_ids,
+            total_cost,
+            rejections,
+        };
+        self.send_tracked(user_chain_id, "BatchConfirmed", confirm_msg);
+    }
+    
+    // Pay the relayer out of the user's funds, as signed for by the user
+    fn pay_relayer(&mut self, user_chain_id: ChainId, relayer_fee: Option<RelayerFee>) {
+        if let Some(fee) = relayer_fee {
+            let fee_msg = self.transfer(user_chain_id, fee.relayer, fee.amount);
+            self.send_message(user_chain_id, fee_msg);
+        }
+    }
+    
+    // Executes a call auction whose collection period is over: all orders fill at the clearing prices
+    fn uncross_if_due(&mut self, now: u64) {
+        // Only the opening auction and a halt auction in continuous trading ever uncross
+        if !matches!(self.status, MarketStatus::Created | MarketStatus::Open) {
+            return;
+        }
+        let Some(auction) = self.auction.take_if(|auction| auction.is_due(now)) else {
+            return;
+        };
+        let (yes_price, no_price) = auction.clearing_prices(self.pool_yes, self.pool_no);
+        for (user_chain_id, orders) in auction.orders {
+            let mut total_cost = Amount::zero();
+            let mut order_ids = Vec::new();
+            let mut rejections = Vec::new();
+            for order in orders {
+                let price = match order.side {
+                    OrderSide::BuyYes => yes_price,
+                    OrderSide::BuyNo => no_price,
+                };
+                // Limits are checked against the clearing price, known only now
+                if let Err(reason) = validation::check_price(&order, price)
+                    .and_then(|()| self.check_caps(user_chain_id, &order, price))
+                {
+                    rejections.push(OrderRejection { order_id: order.id, reason });
+                    continue;
+                }
+                total_cost += self.fill_order(user_chain_id, &order, price, now);
+                order_ids.push(order.id);
+            }
+            self.settle_fills(user_chain_id, order_ids, total_cost, None, now, rejections);
+        }
+        self.update_odds();
+        self.transition(MarketStatus::Open, now);
+        self.rebalance_yield();
+        // The breaker measures continuous trading from the uncrossed price
+        self.circuit_breaker
+            .roll_window(system_api::current_block_height().into(), self.yes_odds);
+    }
+    
+    // Moves to `to` if the lifecycle allows it; false leaves the status unchanged
+    fn transition(&mut self, to: MarketStatus, now: u64) -> bool {
+        if !self.status.can_transition_to(to) {
+            return false;
+        }
+        // Reservations hold prices only continuous trading quotes
+        if self.status == MarketStatus::Open {
+            let held: Vec<(ChainId, u64)> = self.reservations.keys().copied().collect();
+            self.release_reservations(held, RejectionReason::MarketClosed);
+        }
+        // An auction only uncrosses into continuous trading; anywhere else its queue is turned away
+        if to != MarketStatus::Open {
+            self.abandon_auction();
+        }
+        self.status = to;
+        self.status_history.push(StatusChange { status: to, at: now });
+        // Funds only earn yield while the market trades
+        if to != MarketStatus::Open {
+            self.withdraw_yield(now);
+        }
+        // A settled market accrues no more fees, so the treasury gets everything it is owed
+        if to.is_final() {
+            self.sweep_protocol_fees();
+        }
+        if to == MarketStatus::Cancelled {
+            self.send_message(self.registry_chain, RegistryMessage::MarketCancelled);
+        }
+        // Conditional markets learn of every way this market settles
+        if to.is_final() {
+            for watcher in std::mem::take(&mut self.resolution_watchers) {
+                if let Some(settled) = self.settlement_notice() {
+                    self.send_message(watcher, settled);
+                }
+            }
+        }
+        if to.is_final() {
+            self.timers.schedule(now + ARCHIVE_GRACE_PERIOD, TimerKind::Archive);
+        }
+        true
+    }
+    
+    fn sweep_protocol_fees(&mut self) {
+        let amount = std::mem::replace(&mut self.protocol_fees, Amount::zero());
+        if amount == Amount::zero() {
+            return;
+        }
+        self.pay(self.registry_chain, amount);
+        let report = RegistryMessage::ProtocolFeesCollected { amount, token: self.settlement_token };
+        self.send_message(self.registry_chain, report);
+    }
+    
+    // Deposits into or withdraws from the yield source until it holds its share of the pools
+    fn rebalance_yield(&mut self) {
+        let Some(source) = self.yield_config.source else {
+            return;
+        };
+        if self.status != MarketStatus::Open {
+            return;
+        }
+        let target = self.yield_config.target_deposit(self.pool_yes + self.pool_no);
+        let deposited = self.yield_position.deposited;
+        let token = self.settlement_token;
+        if target > deposited {
+            let amount = target.saturating_sub(deposited);
+            let () = self.call_application(source, &YieldSourceCall::Deposit { amount, token });
+            self.yield_position.deposited = target;
+        } else if target < deposited {
+            let amount = deposited.saturating_sub(target);
+            let returned: Amount = self.call_application(source, &YieldSourceCall::Withdraw { amount, token });
+            self.yield_position.deposited = deposited.saturating_sub(returned);
+        }
+    }
+    
+    // Withdraws whatever the source holds beyond the principal and credits it
+    fn harvest_yield(&mut self, now: u64) {
+        let Some(source) = self.yield_config.source else {
+            return;
+        };
+        let token = self.settlement_token;
+        let balance: Amount = self.call_application(source, &YieldSourceCall::Balance { token });
+        let accrued = balance.saturating_sub(self.yield_position.deposited);
+        if accrued == Amount::zero() {
+            return;
+        }
+        let earned: Amount = self.call_application(source, &YieldSourceCall::Withdraw { amount: accrued, token });
+        self.credit_yield(earned, now);
+    }
+    
+    // Harvests, then brings the whole principal back
+    fn withdraw_yield(&mut self, now: u64) {
+        let Some(source) = self.yield_config.source else {
+            return;
+        };
+        if self.yield_position.deposited == Amount::zero() {
+            return;
+        }
+        self.harvest_yield(now);
+        let amount = self.yield_position.deposited;
+        let token = self.settlement_token;
+        let returned: Amount = self.call_application(source, &YieldSourceCall::Withdraw { amount, token });
+        // A source that returns less than the principal has lost funds; the pools absorb it
+        if returned < amount {
+            self.remove_from_pools(amount.saturating_sub(returned));
+        }
+        self.yield_position.deposited = Amount::zero();
+    }
+    
+    fn credit_yield(&mut self, earned: Amount, now: u64) {
+        self.yield_position.harvested += earned;
+        let mut credited = Amount::zero();
+        if self.yield_config.beneficiary == YieldBeneficiary::Providers && self.total_lp_shares > Amount::zero() {
+            for (provider, position) in &self.lp_positions {
+                let share = Amount::from_attos(
+                    u128::from(earned) * u128::from(position.shares) / u128::from(self.total_lp_shares),
+                );
+                *self.lp_yield_earnings.entry(*provider).or_insert(Amount::zero()) += share;
+                credited += share;
+            }
+        }
+        // Rounding dust, and everything when there are no providers to credit, goes to the treasury
+        self.protocol_fees += earned.saturating_sub(credited);
+        self.audit(AuditEvent::YieldHarvested { earned }, now);
+    }
+    
+    fn start_auction(&mut self, ends_at: u64) {
+        self.auction = Some(CallAuction::new(ends_at));
+        self.timers.schedule(ends_at, TimerKind::UncrossAuction);
+    }
+    
+    // Queued orders were never paid for, so rejecting them is the whole refund
+    fn abandon_auction(&mut self) {
+        let Some(auction) = self.auction.take() else {
+            return;
+        };
+        self.timers.cancel(TimerKind::UncrossAuction);
+        for (user_chain_id, orders) in auction.orders {
+            self.reject_batch(user_chain_id, user_chain_id, &orders, RejectionReason::MarketClosed);
+        }
+    }
+    
+    // Fires every timer due at `now`, in deadline order
+    fn run_timers(&mut self, now: u64) {
+        while let Some(timer) = self.timers.pop_due(now) {
+            self.fire(timer);
+        }
+    }
+    
+    fn fire(&mut self, timer: Timer) {
+        let at = timer.at;
+        match timer.kind {
+            TimerKind::UncrossAuction => self.uncross_if_due(at),
+            // Either may find the market already past that point, e.g. resolved early
+            TimerKind::Lock => {
+                self.transition(MarketStatus::Locked, at);
+            }
+            TimerKind::Resolve => {
+                self.transition(MarketStatus::Resolving, at);
+            }
+            // Fallback deadlines only matter while the market still waits for its oracle
+            TimerKind::EscalateOracle if self.status == MarketStatus::Resolving => {
+                self.oracle_escalated = true;
+                self.audit(AuditEvent::OracleEscalated { member_count: self.fallback.committee_size }, at);
+            }
+            TimerKind::VoidUnresolved if self.status == MarketStatus::Resolving => {
+                self.transition(MarketStatus::Cancelled, at);
+                self.audit(AuditEvent::Voided, at);
+            }
+            TimerKind::EscalateOracle | TimerKind::VoidUnresolved => {}
+            TimerKind::Archive => self.retire(at),
+            TimerKind::CompleteKeyRotation => {
+                if let Some(rotation) = self.oracle_key_rotation.take() {
+                    self.oracle_type = OracleType::FastTee { public_key: rotation.new_key };
+                }
+            }
+            TimerKind::RetryOutbox => {
+                for outbox_id in self.outbox.due(at) {
+                    self.resend(outbox_id, at);
+                }
+            }
+            TimerKind::AbortSaga => {
+                let overdue: Vec<u64> = self
+                    .sagas
+                    .iter()
+                    .filter(|(_, saga)| saga.status == SagaStatus::Reserving && saga.created_at + SAGA_TIMEOUT <= at)
+                    .map(|(saga_id, _)| *saga_id)
+                    .collect();
+                for saga_id in overdue {
+                    self.abort_saga(saga_id);
+                }
+            }
+            TimerKind::ExpireReservations => {
+                let lapsed: Vec<(ChainId, u64)> = self
+                    .reservations
+                    .iter()
+                    .filter(|(_, reservation)| reservation.expires_at <= at)
+                    .map(|(key, _)| *key)
+                    .collect();
+                self.release_reservations(lapsed, RejectionReason::ReservationExpired);
+            }
+        }
+    }
+    
+    // Drops reservations and tells their coordinators why, so the sagas abort rather than commit
+    fn release_reservations(&mut self, keys: Vec<(ChainId, u64)>, reason: RejectionReason) {
+        for (user_chain_id, saga_id) in keys {
+            let Some(reservation) = self.reservations.remove(&(user_chain_id, saga_id)) else {
+                continue;
+            };
+            let orders: Vec<Order> = reservation.orders.into_iter().map(|(order, _)| order).collect();
+            let rejections = validation::reject_all(&orders, reason.clone());
+            self.send_message(user_chain_id, MarketMessage::ReservationRejected { saga_id, rejections });
+        }
+    }
+    
+    // Reservations are capped one at a time, so together they can overshoot; checks the
+    // whole reservation against the caps as they stand now, each order after the ones before it
+    fn check_reserved_caps(&self, user_chain_id: ChainId, orders: &[(Order, f64)]) -> Result<(), OrderRejection> {
+        let mut pool = self.pool_yes + self.pool_no;
+        let mut exposure = self.positions.get(&user_chain_id).map_or(Amount::zero(), |position| position.cost_basis);
+        for (order, price) in orders {
+            let cost = self.calculate_cost(order.amount, *price);
+            self.caps
+                .check(pool, order.amount, exposure, cost)
+                .map_err(|reason| OrderRejection { order_id: order.id, reason })?;
+            pool += order.amount;
+            exposure += cost;
+        }
+        Ok(())
+    }
+    
+    // Releases every leg that hasn't refused; legs still voting get the abort after their reserve
+    fn abort_saga(&mut self, saga_id: u64) {
+        let Some(saga) = self.sagas.get_mut(&saga_id) else {
+            return;
+        };
+        saga.status = SagaStatus::Aborted;
+        let holding: Vec<ChainId> = saga
+            .legs
+            .iter()
+            .zip(&saga.votes)
+            .filter(|(_, vote)| **vote != LegVote::Refused)
+            .map(|(leg, _)| leg.market_chain)
+            .collect();
+        for market_chain in holding {
+            self.send_message(market_chain, MarketMessage::AbortReservation { saga_id });
+        }
+    }
+    
+    // A charge on `from` in the settlement token, recorded in the ledger under a fresh ID; sent
+    // to `from`, whose market app pays it if the chain approved it
+    fn transfer(&mut self, from: ChainId, to: ChainId, amount: Amount) -> MarketMessage {
+        let token = self.settlement_token;
+        let transfer_id = self.issue_transfer(from, to, amount);
+        MarketMessage::Transfer { from, to, amount, token, transfer_id }
+    }
+    
+    fn issue_transfer(&mut self, from: ChainId, to: ChainId, amount: Amount) -> TransferId {
+        let issued_at = system_api::current_system_time().micros();
+        let transfer = IssuedTransfer { from, to, amount, token: self.settlement_token, issued_at };
+        self.transfers.issue(self.chain_id(), transfer)
+    }
+    
+    // Pays `to` out of this chain's balance here and now, then sends it the transfer as a receipt
+    fn pay(&mut self, to: ChainId, amount: Amount) -> TransferId {
+        let (from, token) = (self.chain_id(), self.settlement_token);
+        let transfer_id = self.issue_transfer(from, to, amount);
+        self.move_funds(to, amount, token);
+        let receipt = MarketMessage::Transfer { from, to, amount, token, transfer_id };
+        self.send_tracked(to, "Transfer", receipt);
+        transfer_id
+    }
+    
+    fn move_funds(&mut self, to: ChainId, amount: Amount, token: Option<ApplicationId>) {
+        match token {
+            None => system_api::transfer(to, amount),
+            Some(token) => self.call_application(token, &TokenCall::Transfer { to, amount }),
+        }
+    }
+    
+    // Pays a user chain and tells its user app, both under the same transfer ID
+    fn pay_out(&mut self, user_chain_id: ChainId, amount: Amount) {
+        let transfer_id = self.pay(user_chain_id, amount);
+        self.send_message(user_chain_id, UserMessage::PayoutCredited { amount, transfer_id });
+    }
+    
+    // Sends a message the recipient must acknowledge, resending it until it does
+    fn send_tracked(&mut self, to: ChainId, kind: &str, message: MarketMessage) {
+        let now = system_api::current_system_time().micros();
+        let payload = bcs::to_bytes(&message).expect("market messages are serializable");
+        let outbox_id = self.outbox.push(to, kind, payload.clone(), now);
+        if let Some(at) = self.outbox.entries[&outbox_id].next_retry_at() {
+            self.timers.schedule(at, TimerKind::RetryOutbox);
+        }
+        self.send_message(to, MarketMessage::Tracked { outbox_id, payload });
+    }
+    
+    fn resend(&mut self, outbox_id: u64, now: u64) {
+        let Some(entry) = self.outbox.record_attempt(outbox_id, now) else {
+            return;
+        };
+        let (to, payload, retry_at) = (entry.destination, entry.payload.clone(), entry.next_retry_at());
+        if let Some(at) = retry_at {
+            self.timers.schedule(at, TimerKind::RetryOutbox);
+        }
+        self.send_message(to, MarketMessage::Tracked { outbox_id, payload });
+    }
+    
+    // Commits to the final state, pays out whatever is still owed and closes the chain
+    fn retire(&mut self, now: u64) {
+        // Committed before the final payouts, so it matches an export taken during the grace period
+        let snapshot_hash = archive::snapshot_hash(
+            &self.market_id,
+            &self.position_root,
+            &self.audit_log.head,
+            self.audit_log.length,
+        );
+        self.wind_down_parlays();
+        self.pay_out_unclaimed(now);
+        self.sweep_protocol_fees();
+        self.archive = Some(ArchiveRecord { archived_at: now, snapshot_hash });
+        self.send_message(self.registry_chain, RegistryMessage::MarketArchived { snapshot_hash });
+        system_api::close_chain();
+    }
+    
+    // Whatever nobody claimed during the grace period is sent to whoever it is owed to rather
+    // than closing with the chain, exactly as `Claim` and the earnings claims would have paid it.
+    // Only the oracle's cut, which nobody claims, goes to the treasury.
+    fn pay_out_unclaimed(&mut self, now: u64) {
+        self.protocol_fees += std::mem::replace(&mut self.oracle_fees, Amount::zero());
+        let earnings: Vec<(ChainId, Amount)> = std::mem::take(&mut self.referral_earnings)
+            .into_iter()
+            .chain(std::mem::take(&mut self.lp_yield_earnings))
+            .collect();
+        for (earner, earned) in earnings {
+            self.pay(earner, earned);
+        }
+        let voided = self.status == MarketStatus::Cancelled || self.condition_met() == Some(false);
+        let owners: Vec<ChainId> = self.positions.keys().copied().collect();
+        for owner in owners {
+            let owed = if voided {
+                self.take_refund(owner)
+            } else {
+                let credit = self.take_stage_credit(owner);
+                let payout = match self.status {
+                    MarketStatus::Resolved(outcome) => self.take_payout(owner, outcome),
+                    _ => None,
+                };
+                match (credit, payout) {
+                    (None, None) => None,
+                    (credit, payout) => Some(credit.unwrap_or(Amount::zero()) + payout.unwrap_or(Amount::zero())),
+                }
+            };
+            if let Some(owed) = owed.filter(|owed| *owed > Amount::zero()) {
+                self.pay_out(owner, owed);
+                self.audit(AuditEvent::Payout { user_chain_id: owner, amount: owed }, now);
+            }
+        }
+    }
+    
+    fn accepts_oracle(&self, oracle_type: &OracleType) -> bool {
+        // While a key rotation overlaps, either key may sign
+        if let (OracleType::FastTee { public_key }, OracleType::FastTee { public_key: current }) =
+            (oracle_type, &self.oracle_type)
+        {
+            let rotating = self.oracle_key_rotation.as_ref().is_some_and(|rotation| rotation.new_key == *public_key);
+            return public_key == current || rotating;
+        }
+        let committee = matches!(oracle_type, OracleType::Committee { member_count } if *member_count == self.fallback.committee_size);
+        std::mem::discriminant(oracle_type) == std::mem::discriminant(&self.oracle_type)
+            || (self.oracle_escalated && committee)
+    }
+    
+    // Checks a report from an oracle `accepts_oracle` already admitted. Only FastTee reports
+    // carry a key to check; the market holds no committee keys, so nothing can vouch for a
+    // committee or hybrid report and they are refused.
+    fn verify_oracle_signature(
+        &self,
+        oracle_type: &OracleType,
+        message: &str,
+        signature: &[u8],
+    ) -> Result<(), RejectionReason> {
+        match oracle_type {
+            OracleType::FastTee { public_key }
+                if oracle_keys::verify_signature(public_key, message.as_bytes(), signature) => Ok(()),
+            _ => Err(RejectionReason::Unauthorized),
+        }
+    }
+    
+    fn audit(&mut self, event: AuditEvent, timestamp: u64) {
+        let record = self.audit_log.append(event, timestamp);
+        self.emit_event(AUDIT_STREAM, &record);
+    }
+    
+    // Sequences the fill, emits it and keeps it in the bounded recent-trades window
+    fn record_trade(&mut self, mut trade: TradeEvent) {
+        self.next_trade_sequence += 1;
+        trade.sequence = self.next_trade_sequence;
+        self.emit_event(TRADE_STREAM, &trade);
+        if self.recent_trades.len() == MAX_RECENT_TRADES {
+            self.recent_trades.pop_front();
         }
+        self.recent_trades.push_back(trade);
     }
     
     fn update_odds(&mut self) {
@@ -0,0 +1,55 @@
+// Market lifecycle: Created -> Open -> Locked -> Resolving -> Resolved, with Cancelled reachable until resolution
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MarketStatus {
+    // Instantiated; only an opening call auction takes orders
+    #[default]
+    Created,
+    // Continuous trading
+    Open,
+    // Trading closed ahead of the resolution time
+    Locked,
+    // Resolution time reached; waiting for the oracle
+    Resolving,
+    Resolved(bool),
+    // Voided; positions are refunded
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatusChange {
+    pub status: MarketStatus,
+    // Micros timestamp of the transition
+    pub at: u64,
+}
+
+impl MarketStatus {
+    pub fn can_transition_to(self, next: MarketStatus) -> bool {
+        use MarketStatus::*;
+        match (self, next) {
+            (Created, Open) => true,
+            // An oracle may report before the scheduled time, so Open and Locked may skip ahead
+            (Open, Locked | Resolving) => true,
+            (Locked, Resolving) => true,
+            (Resolving, Resolved(_)) => true,
+            (Created | Open | Locked | Resolving, Cancelled) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_final(self) -> bool {
+        matches!(self, MarketStatus::Resolved(_) | MarketStatus::Cancelled)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MarketStatus::Created => "Created",
+            MarketStatus::Open => "Open",
+            MarketStatus::Locked => "Locked",
+            MarketStatus::Resolving => "Resolving",
+            MarketStatus::Resolved(_) => "Resolved",
+            MarketStatus::Cancelled => "Cancelled",
+        }
+    }
+}
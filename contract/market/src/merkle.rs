@@ -0,0 +1,74 @@
+// Merkle commitment over positions, so a holder can prove their shares from the certified root alone
+use crate::Position;
+use linera_sdk::base::ChainId;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+// Distinct prefixes keep a leaf from being passed off as an inner node
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+pub fn leaf_hash(owner: ChainId, position: &Position) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(bcs::to_bytes(&(owner, position)).expect("position is serializable"));
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+// One level up; an unpaired last node is carried up unchanged
+fn parent_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn leaves(positions: &BTreeMap<ChainId, Position>) -> Vec<[u8; 32]> {
+    positions.iter().map(|(owner, position)| leaf_hash(*owner, position)).collect()
+}
+
+// Leaves are in owner order; the empty tree has the all-zero root
+pub fn position_root(positions: &BTreeMap<ChainId, Position>) -> [u8; 32] {
+    let mut level = leaves(positions);
+    if level.is_empty() {
+        return [0; 32];
+    }
+    while level.len() > 1 {
+        level = parent_level(&level);
+    }
+    level[0]
+}
+
+pub struct PositionProof {
+    pub index: usize,
+    pub leaf_count: usize,
+    // Bottom-up; levels where the node was carried up contribute nothing
+    pub siblings: Vec<[u8; 32]>,
+}
+
+pub fn position_proof(positions: &BTreeMap<ChainId, Position>, owner: ChainId) -> Option<PositionProof> {
+    let index = positions.keys().position(|key| *key == owner)?;
+    let mut level = leaves(positions);
+    let mut node = index;
+    let mut siblings = Vec::new();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(node ^ 1) {
+            siblings.push(*sibling);
+        }
+        level = parent_level(&level);
+        node /= 2;
+    }
+    Some(PositionProof { index, leaf_count: positions.len(), siblings })
+}
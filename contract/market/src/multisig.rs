@@ -0,0 +1,140 @@
+// M-of-N co-signing for team-managed user chains. What a chain trades without
+// co-signatures is summed over a rolling window, so splitting a large trade into
+// batches under the threshold doesn't skip the quorum. Once installed, a policy
+// can only be replaced or removed with its own quorum.
+use crate::signing::{self, OrderSignature};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+// Window single-signer notional is summed over, in hourly buckets
+pub const MULTISIG_WINDOW: u64 = 24 * 60 * 60 * 1_000_000;
+const BUCKET: u64 = 60 * 60 * 1_000_000;
+
+// Separate domain, so a policy change signature can't pass as a batch signature or the reverse
+const POLICY_DOMAIN: &[u8] = b"oddsstream-multisig-policy-v1";
+
+// M-of-N policy: past `threshold_amount` of notional within the window, batches need `required` distinct signers
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MultisigPolicy {
+    pub signers: Vec<Vec<u8>>,
+    pub required: u32,
+    pub threshold_amount: Amount,
+}
+
+impl MultisigPolicy {
+    // A quorum the signers can actually reach. An unreachable one would lock the chain out
+    // of large batches for good, since replacing the policy takes that same quorum.
+    pub fn is_valid(&self) -> bool {
+        let distinct = self.signers.iter().enumerate().all(|(index, key)| !self.signers[..index].contains(key));
+        distinct && self.required >= 1 && self.required as usize <= self.signers.len()
+    }
+
+    // Distinct policy signers with a valid signature over `digest`
+    pub fn approvals<'a>(&self, digest: &[u8; 32], signatures: impl Iterator<Item = &'a OrderSignature>) -> u32 {
+        let mut approved: Vec<&Vec<u8>> = Vec::new();
+        for sig in signatures {
+            if self.signers.contains(&sig.public_key)
+                && !approved.contains(&&sig.public_key)
+                && signing::verify_order_signature(digest, sig)
+            {
+                approved.push(&sig.public_key);
+            }
+        }
+        approved.len() as u32
+    }
+}
+
+// Notional a user chain traded without a quorum, by hour
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SingleSignerWindow {
+    hourly: BTreeMap<u64, Amount>,
+}
+
+impl SingleSignerWindow {
+    pub fn record(&mut self, now: u64, amount: Amount) {
+        *self.hourly.entry(now / BUCKET).or_insert(Amount::zero()) += amount;
+        // Buckets past the window can't count again
+        let oldest = (now.saturating_sub(MULTISIG_WINDOW)) / BUCKET;
+        self.hourly = self.hourly.split_off(&oldest);
+    }
+
+    pub fn trailing(&self, now: u64) -> Amount {
+        let oldest = (now.saturating_sub(MULTISIG_WINDOW)) / BUCKET;
+        self.hourly
+            .range(oldest..)
+            .fold(Amount::zero(), |sum, (_, amount)| sum + *amount)
+    }
+}
+
+#[derive(Serialize)]
+struct PolicyPayload<'a> {
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    policy: Option<&'a MultisigPolicy>,
+}
+
+// What the current policy's signers sign to replace it with `policy`, or remove it with `None`
+pub fn policy_digest(
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    policy: Option<&MultisigPolicy>,
+) -> [u8; 32] {
+    let payload = PolicyPayload { user_chain_id, market_chain_id, nonce, policy };
+    let mut hasher = Sha256::new();
+    hasher.update(POLICY_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("policy payload is serializable"));
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(byte: u8) -> ChainId {
+        ChainId::from([byte; 32])
+    }
+
+    fn policy(signers: Vec<Vec<u8>>, required: u32) -> MultisigPolicy {
+        MultisigPolicy { signers, required, threshold_amount: Amount::from_tokens(100) }
+    }
+
+    #[test]
+    fn test_policy_quorum_must_be_reachable() {
+        assert!(policy(vec![vec![1], vec![2]], 2).is_valid());
+        assert!(!policy(vec![vec![1], vec![2]], 3).is_valid());
+        assert!(!policy(vec![vec![1], vec![2]], 0).is_valid());
+        assert!(!policy(vec![vec![1], vec![1]], 2).is_valid());
+        assert!(!policy(Vec::new(), 1).is_valid());
+    }
+
+    #[test]
+    fn test_single_signer_window_rolls_over_by_bucket() {
+        let mut window = SingleSignerWindow::default();
+        window.record(0, Amount::from_tokens(10));
+        window.record(BUCKET + 1, Amount::from_tokens(5));
+        assert_eq!(window.trailing(BUCKET + 1), Amount::from_tokens(15));
+        // The first bucket leaves the window a full window after it opened
+        assert_eq!(window.trailing(MULTISIG_WINDOW + BUCKET - 1), Amount::from_tokens(15));
+        assert_eq!(window.trailing(MULTISIG_WINDOW + BUCKET), Amount::from_tokens(5));
+        // Recording prunes buckets that can no longer count
+        window.record(MULTISIG_WINDOW + BUCKET, Amount::from_tokens(1));
+        assert_eq!(window.hourly.len(), 2);
+        assert_eq!(window.trailing(MULTISIG_WINDOW + BUCKET), Amount::from_tokens(6));
+    }
+
+    #[test]
+    fn test_policy_digest_binds_every_field() {
+        let current = policy(vec![vec![1], vec![2]], 2);
+        let digest = policy_digest(chain(1), chain(2), 7, Some(&current));
+        assert_eq!(digest, policy_digest(chain(1), chain(2), 7, Some(&current)));
+        assert_ne!(digest, policy_digest(chain(3), chain(2), 7, Some(&current)));
+        assert_ne!(digest, policy_digest(chain(1), chain(3), 7, Some(&current)));
+        assert_ne!(digest, policy_digest(chain(1), chain(2), 8, Some(&current)));
+        assert_ne!(digest, policy_digest(chain(1), chain(2), 7, None));
+        assert_ne!(digest, policy_digest(chain(1), chain(2), 7, Some(&policy(vec![vec![1], vec![2]], 1))));
+    }
+}
@@ -0,0 +1,103 @@
+// Rotation of a FastTee oracle's signing key without pausing the market. A new key is
+// announced with an overlap window: resolutions signed by either key are accepted until
+// it ends, then only by the new one. The announcement is signed by the current key, or by
+// the guardian key governance configured at creation when the current key can't be trusted.
+// Also checks the oracle's resolution and stage reports against whichever key is accepted.
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Domain separator so a rotation signature can't be replayed as a resolution or the reverse
+const ROTATION_DOMAIN: &[u8] = b"oddsstream-key-rotation-v1";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyRotation {
+    pub new_key: String,
+    pub announced_at: u64,
+    // Micros timestamp the old key stops being accepted at
+    pub completes_at: u64,
+}
+
+// Exactly what the old key or the guardian signs. Not bound to a market chain: one
+// announcement is relayed to every market the oracle serves.
+#[derive(Serialize)]
+struct RotationPayload<'a> {
+    old_key: &'a str,
+    new_key: &'a str,
+    announced_at: u64,
+    overlap: u64,
+}
+
+pub fn rotation_digest(old_key: &str, new_key: &str, announced_at: u64, overlap: u64) -> [u8; 32] {
+    let payload = RotationPayload { old_key, new_key, announced_at, overlap };
+    let mut hasher = Sha256::new();
+    hasher.update(ROTATION_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("rotation payload is serializable"));
+    hasher.finalize().into()
+}
+
+// The enclave signs these texts themselves rather than a digest. A stage report names its
+// stage, so it can't be replayed as the final resolution or as another stage.
+pub fn resolution_message(market_id: &str, outcome: bool, timestamp: u64) -> String {
+    format!("{}{}{}", market_id, outcome, timestamp)
+}
+
+pub fn stage_message(market_id: &str, stage: u32, outcome: bool, timestamp: u64) -> String {
+    format!("{}#stage{}:{}{}", market_id, stage, outcome, timestamp)
+}
+
+// FastTee keys are configured hex-encoded
+pub fn verify_signature(public_key: &str, message: &[u8], signature: &[u8]) -> bool {
+    let Ok(key_bytes) = hex::decode(public_key) else {
+        return false;
+    };
+    let Ok(key_bytes) = <[u8; 32]>::try_from(key_bytes.as_slice()) else {
+        return false;
+    };
+    let Ok(key) = VerifyingKey::from_bytes(&key_bytes) else {
+        return false;
+    };
+    let Ok(sig) = Signature::from_slice(signature) else {
+        return false;
+    };
+    key.verify(message, &sig).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn oracle_key(seed: u8) -> (SigningKey, String) {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let public_key = hex::encode(key.verifying_key().to_bytes());
+        (key, public_key)
+    }
+
+    #[test]
+    fn test_valid_stage_report_verifies() {
+        let (key, public_key) = oracle_key(1);
+        let message = stage_message("market-1", 2, true, 1_000);
+        let signature = key.sign(message.as_bytes()).to_bytes();
+        assert!(verify_signature(&public_key, message.as_bytes(), &signature));
+    }
+
+    #[test]
+    fn test_forged_stage_report_is_rejected() {
+        let (oracle, public_key) = oracle_key(1);
+        let (forger, _) = oracle_key(2);
+        let message = stage_message("market-1", 2, true, 1_000);
+        let forged = forger.sign(message.as_bytes()).to_bytes();
+        assert!(!verify_signature(&public_key, message.as_bytes(), &forged));
+        // A genuine signature doesn't carry over to another outcome, stage or the final resolution
+        let genuine = oracle.sign(message.as_bytes()).to_bytes();
+        for other in [
+            stage_message("market-1", 2, false, 1_000),
+            stage_message("market-1", 3, true, 1_000),
+            resolution_message("market-1", true, 1_000),
+        ] {
+            assert!(!verify_signature(&public_key, other.as_bytes(), &genuine));
+        }
+        assert!(!verify_signature("not hex", message.as_bytes(), &genuine));
+    }
+}
@@ -0,0 +1,88 @@
+// Delivery tracking for messages a user chain must not miss: confirmations and payouts.
+// Each is sent wrapped with an outbox ID, and the market app on the receiving chain
+// acknowledges it before handling it. Unacknowledged messages are resent with doubling
+// delays; after `MAX_SEND_ATTEMPTS` they stay in the outbox as stuck until someone asks
+// for a manual retry. A retry can deliver a message twice, so handlers must tolerate that.
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const MAX_SEND_ATTEMPTS: u32 = 5;
+// Wait before the first retry; doubles after every attempt
+pub const RETRY_BACKOFF: u64 = 10 * 60 * 1_000_000;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OutboxEntry {
+    pub destination: ChainId,
+    // Variant name, for the stuck-message query
+    pub kind: String,
+    // BCS of the wrapped message, resent as is
+    pub payload: Vec<u8>,
+    pub attempts: u32,
+    pub first_sent_at: u64,
+    pub last_sent_at: u64,
+}
+
+impl OutboxEntry {
+    // `None` once automatic retries are used up
+    pub fn next_retry_at(&self) -> Option<u64> {
+        (self.attempts < MAX_SEND_ATTEMPTS)
+            .then(|| self.last_sent_at + (RETRY_BACKOFF << (self.attempts - 1).min(16)))
+    }
+
+    pub fn is_stuck(&self) -> bool {
+        self.attempts >= MAX_SEND_ATTEMPTS
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Outbox {
+    pub entries: BTreeMap<u64, OutboxEntry>,
+    pub next_id: u64,
+}
+
+impl Outbox {
+    // Record a first send; the ID to wrap the message with
+    pub fn push(&mut self, destination: ChainId, kind: &str, payload: Vec<u8>, now: u64) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.insert(
+            id,
+            OutboxEntry {
+                destination,
+                kind: kind.to_string(),
+                payload,
+                attempts: 1,
+                first_sent_at: now,
+                last_sent_at: now,
+            },
+        );
+        id
+    }
+
+    // Only the chain the message went to can acknowledge it; whether it was pending
+    pub fn acknowledge(&mut self, from: ChainId, id: u64) -> bool {
+        if self.entries.get(&id).is_some_and(|entry| entry.destination == from) {
+            self.entries.remove(&id);
+            return true;
+        }
+        false
+    }
+
+    // Entries whose automatic retry is due at `now`
+    pub fn due(&self, now: u64) -> Vec<u64> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.next_retry_at().is_some_and(|at| at <= now))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    // Count a resend; the entry to send again
+    pub fn record_attempt(&mut self, id: u64, now: u64) -> Option<&OutboxEntry> {
+        let entry = self.entries.get_mut(&id)?;
+        entry.attempts += 1;
+        entry.last_sent_at = now;
+        Some(entry)
+    }
+}
@@ -0,0 +1,95 @@
+// Parlays: one stake across several markets, paid only if every leg wins.
+// The market receiving the order coordinates it; the other legs' markets
+// quote their odds and report back when they resolve.
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+
+// Legs beyond this are rejected; each one costs two cross-chain messages
+pub const MAX_PARLAY_LEGS: usize = 8;
+// Parlay legs a market will watch at once, and how many of them one coordinator may hold;
+// every watcher is a message sent when the market resolves
+pub const MAX_PARLAY_WATCHERS: usize = 512;
+pub const MAX_WATCHERS_PER_COORDINATOR: usize = 64;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParlayLeg {
+    pub market_id: String,
+    pub market_chain: ChainId,
+    // true = YES
+    pub outcome: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Parlay {
+    pub user_chain_id: ChainId,
+    pub stake: Amount,
+    pub legs: Vec<ParlayLeg>,
+    // Implied probability of each leg's outcome when it was quoted
+    pub leg_odds: Vec<Option<f64>>,
+    pub leg_results: Vec<Option<bool>>,
+    // Winnings set aside from the reserve once every leg is quoted
+    pub winnings: Option<Amount>,
+    pub settled: bool,
+}
+
+// A leg market remembers which coordinator to notify when it resolves
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParlayWatcher {
+    pub coordinator: ChainId,
+    pub parlay_id: u64,
+    pub leg_index: usize,
+    pub outcome: bool,
+}
+
+pub enum ParlayStatus {
+    Pending,
+    Lost,
+    Won(Amount),
+}
+
+impl Parlay {
+    pub fn new(user_chain_id: ChainId, stake: Amount, legs: Vec<ParlayLeg>) -> Self {
+        let count = legs.len();
+        Self {
+            user_chain_id,
+            stake,
+            legs,
+            leg_odds: vec![None; count],
+            leg_results: vec![None; count],
+            winnings: None,
+            settled: false,
+        }
+    }
+
+    // What the parlay pays beyond its stake if every leg wins, once every leg is quoted
+    pub fn quoted_winnings(&self) -> Option<Amount> {
+        let odds: Option<Vec<f64>> = self.leg_odds.iter().copied().collect();
+        odds.map(|odds| scale(self.stake, payout_multiplier(&odds)).saturating_sub(self.stake))
+    }
+
+    // A single losing leg settles the parlay; a win needs every leg won and its winnings set aside
+    pub fn status(&self) -> ParlayStatus {
+        if self.leg_results.iter().any(|result| *result == Some(false)) {
+            return ParlayStatus::Lost;
+        }
+        if self.leg_results.iter().any(Option::is_none) {
+            return ParlayStatus::Pending;
+        }
+        match self.winnings {
+            Some(winnings) => ParlayStatus::Won(self.stake + winnings),
+            None => ParlayStatus::Pending,
+        }
+    }
+}
+
+// Product of each leg's decimal odds (1 / implied probability)
+pub fn payout_multiplier(leg_odds: &[f64]) -> f64 {
+    leg_odds
+        .iter()
+        .map(|probability| if *probability > 0.0 { 1.0 / probability } else { 1.0 })
+        .product()
+}
+
+fn scale(amount: Amount, factor: f64) -> Amount {
+    Amount::from_attos((u128::from(amount) as f64 * factor) as u128)
+}
@@ -0,0 +1,79 @@
+// Atomic batches across markets, as a two-phase commit. The market app on the user chain
+// coordinates: it asks every leg's market to reserve its orders, commits all of them once
+// every leg has reserved, and aborts the rest as soon as one refuses or the saga times out.
+// A reservation locks the quoted prices, so a market expires it on its own after
+// `RESERVATION_TTL`; the coordinator gives up well before that so its commits arrive in time.
+use crate::validation::OrderRejection;
+use crate::Order;
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+
+// Legs beyond this are refused; each one costs three cross-chain messages
+pub const MAX_SAGA_LEGS: usize = 8;
+// The coordinator aborts a saga still missing votes after this long
+pub const SAGA_TIMEOUT: u64 = 30 * 1_000_000;
+// A market drops a reservation it has heard nothing about after this long
+pub const RESERVATION_TTL: u64 = 2 * SAGA_TIMEOUT;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AtomicLeg {
+    pub market_chain: ChainId,
+    pub orders: Vec<Order>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegVote {
+    Pending,
+    Reserved { total_cost: Amount },
+    Refused,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SagaStatus {
+    Reserving,
+    Committed,
+    Aborted,
+}
+
+// Coordinator side, kept on the user chain
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Saga {
+    pub legs: Vec<AtomicLeg>,
+    pub votes: Vec<LegVote>,
+    pub status: SagaStatus,
+    pub created_at: u64,
+    // Why the saga was aborted, as reported by the refusing market
+    pub rejections: Vec<OrderRejection>,
+}
+
+impl Saga {
+    pub fn new(legs: Vec<AtomicLeg>, now: u64) -> Self {
+        let votes = vec![LegVote::Pending; legs.len()];
+        Self { legs, votes, status: SagaStatus::Reserving, created_at: now, rejections: Vec::new() }
+    }
+
+    pub fn leg_of(&self, market_chain: ChainId) -> Option<usize> {
+        self.legs.iter().position(|leg| leg.market_chain == market_chain)
+    }
+
+    pub fn all_reserved(&self) -> bool {
+        self.votes.iter().all(|vote| matches!(vote, LegVote::Reserved { .. }))
+    }
+}
+
+// Market side: orders held at the prices quoted when they were reserved
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Reservation {
+    pub orders: Vec<(Order, f64)>,
+    pub expires_at: u64,
+}
+
+// Legs must name distinct markets, and each must carry at least one order
+pub fn is_valid_plan(legs: &[AtomicLeg]) -> bool {
+    let mut chains: Vec<ChainId> = legs.iter().map(|leg| leg.market_chain).collect();
+    chains.sort();
+    chains.dedup();
+    (1..=MAX_SAGA_LEGS).contains(&legs.len())
+        && chains.len() == legs.len()
+        && legs.iter().all(|leg| !leg.orders.is_empty())
+}
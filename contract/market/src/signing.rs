@@ -0,0 +1,89 @@
+// Per-order signatures so the market can verify who authorized a batch
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::Order;
+
+// Domain separator so an order signature can't be replayed as any other payload
+const ORDER_DOMAIN: &[u8] = b"oddsstream-order-v1";
+
+// Fee the user agrees to pay the chain that relays a signed batch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RelayerFee {
+    pub relayer: ChainId,
+    pub amount: Amount,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OrderSignature {
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+// Exactly the fields the user signs; the market chain is included so a batch
+// signed for one market can't be replayed against another
+#[derive(Serialize)]
+struct OrderPayload<'a> {
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    orders: &'a [Order],
+    relayer_fee: Option<&'a RelayerFee>,
+}
+
+pub fn order_digest(
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    orders: &[Order],
+    relayer_fee: Option<&RelayerFee>,
+) -> [u8; 32] {
+    let payload = OrderPayload { user_chain_id, market_chain_id, nonce, orders, relayer_fee };
+    let mut hasher = Sha256::new();
+    hasher.update(ORDER_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("order payload is serializable"));
+    hasher.finalize().into()
+}
+
+// Separate domain, so an amendment signature can't pass as a batch signature or the reverse
+const AMEND_DOMAIN: &[u8] = b"oddsstream-amend-v1";
+
+#[derive(Serialize)]
+struct AmendPayload {
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    order_id: u64,
+    amount: Amount,
+    max_price: Option<Amount>,
+}
+
+pub fn amend_digest(
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    order_id: u64,
+    amount: Amount,
+    max_price: Option<Amount>,
+) -> [u8; 32] {
+    let payload = AmendPayload { user_chain_id, market_chain_id, nonce, order_id, amount, max_price };
+    let mut hasher = Sha256::new();
+    hasher.update(AMEND_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("amend payload is serializable"));
+    hasher.finalize().into()
+}
+
+pub fn verify_order_signature(digest: &[u8; 32], signature: &OrderSignature) -> bool {
+    let Ok(key_bytes) = <[u8; 32]>::try_from(signature.public_key.as_slice()) else {
+        return false;
+    };
+    let Ok(key) = VerifyingKey::from_bytes(&key_bytes) else {
+        return false;
+    };
+    let Ok(sig) = Signature::from_slice(&signature.signature) else {
+        return false;
+    };
+    key.verify(digest, &sig).is_ok()
+}
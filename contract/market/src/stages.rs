@@ -0,0 +1,43 @@
+// Staged resolution: long-running markets release part of every winning payout as each stage settles
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+
+// A stage schedule divides up this much of the payout
+pub const FULL_PAYOUT_BPS: u32 = 10_000;
+
+// Stages beyond this count are rejected to keep market state bounded
+pub const MAX_STAGES: usize = 32;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StageSpec {
+    pub name: String,
+    // Share of the payout released to that stage's winning side, in basis points
+    pub share_bps: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Stage {
+    pub spec: StageSpec,
+    // `None` until the oracle reports the stage
+    pub outcome: Option<bool>,
+    pub resolved_at: u64,
+    // Total credited to winners of this stage
+    pub paid_out: Amount,
+}
+
+impl Stage {
+    pub fn new(spec: StageSpec) -> Self {
+        Self { spec, outcome: None, resolved_at: 0, paid_out: Amount::zero() }
+    }
+}
+
+// Stages can release at most the whole payout between them; the rest pays at final resolution
+pub fn is_valid_schedule(specs: &[StageSpec]) -> bool {
+    specs.len() <= MAX_STAGES
+        && specs.iter().map(|spec| u64::from(spec.share_bps)).sum::<u64>() <= u64::from(FULL_PAYOUT_BPS)
+}
+
+// Basis points of the payout already released by settled stages
+pub fn released_bps(stages: &[Stage]) -> u32 {
+    stages.iter().filter(|stage| stage.outcome.is_some()).map(|stage| stage.spec.share_bps).sum()
+}
@@ -0,0 +1,78 @@
+// Time-based triggers. Contracts only run when a block carries a message for them, so
+// every message first fires the timers that block time has passed: earliest deadline
+// first, and timers sharing a deadline in the order they were scheduled. Each fires as
+// of its own deadline, not the block that noticed it, so the same blocks always
+// produce the same transitions with the same timestamps.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerKind {
+    // A call auction's collection period ends
+    UncrossAuction,
+    // Trading stops ahead of the resolution time
+    Lock,
+    // The resolution time; the market waits for the oracle from here
+    Resolve,
+    // The oracle is overdue; the fallback committee may resolve
+    EscalateOracle,
+    // Nobody resolved in time; the market is voided
+    VoidUnresolved,
+    // The claim window after settlement is over; the market is archived and its chain retired
+    Archive,
+    // A key rotation's overlap window ends; only the new key is accepted from here
+    CompleteKeyRotation,
+    // An unacknowledged outbox message is due to be resent
+    RetryOutbox,
+    // An atomic batch this chain coordinates may have waited too long for its markets' votes
+    AbortSaga,
+    // A reservation held for another chain's atomic batch may have gone stale
+    ExpireReservations,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timer {
+    pub id: u64,
+    // Micros timestamp the timer fires at
+    pub at: u64,
+    pub kind: TimerKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TimerRegistry {
+    // (deadline, id) -> kind; ids grow with every schedule, so they break ties in scheduling order
+    pending: BTreeMap<(u64, u64), TimerKind>,
+    next_id: u64,
+}
+
+impl TimerRegistry {
+    pub fn schedule(&mut self, at: u64, kind: TimerKind) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert((at, id), kind);
+        id
+    }
+
+    // Drop every pending timer of `kind`; the number dropped
+    pub fn cancel(&mut self, kind: TimerKind) -> usize {
+        let before = self.pending.len();
+        self.pending.retain(|_, pending| *pending != kind);
+        before - self.pending.len()
+    }
+
+    // Remove and return the earliest timer due at `now`
+    pub fn pop_due(&mut self, now: u64) -> Option<Timer> {
+        let entry = self.pending.first_entry().filter(|entry| entry.key().0 <= now)?;
+        let ((at, id), kind) = entry.remove_entry();
+        Some(Timer { id, at, kind })
+    }
+
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.pending.keys().next().map(|(at, _)| *at)
+    }
+
+    // Pending timers in firing order
+    pub fn pending(&self) -> impl Iterator<Item = Timer> + '_ {
+        self.pending.iter().map(|((at, id), kind)| Timer { id: *id, at: *at, kind: *kind })
+    }
+}
@@ -0,0 +1,163 @@
+// Exactly-once settlement of `Transfer` messages. Every transfer a market issues carries an
+// ID unique across chains: the issuing chain and its own counter. A market pays out of its
+// own balance on its own chain and sends the recipient the transfer as a receipt. It charges
+// a user chain by sending it the transfer as a request, which that chain's market app pays
+// only within what the chain approved for its orders. Either way the receiving chain applies
+// each ID once and ignores replays, whether from an outbox retry or a resent message, and the
+// issuing market keeps a ledger of what it sent for reconciliation.
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use oddsstream_schema::AppliedSequences;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Issued transfers kept for reconciliation; older ones are dropped from the ledger
+pub const MAX_ISSUED_TRANSFERS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransferId {
+    pub issuer: ChainId,
+    pub sequence: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IssuedTransfer {
+    pub from: ChainId,
+    pub to: ChainId,
+    pub amount: Amount,
+    pub token: Option<ApplicationId>,
+    pub issued_at: u64,
+}
+
+// Call a fungible token application must accept to move the paying chain's tokens
+#[derive(Serialize, Deserialize)]
+pub enum TokenCall {
+    Transfer { to: ChainId, amount: Amount },
+}
+
+// What an incoming transfer does on the chain it arrives at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Admission {
+    // Another chain already paid; only the ledger records it
+    Receipt,
+    // A charge on this chain it approved; the funds are moved now
+    Debit,
+    // A replay, or a charge this chain never approved
+    Refused,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TransferLedger {
+    // Sequence -> transfer, for the last `MAX_ISSUED_TRANSFERS` this chain issued
+    pub issued: BTreeMap<u64, IssuedTransfer>,
+    pub next_sequence: u64,
+    // Issuer -> sequences of its transfers received and applied on this chain
+    pub applied: BTreeMap<ChainId, AppliedSequences>,
+    // Market chain -> what this chain, as a user chain, still lets it charge for orders it submitted
+    pub allowances: BTreeMap<ChainId, Amount>,
+}
+
+impl TransferLedger {
+    pub fn issue(&mut self, issuer: ChainId, transfer: IssuedTransfer) -> TransferId {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.issued.insert(sequence, transfer);
+        while self.issued.len() > MAX_ISSUED_TRANSFERS {
+            self.issued.pop_first();
+        }
+        TransferId { issuer, sequence }
+    }
+
+    // Oldest sequence still in the ledger; everything below it was issued but dropped
+    pub fn retained_from(&self) -> u64 {
+        self.issued.keys().next().copied().unwrap_or(self.next_sequence)
+    }
+
+    // Whether the transfer is new and should be applied. Only its issuer can send it, so a
+    // third party can't use up someone else's IDs.
+    pub fn apply(&mut self, origin: ChainId, id: TransferId) -> bool {
+        id.issuer == origin && self.applied.entry(id.issuer).or_default().insert(id.sequence)
+    }
+
+    pub fn approve(&mut self, market_chain: ChainId, amount: Amount) {
+        *self.allowances.entry(market_chain).or_insert(Amount::zero()) += amount;
+    }
+
+    // Decides a transfer arriving at `chain` from `origin`. A transfer paying out of another
+    // chain moves nothing here. One charging this chain is paid only when the chain sent it
+    // itself, or a market charges it within what the chain approved, which the charge uses up.
+    pub fn admit(&mut self, chain: ChainId, origin: ChainId, from: ChainId, amount: Amount, id: TransferId) -> Admission {
+        if from != chain {
+            return if self.apply(origin, id) { Admission::Receipt } else { Admission::Refused };
+        }
+        let allowance = self.allowances.get(&origin).copied().unwrap_or(Amount::zero());
+        if origin != chain && allowance < amount {
+            return Admission::Refused;
+        }
+        if !self.apply(origin, id) {
+            return Admission::Refused;
+        }
+        if origin != chain {
+            let left = allowance.saturating_sub(amount);
+            if left == Amount::zero() {
+                self.allowances.remove(&origin);
+            } else {
+                self.allowances.insert(origin, left);
+            }
+        }
+        Admission::Debit
+    }
+
+    pub fn is_applied(&self, id: TransferId) -> bool {
+        self.applied.get(&id.issuer).is_some_and(|applied| applied.contains(id.sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(byte: u8) -> ChainId {
+        ChainId::from([byte; 32])
+    }
+
+    #[test]
+    fn test_payout_receipts_never_debit() {
+        let (user, market) = (chain(1), chain(2));
+        let mut ledger = TransferLedger::default();
+        let id = TransferId { issuer: market, sequence: 0 };
+        let amount = Amount::from_tokens(5);
+        assert_eq!(ledger.admit(user, market, market, amount, id), Admission::Receipt);
+        assert_eq!(ledger.admit(user, market, market, amount, id), Admission::Refused);
+    }
+
+    #[test]
+    fn test_charges_need_the_paying_chains_approval() {
+        let (user, market, attacker) = (chain(1), chain(2), chain(3));
+        let mut ledger = TransferLedger::default();
+        let amount = Amount::from_tokens(5);
+        // A charge nobody approved is refused, under any issuer ID
+        let forged = TransferId { issuer: attacker, sequence: 0 };
+        assert_eq!(ledger.admit(user, attacker, user, amount, forged), Admission::Refused);
+        assert!(!ledger.is_applied(forged));
+
+        ledger.approve(market, Amount::from_tokens(8));
+        let first = TransferId { issuer: market, sequence: 0 };
+        assert_eq!(ledger.admit(user, market, user, amount, first), Admission::Debit);
+        assert_eq!(ledger.admit(user, market, user, Amount::from_tokens(1), first), Admission::Refused);
+        // The first charge used up most of the approval
+        let second = TransferId { issuer: market, sequence: 1 };
+        assert_eq!(ledger.admit(user, market, user, amount, second), Admission::Refused);
+        assert_eq!(ledger.admit(user, market, user, Amount::from_tokens(3), second), Admission::Debit);
+        assert!(ledger.allowances.is_empty());
+        // Another approved market can't spend this one's approval
+        assert_eq!(ledger.admit(user, attacker, user, amount, TransferId { issuer: attacker, sequence: 1 }), Admission::Refused);
+    }
+
+    #[test]
+    fn test_a_chain_may_charge_itself() {
+        let user = chain(1);
+        let mut ledger = TransferLedger::default();
+        let id = TransferId { issuer: user, sequence: 0 };
+        assert_eq!(ledger.admit(user, user, user, Amount::from_tokens(1), id), Admission::Debit);
+    }
+}
@@ -0,0 +1,65 @@
+// Typed reasons an order was not filled, reported back to the user chain in `BatchConfirmed`
+use crate::Order;
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+
+const ATTOS_PER_TOKEN: f64 = 1e18;
+
+// Carried in a message, so no floats: prices are `Amount`s, since a share never costs more than 1
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RejectionReason {
+    // The session key's remaining spend cap is below the batch's notional
+    InsufficientFunds { required: Amount, available: Amount },
+    // Resolved, or a conditional market that was voided
+    MarketClosed,
+    // The circuit breaker tripped; orders are refused until `until`
+    Halted { until: u64 },
+    // The batch nonce is not above the last one accepted from this user chain
+    StaleNonce { last_accepted: u64 },
+    // The fill price is above the order's limit
+    PriceProtection { limit: Amount, price: Amount },
+    // Bad or missing signature, expired session key, missing multisig approvals or a misattributed relayer fee
+    Unauthorized,
+    // The market's compliance allowlist refused the user chain
+    NotApproved,
+    // Filling would take the pools past the market's cap; `remaining` is what is left
+    PoolCapReached { remaining: Amount },
+    // Filling would take the user chain past the per-user exposure cap
+    ExposureCapReached { remaining: Amount },
+    // An amendment named an order that is not queued: it filled, never existed, or the auction ended
+    OrderNotFound,
+    // Governance paused the protocol; cancels and claims still go through
+    ProtocolPaused,
+    // An atomic batch's reservation lapsed before the user chain committed it
+    ReservationExpired,
+    // Named a sub-account in a batch the user app didn't place, so nothing checked or funded it
+    UnknownSubaccount,
+    // The user chain excluded itself from trading until `until`
+    SelfExcluded { until: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OrderRejection {
+    pub order_id: u64,
+    pub reason: RejectionReason,
+}
+
+pub fn reject_all(orders: &[Order], reason: RejectionReason) -> Vec<OrderRejection> {
+    orders
+        .iter()
+        .map(|order| OrderRejection { order_id: order.id, reason: reason.clone() })
+        .collect()
+}
+
+pub fn price_amount(price: f64) -> Amount {
+    Amount::from_attos((price.clamp(0.0, 1.0) * ATTOS_PER_TOKEN) as u128)
+}
+
+// Whether `order` may fill at `price`
+pub fn check_price(order: &Order, price: f64) -> Result<(), RejectionReason> {
+    let price = price_amount(price);
+    match order.max_price {
+        Some(limit) if price > limit => Err(RejectionReason::PriceProtection { limit, price }),
+        _ => Ok(()),
+    }
+}
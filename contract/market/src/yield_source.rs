@@ -0,0 +1,58 @@
+// Idle pool funds parked in a whitelisted yield source while the market trades. The source
+// is another application registered on the market chain and called synchronously, like
+// the allowlist. Everything is withdrawn the moment trading stops, so claims and refunds
+// are only ever paid out of funds the market holds itself.
+use crate::fees::{self, apply_bps};
+use linera_sdk::base::{Amount, ApplicationId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YieldBeneficiary {
+    // Credited to liquidity providers in proportion to their shares
+    #[default]
+    Providers,
+    // Added to the protocol fees owed to the treasury
+    Treasury,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct YieldConfig {
+    // `None` keeps every fund on the market chain; the registry only accepts sources it whitelisted
+    pub source: Option<ApplicationId>,
+    // Share of the pools kept deposited; the rest stays on hand for liquidity withdrawals
+    pub deposit_bps: u32,
+    pub beneficiary: YieldBeneficiary,
+}
+
+impl YieldConfig {
+    pub fn is_valid(&self) -> bool {
+        u128::from(self.deposit_bps) <= fees::BPS_DENOMINATOR
+    }
+
+    // Principal the source should hold while the pools total `pool`
+    pub fn target_deposit(&self, pool: Amount) -> Amount {
+        match self.source {
+            Some(_) => apply_bps(pool, self.deposit_bps),
+            None => Amount::zero(),
+        }
+    }
+}
+
+// Call interface a yield source application must implement; amounts are in `token`,
+// the market's settlement token (`None` for the native token)
+#[derive(Serialize, Deserialize)]
+pub enum YieldSourceCall {
+    Deposit { amount: Amount, token: Option<ApplicationId> },
+    // Responds with the amount actually returned to the caller
+    Withdraw { amount: Amount, token: Option<ApplicationId> },
+    // Responds with the caller's principal plus the yield it has accrued
+    Balance { token: Option<ApplicationId> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct YieldPosition {
+    // Principal currently held by the source
+    pub deposited: Amount,
+    // Yield collected over the market's life, whoever it was credited to
+    pub harvested: Amount,
+}
@@ -0,0 +1,122 @@
+// Real-world data sources the oracle reads event outcomes from
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+pub use crate::EventSource;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// The event has not finished, or the source has not published a result yet
+    Pending,
+    Settled(bool),
+}
+
+#[derive(Debug, Error)]
+pub enum AdapterError {
+    #[error("no data adapter named {0}")]
+    UnknownAdapter(String),
+    #[error("data source unreachable: {0}")]
+    Transport(String),
+    #[error("malformed data source response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Reads outcomes from one data provider
+#[async_trait]
+pub trait DataAdapter: Send + Sync {
+    /// Short name used in logs and metrics
+    fn name(&self) -> &str;
+
+    async fn fetch_outcome(&self, event_id: &str) -> Result<EventOutcome, AdapterError>;
+}
+
+/// Which data provider an adapter reads, as written in the operator config
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AdapterKind {
+    /// GET `url_template` with `{event_id}` filled in. `outcome_pointer` is a
+    /// JSON pointer to a boolean; missing or null means the event is pending.
+    /// The API key, if any, is read from the `api_key_env` environment
+    /// variable so it stays out of the config file.
+    HttpJson {
+        url_template: String,
+        outcome_pointer: String,
+        #[serde(default)]
+        api_key_env: Option<String>,
+    },
+    /// Fixed outcomes by event id; for local networks and rehearsals
+    Static { outcomes: HashMap<String, bool> },
+}
+
+impl AdapterKind {
+    pub fn build(&self, name: &str) -> Box<dyn DataAdapter> {
+        match self {
+            AdapterKind::HttpJson { url_template, outcome_pointer, api_key_env } => Box::new(HttpJsonAdapter {
+                name: name.to_string(),
+                url_template: url_template.clone(),
+                outcome_pointer: outcome_pointer.clone(),
+                api_key: api_key_env.as_ref().and_then(|variable| std::env::var(variable).ok()),
+            }),
+            AdapterKind::Static { outcomes } => Box::new(StaticAdapter {
+                name: name.to_string(),
+                outcomes: outcomes.clone(),
+            }),
+        }
+    }
+}
+
+pub struct HttpJsonAdapter {
+    pub name: String,
+    pub url_template: String,
+    pub outcome_pointer: String,
+    pub api_key: Option<String>,
+}
+
+#[async_trait]
+impl DataAdapter for HttpJsonAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch_outcome(&self, event_id: &str) -> Result<EventOutcome, AdapterError> {
+        let url = self.url_template.replace("{event_id}", event_id);
+        let mut request = reqwest::Client::new().get(url);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let body: serde_json::Value = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AdapterError::Transport(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::InvalidResponse(e.to_string()))?;
+
+        match body.pointer(&self.outcome_pointer) {
+            None | Some(serde_json::Value::Null) => Ok(EventOutcome::Pending),
+            Some(serde_json::Value::Bool(outcome)) => Ok(EventOutcome::Settled(*outcome)),
+            Some(other) => Err(AdapterError::InvalidResponse(format!(
+                "{} is {}, not a boolean",
+                self.outcome_pointer, other
+            ))),
+        }
+    }
+}
+
+pub struct StaticAdapter {
+    pub name: String,
+    pub outcomes: HashMap<String, bool>,
+}
+
+#[async_trait]
+impl DataAdapter for StaticAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch_outcome(&self, event_id: &str) -> Result<EventOutcome, AdapterError> {
+        Ok(self.outcomes.get(event_id).map_or(EventOutcome::Pending, |outcome| EventOutcome::Settled(*outcome)))
+    }
+}
@@ -0,0 +1,52 @@
+// oddsstream-oracle: resolves watched markets through the TEE oracle.
+//
+//     oddsstream-oracle <config.json>
+//
+// Logs are JSON lines on stderr; RUST_LOG sets the level (default info).
+use oddsstream_oracle::operator::{Operator, OperatorConfig};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .json()
+        .with_writer(std::io::stderr)
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: oddsstream-oracle <config.json>");
+        std::process::exit(2);
+    };
+    let config = match OperatorConfig::load(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!(error = %e, "cannot start");
+            std::process::exit(1);
+        }
+    };
+    let listener = match TcpListener::bind(&config.status_listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(address = %config.status_listen, error = %e, "cannot bind the status endpoint");
+            std::process::exit(1);
+        }
+    };
+    let (markets, adapters, status) = (config.markets.len(), config.adapters.len(), config.status_listen.clone());
+    let operator = match Operator::new(config) {
+        Ok(operator) => Arc::new(operator),
+        Err(e) => {
+            tracing::error!(error = %e, "cannot start");
+            std::process::exit(1);
+        }
+    };
+    tracing::info!(markets, adapters, %status, "oracle operator started");
+
+    tokio::select! {
+        _ = operator.clone().run() => {}
+        _ = operator.clone().serve_status(listener) => {}
+        _ = tokio::signal::ctrl_c() => tracing::info!("shutting down"),
+    }
+}
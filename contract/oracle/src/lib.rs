@@ -1,3 +1,22 @@
+pub mod attestation;
+pub mod tee_oracle;
+#[cfg(feature = "operator")]
+pub mod adapters;
+#[cfg(feature = "operator")]
+pub mod operator;
+#[cfg(feature = "operator")]
+pub mod transparency;
+
+pub use tee_oracle::{TeeConfig, TeeOracle};
+
+/// Where an event's outcome is published: the data adapter that reads it and
+/// the adapter's own identifier for the event (fixture id, ticker, ...)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EventSource {
+    pub adapter: String,
+    pub event_id: String,
+}
+
 pub enum OracleRequest {
     FastTee {
         market_id: String,
@@ -9,6 +28,13 @@ pub enum OracleRequest {
         event_source: EventSource,
         committee_size: u32,
     },
+    // Result of one stage of a market that pays out in stages
+    FastTeeStage {
+        market_id: String,
+        stage: u32,
+        event_source: EventSource,
+        tee_config: TeeConfig,
+    },
 }
 
 impl OracleAdjudicator {
@@ -19,7 +45,7 @@ impl OracleAdjudicator {
                 let outcome = self.fetch_event_outcome(&event_source).await;
                 
                 // 2. Get TEE-signed attestation
-                let (quote, signature) = self.request_tee_attestation(
+                let (quote, signature, timestamp) = self.request_tee_attestation(
                     &market_id,
                     outcome,
                     &tee_config,
@@ -27,8 +53,10 @@ impl OracleAdjudicator {
                 
                 // 3. Verify and forward to market
                 if self.verify_tee_attestation(&quote, &signature) {
+                    // The market rebuilds the signed text, so it needs the timestamp the enclave used
                     let msg = MarketMessage::Resolution {
                         outcome,
+                        timestamp,
                         signature,
                         oracle_type: OracleType::FastTee,
                     };
@@ -40,6 +68,27 @@ impl OracleAdjudicator {
                 // Start multi-signature gathering
                 self.initiate_committee_vote(&market_id, event_source, committee_size);
             }
+            
+            OracleRequest::FastTeeStage { market_id, stage, event_source, tee_config } => {
+                let outcome = self.fetch_event_outcome(&event_source).await;
+                let (quote, signature, timestamp) = self.request_tee_stage_attestation(
+                    &market_id,
+                    stage,
+                    outcome,
+                    &tee_config,
+                ).await;
+                
+                if self.verify_tee_attestation(&quote, &signature) {
+                    let msg = MarketMessage::StageResolution {
+                        stage,
+                        outcome,
+                        timestamp,
+                        signature,
+                        oracle_type: OracleType::FastTee,
+                    };
+                    self.send_to_market(&market_id, msg);
+                }
+            }
         }
     }
 }
\ No newline at end of file
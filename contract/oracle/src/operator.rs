@@ -0,0 +1,557 @@
+// Runtime of the `oddsstream-oracle` operator binary: watches markets due for
+// resolution, reads their outcomes through the data adapters, has the enclave
+// sign them, checks the attestation and submits the result to the oracle
+// application. Progress is served as JSON and Prometheus metrics, and failed
+// attestations or submissions raise alerts. Every read is kept in the
+// transparency log, whose heads are anchored on the markets.
+use crate::adapters::{AdapterError, AdapterKind, DataAdapter, EventOutcome, EventSource};
+use crate::tee_oracle::{TeeConfig, TeeOracle};
+use crate::transparency::{Observation, TransparencyLog};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Error)]
+pub enum OperatorError {
+    #[error("invalid operator config: {0}")]
+    Config(String),
+    #[error(transparent)]
+    Adapter(#[from] AdapterError),
+    #[error("enclave signing failed: {0}")]
+    Enclave(String),
+    #[error("attestation rejected for {0}")]
+    Attestation(String),
+    #[error("submitting the resolution failed: {0}")]
+    Submission(String),
+    #[error("observation log: {0}")]
+    ObservationLog(#[from] std::io::Error),
+    #[error("anchoring observations failed: {0}")]
+    Anchor(String),
+}
+
+/// A market the operator resolves once `resolves_at` has passed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WatchedMarket {
+    pub market_id: String,
+    /// Unix seconds
+    pub resolves_at: u64,
+    pub source: EventSource,
+    /// Set to resolve one stage of a staged market instead of the market itself
+    #[serde(default)]
+    pub stage: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AlertConfig {
+    /// Receives a JSON POST per alert; the `text` field suits Slack-style webhooks
+    pub webhook_url: Option<String>,
+}
+
+/// The operator's config file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OperatorConfig {
+    /// Serves `/status`, `/metrics` and `/healthz`
+    #[serde(default = "default_status_listen")]
+    pub status_listen: String,
+    /// Linera node service the oracle application is reached through
+    pub node_url: String,
+    pub oracle_chain_id: String,
+    pub oracle_application_id: String,
+    /// Enclave that signs resolutions; its key and attestation provider are in `tee`
+    pub enclave_url: String,
+    pub tee: TeeConfig,
+    pub adapters: HashMap<String, AdapterKind>,
+    pub markets: Vec<WatchedMarket>,
+    #[serde(default = "default_poll_secs")]
+    pub poll_secs: u64,
+    /// Attempts at a resolution before it is left failed for an operator to look at
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    /// JSON-lines file the transparency log is appended to
+    #[serde(default = "default_observation_log")]
+    pub observation_log: String,
+    /// How often a market's new observations are anchored; always anchored before it resolves
+    #[serde(default = "default_anchor_every_secs")]
+    pub anchor_every_secs: u64,
+    /// Base URL the status endpoint is published under, written into anchors
+    /// so auditors can find the log; defaults to `http://<status_listen>`
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+fn default_status_listen() -> String {
+    "127.0.0.1:9464".to_string()
+}
+
+fn default_poll_secs() -> u64 {
+    30
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+fn default_observation_log() -> String {
+    "observations.jsonl".to_string()
+}
+
+fn default_anchor_every_secs() -> u64 {
+    3_600
+}
+
+impl OperatorConfig {
+    pub fn load(path: &str) -> Result<Self, OperatorError> {
+        let text = std::fs::read_to_string(path).map_err(|e| OperatorError::Config(format!("{}: {}", path, e)))?;
+        let config: Self = serde_json::from_str(&text).map_err(|e| OperatorError::Config(format!("{}: {}", path, e)))?;
+        for market in &config.markets {
+            if !config.adapters.contains_key(&market.source.adapter) {
+                return Err(AdapterError::UnknownAdapter(market.source.adapter.clone()).into());
+            }
+        }
+        if config.max_attempts == 0 {
+            return Err(OperatorError::Config("max_attempts must be at least 1".to_string()));
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ResolutionState {
+    /// Not due yet, or due and waiting for the source or a retry
+    Pending,
+    Completed { outcome: bool, transaction: String, completed_at: u64 },
+    /// Gave up after `max_attempts`
+    Failed,
+}
+
+/// Where one watched market's resolution stands, as listed by `/status`
+#[derive(Serialize, Clone, Debug)]
+pub struct ResolutionStatus {
+    pub market_id: String,
+    pub stage: Option<u32>,
+    pub resolves_at: u64,
+    #[serde(flatten)]
+    pub state: ResolutionState,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct Counters {
+    attestations_ok: AtomicU64,
+    attestations_failed: AtomicU64,
+    submissions_failed: AtomicU64,
+    adapter_errors: AtomicU64,
+    observations: AtomicU64,
+    anchor_failures: AtomicU64,
+    last_poll: AtomicU64,
+}
+
+pub struct Operator {
+    config: OperatorConfig,
+    adapters: HashMap<String, Box<dyn DataAdapter>>,
+    tee: TeeOracle,
+    http: reqwest::Client,
+    statuses: Mutex<Vec<ResolutionStatus>>,
+    log: Mutex<TransparencyLog>,
+    // Market -> chain length last anchored and when, in unix seconds
+    anchored: Mutex<HashMap<String, (u64, u64)>>,
+    counters: Counters,
+}
+
+#[derive(Deserialize)]
+struct EnclaveSignature {
+    quote: String,
+    signature: String,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl Operator {
+    pub fn new(config: OperatorConfig) -> Result<Self, OperatorError> {
+        let adapters = config.adapters.iter().map(|(name, kind)| (name.clone(), kind.build(name))).collect();
+        let statuses = config
+            .markets
+            .iter()
+            .map(|market| ResolutionStatus {
+                market_id: market.market_id.clone(),
+                stage: market.stage,
+                resolves_at: market.resolves_at,
+                state: ResolutionState::Pending,
+                attempts: 0,
+                last_error: None,
+            })
+            .collect();
+        Ok(Self {
+            tee: TeeOracle::from_config(&config.tee),
+            log: Mutex::new(TransparencyLog::open(&config.observation_log)?),
+            adapters,
+            config,
+            http: reqwest::Client::new(),
+            statuses: Mutex::new(statuses),
+            anchored: Mutex::default(),
+            counters: Counters::default(),
+        })
+    }
+
+    pub fn statuses(&self) -> Vec<ResolutionStatus> {
+        self.statuses.lock().unwrap().clone()
+    }
+
+    // Resolve every due market once; a market whose source is still pending waits for the next poll
+    pub async fn poll(&self) {
+        let now = unix_now();
+        self.counters.last_poll.store(now, Ordering::Relaxed);
+        for (index, market) in self.config.markets.iter().enumerate() {
+            let due = {
+                let statuses = self.statuses.lock().unwrap();
+                statuses[index].state == ResolutionState::Pending && market.resolves_at <= now
+            };
+            if !due {
+                continue;
+            }
+            let result = self.resolve(market).await;
+
+            let mut statuses = self.statuses.lock().unwrap();
+            let status = &mut statuses[index];
+            match result {
+                Ok(None) => {}
+                Ok(Some((outcome, transaction))) => {
+                    tracing::info!(market_id = %market.market_id, stage = ?market.stage, outcome, %transaction, "resolution submitted");
+                    status.state = ResolutionState::Completed { outcome, transaction, completed_at: unix_now() };
+                    status.last_error = None;
+                }
+                Err(e) => {
+                    status.attempts += 1;
+                    status.last_error = Some(e.to_string());
+                    let gave_up = status.attempts >= self.config.max_attempts;
+                    if gave_up {
+                        status.state = ResolutionState::Failed;
+                    }
+                    let attempts = status.attempts;
+                    drop(statuses);
+                    tracing::warn!(market_id = %market.market_id, stage = ?market.stage, attempts, error = %e, "resolution failed");
+                    // Data sources flap; only trouble on our side of the pipeline pages someone
+                    if gave_up || !matches!(e, OperatorError::Adapter(_)) {
+                        self.alert(market, &e, attempts, gave_up).await;
+                    }
+                }
+            }
+        }
+
+        let anchor_every = self.config.anchor_every_secs;
+        let due: Vec<String> = {
+            let log = self.log.lock().unwrap();
+            let anchored = self.anchored.lock().unwrap();
+            self.config
+                .markets
+                .iter()
+                .filter(|market| {
+                    let length = log.head(&market.market_id).map_or(0, |(length, _)| length);
+                    let (anchored_length, anchored_at) = anchored.get(&market.market_id).copied().unwrap_or_default();
+                    length > anchored_length && now.saturating_sub(anchored_at) >= anchor_every
+                })
+                .map(|market| market.market_id.clone())
+                .collect()
+        };
+        for market_id in due {
+            if let Err(e) = self.anchor(&market_id).await {
+                tracing::warn!(%market_id, error = %e, "observation anchor failed");
+            }
+        }
+    }
+
+    fn observe(&self, market: &WatchedMarket, outcome: Option<bool>) -> Result<(), OperatorError> {
+        let observation = Observation {
+            market_id: market.market_id.clone(),
+            adapter: market.source.adapter.clone(),
+            event_id: market.source.event_id.clone(),
+            observed_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_micros() as u64),
+            outcome,
+        };
+        self.log.lock().unwrap().append(observation)?;
+        self.counters.observations.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // Commit the market's current chain head as `OracleObservation` evidence on the market
+    async fn anchor(&self, market_id: &str) -> Result<(), OperatorError> {
+        let Some((length, head)) = self.log.lock().unwrap().head(market_id) else {
+            return Ok(());
+        };
+        if self.anchored.lock().unwrap().get(market_id).is_some_and(|(anchored, _)| *anchored == length) {
+            return Ok(());
+        }
+        let base = self.config.public_url.clone().unwrap_or_else(|| format!("http://{}", self.config.status_listen));
+        let uri = format!("{}/observations/{}#{}", base, market_id, length);
+        let mutation = r#"
+            mutation AnchorObservations($marketId: String!, $length: Int!, $headHash: String!, $uri: String!) {
+                anchorObservations(marketId: $marketId, length: $length, headHash: $headHash, uri: $uri)
+            }
+        "#;
+        let variables = json!({ "marketId": market_id, "length": length, "headHash": hex::encode(head), "uri": uri });
+        if let Err(e) = self.mutate(mutation, variables, "anchorObservations").await {
+            self.counters.anchor_failures.fetch_add(1, Ordering::Relaxed);
+            return Err(OperatorError::Anchor(e));
+        }
+        tracing::info!(%market_id, length, head = %hex::encode(head), "observations anchored");
+        self.anchored.lock().unwrap().insert(market_id.to_string(), (length, unix_now()));
+        Ok(())
+    }
+
+    // The outcome and transaction once resolved, `None` while the source has no result
+    async fn resolve(&self, market: &WatchedMarket) -> Result<Option<(bool, String)>, OperatorError> {
+        let adapter = self
+            .adapters
+            .get(&market.source.adapter)
+            .ok_or_else(|| AdapterError::UnknownAdapter(market.source.adapter.clone()))?;
+        let outcome = match adapter.fetch_outcome(&market.source.event_id).await {
+            Ok(EventOutcome::Pending) => {
+                self.observe(market, None)?;
+                return Ok(None);
+            }
+            Ok(EventOutcome::Settled(outcome)) => outcome,
+            Err(e) => {
+                self.counters.adapter_errors.fetch_add(1, Ordering::Relaxed);
+                return Err(e.into());
+            }
+        };
+        self.observe(market, Some(outcome))?;
+        // A resolution is never submitted ahead of the evidence for it
+        self.anchor(&market.market_id).await?;
+
+        let signed: EnclaveSignature = self
+            .http
+            .post(format!("{}/sign", self.config.enclave_url))
+            .json(&json!({
+                "marketId": market.market_id,
+                "stage": market.stage,
+                "outcome": outcome,
+                "timestamp": unix_now(),
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| OperatorError::Enclave(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OperatorError::Enclave(e.to_string()))?;
+        let decode = |value: &str| STANDARD.decode(value).map_err(|e| OperatorError::Enclave(e.to_string()));
+        let (quote, signature) = (decode(&signed.quote)?, decode(&signed.signature)?);
+
+        if !self.tee.verify_attestation(&quote, &signature).await {
+            self.counters.attestations_failed.fetch_add(1, Ordering::Relaxed);
+            return Err(OperatorError::Attestation(market.market_id.clone()));
+        }
+        self.counters.attestations_ok.fetch_add(1, Ordering::Relaxed);
+
+        let transaction = self.submit(market, outcome, &signature).await.inspect_err(|_| {
+            self.counters.submissions_failed.fetch_add(1, Ordering::Relaxed);
+        })?;
+        Ok(Some((outcome, transaction)))
+    }
+
+    async fn submit(&self, market: &WatchedMarket, outcome: bool, signature: &[u8]) -> Result<String, OperatorError> {
+        let mutation = r#"
+            mutation ResolveMarket($marketId: String!, $stage: Int, $outcome: Boolean!, $signature: String!) {
+                resolveMarket(marketId: $marketId, stage: $stage, outcome: $outcome, signature: $signature)
+            }
+        "#;
+        let variables = json!({
+            "marketId": market.market_id,
+            "stage": market.stage,
+            "outcome": outcome,
+            "signature": hex::encode(signature),
+        });
+        self.mutate(mutation, variables, "resolveMarket").await.map_err(OperatorError::Submission)
+    }
+
+    // Run a mutation on the oracle application; the transaction it was executed in
+    async fn mutate(&self, mutation: &str, variables: serde_json::Value, field: &str) -> Result<String, String> {
+        let url = format!(
+            "{}/chains/{}/applications/{}",
+            self.config.node_url, self.config.oracle_chain_id, self.config.oracle_application_id
+        );
+        let body: serde_json::Value = self
+            .http
+            .post(url)
+            .json(&json!({ "query": mutation, "variables": variables }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(errors) = body.get("errors") {
+            return Err(errors.to_string());
+        }
+        body.pointer(&format!("/data/{}", field))
+            .and_then(|transaction| transaction.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| "no transaction in the response".to_string())
+    }
+
+    async fn alert(&self, market: &WatchedMarket, error: &OperatorError, attempts: u32, gave_up: bool) {
+        let text = if gave_up {
+            format!("Oracle gave up resolving {} after {} attempts: {}", market.market_id, attempts, error)
+        } else {
+            format!("Oracle failed to resolve {} (attempt {}): {}", market.market_id, attempts, error)
+        };
+        tracing::error!(market_id = %market.market_id, attempts, gave_up, error = %error, "oracle alert");
+        let Some(webhook) = &self.config.alerts.webhook_url else {
+            return;
+        };
+        let sent = self
+            .http
+            .post(webhook)
+            .json(&json!({
+                "text": text,
+                "marketId": market.market_id,
+                "stage": market.stage,
+                "error": error.to_string(),
+                "attempts": attempts,
+                "gaveUp": gave_up,
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = sent {
+            tracing::warn!(error = %e, "alert webhook failed");
+        }
+    }
+
+    // Prometheus text exposition of the counters and resolution states
+    pub fn render_metrics(&self) -> String {
+        let statuses = self.statuses();
+        let count = |wanted: fn(&ResolutionState) -> bool| statuses.iter().filter(|status| wanted(&status.state)).count();
+        let counter = |value: &AtomicU64| value.load(Ordering::Relaxed);
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, u64)]| {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+            for (labels, value) in samples {
+                out.push_str(&format!("{}{} {}\n", name, labels, value));
+            }
+        };
+        metric(
+            "oddsstream_oracle_resolutions",
+            "gauge",
+            "Watched resolutions by state",
+            &[
+                ("{state=\"pending\"}", count(|state| *state == ResolutionState::Pending) as u64),
+                ("{state=\"completed\"}", count(|state| matches!(state, ResolutionState::Completed { .. })) as u64),
+                ("{state=\"failed\"}", count(|state| *state == ResolutionState::Failed) as u64),
+            ],
+        );
+        metric(
+            "oddsstream_oracle_attestations_total",
+            "counter",
+            "Enclave attestations checked",
+            &[
+                ("{result=\"ok\"}", counter(&self.counters.attestations_ok)),
+                ("{result=\"failed\"}", counter(&self.counters.attestations_failed)),
+            ],
+        );
+        metric(
+            "oddsstream_oracle_submission_failures_total",
+            "counter",
+            "Resolutions the oracle application refused or could not be reached for",
+            &[("", counter(&self.counters.submissions_failed))],
+        );
+        metric(
+            "oddsstream_oracle_adapter_errors_total",
+            "counter",
+            "Failed reads from data adapters",
+            &[("", counter(&self.counters.adapter_errors))],
+        );
+        metric(
+            "oddsstream_oracle_observations_total",
+            "counter",
+            "Adapter reads appended to the transparency log",
+            &[("", counter(&self.counters.observations))],
+        );
+        metric(
+            "oddsstream_oracle_anchor_failures_total",
+            "counter",
+            "Transparency log heads that could not be anchored",
+            &[("", counter(&self.counters.anchor_failures))],
+        );
+        metric(
+            "oddsstream_oracle_last_poll_timestamp_seconds",
+            "gauge",
+            "Unix time of the last poll",
+            &[("", counter(&self.counters.last_poll))],
+        );
+        out
+    }
+
+    // Poll every `poll_secs` until the task is dropped
+    pub async fn run(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(self.config.poll_secs.max(1)));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            self.poll().await;
+        }
+    }
+
+    // Answer `GET /status` (JSON), `/metrics` (Prometheus), `/observations/<market>`
+    // (the market's transparency log) and `/healthz` on `listener`
+    pub async fn serve_status(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            let (mut stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!(error = %e, "status accept failed");
+                    continue;
+                }
+            };
+            let operator = self.clone();
+            tokio::spawn(async move {
+                let mut line = String::new();
+                if BufReader::new(&mut stream).read_line(&mut line).await.is_err() {
+                    return;
+                }
+                let mut parts = line.split_whitespace();
+                let (status, content_type, body) = match (parts.next(), parts.next()) {
+                    (Some("GET"), Some("/status")) => {
+                        let statuses = operator.statuses();
+                        let (pending, done): (Vec<_>, Vec<_>) =
+                            statuses.into_iter().partition(|status| status.state == ResolutionState::Pending);
+                        let body = json!({ "pending": pending, "completed": done }).to_string();
+                        ("200 OK", "application/json", body)
+                    }
+                    (Some("GET"), Some("/metrics")) => ("200 OK", "text/plain; version=0.0.4", operator.render_metrics()),
+                    (Some("GET"), Some(path)) if path.starts_with("/observations/") => {
+                        let market_id = &path["/observations/".len()..];
+                        let records = operator.log.lock().unwrap().records(market_id).to_vec();
+                        ("200 OK", "application/json", json!(records).to_string())
+                    }
+                    (Some("GET"), Some("/healthz")) => ("200 OK", "application/json", json!({ "status": "ok" }).to_string()),
+                    _ => ("404 Not Found", "application/json", json!({ "error": "not found" }).to_string()),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                );
+                if let Err(e) = stream.write_all(response.as_bytes()).await {
+                    tracing::debug!(%peer, error = %e, "status response not delivered");
+                }
+            });
+        }
+    }
+}
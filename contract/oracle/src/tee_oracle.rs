@@ -1,30 +1,43 @@
+use crate::attestation::{AttestationProvider, AttestationProviderKind};
+use serde::{Deserialize, Serialize};
+
+// TEE deployment settings supplied with each FastTee request
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TeeConfig {
+    pub tee_public_key: String,
+    pub provider: AttestationProviderKind,
+}
+
 // Simplified TEE attestation verification
 pub struct TeeOracle {
     pub tee_public_key: String,
-    pub attestation_service_url: String,
+    pub provider: Box<dyn AttestationProvider>,
 }
 
 impl TeeOracle {
+    pub fn from_config(config: &TeeConfig) -> Self {
+        Self {
+            tee_public_key: config.tee_public_key.clone(),
+            provider: config.provider.build(),
+        }
+    }
+
     pub async fn verify_attestation(&self, quote: &[u8], event_data: &[u8]) -> bool {
-        // 1. Send quote to attestation service (Intel SGX or DragonBeak)
-        let client = reqwest::Client::new();
-        let response = client.post(&self.attestation_service_url)
-            .json(&json!({
-                "quote": hex::encode(quote),
-                "public_key": self.tee_public_key,
-            }))
-            .send()
-            .await
-            .unwrap();
-        
+        // 1. Send quote to the configured attestation provider (DCAP, Nitro, ...)
+        let verification = match self.provider.verify_quote(quote, &self.tee_public_key).await {
+            Ok(verification) => verification,
+            Err(e) => {
+                tracing::warn!(provider = self.provider.name(), error = %e, "attestation failed");
+                return false;
+            }
+        };
+
         // 2. Verify the quote is valid and matches expected TEE
-        let verification = response.json::<AttestationResult>().await.unwrap();
-        
         // 3. Verify event data signature
-        verification.is_valid && 
+        verification.is_valid &&
         self.verify_signature(event_data, &verification.signature)
     }
-    
+
     pub fn create_resolution_signature(
         &self,
         market_id: &str,
@@ -36,4 +49,17 @@ impl TeeOracle {
         // In reality, this happens inside the secure enclave
         sign_message(message.as_bytes(), &self.tee_private_key)
     }
-}
\ No newline at end of file
+
+    // Stage results sign a distinct message, so a stage signature can't be replayed as the
+    // final resolution or as another stage
+    pub fn create_stage_signature(
+        &self,
+        market_id: &str,
+        stage: u32,
+        outcome: bool,
+        timestamp: u64,
+    ) -> Vec<u8> {
+        let message = format!("{}#stage{}:{}{}", market_id, stage, outcome, timestamp);
+        sign_message(message.as_bytes(), &self.tee_private_key)
+    }
+}
@@ -0,0 +1,118 @@
+// Append-only record of every outcome the operator read from its data adapters,
+// one hash chain per market. Chain heads are anchored on the market as
+// `OracleObservation` evidence, so an auditor holding the published log can
+// check it was not rewritten after the fact, whichever oracle path resolved it.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+// Must match the SDK's `observation_hash`
+const OBSERVATION_DOMAIN: &[u8] = b"oddsstream-observation-v1";
+
+/// One read of an event's outcome from a data adapter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Observation {
+    pub market_id: String,
+    pub adapter: String,
+    pub event_id: String,
+    /// Micros timestamp of the read
+    pub observed_at: u64,
+    /// `None` while the source had no result yet
+    pub outcome: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservationRecord {
+    /// Position in the market's chain, from 1
+    pub sequence: u64,
+    pub observation: Observation,
+    pub prev_hash: [u8; 32],
+    pub hash: [u8; 32],
+}
+
+pub fn observation_hash(prev_hash: &[u8; 32], sequence: u64, observation: &Observation) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(OBSERVATION_DOMAIN);
+    hasher.update(prev_hash);
+    hasher.update(bcs::to_bytes(&(sequence, observation)).expect("observation is serializable"));
+    hasher.finalize().into()
+}
+
+/// The per-market chains, mirrored to a JSON-lines file that is only ever appended to
+pub struct TransparencyLog {
+    chains: BTreeMap<String, Vec<ObservationRecord>>,
+    file: Option<File>,
+}
+
+impl TransparencyLog {
+    pub fn in_memory() -> Self {
+        Self { chains: BTreeMap::new(), file: None }
+    }
+
+    // Reload the records already in `path` and keep appending to it. A file whose
+    // chains do not verify is refused rather than extended.
+    pub fn open(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let mut chains: BTreeMap<String, Vec<ObservationRecord>> = BTreeMap::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: ObservationRecord = serde_json::from_str(&line)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                let chain = chains.entry(record.observation.market_id.clone()).or_default();
+                let (expected_sequence, expected_prev) =
+                    chain.last().map_or((1, [0; 32]), |last| (last.sequence + 1, last.hash));
+                if record.sequence != expected_sequence
+                    || record.prev_hash != expected_prev
+                    || observation_hash(&record.prev_hash, record.sequence, &record.observation) != record.hash
+                {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "{}: record {} of {} does not verify",
+                            path.display(),
+                            record.sequence,
+                            record.observation.market_id
+                        ),
+                    ));
+                }
+                chain.push(record);
+            }
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { chains, file: Some(file) })
+    }
+
+    pub fn append(&mut self, observation: Observation) -> std::io::Result<ObservationRecord> {
+        let chain = self.chains.entry(observation.market_id.clone()).or_default();
+        let (sequence, prev_hash) = chain.last().map_or((1, [0; 32]), |last| (last.sequence + 1, last.hash));
+        let hash = observation_hash(&prev_hash, sequence, &observation);
+        let record = ObservationRecord { sequence, observation, prev_hash, hash };
+        if let Some(file) = &mut self.file {
+            let mut line = serde_json::to_string(&record)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            line.push('\n');
+            file.write_all(line.as_bytes())?;
+            file.flush()?;
+        }
+        chain.push(record.clone());
+        Ok(record)
+    }
+
+    pub fn records(&self, market_id: &str) -> &[ObservationRecord] {
+        self.chains.get(market_id).map_or(&[], Vec::as_slice)
+    }
+
+    // Length and hash of the market's chain, `None` before its first observation
+    pub fn head(&self, market_id: &str) -> Option<(u64, [u8; 32])> {
+        self.chains.get(market_id)?.last().map(|last| (last.sequence, last.hash))
+    }
+}
@@ -0,0 +1,22 @@
+// Fingerprints market.schema so every crate built against it embeds the same hash
+use sha2::{Digest, Sha256};
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=market.schema");
+    let schema = fs::read_to_string("market.schema").expect("market.schema is readable");
+
+    // Comments and blank lines don't change the wire format, so they don't change the hash
+    let mut hasher = Sha256::new();
+    for line in schema.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if !line.is_empty() {
+            hasher.update(line.as_bytes());
+            hasher.update(b"\n");
+        }
+    }
+    let hash: [u8; 32] = hasher.finalize().into();
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("schema_hash.rs");
+    fs::write(out, format!("pub const SCHEMA_HASH: [u8; 32] = {:?};\n", hash)).unwrap();
+}
@@ -0,0 +1,192 @@
+//! Rendering traced serde formats as `market.schema`.
+//!
+//! The market's message types are traced with `serde-reflection`, and the
+//! resulting registry is written out in BCS order: enum variants by index,
+//! struct fields by position. Types from `linera-sdk` are left opaque, since
+//! contracts and SDK always share that crate's encoding of them.
+
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+use std::collections::{BTreeSet, VecDeque};
+
+/// Types both sides take from `linera-sdk`, rendered by name only
+pub const OPAQUE_TYPES: &[&str] = &["Amount", "ApplicationId", "ChainId"];
+
+const HEADER: &str = "\
+# Canonical description of what crosses chain boundaries to and from a market.
+# Entries are in BCS order: enum variants by index, struct fields by position.
+# Generated from the market contract's types; don't edit it by hand. To
+# regenerate it run `UPDATE_SCHEMA=1 cargo test -p oddsstream-sdk schema`.
+# Any change but to comments changes SCHEMA_HASH, and an SDK built against
+# the old hash refuses to send orders to markets built against the new one.
+";
+
+/// `market.schema` for `roots` and every type they reach, each type listed once where first reached
+pub fn render(registry: &Registry, roots: &[&str]) -> String {
+    let mut out = String::from(HEADER);
+    let mut seen: BTreeSet<String> = roots.iter().map(|root| root.to_string()).collect();
+    for root in roots {
+        let mut queue = VecDeque::from([root.to_string()]);
+        while let Some(name) = queue.pop_front() {
+            let Some(container) = registry.get(&name) else {
+                continue;
+            };
+            out.push('\n');
+            out.push_str(&render_container(&name, container));
+            let mut reached = Vec::new();
+            container_names(container, &mut reached);
+            for next in reached {
+                if !OPAQUE_TYPES.contains(&next.as_str()) && seen.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn render_container(name: &str, container: &ContainerFormat) -> String {
+    match container {
+        ContainerFormat::UnitStruct => format!("struct {}\n", name),
+        ContainerFormat::NewTypeStruct(format) => format!("struct {}({})\n", name, render_format(format)),
+        ContainerFormat::TupleStruct(formats) => format!("struct {}({})\n", name, render_tuple(formats)),
+        ContainerFormat::Struct(fields) => format!("struct {} {{ {} }}\n", name, render_fields(fields)),
+        ContainerFormat::Enum(variants) => {
+            let mut out = format!("enum {}\n", name);
+            for (index, variant) in variants {
+                let body = match &variant.value {
+                    VariantFormat::Unit | VariantFormat::Variable(_) => String::new(),
+                    VariantFormat::NewType(format) => format!("({})", render_format(format)),
+                    VariantFormat::Tuple(formats) => format!("({})", render_tuple(formats)),
+                    VariantFormat::Struct(fields) => format!(" {{ {} }}", render_fields(fields)),
+                };
+                out.push_str(&format!("  {} {}{}\n", index, variant.name, body));
+            }
+            out
+        }
+    }
+}
+
+fn render_fields(fields: &[Named<Format>]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name, render_format(&field.value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_tuple(formats: &[Format]) -> String {
+    formats.iter().map(render_format).collect::<Vec<_>>().join(", ")
+}
+
+fn render_format(format: &Format) -> String {
+    match format {
+        Format::Variable(_) => "_".to_string(),
+        Format::TypeName(name) => name.clone(),
+        Format::Unit => "()".to_string(),
+        Format::Bool => "bool".to_string(),
+        Format::I8 => "i8".to_string(),
+        Format::I16 => "i16".to_string(),
+        Format::I32 => "i32".to_string(),
+        Format::I64 => "i64".to_string(),
+        Format::I128 => "i128".to_string(),
+        Format::U8 => "u8".to_string(),
+        Format::U16 => "u16".to_string(),
+        Format::U32 => "u32".to_string(),
+        Format::U64 => "u64".to_string(),
+        Format::U128 => "u128".to_string(),
+        Format::F32 => "f32".to_string(),
+        Format::F64 => "f64".to_string(),
+        Format::Char => "char".to_string(),
+        Format::Str => "String".to_string(),
+        Format::Bytes => "Vec<u8>".to_string(),
+        Format::Option(format) => format!("Option<{}>", render_format(format)),
+        Format::Seq(format) => format!("Vec<{}>", render_format(format)),
+        Format::Map { key, value } => format!("BTreeMap<{}, {}>", render_format(key), render_format(value)),
+        Format::Tuple(formats) => format!("({})", render_tuple(formats)),
+        Format::TupleArray { content, size } => format!("[{}; {}]", render_format(content), size),
+    }
+}
+
+/// Wire shape of the type `name`: its layout with every type, field and variant
+/// name dropped, so two registries that name things differently still compare
+/// equal exactly when they encode the same bytes
+pub fn shape(registry: &Registry, name: &str) -> String {
+    if OPAQUE_TYPES.contains(&name) {
+        return name.to_string();
+    }
+    match registry.get(name) {
+        Some(ContainerFormat::UnitStruct) => "()".to_string(),
+        Some(ContainerFormat::NewTypeStruct(format)) => format_shape(registry, format),
+        Some(ContainerFormat::TupleStruct(formats)) => tuple_shape(registry, formats.iter()),
+        Some(ContainerFormat::Struct(fields)) => tuple_shape(registry, fields.iter().map(|field| &field.value)),
+        Some(ContainerFormat::Enum(variants)) => {
+            let variants: Vec<String> = variants
+                .iter()
+                .map(|(index, variant)| {
+                    let body = match &variant.value {
+                        VariantFormat::Unit | VariantFormat::Variable(_) => "()".to_string(),
+                        VariantFormat::NewType(format) => format_shape(registry, format),
+                        VariantFormat::Tuple(formats) => tuple_shape(registry, formats.iter()),
+                        VariantFormat::Struct(fields) => tuple_shape(registry, fields.iter().map(|field| &field.value)),
+                    };
+                    format!("{}:{}", index, body)
+                })
+                .collect();
+            format!("enum[{}]", variants.join(","))
+        }
+        None => format!("?{}", name),
+    }
+}
+
+fn tuple_shape<'a>(registry: &Registry, formats: impl Iterator<Item = &'a Format>) -> String {
+    let shapes: Vec<String> = formats.map(|format| format_shape(registry, format)).collect();
+    format!("({})", shapes.join(","))
+}
+
+fn format_shape(registry: &Registry, format: &Format) -> String {
+    match format {
+        Format::TypeName(name) => shape(registry, name),
+        Format::Option(format) => format!("Option<{}>", format_shape(registry, format)),
+        Format::Seq(format) => format!("Vec<{}>", format_shape(registry, format)),
+        Format::Map { key, value } => {
+            format!("Map<{},{}>", format_shape(registry, key), format_shape(registry, value))
+        }
+        Format::Tuple(formats) => tuple_shape(registry, formats.iter()),
+        Format::TupleArray { content, size } => format!("[{};{}]", format_shape(registry, content), size),
+        primitive => render_format(primitive),
+    }
+}
+
+fn container_names(container: &ContainerFormat, out: &mut Vec<String>) {
+    match container {
+        ContainerFormat::UnitStruct => {}
+        ContainerFormat::NewTypeStruct(format) => format_names(format, out),
+        ContainerFormat::TupleStruct(formats) => formats.iter().for_each(|format| format_names(format, out)),
+        ContainerFormat::Struct(fields) => fields.iter().for_each(|field| format_names(&field.value, out)),
+        ContainerFormat::Enum(variants) => {
+            for variant in variants.values() {
+                match &variant.value {
+                    VariantFormat::Unit | VariantFormat::Variable(_) => {}
+                    VariantFormat::NewType(format) => format_names(format, out),
+                    VariantFormat::Tuple(formats) => formats.iter().for_each(|format| format_names(format, out)),
+                    VariantFormat::Struct(fields) => fields.iter().for_each(|field| format_names(&field.value, out)),
+                }
+            }
+        }
+    }
+}
+
+fn format_names(format: &Format, out: &mut Vec<String>) {
+    match format {
+        Format::TypeName(name) => out.push(name.clone()),
+        Format::Option(format) | Format::Seq(format) => format_names(format, out),
+        Format::Map { key, value } => {
+            format_names(key, out);
+            format_names(value, out);
+        }
+        Format::Tuple(formats) => formats.iter().for_each(|format| format_names(format, out)),
+        Format::TupleArray { content, .. } => format_names(content, out),
+        _ => {}
+    }
+}
+
@@ -0,0 +1,29 @@
+//! Fingerprint of the market wire schema.
+//!
+//! Contracts and the SDK both depend on this crate, so each binary carries
+//! the hash of the `market.schema` it was built against. A market serves its
+//! hash over GraphQL, and the SDK compares it with its own before sending
+//! anything the market would have to decode.
+//!
+//! `market.schema` is generated from the market contract's types, and the
+//! SDK's tests check its own message types against the same trace, so the
+//! hash stands for the layout both sides actually encode.
+//!
+//! It also holds the few types every contract keeps in its state the same
+//! way, such as the replay protection for transfers.
+
+#[cfg(feature = "generate")]
+pub mod generate;
+pub mod sequences;
+
+pub use sequences::{AppliedSequences, MAX_RECENT_SEQUENCES};
+
+include!(concat!(env!("OUT_DIR"), "/schema_hash.rs"));
+
+/// The schema the hash was taken over
+pub const MARKET_SCHEMA: &str = include_str!("../market.schema");
+
+/// `SCHEMA_HASH` as lowercase hex, the form markets serve it in
+pub fn schema_hash_hex() -> String {
+    hex::encode(SCHEMA_HASH)
+}
@@ -0,0 +1,79 @@
+//! Replay protection for numbered messages from one sender.
+//!
+//! Transfers carry their issuer's sequence number, and the chain applying
+//! them must apply each exactly once. Messages from a chain arrive in the
+//! order it sent them, so once `MAX_RECENT_SEQUENCES` are kept the oldest are
+//! folded into a floor: a sequence below it can only be a replay.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Sequences remembered per sender above the contiguous floor
+pub const MAX_RECENT_SEQUENCES: usize = 1_024;
+
+/// Sequences applied from one sender
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AppliedSequences {
+    /// Every sequence below this counts as applied
+    floor: u64,
+    recent: BTreeSet<u64>,
+}
+
+impl AppliedSequences {
+    /// Records `sequence`; `false` if it was already applied
+    pub fn insert(&mut self, sequence: u64) -> bool {
+        if sequence < self.floor || !self.recent.insert(sequence) {
+            return false;
+        }
+        while self.recent.first() == Some(&self.floor) {
+            self.recent.pop_first();
+            self.floor += 1;
+        }
+        while self.recent.len() > MAX_RECENT_SEQUENCES {
+            if let Some(oldest) = self.recent.pop_first() {
+                self.floor = oldest + 1;
+            }
+        }
+        true
+    }
+
+    pub fn contains(&self, sequence: u64) -> bool {
+        sequence < self.floor || self.recent.contains(&sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contiguous_sequences_advance_the_floor() {
+        let mut applied = AppliedSequences::default();
+        assert!(applied.insert(1));
+        assert_eq!(applied.floor, 0);
+        assert!(applied.insert(0));
+        // 0 and 1 are both folded in, leaving nothing to remember
+        assert_eq!(applied.floor, 2);
+        assert!(applied.recent.is_empty());
+        assert!(!applied.insert(0));
+        assert!(!applied.insert(1));
+        assert!(applied.contains(1));
+        assert!(!applied.contains(2));
+    }
+
+    #[test]
+    fn test_gaps_past_the_limit_fold_into_the_floor() {
+        let mut applied = AppliedSequences::default();
+        // Sequence 0 never arrives, so nothing is contiguous
+        for sequence in 1..=MAX_RECENT_SEQUENCES as u64 + 1 {
+            assert!(applied.insert(sequence));
+        }
+        assert_eq!(applied.recent.len(), MAX_RECENT_SEQUENCES);
+        assert_eq!(applied.floor, 2);
+        // The missing 0 is now below the floor and counts as a replay
+        assert!(!applied.insert(0));
+        assert!(applied.contains(0));
+        assert!(!applied.insert(MAX_RECENT_SEQUENCES as u64 + 1));
+        assert!(applied.insert(MAX_RECENT_SEQUENCES as u64 + 2));
+    }
+}
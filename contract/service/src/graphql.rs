@@ -0,0 +1,444 @@
+// Read-only GraphQL extension served by the registry chain
+use crate::roles::Role;
+use crate::treasury::TreasuryEntryKind;
+use crate::{ActivityKind, OracleType, RegistryState, TraderStats, MICROS_PER_DAY};
+use async_graphql::{EmptyMutation, EmptySubscription, Enum, Object, Schema, SimpleObject};
+use linera_sdk::{base::ChainId, service::system_api};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::Arc;
+
+const ATTOS_PER_TOKEN: f64 = 1e18;
+const DEFAULT_LEADERBOARD_SIZE: usize = 100;
+const MAX_ACTIVITY_PAGE: usize = 200;
+const MAX_REGISTRATION_PAGE: usize = 500;
+
+pub type RegistrySchema = Schema<RegistryQueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(state: Arc<RegistryState>) -> RegistrySchema {
+    Schema::build(RegistryQueryRoot { state }, EmptyMutation, EmptySubscription).finish()
+}
+
+pub struct RegistryQueryRoot {
+    state: Arc<RegistryState>,
+}
+
+// Where a market lives, for operators debugging cross-chain routing
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct MarketChainInfo {
+    pub market_id: String,
+    pub chain_id: String,
+    pub application_id: String,
+    pub contract_version: String,
+    pub created_block: u64,
+    pub block_height: u64,
+    pub validators: Vec<String>,
+    // Set for conditional markets
+    pub parent_market_id: Option<String>,
+    pub activates_on: Option<bool>,
+    // Set once the market has retired its chain; the hex commitment to its final state
+    pub archived_at: Option<u64>,
+    pub snapshot_hash: Option<String>,
+}
+
+// One registered market, without the per-chain lookups of `MarketChainInfo`
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct MarketEntry {
+    pub market_id: String,
+    pub chain_id: String,
+    pub category: Option<String>,
+    pub created_block: u64,
+    pub archived_at: Option<u64>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum LeaderboardPeriod {
+    Day,
+    Week,
+    Month,
+    AllTime,
+}
+
+impl LeaderboardPeriod {
+    fn days(self) -> Option<u64> {
+        match self {
+            LeaderboardPeriod::Day => Some(1),
+            LeaderboardPeriod::Week => Some(7),
+            LeaderboardPeriod::Month => Some(30),
+            LeaderboardPeriod::AllTime => None,
+        }
+    }
+}
+
+// One trader's standing; ranked by realized P&L
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub user_chain_id: String,
+    pub volume: String,
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub win_rate: f64,
+    pub realized_pnl: f64,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ActivityEntry {
+    pub sequence: u64,
+    pub kind: String,
+    pub market_id: String,
+    pub actor: Option<String>,
+    pub amount: Option<String>,
+    pub detail: String,
+    pub timestamp: u64,
+}
+
+// Newest first; pass `next_cursor` back as `cursor` to continue
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ActivityPage {
+    pub events: Vec<ActivityEntry>,
+    pub next_cursor: Option<u64>,
+}
+
+// Treasury holdings in one settlement token
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TreasuryBalance {
+    // Null for the native token
+    pub token: Option<String>,
+    pub balance: String,
+    pub inflows: String,
+    pub withdrawn: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TreasuryEntryInfo {
+    pub sequence: u64,
+    pub kind: String,
+    pub market_id: Option<String>,
+    pub recipient: Option<String>,
+    pub token: Option<String>,
+    pub amount: String,
+    pub timestamp: u64,
+}
+
+// Newest first; pass `next_cursor` back as `cursor` to continue
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TreasuryPage {
+    pub entries: Vec<TreasuryEntryInfo>,
+    pub next_cursor: Option<u64>,
+}
+
+// Markets a user chain has traded in, ascending by ID; pass `next_cursor` back as `cursor` to continue
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct UserMarketsPage {
+    pub market_ids: Vec<String>,
+    pub next_cursor: Option<String>,
+}
+
+// User chains that have traded in a market, ascending; pass `next_cursor` back as `cursor` to continue
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ParticipantsPage {
+    pub user_chain_ids: Vec<String>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ProtocolStatus {
+    pub paused: bool,
+    pub reason: Option<String>,
+    pub paused_at: Option<u64>,
+}
+
+// Roles granted to one signer; the chain's own owners hold every role without a grant
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct RoleGrantInfo {
+    pub owner: String,
+    pub roles: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TemplateInfo {
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub oracle: String,
+    pub taker_fee_bps: u32,
+    pub initial_liquidity: String,
+    pub resolution_delay_secs: u64,
+}
+
+#[Object]
+impl RegistryQueryRoot {
+    async fn market_templates(&self) -> Vec<TemplateInfo> {
+        self.state
+            .templates
+            .iter()
+            .map(|(name, template)| TemplateInfo {
+                name: name.clone(),
+                description: template.description.clone(),
+                category: template.category.clone(),
+                oracle: match &template.oracle_type {
+                    OracleType::FastTee { .. } => "FAST_TEE",
+                    OracleType::Committee { .. } => "COMMITTEE",
+                    OracleType::Hybrid => "HYBRID",
+                }
+                .to_string(),
+                taker_fee_bps: template.fee_schedule.taker_fee_bps,
+                initial_liquidity: template.amm.initial_liquidity.to_string(),
+                resolution_delay_secs: template.resolution_delay / 1_000_000,
+            })
+            .collect()
+    }
+
+    async fn recent_activity(&self, limit: usize, cursor: Option<u64>) -> ActivityPage {
+        let limit = limit.min(MAX_ACTIVITY_PAGE);
+        let events: Vec<ActivityEntry> = self
+            .state
+            .activity_log
+            .iter()
+            .rev()
+            .filter(|event| cursor.map_or(true, |cursor| event.sequence < cursor))
+            .take(limit)
+            .map(|event| ActivityEntry {
+                sequence: event.sequence,
+                kind: match event.kind {
+                    ActivityKind::MarketCreated => "MARKET_CREATED",
+                    ActivityKind::LargeTrade => "LARGE_TRADE",
+                    ActivityKind::Resolved => "RESOLVED",
+                    ActivityKind::Disputed => "DISPUTED",
+                    ActivityKind::Cancelled => "CANCELLED",
+                    ActivityKind::OracleUpdated => "ORACLE_UPDATED",
+                    ActivityKind::Archived => "ARCHIVED",
+                }
+                .to_string(),
+                market_id: event.market_id.clone(),
+                actor: event.actor.map(|actor| actor.to_string()),
+                amount: event.amount.map(|amount| amount.to_string()),
+                detail: event.detail.clone(),
+                timestamp: event.timestamp,
+            })
+            .collect();
+
+        // A short page means the log is exhausted
+        let next_cursor = if events.len() == limit {
+            events.last().map(|event| event.sequence)
+        } else {
+            None
+        };
+        ActivityPage { events, next_cursor }
+    }
+
+    async fn treasury(&self) -> Vec<TreasuryBalance> {
+        self.state
+            .treasury
+            .totals
+            .iter()
+            .map(|(token, totals)| TreasuryBalance {
+                token: token.map(|token| token.to_string()),
+                balance: totals.balance.to_string(),
+                inflows: totals.inflows.to_string(),
+                withdrawn: totals.withdrawn.to_string(),
+            })
+            .collect()
+    }
+
+    // Fee sweeps from markets and governance withdrawals
+    async fn treasury_history(&self, limit: usize, cursor: Option<u64>) -> TreasuryPage {
+        let limit = limit.min(MAX_ACTIVITY_PAGE);
+        let entries: Vec<TreasuryEntryInfo> = self
+            .state
+            .treasury
+            .history
+            .iter()
+            .rev()
+            .filter(|entry| cursor.map_or(true, |cursor| entry.sequence < cursor))
+            .take(limit)
+            .map(|entry| TreasuryEntryInfo {
+                sequence: entry.sequence,
+                kind: match entry.kind {
+                    TreasuryEntryKind::Inflow => "INFLOW",
+                    TreasuryEntryKind::Withdrawal => "WITHDRAWAL",
+                }
+                .to_string(),
+                market_id: entry.market_id.clone(),
+                recipient: entry.recipient.map(|recipient| recipient.to_string()),
+                token: entry.token.map(|token| token.to_string()),
+                amount: entry.amount.to_string(),
+                timestamp: entry.timestamp,
+            })
+            .collect();
+
+        let next_cursor = if entries.len() == limit {
+            entries.last().map(|entry| entry.sequence)
+        } else {
+            None
+        };
+        TreasuryPage { entries, next_cursor }
+    }
+
+    // Both directions are kept as ordered sets, so a page is a range read rather than a scan
+    async fn user_markets(&self, user_chain_id: String, limit: usize, cursor: Option<String>) -> UserMarketsPage {
+        let limit = limit.min(MAX_REGISTRATION_PAGE);
+        let registered = user_chain_id
+            .parse::<ChainId>()
+            .ok()
+            .and_then(|user_chain_id| self.state.user_registrations.get(&user_chain_id));
+        let start = cursor.as_ref().map_or(Bound::Unbounded, Bound::Excluded);
+        let market_ids: Vec<String> = registered
+            .into_iter()
+            .flat_map(|markets| markets.range::<String, _>((start, Bound::Unbounded)))
+            .take(limit)
+            .cloned()
+            .collect();
+
+        // A short page means the set is exhausted
+        let next_cursor = if market_ids.len() == limit { market_ids.last().cloned() } else { None };
+        UserMarketsPage { market_ids, next_cursor }
+    }
+
+    async fn market_participants(&self, market_id: String, limit: usize, cursor: Option<String>) -> ParticipantsPage {
+        let limit = limit.min(MAX_REGISTRATION_PAGE);
+        let start = match cursor.map(|cursor| cursor.parse::<ChainId>()) {
+            Some(Ok(cursor)) => Bound::Excluded(cursor),
+            Some(Err(_)) => return ParticipantsPage { user_chain_ids: Vec::new(), next_cursor: None },
+            None => Bound::Unbounded,
+        };
+        let user_chain_ids: Vec<String> = self
+            .state
+            .market_participants
+            .get(&market_id)
+            .into_iter()
+            .flat_map(|participants| participants.range((start, Bound::Unbounded)))
+            .take(limit)
+            .map(|user_chain_id| user_chain_id.to_string())
+            .collect();
+
+        let next_cursor = if user_chain_ids.len() == limit { user_chain_ids.last().cloned() } else { None };
+        ParticipantsPage { user_chain_ids, next_cursor }
+    }
+
+    async fn protocol_status(&self) -> ProtocolStatus {
+        let pause = self.state.pause.as_ref();
+        ProtocolStatus {
+            paused: pause.is_some(),
+            reason: pause.map(|pause| pause.reason.clone()),
+            paused_at: pause.map(|pause| pause.paused_at),
+        }
+    }
+
+    async fn role_grants(&self) -> Vec<RoleGrantInfo> {
+        self.state
+            .roles
+            .grants
+            .iter()
+            .map(|(owner, roles)| RoleGrantInfo {
+                owner: owner.to_string(),
+                roles: roles
+                    .iter()
+                    .map(|role| match role {
+                        Role::Owner => "OWNER",
+                        Role::Operator => "OPERATOR",
+                        Role::OracleAdmin => "ORACLE_ADMIN",
+                    })
+                    .map(str::to_string)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    async fn leaderboard(&self, period: LeaderboardPeriod, limit: Option<usize>) -> Vec<LeaderboardEntry> {
+        let today = system_api::current_system_time().micros() / MICROS_PER_DAY;
+        let since = period.days().map_or(0, |days| today.saturating_sub(days - 1));
+
+        let mut totals: BTreeMap<ChainId, TraderStats> = BTreeMap::new();
+        for ((user_chain_id, day), stats) in &self.state.trader_stats {
+            if *day >= since && !self.state.leaderboard_hidden.contains(user_chain_id) {
+                totals.entry(*user_chain_id).or_default().merge(stats);
+            }
+        }
+
+        let mut ranked: Vec<_> = totals.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.realized_pnl.cmp(&a.1.realized_pnl));
+        ranked
+            .into_iter()
+            .take(limit.unwrap_or(DEFAULT_LEADERBOARD_SIZE))
+            .enumerate()
+            .map(|(index, (user_chain_id, stats))| {
+                let settled = stats.wins + stats.losses;
+                LeaderboardEntry {
+                    rank: index as u32 + 1,
+                    user_chain_id: user_chain_id.to_string(),
+                    volume: stats.volume.to_string(),
+                    trades: stats.trades,
+                    wins: stats.wins,
+                    losses: stats.losses,
+                    win_rate: if settled > 0 { stats.wins as f64 / settled as f64 } else { 0.0 },
+                    realized_pnl: stats.realized_pnl as f64 / ATTOS_PER_TOKEN,
+                }
+            })
+            .collect()
+    }
+
+    async fn market_chain_info(&self, market_id: String) -> Option<MarketChainInfo> {
+        let (app_id, chain_id) = self.state.markets.get(&market_id)?;
+        let meta = self.state.market_meta.get(&market_id).cloned().unwrap_or_default();
+        let condition = self.state.market_conditions.get(&market_id);
+
+        Some(MarketChainInfo {
+            market_id,
+            chain_id: chain_id.to_string(),
+            application_id: app_id.to_string(),
+            contract_version: meta.contract_version,
+            created_block: meta.created_block,
+            block_height: system_api::chain_block_height(*chain_id).into(),
+            validators: system_api::current_committee()
+                .validators()
+                .keys()
+                .map(|name| name.to_string())
+                .collect(),
+            parent_market_id: condition.map(|c| c.parent_market_id.clone()),
+            activates_on: condition.map(|c| c.activates_on),
+            archived_at: meta.archive.map(|archive| archive.archived_at),
+            snapshot_hash: meta.archive.map(|archive| hex::encode(archive.snapshot_hash)),
+        })
+    }
+
+    // Every registered market, optionally of one category; archived markets only when asked for
+    async fn market_directory(&self, category: Option<String>, include_archived: Option<bool>) -> Vec<MarketEntry> {
+        let include_archived = include_archived.unwrap_or(false);
+        self.state
+            .markets
+            .iter()
+            .filter_map(|(market_id, (_, chain_id))| {
+                let meta = self.state.market_meta.get(market_id).cloned().unwrap_or_default();
+                if meta.archive.is_some() && !include_archived {
+                    return None;
+                }
+                if category.is_some() && meta.category != category {
+                    return None;
+                }
+                Some(MarketEntry {
+                    market_id: market_id.clone(),
+                    chain_id: chain_id.to_string(),
+                    category: meta.category,
+                    created_block: meta.created_block,
+                    archived_at: meta.archive.map(|archive| archive.archived_at),
+                })
+            })
+            .collect()
+    }
+}
@@ -1,20 +1,242 @@
 use linera_sdk::{
-    base::{ChainId, WithContractAbi, ApplicationId, Owner},
+    base::{Amount, ChainId, WithContractAbi, ApplicationId, Owner},
     contract::system_api,
     ApplicationCallResult, CalleeContext, Contract, ExecutionResult,
-    OperationContext, SessionCallResult, ViewStateStorage,
+    MessageContext, OperationContext, SessionCallResult, ViewStateStorage,
 };
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use serde::ser::SerializeStructVariant;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use thiserror::Error;
+
+pub mod graphql;
+pub mod roles;
+pub mod treasury;
+
+use roles::{Role, RoleGrants};
+use treasury::{TokenCall, Treasury};
 
 // Main registry state - stored on-chain
 #[derive(Default, ViewStateStorage)]
 pub struct RegistryState {
     // Market ID -> (ApplicationId, ChainId)
     pub markets: BTreeMap<String, (ApplicationId, ChainId)>,
-    // User ChainId -> list of markets they participate in
-    pub user_registrations: BTreeMap<ChainId, Vec<String>>,
+    // Market ChainId -> market ID; reverse of `markets` for routing messages from market chains
+    pub market_chains: BTreeMap<ChainId, String>,
+    // User ChainId -> markets they have traded in, ordered so queries can page through them
+    pub user_registrations: BTreeMap<ChainId, BTreeSet<String>>,
+    // Market ID -> user chains that have traded in it; the same relation indexed the other way
+    pub market_participants: BTreeMap<String, BTreeSet<ChainId>>,
+    // Market ID -> deployment metadata exposed through the GraphQL extension
+    pub market_meta: BTreeMap<String, MarketMeta>,
+    // (User chain, day index) -> trading statistics reported by market chains
+    pub trader_stats: BTreeMap<(ChainId, u64), TraderStats>,
+    // User chains that opted out of the public leaderboard
+    pub leaderboard_hidden: BTreeSet<ChainId>,
+    // Explorer feed across all markets, oldest first; trimmed to MAX_ACTIVITY_EVENTS
+    pub activity_log: VecDeque<ActivityEvent>,
+    pub next_activity_sequence: u64,
+    // Template name -> settings for recurring markets
+    pub templates: BTreeMap<String, MarketTemplate>,
+    // Conditional market ID -> the parent outcome it depends on
+    pub market_conditions: BTreeMap<String, MarketCondition>,
+    // Compliance allowlist handed to every market created from now on; `None` leaves them ungated
+    pub allowlist: Option<ApplicationId>,
+    // Yield applications markets may park idle pool funds in
+    pub yield_sources: BTreeSet<ApplicationId>,
+    // Protocol fees swept in from markets, less governance withdrawals
+    pub treasury: Treasury,
+    // Admin roles granted beyond the chain's own owners
+    pub roles: RoleGrants,
+    // Hex key handed to every market created from now on, able to rotate its FastTee oracle key
+    pub oracle_guardian: Option<String>,
+    // Set while governance has halted trading on every market
+    pub pause: Option<PauseInfo>,
+}
+
+pub const MICROS_PER_DAY: u64 = 86_400_000_000;
+pub const MAX_ACTIVITY_EVENTS: usize = 10_000;
+// Event stream every activity entry is emitted on as it is recorded, for subscribers
+pub const ACTIVITY_STREAM: &[u8] = b"activity";
+// Event stream markets subscribe to for protocol-wide pauses
+pub const PAUSE_STREAM: &[u8] = b"pause";
+// Batches costing at least this much show up in the activity feed
+pub const LARGE_TRADE_THRESHOLD: Amount = Amount::from_tokens(1_000);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    MarketCreated,
+    LargeTrade,
+    Resolved,
+    Disputed,
+    Cancelled,
+    OracleUpdated,
+    Archived,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ActivityEvent {
+    // Monotonic; doubles as the feed's pagination cursor
+    pub sequence: u64,
+    pub kind: ActivityKind,
+    pub market_id: String,
+    // Trader or submitter, when there is one
+    pub actor: Option<ChainId>,
+    pub amount: Option<Amount>,
+    pub detail: String,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PauseInfo {
+    pub reason: String,
+    pub paused_at: u64,
+}
+
+// Broadcast on `PAUSE_STREAM` whenever the switch is flipped; markets keep only the latest
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PauseUpdate {
+    pub paused: bool,
+    pub reason: String,
+    pub at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TraderStats {
+    pub volume: Amount,
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    // Signed, in attos
+    pub realized_pnl: i128,
+}
+
+impl TraderStats {
+    pub fn merge(&mut self, other: &TraderStats) {
+        self.volume += other.volume;
+        self.trades += other.trades;
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.realized_pnl += other.realized_pnl;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct MarketMeta {
+    pub contract_version: String,
+    pub created_block: u64,
+    pub category: Option<String>,
+    // Set once the market has retired its chain
+    pub archive: Option<ArchiveRecord>,
+}
+
+// What the registry keeps of a retired market: when, and the market's commitment to its final state
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ArchiveRecord {
+    pub archived_at: u64,
+    pub snapshot_hash: [u8; 32],
+}
+
+// Instantiation argument of the market application
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MarketArgs {
+    pub market_id: String,
+    pub description: String,
+    pub oracle_type: OracleType,
+    pub resolution_time: u64,
+    // Micros before `resolution_time` during which the market refuses orders
+    pub lock_period: u64,
+    // Stages that pay out part of the pool ahead of final resolution
+    pub stages: Vec<StageSpec>,
+    pub fallback: FallbackPolicy,
+    pub fee_schedule: FeeSchedule,
+    pub amm: AmmParams,
+    pub registry_chain: ChainId,
+    pub condition: Option<MarketCondition>,
+    // Fungible token application the market settles in; `None` for the native token
+    pub settlement_token: Option<ApplicationId>,
+    pub circuit_breaker: BreakerConfig,
+    pub auction: AuctionConfig,
+    pub caps: MarketCaps,
+    pub yield_config: YieldConfig,
+    // Set by the registry from its own configuration, not by the caller
+    pub allowlist: Option<ApplicationId>,
+    pub oracle_guardian: Option<String>,
+    // Markets created during a pause start paused
+    pub paused: bool,
+}
+
+// What the caller asks for: settle only if `parent_market_id` resolves to `activates_on`,
+// otherwise refund every trade
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConditionSpec {
+    pub parent_market_id: String,
+    pub activates_on: bool,
+}
+
+// The dependency as stored and handed to the market, with the parent's chain resolved
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MarketCondition {
+    pub parent_market_id: String,
+    pub parent_chain: ChainId,
+    pub activates_on: bool,
+}
+
+// Initial pool seeding; a zero liquidity market starts at even odds
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AmmParams {
+    pub initial_liquidity: Amount,
+    // Opening YES probability in basis points; 0 means 5000
+    pub initial_yes_odds_bps: u32,
+}
+
+// Everything a recurring market shares; only the fixture differs between instances
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MarketTemplate {
+    // `{fixture}` is replaced with the fixture name
+    pub description: String,
+    pub oracle_type: OracleType,
+    pub fee_schedule: FeeSchedule,
+    pub amm: AmmParams,
+    pub category: String,
+    // Micros between the fixture start and market resolution
+    pub resolution_delay: u64,
+    pub settlement_token: Option<ApplicationId>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Fixture {
+    pub name: String,
+    pub starts_at: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("unknown market template {0}")]
+    UnknownTemplate(String),
+    #[error("market {0} already exists")]
+    MarketExists(String),
+    #[error("parent market {0} does not exist")]
+    UnknownParent(String),
+    #[error("stage schedule releases more than the whole pool or has too many stages")]
+    InvalidStages,
+    #[error("fallback policy must refund after escalating and give the committee members")]
+    InvalidFallback,
+    #[error("yield source {0} is not whitelisted")]
+    UnlistedYieldSource(ApplicationId),
+    #[error("yield deposit share exceeds the whole pool")]
+    InvalidYieldDeposit,
+    #[error("fee tiers must start at increasing volumes and charge at most 100%")]
+    InvalidFeeTiers,
+    #[error("treasury holds only {0}")]
+    InsufficientTreasury(Amount),
+    #[error("market {0} does not exist")]
+    UnknownMarket(String),
+    #[error("signer does not hold the {0:?} role")]
+    Unauthorized(Role),
+    #[error("referral, oracle and protocol fee shares add up to more than the whole fee")]
+    InvalidFeeShares,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -24,6 +246,9 @@ pub enum RegistryOperation {
         description: String,
         oracle_type: OracleType,
         resolution_time: u64,
+        fee_schedule: FeeSchedule,
+        condition: Option<ConditionSpec>,
+        settlement_token: Option<ApplicationId>,
     },
     RegisterUserChain {
         user_chain_id: ChainId,
@@ -32,6 +257,146 @@ pub enum RegistryOperation {
         market_id: String,
         new_oracle: OracleType,
     },
+    SaveTemplate {
+        name: String,
+        template: MarketTemplate,
+    },
+    CreateMarketFromTemplate {
+        template: String,
+        fixture: Fixture,
+    },
+    // Provisions many market chains in one block; see `RegistryResponse::MarketsCreated`
+    CreateMarkets {
+        markets: Vec<CreateMarketParams>,
+    },
+    // Gate markets created afterwards behind a KYC/allowlist application; existing markets keep theirs
+    SetAllowlist {
+        allowlist: Option<ApplicationId>,
+    },
+    // Replace the whitelist of yield sources; markets already depositing keep their source
+    SetYieldSources {
+        sources: Vec<ApplicationId>,
+    },
+    // Pay out of the treasury; needs the `Owner` role
+    WithdrawTreasury {
+        token: Option<ApplicationId>,
+        amount: Amount,
+        recipient: ChainId,
+    },
+    // Key that may rotate the oracle key of markets created afterwards; existing markets keep theirs
+    SetOracleGuardian {
+        public_key: Option<String>,
+    },
+    // Stop (or resume) order intake on every market; cancels and claims keep working
+    SetPaused {
+        paused: bool,
+        reason: String,
+    },
+    GrantRole {
+        owner: Owner,
+        role: Role,
+    },
+    RevokeRole {
+        owner: Owner,
+        role: Role,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreateMarketParams {
+    pub market_id: String,
+    pub description: String,
+    pub oracle_type: OracleType,
+    pub resolution_time: u64,
+    pub fee_schedule: FeeSchedule,
+    pub amm: AmmParams,
+    pub category: Option<String>,
+    pub condition: Option<ConditionSpec>,
+    pub settlement_token: Option<ApplicationId>,
+    pub circuit_breaker: BreakerConfig,
+    pub auction: AuctionConfig,
+    pub lock_period: u64,
+    pub stages: Vec<StageSpec>,
+    pub caps: MarketCaps,
+    pub fallback: FallbackPolicy,
+    pub yield_config: YieldConfig,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub enum RegistryResponse {
+    #[default]
+    Done,
+    // Per-market result of a bulk creation, in request order
+    MarketsCreated(Vec<MarketCreationOutcome>),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MarketCreationOutcome {
+    pub market_id: String,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum RegistryMessage {
+    // Fills reported by a market chain after each batch
+    TradesExecuted {
+        user_chain_id: ChainId,
+        volume: Amount,
+        trades: u64,
+    },
+    // Outcome of every position once a market resolves
+    MarketSettled {
+        outcome: bool,
+        results: Vec<TraderResult>,
+    },
+    // Sent by a user chain to hide itself from (or show itself on) the leaderboard
+    SetLeaderboardPrivacy {
+        user_chain_id: ChainId,
+        hidden: bool,
+    },
+    // Dispute evidence attached to a market
+    DisputeFiled {
+        submitter: ChainId,
+        uri: String,
+    },
+    // A market's accrued protocol fees, transferred alongside this message
+    ProtocolFeesCollected {
+        amount: Amount,
+        token: Option<ApplicationId>,
+    },
+    // The market was voided: its parent activated the other way, or nobody resolved it in time
+    MarketCancelled,
+    // The market's grace period is over; it has committed to its final state and closed its chain
+    MarketArchived {
+        snapshot_hash: [u8; 32],
+    },
+    // The market applied an `UpdateOracle` and resolves through `oracle_type` from now on
+    OracleUpdated {
+        oracle_type: OracleType,
+    },
+}
+
+// The market's `UpdateOracle` message. The registry sends markets nothing else, so only this
+// variant is mirrored and its BCS tag is written out.
+pub struct UpdateMarketOracle {
+    pub oracle_type: OracleType,
+}
+
+const UPDATE_ORACLE_TAG: u32 = 41;
+
+impl Serialize for UpdateMarketOracle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut variant = serializer.serialize_struct_variant("MarketMessage", UPDATE_ORACLE_TAG, "UpdateOracle", 1)?;
+        variant.serialize_field("oracle_type", &self.oracle_type)?;
+        variant.end()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TraderResult {
+    pub user_chain_id: ChainId,
+    pub cost: Amount,
+    pub payout: Amount,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -41,6 +406,84 @@ pub enum OracleType {
     Hybrid,
 }
 
+// Mirrors the market contract's circuit breaker settings; the default disables it
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct BreakerConfig {
+    pub max_move_bps: u32,
+    pub window_blocks: u64,
+    pub cooldown: u64,
+}
+
+// Mirrors the market contract's stage schedule entry
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StageSpec {
+    pub name: String,
+    pub share_bps: u32,
+}
+
+// Mirrors the market contract's limits on a stage schedule
+const MAX_STAGES: usize = 32;
+const FULL_PAYOUT_BPS: u64 = 10_000;
+
+// Mirrors the market contract's deadlines for an oracle that never reports; the default waits forever
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct FallbackPolicy {
+    pub escalate_after: Option<u64>,
+    pub committee_size: u32,
+    pub refund_after: Option<u64>,
+}
+
+// Mirrors the market contract's open-interest caps; the default leaves the market uncapped
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct MarketCaps {
+    pub max_pool: Option<Amount>,
+    pub max_user_exposure: Option<Amount>,
+}
+
+// Mirrors the market contract's yield settings; the default keeps all funds on the market chain
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct YieldConfig {
+    pub source: Option<ApplicationId>,
+    pub deposit_bps: u32,
+    pub beneficiary: YieldBeneficiary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum YieldBeneficiary {
+    #[default]
+    Providers,
+    Treasury,
+}
+
+// Mirrors the market contract's call auction settings; the default trades continuously from creation
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AuctionConfig {
+    pub opening_duration: u64,
+    pub reopen_after_halt: bool,
+}
+
+// Mirrors the market contract's fee schedule, passed through at creation
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct FeeSchedule {
+    pub taker_fee_bps: u32,
+    pub referral_share_bps: u32,
+    pub oracle_share_bps: u32,
+    pub protocol_share_bps: u32,
+    pub lp_cooldown: u64,
+    pub early_exit_fee_bps: u32,
+    #[serde(default)]
+    pub maker_fee_bps: u32,
+    #[serde(default)]
+    pub tiers: Vec<FeeTier>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FeeTier {
+    pub min_volume: Amount,
+    pub taker_fee_bps: u32,
+    pub maker_fee_bps: u32,
+}
+
 pub struct OddsStreamService {
     state: RegistryState,
 }
@@ -48,7 +491,8 @@ pub struct OddsStreamService {
 #[async_trait]
 impl Contract for OddsStreamService {
     type Operation = RegistryOperation;
-    type Response = ();
+    type Message = RegistryMessage;
+    type Response = RegistryResponse;
 
     async fn execute_operation(
         &mut self,
@@ -60,40 +504,267 @@ impl Contract for OddsStreamService {
                 description,
                 oracle_type,
                 resolution_time,
+                fee_schedule,
+                condition,
+                settlement_token,
             } => {
-                // 1. Create new microchain for this market
-                let market_chain_id = system_api::create_chain(Owner::None).await?;
-                
-                // 2. Prepare market initialization arguments
+                self.authorize(context.authenticated_signer, Role::Operator)?;
+                let condition = self.resolve_condition(condition)?;
                 let market_args = MarketArgs {
-                    market_id: market_id.clone(),
+                    market_id,
                     description,
-                    oracle_type: oracle_type.clone(),
+                    oracle_type,
                     resolution_time,
+                    lock_period: 0,
+                    stages: Vec::new(),
+                    fallback: FallbackPolicy::default(),
+                    fee_schedule,
+                    amm: AmmParams::default(),
+                    registry_chain: context.chain_id,
+                    condition,
+                    settlement_token,
+                    circuit_breaker: BreakerConfig::default(),
+                    auction: AuctionConfig::default(),
+                    caps: MarketCaps::default(),
+                    yield_config: YieldConfig::default(),
+                    allowlist: None,
+                    oracle_guardian: None,
+                    paused: false,
+                };
+                self.create_market(market_args, None).await?;
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::CreateMarkets { markets } => {
+                self.authorize(context.authenticated_signer, Role::Operator)?;
+                // One failing market must not abort the rest of the batch
+                let mut outcomes = Vec::with_capacity(markets.len());
+                for params in markets {
+                    let market_id = params.market_id.clone();
+                    let condition = match self.resolve_condition(params.condition) {
+                        Ok(condition) => condition,
+                        Err(e) => {
+                            outcomes.push(MarketCreationOutcome { market_id, error: Some(e.to_string()) });
+                            continue;
+                        }
+                    };
+                    let market_args = MarketArgs {
+                        market_id: params.market_id,
+                        description: params.description,
+                        oracle_type: params.oracle_type,
+                        resolution_time: params.resolution_time,
+                        lock_period: params.lock_period,
+                        stages: params.stages,
+                        fallback: params.fallback,
+                        fee_schedule: params.fee_schedule,
+                        amm: params.amm,
+                        registry_chain: context.chain_id,
+                        condition,
+                        settlement_token: params.settlement_token,
+                        circuit_breaker: params.circuit_breaker,
+                        auction: params.auction,
+                        caps: params.caps,
+                        yield_config: params.yield_config,
+                        allowlist: None,
+                        oracle_guardian: None,
+                        paused: false,
+                    };
+                    let error = self.create_market(market_args, params.category).await.err();
+                    outcomes.push(MarketCreationOutcome {
+                        market_id,
+                        error: error.map(|e| e.to_string()),
+                    });
+                }
+                Ok(RegistryResponse::MarketsCreated(outcomes))
+            }
+            RegistryOperation::SetAllowlist { allowlist } => {
+                self.authorize(context.authenticated_signer, Role::Operator)?;
+                self.state.allowlist = allowlist;
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::SetYieldSources { sources } => {
+                self.authorize(context.authenticated_signer, Role::Operator)?;
+                self.state.yield_sources = sources.into_iter().collect();
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::WithdrawTreasury { token, amount, recipient } => {
+                self.authorize(context.authenticated_signer, Role::Owner)?;
+                let now = system_api::current_system_time().micros();
+                let held = self.held(token);
+                self.state
+                    .treasury
+                    .withdraw(token, amount, recipient, held, now)
+                    .map_err(RegistryError::InsufficientTreasury)?;
+                match token {
+                    None => system_api::transfer(recipient, amount),
+                    Some(token) => system_api::call_application(token, &TokenCall::Transfer { to: recipient, amount }),
+                }
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::SaveTemplate { name, template } => {
+                self.authorize(context.authenticated_signer, Role::Operator)?;
+                self.state.templates.insert(name, template);
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::CreateMarketFromTemplate { template, fixture } => {
+                self.authorize(context.authenticated_signer, Role::Operator)?;
+                let stored = self
+                    .state
+                    .templates
+                    .get(&template)
+                    .cloned()
+                    .ok_or_else(|| RegistryError::UnknownTemplate(template.clone()))?;
+                let market_args = MarketArgs {
+                    market_id: format!("{}-{}", template, slugify(&fixture.name)),
+                    description: stored.description.replace("{fixture}", &fixture.name),
+                    oracle_type: stored.oracle_type,
+                    resolution_time: fixture.starts_at + stored.resolution_delay,
+                    // Stop trading at kick-off; the outcome starts to be known from then
+                    lock_period: stored.resolution_delay,
+                    stages: Vec::new(),
+                    fallback: FallbackPolicy::default(),
+                    fee_schedule: stored.fee_schedule,
+                    amm: stored.amm,
                     registry_chain: context.chain_id,
+                    condition: None,
+                    settlement_token: stored.settlement_token,
+                    circuit_breaker: BreakerConfig::default(),
+                    auction: AuctionConfig::default(),
+                    caps: MarketCaps::default(),
+                    yield_config: YieldConfig::default(),
+                    allowlist: None,
+                    oracle_guardian: None,
+                    paused: false,
                 };
-                
-                // 3. Publish market application on the new chain
-                let app_id = system_api::create_application(
-                    market_chain_id,
-                    MARKET_BYTECODE_ID, // You'll set this after publishing
-                    &market_args,
-                ).await?;
-                
-                // 4. Store in registry
-                self.state.markets.insert(market_id, (app_id, market_chain_id));
-                
-                Ok(())
+                self.create_market(market_args, Some(stored.category)).await?;
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::UpdateOracle { market_id, new_oracle } => {
+                self.authorize(context.authenticated_signer, Role::OracleAdmin)?;
+                let (_, market_chain) = self
+                    .state
+                    .markets
+                    .get(&market_id)
+                    .ok_or_else(|| RegistryError::UnknownMarket(market_id.clone()))?;
+                // Logged once the market reports it applied the change; a settled market ignores it
+                system_api::send_message(*market_chain, &UpdateMarketOracle { oracle_type: new_oracle });
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::SetOracleGuardian { public_key } => {
+                self.authorize(context.authenticated_signer, Role::Owner)?;
+                self.state.oracle_guardian = public_key;
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::SetPaused { paused, reason } => {
+                self.authorize(context.authenticated_signer, Role::Owner)?;
+                let now = system_api::current_system_time().micros();
+                self.state.pause = paused.then(|| PauseInfo { reason: reason.clone(), paused_at: now });
+                system_api::emit(PAUSE_STREAM, &PauseUpdate { paused, reason, at: now });
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::GrantRole { owner, role } => {
+                self.authorize(context.authenticated_signer, Role::Owner)?;
+                self.state.roles.grant(owner, role);
+                Ok(RegistryResponse::Done)
+            }
+            RegistryOperation::RevokeRole { owner, role } => {
+                self.authorize(context.authenticated_signer, Role::Owner)?;
+                self.state.roles.revoke(&owner, role);
+                Ok(RegistryResponse::Done)
             }
             RegistryOperation::RegisterUserChain { user_chain_id } => {
                 self.state.user_registrations.entry(user_chain_id)
-                    .or_insert_with(Vec::new);
-                Ok(())
+                    .or_default();
+                Ok(RegistryResponse::Done)
             }
-            _ => Ok(()),
         }
     }
 
+    async fn execute_message(
+        &mut self,
+        context: &MessageContext,
+        message: Self::Message,
+    ) -> ExecutionResult<Self::Response> {
+        let origin = context.message_id.chain_id;
+        let today = system_api::current_system_time().micros() / MICROS_PER_DAY;
+        
+        match message {
+            RegistryMessage::TradesExecuted { user_chain_id, volume, trades } => {
+                // Only market chains created by this registry may report trades
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    self.register_participant(user_chain_id, &market_id);
+                    let stats = self.state.trader_stats.entry((user_chain_id, today)).or_default();
+                    stats.volume += volume;
+                    stats.trades += trades;
+                    if volume >= LARGE_TRADE_THRESHOLD {
+                        let detail = format!("{trades} orders");
+                        self.record_activity(ActivityKind::LargeTrade, market_id, Some(user_chain_id), Some(volume), detail);
+                    }
+                }
+            }
+            RegistryMessage::MarketSettled { outcome, results } => {
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    let detail = if outcome { "YES" } else { "NO" }.to_string();
+                    self.record_activity(ActivityKind::Resolved, market_id, None, None, detail);
+                    for result in results {
+                        let stats = self.state.trader_stats.entry((result.user_chain_id, today)).or_default();
+                        if result.payout > result.cost {
+                            stats.wins += 1;
+                        } else {
+                            stats.losses += 1;
+                        }
+                        stats.realized_pnl += u128::from(result.payout) as i128 - u128::from(result.cost) as i128;
+                    }
+                }
+            }
+            RegistryMessage::SetLeaderboardPrivacy { user_chain_id, hidden } => {
+                if origin == user_chain_id {
+                    if hidden {
+                        self.state.leaderboard_hidden.insert(user_chain_id);
+                    } else {
+                        self.state.leaderboard_hidden.remove(&user_chain_id);
+                    }
+                }
+            }
+            RegistryMessage::DisputeFiled { submitter, uri } => {
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    self.record_activity(ActivityKind::Disputed, market_id, Some(submitter), None, uri);
+                }
+            }
+            RegistryMessage::MarketCancelled => {
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    self.record_activity(ActivityKind::Cancelled, market_id, None, None, String::new());
+                }
+            }
+            RegistryMessage::MarketArchived { snapshot_hash } => {
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    let archived_at = system_api::current_system_time().micros();
+                    let meta = self.state.market_meta.entry(market_id.clone()).or_default();
+                    meta.archive = Some(ArchiveRecord { archived_at, snapshot_hash });
+                    self.record_activity(ActivityKind::Archived, market_id, None, None, hex::encode(snapshot_hash));
+                }
+            }
+            RegistryMessage::OracleUpdated { oracle_type } => {
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    let detail = match oracle_type {
+                        OracleType::FastTee { .. } => "FAST_TEE".to_string(),
+                        OracleType::Committee { member_count } => format!("COMMITTEE:{member_count}"),
+                        OracleType::Hybrid => "HYBRID".to_string(),
+                    };
+                    self.record_activity(ActivityKind::OracleUpdated, market_id, None, None, detail);
+                }
+            }
+            RegistryMessage::ProtocolFeesCollected { amount, token } => {
+                if let Some(market_id) = self.market_of_chain(origin) {
+                    let now = system_api::current_system_time().micros();
+                    // The market pays before it reports, so the funds are already on this chain
+                    let held = self.held(token);
+                    self.state.treasury.record_inflow(market_id, token, amount, held, now);
+                }
+            }
+        }
+        Ok(RegistryResponse::Done)
+    }
+
     async fn handle_application_call(
         &mut self,
         _call: (),
@@ -102,4 +773,167 @@ impl Contract for OddsStreamService {
     ) -> ApplicationCallResult<Self::Response> {
         Ok((vec![], None))
     }
-}
\ No newline at end of file
+}
+
+impl OddsStreamService {
+    async fn create_market(&mut self, mut market_args: MarketArgs, category: Option<String>) -> ExecutionResult<()> {
+        let market_id = market_args.market_id.clone();
+        if self.state.markets.contains_key(&market_id) {
+            return Err(RegistryError::MarketExists(market_id).into());
+        }
+        // The market would silently drop an invalid schedule, so refuse it here instead
+        let released: u64 = market_args.stages.iter().map(|stage| u64::from(stage.share_bps)).sum();
+        if market_args.stages.len() > MAX_STAGES || released > FULL_PAYOUT_BPS {
+            return Err(RegistryError::InvalidStages.into());
+        }
+        let fallback = &market_args.fallback;
+        let refunds_after_escalating = match (fallback.escalate_after, fallback.refund_after) {
+            (Some(escalate), Some(refund)) => escalate < refund,
+            _ => true,
+        };
+        if !refunds_after_escalating || (fallback.escalate_after.is_some() && fallback.committee_size == 0) {
+            return Err(RegistryError::InvalidFallback.into());
+        }
+        if let Some(source) = market_args.yield_config.source {
+            if !self.state.yield_sources.contains(&source) {
+                return Err(RegistryError::UnlistedYieldSource(source).into());
+            }
+        }
+        if u64::from(market_args.yield_config.deposit_bps) > FULL_PAYOUT_BPS {
+            return Err(RegistryError::InvalidYieldDeposit.into());
+        }
+        let tiers = &market_args.fee_schedule.tiers;
+        let increasing = tiers.windows(2).all(|pair| pair[0].min_volume < pair[1].min_volume);
+        let bounded = tiers
+            .iter()
+            .all(|tier| u64::from(tier.taker_fee_bps.max(tier.maker_fee_bps)) <= FULL_PAYOUT_BPS);
+        if !increasing || !bounded {
+            return Err(RegistryError::InvalidFeeTiers.into());
+        }
+        let fees = &market_args.fee_schedule;
+        let shares = [fees.referral_share_bps, fees.oracle_share_bps, fees.protocol_share_bps];
+        if shares.iter().map(|share| u64::from(*share)).sum::<u64>() > FULL_PAYOUT_BPS {
+            return Err(RegistryError::InvalidFeeShares.into());
+        }
+        
+        market_args.allowlist = self.state.allowlist;
+        market_args.oracle_guardian = self.state.oracle_guardian.clone();
+        market_args.paused = self.state.pause.is_some();
+        
+        // 1. Create new microchain for this market
+        let market_chain_id = system_api::create_chain(Owner::None).await?;
+        
+        // 2. Publish market application on the new chain
+        let app_id = system_api::create_application(
+            market_chain_id,
+            MARKET_BYTECODE_ID, // You'll set this after publishing
+            &market_args,
+        ).await?;
+        
+        // 3. Store in registry
+        self.state.markets.insert(market_id.clone(), (app_id, market_chain_id));
+        self.state.market_chains.insert(market_chain_id, market_id.clone());
+        if let Some(condition) = market_args.condition {
+            self.state.market_conditions.insert(market_id.clone(), condition);
+        }
+        self.record_activity(ActivityKind::MarketCreated, market_id.clone(), None, None, String::new());
+        // Registry and market bytecode are published together, so they share a version
+        self.state.market_meta.insert(market_id, MarketMeta {
+            contract_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_block: system_api::current_block_height().into(),
+            category,
+            archive: None,
+        });
+        
+        Ok(())
+    }
+    
+    // Chain owners pass every check; other signers need the role, or `Owner`
+    fn authorize(&self, signer: Option<Owner>, role: Role) -> Result<(), RegistryError> {
+        if system_api::assert_owner(signer).is_ok() {
+            return Ok(());
+        }
+        match signer {
+            Some(signer) if self.state.roles.has(&signer, role) => Ok(()),
+            _ => Err(RegistryError::Unauthorized(role)),
+        }
+    }
+    
+    fn resolve_condition(&self, spec: Option<ConditionSpec>) -> Result<Option<MarketCondition>, RegistryError> {
+        let Some(spec) = spec else {
+            return Ok(None);
+        };
+        let (_, parent_chain) = self
+            .state
+            .markets
+            .get(&spec.parent_market_id)
+            .ok_or_else(|| RegistryError::UnknownParent(spec.parent_market_id.clone()))?;
+        Ok(Some(MarketCondition {
+            parent_chain: *parent_chain,
+            parent_market_id: spec.parent_market_id,
+            activates_on: spec.activates_on,
+        }))
+    }
+    
+    // What this chain actually holds of a settlement token; backs the treasury balance
+    fn held(&self, token: Option<ApplicationId>) -> Amount {
+        match token {
+            None => system_api::current_chain_balance(),
+            Some(token) => {
+                let owner = system_api::current_chain_id();
+                system_api::call_application(token, &TokenCall::Balance { owner })
+            }
+        }
+    }
+    
+    fn market_of_chain(&self, chain_id: ChainId) -> Option<String> {
+        self.state.market_chains.get(&chain_id).cloned()
+    }
+    
+    fn register_participant(&mut self, user_chain_id: ChainId, market_id: &str) {
+        self.state
+            .user_registrations
+            .entry(user_chain_id)
+            .or_default()
+            .insert(market_id.to_string());
+        self.state
+            .market_participants
+            .entry(market_id.to_string())
+            .or_default()
+            .insert(user_chain_id);
+    }
+    
+    fn record_activity(
+        &mut self,
+        kind: ActivityKind,
+        market_id: String,
+        actor: Option<ChainId>,
+        amount: Option<Amount>,
+        detail: String,
+    ) {
+        let event = ActivityEvent {
+            sequence: self.state.next_activity_sequence,
+            kind,
+            market_id,
+            actor,
+            amount,
+            detail,
+            timestamp: system_api::current_system_time().micros(),
+        };
+        self.state.next_activity_sequence += 1;
+        system_api::emit(ACTIVITY_STREAM, &event);
+        self.state.activity_log.push_back(event);
+        if self.state.activity_log.len() > MAX_ACTIVITY_EVENTS {
+            self.state.activity_log.pop_front();
+        }
+    }
+}
+
+// "Arsenal vs Chelsea" -> "arsenal-vs-chelsea"
+fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
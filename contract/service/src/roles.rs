@@ -0,0 +1,45 @@
+// Access control for admin operations. The chain's own owners hold every role; anyone else
+// needs a grant, and only holders of `Owner` may grant or revoke.
+use linera_sdk::base::Owner;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    // Manages roles, the oracle guardian, the protocol pause and the treasury; implies every other role
+    Owner,
+    // Creates markets and configures what they get: templates, allowlist, yield sources
+    Operator,
+    // Moves markets that haven't settled to another oracle with `UpdateOracle`
+    OracleAdmin,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct RoleGrants {
+    pub grants: BTreeMap<Owner, BTreeSet<Role>>,
+}
+
+impl RoleGrants {
+    pub fn has(&self, owner: &Owner, role: Role) -> bool {
+        self.grants
+            .get(owner)
+            .is_some_and(|roles| roles.contains(&role) || roles.contains(&Role::Owner))
+    }
+
+    // Whether the grant is new
+    pub fn grant(&mut self, owner: Owner, role: Role) -> bool {
+        self.grants.entry(owner).or_default().insert(role)
+    }
+
+    // Whether the owner held the role
+    pub fn revoke(&mut self, owner: &Owner, role: Role) -> bool {
+        let Some(roles) = self.grants.get_mut(owner) else {
+            return false;
+        };
+        let held = roles.remove(&role);
+        if roles.is_empty() {
+            self.grants.remove(owner);
+        }
+        held
+    }
+}
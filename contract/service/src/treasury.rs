@@ -0,0 +1,155 @@
+// Protocol treasury: the protocol's share of trading fees, swept in from every market,
+// and the withdrawals governance pays out of it. Balances are kept per settlement token and
+// never exceed what the registry chain actually holds of it, so a fee report that arrives
+// without its funds credits nothing.
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+pub const MAX_TREASURY_ENTRIES: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TreasuryEntryKind {
+    Inflow,
+    Withdrawal,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TreasuryEntry {
+    // Monotonic; doubles as the history's pagination cursor
+    pub sequence: u64,
+    pub kind: TreasuryEntryKind,
+    // Market the fees came from, for inflows
+    pub market_id: Option<String>,
+    // Chain paid, for withdrawals
+    pub recipient: Option<ChainId>,
+    // `None` for the native token
+    pub token: Option<ApplicationId>,
+    pub amount: Amount,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TokenTotals {
+    pub balance: Amount,
+    // Everything ever swept in and paid out; unaffected by trimming the history
+    pub inflows: Amount,
+    pub withdrawn: Amount,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Treasury {
+    pub totals: BTreeMap<Option<ApplicationId>, TokenTotals>,
+    // Oldest first; trimmed to MAX_TREASURY_ENTRIES
+    pub history: VecDeque<TreasuryEntry>,
+    pub next_sequence: u64,
+}
+
+impl Treasury {
+    // Credits a market's reported fees, up to what `held` shows arrived beyond the balance
+    // already credited; returns the amount credited
+    pub fn record_inflow(
+        &mut self,
+        market_id: String,
+        token: Option<ApplicationId>,
+        amount: Amount,
+        held: Amount,
+        now: u64,
+    ) -> Amount {
+        let totals = self.totals.entry(token).or_default();
+        let amount = amount.min(held.saturating_sub(totals.balance));
+        if amount == Amount::ZERO {
+            return amount;
+        }
+        totals.balance += amount;
+        totals.inflows += amount;
+        self.push(TreasuryEntryKind::Inflow, Some(market_id), None, token, amount, now);
+        amount
+    }
+
+    // Debits a withdrawal of at most the balance, and at most the `held` funds backing it;
+    // what could be withdrawn is the error when it falls short
+    pub fn withdraw(
+        &mut self,
+        token: Option<ApplicationId>,
+        amount: Amount,
+        recipient: ChainId,
+        held: Amount,
+        now: u64,
+    ) -> Result<(), Amount> {
+        let totals = self.totals.entry(token).or_default();
+        let available = totals.balance.min(held);
+        if amount > available {
+            return Err(available);
+        }
+        totals.balance = totals.balance.saturating_sub(amount);
+        totals.withdrawn += amount;
+        self.push(TreasuryEntryKind::Withdrawal, None, Some(recipient), token, amount, now);
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        kind: TreasuryEntryKind,
+        market_id: Option<String>,
+        recipient: Option<ChainId>,
+        token: Option<ApplicationId>,
+        amount: Amount,
+        timestamp: u64,
+    ) {
+        self.history.push_back(TreasuryEntry {
+            sequence: self.next_sequence,
+            kind,
+            market_id,
+            recipient,
+            token,
+            amount,
+            timestamp,
+        });
+        self.next_sequence += 1;
+        if self.history.len() > MAX_TREASURY_ENTRIES {
+            self.history.pop_front();
+        }
+    }
+}
+
+// Calls a fungible token application must accept: paying out of the registry's account
+// and reporting what an account holds
+#[derive(Serialize, Deserialize)]
+pub enum TokenCall {
+    Transfer { to: ChainId, amount: Amount },
+    Balance { owner: ChainId },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(byte: u8) -> ChainId {
+        ChainId::from([byte; 32])
+    }
+
+    #[test]
+    fn test_fees_reported_without_funds_credit_nothing() {
+        let mut treasury = Treasury::default();
+        let fees = Amount::from_tokens(10);
+        assert_eq!(treasury.record_inflow("m1".to_string(), None, fees, Amount::ZERO, 1), Amount::ZERO);
+        assert!(treasury.history.is_empty());
+        // Only the part that actually arrived is credited
+        assert_eq!(treasury.record_inflow("m1".to_string(), None, fees, Amount::from_tokens(4), 2), Amount::from_tokens(4));
+        assert_eq!(treasury.record_inflow("m2".to_string(), None, fees, Amount::from_tokens(14), 3), fees);
+        assert_eq!(treasury.totals[&None].balance, Amount::from_tokens(14));
+    }
+
+    #[test]
+    fn test_withdrawals_are_capped_at_funds_held() {
+        let mut treasury = Treasury::default();
+        treasury.record_inflow("m1".to_string(), None, Amount::from_tokens(10), Amount::from_tokens(10), 1);
+        // Something else spent the chain's funds since
+        let held = Amount::from_tokens(6);
+        assert_eq!(treasury.withdraw(None, Amount::from_tokens(8), chain(1), held, 2), Err(held));
+        assert_eq!(treasury.withdraw(None, Amount::from_tokens(6), chain(1), held, 2), Ok(()));
+        assert_eq!(treasury.totals[&None].balance, Amount::from_tokens(4));
+        assert_eq!(treasury.totals[&None].withdrawn, Amount::from_tokens(6));
+    }
+}
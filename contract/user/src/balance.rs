@@ -0,0 +1,88 @@
+// Keeps `main` in step with the chain balance without counting anything twice. Stakes are
+// debited from sub-accounts when orders are placed and payouts split across them when the
+// market reports them, so the funds those paths move are expected here ahead of time; only
+// what moves beyond them is a deposit or withdrawal for `main`.
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct BalanceSync {
+    // Chain balance at the last sync
+    pub synced: Amount,
+    // Stakes sub-accounts paid for that haven't left the chain yet. What a batch doesn't
+    // spend of its stake stays here until later flows net it out, which returns it to `main`.
+    pub awaiting_charges: Amount,
+    // Payouts credited to sub-accounts that haven't reached the chain yet
+    pub awaiting_payouts: Amount,
+}
+
+// What `sync` found that no sub-account path accounts for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unaccounted {
+    pub deposited: Amount,
+    pub withdrawn: Amount,
+}
+
+impl BalanceSync {
+    // Expected charges and payouts net out, so a block that sees both lands correctly
+    pub fn expect_charge(&mut self, amount: Amount) {
+        let netted = amount.min(self.awaiting_payouts);
+        self.awaiting_payouts = self.awaiting_payouts.saturating_sub(netted);
+        self.awaiting_charges += amount.saturating_sub(netted);
+    }
+
+    pub fn expect_payout(&mut self, amount: Amount) {
+        let netted = amount.min(self.awaiting_charges);
+        self.awaiting_charges = self.awaiting_charges.saturating_sub(netted);
+        self.awaiting_payouts += amount.saturating_sub(netted);
+    }
+
+    pub fn sync(&mut self, balance: Amount) -> Unaccounted {
+        let synced = std::mem::replace(&mut self.synced, balance);
+        let received = balance.saturating_sub(synced);
+        let sent = synced.saturating_sub(balance);
+        let payouts = received.min(self.awaiting_payouts);
+        self.awaiting_payouts = self.awaiting_payouts.saturating_sub(payouts);
+        let charges = sent.min(self.awaiting_charges);
+        self.awaiting_charges = self.awaiting_charges.saturating_sub(charges);
+        Unaccounted { deposited: received.saturating_sub(payouts), withdrawn: sent.saturating_sub(charges) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(amount: u128) -> Amount {
+        Amount::from_tokens(amount)
+    }
+
+    fn unaccounted(deposited: u128, withdrawn: u128) -> Unaccounted {
+        Unaccounted { deposited: tokens(deposited), withdrawn: tokens(withdrawn) }
+    }
+
+    #[test]
+    fn test_place_payout_and_sync_count_once() {
+        let mut sync = BalanceSync::default();
+        // A deposit is the only thing `main` sees
+        assert_eq!(sync.sync(tokens(100)), unaccounted(100, 0));
+        // Orders staking 10 were debited from a sub-account; the market charges 8 of it
+        sync.expect_charge(tokens(10));
+        assert_eq!(sync.sync(tokens(100)), unaccounted(0, 0));
+        assert_eq!(sync.sync(tokens(92)), unaccounted(0, 0));
+        // A payout already split across sub-accounts; the 2 left of the stake goes back to `main`
+        sync.expect_payout(tokens(20));
+        assert_eq!(sync.sync(tokens(112)), unaccounted(2, 0));
+        assert_eq!(sync.sync(tokens(109)), unaccounted(0, 3));
+    }
+
+    #[test]
+    fn test_charge_and_payout_in_one_block_net_out() {
+        let mut sync = BalanceSync { synced: tokens(50), ..Default::default() };
+        sync.expect_charge(tokens(5));
+        sync.expect_payout(tokens(12));
+        assert_eq!(sync.sync(tokens(57)), unaccounted(0, 0));
+        assert_eq!(sync.awaiting_charges, Amount::ZERO);
+        assert_eq!(sync.awaiting_payouts, Amount::ZERO);
+    }
+}
@@ -0,0 +1,154 @@
+// GraphQL service of the user application: queries over the chain's own
+// accounts and mutations that schedule its operations
+use crate::{Order, OrderSide, UserOperation, UserState};
+use async_graphql::{EmptySubscription, Enum, InputObject, Object, Schema, SimpleObject};
+use linera_sdk::base::{Amount, ChainId};
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub type UserSchema = Schema<UserQueryRoot, UserMutationRoot, EmptySubscription>;
+
+pub fn build_schema(state: Arc<UserState>) -> UserSchema {
+    Schema::build(UserQueryRoot { state }, UserMutationRoot, EmptySubscription).finish()
+}
+
+pub struct UserQueryRoot {
+    state: Arc<UserState>,
+}
+
+pub struct UserMutationRoot;
+
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct SubaccountInfo {
+    pub name: String,
+    pub balance: String,
+    pub markets: Vec<String>,
+}
+
+// Field names match the SDK's `TradingLimits`
+#[derive(SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct TradingLimitsInfo {
+    pub daily_stake_limit: Option<String>,
+    pub daily_loss_limit: Option<String>,
+    pub excluded_until: u64,
+    pub pending_stake_limit: Option<String>,
+    pub pending_loss_limit: Option<String>,
+    pub pending_effective_at: Option<u64>,
+    // As of the last order or payout; a new UTC day resets them on the next one
+    pub staked_today: String,
+    pub net_loss_today: String,
+}
+
+// The SDK sends its wire `Order` as JSON, so sides keep their Rust names
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "OrderSide", rename_items = "PascalCase")]
+pub enum OrderSideInput {
+    BuyYes,
+    BuyNo,
+}
+
+#[derive(InputObject)]
+#[graphql(name = "Order")]
+pub struct OrderInput {
+    pub id: u64,
+    pub side: OrderSideInput,
+    pub amount: Amount,
+    pub max_price: Option<Amount>,
+    pub subaccount: Option<String>,
+    pub referral_code: Option<String>,
+}
+
+impl From<OrderInput> for Order {
+    fn from(input: OrderInput) -> Self {
+        Order {
+            id: input.id,
+            side: match input.side {
+                OrderSideInput::BuyYes => OrderSide::BuyYes,
+                OrderSideInput::BuyNo => OrderSide::BuyNo,
+            },
+            amount: input.amount,
+            max_price: input.max_price,
+            subaccount: input.subaccount,
+            referral_code: input.referral_code,
+        }
+    }
+}
+
+fn parse_chain(raw: &str) -> async_graphql::Result<ChainId> {
+    ChainId::from_str(raw).map_err(|e| format!("invalid chain ID {}: {}", raw, e).into())
+}
+
+// The node executes the returned operation in a block on this chain
+fn schedule(operation: UserOperation) -> Vec<u8> {
+    bcs::to_bytes(&operation).expect("user operation is serializable")
+}
+
+#[Object]
+impl UserQueryRoot {
+    async fn subaccounts(&self) -> Vec<SubaccountInfo> {
+        self.state
+            .subaccounts
+            .iter()
+            .map(|(name, subaccount)| SubaccountInfo {
+                name: name.clone(),
+                balance: subaccount.balance.to_string(),
+                markets: subaccount.markets.clone(),
+            })
+            .collect()
+    }
+
+    async fn trading_limits(&self) -> TradingLimitsInfo {
+        let limits = &self.state.limits;
+        let activity = &self.state.activity;
+        let pending = limits.pending.as_ref();
+        TradingLimitsInfo {
+            daily_stake_limit: limits.daily_stake_limit.map(|limit| limit.to_string()),
+            daily_loss_limit: limits.daily_loss_limit.map(|limit| limit.to_string()),
+            excluded_until: limits.excluded_until,
+            pending_stake_limit: pending.and_then(|pending| pending.daily_stake_limit).map(|limit| limit.to_string()),
+            pending_loss_limit: pending.and_then(|pending| pending.daily_loss_limit).map(|limit| limit.to_string()),
+            pending_effective_at: pending.map(|pending| pending.effective_at),
+            staked_today: activity.staked.to_string(),
+            net_loss_today: activity.net_loss().to_string(),
+        }
+    }
+}
+
+#[Object]
+impl UserMutationRoot {
+    async fn create_subaccount(&self, name: String, markets: Vec<String>) -> Vec<u8> {
+        schedule(UserOperation::CreateSubaccount { name, markets })
+    }
+
+    async fn transfer_between_subaccounts(&self, from: String, to: String, amount: Amount) -> Vec<u8> {
+        schedule(UserOperation::TransferBetweenSubaccounts { from, to, amount })
+    }
+
+    async fn set_leaderboard_privacy(&self, registry_chain: String, hidden: bool) -> async_graphql::Result<Vec<u8>> {
+        let registry_chain = parse_chain(&registry_chain)?;
+        Ok(schedule(UserOperation::SetLeaderboardPrivacy { registry_chain, hidden }))
+    }
+
+    // Orders in the market's wire layout; the contract checks them against the limits and sub-accounts
+    async fn place_orders(
+        &self,
+        market_chain: String,
+        market_id: String,
+        orders: Vec<OrderInput>,
+        nonce: u64,
+    ) -> async_graphql::Result<Vec<u8>> {
+        let market_chain = parse_chain(&market_chain)?;
+        let orders = orders.into_iter().map(Order::from).collect();
+        Ok(schedule(UserOperation::PlaceOrders { market_chain, market_id, orders, nonce }))
+    }
+
+    async fn set_trading_limits(&self, daily_stake_limit: Option<Amount>, daily_loss_limit: Option<Amount>) -> Vec<u8> {
+        schedule(UserOperation::SetTradingLimits { daily_stake_limit, daily_loss_limit })
+    }
+
+    async fn self_exclude(&self, until: u64) -> Vec<u8> {
+        schedule(UserOperation::SelfExclude { until })
+    }
+}
@@ -0,0 +1,413 @@
+use linera_sdk::{
+    base::{Amount, ChainId},
+    contract::system_api,
+    Contract, ExecutionResult, MessageContext, OperationContext, ViewStateStorage,
+};
+use async_trait::async_trait;
+use serde::ser::SerializeStructVariant;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+pub mod balance;
+pub mod graphql;
+pub mod limits;
+
+use balance::BalanceSync;
+use limits::{DailyActivity, LimitError, TradingLimits};
+use oddsstream_schema::AppliedSequences;
+
+// Every user chain has this sub-account; deposits land in it and orders that name no sub-account draw on it
+pub const MAIN_SUBACCOUNT: &str = "main";
+
+// Per-user account state - stored on the user's own microchain
+#[derive(Default, ViewStateStorage)]
+pub struct UserState {
+    // Sub-account name -> earmarked funds
+    pub subaccounts: BTreeMap<String, SubAccount>,
+    // Chain balance last folded into `main`, and what sub-accounts expect to move on it
+    pub balance_sync: BalanceSync,
+    // (Market chain, sub-account) -> stakes placed; payouts from a market are split by them
+    pub market_stakes: BTreeMap<(ChainId, String), Amount>,
+    // Market ID -> the chain it was first traded on, so sub-account market lists can't be dodged
+    pub market_chains: BTreeMap<String, ChainId>,
+    // Self-imposed stake and loss limits and self-exclusion
+    pub limits: TradingLimits,
+    pub activity: DailyActivity,
+    // Market chains this chain has placed orders on; only they may credit payouts
+    pub traded_markets: BTreeSet<ChainId>,
+    // Market chain -> payouts already counted, so a resent credit doesn't offset stakes twice;
+    // kept the way markets keep the transfers they applied
+    pub credited_transfers: BTreeMap<ChainId, AppliedSequences>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SubAccount {
+    pub balance: Amount,
+    // Markets this sub-account is dedicated to; empty means any market
+    pub markets: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum UserOperation {
+    CreateSubaccount {
+        name: String,
+        markets: Vec<String>,
+    },
+    TransferBetweenSubaccounts {
+        from: String,
+        to: String,
+        amount: Amount,
+    },
+    // Hide this chain from (or show it on) the registry's public leaderboard
+    SetLeaderboardPrivacy {
+        registry_chain: ChainId,
+        hidden: bool,
+    },
+    // Send orders to a market after checking them against the trading limits and debiting
+    // each order's sub-account
+    PlaceOrders {
+        market_chain: ChainId,
+        market_id: String,
+        orders: Vec<Order>,
+        nonce: u64,
+    },
+    // Tighter limits apply at once, looser ones after `LIMIT_INCREASE_DELAY`
+    SetTradingLimits {
+        daily_stake_limit: Option<Amount>,
+        daily_loss_limit: Option<Amount>,
+    },
+    // Block all orders until `until`; can be extended but not shortened
+    SelfExclude {
+        until: u64,
+    },
+}
+
+// Sent to the user chain by markets it trades on
+#[derive(Serialize, Deserialize)]
+pub enum UserMessage {
+    // Winnings or a refund paid out; offsets the day's stakes for the loss limit
+    PayoutCredited {
+        amount: Amount,
+        transfer_id: TransferId,
+    },
+}
+
+// Mirrors the market's transfer ID: the issuing chain and its counter
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransferId {
+    pub issuer: ChainId,
+    pub sequence: u64,
+}
+
+// Mirrors the market's order
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Order {
+    pub id: u64,
+    pub side: OrderSide,
+    // Shares bought; each costs at most 1, so this bounds the stake
+    pub amount: Amount,
+    // Highest price per share the order may fill at
+    pub max_price: Option<Amount>,
+    pub subaccount: Option<String>,
+    pub referral_code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub enum OrderSide {
+    BuyYes,
+    BuyNo,
+}
+
+// Mirrors the leading variant of the market's message enum, the only one the user chain sends
+#[derive(Serialize, Deserialize)]
+pub enum MarketMessage {
+    BatchedOrders {
+        user_chain_id: ChainId,
+        orders: Vec<Order>,
+        nonce: u64,
+        signature: Option<OrderSignature>,
+        relayer_fee: Option<RelayerFee>,
+        cosignatures: Vec<OrderSignature>,
+    },
+}
+
+// The market's `ApprovePayments`, sent to this chain's own market app so the market an order
+// batch goes to may charge for it. Later variants aren't mirrored in order, so their BCS tags are written out.
+pub struct ApprovePayments {
+    pub market_chain: ChainId,
+    pub amount: Amount,
+}
+
+const APPROVE_PAYMENTS_TAG: u32 = 39;
+
+impl Serialize for ApprovePayments {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut variant =
+            serializer.serialize_struct_variant("MarketMessage", APPROVE_PAYMENTS_TAG, "ApprovePayments", 2)?;
+        variant.serialize_field("market_chain", &self.market_chain)?;
+        variant.serialize_field("amount", &self.amount)?;
+        variant.end()
+    }
+}
+
+// The market's `SelfExclude`, so markets refuse this chain's orders however they are sent
+pub struct SelfExclude {
+    pub user_chain_id: ChainId,
+    pub until: u64,
+}
+
+const SELF_EXCLUDE_TAG: u32 = 40;
+
+impl Serialize for SelfExclude {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut variant = serializer.serialize_struct_variant("MarketMessage", SELF_EXCLUDE_TAG, "SelfExclude", 2)?;
+        variant.serialize_field("user_chain_id", &self.user_chain_id)?;
+        variant.serialize_field("until", &self.until)?;
+        variant.end()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OrderSignature {
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RelayerFee {
+    pub relayer: ChainId,
+    pub amount: Amount,
+}
+
+// Mirrors the registry's message enum; variant order must match
+#[derive(Serialize, Deserialize)]
+pub enum RegistryMessage {
+    TradesExecuted {
+        user_chain_id: ChainId,
+        volume: Amount,
+        trades: u64,
+    },
+    MarketSettled {
+        outcome: bool,
+        results: Vec<TraderResult>,
+    },
+    SetLeaderboardPrivacy {
+        user_chain_id: ChainId,
+        hidden: bool,
+    },
+    DisputeFiled {
+        submitter: ChainId,
+        uri: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TraderResult {
+    pub user_chain_id: ChainId,
+    pub cost: Amount,
+    pub payout: Amount,
+}
+
+#[derive(Debug, Error)]
+pub enum UserError {
+    #[error("sub-account {0} already exists")]
+    SubaccountExists(String),
+    #[error("unknown sub-account {0}")]
+    UnknownSubaccount(String),
+    #[error("sub-account {0} has insufficient funds")]
+    InsufficientFunds(String),
+    #[error("sub-account {subaccount} is not dedicated to market {market_id}")]
+    MarketNotAllowed { subaccount: String, market_id: String },
+    #[error("market {0} trades on a different chain")]
+    MarketChainMismatch(String),
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+}
+
+pub struct UserApplication {
+    state: UserState,
+}
+
+#[async_trait]
+impl Contract for UserApplication {
+    type Operation = UserOperation;
+    type Message = UserMessage;
+    type Response = ();
+
+    async fn execute_operation(
+        &mut self,
+        context: OperationContext<Self::Operation>,
+    ) -> ExecutionResult<Self::Response> {
+        // Only the chain owner can move funds between its own sub-accounts
+        system_api::assert_owner(context.authenticated_signer)?;
+        self.sync_main();
+
+        match context.operation {
+            UserOperation::CreateSubaccount { name, markets } => {
+                if self.state.subaccounts.contains_key(&name) {
+                    return Err(UserError::SubaccountExists(name).into());
+                }
+                self.state.subaccounts.insert(name, SubAccount { balance: Amount::ZERO, markets });
+                Ok(())
+            }
+            UserOperation::TransferBetweenSubaccounts { from, to, amount } => {
+                if !self.state.subaccounts.contains_key(&to) {
+                    return Err(UserError::UnknownSubaccount(to).into());
+                }
+                let source = self
+                    .state
+                    .subaccounts
+                    .get_mut(&from)
+                    .ok_or_else(|| UserError::UnknownSubaccount(from.clone()))?;
+                source.balance = source
+                    .balance
+                    .try_sub(amount)
+                    .map_err(|_| UserError::InsufficientFunds(from))?;
+                self.state.subaccounts.get_mut(&to).unwrap().balance += amount;
+                Ok(())
+            }
+            UserOperation::SetLeaderboardPrivacy { registry_chain, hidden } => {
+                let message = RegistryMessage::SetLeaderboardPrivacy {
+                    user_chain_id: system_api::current_chain_id(),
+                    hidden,
+                };
+                self.send_message(registry_chain, message);
+                Ok(())
+            }
+            UserOperation::PlaceOrders { market_chain, market_id, orders, nonce } => {
+                let now = system_api::current_system_time().micros();
+                self.state.limits.apply_pending(now);
+                self.state.activity.roll(now);
+                let stake = orders.iter().fold(Amount::ZERO, |total, order| total + order.amount);
+                self.state.limits.check(&self.state.activity, stake, now).map_err(UserError::from)?;
+                if self.state.market_chains.get(&market_id).is_some_and(|chain| *chain != market_chain) {
+                    return Err(UserError::MarketChainMismatch(market_id).into());
+                }
+                let debits = self.subaccount_debits(&market_id, &orders)?;
+                for (name, amount) in debits {
+                    self.state.subaccounts.get_mut(&name).unwrap().balance -= amount;
+                    *self.state.market_stakes.entry((market_chain, name)).or_insert(Amount::ZERO) += amount;
+                }
+                self.state.activity.staked += stake;
+                self.state.balance_sync.expect_charge(stake);
+                self.state.traded_markets.insert(market_chain);
+                self.state.market_chains.insert(market_id, market_chain);
+                
+                // Lets the market charge for the batch; each order costs at most its amount
+                let approval = ApprovePayments { market_chain, amount: stake };
+                self.send_message(system_api::current_chain_id(), approval);
+                // Sent from this chain, so the market authorizes it by origin without a signature
+                let message = MarketMessage::BatchedOrders {
+                    user_chain_id: system_api::current_chain_id(),
+                    orders,
+                    nonce,
+                    signature: None,
+                    relayer_fee: None,
+                    cosignatures: Vec::new(),
+                };
+                self.send_message(market_chain, message);
+                Ok(())
+            }
+            UserOperation::SetTradingLimits { daily_stake_limit, daily_loss_limit } => {
+                let now = system_api::current_system_time().micros();
+                self.state.limits.update(daily_stake_limit, daily_loss_limit, now);
+                Ok(())
+            }
+            UserOperation::SelfExclude { until } => {
+                self.state.limits.self_exclude(until).map_err(UserError::from)?;
+                // Orders signed elsewhere or sent without this app never reach the check above,
+                // so the markets traded on hold the exclusion too, and this chain's own market
+                // app refuses to start atomic batches
+                let user_chain_id = system_api::current_chain_id();
+                let markets: Vec<ChainId> = self.state.traded_markets.iter().copied().collect();
+                for market_chain in std::iter::once(user_chain_id).chain(markets) {
+                    self.send_message(market_chain, SelfExclude { user_chain_id, until });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn execute_message(
+        &mut self,
+        context: &MessageContext,
+        message: Self::Message,
+    ) -> ExecutionResult<Self::Response> {
+        match message {
+            UserMessage::PayoutCredited { amount, transfer_id } => {
+                let origin = context.message_id.chain_id;
+                if self.state.traded_markets.contains(&origin)
+                    && transfer_id.issuer == origin
+                    && self.state.credited_transfers.entry(origin).or_default().insert(transfer_id.sequence)
+                {
+                    self.state.activity.roll(system_api::current_system_time().micros());
+                    self.state.activity.paid_out += amount;
+                    self.credit_payout(origin, amount);
+                    self.state.balance_sync.expect_payout(amount);
+                }
+            }
+        }
+        // After the payout is expected: its funds usually landed just before this message
+        self.sync_main();
+        Ok(())
+    }
+}
+
+impl UserApplication {
+    // Folds deposits and withdrawals since the last sync into `main`. Stakes and payouts
+    // were already booked to sub-accounts, so `BalanceSync` leaves them out.
+    fn sync_main(&mut self) {
+        let unaccounted = self.state.balance_sync.sync(system_api::current_chain_balance());
+        let main = self.state.subaccounts.entry(MAIN_SUBACCOUNT.to_string()).or_default();
+        main.balance = (main.balance + unaccounted.deposited).saturating_sub(unaccounted.withdrawn);
+    }
+    
+    // What each sub-account named by `orders` pays for them; fails without debiting anything
+    // if a name is unknown, reserved for other markets or short of funds
+    fn subaccount_debits(&self, market_id: &str, orders: &[Order]) -> Result<BTreeMap<String, Amount>, UserError> {
+        let mut debits: BTreeMap<String, Amount> = BTreeMap::new();
+        for order in orders {
+            let name = order.subaccount.clone().unwrap_or_else(|| MAIN_SUBACCOUNT.to_string());
+            *debits.entry(name).or_insert(Amount::ZERO) += order.amount;
+        }
+        for (name, amount) in &debits {
+            let subaccount = self
+                .state
+                .subaccounts
+                .get(name)
+                .ok_or_else(|| UserError::UnknownSubaccount(name.clone()))?;
+            if !subaccount.markets.is_empty() && !subaccount.markets.iter().any(|market| market == market_id) {
+                return Err(UserError::MarketNotAllowed { subaccount: name.clone(), market_id: market_id.to_string() });
+            }
+            if subaccount.balance < *amount {
+                return Err(UserError::InsufficientFunds(name.clone()));
+            }
+        }
+        Ok(debits)
+    }
+    
+    // Splits a market's payout across the sub-accounts in proportion to what they staked on it
+    fn credit_payout(&mut self, market_chain: ChainId, amount: Amount) {
+        let stakes: Vec<(String, Amount)> = self
+            .state
+            .market_stakes
+            .range((market_chain, String::new())..)
+            .take_while(|((chain, _), _)| *chain == market_chain)
+            .map(|((_, name), stake)| (name.clone(), *stake))
+            .collect();
+        let total = stakes.iter().fold(0u128, |sum, (_, stake)| sum + u128::from(*stake));
+        let mut credited = Amount::ZERO;
+        if total > 0 {
+            for (name, stake) in stakes {
+                let share = Amount::from_attos(u128::from(amount) * u128::from(stake) / total);
+                if let Some(subaccount) = self.state.subaccounts.get_mut(&name) {
+                    subaccount.balance += share;
+                    credited += share;
+                }
+            }
+        }
+        // Rounding dust, and payouts from markets with no recorded stakes, go to main
+        self.state.subaccounts.entry(MAIN_SUBACCOUNT.to_string()).or_default().balance += amount.saturating_sub(credited);
+    }
+}
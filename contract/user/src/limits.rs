@@ -0,0 +1,123 @@
+// Responsible-gaming controls a chain owner places on their own trading
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub const MICROS_PER_DAY: u64 = 86_400_000_000;
+
+// Loosening a limit only takes effect after this cooling-off period; tightening is immediate
+pub const LIMIT_INCREASE_DELAY: u64 = MICROS_PER_DAY;
+
+#[derive(Debug, Error)]
+pub enum LimitError {
+    #[error("self-excluded until {0}")]
+    SelfExcluded(u64),
+    #[error("daily stake limit of {0} reached")]
+    StakeLimit(Amount),
+    #[error("daily loss limit of {0} reached")]
+    LossLimit(Amount),
+    #[error("self-exclusion cannot be shortened")]
+    ExclusionShortened,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TradingLimits {
+    // Most that can be staked per UTC day; `None` is unlimited
+    pub daily_stake_limit: Option<Amount>,
+    // Most that stakes may exceed payouts by per UTC day
+    pub daily_loss_limit: Option<Amount>,
+    // No orders before this micros timestamp
+    pub excluded_until: u64,
+    // Looser limits waiting out the cooling-off period
+    pub pending: Option<PendingLimits>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PendingLimits {
+    pub daily_stake_limit: Option<Amount>,
+    pub daily_loss_limit: Option<Amount>,
+    pub effective_at: u64,
+}
+
+// Totals for the current UTC day; reset when the day rolls over
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct DailyActivity {
+    pub day: u64,
+    pub staked: Amount,
+    pub paid_out: Amount,
+}
+
+impl DailyActivity {
+    pub fn roll(&mut self, now: u64) {
+        let today = now / MICROS_PER_DAY;
+        if self.day != today {
+            *self = DailyActivity { day: today, ..Default::default() };
+        }
+    }
+
+    pub fn net_loss(&self) -> Amount {
+        self.staked.saturating_sub(self.paid_out)
+    }
+}
+
+// `None` means no limit, which is looser than any amount
+fn is_looser(new: Option<Amount>, current: Option<Amount>) -> bool {
+    match (new, current) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(new), Some(current)) => new > current,
+    }
+}
+
+impl TradingLimits {
+    // Applies tighter limits now and schedules looser ones, so a limit can't be lifted on impulse
+    pub fn update(&mut self, daily_stake_limit: Option<Amount>, daily_loss_limit: Option<Amount>, now: u64) {
+        self.apply_pending(now);
+        let looser = is_looser(daily_stake_limit, self.daily_stake_limit)
+            || is_looser(daily_loss_limit, self.daily_loss_limit);
+        if looser {
+            self.pending = Some(PendingLimits {
+                daily_stake_limit,
+                daily_loss_limit,
+                effective_at: now + LIMIT_INCREASE_DELAY,
+            });
+        } else {
+            self.daily_stake_limit = daily_stake_limit;
+            self.daily_loss_limit = daily_loss_limit;
+            self.pending = None;
+        }
+    }
+
+    pub fn self_exclude(&mut self, until: u64) -> Result<(), LimitError> {
+        if until < self.excluded_until {
+            return Err(LimitError::ExclusionShortened);
+        }
+        self.excluded_until = until;
+        Ok(())
+    }
+
+    pub fn apply_pending(&mut self, now: u64) {
+        if let Some(pending) = self.pending.take_if(|pending| now >= pending.effective_at) {
+            self.daily_stake_limit = pending.daily_stake_limit;
+            self.daily_loss_limit = pending.daily_loss_limit;
+        }
+    }
+
+    // Whether `stake` more can be put at risk today
+    pub fn check(&self, activity: &DailyActivity, stake: Amount, now: u64) -> Result<(), LimitError> {
+        if now < self.excluded_until {
+            return Err(LimitError::SelfExcluded(self.excluded_until));
+        }
+        if let Some(limit) = self.daily_stake_limit {
+            if activity.staked + stake > limit {
+                return Err(LimitError::StakeLimit(limit));
+            }
+        }
+        if let Some(limit) = self.daily_loss_limit {
+            if activity.net_loss() + stake > limit {
+                return Err(LimitError::LossLimit(limit));
+            }
+        }
+        Ok(())
+    }
+}
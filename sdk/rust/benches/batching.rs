@@ -0,0 +1,45 @@
+//! Order grouping as done by `submit_batched_orders` before any network traffic
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use linera_sdk::base::ChainId;
+use oddsstream_sdk::{group_orders_by_chain, MarketOrder, OrderSide};
+use std::collections::HashMap;
+
+fn orders(count: usize, markets: usize) -> Vec<MarketOrder> {
+    (0..count)
+        .map(|i| MarketOrder {
+            market_id: format!("market-{}", i % markets),
+            side: if i % 2 == 0 { OrderSide::Yes } else { OrderSide::No },
+            amount: "10".to_string(),
+            max_price: None,
+            subaccount: None,
+            referral_code: None,
+        })
+        .collect()
+}
+
+fn batching(c: &mut Criterion) {
+    let mut group = c.benchmark_group("group_orders_by_chain");
+    for (count, markets) in [(100, 5), (1_000, 50), (10_000, 500)] {
+        let chains: HashMap<String, ChainId> = (0..markets)
+            .map(|i| (format!("market-{}", i), ChainId::root(i as u32)))
+            .collect();
+        let input = orders(count, markets);
+
+        group.bench_with_input(
+            BenchmarkId::new(format!("{}_markets", markets), count),
+            &input,
+            |b, input| {
+                b.iter_batched(
+                    || input.clone(),
+                    |orders| group_orders_by_chain(orders, |market_id| chains[market_id]),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, batching);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+//! Market update decoding: `serde_json` against the in-place scanner
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use oddsstream_sdk::{DecodeFrame, MarketUpdate, MarketUpdateRef};
+
+const FRAME: &str = r#"{"marketId":"nba-finals-game-7","yesOdds":0.6234,"noOdds":0.3766,"volume":1250431.25,"status":"OPEN","timestamp":1700000000000,"sequence":918273}"#;
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("market_update_decode");
+    group.throughput(Throughput::Bytes(FRAME.len() as u64));
+
+    group.bench_function("serde_json", |b| {
+        b.iter(|| serde_json::from_str::<MarketUpdate>(black_box(FRAME)).unwrap())
+    });
+    group.bench_function("decode_frame", |b| {
+        b.iter(|| MarketUpdate::decode_frame(black_box(FRAME)).unwrap())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| MarketUpdateRef::parse(black_box(FRAME)).unwrap())
+    });
+
+    let mut scratch = MarketUpdate::decode_frame(FRAME).unwrap();
+    group.bench_function("reused_buffer", |b| {
+        b.iter(|| MarketUpdateRef::parse(black_box(FRAME)).unwrap().write_into(&mut scratch))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
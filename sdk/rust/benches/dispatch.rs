@@ -0,0 +1,56 @@
+//! Subscription dispatch throughput under each overflow policy
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oddsstream_sdk::{update_channel, DispatchConfig, MarketUpdate, OverflowPolicy};
+
+const UPDATES: usize = 10_000;
+
+fn update(i: usize) -> MarketUpdate {
+    MarketUpdate {
+        market_id: format!("market-{}", i % 200),
+        yes_odds: 0.5,
+        no_odds: 0.5,
+        volume: i as f64,
+        status: "OPEN".to_string(),
+        timestamp: i as u64,
+        sequence: i as u64 + 1,
+    }
+}
+
+fn dispatch(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let updates: Vec<_> = (0..UPDATES).map(update).collect();
+
+    let mut group = c.benchmark_group("dispatch_throughput");
+    group.throughput(Throughput::Elements(UPDATES as u64));
+    for overflow in [OverflowPolicy::Block, OverflowPolicy::DropOldest, OverflowPolicy::CoalesceByMarket] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{:?}", overflow)),
+            &overflow,
+            |b, &overflow| {
+                b.to_async(&runtime).iter(|| {
+                    let updates = updates.clone();
+                    async move {
+                        let (sender, mut receiver) = update_channel(DispatchConfig { capacity: 1024, overflow });
+                        let consumer = tokio::spawn(async move {
+                            let mut received = 0;
+                            while receiver.recv().await.is_some() {
+                                received += 1;
+                            }
+                            received
+                        });
+                        for update in updates {
+                            sender.send(update).await;
+                        }
+                        drop(sender);
+                        consumer.await.unwrap()
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
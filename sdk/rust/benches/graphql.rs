@@ -0,0 +1,60 @@
+//! Decoding a `markets` query response the way the transport does
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oddsstream_sdk::{GraphQLResponse, MarketInfo};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct MarketsData {
+    #[allow(dead_code)]
+    markets: Vec<MarketInfo>,
+}
+
+fn response(markets: usize) -> String {
+    let markets: Vec<_> = (0..markets)
+        .map(|i| {
+            serde_json::json!({
+                "id": format!("market-{}", i),
+                "description": "Will the home team win the final?",
+                "yesOdds": 0.55,
+                "noOdds": 0.45,
+                "volume": 125_000.0,
+                "liquidity": 40_000.0,
+                "status": "OPEN",
+                "oracleType": "Sports",
+                "resolutionTime": 1_700_000_000_000_000u64,
+                "createdBlock": 1_024,
+                "realizedVolatility": 0.31,
+                "yesDepth": 22_000.0,
+                "noDepth": 18_000.0,
+                "averageTradeSize": 120.0,
+                "lastTradeAt": 1_699_999_000_000u64,
+            })
+        })
+        .collect();
+    serde_json::json!({ "data": { "markets": markets } }).to_string()
+}
+
+fn graphql(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graphql_markets_decode");
+    for size in [10, 100, 1_000] {
+        let body = response(size);
+        group.throughput(Throughput::Bytes(body.len() as u64));
+
+        // Typed decode straight from the body
+        group.bench_with_input(BenchmarkId::new("typed", size), &body, |b, body| {
+            b.iter(|| serde_json::from_str::<GraphQLResponse<MarketsData>>(black_box(body)).unwrap())
+        });
+        // The transport's path: untyped envelope first, then `data` into the target type
+        group.bench_with_input(BenchmarkId::new("via_value", size), &body, |b, body| {
+            b.iter(|| {
+                let raw: GraphQLResponse<serde_json::Value> = serde_json::from_str(black_box(body)).unwrap();
+                serde_json::from_value::<MarketsData>(raw.data.unwrap()).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, graphql);
+criterion_main!(benches);
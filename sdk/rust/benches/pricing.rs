@@ -0,0 +1,34 @@
+//! AMM pricing: single fills and whole batches against the client-side pool mirror
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use oddsstream_sdk::{AmmPools, OrderSide};
+
+fn pricing(c: &mut Criterion) {
+    let pools = AmmPools::new(60_000.0, 40_000.0);
+
+    c.bench_function("amm_buy", |b| {
+        b.iter_batched(
+            || pools,
+            |mut pools| pools.buy(black_box(OrderSide::Yes), black_box(250.0)),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let mut group = c.benchmark_group("amm_fill_batch");
+    for size in [10, 100, 1_000] {
+        let orders: Vec<_> = (0..size)
+            .map(|i| (if i % 3 == 0 { OrderSide::No } else { OrderSide::Yes }, 10.0 + i as f64))
+            .collect();
+        group.bench_with_input(BenchmarkId::from_parameter(size), &orders, |b, orders| {
+            b.iter_batched(
+                || pools,
+                |mut pools| pools.fill_batch(orders.iter().copied()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pricing);
+criterion_main!(benches);
@@ -0,0 +1,157 @@
+//! User-chain sub-accounts for per-strategy accounting isolation
+
+use crate::{OddsStreamSdk, Position, SdkError};
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Sub-account every user chain starts with
+pub const MAIN_SUBACCOUNT: &str = "main";
+
+/// Funds earmarked within a user chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubAccount {
+    pub name: String,
+    pub balance: Amount,
+    /// Markets the sub-account is dedicated to; empty means any market
+    pub markets: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SubaccountsData {
+    subaccounts: Vec<SubAccount>,
+}
+
+impl OddsStreamSdk {
+    /// Run a mutation against the OddsStream application on this SDK's own chain
+    pub(crate) async fn execute_user_operation(
+        &self,
+        mutation: &str,
+        variables: serde_json::Value,
+    ) -> Result<String, SdkError> {
+        let app_id = self
+            .user_application_id
+            .ok_or_else(|| SdkError::InvalidInput("no user application configured".to_string()))?;
+        self.execute_operation(self.chain_id, app_id, mutation, variables).await
+    }
+
+    /// Query the OddsStream application's own service on `chain_id`
+    pub(crate) async fn query_user_application<T: DeserializeOwned>(
+        &self,
+        chain_id: ChainId,
+        query: &str,
+    ) -> Result<T, SdkError> {
+        let app_id = self
+            .user_application_id
+            .ok_or_else(|| SdkError::InvalidInput("no user application configured".to_string()))?;
+        let response = self
+            .transport
+            .client
+            .post(format!(
+                "{}/chains/{}/applications/{}",
+                self.transport.rpc_url, chain_id, app_id
+            ))
+            .json(&serde_json::json!({ "query": query }))
+            .send()
+            .await?;
+
+        let body: crate::GraphQLResponse<T> = response.json().await?;
+        if let Some(error) = body.errors.into_iter().next() {
+            return Err(error.into());
+        }
+        body.data.ok_or(SdkError::EmptyResponse)
+    }
+
+    /// Run a mutation against an application; the node executes it as a block on `chain_id`
+    pub(crate) async fn execute_operation(
+        &self,
+        chain_id: ChainId,
+        app_id: ApplicationId,
+        mutation: &str,
+        variables: serde_json::Value,
+    ) -> Result<String, SdkError> {
+        let response = self
+            .transport
+            .client
+            .post(format!(
+                "{}/chains/{}/applications/{}",
+                self.transport.rpc_url, chain_id, app_id
+            ))
+            .json(&serde_json::json!({ "query": mutation, "variables": variables }))
+            .send()
+            .await?;
+
+        // Linera returns the hash of the block that executed the operation
+        let body: crate::GraphQLResponse<serde_json::Value> = response.json().await?;
+        if let Some(error) = body.errors.into_iter().next() {
+            return Err(error.into());
+        }
+        Ok(body
+            .data
+            .and_then(|data| data.as_object()?.values().next()?.as_str().map(str::to_string))
+            .unwrap_or_default())
+    }
+
+    /// Create a named sub-account, optionally dedicated to a set of markets
+    pub async fn create_subaccount(
+        &self,
+        name: &str,
+        markets: Vec<String>,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation CreateSubaccount($name: String!, $markets: [String!]!) {
+                createSubaccount(name: $name, markets: $markets)
+            }
+        "#;
+        self.execute_user_operation(mutation, serde_json::json!({ "name": name, "markets": markets }))
+            .await
+    }
+
+    /// Move earmarked funds between two sub-accounts of this chain
+    pub async fn transfer_between_subaccounts(
+        &self,
+        from: &str,
+        to: &str,
+        amount: Amount,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation TransferBetweenSubaccounts($from: String!, $to: String!, $amount: Amount!) {
+                transferBetweenSubaccounts(from: $from, to: $to, amount: $amount)
+            }
+        "#;
+        self.execute_user_operation(
+            mutation,
+            serde_json::json!({ "from": from, "to": to, "amount": amount.to_string() }),
+        )
+        .await
+    }
+
+    /// List the sub-accounts of a user chain, read from its user application
+    pub async fn subaccounts(&self, user_chain_id: ChainId) -> Result<Vec<SubAccount>, SdkError> {
+        let query = r#"
+            query Subaccounts {
+                subaccounts { name balance markets }
+            }
+        "#;
+        let data: SubaccountsData = self.query_user_application(user_chain_id, query).await?;
+        Ok(data.subaccounts)
+    }
+
+    /// Positions of a user chain grouped by the sub-account they are attributed to
+    pub async fn positions_by_subaccount(
+        &self,
+        user_chain_id: ChainId,
+    ) -> Result<BTreeMap<String, Vec<Position>>, SdkError> {
+        let mut grouped: BTreeMap<String, Vec<Position>> = BTreeMap::new();
+        for position in self.user_positions(user_chain_id).await? {
+            let name = position
+                .subaccount
+                .clone()
+                .unwrap_or_else(|| MAIN_SUBACCOUNT.to_string());
+            grouped.entry(name).or_default().push(position);
+        }
+        Ok(grouped)
+    }
+}
@@ -0,0 +1,100 @@
+//! Explorer-style activity feed across all markets
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivityKind {
+    MarketCreated,
+    LargeTrade,
+    Resolved,
+    Disputed,
+    Cancelled,
+    OracleUpdated,
+    Archived,
+}
+
+/// One entry of the registry's event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEvent {
+    pub sequence: u64,
+    pub kind: ActivityKind,
+    pub market_id: String,
+    /// Trader for large trades, submitter for disputes
+    pub actor: Option<String>,
+    pub amount: Option<String>,
+    /// Outcome for resolutions, evidence URI for disputes, the new oracle for oracle
+    /// updates, the hex snapshot hash for archivals
+    pub detail: String,
+    /// Micros timestamp of the block that recorded the event
+    pub timestamp: u64,
+}
+
+/// Newest-first page of activity; pass `next_cursor` back to fetch older events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityPage {
+    pub events: Vec<ActivityEvent>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ActivityData {
+    #[serde(rename = "recentActivity")]
+    recent_activity: ActivityPage,
+}
+
+impl Transport {
+    pub(crate) async fn recent_activity(
+        &self,
+        limit: usize,
+        cursor: Option<u64>,
+    ) -> Result<ActivityPage, SdkError> {
+        let query = r#"
+            query RecentActivity($limit: Int!, $cursor: Int) {
+                recentActivity(limit: $limit, cursor: $cursor) {
+                    events {
+                        sequence
+                        kind
+                        marketId
+                        actor
+                        amount
+                        detail
+                        timestamp
+                    }
+                    nextCursor
+                }
+            }
+        "#;
+
+        let data: ActivityData = self
+            .graphql_query(query, serde_json::json!({ "limit": limit, "cursor": cursor }))
+            .await?;
+        Ok(data.recent_activity)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Market creations, large trades, resolutions, cancellations, disputes and oracle updates, newest first
+    pub async fn recent_activity(
+        &self,
+        limit: usize,
+        cursor: Option<u64>,
+    ) -> Result<ActivityPage, SdkError> {
+        self.transport.recent_activity(limit, cursor).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Market creations, large trades, resolutions, cancellations, disputes and oracle updates, newest first
+    pub async fn recent_activity(
+        &self,
+        limit: usize,
+        cursor: Option<u64>,
+    ) -> Result<ActivityPage, SdkError> {
+        self.transport.recent_activity(limit, cursor).await
+    }
+}
@@ -0,0 +1,119 @@
+//! Just enough HTTP/1.1 for the local admin endpoints agents expose; one
+//! request per connection, bodies sized by `Content-Length`
+
+use crate::SdkError;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Largest request head or body an admin endpoint reads
+const MAX_ADMIN_REQUEST: usize = 64 * 1024;
+
+pub(crate) struct AdminRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) bearer: Option<String>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl AdminRequest {
+    /// Whether the request carries `token`; anything goes when no token is configured
+    pub(crate) fn is_authorized(&self, token: Option<&str>) -> bool {
+        token.is_none_or(|token| self.bearer.as_deref() == Some(token))
+    }
+}
+
+fn malformed(what: &str) -> SdkError {
+    SdkError::InvalidInput(format!("malformed admin request: {}", what))
+}
+
+pub(crate) async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Result<AdminRequest, SdkError> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(malformed("request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let (mut bearer, mut content_length, mut head_size) = (None, 0usize, line.len());
+    loop {
+        line.clear();
+        head_size += reader.read_line(&mut line).await?;
+        if head_size > MAX_ADMIN_REQUEST {
+            return Err(malformed("head too large"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(malformed("header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| malformed("content length"))?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            bearer = value.strip_prefix("Bearer ").map(str::to_string);
+        }
+    }
+    if content_length > MAX_ADMIN_REQUEST {
+        return Err(malformed("body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(AdminRequest { method, path, bearer, body })
+}
+
+pub(crate) async fn write_response<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    status: u16,
+    body: &serde_json::Value,
+) -> Result<(), SdkError> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        _ => "",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Send one request and read the status and JSON body of the reply, which
+/// runs to the end of the stream
+pub(crate) async fn send_request<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    method: &str,
+    path: &str,
+    bearer: Option<&str>,
+) -> Result<(u16, serde_json::Value), SdkError> {
+    let authorization = bearer.map_or(String::new(), |token| format!("Authorization: Bearer {}\r\n", token));
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+        method, path, authorization
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.take(MAX_ADMIN_REQUEST as u64).read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+    let malformed = || SdkError::InvalidInput("malformed admin response".to_string());
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(malformed)?;
+    let status = head.split_whitespace().nth(1).and_then(|status| status.parse().ok()).ok_or_else(malformed)?;
+    let body = if body.is_empty() { serde_json::Value::Null } else { serde_json::from_str(body)? };
+    Ok((status, body))
+}
@@ -0,0 +1,684 @@
+//! Agent runtime: a `TradingStrategy` decides what to do from market
+//! updates, and `AIAgent` carries its decisions out within the risk limits
+//! of an `AgentConfig`
+
+use crate::{
+    CachedResearch, LpPosition, MarketFilters, MarketInfo, MarketOrder, MarketUpdate, OddsStreamSdk, ResearchSignal,
+    SdkError, Shutdown,
+};
+use futures::StreamExt;
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::MissedTickBehavior;
+
+/// Events buffered per subscriber before the slowest one starts missing them
+const EVENT_CAPACITY: usize = 256;
+
+/// Risk limits and parameters of one agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentConfig {
+    pub name: String,
+    /// Markets the agent follows and trades
+    pub market_ids: Vec<String>,
+    /// Largest single order or deposit, in tokens
+    pub max_order_size: f64,
+    /// Most the agent may have committed across all markets, in tokens
+    pub max_exposure: f64,
+    /// How often `on_tick` runs and market details are refreshed
+    pub decision_interval_secs: u64,
+    /// Strategy-specific parameters, passed to `TradingStrategy::configure`
+    #[serde(default)]
+    pub strategy_params: serde_json::Value,
+}
+
+impl AgentConfig {
+    pub fn decision_interval(&self) -> Duration {
+        Duration::from_secs(self.decision_interval_secs.max(1))
+    }
+
+    /// Check the risk limits are usable; strategy parameters are checked by the strategy
+    pub fn validate(&self) -> Result<(), SdkError> {
+        let invalid = |what: &str| Err(SdkError::InvalidInput(format!("agent {}: {}", self.name, what)));
+        if self.name.is_empty() {
+            return Err(SdkError::InvalidInput("agent name must not be empty".to_string()));
+        }
+        if self.market_ids.is_empty() {
+            return invalid("follows no markets");
+        }
+        if !(self.max_order_size.is_finite() && self.max_order_size > 0.0) {
+            return invalid("order size limit must be positive");
+        }
+        if !(self.max_exposure.is_finite() && self.max_exposure >= self.max_order_size) {
+            return invalid("exposure limit must be at least the order size limit");
+        }
+        if self.decision_interval_secs == 0 {
+            return invalid("decision interval must be at least a second");
+        }
+        Ok(())
+    }
+
+    /// Read a JSON config file and validate it
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, SdkError> {
+        let config: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Something a strategy wants done
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AgentDecision {
+    PlaceOrder(MarketOrder),
+    /// Deposit `amount` tokens into both pools
+    AddLiquidity { market_id: String, amount: f64 },
+    /// Burn LP shares; `shares` may be the whole position
+    RemoveLiquidity { market_id: String, shares: Amount },
+    Claim { market_id: String },
+}
+
+impl AgentDecision {
+    pub fn market_id(&self) -> &str {
+        match self {
+            AgentDecision::PlaceOrder(order) => &order.market_id,
+            AgentDecision::AddLiquidity { market_id, .. }
+            | AgentDecision::RemoveLiquidity { market_id, .. }
+            | AgentDecision::Claim { market_id } => market_id,
+        }
+    }
+
+    /// Tokens the decision commits; withdrawals and claims commit nothing.
+    /// A share never costs more than one token, so an order counts its share amount.
+    pub fn notional(&self) -> f64 {
+        match self {
+            AgentDecision::PlaceOrder(order) => order.amount.parse().unwrap_or(f64::INFINITY),
+            AgentDecision::AddLiquidity { amount, .. } => *amount,
+            AgentDecision::RemoveLiquidity { .. } | AgentDecision::Claim { .. } => 0.0,
+        }
+    }
+}
+
+/// What an agent reports while it runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AgentEvent {
+    /// The decision was sent; `transaction_id` is the block that carried it
+    Executed { decision: AgentDecision, transaction_id: String },
+    /// The decision broke a risk limit and was dropped
+    Rejected { decision: AgentDecision, reason: String },
+    /// Sending the decision or refreshing market details failed; the agent carries on
+    Failed { decision: Option<AgentDecision>, error: String },
+    /// A new config took effect; `config` is the one now in force
+    ConfigReloaded { config: AgentConfig },
+    /// A new strategy took over; `strategy` is its name
+    StrategySwapped { strategy: String },
+    /// Decisions are suspended; the agent keeps following its markets
+    Paused,
+    Resumed,
+    Stopped,
+    /// `run` returned an error or panicked. Sent by the `AgentFleet` running
+    /// the agent, since the agent itself is gone by then.
+    Crashed { error: String },
+}
+
+/// Instructions a running agent takes from its `AgentHandle`
+pub enum AgentCommand {
+    /// Swap in a new config; the strategy must accept its parameters
+    Reconfigure(AgentConfig),
+    /// Replace the strategy, keeping the subscription, context and exposure;
+    /// the new strategy must accept the current parameters
+    SwapStrategy(Box<dyn TradingStrategy>),
+    /// Stop deciding until `Resume`, without dropping the subscription or context
+    Pause,
+    Resume,
+    Stop,
+}
+
+/// Controls an agent after `AIAgent::run` has taken it
+#[derive(Debug, Clone)]
+pub struct AgentHandle {
+    name: String,
+    commands: mpsc::UnboundedSender<AgentCommand>,
+}
+
+impl AgentHandle {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&self, command: AgentCommand) -> Result<(), SdkError> {
+        self.commands
+            .send(command)
+            .map_err(|_| SdkError::InvalidInput(format!("agent {} is not running", self.name)))
+    }
+
+    /// Validated here, so a malformed config fails the caller instead of surfacing as an event
+    pub fn reconfigure(&self, config: AgentConfig) -> Result<(), SdkError> {
+        config.validate()?;
+        self.send(AgentCommand::Reconfigure(config))
+    }
+
+    /// Stop after the decision in progress, if any
+    pub fn stop(&self) -> Result<(), SdkError> {
+        self.send(AgentCommand::Stop)
+    }
+
+    /// Replace the strategy once the decisions in progress have been sent
+    pub fn swap_strategy(&self, strategy: Box<dyn TradingStrategy>) -> Result<(), SdkError> {
+        self.send(AgentCommand::SwapStrategy(strategy))
+    }
+
+    /// Suspend decisions after the one in progress, if any
+    pub fn pause(&self) -> Result<(), SdkError> {
+        self.send(AgentCommand::Pause)
+    }
+
+    pub fn resume(&self) -> Result<(), SdkError> {
+        self.send(AgentCommand::Resume)
+    }
+}
+
+/// What a strategy sees when it decides
+#[derive(Debug, Clone, Default)]
+pub struct AgentContext {
+    pub chain_id: Option<ChainId>,
+    /// Micros since the Unix epoch
+    pub now_micros: u64,
+    /// Latest update of each followed market
+    pub updates: HashMap<String, MarketUpdate>,
+    /// Details of each followed market, refreshed every decision interval
+    pub markets: HashMap<String, MarketInfo>,
+    /// Liquidity the agent's chain provides, by market
+    pub lp_positions: HashMap<String, LpPosition>,
+    /// Tokens committed so far, counted against `AgentConfig::max_exposure`
+    pub exposure: f64,
+    /// Latest research signal per market, when the agent has a research provider
+    pub research: HashMap<String, ResearchSignal>,
+}
+
+/// Decision logic plugged into an `AIAgent`.
+///
+/// Strategies are synchronous and only see the `AgentContext`, so the same
+/// strategy can be driven live or over recorded history.
+pub trait TradingStrategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Called for every market update
+    fn on_update(&mut self, update: &MarketUpdate, context: &AgentContext) -> Vec<AgentDecision>;
+
+    /// Called once per decision interval, after market details are refreshed
+    fn on_tick(&mut self, _context: &AgentContext) -> Vec<AgentDecision> {
+        Vec::new()
+    }
+
+    /// Apply `AgentConfig::strategy_params`
+    fn configure(&mut self, _params: &serde_json::Value) -> Result<(), SdkError> {
+        Ok(())
+    }
+}
+
+/// How an `AIAgent` works an order its strategy decided on into the market.
+///
+/// Without one, orders are sent whole the moment they are decided.
+pub trait ExecutionStrategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Child orders to send for `order`, each with its delay from now. The
+    /// children's amounts should add up to the order's, which is what the
+    /// risk limits were checked against.
+    fn slice(&mut self, order: &MarketOrder, context: &AgentContext) -> Vec<(Duration, MarketOrder)>;
+}
+
+/// Runs one strategy against the markets in its config
+pub struct AIAgent {
+    strategy: Box<dyn TradingStrategy>,
+    execution: Option<Box<dyn ExecutionStrategy>>,
+    /// Child orders waiting for their micros timestamp
+    scheduled: Vec<(u64, AgentDecision)>,
+    config: AgentConfig,
+    context: AgentContext,
+    events: broadcast::Sender<AgentEvent>,
+    research: Option<Arc<CachedResearch>>,
+    commands: mpsc::UnboundedReceiver<AgentCommand>,
+    command_sender: mpsc::UnboundedSender<AgentCommand>,
+}
+
+impl AIAgent {
+    pub fn new(strategy: Box<dyn TradingStrategy>, config: AgentConfig, chain_id: ChainId) -> Self {
+        let (command_sender, commands) = mpsc::unbounded_channel();
+        Self {
+            strategy,
+            execution: None,
+            scheduled: Vec::new(),
+            config,
+            context: AgentContext { chain_id: Some(chain_id), ..Default::default() },
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            research: None,
+            commands,
+            command_sender,
+        }
+    }
+
+    /// Fill `AgentContext::research` from `research` on every refresh
+    pub fn with_research(mut self, research: Arc<CachedResearch>) -> Self {
+        self.research = Some(research);
+        self
+    }
+
+    /// Work orders into the market with `execution` instead of sending them whole
+    pub fn with_execution(mut self, execution: Box<dyn ExecutionStrategy>) -> Self {
+        self.execution = Some(execution);
+        self
+    }
+
+    pub fn config(&self) -> &AgentConfig {
+        &self.config
+    }
+
+    pub fn context(&self) -> &AgentContext {
+        &self.context
+    }
+
+    pub fn strategy_name(&self) -> &str {
+        self.strategy.name()
+    }
+
+    /// Handle for controlling the agent once it runs
+    pub fn handle(&self) -> AgentHandle {
+        AgentHandle { name: self.config.name.clone(), commands: self.command_sender.clone() }
+    }
+
+    /// Receive events from now on
+    pub fn events(&self) -> broadcast::Receiver<AgentEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: AgentEvent) {
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+    }
+
+    /// Drop decisions that break the order-size or exposure limit, counting
+    /// the ones kept against the exposure
+    pub fn apply_limits(&mut self, decisions: Vec<AgentDecision>) -> Vec<AgentDecision> {
+        let mut allowed = Vec::with_capacity(decisions.len());
+        for decision in decisions {
+            match limit_violation(&self.config, self.context.exposure, &decision) {
+                Some(reason) => self.emit(AgentEvent::Rejected { decision, reason }),
+                None => {
+                    self.context.exposure += decision.notional();
+                    allowed.push(decision);
+                }
+            }
+        }
+        allowed
+    }
+
+    /// Whether `config` may replace the current one
+    fn check_reload(&self, config: &AgentConfig) -> Result<(), SdkError> {
+        config.validate()?;
+        if config.name != self.config.name {
+            return Err(SdkError::InvalidInput(format!(
+                "config for {} cannot be loaded into agent {}",
+                config.name, self.config.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Take `config` if it is valid and the strategy accepts its parameters;
+    /// a rejected config changes nothing
+    pub fn apply_config(&mut self, config: AgentConfig) -> Result<(), SdkError> {
+        self.check_reload(&config)?;
+        self.strategy.configure(&config.strategy_params)?;
+        self.context.updates.retain(|market_id, _| config.market_ids.contains(market_id));
+        self.context.markets.retain(|market_id, _| config.market_ids.contains(market_id));
+        self.config = config;
+        Ok(())
+    }
+
+    /// Hand trading to `strategy` if it accepts the current parameters, and
+    /// return the name of the one it replaced. A refused strategy changes nothing.
+    pub fn swap_strategy(&mut self, mut strategy: Box<dyn TradingStrategy>) -> Result<String, SdkError> {
+        strategy.configure(&self.config.strategy_params)?;
+        let previous = std::mem::replace(&mut self.strategy, strategy);
+        Ok(previous.name().to_string())
+    }
+
+    /// Value withdrawn by burning `shares` of the agent's position in `market_id`
+    fn released_by(&self, market_id: &str, shares: Amount) -> f64 {
+        let Some(position) = self.context.lp_positions.get(market_id) else {
+            return 0.0;
+        };
+        let held = u128::from(position.shares);
+        if held == 0 {
+            return 0.0;
+        }
+        position.value * (u128::from(shares).min(held) as f64 / held as f64)
+    }
+
+    /// Reload market details, LP positions and research into the context
+    pub async fn refresh(&mut self, sdk: &OddsStreamSdk) -> Result<(), SdkError> {
+        let markets = sdk.query_markets(MarketFilters::default()).await?;
+        self.context.markets = markets
+            .into_iter()
+            .filter(|market| self.config.market_ids.contains(&market.id))
+            .map(|market| (market.id.clone(), market))
+            .collect();
+        for market_id in &self.config.market_ids {
+            match sdk.lp_position(market_id, *sdk.chain_id()).await? {
+                Some(position) => self.context.lp_positions.insert(market_id.clone(), position),
+                None => self.context.lp_positions.remove(market_id),
+            };
+        }
+        if let Some(research) = &self.research {
+            for market in self.context.markets.values() {
+                // Research is advisory: a failing provider leaves the last signal in place
+                match research.research(market).await {
+                    Ok(Some(signal)) => {
+                        self.context.research.insert(market.id.clone(), signal);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!(
+                        agent = %self.config.name,
+                        provider = %research.provider_name(),
+                        market_id = %market.id,
+                        error = %e,
+                        "research failed"
+                    ),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Send `decisions` after the risk checks; a failed decision is reported and skipped.
+    /// Orders sliced by the execution strategy go out as their children fall due,
+    /// with this or a later call.
+    pub async fn execute(&mut self, sdk: &OddsStreamSdk, decisions: Vec<AgentDecision>) {
+        let now = self.context.now_micros;
+        for decision in self.apply_limits(decisions) {
+            if let (AgentDecision::PlaceOrder(order), Some(execution)) = (&decision, self.execution.as_mut()) {
+                for (delay, child) in execution.slice(order, &self.context) {
+                    let due = now.saturating_add(delay.as_micros() as u64);
+                    self.scheduled.push((due, AgentDecision::PlaceOrder(child)));
+                }
+                continue;
+            }
+            self.send(sdk, decision).await;
+        }
+        let (due, later): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduled).into_iter().partition(|(at, _)| *at <= now);
+        self.scheduled = later;
+        for (_, decision) in due {
+            self.send(sdk, decision).await;
+        }
+    }
+
+    async fn send(&mut self, sdk: &OddsStreamSdk, decision: AgentDecision) {
+        match send_decision(sdk, &decision).await {
+            Ok(transaction_id) => {
+                if let AgentDecision::RemoveLiquidity { market_id, shares } = &decision {
+                    self.context.exposure = (self.context.exposure - self.released_by(market_id, *shares)).max(0.0);
+                }
+                self.emit(AgentEvent::Executed { decision, transaction_id });
+            }
+            Err(e) => {
+                // Nothing was committed, so release the exposure counted for it
+                self.context.exposure -= decision.notional();
+                tracing::warn!(agent = %self.config.name, error = %e, "decision failed");
+                self.emit(AgentEvent::Failed { decision: Some(decision), error: e.to_string() });
+            }
+        }
+    }
+
+    /// Trade until `shutdown` triggers or a handle stops the agent. The
+    /// market feed ending is an error: the agent cannot trade without it.
+    ///
+    /// While paused the agent still records market updates, so it resumes
+    /// with a current context, but the strategy is not consulted.
+    pub async fn run(mut self, sdk: OddsStreamSdk, shutdown: Shutdown) -> Result<(), SdkError> {
+        self.config.validate()?;
+        self.strategy.configure(&self.config.strategy_params)?;
+        let mut updates = sdk.subscribe_market_updates_stream(self.config.market_ids.clone()).await?;
+        let mut ticker = decision_ticker(&self.config);
+        let mut paused = false;
+        let _guard = shutdown.guard();
+
+        loop {
+            let decisions = tokio::select! {
+                _ = shutdown.triggered() => break,
+                command = self.commands.recv() => match command {
+                    Some(AgentCommand::Reconfigure(config)) => {
+                        let previous = self.config.clone();
+                        // Whatever can fail runs before the swap, so a bad config leaves the agent as it was
+                        let resubscribed = match self.check_reload(&config) {
+                            Ok(()) if config.market_ids != previous.market_ids => {
+                                sdk.subscribe_market_updates_stream(config.market_ids.clone()).await.map(Some)
+                            }
+                            Ok(()) => Ok(None),
+                            Err(e) => Err(e),
+                        };
+                        let swapped = resubscribed.and_then(|stream| self.apply_config(config).map(|()| stream));
+                        match swapped {
+                            Ok(stream) => {
+                                if let Some(stream) = stream {
+                                    updates = stream;
+                                }
+                                if self.config.decision_interval_secs != previous.decision_interval_secs {
+                                    ticker = decision_ticker(&self.config);
+                                }
+                                if self.config != previous {
+                                    tracing::info!(agent = %self.config.name, "config reloaded");
+                                    self.emit(AgentEvent::ConfigReloaded { config: self.config.clone() });
+                                }
+                            }
+                            Err(e) => {
+                                tracing::warn!(agent = %self.config.name, error = %e, "config rejected");
+                                self.emit(AgentEvent::Failed { decision: None, error: e.to_string() });
+                            }
+                        }
+                        continue;
+                    }
+                    Some(AgentCommand::SwapStrategy(strategy)) => {
+                        // Commands wait for the decisions in progress, so nothing in flight is lost
+                        match self.swap_strategy(strategy) {
+                            Ok(previous) => {
+                                let strategy = self.strategy.name().to_string();
+                                tracing::info!(agent = %self.config.name, %previous, %strategy, "strategy swapped");
+                                self.emit(AgentEvent::StrategySwapped { strategy });
+                            }
+                            Err(e) => {
+                                tracing::warn!(agent = %self.config.name, error = %e, "strategy rejected");
+                                self.emit(AgentEvent::Failed { decision: None, error: e.to_string() });
+                            }
+                        }
+                        continue;
+                    }
+                    Some(AgentCommand::Pause) => {
+                        if !paused {
+                            paused = true;
+                            tracing::info!(agent = %self.config.name, "paused");
+                            self.emit(AgentEvent::Paused);
+                        }
+                        continue;
+                    }
+                    Some(AgentCommand::Resume) => {
+                        if paused {
+                            paused = false;
+                            tracing::info!(agent = %self.config.name, "resumed");
+                            self.emit(AgentEvent::Resumed);
+                        }
+                        continue;
+                    }
+                    // The agent holds a sender itself, so the channel never closes
+                    Some(AgentCommand::Stop) | None => break,
+                },
+                update = updates.next() => {
+                    let Some(update) = update else {
+                        return Err(SdkError::ConnectionError(format!(
+                            "market update stream of agent {} ended",
+                            self.config.name
+                        )));
+                    };
+                    self.context.now_micros = sdk.clock.now_micros();
+                    self.context.updates.insert(update.market_id.clone(), update.clone());
+                    if paused {
+                        continue;
+                    }
+                    self.strategy.on_update(&update, &self.context)
+                }
+                _ = ticker.tick() => {
+                    if paused {
+                        continue;
+                    }
+                    self.context.now_micros = sdk.clock.now_micros();
+                    if let Err(e) = self.refresh(&sdk).await {
+                        tracing::warn!(agent = %self.config.name, error = %e, "refresh failed");
+                        self.emit(AgentEvent::Failed { decision: None, error: e.to_string() });
+                        continue;
+                    }
+                    self.strategy.on_tick(&self.context)
+                }
+            };
+            self.execute(&sdk, decisions).await;
+        }
+        self.emit(AgentEvent::Stopped);
+        Ok(())
+    }
+}
+
+/// Why `decision` breaks `config`'s limits on top of `exposure`, if it does
+pub(crate) fn limit_violation(config: &AgentConfig, exposure: f64, decision: &AgentDecision) -> Option<String> {
+    let notional = decision.notional();
+    if !config.market_ids.iter().any(|id| id == decision.market_id()) {
+        Some(format!("{} is not a market this agent follows", decision.market_id()))
+    } else if notional > config.max_order_size {
+        Some(format!("{} exceeds the order size limit {}", notional, config.max_order_size))
+    } else if exposure + notional > config.max_exposure {
+        Some(format!("{} would exceed the exposure limit {}", notional, config.max_exposure))
+    } else {
+        None
+    }
+}
+
+fn decision_ticker(config: &AgentConfig) -> tokio::time::Interval {
+    let mut ticker = tokio::time::interval(config.decision_interval());
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Whole tokens as an `Amount`, to the nearest nano-token
+pub(crate) fn tokens_to_amount(tokens: f64) -> Result<Amount, SdkError> {
+    if !tokens.is_finite() || tokens < 0.0 {
+        return Err(SdkError::InvalidInput(format!("invalid token amount {}", tokens)));
+    }
+    Amount::from_str(&format!("{:.9}", tokens))
+        .map_err(|e| SdkError::InvalidInput(format!("invalid token amount {}: {}", tokens, e)))
+}
+
+async fn send_decision(sdk: &OddsStreamSdk, decision: &AgentDecision) -> Result<String, SdkError> {
+    let chain_id = *sdk.chain_id();
+    match decision {
+        AgentDecision::PlaceOrder(order) => {
+            let response = sdk.submit_batched_orders(vec![order.clone()], chain_id).await?;
+            Ok(response.transaction_ids.into_iter().next().unwrap_or_default())
+        }
+        AgentDecision::AddLiquidity { market_id, amount } => {
+            sdk.add_liquidity(market_id, chain_id, tokens_to_amount(*amount)?).await
+        }
+        AgentDecision::RemoveLiquidity { market_id, shares } => {
+            sdk.remove_liquidity(market_id, chain_id, *shares).await
+        }
+        AgentDecision::Claim { market_id } => sdk.claim(market_id, chain_id).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Idle;
+
+    impl TradingStrategy for Idle {
+        fn name(&self) -> &str {
+            "idle"
+        }
+
+        fn on_update(&mut self, _update: &MarketUpdate, _context: &AgentContext) -> Vec<AgentDecision> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_limits_drop_oversized_and_overexposed_decisions() {
+        let config = AgentConfig {
+            name: "test".to_string(),
+            market_ids: vec!["m1".to_string()],
+            max_order_size: 50.0,
+            max_exposure: 80.0,
+            decision_interval_secs: 1,
+            strategy_params: serde_json::Value::Null,
+        };
+        let mut agent = AIAgent::new(Box::new(Idle), config, ChainId::from([0u8; 32]));
+        let deposit = |market_id: &str, amount| AgentDecision::AddLiquidity { market_id: market_id.to_string(), amount };
+
+        let kept = agent.apply_limits(vec![
+            deposit("m1", 40.0),
+            deposit("m1", 60.0),
+            deposit("m2", 10.0),
+            deposit("m1", 30.0),
+            deposit("m1", 40.0),
+        ]);
+        assert_eq!(kept, vec![deposit("m1", 40.0), deposit("m1", 30.0)]);
+        assert_eq!(agent.context().exposure, 70.0);
+    }
+
+    struct Picky;
+
+    impl TradingStrategy for Picky {
+        fn name(&self) -> &str {
+            "picky"
+        }
+
+        fn on_update(&mut self, _update: &MarketUpdate, _context: &AgentContext) -> Vec<AgentDecision> {
+            Vec::new()
+        }
+
+        fn configure(&mut self, params: &serde_json::Value) -> Result<(), SdkError> {
+            match params.get("picky") {
+                Some(_) => Ok(()),
+                None => Err(SdkError::InvalidInput("picky needs its parameter".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_swap_keeps_context_and_refused_strategy_changes_nothing() {
+        let config = AgentConfig {
+            name: "test".to_string(),
+            market_ids: vec!["m1".to_string()],
+            max_order_size: 50.0,
+            max_exposure: 80.0,
+            decision_interval_secs: 1,
+            strategy_params: serde_json::json!({ "other": true }),
+        };
+        let mut agent = AIAgent::new(Box::new(Idle), config, ChainId::from([0u8; 32]));
+        agent.apply_limits(vec![AgentDecision::AddLiquidity { market_id: "m1".to_string(), amount: 20.0 }]);
+
+        assert!(agent.swap_strategy(Box::new(Picky)).is_err());
+        assert_eq!(agent.strategy_name(), "idle");
+
+        let mut config = agent.config().clone();
+        config.strategy_params = serde_json::json!({ "picky": 1 });
+        agent.apply_config(config).unwrap();
+        assert_eq!(agent.swap_strategy(Box::new(Picky)).unwrap(), "idle");
+        assert_eq!(agent.strategy_name(), "picky");
+        assert_eq!(agent.context().exposure, 20.0);
+    }
+}
@@ -0,0 +1,347 @@
+//! Execution algorithms: work a parent order into a market as timed child orders
+//!
+//! A TWAP spreads the parent evenly over a period, optionally jittering
+//! each child's time so the pattern is harder to spot. An iceberg only ever
+//! shows a small, optionally randomized slice, sending the next one an
+//! interval after the last. [`OddsStreamSdk::execute_algo`] runs either in
+//! the background: before each child it re-quotes the market and, when the
+//! price has moved against the parent by more than the limit, holds off or
+//! gives up. An agent can run the same schedules through [`AlgoExecution`],
+//! without the price checks.
+
+use crate::{AgentContext, ExecutionStrategy, MarketOrder, OddsStreamSdk, SdkError, Shutdown, SimRng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How to slice the parent order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlgoKind {
+    /// `slices` equal children across `duration_secs`; each child's time
+    /// moves by up to `jitter` of the interval either way, 0 to 1
+    Twap { duration_secs: u64, slices: u32, jitter: f64 },
+    /// Children of `visible` shares, each `interval_secs` apart; sizes vary
+    /// by up to `randomize` of `visible` either way, 0 to 1
+    Iceberg { visible: f64, interval_secs: u64, randomize: f64 },
+}
+
+impl AlgoKind {
+    /// Time between children, and how long a paused algo waits before re-checking the price
+    pub fn interval(&self) -> Duration {
+        match self {
+            AlgoKind::Twap { duration_secs, slices, .. } => {
+                Duration::from_secs(*duration_secs) / (*slices).max(1)
+            }
+            AlgoKind::Iceberg { interval_secs, .. } => Duration::from_secs(*interval_secs),
+        }
+    }
+}
+
+/// What to do when the price has moved against the parent order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdverseAction {
+    /// Hold the next child back until the price comes back within the limit
+    #[default]
+    Pause,
+    /// Stop, leaving the rest unsent
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgoParams {
+    pub kind: AlgoKind,
+    /// Largest rise of the side's price over the arrival price, in probability points, children are still sent at
+    #[serde(default)]
+    pub max_adverse_move: Option<f64>,
+    #[serde(default)]
+    pub on_adverse: AdverseAction,
+    /// Fixes the random timing and sizes, for a reproducible schedule
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl AlgoParams {
+    pub fn validate(&self) -> Result<(), SdkError> {
+        let fraction = |value: f64| (0.0..=1.0).contains(&value);
+        let valid = match self.kind {
+            AlgoKind::Twap { slices, jitter, .. } => slices > 0 && fraction(jitter),
+            AlgoKind::Iceberg { visible, randomize, .. } => visible > 0.0 && visible.is_finite() && fraction(randomize),
+        };
+        if !valid || self.max_adverse_move.is_some_and(|limit| limit.is_nan() || limit < 0.0) {
+            return Err(SdkError::InvalidInput(format!("invalid algo parameters {:?}", self)));
+        }
+        Ok(())
+    }
+
+    fn is_adverse(&self, arrival_price: f64, price: f64) -> bool {
+        self.max_adverse_move.is_some_and(|limit| price - arrival_price > limit)
+    }
+}
+
+/// Child sizes and their times from the start, in the order they go out
+pub fn algo_schedule(shares: f64, kind: &AlgoKind, rng: &mut SimRng) -> Vec<(Duration, f64)> {
+    let interval = kind.interval();
+    let mut children = match *kind {
+        AlgoKind::Twap { slices, jitter, .. } => (0..slices)
+            .map(|index| {
+                let shift = jitter * interval.as_secs_f64() * rng.gen_range(-0.5..=0.5);
+                let at = (interval.as_secs_f64() * f64::from(index) + shift).max(0.0);
+                (Duration::from_secs_f64(at), shares / f64::from(slices))
+            })
+            .collect::<Vec<_>>(),
+        AlgoKind::Iceberg { visible, randomize, .. } => {
+            let mut children = Vec::new();
+            let mut remaining = shares;
+            while remaining > 0.0 {
+                let size = (visible * (1.0 + randomize * rng.gen_range(-1.0..=1.0))).max(visible * 0.01);
+                let size = size.min(remaining);
+                children.push((interval * children.len() as u32, size));
+                remaining -= size;
+            }
+            children
+        }
+    };
+    // Jitter must not reorder children
+    children.sort_by_key(|(at, _)| *at);
+    children
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlgoState {
+    Running,
+    /// Waiting for the price to come back within `max_adverse_move`
+    Paused,
+    Completed,
+    /// Cancelled by the handle or by an adverse move; `reason` says which
+    Cancelled { reason: String },
+    /// A child could not be quoted or sent; the rest is unsent
+    Failed { error: String },
+}
+
+impl AlgoState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, AlgoState::Completed | AlgoState::Cancelled { .. } | AlgoState::Failed { .. })
+    }
+}
+
+/// How far an algo has worked its parent order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgoProgress {
+    pub state: AlgoState,
+    pub total_shares: f64,
+    /// Shares in children sent; fills confirm on the user chain with `BatchConfirmed`
+    pub sent_shares: f64,
+    pub children_sent: u32,
+    pub children_planned: u32,
+    /// Quoted cost of the children sent, fees included
+    pub quoted_cost: f64,
+    /// The side's price when the algo started
+    pub arrival_price: f64,
+    pub last_price: f64,
+    pub transaction_ids: Vec<String>,
+}
+
+impl AlgoProgress {
+    /// Share of the parent sent so far, 0 to 1
+    pub fn completion(&self) -> f64 {
+        if self.total_shares > 0.0 {
+            (self.sent_shares / self.total_shares).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Average quoted price of the children sent, fees included
+    pub fn average_price(&self) -> Option<f64> {
+        (self.sent_shares > 0.0).then(|| self.quoted_cost / self.sent_shares)
+    }
+}
+
+/// A running algo; dropping the handle leaves it running
+pub struct AlgoHandle {
+    progress: watch::Receiver<AlgoProgress>,
+    cancel: Shutdown,
+}
+
+impl AlgoHandle {
+    pub fn progress(&self) -> AlgoProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// Receive every progress change from now on
+    pub fn watch(&self) -> watch::Receiver<AlgoProgress> {
+        self.progress.clone()
+    }
+
+    /// Stop before the next child; children already sent stand
+    pub fn cancel(&self) {
+        self.cancel.trigger();
+    }
+
+    /// Wait for the algo to complete, be cancelled or fail
+    pub async fn finished(mut self) -> AlgoProgress {
+        if let Ok(progress) = self.progress.wait_for(|progress| progress.state.is_finished()).await {
+            return progress.clone();
+        }
+        // The task only drops its sender once finished
+        self.progress.borrow().clone()
+    }
+}
+
+impl OddsStreamSdk {
+    /// Work `parent` into its market as child orders from this SDK's chain, in the background.
+    ///
+    /// The parent's `max_price`, sub-account and referral code carry over to every child.
+    pub async fn execute_algo(&self, parent: MarketOrder, params: AlgoParams) -> Result<AlgoHandle, SdkError> {
+        params.validate()?;
+        let shares = parent
+            .amount
+            .parse::<f64>()
+            .ok()
+            .filter(|shares| shares.is_finite() && *shares > 0.0)
+            .ok_or_else(|| SdkError::InvalidInput(format!("invalid amount {}", parent.amount)))?;
+        self.check_schema(&parent.market_id).await?;
+        let arrival_price = self.quote_order(&parent, None).await?.price;
+
+        let mut rng = params.seed.map_or_else(SimRng::from_entropy, SimRng::seeded);
+        let schedule = algo_schedule(shares, &params.kind, &mut rng);
+        let (progress, receiver) = watch::channel(AlgoProgress {
+            state: AlgoState::Running,
+            total_shares: shares,
+            sent_shares: 0.0,
+            children_sent: 0,
+            children_planned: schedule.len() as u32,
+            quoted_cost: 0.0,
+            arrival_price,
+            last_price: arrival_price,
+            transaction_ids: Vec::new(),
+        });
+        let cancel = Shutdown::new();
+        tracing::info!(
+            market_id = %parent.market_id,
+            shares,
+            children = schedule.len(),
+            seed = rng.seed(),
+            "starting execution algo"
+        );
+        tokio::spawn(run_algo(self.clone(), parent, params, schedule, progress, cancel.clone()));
+        Ok(AlgoHandle { progress: receiver, cancel })
+    }
+}
+
+async fn run_algo(
+    sdk: OddsStreamSdk,
+    parent: MarketOrder,
+    params: AlgoParams,
+    schedule: Vec<(Duration, f64)>,
+    progress: watch::Sender<AlgoProgress>,
+    cancel: Shutdown,
+) {
+    let finish = |state: AlgoState| progress.send_modify(|progress| progress.state = state);
+    let start = sdk.clock.now_micros();
+    let arrival_price = progress.borrow().arrival_price;
+    // Pauses push every later child back
+    let mut delay = Duration::ZERO;
+    for (at, shares) in schedule {
+        let child = MarketOrder { amount: format!("{:.9}", shares), ..parent.clone() };
+        loop {
+            let due = start + (at + delay).as_micros() as u64;
+            let wait = Duration::from_micros(due.saturating_sub(sdk.clock.now_micros()));
+            tokio::select! {
+                _ = cancel.triggered() => {
+                    return finish(AlgoState::Cancelled { reason: "cancelled".to_string() });
+                }
+                _ = sdk.clock.sleep(wait) => {}
+            }
+
+            let quote = match sdk.quote_order(&child, None).await {
+                Ok(quote) => quote,
+                Err(e) => return finish(AlgoState::Failed { error: e.to_string() }),
+            };
+            progress.send_modify(|progress| progress.last_price = quote.price);
+            if params.is_adverse(arrival_price, quote.price) {
+                if params.on_adverse == AdverseAction::Cancel {
+                    let reason = format!("price moved from {:.4} to {:.4}", arrival_price, quote.price);
+                    return finish(AlgoState::Cancelled { reason });
+                }
+                progress.send_modify(|progress| progress.state = AlgoState::Paused);
+                delay += params.kind.interval().max(Duration::from_secs(1));
+                continue;
+            }
+
+            match sdk.submit_batched_orders(vec![child], sdk.chain_id).await {
+                Ok(response) => progress.send_modify(|progress| {
+                    progress.state = AlgoState::Running;
+                    progress.sent_shares += shares;
+                    progress.children_sent += 1;
+                    progress.quoted_cost += quote.total_cost;
+                    progress.transaction_ids.extend(response.transaction_ids);
+                }),
+                Err(e) => return finish(AlgoState::Failed { error: e.to_string() }),
+            }
+            break;
+        }
+    }
+    finish(AlgoState::Completed);
+}
+
+/// Agent execution on an algo's schedule. Children go out at their planned
+/// times whatever the price does; use `execute_algo` for adverse-move checks.
+#[derive(Debug, Clone)]
+pub struct AlgoExecution {
+    pub kind: AlgoKind,
+    rng: SimRng,
+}
+
+impl AlgoExecution {
+    pub fn new(kind: AlgoKind, rng: SimRng) -> Self {
+        Self { kind, rng }
+    }
+}
+
+impl ExecutionStrategy for AlgoExecution {
+    fn name(&self) -> &str {
+        match self.kind {
+            AlgoKind::Twap { .. } => "twap",
+            AlgoKind::Iceberg { .. } => "iceberg",
+        }
+    }
+
+    fn slice(&mut self, order: &MarketOrder, _context: &AgentContext) -> Vec<(Duration, MarketOrder)> {
+        let Ok(shares) = order.amount.parse::<f64>() else {
+            return vec![(Duration::ZERO, order.clone())];
+        };
+        algo_schedule(shares, &self.kind, &mut self.rng)
+            .into_iter()
+            .map(|(at, shares)| (at, MarketOrder { amount: format!("{:.9}", shares), ..order.clone() }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedules_add_up_to_the_parent() {
+        let twap = AlgoKind::Twap { duration_secs: 600, slices: 10, jitter: 0.5 };
+        let children = algo_schedule(100.0, &twap, &mut SimRng::seeded(7));
+        assert_eq!(children.len(), 10);
+        assert!(children.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(children.iter().all(|(at, _)| *at < Duration::from_secs(630)));
+        assert!((children.iter().map(|(_, shares)| shares).sum::<f64>() - 100.0).abs() < 1e-9);
+
+        let iceberg = AlgoKind::Iceberg { visible: 15.0, interval_secs: 30, randomize: 0.3 };
+        let children = algo_schedule(100.0, &iceberg, &mut SimRng::seeded(7));
+        assert!(children.iter().all(|(_, shares)| *shares <= 15.0 * 1.3 + 1e-9));
+        assert_eq!(children[1].0, Duration::from_secs(30));
+        assert!((children.iter().map(|(_, shares)| shares).sum::<f64>() - 100.0).abs() < 1e-9);
+        assert_eq!(children, algo_schedule(100.0, &iceberg, &mut SimRng::seeded(7)));
+    }
+}
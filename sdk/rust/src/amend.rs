@@ -0,0 +1,50 @@
+//! Amending an order queued in a call auction: a new size and limit in one
+//! atomic cancel-and-replace that keeps the order's id and queue position
+
+use crate::{amend_digest, MarketMessage, OddsStreamSdk, OrderSignature, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+
+impl OddsStreamSdk {
+    /// Replace the size and limit of queued order `order_id`. `max_price` is
+    /// a probability; `None` removes the limit. A market that refuses the
+    /// amendment leaves the original queued and reports a rejection for the
+    /// order id, with `OrderNotFound` if it had already filled.
+    pub async fn amend_order(
+        &self,
+        market_id: &str,
+        order_id: u64,
+        amount: Amount,
+        max_price: Option<f64>,
+        user_chain_id: ChainId,
+    ) -> Result<String, SdkError> {
+        let max_price = max_price
+            .map(|price| {
+                if !(0.0..=1.0).contains(&price) {
+                    return Err(SdkError::InvalidInput(format!("max price {} is not a probability", price)));
+                }
+                price
+                    .to_string()
+                    .parse::<Amount>()
+                    .map_err(|e| SdkError::InvalidInput(format!("max price {}: {}", price, e)))
+            })
+            .transpose()?;
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.check_schema(market_id).await?;
+        self.ensure_not_limited(user_chain_id).await?;
+
+        // Approves the whole new size; what the original order was approved for is spent or left over
+        self.approve_own_payments(user_chain_id, market_chain_id, amount).await?;
+        let nonce = self.get_nonce().await?;
+        let signature = match &self.signer {
+            Some(signer) => {
+                let digest = amend_digest(user_chain_id, market_chain_id, nonce, order_id, amount, max_price);
+                Some(OrderSignature { public_key: signer.public_key(), signature: signer.sign(&digest).await? })
+            }
+            None => None,
+        };
+        let message = MarketMessage::AmendOrder { user_chain_id, order_id, amount, max_price, nonce, signature };
+        let transaction_id = self.send_message(market_chain_id, message).await?;
+        tracing::info!(%market_chain_id, %user_chain_id, order_id, nonce, transaction_id = %transaction_id, "submitted order amendment");
+        Ok(transaction_id)
+    }
+}
@@ -0,0 +1,62 @@
+//! Client-side mirror of the market contract's pricing, for quotes without a round trip
+
+use crate::OrderSide;
+use serde::{Deserialize, Serialize};
+
+/// Pool balances of a binary market; each side's odds are the opposite pool's share
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AmmPools {
+    pub yes: f64,
+    pub no: f64,
+}
+
+impl AmmPools {
+    pub fn new(yes: f64, no: f64) -> Self {
+        Self { yes, no }
+    }
+
+    /// Implied probability of `side`; an empty market sits at even odds
+    pub fn odds(&self, side: OrderSide) -> f64 {
+        let total = self.yes + self.no;
+        if total <= 0.0 {
+            return 0.5;
+        }
+        match side {
+            OrderSide::Yes => self.no / total,
+            OrderSide::No => self.yes / total,
+        }
+    }
+
+    /// Buy `amount` shares at the current odds and move the pools; returns the cost
+    pub fn buy(&mut self, side: OrderSide, amount: f64) -> f64 {
+        let cost = amount * self.odds(side);
+        match side {
+            OrderSide::Yes => self.yes += amount,
+            OrderSide::No => self.no += amount,
+        }
+        cost
+    }
+
+    /// Fill orders one after another as the market chain does within a batch; returns the total cost
+    pub fn fill_batch(&mut self, orders: impl IntoIterator<Item = (OrderSide, f64)>) -> f64 {
+        orders.into_iter().map(|(side, amount)| self.buy(side, amount)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buy_mirrors_contract_pricing() {
+        let mut pools = AmmPools::new(600.0, 400.0);
+        assert!((pools.odds(OrderSide::Yes) - 0.4).abs() < 1e-12);
+
+        let first = pools.buy(OrderSide::Yes, 100.0);
+        assert!((first - 40.0).abs() < 1e-9);
+        // As on chain, shares go into the bought side's pool, which lowers that side's odds
+        let second = pools.buy(OrderSide::Yes, 100.0);
+        assert!(second < first);
+        assert!((pools.odds(OrderSide::Yes) + pools.odds(OrderSide::No) - 1.0).abs() < 1e-12);
+    }
+}
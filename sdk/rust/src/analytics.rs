@@ -0,0 +1,248 @@
+//! Cross-market correlation and cointegration over implied probabilities
+
+use crate::{OddsStreamSdk, SdkError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Samples are aligned on buckets of this width before comparing markets
+const ALIGN_BUCKET_MS: u64 = 60_000;
+
+/// ADF 5% critical value for Engle-Granger residuals with two series
+const EG_CRITICAL_5PCT: f64 = -3.34;
+
+/// One historical observation of a market's YES implied probability
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricePoint {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub yes_odds: f64,
+}
+
+/// Relationship between two markets over the analysis window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairStats {
+    pub market_a: String,
+    pub market_b: String,
+    /// Number of aligned samples used
+    pub samples: usize,
+    /// Pearson correlation of per-bucket probability changes
+    pub correlation: f64,
+    /// OLS slope of A on B; the spread is `A - hedge_ratio * B`
+    pub hedge_ratio: f64,
+    /// Dickey-Fuller t-statistic of the spread
+    pub adf_statistic: f64,
+    /// Whether the spread looks stationary at the 5% level
+    pub cointegrated: bool,
+}
+
+/// Pearson correlation coefficient; `None` for short or constant series
+pub fn pearson(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return None;
+    }
+    let (mean_a, mean_b) = (mean(&a[..n]), mean(&b[..n]));
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for i in 0..n {
+        let (da, db) = (a[i] - mean_a, b[i] - mean_b);
+        cov += da * db;
+        var_a += da * da;
+        var_b += db * db;
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return None;
+    }
+    Some(cov / (var_a.sqrt() * var_b.sqrt()))
+}
+
+/// Correlation over each trailing `window` of samples
+pub fn rolling_correlation(a: &[f64], b: &[f64], window: usize) -> Vec<Option<f64>> {
+    let n = a.len().min(b.len());
+    if window < 2 || n < window {
+        return Vec::new();
+    }
+    (window..=n)
+        .map(|end| pearson(&a[end - window..end], &b[end - window..end]))
+        .collect()
+}
+
+/// Engle-Granger test: regress `a` on `b`, then Dickey-Fuller on the residuals.
+/// Returns `(hedge_ratio, adf_statistic)`.
+pub fn engle_granger(a: &[f64], b: &[f64]) -> Option<(f64, f64)> {
+    let n = a.len().min(b.len());
+    if n < 3 {
+        return None;
+    }
+    let (a, b) = (&a[..n], &b[..n]);
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let var_b: f64 = b.iter().map(|x| (x - mean_b).powi(2)).sum();
+    if var_b == 0.0 {
+        return None;
+    }
+    let cov: f64 = a.iter().zip(b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum();
+    let beta = cov / var_b;
+    let alpha = mean_a - beta * mean_b;
+    let spread: Vec<f64> = a.iter().zip(b).map(|(x, y)| x - alpha - beta * y).collect();
+
+    // Δs_t = γ s_{t-1} + ε_t ; the statistic is γ / se(γ)
+    let lagged = &spread[..n - 1];
+    let delta: Vec<f64> = spread.windows(2).map(|w| w[1] - w[0]).collect();
+    let sxx: f64 = lagged.iter().map(|x| x * x).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let gamma = lagged.iter().zip(&delta).map(|(x, d)| x * d).sum::<f64>() / sxx;
+    let rss: f64 = lagged.iter().zip(&delta).map(|(x, d)| (d - gamma * x).powi(2)).sum();
+    let dof = (n - 2).max(1) as f64;
+    let se = (rss / dof / sxx).sqrt();
+    if se == 0.0 {
+        return Some((beta, f64::NEG_INFINITY));
+    }
+    Some((beta, gamma / se))
+}
+
+/// Bucket each series and keep only buckets present in all of them
+pub fn align_series(series: &[Vec<PricePoint>], bucket_ms: u64) -> Vec<Vec<f64>> {
+    let bucketed: Vec<BTreeMap<u64, f64>> = series
+        .iter()
+        .map(|points| {
+            points
+                .iter()
+                .map(|p| (p.timestamp / bucket_ms, p.yes_odds))
+                .collect()
+        })
+        .collect();
+
+    let Some(first) = bucketed.first() else {
+        return Vec::new();
+    };
+    let common: Vec<u64> = first
+        .keys()
+        .filter(|bucket| bucketed.iter().all(|b| b.contains_key(bucket)))
+        .copied()
+        .collect();
+
+    bucketed
+        .iter()
+        .map(|b| common.iter().map(|bucket| b[bucket]).collect())
+        .collect()
+}
+
+/// Correlation and cointegration statistics for two aligned level series
+pub fn pair_stats(market_a: &str, market_b: &str, a: &[f64], b: &[f64]) -> Option<PairStats> {
+    let (da, db) = (differences(a), differences(b));
+    let correlation = pearson(&da, &db)?;
+    let (hedge_ratio, adf_statistic) = engle_granger(a, b)?;
+    Some(PairStats {
+        market_a: market_a.to_string(),
+        market_b: market_b.to_string(),
+        samples: a.len().min(b.len()),
+        correlation,
+        hedge_ratio,
+        adf_statistic,
+        cointegrated: adf_statistic < EG_CRITICAL_5PCT,
+    })
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn differences(values: &[f64]) -> Vec<f64> {
+    values.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+#[derive(Deserialize)]
+struct HistoryData {
+    #[serde(rename = "oddsHistory")]
+    odds_history: Vec<PricePoint>,
+}
+
+impl OddsStreamSdk {
+    /// Most recent `limit` implied-probability observations for a market, oldest first
+    pub async fn price_history(&self, market_id: &str, limit: usize) -> Result<Vec<PricePoint>, SdkError> {
+        let query = r#"
+            query OddsHistory($marketId: String!, $limit: Int!) {
+                oddsHistory(marketId: $marketId, limit: $limit) {
+                    timestamp
+                    yesOdds
+                }
+            }
+        "#;
+
+        let data: HistoryData = self
+            .graphql_query(query, serde_json::json!({ "marketId": market_id, "limit": limit }))
+            .await?;
+        Ok(data.odds_history)
+    }
+
+    /// Pairwise correlation and cointegration over the last `window` aligned minutes
+    pub async fn correlations(
+        &self,
+        market_ids: &[String],
+        window: usize,
+    ) -> Result<Vec<PairStats>, SdkError> {
+        let mut histories = Vec::with_capacity(market_ids.len());
+        for market_id in market_ids {
+            histories.push(self.price_history(market_id, window * 4).await?);
+        }
+
+        let aligned = align_series(&histories, ALIGN_BUCKET_MS);
+        let mut stats = Vec::new();
+        for i in 0..market_ids.len() {
+            for j in i + 1..market_ids.len() {
+                let (a, b) = (&aligned[i], &aligned[j]);
+                let start = a.len().saturating_sub(window);
+                if let Some(pair) = pair_stats(&market_ids[i], &market_ids[j], &a[start..], &b[start..]) {
+                    stats.push(pair);
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pearson() {
+        let a = [0.1, 0.2, 0.3, 0.4];
+        let b = [0.9, 0.8, 0.7, 0.6];
+        assert!((pearson(&a, &a).unwrap() - 1.0).abs() < 1e-12);
+        assert!((pearson(&a, &b).unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(pearson(&a, &[0.5; 4]), None);
+        assert_eq!(rolling_correlation(&a, &b, 3).len(), 2);
+    }
+
+    #[test]
+    fn test_cointegrated_pair() {
+        // B wanders; A tracks 0.5 * B with a small mean-reverting spread
+        let mut b = vec![0.4];
+        for i in 1..200 {
+            b.push(b[i - 1] + if (i * 7) % 5 < 2 { 0.003 } else { -0.002 });
+        }
+        let a: Vec<f64> = b
+            .iter()
+            .enumerate()
+            .map(|(i, x)| 0.5 * x + if i % 2 == 0 { 0.01 } else { -0.01 })
+            .collect();
+
+        let (beta, adf) = engle_granger(&a, &b).unwrap();
+        assert!((beta - 0.5).abs() < 0.05);
+        assert!(adf < EG_CRITICAL_5PCT);
+    }
+
+    #[test]
+    fn test_align_series() {
+        let p = |timestamp, yes_odds| PricePoint { timestamp, yes_odds };
+        let aligned = align_series(
+            &[vec![p(0, 0.1), p(60_000, 0.2)], vec![p(60_500, 0.7), p(120_000, 0.8)]],
+            60_000,
+        );
+        assert_eq!(aligned, vec![vec![0.2], vec![0.7]]);
+    }
+}
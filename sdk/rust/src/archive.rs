@@ -0,0 +1,182 @@
+//! Archived markets
+//!
+//! A settled market stays live for a grace period in which holders claim.
+//! After that it commits to its final state, sends holders, referrers and
+//! liquidity providers whatever they left unclaimed and retires its chain;
+//! the registry keeps the commitment. Export
+//! the market with [`OddsStreamSdk::export_market_archive`] before then to
+//! keep its state, and check the export against the registry afterwards.
+
+use crate::transport::Transport;
+use crate::{AuditRecord, MarketLifecycle, MarketSnapshot, OddsStreamSdk, ReadOnlyClient, SdkError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Must match the market contract's domain separator
+const SNAPSHOT_DOMAIN: &[u8] = b"oddsstream-snapshot-v1";
+
+/// What the registry keeps of a retired market
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveRecord {
+    /// Micros timestamp the registry recorded the archival at
+    pub archived_at: u64,
+    pub snapshot_hash: [u8; 32],
+}
+
+/// Everything exported from a market chain, enough to serve it after the chain is gone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketArchive {
+    pub snapshot: MarketSnapshot,
+    pub lifecycle: MarketLifecycle,
+    /// Merkle root over every position; see `get_position_proof`
+    pub position_root: [u8; 32],
+    /// The records the market still held, oldest first; the last one is the chain head
+    pub audit_log: Vec<AuditRecord>,
+}
+
+/// Commitment to a market's final state, as the market contract computes it
+pub fn snapshot_hash(market_id: &str, position_root: &[u8; 32], audit_head: &[u8; 32], audit_length: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SNAPSHOT_DOMAIN);
+    hasher.update(bcs::to_bytes(&(market_id, position_root, audit_head, audit_length)).expect("snapshot is serializable"));
+    hasher.finalize().into()
+}
+
+impl MarketArchive {
+    /// The hash the market reports when it archives, if nothing changed after the export
+    pub fn commitment(&self) -> [u8; 32] {
+        let (head, length) = self.audit_log.last().map_or(([0; 32], 0), |record| (record.hash, record.sequence));
+        snapshot_hash(&self.snapshot.market_id, &self.position_root, &head, length)
+    }
+
+    /// Whether this export is the state the market committed to when it archived
+    pub fn matches(&self, record: &ArchiveRecord) -> bool {
+        self.commitment() == record.snapshot_hash
+    }
+
+    /// Write the archive as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SdkError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Load an archive previously written with [`MarketArchive::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SdkError> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawArchiveInfo {
+    archived_at: Option<u64>,
+    snapshot_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ArchiveInfoData {
+    #[serde(rename = "marketChainInfo")]
+    market_chain_info: Option<RawArchiveInfo>,
+}
+
+impl Transport {
+    pub(crate) async fn market_archive_record(&self, market_id: &str) -> Result<Option<ArchiveRecord>, SdkError> {
+        let query = r#"
+            query MarketArchive($marketId: String!) {
+                marketChainInfo(marketId: $marketId) { archivedAt snapshotHash }
+            }
+        "#;
+
+        let data: ArchiveInfoData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let info = data
+            .market_chain_info
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+        let (Some(archived_at), Some(hash)) = (info.archived_at, info.snapshot_hash) else {
+            return Ok(None);
+        };
+        let bytes = hex::decode(&hash).map_err(|e| SdkError::InvalidInput(format!("snapshot hash: {}", e)))?;
+        let snapshot_hash = <[u8; 32]>::try_from(bytes.as_slice())
+            .map_err(|_| SdkError::InvalidInput(format!("snapshot hash has {} bytes", bytes.len())))?;
+        Ok(Some(ArchiveRecord { archived_at, snapshot_hash }))
+    }
+}
+
+impl OddsStreamSdk {
+    /// Export a market's state, positions root and audit log. Only possible
+    /// while its chain is live, i.e. until the grace period after settlement ends.
+    pub async fn export_market_archive(&self, market_id: &str) -> Result<MarketArchive, SdkError> {
+        Ok(MarketArchive {
+            snapshot: self.snapshot_market(market_id).await?,
+            lifecycle: self.market_lifecycle(market_id).await?,
+            position_root: self.position_root(market_id).await?,
+            audit_log: self.audit_log(market_id, 0).await?,
+        })
+    }
+
+    /// The registry's record of a retired market; `None` while the market is live
+    pub async fn market_archive_record(&self, market_id: &str) -> Result<Option<ArchiveRecord>, SdkError> {
+        self.transport.market_archive_record(market_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The registry's record of a retired market; `None` while the market is live
+    pub async fn market_archive_record(&self, market_id: &str) -> Result<Option<ArchiveRecord>, SdkError> {
+        self.transport.market_archive_record(market_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuditEvent, MarketStatus};
+
+    #[test]
+    fn test_commitment_covers_the_audit_head() {
+        let record = AuditRecord {
+            sequence: 1,
+            timestamp: 5,
+            event: AuditEvent::Voided,
+            prev_hash: [0; 32],
+            hash: [7; 32],
+        };
+        let mut archive = MarketArchive {
+            snapshot: MarketSnapshot {
+                market_id: "btc-100k".to_string(),
+                description: String::new(),
+                status: "Cancelled".to_string(),
+                pool_yes: 0.0,
+                pool_no: 0.0,
+                yes_odds: 0.5,
+                no_odds: 0.5,
+                resolution_time: 0,
+                block_height: 0,
+                resting_orders: Vec::new(),
+                positions: Vec::new(),
+            },
+            lifecycle: MarketLifecycle {
+                status: MarketStatus::Cancelled,
+                locks_at: 0,
+                resolution_time: 0,
+                history: Vec::new(),
+                pending_timers: Vec::new(),
+                archived_at: None,
+                protocol_paused: false,
+            },
+            position_root: [1; 32],
+            audit_log: vec![record],
+        };
+        let committed = ArchiveRecord { archived_at: 9, snapshot_hash: snapshot_hash("btc-100k", &[1; 32], &[7; 32], 1) };
+        assert!(archive.matches(&committed));
+
+        archive.audit_log.clear();
+        assert!(!archive.matches(&committed));
+    }
+}
@@ -0,0 +1,130 @@
+//! All-or-nothing batches across markets
+//!
+//! `submit_batched_orders` sends each market its orders independently, so
+//! one market refusing leaves the others filled. In atomic mode the market
+//! app on the user chain coordinates a two-phase commit instead: every
+//! market reserves its orders at the current prices, and only once all of
+//! them have does the user chain tell them to fill. A refusal, or a market
+//! that doesn't answer within 30 seconds, aborts the reservations held
+//! everywhere else. Committed legs confirm with `BatchConfirmed` as usual;
+//! an aborted batch fills nothing.
+
+use crate::{wire_orders, MarketMessage, MarketOrder, OddsStreamSdk, Order, SdkError};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// Markets one atomic batch may span; each costs three cross-chain messages
+pub const MAX_ATOMIC_MARKETS: usize = 8;
+
+/// One market's share of an atomic batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtomicLeg {
+    pub market_chain: ChainId,
+    pub orders: Vec<Order>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtomicBatchResponse {
+    pub transaction_id: String,
+    /// Market chains asked to reserve, in leg order
+    pub market_chains: Vec<ChainId>,
+    pub total_orders: usize,
+}
+
+/// Split `orders` into one leg per market chain, ordered by chain for a stable
+/// layout. Orders are numbered from `nonce` across the whole batch, so a
+/// rejection names one order even when it comes back through the coordinator.
+pub fn atomic_legs(
+    orders: Vec<MarketOrder>,
+    nonce: u64,
+    chain_of: impl Fn(&str) -> ChainId,
+) -> Result<Vec<AtomicLeg>, SdkError> {
+    let wire = wire_orders(&orders, nonce)?;
+    let mut legs: Vec<AtomicLeg> = Vec::new();
+    for (order, wire) in orders.iter().zip(wire) {
+        let market_chain = chain_of(&order.market_id);
+        match legs.iter_mut().find(|leg| leg.market_chain == market_chain) {
+            Some(leg) => leg.orders.push(wire),
+            None => legs.push(AtomicLeg { market_chain, orders: vec![wire] }),
+        }
+    }
+    legs.sort_by_key(|leg| leg.market_chain);
+    if legs.is_empty() || legs.len() > MAX_ATOMIC_MARKETS {
+        return Err(SdkError::InvalidInput(format!(
+            "an atomic batch spans 1 to {} markets, got {}",
+            MAX_ATOMIC_MARKETS,
+            legs.len()
+        )));
+    }
+    Ok(legs)
+}
+
+impl OddsStreamSdk {
+    /// Submit orders across markets so that either all of them fill or none do.
+    ///
+    /// The user chain coordinates the batch, so `user_chain_id` must be this
+    /// SDK's chain. Atomic batches never join a call auction: a market in one
+    /// refuses its leg and the batch aborts.
+    pub async fn submit_batched_orders_atomic(
+        &self,
+        orders: Vec<MarketOrder>,
+        user_chain_id: ChainId,
+    ) -> Result<AtomicBatchResponse, SdkError> {
+        if user_chain_id != self.chain_id {
+            return Err(SdkError::InvalidInput(
+                "atomic batches are coordinated by the SDK's own chain".to_string(),
+            ));
+        }
+        let total_orders = orders.len();
+        let chains = self
+            .resolve_market_chains(orders.iter().map(|order| order.market_id.as_str()))
+            .await?;
+        let market_ids: std::collections::BTreeSet<String> = orders.iter().map(|order| order.market_id.clone()).collect();
+        // A market that would drop the reservation silently can only time the batch out; fail now instead
+        for market_id in &market_ids {
+            self.check_schema(market_id).await?;
+            self.check_compliance(market_id, user_chain_id).await?;
+        }
+        self.ensure_not_limited(user_chain_id).await?;
+        let nonce = self.get_nonce().await?;
+        let legs = atomic_legs(orders, nonce, |market_id| chains[market_id])?;
+        let market_chains: Vec<ChainId> = legs.iter().map(|leg| leg.market_chain).collect();
+
+        let transaction_id = self.send_message(self.chain_id, MarketMessage::SubmitAtomic { legs }).await?;
+        tracing::info!(
+            %user_chain_id,
+            orders = total_orders,
+            markets = ?market_ids,
+            transaction_id = %transaction_id,
+            "submitted atomic order batch"
+        );
+        Ok(AtomicBatchResponse { transaction_id, market_chains, total_orders })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{order_id, OrderSide};
+
+    #[test]
+    fn test_orders_split_into_one_leg_per_market() {
+        let order = |market_id: &str| MarketOrder {
+            market_id: market_id.to_string(),
+            side: OrderSide::Yes,
+            amount: "1".to_string(),
+            max_price: None,
+            subaccount: None,
+            referral_code: None,
+        };
+        let chain_of = |market_id: &str| ChainId::from([if market_id == "a" { 1u8 } else { 2u8 }; 32]);
+        let legs = atomic_legs(vec![order("b"), order("a"), order("b")], 5, chain_of).unwrap();
+        assert_eq!(legs.len(), 2);
+        assert_eq!(legs[0].orders, vec![order("a").to_wire(order_id(5, 1).unwrap()).unwrap()]);
+        let ids: Vec<u64> = legs[1].orders.iter().map(|order| order.id).collect();
+        assert_eq!(ids, vec![order_id(5, 0).unwrap(), order_id(5, 2).unwrap()]);
+        assert!(atomic_legs(Vec::new(), 5, chain_of).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Call auctions at market open and after circuit-breaker halts: orders queue
+//! until the auction ends, then all execute at a single clearing price
+
+use crate::transport::Transport;
+use crate::{MarketMessage, OddsStreamSdk, ReadOnlyClient, SdkError};
+use serde::{Deserialize, Serialize};
+
+/// An auction in progress and the prices it would clear at now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionState {
+    pub market_id: String,
+    /// Micros timestamp after which the auction can be uncrossed
+    pub ends_at: u64,
+    pub order_count: u64,
+    /// Shares queued on each side
+    pub yes_demand: f64,
+    pub no_demand: f64,
+    /// Clearing prices if the auction ended now; they move as orders arrive
+    pub indicative_yes_price: f64,
+    pub indicative_no_price: f64,
+}
+
+#[derive(Deserialize)]
+struct AuctionData {
+    auction: Option<RawAuction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAuction {
+    ends_at: u64,
+    order_count: u64,
+    yes_demand: String,
+    no_demand: String,
+    indicative_yes_price: f64,
+    indicative_no_price: f64,
+}
+
+impl Transport {
+    pub(crate) async fn auction_state(&self, market_id: &str) -> Result<Option<AuctionState>, SdkError> {
+        let query = r#"
+            query Auction($marketId: String!) {
+                auction(marketId: $marketId) {
+                    endsAt
+                    orderCount
+                    yesDemand
+                    noDemand
+                    indicativeYesPrice
+                    indicativeNoPrice
+                }
+            }
+        "#;
+
+        let data: AuctionData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let Some(raw) = data.auction else {
+            return Ok(None);
+        };
+        let parse_amount = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|e| SdkError::InvalidInput(format!("invalid demand {value}: {e}")))
+        };
+        Ok(Some(AuctionState {
+            market_id: market_id.to_string(),
+            ends_at: raw.ends_at,
+            order_count: raw.order_count,
+            yes_demand: parse_amount(&raw.yes_demand)?,
+            no_demand: parse_amount(&raw.no_demand)?,
+            indicative_yes_price: raw.indicative_yes_price,
+            indicative_no_price: raw.indicative_no_price,
+        }))
+    }
+}
+
+impl OddsStreamSdk {
+    /// The market's call auction, or `None` while it trades continuously
+    pub async fn auction_state(&self, market_id: &str) -> Result<Option<AuctionState>, SdkError> {
+        self.transport.auction_state(market_id).await
+    }
+
+    /// Execute a market's auction once its collection period is over. The
+    /// next order batch does this too; this is for when none arrives.
+    pub async fn uncross_auction(&self, market_id: &str) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.send_message(market_chain_id, MarketMessage::UncrossAuction).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The market's call auction, or `None` while it trades continuously
+    pub async fn auction_state(&self, market_id: &str) -> Result<Option<AuctionState>, SdkError> {
+        self.transport.auction_state(market_id).await
+    }
+}
@@ -0,0 +1,281 @@
+//! Export and independent verification of a market's hash-chained audit log
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Must match the market contract's domain separator
+const AUDIT_DOMAIN: &[u8] = b"oddsstream-audit-v1";
+
+/// Records fetched per request while exporting
+const AUDIT_PAGE: usize = 500;
+
+/// A state transition, mirroring the market contract's `AuditEvent`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditEvent {
+    OrdersAccepted { user_chain_id: ChainId, nonce: u64, order_count: u64 },
+    Fill { user_chain_id: ChainId, buy_yes: bool, size: Amount, cost: Amount },
+    Resolved { outcome: bool },
+    Payout { user_chain_id: ChainId, amount: Amount },
+    StageResolved { stage: u32, outcome: bool },
+    /// A queued order was replaced in place, keeping its id
+    OrderAmended { user_chain_id: ChainId, order_id: u64, amount: Amount, max_price: Option<Amount> },
+    /// The oracle missed its deadline; the fallback committee may now resolve
+    OracleEscalated { member_count: u32 },
+    /// Nobody resolved before the refund deadline; positions are refunded
+    Voided,
+    /// Yield collected from the market's yield source and credited to providers or the treasury
+    YieldHarvested { earned: Amount },
+}
+
+/// One link of the audit chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub sequence: u64,
+    pub timestamp: u64,
+    pub event: AuditEvent,
+    pub prev_hash: [u8; 32],
+    pub hash: [u8; 32],
+}
+
+/// What is wrong with a log, at the first record that fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditViolation {
+    /// The record's hash does not match its contents
+    HashMismatch { sequence: u64 },
+    /// The record does not point at the previous record's hash
+    BrokenLink { sequence: u64 },
+    /// A record is missing or repeated
+    SequenceGap { expected: u64, found: u64 },
+}
+
+/// Outcome of `verify_audit_log`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditVerification {
+    pub records_checked: usize,
+    pub violation: Option<AuditViolation>,
+    /// The log ends at the market's current head, so nothing was dropped from its tail
+    pub matches_chain_head: bool,
+}
+
+impl AuditVerification {
+    pub fn is_valid(&self) -> bool {
+        self.violation.is_none()
+    }
+}
+
+/// Hash of a record as the market contract computes it
+pub fn audit_hash(prev_hash: &[u8; 32], sequence: u64, timestamp: u64, event: &AuditEvent) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(AUDIT_DOMAIN);
+    hasher.update(prev_hash);
+    hasher.update(bcs::to_bytes(&(sequence, timestamp, event)).expect("audit event is serializable"));
+    hasher.finalize().into()
+}
+
+/// Check that `records` form an unbroken chain. A log exported from
+/// sequence 1 must start from the all-zero hash; a later slice is checked
+/// from its own first record.
+pub fn verify_audit_chain(records: &[AuditRecord]) -> Option<AuditViolation> {
+    let mut previous: Option<&AuditRecord> = None;
+    for record in records {
+        match previous {
+            Some(prev) => {
+                if record.sequence != prev.sequence + 1 {
+                    return Some(AuditViolation::SequenceGap { expected: prev.sequence + 1, found: record.sequence });
+                }
+                if record.prev_hash != prev.hash {
+                    return Some(AuditViolation::BrokenLink { sequence: record.sequence });
+                }
+            }
+            None if record.sequence == 1 && record.prev_hash != [0; 32] => {
+                return Some(AuditViolation::BrokenLink { sequence: 1 });
+            }
+            None => {}
+        }
+        if audit_hash(&record.prev_hash, record.sequence, record.timestamp, &record.event) != record.hash {
+            return Some(AuditViolation::HashMismatch { sequence: record.sequence });
+        }
+        previous = Some(record);
+    }
+    None
+}
+
+#[derive(Deserialize)]
+struct AuditLogData {
+    #[serde(rename = "auditLog")]
+    audit_log: Vec<RawAuditRecord>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAuditRecord {
+    sequence: u64,
+    timestamp: u64,
+    event: String,
+    prev_hash: String,
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct AuditHeadData {
+    #[serde(rename = "auditHead")]
+    audit_head: Option<RawAuditHead>,
+}
+
+#[derive(Deserialize)]
+struct RawAuditHead {
+    length: u64,
+    hash: String,
+}
+
+fn decode_hash(hex_hash: &str) -> Result<[u8; 32], SdkError> {
+    let bytes = hex::decode(hex_hash).map_err(|e| SdkError::InvalidInput(format!("audit hash: {}", e)))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| SdkError::InvalidInput(format!("audit hash has {} bytes", bytes.len())))
+}
+
+impl Transport {
+    pub(crate) async fn audit_log(&self, market_id: &str, after_sequence: u64) -> Result<Vec<AuditRecord>, SdkError> {
+        let query = r#"
+            query AuditLog($marketId: String!, $afterSequence: Int, $limit: Int) {
+                auditLog(marketId: $marketId, afterSequence: $afterSequence, limit: $limit) {
+                    sequence
+                    timestamp
+                    event
+                    prevHash
+                    hash
+                }
+            }
+        "#;
+
+        let mut records = Vec::new();
+        let mut cursor = after_sequence;
+        loop {
+            let data: AuditLogData = self
+                .graphql_query_fresh(
+                    query,
+                    serde_json::json!({ "marketId": market_id, "afterSequence": cursor, "limit": AUDIT_PAGE }),
+                )
+                .await?;
+            let page_len = data.audit_log.len();
+            for raw in data.audit_log {
+                cursor = raw.sequence;
+                records.push(AuditRecord {
+                    sequence: raw.sequence,
+                    timestamp: raw.timestamp,
+                    event: serde_json::from_str(&raw.event)?,
+                    prev_hash: decode_hash(&raw.prev_hash)?,
+                    hash: decode_hash(&raw.hash)?,
+                });
+            }
+            if page_len < AUDIT_PAGE {
+                return Ok(records);
+            }
+        }
+    }
+
+    pub(crate) async fn verify_audit_log(
+        &self,
+        market_id: &str,
+        log: &[AuditRecord],
+    ) -> Result<AuditVerification, SdkError> {
+        let query = r#"
+            query AuditHead($marketId: String!) {
+                auditHead(marketId: $marketId) { length hash }
+            }
+        "#;
+
+        let violation = verify_audit_chain(log);
+        let data: AuditHeadData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let head = data
+            .audit_head
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+        let head_hash = decode_hash(&head.hash)?;
+        let matches_chain_head = log
+            .last()
+            .is_some_and(|last| last.sequence == head.length && last.hash == head_hash);
+
+        Ok(AuditVerification { records_checked: log.len(), violation, matches_chain_head })
+    }
+}
+
+impl OddsStreamSdk {
+    /// Export the audit records the market still holds after `after_sequence`, oldest first
+    pub async fn audit_log(&self, market_id: &str, after_sequence: u64) -> Result<Vec<AuditRecord>, SdkError> {
+        self.transport.audit_log(market_id, after_sequence).await
+    }
+
+    /// Recompute every hash in `log` and check it ends at the market's current head
+    pub async fn verify_audit_log(
+        &self,
+        market_id: &str,
+        log: &[AuditRecord],
+    ) -> Result<AuditVerification, SdkError> {
+        self.transport.verify_audit_log(market_id, log).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Export the audit records the market still holds after `after_sequence`, oldest first
+    pub async fn audit_log(&self, market_id: &str, after_sequence: u64) -> Result<Vec<AuditRecord>, SdkError> {
+        self.transport.audit_log(market_id, after_sequence).await
+    }
+
+    /// Recompute every hash in `log` and check it ends at the market's current head
+    pub async fn verify_audit_log(
+        &self,
+        market_id: &str,
+        log: &[AuditRecord],
+    ) -> Result<AuditVerification, SdkError> {
+        self.transport.verify_audit_log(market_id, log).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(events: Vec<AuditEvent>) -> Vec<AuditRecord> {
+        let mut prev_hash = [0; 32];
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let sequence = i as u64 + 1;
+                let hash = audit_hash(&prev_hash, sequence, 1_000 * sequence, &event);
+                let record = AuditRecord { sequence, timestamp: 1_000 * sequence, event, prev_hash, hash };
+                prev_hash = hash;
+                record
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let user = ChainId::default();
+        let mut log = chain(vec![
+            AuditEvent::OrdersAccepted { user_chain_id: user, nonce: 1, order_count: 1 },
+            AuditEvent::Fill { user_chain_id: user, buy_yes: true, size: Amount::from_tokens(10), cost: Amount::from_tokens(4) },
+            AuditEvent::Resolved { outcome: true },
+            AuditEvent::Payout { user_chain_id: user, amount: Amount::from_tokens(10) },
+        ]);
+        assert_eq!(verify_audit_chain(&log), None);
+        // A slice from the middle still verifies on its own
+        assert_eq!(verify_audit_chain(&log[1..]), None);
+
+        let mut inflated = log.clone();
+        inflated[3].event = AuditEvent::Payout { user_chain_id: user, amount: Amount::from_tokens(20) };
+        assert_eq!(verify_audit_chain(&inflated), Some(AuditViolation::HashMismatch { sequence: 4 }));
+
+        log.remove(1);
+        assert_eq!(verify_audit_chain(&log), Some(AuditViolation::SequenceGap { expected: 2, found: 3 }));
+    }
+}
@@ -0,0 +1,532 @@
+//! Backtesting: replay recorded market updates through a `TradingStrategy`
+//! and account for its orders as if they had been sent, with the same risk
+//! limits an `AIAgent` applies and the delays and costs of an `ExecutionModel`
+
+use crate::agent::limit_violation;
+use crate::execution::PendingOrder;
+use crate::{
+    AgentConfig, AgentContext, AgentDecision, ExecutionModel, MarketUpdate, OrderSide, SdkError, SimRng,
+    TradingStrategy,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How a backtest accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BacktestConfig {
+    /// Tokens the strategy starts with
+    pub initial_cash: f64,
+    /// Known outcomes; positions in these markets settle at 1 or 0 when the
+    /// run ends, the rest are marked at their last price
+    pub outcomes: HashMap<String, bool>,
+    /// Latency, costs and queueing; the default fills instantly at the quote
+    pub execution: ExecutionModel,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self { initial_cash: 1_000.0, outcomes: HashMap::new(), execution: ExecutionModel::default() }
+    }
+}
+
+/// An order the simulation filled, in whole or in part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestFill {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub shares: f64,
+    /// Per share, slippage included
+    pub price: f64,
+    pub fee: f64,
+    /// Micros since the Unix epoch
+    pub at: u64,
+}
+
+/// A decision the strategy made and what became of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestDecision {
+    /// Micros since the Unix epoch
+    pub at: u64,
+    pub decision: AgentDecision,
+    /// At least part of the order filled
+    pub filled: bool,
+    pub filled_shares: f64,
+    /// Why the decision did not fill, or did not fill completely, or how it was treated
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquityPoint {
+    pub at: u64,
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestReport {
+    pub initial_cash: f64,
+    pub final_equity: f64,
+    /// `final_equity / initial_cash - 1`
+    pub total_return: f64,
+    /// Largest fall from a running peak of equity, as a fraction of that peak
+    pub max_drawdown: f64,
+    pub fees_paid: f64,
+    pub fills: Vec<BacktestFill>,
+    pub decisions: Vec<BacktestDecision>,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+impl BacktestReport {
+    pub fn trades(&self) -> usize {
+        self.fills.len()
+    }
+}
+
+/// Read updates saved by `download_history`: one JSON `MarketUpdate` per line
+pub fn load_history(path: impl AsRef<Path>) -> Result<Vec<MarketUpdate>, SdkError> {
+    let text = std::fs::read_to_string(path)?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(SdkError::from))
+        .collect()
+}
+
+/// Largest peak-to-trough fall along `curve`, as a fraction of the peak
+pub fn max_drawdown(curve: &[EquityPoint]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst: f64 = 0.0;
+    for point in curve {
+        peak = peak.max(point.equity);
+        if peak > 0.0 {
+            worst = worst.max((peak - point.equity) / peak);
+        }
+    }
+    worst
+}
+
+/// Cash and positions during a run
+#[derive(Debug, Default)]
+struct Book {
+    cash: f64,
+    positions: HashMap<(String, OrderSide), f64>,
+    last: HashMap<String, MarketUpdate>,
+}
+
+impl Book {
+    fn price(&self, market_id: &str, side: OrderSide) -> Option<f64> {
+        let update = self.last.get(market_id)?;
+        Some(match side {
+            OrderSide::Yes => update.yes_odds,
+            OrderSide::No => update.no_odds,
+        })
+    }
+
+    /// Cash plus positions marked at their last price, or settled where `outcomes` knows the result
+    fn equity(&self, outcomes: &HashMap<String, bool>) -> f64 {
+        let positions: f64 = self
+            .positions
+            .iter()
+            .map(|((market_id, side), shares)| {
+                let value = match outcomes.get(market_id) {
+                    Some(outcome) => f64::from(u8::from(*outcome == (*side == OrderSide::Yes))),
+                    None => self.price(market_id, *side).unwrap_or_default(),
+                };
+                shares * value
+            })
+            .sum();
+        self.cash + positions
+    }
+}
+
+/// Replays history through strategies
+#[derive(Debug, Clone, Default)]
+pub struct Backtester {
+    config: BacktestConfig,
+}
+
+impl Backtester {
+    pub fn new(config: BacktestConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &BacktestConfig {
+        &self.config
+    }
+
+    /// Run `strategy` over the updates of `agent`'s markets, in time order.
+    ///
+    /// `on_tick` runs every decision interval of strategy time. An order
+    /// reaches the market after the model's latency and fills at the quote
+    /// then, plus slippage and fees, if that is within its `max_price` and
+    /// the cash covers it. A limit order priced out on arrival is dropped,
+    /// or rests and fills from later volume when the model has a
+    /// `RestingModel`. Liquidity is not simulated, and claims wait for the
+    /// settlement at the end of the run.
+    pub fn run(&self, strategy: &mut dyn TradingStrategy, agent: &AgentConfig, updates: &[MarketUpdate]) -> BacktestReport {
+        let mut updates: Vec<&MarketUpdate> =
+            updates.iter().filter(|update| agent.market_ids.contains(&update.market_id)).collect();
+        updates.sort_by_key(|update| (update.timestamp, update.sequence));
+
+        let model = &self.config.execution;
+        let mut run = Run {
+            agent,
+            model,
+            rng: SimRng::seeded(model.seed),
+            book: Book { cash: self.config.initial_cash, ..Default::default() },
+            context: AgentContext::default(),
+            pending: Vec::new(),
+            fills: Vec::new(),
+            decisions: Vec::new(),
+        };
+        let mut equity_curve = Vec::with_capacity(updates.len());
+        let interval = agent.decision_interval().as_micros() as u64;
+        let mut next_tick = updates.first().map(|update| update.timestamp * 1_000 + interval);
+
+        for update in updates {
+            let now = update.timestamp * 1_000;
+            while let Some(tick) = next_tick.filter(|tick| *tick <= now) {
+                run.context.now_micros = tick;
+                run.match_orders(None);
+                let decisions = strategy.on_tick(&run.context);
+                run.execute(decisions);
+                next_tick = Some(tick + interval);
+            }
+            run.context.now_micros = now;
+            let traded = run
+                .book
+                .last
+                .insert(update.market_id.clone(), update.clone())
+                .map_or(0.0, |previous| (update.volume - previous.volume).max(0.0));
+            run.context.updates.insert(update.market_id.clone(), update.clone());
+            // Orders already at the market trade against the new prices before the strategy reacts to them
+            run.match_orders(Some((update.market_id.as_str(), traded)));
+            let decisions = strategy.on_update(update, &run.context);
+            run.execute(decisions);
+            // Open positions are marked to market until the run ends
+            equity_curve.push(EquityPoint { at: now, equity: run.book.equity(&HashMap::new()) });
+        }
+        run.cancel_all("unfilled when the run ended");
+
+        let final_equity = run.book.equity(&self.config.outcomes);
+        if let Some(last) = equity_curve.last().copied() {
+            equity_curve.push(EquityPoint { at: last.at, equity: final_equity });
+        }
+        BacktestReport {
+            initial_cash: self.config.initial_cash,
+            final_equity,
+            total_return: if self.config.initial_cash > 0.0 { final_equity / self.config.initial_cash - 1.0 } else { 0.0 },
+            max_drawdown: max_drawdown(&equity_curve),
+            fees_paid: run.fills.iter().map(|fill| fill.fee).sum(),
+            fills: run.fills,
+            decisions: run.decisions,
+            equity_curve,
+        }
+    }
+}
+
+/// State of one backtest while it runs
+struct Run<'a> {
+    agent: &'a AgentConfig,
+    model: &'a ExecutionModel,
+    rng: SimRng,
+    book: Book,
+    context: AgentContext,
+    pending: Vec<PendingOrder>,
+    fills: Vec<BacktestFill>,
+    decisions: Vec<BacktestDecision>,
+}
+
+impl Run<'_> {
+    fn execute(&mut self, decisions: Vec<AgentDecision>) {
+        let now = self.context.now_micros;
+        for decision in decisions {
+            let index = self.decisions.len();
+            let note = match limit_violation(self.agent, self.context.exposure, &decision) {
+                Some(reason) => Some(reason),
+                None => self.accept(index, &decision),
+            };
+            self.decisions.push(BacktestDecision { at: now, decision, filled: false, filled_shares: 0.0, note });
+        }
+        // Orders with no latency reach the market straight away
+        self.match_orders(None);
+    }
+
+    /// Queue an order for the market; anything else is settled here. Returns the decision's note.
+    fn accept(&mut self, index: usize, decision: &AgentDecision) -> Option<String> {
+        let order = match decision {
+            AgentDecision::PlaceOrder(order) => order,
+            AgentDecision::Claim { .. } => return Some("settled when the run ends".to_string()),
+            AgentDecision::AddLiquidity { .. } | AgentDecision::RemoveLiquidity { .. } => {
+                return Some("liquidity is not simulated".to_string())
+            }
+        };
+        let Ok(shares) = order.amount.parse::<f64>() else {
+            return Some(format!("invalid amount {}", order.amount));
+        };
+        let limit = match order.max_price.as_deref().map(str::parse::<f64>).transpose() {
+            Ok(limit) => limit,
+            Err(_) => return Some(format!("invalid max price {}", order.max_price.as_deref().unwrap_or_default())),
+        };
+        // Committed when sent, as a live agent counts it; released again for whatever never fills
+        self.context.exposure += decision.notional();
+        self.pending.push(PendingOrder {
+            decision: index,
+            remaining: shares,
+            limit,
+            arrives_at: self.context.now_micros + self.model.latency.sample(&mut self.rng),
+            queue_ahead: 0.0,
+            expires_at: None,
+            resting: false,
+        });
+        None
+    }
+
+    fn order(&self, pending: &PendingOrder) -> (String, OrderSide) {
+        match &self.decisions[pending.decision].decision {
+            AgentDecision::PlaceOrder(order) => (order.market_id.clone(), order.side),
+            _ => unreachable!("only orders are queued"),
+        }
+    }
+
+    /// Work through the orders that have reached the market by now. `traded`
+    /// is the market that just printed and the volume it traded since its previous update.
+    fn match_orders(&mut self, traded: Option<(&str, f64)>) {
+        let now = self.context.now_micros;
+        let mut still_pending = Vec::with_capacity(self.pending.len());
+        for mut pending in std::mem::take(&mut self.pending) {
+            if pending.arrives_at > now {
+                still_pending.push(pending);
+                continue;
+            }
+            if pending.expires_at.is_some_and(|expires_at| expires_at <= now) {
+                self.close(&pending, "expired while resting");
+                continue;
+            }
+            let (market_id, side) = self.order(&pending);
+            let Some(quote) = self.book.price(&market_id, side) else {
+                self.close(&pending, &format!("no price for {} yet", market_id));
+                continue;
+            };
+
+            if !pending.resting {
+                let price = self.model.slippage.fill_price(quote, pending.remaining);
+                if pending.limit.is_none_or(|limit| price <= limit) {
+                    let filled = self.fill(&pending, &market_id, side, pending.remaining, price);
+                    pending.remaining -= filled;
+                    if pending.remaining > 0.0 {
+                        self.close(&pending, "cash ran out before the order filled");
+                    }
+                    continue;
+                }
+                let Some(resting) = self.model.resting else {
+                    self.close(&pending, &format!("price {} is above the limit", price));
+                    continue;
+                };
+                pending.resting = true;
+                pending.queue_ahead = resting.queue_ahead_shares;
+                pending.expires_at = resting.ttl_secs.map(|ttl| pending.arrives_at + ttl * 1_000_000);
+                still_pending.push(pending);
+                continue;
+            }
+
+            // Resting: fill from volume that printed at or below the limit, at the limit
+            let limit = pending.limit.unwrap_or(1.0);
+            if let (Some((printed, volume)), Some(resting)) = (traded, self.model.resting) {
+                if printed == market_id && quote <= limit && volume > 0.0 {
+                    let shares = pending.take_from_volume(volume, &resting);
+                    if shares > 0.0 {
+                        let filled = self.fill(&pending, &market_id, side, shares, limit);
+                        pending.remaining -= filled;
+                    }
+                }
+            }
+            if pending.remaining > 1e-12 {
+                still_pending.push(pending);
+            }
+        }
+        self.pending.extend(still_pending);
+    }
+
+    /// Fill up to `shares` at `price` plus fees, as far as the cash goes; returns the shares filled
+    fn fill(&mut self, pending: &PendingOrder, market_id: &str, side: OrderSide, shares: f64, price: f64) -> f64 {
+        let per_share = price + self.model.fee(price);
+        let shares = if per_share > 0.0 { shares.min(self.book.cash / per_share) } else { shares };
+        if shares <= 0.0 {
+            return 0.0;
+        }
+        let fee = self.model.fee(shares * price);
+        self.book.cash -= shares * price + fee;
+        *self.book.positions.entry((market_id.to_string(), side)).or_default() += shares;
+        self.fills.push(BacktestFill {
+            market_id: market_id.to_string(),
+            side,
+            shares,
+            price,
+            fee,
+            at: self.context.now_micros,
+        });
+        let record = &mut self.decisions[pending.decision];
+        record.filled = true;
+        record.filled_shares += shares;
+        shares
+    }
+
+    /// Give up on what is left of `pending`, releasing its exposure
+    fn close(&mut self, pending: &PendingOrder, reason: &str) {
+        self.context.exposure = (self.context.exposure - pending.remaining).max(0.0);
+        let record = &mut self.decisions[pending.decision];
+        record.note = Some(if record.filled {
+            format!("{} with {} shares unfilled", reason, pending.remaining)
+        } else {
+            reason.to_string()
+        });
+    }
+
+    fn cancel_all(&mut self, reason: &str) {
+        for pending in std::mem::take(&mut self.pending) {
+            self.close(&pending, reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarketOrder;
+
+    /// Buys 10 YES shares whenever YES trades below 0.4
+    struct BuyCheap;
+
+    impl TradingStrategy for BuyCheap {
+        fn name(&self) -> &str {
+            "buy_cheap"
+        }
+
+        fn on_update(&mut self, update: &MarketUpdate, _context: &AgentContext) -> Vec<AgentDecision> {
+            if update.yes_odds >= 0.4 {
+                return Vec::new();
+            }
+            vec![AgentDecision::PlaceOrder(MarketOrder {
+                market_id: update.market_id.clone(),
+                side: OrderSide::Yes,
+                amount: "10".to_string(),
+                max_price: None,
+                subaccount: None,
+                referral_code: None,
+            })]
+        }
+    }
+
+    fn update(yes_odds: f64, timestamp: u64) -> MarketUpdate {
+        MarketUpdate {
+            market_id: "m1".to_string(),
+            yes_odds,
+            no_odds: 1.0 - yes_odds,
+            volume: 0.0,
+            status: "Open".to_string(),
+            timestamp,
+            sequence: timestamp,
+        }
+    }
+
+    #[test]
+    fn test_backtest_settles_known_outcomes_and_respects_limits() {
+        let agent = AgentConfig {
+            name: "bt".to_string(),
+            market_ids: vec!["m1".to_string()],
+            max_order_size: 10.0,
+            max_exposure: 15.0,
+            decision_interval_secs: 60,
+            strategy_params: serde_json::Value::Null,
+        };
+        let config = BacktestConfig {
+            initial_cash: 100.0,
+            outcomes: HashMap::from([("m1".to_string(), true)]),
+            ..Default::default()
+        };
+        let history = [update(0.5, 1), update(0.3, 2), update(0.2, 3), update(0.6, 4)];
+
+        let report = Backtester::new(config).run(&mut BuyCheap, &agent, &history);
+        // The second cheap print would take exposure to 20
+        assert_eq!(report.trades(), 1);
+        assert_eq!(report.decisions.iter().filter(|decision| !decision.filled).count(), 1);
+        // 10 shares bought at 0.3 pay 1 each
+        assert!((report.final_equity - 107.0).abs() < 1e-9);
+        // Marked at 0.2 the position took 1 off the 100 peak
+        assert!((report.max_drawdown - 1.0 / 100.0).abs() < 1e-9);
+    }
+
+    /// Bids for 10 YES shares at 0.4 once
+    struct BidOnce(bool);
+
+    impl TradingStrategy for BidOnce {
+        fn name(&self) -> &str {
+            "bid_once"
+        }
+
+        fn on_update(&mut self, update: &MarketUpdate, _context: &AgentContext) -> Vec<AgentDecision> {
+            if std::mem::replace(&mut self.0, true) {
+                return Vec::new();
+            }
+            vec![AgentDecision::PlaceOrder(MarketOrder {
+                market_id: update.market_id.clone(),
+                side: OrderSide::Yes,
+                amount: "10".to_string(),
+                max_price: Some("0.4".to_string()),
+                subaccount: None,
+                referral_code: None,
+            })]
+        }
+    }
+
+    #[test]
+    fn test_late_limit_order_rests_and_fills_from_volume() {
+        let agent = AgentConfig {
+            name: "bt".to_string(),
+            market_ids: vec!["m1".to_string()],
+            max_order_size: 10.0,
+            max_exposure: 10.0,
+            decision_interval_secs: 60,
+            strategy_params: serde_json::Value::Null,
+        };
+        let execution = ExecutionModel {
+            latency: crate::LatencyModel::Fixed { millis: 1_000 },
+            fee_bps: 100,
+            resting: Some(crate::RestingModel { queue_ahead_shares: 5.0, participation: 0.5, ttl_secs: None }),
+            ..Default::default()
+        };
+        let print = |yes_odds, timestamp, volume| MarketUpdate { volume, ..update(yes_odds, timestamp) };
+        let history = [
+            print(0.5, 0, 0.0),
+            // Cheap enough, but the order is still on its way
+            print(0.35, 500, 10.0),
+            // Arrives priced out and joins the queue
+            print(0.45, 1_000, 20.0),
+            print(0.38, 2_000, 40.0),
+            print(0.39, 3_000, 50.0),
+        ];
+
+        let config = BacktestConfig { initial_cash: 100.0, execution, ..Default::default() };
+        let report = Backtester::new(config).run(&mut BidOnce(false), &agent, &history);
+        // 20 traded: 5 clear the queue, half of the other 15 reach the order; then 2.5 of the next 5
+        let fills: Vec<(f64, f64)> = report.fills.iter().map(|fill| (fill.shares, fill.price)).collect();
+        assert_eq!(fills, vec![(7.5, 0.4), (2.5, 0.4)]);
+        assert!((report.fees_paid - 0.04).abs() < 1e-9);
+        assert_eq!(report.decisions[0].filled_shares, 10.0);
+    }
+
+    #[test]
+    fn test_drawdown_is_measured_from_the_running_peak() {
+        let curve: Vec<EquityPoint> = [100.0, 120.0, 90.0, 130.0, 117.0]
+            .iter()
+            .enumerate()
+            .map(|(at, equity)| EquityPoint { at: at as u64, equity: *equity })
+            .collect();
+        assert!((max_drawdown(&curve) - 0.25).abs() < 1e-12);
+    }
+}
@@ -1,7 +1,8 @@
 //! OddsStream CLI for Conway Testnet
 //! Provides command-line interface for market operations
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
 use oddsstream_sdk::*;
 use std::str::FromStr;
 
@@ -10,7 +11,15 @@ use std::str::FromStr;
 #[command(about = "OddsStream CLI for Linera Conway Testnet", long_about = None)]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+    
+    /// Read commands from stdin, one per line, and print one JSON result per line
+    #[arg(long)]
+    stdin: bool,
+    
+    /// Send orders, claims and other writes without asking first
+    #[arg(long, short = 'y', global = true)]
+    yes: bool,
     
     #[arg(long, default_value = "https://faucet.testnet-conway.linera.net")]
     rpc_url: String,
@@ -20,6 +29,34 @@ struct Cli {
     
     #[arg(long)]
     private_key: Option<String>,
+    
+    /// Registry chain, for market administration commands
+    #[arg(long, requires = "registry_app_id")]
+    registry_chain_id: Option<String>,
+    
+    #[arg(long, requires = "registry_chain_id")]
+    registry_app_id: Option<String>,
+    
+    /// Oracle chain, for committee voting commands
+    #[arg(long, requires = "oracle_app_id")]
+    oracle_chain_id: Option<String>,
+    
+    #[arg(long, requires = "oracle_chain_id")]
+    oracle_app_id: Option<String>,
+    
+    /// Sign orders with a connected Ledger instead of a private key
+    #[cfg(feature = "ledger")]
+    #[arg(long, conflicts_with = "private_key")]
+    ledger: bool,
+    
+    /// Ledger account index to sign with
+    #[cfg(feature = "ledger")]
+    #[arg(long, default_value = "0")]
+    ledger_account: u32,
+    
+    /// Odds display/entry format: probability, decimal, american, fractional
+    #[arg(long, global = true, default_value = "probability")]
+    odds_format: OddsFormat,
 }
 
 #[derive(Subcommand)]
@@ -34,27 +71,86 @@ enum Commands {
         
         #[arg(long, default_value = "10")]
         limit: usize,
+        
+        /// Also list markets whose chains have been retired
+        #[arg(long)]
+        include_archived: bool,
     },
     
     /// Place an order
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Order {
+        #[command(subcommand)]
+        action: Option<OrderAction>,
+        
+        #[arg(long, required = true)]
+        market_id: Option<String>,
+        
+        #[arg(long, required = true)]
+        side: Option<String>,
+        
+        #[arg(long, required = true)]
+        amount: Option<f64>,
+        
+        /// Worst acceptable price, in the selected odds format
         #[arg(long)]
-        market_id: String,
+        max_price: Option<String>,
         
+        /// Attribute the position to this sub-account
         #[arg(long)]
-        side: String,
+        subaccount: Option<String>,
         
         #[arg(long)]
-        amount: f64,
+        referral_code: Option<String>,
         
+        /// Show the cost breakdown without submitting
         #[arg(long)]
-        max_price: Option<f64>,
+        dry_run: bool,
     },
     
     /// Submit batched orders
     Batch {
-        #[arg(long, value_delimiter = ',')]
+        #[arg(long, value_delimiter = ',', required_unless_present = "file")]
         orders: Vec<String>, // Format: "market_id:side:amount"
+        
+        /// Read orders from a CSV file with a header row, or a JSON array
+        /// (.json); columns: market_id, side, amount, and optionally
+        /// max_price (in the selected odds format), subaccount, referral_code
+        #[arg(long, conflicts_with = "orders")]
+        file: Option<String>,
+        
+        /// Predict fills, rejections and final odds against the markets' current state without submitting
+        #[arg(long)]
+        simulate: bool,
+    },
+    
+    /// Inspect a single market
+    Market {
+        #[command(subcommand)]
+        action: MarketAction,
+    },
+    
+    /// Manage market templates for recurring events
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    
+    /// Claim winnings from resolved markets
+    Claim {
+        /// Claim from every resolved market with a winning position
+        #[arg(long)]
+        all: bool,
+        
+        #[arg(long, required_unless_present = "all")]
+        market_id: Option<String>,
+    },
+    
+    /// Show P&L and exposure across all positions
+    Portfolio {
+        /// Defaults to the configured chain
+        #[arg(long)]
+        user_chain_id: Option<String>,
     },
     
     /// Wallet operations
@@ -68,6 +164,147 @@ enum Commands {
         #[command(subcommand)]
         action: AgentAction,
     },
+    
+    /// Oracle committee membership
+    Oracle {
+        #[command(subcommand)]
+        action: OracleAction,
+    },
+    
+    /// Follow live odds on markets, or market announcements from the registry, until interrupted
+    Watch {
+        /// Follow market creations, resolutions, cancellations and oracle updates instead
+        #[arg(long, conflicts_with = "market_ids")]
+        registry: bool,
+        
+        #[arg(long, value_delimiter = ',', required_unless_present = "registry")]
+        market_ids: Vec<String>,
+    },
+    
+    /// Run commands read from stdin over one connection, printing JSON lines
+    Repl,
+}
+
+/// One line of a repl session: any command, without the binary name or global flags
+#[derive(Parser)]
+#[command(name = "oddsstream-cli", no_binary_name = true)]
+struct ReplLine {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum OrderAction {
+    /// Change the size and limit of an order queued in a call auction; it keeps its id
+    Amend {
+        #[arg(long)]
+        market_id: String,
+        
+        #[arg(long)]
+        order_id: u64,
+        
+        /// New limit in the selected odds format; "none" removes it
+        #[arg(long)]
+        price: String,
+        
+        /// New size, replacing the old one
+        #[arg(long)]
+        amount: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MarketAction {
+    /// Show a market's settings, fees, oracle, timing, pools and recent trades
+    Show {
+        market_id: String,
+        
+        /// How many of the latest trades to list
+        #[arg(long, default_value = "10")]
+        trades: usize,
+    },
+    
+    /// Export the full market state as JSON
+    Snapshot {
+        #[arg(long)]
+        market_id: String,
+        
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<String>,
+    },
+    
+    /// Show a running call auction and its indicative clearing price
+    Auction {
+        #[arg(long)]
+        market_id: String,
+    },
+    
+    /// Estimate how far a large order moves the price and how to split it
+    Impact {
+        #[arg(long)]
+        market_id: String,
+        
+        #[arg(long)]
+        side: String,
+        
+        #[arg(long)]
+        amount: f64,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// Store a template read from a JSON file
+    Save {
+        #[arg(long)]
+        name: String,
+        
+        #[arg(long)]
+        file: String,
+    },
+    
+    /// List stored templates
+    List,
+    
+    /// Create a market for one fixture
+    Create {
+        #[arg(long)]
+        template: String,
+        
+        #[arg(long)]
+        fixture: String,
+        
+        /// Fixture start, in seconds since the Unix epoch
+        #[arg(long)]
+        starts_at: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum OracleAction {
+    /// List committee markets waiting for your vote
+    Pending {
+        /// Hex public key to list for; defaults to the signing key
+        #[arg(long)]
+        member_key: Option<String>,
+    },
+    
+    /// Sign and cast your vote on a market's outcome
+    Vote {
+        #[arg(long)]
+        market_id: String,
+        
+        /// yes or no
+        #[arg(long)]
+        outcome: String,
+    },
+    
+    /// Show the tally of a committee vote
+    Status {
+        #[arg(long)]
+        market_id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -80,6 +317,32 @@ enum WalletAction {
     
     /// Request test tokens
     Faucet,
+    
+    /// List payments, payouts, fees and claims on the connected chain
+    History {
+        /// Only entries from this date (YYYY-MM-DD, UTC) or Unix time in seconds
+        #[arg(long)]
+        since: Option<String>,
+        
+        /// table or csv
+        #[arg(long, default_value = "table")]
+        output: String,
+    },
+    
+    /// Show or change daily trading limits and self-exclusion
+    Limits {
+        /// New daily stake limit; "none" removes it
+        #[arg(long)]
+        daily_stake: Option<String>,
+        
+        /// New daily loss limit; "none" removes it
+        #[arg(long)]
+        daily_loss: Option<String>,
+        
+        /// Refuse all orders for this many days; cannot be undone
+        #[arg(long)]
+        exclude_days: Option<u64>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -95,6 +358,140 @@ enum AgentAction {
     
     /// List available strategies
     Strategies,
+    
+    /// Show the agents of a running agent process
+    Status {
+        /// One agent; all of them when omitted
+        name: Option<String>,
+        
+        #[command(flatten)]
+        control: ControlArgs,
+    },
+    
+    /// Stop an agent after the decision in progress
+    Stop {
+        name: String,
+        
+        #[command(flatten)]
+        control: ControlArgs,
+    },
+    
+    /// Suspend an agent's decisions; it keeps following its markets
+    Pause {
+        name: String,
+        
+        #[command(flatten)]
+        control: ControlArgs,
+    },
+    
+    /// Resume a paused agent
+    Resume {
+        name: String,
+        
+        #[command(flatten)]
+        control: ControlArgs,
+    },
+    
+    /// Run the agents of a daemon config as a service until SIGINT or SIGTERM
+    Daemon {
+        /// JSON file with the agents, their strategies and the restart policy
+        config: String,
+        
+        /// Where liveness (/healthz) and readiness (/readyz) probes are served
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        health: String,
+        
+        #[command(flatten)]
+        control: ControlArgs,
+        
+        /// Log format on stderr: json or text
+        #[arg(long, default_value = "json")]
+        log_format: String,
+        
+        /// Seconds agents get to wind down once asked to stop
+        #[arg(long, default_value = "30")]
+        grace_secs: u64,
+    },
+}
+
+/// How to reach a running agent process
+#[derive(Args)]
+struct ControlArgs {
+    /// Control endpoint: host:port or unix:<path>
+    #[arg(long, default_value = DEFAULT_CONTROL_ADDRESS)]
+    control: String,
+    
+    /// Bearer token the agent process was started with; defaults to $ODDSSTREAM_CONTROL_TOKEN
+    #[arg(long)]
+    control_token: Option<String>,
+}
+
+impl ControlArgs {
+    fn token(&self) -> Option<String> {
+        self.control_token.clone().or_else(|| std::env::var("ODDSSTREAM_CONTROL_TOKEN").ok())
+    }
+    
+    fn client(self) -> Result<AgentControlClient, SdkError> {
+        let token = self.token();
+        Ok(AgentControlClient::new(self.control.parse()?, token))
+    }
+}
+
+/// How often the daemon checks strategy plugin files for a new build
+#[cfg(feature = "plugins")]
+const PLUGIN_POLL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Strategies the daemon can build from a config alone: `wasm:<path>`
+/// plugins and the built-ins that need no live inputs, such as external
+/// odds feeds, wired up in code
+fn build_strategy(name: &str) -> Result<Box<dyn TradingStrategy>, SdkError> {
+    if let Some(path) = name.strip_prefix("wasm:") {
+        #[cfg(feature = "plugins")]
+        return Ok(Box::new(WasmStrategy::load(path, PluginLimits::default())?));
+        #[cfg(not(feature = "plugins"))]
+        return Err(SdkError::InvalidInput(format!("{} is a plugin; rebuild with the plugins feature", path)));
+    }
+    match name {
+        "lp_vault" => Ok(Box::new(LpVaultStrategy::default())),
+        other if STRATEGIES.contains(&other) => {
+            Err(SdkError::InvalidInput(format!("strategy {} cannot run in the daemon yet", other)))
+        }
+        other => Err(SdkError::InvalidInput(format!("unknown strategy {}", other))),
+    }
+}
+
+/// Log to stderr, as JSON lines or plain text; `RUST_LOG` sets the level, info by default
+fn init_logging(format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    match format {
+        "json" => subscriber.json().flatten_event(true).try_init()?,
+        "text" => subscriber.try_init()?,
+        other => return Err(format!("unknown log format {}; use json or text", other).into()),
+    }
+    Ok(())
+}
+
+/// Strategies `agent start` accepts
+const STRATEGIES: [&str; 6] = ["market_making", "arbitrage", "trend_following", "mean_reversion", "lp_vault", "value_betting"];
+
+/// Connection and output settings shared by every command of a run
+struct Session {
+    sdk: OddsStreamSdk,
+    odds_format: OddsFormat,
+    /// Results go out as JSON lines; human-readable output is suppressed
+    json: bool,
+    /// Writes go out without a confirmation prompt
+    yes: bool,
+}
+
+/// Human-readable output, skipped when the session prints JSON
+macro_rules! say {
+    ($session:expr, $($arg:tt)*) => {
+        if !$session.json {
+            println!($($arg)*);
+        }
+    };
 }
 
 #[tokio::main]
@@ -109,93 +506,746 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ChainId::default()
     };
     
-    let sdk = OddsStreamSdk::with_rpc_url(chain_id, cli.rpc_url);
+    let mut sdk = OddsStreamSdk::with_rpc_url(chain_id, cli.rpc_url);
+    if let Some(key) = &cli.private_key {
+        sdk = sdk.with_signer(std::sync::Arc::new(LocalSigner::from_hex(key)?));
+    }
+    #[cfg(feature = "ledger")]
+    if cli.ledger {
+        let ledger = LedgerSigner::connect(cli.ledger_account)?.with_confirm_hook(Box::new(|digest| {
+            eprintln!("🔐 Confirm on your Ledger. Order digest:");
+            eprintln!("   {}", hex::encode(digest));
+        }));
+        eprintln!("Using Ledger key {}", hex::encode(ledger.public_key()));
+        sdk = sdk.with_signer(std::sync::Arc::new(ledger));
+    }
+    if let (Some(chain), Some(app)) = (&cli.registry_chain_id, &cli.registry_app_id) {
+        sdk = sdk.with_registry_application(ChainId::from_str(chain)?, ApplicationId::from_str(app)?);
+    }
+    if let (Some(chain), Some(app)) = (&cli.oracle_chain_id, &cli.oracle_app_id) {
+        sdk = sdk.with_oracle_application(ChainId::from_str(chain)?, ApplicationId::from_str(app)?);
+    }
     
     match cli.command {
-        Commands::Markets { filter_status, min_volume, limit } => {
+        Some(Commands::Repl) => {}
+        Some(command) if !cli.stdin => {
+            let session = Session { sdk, odds_format: cli.odds_format, json: false, yes: cli.yes };
+            run_command(&session, command).await?;
+            return Ok(());
+        }
+        Some(_) => return Err("--stdin takes no command; put commands on stdin".into()),
+        None if !cli.stdin => return Err("no command given; see --help".into()),
+        None => {}
+    }
+    run_repl(&Session { sdk, odds_format: cli.odds_format, json: true, yes: cli.yes }).await
+}
+
+/// Run commands from stdin until it closes, one JSON result per line on
+/// stdout. A failing command reports its error and the session carries on.
+async fn run_repl(session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::IsTerminal;
+    use tokio::io::AsyncBufReadExt;
+    
+    let interactive = std::io::stdin().is_terminal();
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    let mut number = 0;
+    loop {
+        if interactive {
+            eprint!("oddsstream> ");
+        }
+        let Some(line) = lines.next_line().await? else { break };
+        number += 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if matches!(line, "exit" | "quit") {
+            break;
+        }
+        
+        let result = match split_command_line(line) {
+            Err(e) => Err(e),
+            Ok(words) => match ReplLine::try_parse_from(words) {
+                // Help and usage errors both come back as text for the caller to read
+                Err(e) => Err(e.render().to_string()),
+                Ok(ReplLine { command: Commands::Repl }) => Err("already in a repl".to_string()),
+                Ok(ReplLine { command }) => run_command(session, command).await.map_err(|e| e.to_string()),
+            },
+        };
+        let output = match result {
+            Ok(result) => serde_json::json!({ "line": number, "ok": true, "result": result }),
+            Err(error) => serde_json::json!({ "line": number, "ok": false, "error": error.trim_end() }),
+        };
+        println!("{}", output);
+    }
+    Ok(())
+}
+
+/// Whether a write should be confirmed first: not with `--yes`, and never
+/// silently when there is no one to ask. JSON sessions read commands from
+/// stdin, so they need `--yes` for writes.
+fn needs_confirmation(session: &Session) -> Result<bool, Box<dyn std::error::Error>> {
+    use std::io::IsTerminal;
+    
+    if session.yes {
+        Ok(false)
+    } else if session.json || !std::io::stdin().is_terminal() {
+        Err("this command needs confirmation; pass --yes to run it non-interactively".into())
+    } else {
+        Ok(true)
+    }
+}
+
+/// Ask a yes/no question on the terminal; anything but yes cancels
+fn confirm(question: &str) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+    
+    eprint!("{} [y/N] ", question);
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("cancelled; nothing sent".into()),
+    }
+}
+
+/// Ask before a write unless `--yes` was given
+fn confirm_write(session: &Session, question: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if needs_confirmation(session)? {
+        confirm(question)?;
+    }
+    Ok(())
+}
+
+/// Cost, slippage, fees and resulting position of each order, then the totals
+fn print_order_risks(odds_format: OddsFormat, orders: &[MarketOrder], risks: &[OrderRisk]) {
+    println!("🧾 Risk summary");
+    println!("==================");
+    println!("{:>4}  {:<24} {:<4} {:>10} {:>10} {:>21} {:>9} {:>9} {:>10}  {}",
+        "#", "Market", "Side", "Shares", "Max price", "Price", "Slippage", "Fees", "Total", "Position after");
+    for (index, (order, risk)) in orders.iter().zip(risks).enumerate() {
+        let quote = &risk.quote;
+        let max_price = order
+            .max_price
+            .as_deref()
+            .and_then(|price| price.parse().ok())
+            .map_or("-".to_string(), |price| odds_format.format(price));
+        println!("{:>4}  {:<24} {:<4} {:>10.2} {:>10} {:>21} {:>+9.4} {:>9.4} {:>10.4}  YES {:.2} | NO {:.2}{}",
+            index + 1,
+            quote.market_id,
+            format!("{:?}", quote.side).to_uppercase(),
+            quote.shares,
+            max_price,
+            format!("{} -> {}", odds_format.format(quote.price), odds_format.format(quote.price_after)),
+            quote.slippage(),
+            quote.taker_fee + quote.relayer_fee,
+            quote.total_cost,
+            risk.yes_shares_after,
+            risk.no_shares_after,
+            risk.subaccount.as_deref().map_or(String::new(), |subaccount| format!(" ({})", subaccount)));
+    }
+    let fees: f64 = risks.iter().map(|risk| risk.quote.taker_fee + risk.quote.relayer_fee).sum();
+    let total: f64 = risks.iter().map(|risk| risk.quote.total_cost).sum();
+    println!("Total cost: ${:.4} (fees ${:.4})", total, fees);
+}
+
+/// One agent's status and counters
+fn say_agent(session: &Session, agent: &AgentMetrics) {
+    say!(session, "Agent: {} ({})", agent.name, agent.strategy);
+    say!(session, "Status: {}", match &agent.status {
+        AgentStatus::Failed(error) => format!("Failed: {}", error),
+        status => format!("{:?}", status),
+    });
+    say!(session, "Executed: {} | Rejected: {} | Failed: {} | Notional: {:.2}",
+        agent.executed,
+        agent.rejected,
+        agent.failed,
+        agent.notional);
+    if let Some(at) = agent.last_event_at {
+        say!(session, "Last event: {} (micros)", at);
+    }
+}
+
+/// Split a command line into words the way a shell would for plain
+/// arguments: whitespace separates, quotes group, backslash escapes
+fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some('"') | None, '\\') => {
+                word.push(chars.next().ok_or("line ends in a backslash")?);
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Run one command, printing for people unless the session is in JSON mode,
+/// and return its result as JSON
+async fn run_command(session: &Session, command: Commands) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let sdk = &session.sdk;
+    let odds_format = session.odds_format;
+    
+    let result = match command {
+        Commands::Markets { filter_status, min_volume, limit, include_archived } => {
             let filters = MarketFilters {
                 status: filter_status,
                 min_volume,
                 limit: Some(limit),
+                include_archived,
                 ..Default::default()
             };
             
             let markets = sdk.query_markets(filters).await?;
+            let protocol = sdk.protocol_status().await?;
             
-            println!("📊 Active Markets:");
-            println!("==================");
-            for market in markets {
-                println!();
-                println!("ID: {}", market.id);
-                println!("Description: {}", market.description);
-                println!("YES: {:.2}% | NO: {:.2}%", 
-                    market.yes_odds * 100.0, 
-                    market.no_odds * 100.0);
-                println!("Volume: ${:.2}", market.volume);
-                println!("Status: {}", market.status);
+            if let Some(banner) = protocol.banner() {
+                say!(session, "⛔ {}", banner);
+                say!(session, );
+            }
+            say!(session, "📊 Active Markets:");
+            say!(session, "==================");
+            for market in &markets {
+                say!(session, );
+                say!(session, "ID: {}", market.id);
+                say!(session, "Description: {}", market.description);
+                say!(session, "YES: {} | NO: {}", 
+                    market.yes_odds_in(odds_format), 
+                    market.no_odds_in(odds_format));
+                say!(session, "Volume: ${:.2}", market.volume);
+                say!(session, "Status: {}", market.status);
             }
+            serde_json::to_value(&markets)?
         }
         
-        Commands::Order { market_id, side, amount, max_price } => {
-            println!("Placing order: {} {} ${}", side, market_id, amount);
+        Commands::Order { action: Some(OrderAction::Amend { market_id, order_id, price, amount }), .. } => {
+            let max_price = if price.eq_ignore_ascii_case("none") {
+                None
+            } else {
+                Some(odds_format.parse(&price)?)
+            };
+            let amount = Amount::from_str(&amount)?;
+            let user_chain_id = *sdk.chain_id();
+            
+            say!(session, "Amending order {} on {}: {} at {}",
+                order_id,
+                market_id,
+                amount,
+                max_price.map_or("no limit".to_string(), |price| odds_format.format(price)));
+            confirm_write(session, "Send this amendment?")?;
+            let transaction_id = sdk.amend_order(&market_id, order_id, amount, max_price, user_chain_id).await?;
+            say!(session, "✅ Amendment submitted");
+            say!(session, "Transaction ID: {}", transaction_id);
+            serde_json::json!({ "marketId": market_id, "orderId": order_id, "transactionId": transaction_id })
+        }
+        
+        Commands::Order {
+            action: None,
+            market_id: Some(market_id),
+            side: Some(side),
+            amount: Some(amount),
+            max_price,
+            subaccount,
+            referral_code,
+            dry_run,
+        } => {
+            if !dry_run {
+                say!(session, "Placing order: {} {} ${}", side, market_id, amount);
+            }
+            
+            let max_price = max_price
+                .map(|p| odds_format.parse(&p))
+                .transpose()?;
             
             let order = MarketOrder {
                 market_id,
                 side: if side.to_lowercase() == "yes" { OrderSide::Yes } else { OrderSide::No },
                 amount: amount.to_string(),
                 max_price: max_price.map(|p| p.to_string()),
+                subaccount,
+                referral_code,
             };
             
-            // In production, you would get user chain ID from wallet
-            let user_chain_id = ChainId::default(); // Placeholder
+            if dry_run {
+                let quote = sdk.quote_order(&order, None).await?;
+                say!(session, "🧾 Order preview: {} {} x {}", side, quote.market_id, quote.shares);
+                say!(session, "==================");
+                say!(session, "Price: {} -> {} (impact {:+.4})",
+                    odds_format.format(quote.price),
+                    odds_format.format(quote.price_after),
+                    quote.price_impact);
+                say!(session, "AMM cost:      ${:.4}", quote.amm_cost);
+                say!(session, "Taker fee:     ${:.4}", quote.taker_fee);
+                say!(session, "  oracle:      ${:.4}", quote.oracle_fee);
+                say!(session, "  protocol:    ${:.4}", quote.protocol_fee);
+                say!(session, "Relayer fee:   ${:.4}", quote.relayer_fee);
+                say!(session, "Total:         ${:.4}", quote.total_cost);
+                say!(session, "Effective price: {}", odds_format.format(quote.effective_price));
+                return Ok(serde_json::to_value(&quote)?);
+            }
             
+            let user_chain_id = *sdk.chain_id();
+            
+            if needs_confirmation(session)? {
+                let orders = [order.clone()];
+                print_order_risks(odds_format, &orders, &sdk.order_risks(&orders, user_chain_id).await?);
+                confirm("Submit this order?")?;
+            }
             let response = sdk.submit_batched_orders(vec![order], user_chain_id).await?;
             
-            println!("✅ Order submitted!");
-            println!("Transaction IDs: {:?}", response.transaction_ids);
+            say!(session, "✅ Order submitted!");
+            say!(session, "Transaction IDs: {:?}", response.transaction_ids);
+            serde_json::to_value(&response)?
         }
         
-        Commands::Batch { orders } => {
-            println!("Submitting {} batched orders", orders.len());
+        // Only reachable if clap's requirements change
+        Commands::Order { .. } => return Err("--market-id, --side and --amount are required".into()),
+        
+        Commands::Batch { orders, file, simulate } => {
+            let market_orders = if let Some(path) = file {
+                let file = load_order_file(&path, odds_format)?;
+                for error in &file.errors {
+                    eprintln!("{}: row {}: {}", path, error.row, error.message);
+                }
+                if !file.errors.is_empty() {
+                    return Err(format!("{} invalid row(s) in {}; nothing submitted", file.errors.len(), path).into());
+                }
+                file.orders
+            } else {
+                let mut market_orders = Vec::new();
+                for order_str in orders {
+                    let parts: Vec<&str> = order_str.split(':').collect();
+                    if parts.len() != 3 {
+                        eprintln!("Invalid order format: {}", order_str);
+                        continue;
+                    }
+                    
+                    let order = MarketOrder {
+                        market_id: parts[0].to_string(),
+                        side: if parts[1].to_lowercase() == "yes" { OrderSide::Yes } else { OrderSide::No },
+                        amount: parts[2].to_string(),
+                        max_price: None,
+                        subaccount: None,
+                        referral_code: None,
+                    };
+                    market_orders.push(order);
+                }
+                market_orders
+            };
             
-            let mut market_orders = Vec::new();
-            for order_str in orders {
-                let parts: Vec<&str> = order_str.split(':').collect();
-                if parts.len() != 3 {
-                    eprintln!("Invalid order format: {}", order_str);
-                    continue;
+            let user_chain_id = *sdk.chain_id();
+            
+            if simulate {
+                let simulation = sdk.simulate_batch(&market_orders, user_chain_id).await?;
+                say!(session, "🔮 Batch simulation");
+                say!(session, "==================");
+                for (index, order) in simulation.orders.iter().enumerate() {
+                    let outcome = match &order.outcome {
+                        SimulatedOutcome::Filled { price, cost } => {
+                            format!("fills at {} for ${:.4}", odds_format.format(*price), cost)
+                        }
+                        SimulatedOutcome::Queued => "queues for the auction".to_string(),
+                        SimulatedOutcome::Rejected(reason) => format!("❌ {}", reason),
+                    };
+                    say!(session, "{:>4}  {:<24} {:<4} {:>10.2}  {}",
+                        index + 1,
+                        order.market_id,
+                        format!("{:?}", order.side).to_uppercase(),
+                        order.shares,
+                        outcome);
+                }
+                for market in &simulation.markets {
+                    say!(session, "{}: YES {} -> {}{}",
+                        market.market_id,
+                        odds_format.format(market.yes_odds_before),
+                        odds_format.format(market.yes_odds_after),
+                        if market.trips_breaker { " (trips the circuit breaker)" } else { "" });
+                }
+                say!(session, "Total cost: ${:.4}", simulation.total_cost);
+                return Ok(serde_json::to_value(&simulation)?);
+            }
+            
+            say!(session, "Submitting {} batched orders", market_orders.len());
+            if needs_confirmation(session)? {
+                print_order_risks(odds_format, &market_orders, &sdk.order_risks(&market_orders, user_chain_id).await?);
+                confirm(&format!("Submit these {} orders?", market_orders.len()))?;
+            } else {
+                say!(session, "{:>4}  {:<24} {:<4} {:>12} {:>12}", "#", "Market", "Side", "Amount", "Max price");
+                for (index, order) in market_orders.iter().enumerate() {
+                    let max_price = order
+                        .max_price
+                        .as_deref()
+                        .and_then(|price| price.parse().ok())
+                        .map_or("-".to_string(), |price| odds_format.format(price));
+                    say!(session, "{:>4}  {:<24} {:<4} {:>12} {:>12}",
+                        index + 1,
+                        order.market_id,
+                        format!("{:?}", order.side).to_uppercase(),
+                        order.amount,
+                        max_price);
                 }
-                
-                let order = MarketOrder {
-                    market_id: parts[0].to_string(),
-                    side: if parts[1].to_lowercase() == "yes" { OrderSide::Yes } else { OrderSide::No },
-                    amount: parts[2].to_string(),
-                    max_price: None,
-                };
-                market_orders.push(order);
             }
             
-            let user_chain_id = ChainId::default(); // Placeholder
             let response = sdk.submit_batched_orders(market_orders, user_chain_id).await?;
             
-            println!("✅ Batch submitted!");
-            println!("Total orders: {}", response.total_orders);
-            println!("Transactions: {}", response.transaction_ids.len());
+            say!(session, "✅ Batch submitted!");
+            say!(session, "Total orders: {}", response.total_orders);
+            say!(session, "Transactions: {}", response.transaction_ids.len());
+            serde_json::to_value(&response)?
+        }
+        
+        Commands::Market { action } => {
+            match action {
+                MarketAction::Show { market_id, trades } => {
+                    let details = sdk.market_details(&market_id, trades).await?;
+                    let protocol = sdk.protocol_status().await?;
+                    let config = &details.config;
+                    let fees = &details.fee_schedule;
+                    let amount = |amount: Option<Amount>| amount.map_or("uncapped".to_string(), |amount| amount.to_string());
+                    
+                    if let Some(banner) = protocol.banner() {
+                        say!(session, "⛔ {}", banner);
+                        say!(session, );
+                    }
+                    say!(session, "📈 Market {}", config.market_id);
+                    say!(session, "==================");
+                    say!(session, "Description: {}", config.description);
+                    say!(session, "Status: {:?}", details.lifecycle.status);
+                    say!(session, "Locks at: {} | Resolves at: {} (micros)",
+                        details.lifecycle.locks_at,
+                        details.lifecycle.resolution_time);
+                    if let Some(parent) = &config.parent_market_id {
+                        say!(session, "Conditional on: {} resolving {}",
+                            parent,
+                            if config.activates_on == Some(true) { "YES" } else { "NO" });
+                    }
+                    if config.stage_count > 0 {
+                        say!(session, "Stages: {}", config.stage_count);
+                    }
+                    say!(session, );
+                    say!(session, "Oracle: {:?}", config.oracle_kind);
+                    if let Some(key) = &config.oracle_public_key {
+                        say!(session, "  TEE key: {}", key);
+                    }
+                    if let Some(size) = config.committee_size {
+                        say!(session, "  Committee members: {}", size);
+                    }
+                    say!(session, );
+                    let pools = AmmPools::new(details.stats.yes_depth, details.stats.no_depth);
+                    say!(session, "AMM: {}", config.amm_kind);
+                    say!(session, "Pools: YES {:.2} | NO {:.2}", pools.yes, pools.no);
+                    say!(session, "Odds: YES {} | NO {}",
+                        odds_format.format(pools.odds(OrderSide::Yes)),
+                        odds_format.format(pools.odds(OrderSide::No)));
+                    say!(session, "Pool cap: {} | Per-user exposure cap: {}", amount(config.max_pool), amount(config.max_user_exposure));
+                    say!(session, "Opening auction: {}s | Reopen after halt: {}", config.opening_auction_secs, config.reopen_after_halt);
+                    say!(session, "Settles in: {}", config.settlement_token.as_deref().unwrap_or("native token"));
+                    if let Some(allowlist) = &config.allowlist {
+                        say!(session, "Allowlist: {}", allowlist);
+                    }
+                    say!(session, );
+                    say!(session, "Taker fee: {} bps (referral {} | oracle {} | protocol {} bps of it)",
+                        fees.taker_fee_bps,
+                        fees.referral_share_bps,
+                        fees.oracle_share_bps,
+                        fees.protocol_share_bps);
+                    say!(session, "Maker fee: {} bps", fees.maker_fee_bps);
+                    for tier in &fees.tiers {
+                        say!(session, "  From {} volume (30d): taker {} | maker {} bps",
+                            tier.min_volume,
+                            tier.taker_fee_bps,
+                            tier.maker_fee_bps);
+                    }
+                    say!(session, "LP cooldown: {}s | Early exit fee: {} bps", fees.lp_cooldown_secs, fees.early_exit_fee_bps);
+                    say!(session, );
+                    say!(session, "Trades: {} | Average size: {:.2}", details.stats.trade_count, details.stats.average_trade_size);
+                    for trade in &details.recent_trades {
+                        say!(session, "  #{} {:?} {:.2} @ {} ({} micros)",
+                            trade.sequence,
+                            trade.aggressor,
+                            trade.size,
+                            odds_format.format(trade.price),
+                            trade.timestamp);
+                    }
+                    serde_json::to_value(&details)?
+                }
+                MarketAction::Snapshot { market_id, output } => {
+                    let snapshot = sdk.snapshot_market(&market_id).await?;
+                    
+                    if let Some(path) = output {
+                        snapshot.save(&path)?;
+                        say!(session, "✅ Snapshot of {} written to {}", market_id, path);
+                        say!(session, "Block height: {}", snapshot.block_height);
+                        say!(session, "Resting orders: {} | Positions: {}",
+                            snapshot.resting_orders.len(),
+                            snapshot.positions.len());
+                        serde_json::json!({ "marketId": market_id, "path": path, "blockHeight": snapshot.block_height })
+                    } else {
+                        say!(session, "{}", serde_json::to_string_pretty(&snapshot)?);
+                        serde_json::to_value(&snapshot)?
+                    }
+                }
+                MarketAction::Auction { market_id } => {
+                    match sdk.auction_state(&market_id).await? {
+                        None => {
+                            say!(session, "{} is trading continuously", market_id);
+                            serde_json::Value::Null
+                        }
+                        Some(auction) => {
+                            say!(session, "🔨 Call auction on {}", market_id);
+                            say!(session, "Ends at: {} (micros)", auction.ends_at);
+                            say!(session, "Queued orders: {}", auction.order_count);
+                            say!(session, "Demand: YES {:.2} | NO {:.2}", auction.yes_demand, auction.no_demand);
+                            say!(session, "Indicative price: YES {} | NO {}",
+                                odds_format.format(auction.indicative_yes_price),
+                                odds_format.format(auction.indicative_no_price));
+                            serde_json::to_value(&auction)?
+                        }
+                    }
+                }
+                MarketAction::Impact { market_id, side, amount } => {
+                    let side = if side.to_lowercase() == "yes" { OrderSide::Yes } else { OrderSide::No };
+                    let estimate = sdk.estimate_impact(&market_id, side, amount).await?;
+                    say!(session, "📉 Impact of {} x {:?} on {}", amount, side, market_id);
+                    say!(session, "Price: {} -> {} (impact {:+.4})",
+                        odds_format.format(estimate.price_before),
+                        odds_format.format(estimate.price_after),
+                        estimate.price_impact);
+                    say!(session, "Sent whole: ${:.4}", estimate.total_cost);
+                    let schedule = &estimate.schedule;
+                    if schedule.children.len() > 1 {
+                        say!(session, "Split into {} child orders: ${:.4} (saves ${:.4})",
+                            schedule.children.len(), schedule.total_cost, schedule.saving);
+                        for child in &schedule.children {
+                            say!(session, "  +{:>5}s  {:>10.2} at {}",
+                                child.offset_secs, child.shares, odds_format.format(child.expected_price));
+                        }
+                    } else {
+                        say!(session, "Splitting would not save enough to be worth it");
+                    }
+                    serde_json::to_value(&estimate)?
+                }
+            }
+        }
+        
+        Commands::Template { action } => {
+            match action {
+                TemplateAction::Save { name, file } => {
+                    let template: MarketTemplate = serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+                    confirm_write(session, &format!("Save template {} to the registry?", name))?;
+                    sdk.save_market_template(&name, &template).await?;
+                    say!(session, "✅ Template {} saved", name);
+                    serde_json::json!({ "name": name })
+                }
+                TemplateAction::List => {
+                    let templates = sdk.market_templates().await?;
+                    say!(session, "📋 Market Templates:");
+                    say!(session, "==================");
+                    for template in &templates {
+                        say!(session, );
+                        say!(session, "Name: {} ({})", template.name, template.category);
+                        say!(session, "Description: {}", template.description);
+                        say!(session, "Oracle: {} | Taker fee: {} bps", template.oracle, template.taker_fee_bps);
+                    }
+                    serde_json::to_value(&templates)?
+                }
+                TemplateAction::Create { template, fixture, starts_at } => {
+                    let fixture = Fixture { name: fixture, starts_at: starts_at * 1_000_000 };
+                    confirm_write(session, &format!("Create a {} market for {}?", template, fixture.name))?;
+                    let transaction_id = sdk.create_market_from_template(&template, &fixture).await?;
+                    say!(session, "✅ Market created from {} for {}", template, fixture.name);
+                    say!(session, "Transaction ID: {}", transaction_id);
+                    serde_json::json!({ "template": template, "fixture": fixture.name, "transactionId": transaction_id })
+                }
+            }
+        }
+        
+        Commands::Claim { all, market_id } => {
+            let user_chain_id = *sdk.chain_id();
+            
+            let summary = if all {
+                confirm_write(session, "Claim winnings from every resolved market?")?;
+                say!(session, "Sweeping resolved markets for winnings...");
+                sdk.claim_all(user_chain_id).await?
+            } else {
+                let market_id = market_id.unwrap_or_default();
+                let resolved = sdk.get_resolved_markets(user_chain_id, 0).await?;
+                let Some(market) = resolved.into_iter().find(|m| m.market_id == market_id) else {
+                    say!(session, "Market {} has not resolved or holds no position", market_id);
+                    return Ok(serde_json::to_value(ClaimSummary::default())?);
+                };
+                
+                confirm_write(session, &format!("Claim ${:.2} from {}?", market.payout, market.market_id))?;
+                let transaction_id = sdk.claim(&market.market_id, user_chain_id).await?;
+                ClaimSummary {
+                    claimed_markets: vec![market.market_id],
+                    transaction_ids: vec![transaction_id],
+                    total_payout: market.payout,
+                    failed: Vec::new(),
+                }
+            };
+            
+            if summary.claimed_markets.is_empty() && summary.failed.is_empty() {
+                say!(session, "Nothing to claim.");
+            } else {
+                say!(session, "✅ Claimed from {} market(s)", summary.claimed_markets.len());
+                for market_id in &summary.claimed_markets {
+                    say!(session, "  - {}", market_id);
+                }
+                say!(session, "Total payout: ${:.2}", summary.total_payout);
+            }
+            for (market_id, error) in &summary.failed {
+                say!(session, "❌ {}: {}", market_id, error);
+            }
+            serde_json::to_value(&summary)?
+        }
+        
+        Commands::Portfolio { user_chain_id } => {
+            let user_chain_id = match user_chain_id {
+                Some(id) => ChainId::from_str(&id)?,
+                None => *sdk.chain_id(),
+            };
+            
+            let report = sdk.portfolio_report(user_chain_id).await?;
+            
+            say!(session, "💼 Portfolio:");
+            say!(session, "==================");
+            for position in &report.positions {
+                say!(session, );
+                say!(session, "Market: {}", position.market_id);
+                say!(session, "YES shares: {:.2} | NO shares: {:.2}", position.yes_shares, position.no_shares);
+                say!(session, "Value: ${:.2} | Cost: ${:.2} | Unrealized: ${:+.2}",
+                    position.market_value(),
+                    position.cost_basis,
+                    position.unrealized_pnl());
+            }
+            
+            say!(session, );
+            say!(session, "Market value: ${:.2}", report.market_value);
+            say!(session, "Realized P&L: ${:+.2} | Unrealized P&L: ${:+.2}",
+                report.realized_pnl,
+                report.unrealized_pnl);
+            say!(session, "Exposure by category:");
+            for (category, exposure) in &report.exposure_by_category {
+                say!(session, "  - {}: ${:.2}", category, exposure);
+            }
+            say!(session, "Largest position: {:.1}% | HHI: {:.3}",
+                report.largest_position_share * 100.0,
+                report.herfindahl_index);
+            serde_json::to_value(&report)?
         }
         
         Commands::Wallet { action } => {
             match action {
                 WalletAction::Connect => {
-                    println!("Connecting wallet to Conway testnet...");
+                    say!(session, "Connecting wallet to Conway testnet...");
                     // Wallet connection logic
+                    serde_json::Value::Null
                 }
                 WalletAction::Balance => {
-                    println!("Fetching balance...");
+                    say!(session, "Fetching balance...");
                     // Balance query logic
+                    serde_json::Value::Null
                 }
                 WalletAction::Faucet => {
-                    println!("Requesting test tokens from faucet...");
+                    say!(session, "Requesting test tokens from faucet...");
                     // Faucet request logic
+                    serde_json::Value::Null
+                }
+                WalletAction::History { since, output } => {
+                    let since = since.as_deref().map(parse_since).transpose()?.unwrap_or(0);
+                    let receipts = sdk.wallet_history(since).await?;
+                    match output.as_str() {
+                        // In a repl the receipts are the JSON result instead
+                        "csv" if !session.json => write_history_csv(&receipts, std::io::stdout().lock())?,
+                        "csv" => {}
+                        "table" => {
+                            say!(session, "🧾 Wallet history ({} entries):", receipts.len());
+                            say!(session, "==================");
+                            for receipt in &receipts {
+                                say!(session, "{:>8}  {:<14} {}{:<24} {}",
+                                    receipt.height,
+                                    format!("{:?}", receipt.kind),
+                                    if receipt.kind.is_debit() { "-" } else { "+" },
+                                    receipt.amount,
+                                    receipt.counterparty);
+                            }
+                        }
+                        other => return Err(format!("unknown output format {}; use table or csv", other).into()),
+                    }
+                    serde_json::to_value(&receipts)?
+                }
+                WalletAction::Limits { daily_stake, daily_loss, exclude_days } => {
+                    let parse_limit = |value: &str| -> Result<Option<Amount>, Box<dyn std::error::Error>> {
+                        if value.eq_ignore_ascii_case("none") {
+                            Ok(None)
+                        } else {
+                            Ok(Some(Amount::from_str(value)?))
+                        }
+                    };
+                    if daily_stake.is_some() || daily_loss.is_some() {
+                        confirm_write(session, "Change your trading limits?")?;
+                        let current = sdk.trading_limits(*sdk.chain_id()).await?;
+                        let stake = match &daily_stake {
+                            Some(value) => parse_limit(value)?,
+                            None => current.daily_stake_limit,
+                        };
+                        let loss = match &daily_loss {
+                            Some(value) => parse_limit(value)?,
+                            None => current.daily_loss_limit,
+                        };
+                        sdk.set_trading_limits(stake, loss).await?;
+                        say!(session, "✅ Limits updated (increases apply after 24 hours)");
+                    }
+                    if let Some(days) = exclude_days {
+                        confirm_write(session, &format!("Self-exclude from trading for {} day(s)? This cannot be undone early.", days))?;
+                        sdk.self_exclude(std::time::Duration::from_secs(days * 86_400)).await?;
+                        say!(session, "✅ Self-excluded for {} day(s)", days);
+                    }
+                    
+                    let limits = sdk.trading_limits(*sdk.chain_id()).await?;
+                    let show = |limit: Option<Amount>| limit.map_or("none".to_string(), |limit| limit.to_string());
+                    say!(session, "🛡️  Trading limits:");
+                    say!(session, "==================");
+                    say!(session, "Daily stake limit: {} (staked today: {})", show(limits.daily_stake_limit), limits.staked_today);
+                    say!(session, "Daily loss limit: {} (net loss today: {})", show(limits.daily_loss_limit), limits.net_loss_today);
+                    if let Some(effective_at) = limits.pending_effective_at {
+                        say!(session, "Pending: stake {} | loss {} from {} (micros)",
+                            show(limits.pending_stake_limit),
+                            show(limits.pending_loss_limit),
+                            effective_at);
+                    }
+                    if limits.excluded_until > 0 {
+                        say!(session, "Self-excluded until: {} (micros)", limits.excluded_until);
+                    }
+                    serde_json::to_value(&limits)?
                 }
             }
         }
@@ -203,19 +1253,220 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Agent { action } => {
             match action {
                 AgentAction::Start { strategy, market_id } => {
-                    println!("Starting {} agent for market {}", strategy, market_id);
+                    say!(session, "Starting {} agent for market {}", strategy, market_id);
                     // Agent startup logic
+                    serde_json::Value::Null
                 }
                 AgentAction::Strategies => {
-                    println!("Available strategies:");
-                    println!("  - market_making");
-                    println!("  - arbitrage");
-                    println!("  - trend_following");
-                    println!("  - mean_reversion");
+                    say!(session, "Available strategies:");
+                    for strategy in STRATEGIES {
+                        say!(session, "  - {}", strategy);
+                    }
+                    serde_json::json!(STRATEGIES)
+                }
+                AgentAction::Status { name: Some(name), control } => {
+                    let agent = control.client()?.agent_status(&name).await?;
+                    say_agent(session, &agent);
+                    serde_json::to_value(&agent)?
+                }
+                AgentAction::Status { name: None, control } => {
+                    let status = control.client()?.status().await?;
+                    say!(session, "🤖 Agents: {} running | {} paused | {} failed",
+                        status.fleet.running,
+                        status.fleet.paused,
+                        status.fleet.failed_agents);
+                    say!(session, "==================");
+                    for agent in &status.agents {
+                        say!(session, );
+                        say_agent(session, agent);
+                    }
+                    serde_json::to_value(&status)?
+                }
+                AgentAction::Stop { name, control } => {
+                    confirm_write(session, &format!("Stop agent {}?", name))?;
+                    control.client()?.stop(&name).await?;
+                    say!(session, "✅ Stop sent to {}", name);
+                    serde_json::json!({ "agent": name, "command": "stop" })
+                }
+                AgentAction::Pause { name, control } => {
+                    control.client()?.pause(&name).await?;
+                    say!(session, "⏸️  Pause sent to {}", name);
+                    serde_json::json!({ "agent": name, "command": "pause" })
+                }
+                AgentAction::Resume { name, control } => {
+                    confirm_write(session, &format!("Resume trading for agent {}?", name))?;
+                    control.client()?.resume(&name).await?;
+                    say!(session, "▶️  Resume sent to {}", name);
+                    serde_json::json!({ "agent": name, "command": "resume" })
+                }
+                AgentAction::Daemon { config, health, control, log_format, grace_secs } => {
+                    init_logging(&log_format)?;
+                    let daemon = DaemonConfig::load(&config)?;
+                    // Unknown strategies fail here rather than as endless restarts
+                    for agent in &daemon.agents {
+                        build_strategy(&agent.strategy)?;
+                    }
+                    
+                    let shutdown = Shutdown::new();
+                    shutdown.listen_for_signals();
+                    let fleet = std::sync::Arc::new(AgentFleet::new(sdk.clone(), shutdown.clone()));
+                    let token = control.token();
+                    let address: ControlAddress = control.control.parse()?;
+                    if token.is_none() && matches!(address, ControlAddress::Tcp(_)) {
+                        tracing::warn!(control = %address, "control endpoint accepts requests without a token");
+                    }
+                    serve_agent_control(ControlListener::bind(&address).await?, fleet.clone(), token, shutdown.clone());
+                    serve_health(tokio::net::TcpListener::bind(&health).await?, fleet.clone(), shutdown.clone());
+                    let factory: StrategyFactory = std::sync::Arc::new(build_strategy);
+                    let agents = daemon.agents.len();
+                    for agent in daemon.agents {
+                        // A new build of a plugin replaces the strategy in place, keeping the agent's state
+                        #[cfg(feature = "plugins")]
+                        if let Some(path) = agent.strategy.strip_prefix("wasm:") {
+                            watch_strategy_plugin(
+                                path,
+                                agent.config.name.clone(),
+                                fleet.clone(),
+                                agent.config.strategy_params.clone(),
+                                PluginLimits::default(),
+                                PLUGIN_POLL,
+                                shutdown.clone(),
+                            );
+                        }
+                        supervise_agent(
+                            fleet.clone(),
+                            factory.clone(),
+                            agent.strategy,
+                            agent.config,
+                            daemon.restart.clone(),
+                            shutdown.clone(),
+                        );
+                    }
+                    tracing::info!(agents, %health, control = %address, "agent daemon started");
+                    
+                    shutdown.triggered().await;
+                    let drained = shutdown.shutdown(std::time::Duration::from_secs(grace_secs)).await;
+                    if !drained {
+                        tracing::warn!(grace_secs, "agents did not wind down in time");
+                    }
+                    tracing::info!("agent daemon stopped");
+                    serde_json::json!({ "agents": agents, "drained": drained })
                 }
             }
         }
-    }
+        
+        Commands::Oracle { action } => {
+            match action {
+                OracleAction::Pending { member_key } => {
+                    let member_key = match member_key {
+                        Some(key) => hex::decode(key.trim_start_matches("0x"))?,
+                        None => sdk.signer_public_key().ok_or("pass --member-key or a signing key")?,
+                    };
+                    let pending = sdk.list_pending_votes(&member_key).await?;
+                    if pending.is_empty() {
+                        say!(session, "No votes waiting for {}", hex::encode(&member_key));
+                    }
+                    for vote in &pending {
+                        say!(session, "🗳️  {} — {}", vote.market_id, vote.question);
+                        say!(session, "   Due: {} (micros) | Votes: {} of {} needed",
+                            vote.resolution_time, vote.votes_cast, vote.quorum);
+                    }
+                    serde_json::to_value(&pending)?
+                }
+                OracleAction::Vote { market_id, outcome } => {
+                    let outcome = match outcome.to_lowercase().as_str() {
+                        "yes" => true,
+                        "no" => false,
+                        other => return Err(format!("outcome must be yes or no, not {}", other).into()),
+                    };
+                    let status = sdk.vote_status(&market_id).await?;
+                    let public_key = sdk.signer_public_key().ok_or("voting requires a signing key")?;
+                    if status.has_voted(&public_key) {
+                        return Err(format!("{} already voted on {}", hex::encode(&public_key), market_id).into());
+                    }
+                    if let Some(decided) = status.outcome {
+                        return Err(format!("{} is already decided: {}", market_id, if decided { "YES" } else { "NO" }).into());
+                    }
+                    say!(session, "Tally: YES {} | NO {} | quorum {} of {}",
+                        status.yes_votes, status.no_votes, status.quorum, status.member_count);
+                    confirm_write(session, &format!("Vote {} on {}? Votes cannot be changed",
+                        if outcome { "YES" } else { "NO" }, market_id))?;
+                    let vote = sdk.sign_vote(&market_id, outcome).await?;
+                    let transaction_id = sdk.submit_vote(&market_id, outcome, vote).await?;
+                    say!(session, "✅ Vote cast on {}", market_id);
+                    say!(session, "Transaction ID: {}", transaction_id);
+                    serde_json::json!({ "marketId": market_id, "outcome": outcome, "transactionId": transaction_id })
+                }
+                OracleAction::Status { market_id } => {
+                    let status = sdk.vote_status(&market_id).await?;
+                    say!(session, "🗳️  Committee vote on {}", status.market_id);
+                    say!(session, "YES {} | NO {} | quorum {} of {} members",
+                        status.yes_votes, status.no_votes, status.quorum, status.member_count);
+                    match status.outcome {
+                        Some(outcome) => say!(session, "Decided: {}", if outcome { "YES" } else { "NO" }),
+                        None => say!(session, "Open: {} more vote(s) needed", status.votes_needed()),
+                    }
+                    serde_json::to_value(&status)?
+                }
+            }
+        }
+        
+        Commands::Watch { registry: true, .. } => {
+            use futures::StreamExt;
+            
+            let mut events = sdk.subscribe_registry_events();
+            say!(session, "👀 Watching the registry (Ctrl-C to stop)");
+            let mut seen = 0u64;
+            loop {
+                let event = tokio::select! {
+                    event = events.next() => event,
+                    _ = tokio::signal::ctrl_c() => None,
+                };
+                let Some(event) = event else { break };
+                seen += 1;
+                if session.json {
+                    println!("{}", serde_json::to_string(&event)?);
+                    continue;
+                }
+                match event.kind {
+                    RegistryEventKind::MarketCreated => say!(session, "🆕 {} created", event.market_id),
+                    RegistryEventKind::MarketResolved => say!(session, "🏁 {} resolved {}", event.market_id, event.detail),
+                    RegistryEventKind::MarketCancelled => say!(session, "🚫 {} cancelled", event.market_id),
+                    RegistryEventKind::OracleUpdated => say!(session, "🔮 {} oracle is now {}", event.market_id, event.detail),
+                }
+            }
+            serde_json::json!({ "events": seen })
+        }
+        
+        Commands::Watch { market_ids, .. } => {
+            use futures::StreamExt;
+            
+            let mut updates = sdk.subscribe_market_updates_stream(market_ids.clone()).await?;
+            say!(session, "👀 Watching {} (Ctrl-C to stop)", market_ids.join(", "));
+            let mut seen = 0u64;
+            loop {
+                let update = tokio::select! {
+                    update = updates.next() => update,
+                    _ = tokio::signal::ctrl_c() => None,
+                };
+                let Some(update) = update else { break };
+                seen += 1;
+                if session.json {
+                    println!("{}", serde_json::to_string(&update)?);
+                    continue;
+                }
+                say!(session, "{} | YES {} | NO {} | volume {:.2} | {}",
+                    update.market_id,
+                    odds_format.format(update.yes_odds),
+                    odds_format.format(update.no_odds),
+                    update.volume,
+                    update.status);
+            }
+            serde_json::json!({ "updates": seen })
+        }
+        
+        Commands::Repl => return Err("repl only runs from the top level".into()),
+    };
     
-    Ok(())
-}
\ No newline at end of file
+    Ok(result)
+}
@@ -0,0 +1,204 @@
+//! Response cache for GraphQL reads: ETag revalidation plus an in-memory LRU
+
+use reqwest::header::HeaderValue;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Responses kept before the least recently used is evicted
+    pub capacity: usize,
+    /// How long rarely-changing reads (registry mapping, fee schedules,
+    /// templates) are served locally when the server sends no `max-age`
+    pub default_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { capacity: 256, default_ttl: Duration::from_secs(30) }
+    }
+}
+
+/// How a query may use the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CacheMode {
+    /// Serve while the server's `max-age` holds, then revalidate by ETag
+    Revalidate,
+    /// As `Revalidate`, but also serve for `default_ttl` after each fetch
+    Reuse,
+    /// Always hit the server; for reads that feed trading decisions
+    Bypass,
+}
+
+/// What the server's `Cache-Control` allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Freshness {
+    NoStore,
+    MaxAge(Duration),
+    Unspecified,
+}
+
+impl Freshness {
+    pub(crate) fn from_header(value: Option<&HeaderValue>) -> Self {
+        let Some(value) = value.and_then(|value| value.to_str().ok()) else {
+            return Self::Unspecified;
+        };
+        let mut freshness = Self::Unspecified;
+        for directive in value.split(',').map(str::trim) {
+            if directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("private") {
+                return Self::NoStore;
+            }
+            if directive.eq_ignore_ascii_case("no-cache") {
+                freshness = Self::MaxAge(Duration::ZERO);
+            } else if let Some(secs) = directive.strip_prefix("max-age=").and_then(|s| s.parse().ok()) {
+                if freshness == Self::Unspecified {
+                    freshness = Self::MaxAge(Duration::from_secs(secs));
+                }
+            }
+        }
+        freshness
+    }
+}
+
+pub(crate) enum Lookup {
+    Fresh(serde_json::Value),
+    /// Cached but expired; revalidate with the ETag if there is one
+    Stale(Option<String>),
+    Miss,
+}
+
+struct CachedResponse {
+    body: serde_json::Value,
+    etag: Option<String>,
+    /// When the server's `max-age` runs out
+    expires_at: Instant,
+    fetched_at: Instant,
+    last_used: u64,
+}
+
+pub(crate) struct ResponseCache {
+    config: CacheConfig,
+    entries: HashMap<String, CachedResponse>,
+    clock: u64,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(config: CacheConfig) -> Self {
+        Self { config, entries: HashMap::new(), clock: 0 }
+    }
+
+    /// Whitespace-insensitive query text plus variables; `serde_json` maps are
+    /// sorted, so equal variables always serialize the same way
+    pub(crate) fn key(query: &str, variables: &serde_json::Value) -> String {
+        let query: Vec<&str> = query.split_whitespace().collect();
+        format!("{}\n{}", query.join(" "), variables)
+    }
+
+    pub(crate) fn lookup(&mut self, key: &str, mode: CacheMode) -> Lookup {
+        self.clock += 1;
+        let Some(entry) = self.entries.get_mut(key) else {
+            return Lookup::Miss;
+        };
+        entry.last_used = self.clock;
+
+        let now = Instant::now();
+        let reusable = mode == CacheMode::Reuse && now < entry.fetched_at + self.config.default_ttl;
+        if now < entry.expires_at || reusable {
+            Lookup::Fresh(entry.body.clone())
+        } else {
+            Lookup::Stale(entry.etag.clone())
+        }
+    }
+
+    pub(crate) fn store(
+        &mut self,
+        key: String,
+        body: serde_json::Value,
+        etag: Option<String>,
+        freshness: Freshness,
+    ) {
+        let max_age = match freshness {
+            Freshness::NoStore => {
+                self.entries.remove(&key);
+                return;
+            }
+            Freshness::MaxAge(max_age) => max_age,
+            Freshness::Unspecified => Duration::ZERO,
+        };
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.config.capacity {
+            self.evict_least_recent();
+        }
+        if self.config.capacity == 0 {
+            return;
+        }
+
+        self.clock += 1;
+        let now = Instant::now();
+        self.entries.insert(
+            key,
+            CachedResponse { body, etag, expires_at: now + max_age, fetched_at: now, last_used: self.clock },
+        );
+    }
+
+    /// Extend a revalidated entry after a `304 Not Modified`; `None` if it was evicted meanwhile
+    pub(crate) fn revalidated(&mut self, key: &str, freshness: Freshness) -> Option<serde_json::Value> {
+        let entry = self.entries.get_mut(key)?;
+        let now = Instant::now();
+        entry.fetched_at = now;
+        entry.expires_at = match freshness {
+            Freshness::MaxAge(max_age) => now + max_age,
+            Freshness::NoStore | Freshness::Unspecified => now,
+        };
+        Some(entry.body.clone())
+    }
+
+    fn evict_least_recent(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_ignores_query_whitespace() {
+        let a = ResponseCache::key("query  A {\n  x\n}", &json!({ "b": 1, "a": 2 }));
+        let b = ResponseCache::key("query A { x }", &json!({ "a": 2, "b": 1 }));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_cache_control_parsing() {
+        let header = |s: &'static str| Freshness::from_header(Some(&HeaderValue::from_static(s)));
+        assert_eq!(header("public, max-age=60"), Freshness::MaxAge(Duration::from_secs(60)));
+        assert_eq!(header("no-cache"), Freshness::MaxAge(Duration::ZERO));
+        assert_eq!(header("max-age=60, no-store"), Freshness::NoStore);
+        assert_eq!(Freshness::from_header(None), Freshness::Unspecified);
+    }
+
+    #[test]
+    fn test_lru_eviction_and_modes() {
+        let mut cache = ResponseCache::new(CacheConfig { capacity: 2, default_ttl: Duration::from_secs(60) });
+        cache.store("a".into(), json!(1), Some("\"v1\"".into()), Freshness::Unspecified);
+        cache.store("b".into(), json!(2), None, Freshness::MaxAge(Duration::from_secs(60)));
+
+        // No max-age: must revalidate unless the caller opts into reuse
+        assert!(matches!(cache.lookup("a", CacheMode::Revalidate), Lookup::Stale(Some(_))));
+        assert!(matches!(cache.lookup("a", CacheMode::Reuse), Lookup::Fresh(_)));
+
+        // "a" was used last, so "b" goes
+        cache.store("c".into(), json!(3), None, Freshness::Unspecified);
+        assert!(matches!(cache.lookup("b", CacheMode::Revalidate), Lookup::Miss));
+        assert!(matches!(cache.lookup("a", CacheMode::Reuse), Lookup::Fresh(_)));
+    }
+}
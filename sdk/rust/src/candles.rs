@@ -0,0 +1,245 @@
+//! Client-side OHLC candle aggregation over market update streams
+
+use crate::{MarketUpdate, OddsStreamSdk, SdkError, SubscriptionHandle};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Candle bucket width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+}
+
+impl CandleInterval {
+    /// Bucket width in milliseconds
+    pub fn millis(&self) -> u64 {
+        match self {
+            CandleInterval::OneMinute => 60_000,
+            CandleInterval::FiveMinutes => 5 * 60_000,
+            CandleInterval::OneHour => 60 * 60_000,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp` (milliseconds)
+    pub fn bucket_start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.millis()
+    }
+}
+
+/// OHLC candle of the YES implied probability for one market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candle {
+    pub market_id: String,
+    pub interval: CandleInterval,
+    /// Bucket start, milliseconds since the Unix epoch
+    pub open_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Traded volume within the bucket
+    pub volume: f64,
+    /// Number of updates folded into the candle; zero for gap-filled candles
+    pub updates: u32,
+}
+
+impl Candle {
+    fn open_at(market_id: &str, interval: CandleInterval, open_time: u64, price: f64) -> Self {
+        Self {
+            market_id: market_id.to_string(),
+            interval,
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0.0,
+            updates: 0,
+        }
+    }
+}
+
+/// Buckets `MarketUpdate`s into candles, one open candle per market.
+///
+/// Updates carry the market's cumulative volume, so candle volume is the
+/// difference between consecutive updates. Updates older than the open bucket
+/// are dropped since the earlier candle has already been emitted.
+pub struct CandleAggregator {
+    interval: CandleInterval,
+    fill_gaps: bool,
+    open: HashMap<String, Candle>,
+    last_volume: HashMap<String, f64>,
+}
+
+impl CandleAggregator {
+    pub fn new(interval: CandleInterval) -> Self {
+        Self {
+            interval,
+            fill_gaps: true,
+            open: HashMap::new(),
+            last_volume: HashMap::new(),
+        }
+    }
+
+    /// Emit flat candles for buckets with no updates (enabled by default)
+    pub fn with_gap_filling(mut self, fill_gaps: bool) -> Self {
+        self.fill_gaps = fill_gaps;
+        self
+    }
+
+    /// Fold an update in, returning any candles it closed
+    pub fn push(&mut self, update: &MarketUpdate) -> Vec<Candle> {
+        let bucket = self.interval.bucket_start(update.timestamp);
+        let price = update.yes_odds;
+
+        let mut closed = Vec::new();
+        // Dropped before its volume is recorded, so the next delta is taken from the last
+        // update that was counted
+        if matches!(self.open.get(&update.market_id), Some(candle) if bucket < candle.open_time) {
+            return closed;
+        }
+        let volume_delta = match self.last_volume.insert(update.market_id.clone(), update.volume) {
+            Some(previous) => (update.volume - previous).max(0.0),
+            None => 0.0,
+        };
+
+        match self.open.get_mut(&update.market_id) {
+            Some(candle) if bucket == candle.open_time => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += volume_delta;
+                candle.updates += 1;
+                return closed;
+            }
+            Some(candle) => {
+                let finished = candle.clone();
+                let step = self.interval.millis();
+                if self.fill_gaps {
+                    let mut gap = finished.open_time + step;
+                    closed.push(finished.clone());
+                    while gap < bucket {
+                        closed.push(Candle::open_at(
+                            &finished.market_id,
+                            self.interval,
+                            gap,
+                            finished.close,
+                        ));
+                        gap += step;
+                    }
+                } else {
+                    closed.push(finished);
+                }
+            }
+            None => {}
+        }
+
+        let mut candle = Candle::open_at(&update.market_id, self.interval, bucket, price);
+        candle.volume = volume_delta;
+        candle.updates = 1;
+        self.open.insert(update.market_id.clone(), candle);
+        closed
+    }
+
+    /// Current, not yet closed candle for a market
+    pub fn current(&self, market_id: &str) -> Option<&Candle> {
+        self.open.get(market_id)
+    }
+
+    /// Close and return every open candle
+    pub fn flush(&mut self) -> Vec<Candle> {
+        self.open.drain().map(|(_, candle)| candle).collect()
+    }
+}
+
+impl OddsStreamSdk {
+    /// Subscribe to market updates and receive completed candles
+    pub async fn subscribe_candles(
+        &self,
+        market_ids: Vec<String>,
+        interval: CandleInterval,
+        callback: impl Fn(Candle) + Send + 'static,
+    ) -> Result<SubscriptionHandle, SdkError> {
+        let aggregator = Arc::new(Mutex::new(CandleAggregator::new(interval)));
+
+        self.subscribe_market_updates(market_ids, move |update| {
+            let closed = aggregator.lock().unwrap().push(&update);
+            for candle in closed {
+                callback(candle);
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(timestamp: u64, yes_odds: f64, volume: f64) -> MarketUpdate {
+        MarketUpdate {
+            market_id: "m1".to_string(),
+            yes_odds,
+            no_odds: 1.0 - yes_odds,
+            volume,
+            status: "active".to_string(),
+            timestamp,
+            sequence: 0,
+        }
+    }
+
+    #[test]
+    fn test_candle_ohlc_and_volume() {
+        let mut agg = CandleAggregator::new(CandleInterval::OneMinute);
+        assert!(agg.push(&update(1_000, 0.50, 100.0)).is_empty());
+        assert!(agg.push(&update(20_000, 0.60, 130.0)).is_empty());
+        assert!(agg.push(&update(40_000, 0.45, 150.0)).is_empty());
+
+        let closed = agg.push(&update(61_000, 0.55, 160.0));
+        assert_eq!(closed.len(), 1);
+        let candle = &closed[0];
+        assert_eq!(candle.open_time, 0);
+        assert_eq!(candle.open, 0.50);
+        assert_eq!(candle.high, 0.60);
+        assert_eq!(candle.low, 0.45);
+        assert_eq!(candle.close, 0.45);
+        assert_eq!(candle.volume, 50.0);
+        assert_eq!(candle.updates, 3);
+    }
+
+    #[test]
+    fn test_out_of_order_update_leaves_volume_alone() {
+        let mut agg = CandleAggregator::new(CandleInterval::OneMinute);
+        agg.push(&update(1_000, 0.50, 100.0));
+        agg.push(&update(61_000, 0.55, 120.0));
+        // Belongs to the candle already closed; neither its price nor its volume counts
+        assert!(agg.push(&update(30_000, 0.40, 110.0)).is_empty());
+        agg.push(&update(62_000, 0.60, 125.0));
+
+        let candle = agg.current("m1").unwrap();
+        assert_eq!(candle.open_time, 60_000);
+        assert_eq!(candle.low, 0.55);
+        assert_eq!(candle.volume, 25.0);
+        assert_eq!(candle.updates, 2);
+    }
+
+    #[test]
+    fn test_gap_filling() {
+        let mut agg = CandleAggregator::new(CandleInterval::OneMinute);
+        agg.push(&update(0, 0.50, 0.0));
+        let closed = agg.push(&update(3 * 60_000, 0.70, 10.0));
+
+        assert_eq!(closed.len(), 3);
+        assert_eq!(closed[1].open_time, 60_000);
+        assert_eq!(closed[1].open, 0.50);
+        assert_eq!(closed[2].updates, 0);
+
+        let mut agg = CandleAggregator::new(CandleInterval::OneMinute).with_gap_filling(false);
+        agg.push(&update(0, 0.50, 0.0));
+        assert_eq!(agg.push(&update(3 * 60_000, 0.70, 10.0)).len(), 1);
+    }
+}
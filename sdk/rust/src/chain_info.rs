@@ -0,0 +1,131 @@
+//! Market chain discovery for debugging cross-chain routing
+
+use crate::{OddsStreamSdk, SdkError};
+use futures::stream::{FuturesUnordered, StreamExt};
+use linera_sdk::base::{ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Registry lookups in flight at once when resolving many markets
+pub const MAX_CONCURRENT_RESOLUTIONS: usize = 16;
+
+/// Where a market lives and the state of its microchain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketChainInfo {
+    pub market_id: String,
+    pub chain_id: ChainId,
+    pub application_id: ApplicationId,
+    pub contract_version: String,
+    pub created_block: u64,
+    pub block_height: u64,
+    pub validators: Vec<String>,
+    /// Parent outcome a conditional market depends on
+    pub condition: Option<ConditionSpec>,
+}
+
+/// Settle only if `parent_market_id` resolves to `activates_on`; otherwise every trade is refunded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionSpec {
+    pub parent_market_id: String,
+    pub activates_on: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawChainInfo {
+    market_id: String,
+    chain_id: String,
+    application_id: String,
+    contract_version: String,
+    created_block: u64,
+    block_height: u64,
+    validators: Vec<String>,
+    parent_market_id: Option<String>,
+    activates_on: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct ChainInfoData {
+    #[serde(rename = "marketChainInfo")]
+    market_chain_info: Option<RawChainInfo>,
+}
+
+impl OddsStreamSdk {
+    /// Look up the microchain, application and validator set serving a market
+    pub async fn market_chain_info(&self, market_id: &str) -> Result<MarketChainInfo, SdkError> {
+        let query = r#"
+            query MarketChainInfo($marketId: String!) {
+                marketChainInfo(marketId: $marketId) {
+                    marketId
+                    chainId
+                    applicationId
+                    contractVersion
+                    createdBlock
+                    blockHeight
+                    validators
+                    parentMarketId
+                    activatesOn
+                }
+            }
+        "#;
+
+        let data: ChainInfoData = self
+            .graphql_query_cached(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let raw = data
+            .market_chain_info
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+
+        Ok(MarketChainInfo {
+            chain_id: ChainId::from_str(&raw.chain_id)
+                .map_err(|e| SdkError::InvalidInput(format!("chain ID: {}", e)))?,
+            application_id: ApplicationId::from_str(&raw.application_id)
+                .map_err(|e| SdkError::InvalidInput(format!("application ID: {}", e)))?,
+            market_id: raw.market_id,
+            contract_version: raw.contract_version,
+            created_block: raw.created_block,
+            block_height: raw.block_height,
+            validators: raw.validators,
+            condition: raw.parent_market_id.zip(raw.activates_on).map(|(parent_market_id, activates_on)| {
+                ConditionSpec { parent_market_id, activates_on }
+            }),
+        })
+    }
+
+    /// Chain serving `market_id`. A market never moves, so answers are kept for
+    /// the lifetime of the SDK and shared by all of its clones.
+    pub(crate) async fn resolve_market_chain(&self, market_id: &str) -> Result<ChainId, SdkError> {
+        if let Some(chain_id) = self.market_chains.read().unwrap().get(market_id) {
+            return Ok(*chain_id);
+        }
+        let info = self.market_chain_info(market_id).await?;
+        self.market_chains
+            .write()
+            .unwrap()
+            .insert(market_id.to_string(), info.chain_id);
+        Ok(info.chain_id)
+    }
+
+    /// Resolve every distinct market concurrently, at most `MAX_CONCURRENT_RESOLUTIONS` at a time
+    pub(crate) async fn resolve_market_chains<'a>(
+        &self,
+        market_ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<HashMap<String, ChainId>, SdkError> {
+        let mut seen = std::collections::HashSet::new();
+        let mut pending = market_ids.into_iter().filter(|market_id| seen.insert(*market_id));
+        let mut in_flight = FuturesUnordered::new();
+        let mut chains = HashMap::new();
+
+        loop {
+            while in_flight.len() < MAX_CONCURRENT_RESOLUTIONS {
+                let Some(market_id) = pending.next() else { break };
+                in_flight.push(async move { (market_id, self.resolve_market_chain(market_id).await) });
+            }
+            let Some((market_id, chain_id)) = in_flight.next().await else { break };
+            chains.insert(market_id.to_string(), chain_id?);
+        }
+        Ok(chains)
+    }
+}
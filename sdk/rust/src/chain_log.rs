@@ -0,0 +1,381 @@
+//! Direct reads of a chain's blocks, messages and event streams from the
+//! Linera node, bypassing the OddsStream service. Enough to build an
+//! independent indexer or to cross-check what the gateway reports.
+
+use crate::transport::Transport;
+use crate::{AuditRecord, OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{ApplicationId, ChainId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Blocks fetched per request when reading a chain's history
+pub const CHAIN_LOG_PAGE: u64 = 50;
+
+/// Default interval between polls while following a chain
+pub const CHAIN_LOG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A message the block took from its inbox
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingMessage {
+    pub origin: ChainId,
+    /// Height of the sending block on `origin`
+    pub origin_height: u64,
+    pub kind: String,
+    /// BCS-encoded message, as produced by the sending application
+    pub payload: Vec<u8>,
+}
+
+/// A message the block put in its outbox
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingMessage {
+    pub destination: ChainId,
+    pub kind: String,
+    pub payload: Vec<u8>,
+}
+
+/// An entry an application appended to one of its event streams
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainEvent {
+    pub application_id: ApplicationId,
+    pub stream: String,
+    /// Position in the stream, contiguous from 0
+    pub index: u32,
+    pub payload: Vec<u8>,
+}
+
+impl ChainEvent {
+    /// Decode the payload as the BCS value the contract emitted
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, SdkError> {
+        bcs::from_bytes(&self.payload)
+            .map_err(|e| SdkError::InvalidInput(format!("event {} of {}: {}", self.index, self.stream, e)))
+    }
+
+    /// A market's audit record, when this is an entry of its `audit` stream
+    pub fn audit_record(&self) -> Option<AuditRecord> {
+        (self.stream == "audit").then(|| self.decode().ok()).flatten()
+    }
+}
+
+/// Everything one block consumed and produced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainBlock {
+    pub chain_id: ChainId,
+    pub height: u64,
+    pub hash: String,
+    pub timestamp: u64,
+    pub incoming: Vec<IncomingMessage>,
+    pub outgoing: Vec<OutgoingMessage>,
+    pub events: Vec<ChainEvent>,
+}
+
+#[derive(Deserialize)]
+struct BlocksData {
+    blocks: Vec<RawBlock>,
+}
+
+#[derive(Deserialize)]
+struct RawBlock {
+    hash: String,
+    block: RawBlockContent,
+}
+
+#[derive(Deserialize)]
+struct RawBlockContent {
+    header: RawHeader,
+    body: RawBody,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawHeader {
+    chain_id: String,
+    height: u64,
+    timestamp: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBody {
+    incoming_bundles: Vec<RawIncomingBundle>,
+    messages: Vec<Vec<RawOutgoing>>,
+    events: Vec<Vec<RawEvent>>,
+}
+
+#[derive(Deserialize)]
+struct RawIncomingBundle {
+    origin: String,
+    bundle: RawBundle,
+}
+
+#[derive(Deserialize)]
+struct RawBundle {
+    height: u64,
+    messages: Vec<RawPostedMessage>,
+}
+
+#[derive(Deserialize)]
+struct RawPostedMessage {
+    kind: String,
+    message: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct RawOutgoing {
+    destination: String,
+    kind: String,
+    message: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEvent {
+    stream_id: RawStreamId,
+    index: u32,
+    value: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawStreamId {
+    application_id: String,
+    stream_name: String,
+}
+
+fn parse_id<T: std::str::FromStr>(raw: &str, what: &str) -> Result<T, SdkError>
+where
+    T::Err: std::fmt::Display,
+{
+    raw.parse()
+        .map_err(|e| SdkError::InvalidInput(format!("invalid {} {}: {}", what, raw, e)))
+}
+
+impl TryFrom<RawBlock> for ChainBlock {
+    type Error = SdkError;
+
+    fn try_from(raw: RawBlock) -> Result<Self, SdkError> {
+        let RawBlockContent { header, body } = raw.block;
+        let mut incoming = Vec::new();
+        for bundle in body.incoming_bundles {
+            let origin = parse_id(&bundle.origin, "chain ID")?;
+            for message in bundle.bundle.messages {
+                incoming.push(IncomingMessage {
+                    origin,
+                    origin_height: bundle.bundle.height,
+                    kind: message.kind,
+                    payload: message.message,
+                });
+            }
+        }
+        // Messages and events are grouped per transaction; the order within the block is kept
+        let outgoing = body
+            .messages
+            .into_iter()
+            .flatten()
+            .map(|message| {
+                Ok(OutgoingMessage {
+                    destination: parse_id(&message.destination, "chain ID")?,
+                    kind: message.kind,
+                    payload: message.message,
+                })
+            })
+            .collect::<Result<_, SdkError>>()?;
+        let events = body
+            .events
+            .into_iter()
+            .flatten()
+            .map(|event| {
+                Ok(ChainEvent {
+                    application_id: parse_id(&event.stream_id.application_id, "application ID")?,
+                    stream: event.stream_id.stream_name,
+                    index: event.index,
+                    payload: event.value,
+                })
+            })
+            .collect::<Result<_, SdkError>>()?;
+
+        Ok(ChainBlock {
+            chain_id: parse_id(&header.chain_id, "chain ID")?,
+            height: header.height,
+            hash: raw.hash,
+            timestamp: header.timestamp,
+            incoming,
+            outgoing,
+            events,
+        })
+    }
+}
+
+impl Transport {
+    /// Blocks of `chain_id` from `from_height` on, oldest first, at most `limit`
+    pub(crate) async fn chain_blocks(
+        &self,
+        chain_id: ChainId,
+        from_height: u64,
+        limit: u64,
+    ) -> Result<Vec<ChainBlock>, SdkError> {
+        let query = r#"
+            query Blocks($chainId: ChainId!, $from: BlockHeight!, $limit: Int!) {
+                blocks(chainId: $chainId, fromHeight: $from, limit: $limit) {
+                    hash
+                    block {
+                        header { chainId height timestamp }
+                        body {
+                            incomingBundles { origin bundle { height messages { kind message } } }
+                            messages { destination kind message }
+                            events { streamId { applicationId streamName } index value }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        // Blocks are immutable once certified, but the tip moves, so nothing is cached
+        let data: BlocksData = self
+            .graphql_query_fresh(
+                query,
+                serde_json::json!({ "chainId": chain_id.to_string(), "from": from_height, "limit": limit }),
+            )
+            .await?;
+        let mut blocks: Vec<ChainBlock> = data
+            .blocks
+            .into_iter()
+            .map(ChainBlock::try_from)
+            .collect::<Result<_, _>>()?;
+        blocks.sort_by_key(|block| block.height);
+        Ok(blocks)
+    }
+}
+
+/// Follows a chain block by block, yielding each block once, in height order
+pub struct ChainLogFollower {
+    sdk: OddsStreamSdk,
+    chain_id: ChainId,
+    next_height: u64,
+    interval: Duration,
+    buffered: VecDeque<ChainBlock>,
+    // Matching events of the last block read by `next_event` not yet returned
+    pending_events: VecDeque<ChainEvent>,
+}
+
+impl ChainLogFollower {
+    /// Height of the next block to be returned
+    pub fn next_height(&self) -> u64 {
+        self.next_height
+    }
+
+    /// Wait for the next block
+    pub async fn next(&mut self) -> Result<ChainBlock, SdkError> {
+        loop {
+            if let Some(block) = self.buffered.pop_front() {
+                self.next_height = block.height + 1;
+                return Ok(block);
+            }
+            let blocks = self
+                .sdk
+                .transport
+                .chain_blocks(self.chain_id, self.next_height, CHAIN_LOG_PAGE)
+                .await?;
+            // A node that skips heights is not trusted to have served the chain faithfully
+            let mut expected = self.next_height;
+            for block in blocks {
+                if block.height != expected {
+                    return Err(SdkError::InvalidInput(format!(
+                        "node returned block {} of {} while {} was expected",
+                        block.height, self.chain_id, expected
+                    )));
+                }
+                expected += 1;
+                self.buffered.push_back(block);
+            }
+            if self.buffered.is_empty() {
+                self.sdk.clock.sleep(self.interval).await;
+            }
+        }
+    }
+
+    /// Wait for the next event `application_id` put on `stream`, skipping
+    /// blocks without one. Events of a block are returned one call at a time.
+    pub async fn next_event(&mut self, application_id: ApplicationId, stream: &str) -> Result<ChainEvent, SdkError> {
+        loop {
+            if let Some(event) = self.pending_events.pop_front() {
+                return Ok(event);
+            }
+            let block = self.next().await?;
+            self.pending_events.extend(
+                block
+                    .events
+                    .into_iter()
+                    .filter(|event| event.application_id == application_id && event.stream == stream),
+            );
+        }
+    }
+}
+
+impl OddsStreamSdk {
+    /// Blocks of `chain_id` starting at `from_height`, read from the node
+    pub async fn chain_blocks(&self, chain_id: ChainId, from_height: u64, limit: u64) -> Result<Vec<ChainBlock>, SdkError> {
+        self.transport.chain_blocks(chain_id, from_height, limit).await
+    }
+
+    /// Follow `chain_id` from `from_height`, polling every `interval`
+    pub fn follow_chain(&self, chain_id: ChainId, from_height: u64, interval: Duration) -> ChainLogFollower {
+        ChainLogFollower {
+            sdk: self.clone(),
+            chain_id,
+            next_height: from_height,
+            interval,
+            buffered: VecDeque::new(),
+            pending_events: VecDeque::new(),
+        }
+    }
+}
+
+impl ReadOnlyClient {
+    /// Blocks of `chain_id` starting at `from_height`, read from the node
+    pub async fn chain_blocks(&self, chain_id: ChainId, from_height: u64, limit: u64) -> Result<Vec<ChainBlock>, SdkError> {
+        self.transport.chain_blocks(chain_id, from_height, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_flattens_bundles_and_transactions() {
+        let origin = ChainId::from([1u8; 32]);
+        let market = ChainId::from([2u8; 32]);
+        let raw: RawBlock = serde_json::from_value(serde_json::json!({
+            "hash": "ab",
+            "block": {
+                "header": { "chainId": market.to_string(), "height": 7, "timestamp": 1_000 },
+                "body": {
+                    "incomingBundles": [{
+                        "origin": origin.to_string(),
+                        "bundle": { "height": 3, "messages": [
+                            { "kind": "Tracked", "message": [0, 1] },
+                            { "kind": "Simple", "message": [2] }
+                        ] }
+                    }],
+                    "messages": [[], [{ "destination": origin.to_string(), "kind": "Simple", "message": [9] }]],
+                    "events": [[]]
+                }
+            }
+        }))
+        .unwrap();
+
+        let block = ChainBlock::try_from(raw).unwrap();
+        assert_eq!(block.chain_id, market);
+        assert_eq!(block.height, 7);
+        assert_eq!(block.incoming.len(), 2);
+        assert!(block.incoming.iter().all(|message| message.origin == origin && message.origin_height == 3));
+        assert_eq!(block.outgoing, vec![OutgoingMessage { destination: origin, kind: "Simple".to_string(), payload: vec![9] }]);
+        assert!(block.events.is_empty());
+    }
+}
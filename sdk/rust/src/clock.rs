@@ -0,0 +1,97 @@
+//! Injectable time source so expiry and scheduling logic can run in virtual time
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// Wall-clock reads and sleeps, in the microsecond units Linera blocks use
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Microseconds since the Unix epoch
+    fn now_micros(&self) -> u64;
+
+    async fn sleep(&self, duration: Duration);
+}
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now_micros(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Virtual clock that only moves when told to.
+///
+/// Sleepers wake as soon as `advance` or `set` carries time past their
+/// deadline, so a simulation can cover hours of strategy time in milliseconds.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<watch::Sender<u64>>,
+}
+
+impl MockClock {
+    pub fn new(start_micros: u64) -> Self {
+        Self { now: Arc::new(watch::Sender::new(start_micros)) }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += duration.as_micros() as u64);
+    }
+
+    /// Jump to `micros`; time never moves backwards
+    pub fn set(&self, micros: u64) {
+        self.now.send_modify(|now| *now = (*now).max(micros));
+    }
+}
+
+#[async_trait]
+impl Clock for MockClock {
+    fn now_micros(&self) -> u64 {
+        *self.now.borrow()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let deadline = self.now_micros() + duration.as_micros() as u64;
+        let mut receiver = self.now.subscribe();
+        let _ = receiver.wait_for(|now| *now >= deadline).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_clock_wakes_sleepers_on_advance() {
+        let clock = MockClock::new(1_000);
+        let sleeper = {
+            let clock = clock.clone();
+            tokio::spawn(async move {
+                clock.sleep(Duration::from_secs(3600)).await;
+                clock.now_micros()
+            })
+        };
+
+        clock.advance(Duration::from_secs(1800));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::from_secs(1800));
+        assert_eq!(sleeper.await.unwrap(), 1_000 + 3_600_000_000);
+
+        clock.set(0);
+        assert_eq!(clock.now_micros(), 1_000 + 3_600_000_000);
+    }
+}
@@ -0,0 +1,183 @@
+//! Voting on resolutions as a member of a market's oracle committee
+//!
+//! Committee markets resolve once `quorum` members have signed the same
+//! outcome. Members sign the vote digest with the key the oracle application
+//! lists them under, so a vote can be relayed by anyone without being forged.
+
+use crate::{OddsStreamSdk, OrderSignature, SdkError};
+use linera_sdk::base::{ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Must match the oracle application's vote domain separator
+const VOTE_DOMAIN: &[u8] = b"oddsstream-vote-v1";
+
+#[derive(Serialize)]
+struct VotePayload<'a> {
+    market_id: &'a str,
+    outcome: bool,
+}
+
+/// Digest a committee member signs to vote `outcome` on `market_id`
+pub fn vote_digest(market_id: &str, outcome: bool) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(VOTE_DOMAIN);
+    hasher.update(bcs::to_bytes(&VotePayload { market_id, outcome }).expect("vote payload is serializable"));
+    hasher.finalize().into()
+}
+
+/// A market waiting for a member's vote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingVote {
+    pub market_id: String,
+    pub question: String,
+    /// Micros timestamp the market was due to resolve at
+    pub resolution_time: u64,
+    pub votes_cast: u32,
+    pub quorum: u32,
+}
+
+/// Tally of a committee vote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteStatus {
+    pub market_id: String,
+    pub member_count: u32,
+    pub quorum: u32,
+    pub yes_votes: u32,
+    pub no_votes: u32,
+    /// Hex public keys of the members who have voted
+    pub voters: Vec<String>,
+    /// Set once one side reaches quorum
+    pub outcome: Option<bool>,
+}
+
+impl VoteStatus {
+    /// Further votes the leading side needs to reach quorum; 0 once decided
+    pub fn votes_needed(&self) -> u32 {
+        if self.outcome.is_some() {
+            return 0;
+        }
+        self.quorum.saturating_sub(self.yes_votes.max(self.no_votes))
+    }
+
+    pub fn has_voted(&self, member_key: &[u8]) -> bool {
+        let key = hex::encode(member_key);
+        self.voters.iter().any(|voter| voter.eq_ignore_ascii_case(&key))
+    }
+}
+
+#[derive(Deserialize)]
+struct PendingVotesData {
+    #[serde(rename = "pendingVotes")]
+    pending_votes: Vec<PendingVote>,
+}
+
+#[derive(Deserialize)]
+struct VoteStatusData {
+    #[serde(rename = "voteStatus")]
+    vote_status: Option<VoteStatus>,
+}
+
+impl OddsStreamSdk {
+    fn oracle(&self) -> Result<(ChainId, ApplicationId), SdkError> {
+        self.oracle
+            .ok_or_else(|| SdkError::InvalidInput("no oracle application configured".to_string()))
+    }
+
+    /// Committee markets past their resolution time that `member_key` has not voted on
+    pub async fn list_pending_votes(&self, member_key: &[u8]) -> Result<Vec<PendingVote>, SdkError> {
+        let (chain_id, app_id) = self.oracle()?;
+        let query = r#"
+            query PendingVotes($memberKey: String!) {
+                pendingVotes(memberKey: $memberKey) { marketId question resolutionTime votesCast quorum }
+            }
+        "#;
+        let data: PendingVotesData = self
+            .transport
+            .application_query(chain_id, app_id, query, serde_json::json!({ "memberKey": hex::encode(member_key) }))
+            .await?;
+        Ok(data.pending_votes)
+    }
+
+    /// This SDK's signer's vote for `outcome` on `market_id`, ready for `submit_vote`
+    pub async fn sign_vote(&self, market_id: &str, outcome: bool) -> Result<OrderSignature, SdkError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| SdkError::InvalidInput("voting requires a signer".to_string()))?;
+        Ok(OrderSignature {
+            public_key: signer.public_key(),
+            signature: signer.sign(&vote_digest(market_id, outcome)).await?,
+        })
+    }
+
+    /// Cast a signed vote; the oracle application rejects keys outside the
+    /// market's committee and second votes from the same member
+    pub async fn submit_vote(
+        &self,
+        market_id: &str,
+        outcome: bool,
+        signature: OrderSignature,
+    ) -> Result<String, SdkError> {
+        let (chain_id, app_id) = self.oracle()?;
+        let mutation = r#"
+            mutation SubmitVote($marketId: String!, $outcome: Boolean!, $publicKey: String!, $signature: String!) {
+                submitVote(marketId: $marketId, outcome: $outcome, publicKey: $publicKey, signature: $signature)
+            }
+        "#;
+        let variables = serde_json::json!({
+            "marketId": market_id,
+            "outcome": outcome,
+            "publicKey": hex::encode(&signature.public_key),
+            "signature": hex::encode(&signature.signature),
+        });
+        self.execute_operation(chain_id, app_id, mutation, variables).await
+    }
+
+    /// Current tally of the committee vote on `market_id`
+    pub async fn vote_status(&self, market_id: &str) -> Result<VoteStatus, SdkError> {
+        let (chain_id, app_id) = self.oracle()?;
+        let query = r#"
+            query VoteStatus($marketId: String!) {
+                voteStatus(marketId: $marketId) { marketId memberCount quorum yesVotes noVotes voters outcome }
+            }
+        "#;
+        let data: VoteStatusData = self
+            .transport
+            .application_query(chain_id, app_id, query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.vote_status.ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vote_digest_binds_market_and_outcome() {
+        let yes = vote_digest("btc-100k", true);
+        assert_ne!(yes, vote_digest("btc-100k", false));
+        assert_ne!(yes, vote_digest("eth-10k", true));
+        assert_eq!(yes, vote_digest("btc-100k", true));
+    }
+
+    #[test]
+    fn test_votes_needed_follows_the_leading_side() {
+        let mut status = VoteStatus {
+            market_id: "btc-100k".to_string(),
+            member_count: 5,
+            quorum: 3,
+            yes_votes: 2,
+            no_votes: 1,
+            voters: vec!["ab01".to_string()],
+            outcome: None,
+        };
+        assert_eq!(status.votes_needed(), 1);
+        assert!(status.has_voted(&[0xab, 0x01]));
+        status.outcome = Some(true);
+        assert_eq!(status.votes_needed(), 0);
+    }
+}
@@ -0,0 +1,96 @@
+//! Allowlist gating for regulated deployments: markets created while the
+//! registry has an allowlist only accept orders from chains it approves
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{ApplicationId, ChainId};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+struct AllowlistData {
+    allowlist: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApprovalData {
+    #[serde(rename = "isApproved")]
+    is_approved: bool,
+}
+
+impl Transport {
+    pub(crate) async fn market_allowlist(&self, market_id: &str) -> Result<Option<ApplicationId>, SdkError> {
+        let query = r#"
+            query Allowlist($marketId: String!) {
+                allowlist(marketId: $marketId)
+            }
+        "#;
+
+        // Fixed at creation, so a cached answer is always correct
+        let data: AllowlistData = self
+            .graphql_query_cached(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.allowlist
+            .map(|raw| ApplicationId::from_str(&raw))
+            .transpose()
+            .map_err(|e| SdkError::InvalidInput(format!("allowlist application ID: {}", e)))
+    }
+}
+
+impl OddsStreamSdk {
+    /// The allowlist application gating `market_id`, if any
+    pub async fn market_allowlist(&self, market_id: &str) -> Result<Option<ApplicationId>, SdkError> {
+        self.transport.market_allowlist(market_id).await
+    }
+
+    /// `Ok` if `user_chain_id` may trade on `market_id`, `SdkError::ComplianceRejected` if not
+    pub async fn check_compliance(&self, market_id: &str, user_chain_id: ChainId) -> Result<(), SdkError> {
+        let Some(allowlist) = self.transport.market_allowlist(market_id).await? else {
+            return Ok(());
+        };
+        // The market calls the allowlist on its own chain, so ask it there
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        let query = r#"
+            query IsApproved($userChainId: String!) {
+                isApproved(userChainId: $userChainId)
+            }
+        "#;
+        let data: ApprovalData = self
+            .transport
+            .application_query(
+                market_chain_id,
+                allowlist,
+                query,
+                serde_json::json!({ "userChainId": user_chain_id.to_string() }),
+            )
+            .await?;
+        if !data.is_approved {
+            return Err(SdkError::ComplianceRejected {
+                market_id: market_id.to_string(),
+                user_chain_id: user_chain_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Registry operator: gate markets created from now on behind `allowlist`, or stop gating with `None`
+    pub async fn set_registry_allowlist(&self, allowlist: Option<ApplicationId>) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation SetAllowlist($allowlist: ApplicationId) {
+                setAllowlist(allowlist: $allowlist)
+            }
+        "#;
+        self.execute_registry_operation(
+            mutation,
+            serde_json::json!({ "allowlist": allowlist.map(|allowlist| allowlist.to_string()) }),
+        )
+        .await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The allowlist application gating `market_id`, if any
+    pub async fn market_allowlist(&self, market_id: &str) -> Result<Option<ApplicationId>, SdkError> {
+        self.transport.market_allowlist(market_id).await
+    }
+}
@@ -0,0 +1,165 @@
+//! Reloading agent configs without a restart, from a watched file or an
+//! admin endpoint. Configs are validated before they are handed over, and
+//! agents swap them in whole or not at all.
+
+use crate::admin::{read_request, write_response};
+use crate::{AgentConfig, AgentFleet, AgentHandle, SdkError, Shutdown};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Something that accepts new agent configs: one agent, or a fleet by agent name
+pub trait ConfigTarget: Send + Sync {
+    fn reconfigure(&self, config: AgentConfig) -> Result<(), SdkError>;
+}
+
+impl ConfigTarget for AgentHandle {
+    fn reconfigure(&self, config: AgentConfig) -> Result<(), SdkError> {
+        AgentHandle::reconfigure(self, config)
+    }
+}
+
+impl ConfigTarget for AgentFleet {
+    fn reconfigure(&self, config: AgentConfig) -> Result<(), SdkError> {
+        AgentFleet::reconfigure(self, config)
+    }
+}
+
+/// A config file holds one agent's config or a fleet's
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigFile {
+    One(AgentConfig),
+    Many(Vec<AgentConfig>),
+}
+
+impl ConfigFile {
+    fn into_configs(self) -> Vec<AgentConfig> {
+        match self {
+            ConfigFile::One(config) => vec![config],
+            ConfigFile::Many(configs) => configs,
+        }
+    }
+}
+
+/// Parse and validate every config in `bytes`; one bad config rejects them all
+pub fn parse_configs(bytes: &[u8]) -> Result<Vec<AgentConfig>, SdkError> {
+    let configs = serde_json::from_slice::<ConfigFile>(bytes)?.into_configs();
+    for config in &configs {
+        config.validate()?;
+    }
+    Ok(configs)
+}
+
+fn deliver(target: &dyn ConfigTarget, configs: Vec<AgentConfig>) -> Result<(), SdkError> {
+    for config in configs {
+        target.reconfigure(config)?;
+    }
+    Ok(())
+}
+
+/// Reload `path` into `target` whenever its modification time changes, checking every `poll`
+pub fn watch_config_file(
+    path: impl Into<PathBuf>,
+    target: Arc<dyn ConfigTarget>,
+    poll: Duration,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    let path = path.into();
+    tokio::spawn(async move {
+        let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        // The config in the file at startup is the one the agent was built with
+        let mut seen: Option<SystemTime> = modified(&path);
+        let mut ticker = tokio::time::interval(poll);
+        loop {
+            tokio::select! {
+                _ = shutdown.triggered() => break,
+                _ = ticker.tick() => {}
+            }
+            let current = modified(&path);
+            if current.is_none() || current == seen {
+                continue;
+            }
+            seen = current;
+            let reloaded = tokio::fs::read(&path)
+                .await
+                .map_err(SdkError::from)
+                .and_then(|bytes| parse_configs(&bytes))
+                .and_then(|configs| deliver(target.as_ref(), configs));
+            match reloaded {
+                Ok(()) => tracing::info!(path = %path.display(), "config file reloaded"),
+                // The agents keep their current config until the file is fixed
+                Err(e) => tracing::warn!(path = %path.display(), error = %e, "config file rejected"),
+            }
+        }
+    })
+}
+
+/// Accept `PUT /config` with a JSON config (or list of configs) on `listener`.
+///
+/// With a `token`, requests must carry it as `Authorization: Bearer <token>`.
+/// A `202` means the config passed validation and was handed over; a strategy
+/// that refuses its parameters reports that as an agent event.
+pub fn serve_config_admin(
+    listener: TcpListener,
+    target: Arc<dyn ConfigTarget>,
+    token: Option<String>,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = tokio::select! {
+                _ = shutdown.triggered() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "config admin accept failed");
+                        continue;
+                    }
+                },
+            };
+            let (status, body) = match read_request(&mut stream).await {
+                Err(e) => (400, serde_json::json!({ "error": e.to_string() })),
+                Ok(request) if !request.is_authorized(token.as_deref()) => {
+                    tracing::warn!(%peer, "unauthorized config reload");
+                    (401, serde_json::json!({ "error": "unauthorized" }))
+                }
+                Ok(request) if request.path != "/config" => (404, serde_json::json!({ "error": "not found" })),
+                Ok(request) if request.method != "PUT" => (405, serde_json::json!({ "error": "use PUT" })),
+                Ok(request) => match parse_configs(&request.body).and_then(|configs| deliver(target.as_ref(), configs)) {
+                    Ok(()) => (202, serde_json::json!({ "status": "accepted" })),
+                    Err(e) => (400, serde_json::json!({ "error": e.to_string() })),
+                },
+            };
+            if let Err(e) = write_response(&mut stream, status, &body).await {
+                tracing::debug!(%peer, error = %e, "config admin response not delivered");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_bad_config_rejects_the_file() {
+        let agent = |name: &str, max_exposure: f64| {
+            serde_json::json!({
+                "name": name,
+                "marketIds": ["m1"],
+                "maxOrderSize": 10.0,
+                "maxExposure": max_exposure,
+                "decisionIntervalSecs": 30,
+            })
+        };
+        let single = serde_json::to_vec(&agent("a", 100.0)).unwrap();
+        assert_eq!(parse_configs(&single).unwrap().len(), 1);
+
+        let fleet = serde_json::to_vec(&serde_json::json!([agent("a", 100.0), agent("b", 5.0)])).unwrap();
+        assert!(matches!(parse_configs(&fleet), Err(SdkError::InvalidInput(_))));
+    }
+}
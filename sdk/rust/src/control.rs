@@ -0,0 +1,311 @@
+//! Control endpoint of a running agent process: status, stop, pause and
+//! resume over a local socket, so an operator can manage bots without
+//! signalling the process. Requests are admin-endpoint HTTP and must carry
+//! the process's token as a bearer when one is set.
+
+use crate::admin::{read_request, send_request, write_response};
+use crate::{AgentFleet, AgentMetrics, FleetMetrics, SdkError, Shutdown};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Where agent processes take control requests unless told otherwise
+pub const DEFAULT_CONTROL_ADDRESS: &str = "127.0.0.1:7171";
+
+/// Where a control endpoint listens: `unix:<path>` for a Unix socket,
+/// anything else a TCP `host:port`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlAddress {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl FromStr for ControlAddress {
+    type Err = SdkError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.strip_prefix("unix:") {
+            Some("") => Err(SdkError::InvalidInput("unix control address needs a path".to_string())),
+            Some(path) => Ok(ControlAddress::Unix(PathBuf::from(path))),
+            None if input.contains(':') => Ok(ControlAddress::Tcp(input.to_string())),
+            None => Err(SdkError::InvalidInput(format!(
+                "control address {} is neither unix:<path> nor host:port",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ControlAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlAddress::Tcp(address) => write!(f, "{}", address),
+            ControlAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// What `GET /agents` reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlStatus {
+    pub fleet: FleetMetrics,
+    /// Sorted by name
+    pub agents: Vec<AgentMetrics>,
+}
+
+trait ControlStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> ControlStream for S {}
+
+/// A bound control endpoint
+pub enum ControlListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+impl ControlListener {
+    /// Bind `address`. A Unix socket is made accessible to its owner only and
+    /// replaces a stale socket file left by a process that did not exit cleanly.
+    pub async fn bind(address: &ControlAddress) -> Result<Self, SdkError> {
+        match address {
+            ControlAddress::Tcp(address) => Ok(ControlListener::Tcp(TcpListener::bind(address).await?)),
+            #[cfg(unix)]
+            ControlAddress::Unix(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+                if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                        return Err(SdkError::InvalidInput(format!("{} is in use by a running agent process", path.display())));
+                    }
+                    std::fs::remove_file(path)?;
+                }
+                let listener = tokio::net::UnixListener::bind(path)?;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+                Ok(ControlListener::Unix(listener, path.clone()))
+            }
+            #[cfg(not(unix))]
+            ControlAddress::Unix(_) => {
+                Err(SdkError::InvalidInput("unix control sockets are not supported on this platform".to_string()))
+            }
+        }
+    }
+
+    async fn accept(&self) -> std::io::Result<(Box<dyn ControlStream>, String)> {
+        match self {
+            ControlListener::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                Ok((Box::new(stream), peer.to_string()))
+            }
+            #[cfg(unix)]
+            ControlListener::Unix(listener, path) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((Box::new(stream), path.display().to_string()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AgentAction {
+    Stop,
+    Pause,
+    Resume,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ControlRoute {
+    Status,
+    AgentStatus(String),
+    Act(String, AgentAction),
+}
+
+/// The route for `method` on `path`, or the status and error to answer with
+fn route(method: &str, path: &str) -> Result<ControlRoute, (u16, &'static str)> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (expected, route) = match segments.as_slice() {
+        ["agents"] => ("GET", ControlRoute::Status),
+        ["agents", name] if !name.is_empty() => ("GET", ControlRoute::AgentStatus(name.to_string())),
+        ["agents", name, action] if !name.is_empty() => {
+            let action = match *action {
+                "stop" => AgentAction::Stop,
+                "pause" => AgentAction::Pause,
+                "resume" => AgentAction::Resume,
+                _ => return Err((404, "not found")),
+            };
+            ("POST", ControlRoute::Act(name.to_string(), action))
+        }
+        _ => return Err((404, "not found")),
+    };
+    if method != expected {
+        return Err((405, if expected == "GET" { "use GET" } else { "use POST" }));
+    }
+    Ok(route)
+}
+
+fn answer(fleet: &AgentFleet, route: ControlRoute) -> (u16, serde_json::Value) {
+    let unknown = |name: &str| (404, serde_json::json!({ "error": format!("no agent named {}", name) }));
+    match route {
+        ControlRoute::Status => {
+            let agents: Vec<AgentMetrics> =
+                fleet.agents().iter().filter_map(|name| fleet.agent_metrics(name)).collect();
+            let status = ControlStatus { fleet: fleet.metrics(), agents };
+            (200, serde_json::to_value(status).unwrap_or_default())
+        }
+        ControlRoute::AgentStatus(name) => match fleet.agent_metrics(&name) {
+            Some(metrics) => (200, serde_json::to_value(metrics).unwrap_or_default()),
+            None => unknown(&name),
+        },
+        ControlRoute::Act(name, action) => {
+            if fleet.agent_metrics(&name).is_none() {
+                return unknown(&name);
+            }
+            let sent = match action {
+                AgentAction::Stop => fleet.stop(&name),
+                AgentAction::Pause => fleet.pause(&name),
+                AgentAction::Resume => fleet.resume(&name),
+            };
+            match sent {
+                Ok(()) => (202, serde_json::json!({ "status": "accepted" })),
+                // The agent has already finished
+                Err(e) => (409, serde_json::json!({ "error": e.to_string() })),
+            }
+        }
+    }
+}
+
+/// Serve the control endpoint for `fleet` on `listener`:
+///
+/// - `GET /agents`: a `ControlStatus`
+/// - `GET /agents/<name>`: that agent's `AgentMetrics`
+/// - `POST /agents/<name>/stop`, `/pause` or `/resume`: `202` once the
+///   command is queued; the agent acts on it after the decision in progress
+///
+/// With a `token`, requests must carry it as `Authorization: Bearer <token>`.
+pub fn serve_agent_control(
+    listener: ControlListener,
+    fleet: Arc<AgentFleet>,
+    token: Option<String>,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = tokio::select! {
+                _ = shutdown.triggered() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "agent control accept failed");
+                        continue;
+                    }
+                },
+            };
+            let (status, body) = match read_request(&mut stream).await {
+                Err(e) => (400, serde_json::json!({ "error": e.to_string() })),
+                Ok(request) if !request.is_authorized(token.as_deref()) => {
+                    tracing::warn!(%peer, "unauthorized agent control request");
+                    (401, serde_json::json!({ "error": "unauthorized" }))
+                }
+                Ok(request) => match route(&request.method, &request.path) {
+                    Ok(route) => {
+                        tracing::info!(%peer, method = %request.method, path = %request.path, "agent control request");
+                        answer(&fleet, route)
+                    }
+                    Err((status, error)) => (status, serde_json::json!({ "error": error })),
+                },
+            };
+            if let Err(e) = write_response(&mut stream, status, &body).await {
+                tracing::debug!(%peer, error = %e, "agent control response not delivered");
+            }
+        }
+        #[cfg(unix)]
+        if let ControlListener::Unix(_, path) = &listener {
+            let _ = std::fs::remove_file(path);
+        }
+    })
+}
+
+/// Talks to the control endpoint of an agent process
+#[derive(Debug, Clone)]
+pub struct AgentControlClient {
+    address: ControlAddress,
+    token: Option<String>,
+}
+
+impl AgentControlClient {
+    pub fn new(address: ControlAddress, token: Option<String>) -> Self {
+        Self { address, token }
+    }
+
+    async fn request(&self, method: &str, path: &str) -> Result<serde_json::Value, SdkError> {
+        let unreachable = |e: std::io::Error| SdkError::ConnectionError(format!("agent control at {}: {}", self.address, e));
+        let (status, body) = match &self.address {
+            ControlAddress::Tcp(address) => {
+                let mut stream = TcpStream::connect(address).await.map_err(unreachable)?;
+                send_request(&mut stream, method, path, self.token.as_deref()).await?
+            }
+            #[cfg(unix)]
+            ControlAddress::Unix(path_on_disk) => {
+                let mut stream = tokio::net::UnixStream::connect(path_on_disk).await.map_err(unreachable)?;
+                send_request(&mut stream, method, path, self.token.as_deref()).await?
+            }
+            #[cfg(not(unix))]
+            ControlAddress::Unix(_) => {
+                return Err(SdkError::InvalidInput("unix control sockets are not supported on this platform".to_string()))
+            }
+        };
+        if (200..300).contains(&status) {
+            return Ok(body);
+        }
+        let error = body.get("error").and_then(|error| error.as_str()).unwrap_or("no reason given");
+        Err(SdkError::InvalidInput(format!("agent control refused {} {} ({}): {}", method, path, status, error)))
+    }
+
+    /// Every agent in the process, with fleet totals
+    pub async fn status(&self) -> Result<ControlStatus, SdkError> {
+        Ok(serde_json::from_value(self.request("GET", "/agents").await?)?)
+    }
+
+    pub async fn agent_status(&self, name: &str) -> Result<AgentMetrics, SdkError> {
+        Ok(serde_json::from_value(self.request("GET", &format!("/agents/{}", name)).await?)?)
+    }
+
+    /// Ask the agent to stop after the decision in progress
+    pub async fn stop(&self, name: &str) -> Result<(), SdkError> {
+        self.request("POST", &format!("/agents/{}/stop", name)).await.map(drop)
+    }
+
+    /// Ask the agent to stop deciding until resumed
+    pub async fn pause(&self, name: &str) -> Result<(), SdkError> {
+        self.request("POST", &format!("/agents/{}/pause", name)).await.map(drop)
+    }
+
+    pub async fn resume(&self, name: &str) -> Result<(), SdkError> {
+        self.request("POST", &format!("/agents/{}/resume", name)).await.map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_check_path_then_method() {
+        assert_eq!(route("GET", "/agents"), Ok(ControlRoute::Status));
+        assert_eq!(route("GET", "/agents/mm-1"), Ok(ControlRoute::AgentStatus("mm-1".to_string())));
+        assert_eq!(route("POST", "/agents/mm-1/pause"), Ok(ControlRoute::Act("mm-1".to_string(), AgentAction::Pause)));
+        assert_eq!(route("GET", "/agents/mm-1/stop"), Err((405, "use POST")));
+        assert_eq!(route("POST", "/agents/mm-1/kill"), Err((404, "not found")));
+        assert_eq!(route("GET", "/config"), Err((404, "not found")));
+
+        assert_eq!("unix:/run/agent.sock".parse::<ControlAddress>().unwrap(), ControlAddress::Unix("/run/agent.sock".into()));
+        assert_eq!("127.0.0.1:7070".parse::<ControlAddress>().unwrap(), ControlAddress::Tcp("127.0.0.1:7070".to_string()));
+        assert!("agent.sock".parse::<ControlAddress>().is_err());
+    }
+}
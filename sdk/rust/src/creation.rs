@@ -0,0 +1,309 @@
+//! Bulk market creation for operators listing whole series of events
+
+use crate::{validate_stage_schedule, ConditionSpec, FeeTier, OddsStreamSdk, OracleConfig, SdkError, StageSpec};
+use futures::future::join_all;
+use linera_sdk::base::{Amount, ApplicationId};
+use serde::{Deserialize, Serialize};
+
+/// Markets provisioned per registry block; larger requests are split and sent concurrently
+pub const MARKETS_PER_BLOCK: usize = 20;
+
+/// Halts trading for `cooldown_secs` when the YES probability moves more than
+/// `max_move_bps` within `window_blocks`; the default leaves it off
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerConfig {
+    pub max_move_bps: u32,
+    pub window_blocks: u64,
+    pub cooldown_secs: u64,
+}
+
+/// What happens if the market's oracle never reports: after
+/// `escalate_after_secs` past the resolution time a committee of
+/// `committee_size` may resolve instead, and after `refund_after_secs` the
+/// market is voided and positions refunded. The default waits forever.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FallbackPolicy {
+    pub escalate_after_secs: Option<u64>,
+    pub committee_size: u32,
+    pub refund_after_secs: Option<u64>,
+}
+
+impl FallbackPolicy {
+    /// Check the policy the way the registry will
+    pub fn validate(&self) -> Result<(), SdkError> {
+        if self.escalate_after_secs.is_some() && self.committee_size == 0 {
+            return Err(SdkError::InvalidInput("fallback committee needs at least one member".to_string()));
+        }
+        if let (Some(escalate), Some(refund)) = (self.escalate_after_secs, self.refund_after_secs) {
+            if escalate >= refund {
+                return Err(SdkError::InvalidInput(format!(
+                    "fallback refunds after {}s, before escalating at {}s",
+                    refund, escalate
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Who the yield on a market's idle funds is credited to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum YieldBeneficiary {
+    /// Liquidity providers, in proportion to their shares
+    #[default]
+    Providers,
+    /// The protocol treasury
+    Treasury,
+}
+
+/// Parks `deposit_bps` of the pools in a yield source application while the
+/// market trades; everything is withdrawn once trading stops. The registry
+/// only accepts sources on its whitelist. The default keeps funds on-chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YieldConfig {
+    pub source: Option<ApplicationId>,
+    pub deposit_bps: u32,
+    pub beneficiary: YieldBeneficiary,
+}
+
+impl YieldConfig {
+    pub fn validate(&self) -> Result<(), SdkError> {
+        if self.deposit_bps > 10_000 {
+            return Err(SdkError::InvalidInput(format!(
+                "yield deposit of {} bps exceeds the whole pool",
+                self.deposit_bps
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMarketParams {
+    pub market_id: String,
+    pub description: String,
+    pub oracle: OracleConfig,
+    /// Micros since the Unix epoch
+    pub resolution_time: u64,
+    #[serde(default)]
+    pub taker_fee_bps: u32,
+    #[serde(default)]
+    pub referral_share_bps: u32,
+    /// Parts of the taker fee paid to the oracle and the protocol treasury
+    #[serde(default)]
+    pub oracle_share_bps: u32,
+    #[serde(default)]
+    pub protocol_share_bps: u32,
+    /// How long liquidity must stay deposited before it leaves without the early-exit fee
+    #[serde(default)]
+    pub lp_cooldown_secs: u64,
+    #[serde(default)]
+    pub early_exit_fee_bps: u32,
+    /// Charged on fills against resting orders once the order book lands
+    #[serde(default)]
+    pub maker_fee_bps: u32,
+    /// Lower rates by 30-day volume, ascending by `min_volume`
+    #[serde(default)]
+    pub fee_tiers: Vec<FeeTier>,
+    #[serde(default)]
+    pub initial_liquidity: Amount,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Makes this a conditional market on another market's outcome
+    #[serde(default)]
+    pub condition: Option<ConditionSpec>,
+    /// Fungible token application to settle in; `None` settles in the native token
+    #[serde(default)]
+    pub settlement_token: Option<ApplicationId>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Collect orders this long after creation and open at a single clearing price; 0 skips the auction
+    #[serde(default)]
+    pub opening_auction_secs: u64,
+    /// Queue orders during circuit-breaker halts and reopen through an auction instead of rejecting them
+    #[serde(default)]
+    pub reopen_with_auction: bool,
+    /// Refuse orders this long before `resolution_time`, once the outcome may already be known
+    #[serde(default)]
+    pub lock_period_secs: u64,
+    /// Stages that each release part of the payout before final resolution; empty resolves in one go
+    #[serde(default)]
+    pub stages: Vec<StageSpec>,
+    /// Most the market's pools may hold, initial liquidity included; `None` is uncapped.
+    /// Caps make thin markets less attractive to manipulate through the oracle.
+    #[serde(default)]
+    pub max_pool: Option<Amount>,
+    /// Most one user chain may pay for its shares in the market; `None` is uncapped
+    #[serde(default)]
+    pub max_user_exposure: Option<Amount>,
+    #[serde(default)]
+    pub fallback: FallbackPolicy,
+    #[serde(default)]
+    pub yield_config: YieldConfig,
+}
+
+/// Check a fee schedule the way the registry will: the rate is at most the
+/// whole cost, and the referral, oracle and protocol shares at most the whole fee
+pub fn validate_fee_shares(
+    taker_fee_bps: u32,
+    referral_share_bps: u32,
+    oracle_share_bps: u32,
+    protocol_share_bps: u32,
+) -> Result<(), SdkError> {
+    if taker_fee_bps > 10_000 {
+        return Err(SdkError::InvalidInput(format!("taker fee of {} bps exceeds 100%", taker_fee_bps)));
+    }
+    let shares = u64::from(referral_share_bps) + u64::from(oracle_share_bps) + u64::from(protocol_share_bps);
+    if shares > 10_000 {
+        return Err(SdkError::InvalidInput(format!(
+            "fee shares add up to {} bps, more than the whole fee",
+            shares
+        )));
+    }
+    Ok(())
+}
+
+impl CreateMarketParams {
+    /// The registry's `CreateMarketParams` input shape
+    fn to_registry_input(&self) -> serde_json::Value {
+        serde_json::json!({
+            "marketId": self.market_id,
+            "description": self.description,
+            "oracleType": self.oracle,
+            "resolutionTime": self.resolution_time,
+            "feeSchedule": {
+                "takerFeeBps": self.taker_fee_bps,
+                "referralShareBps": self.referral_share_bps,
+                "oracleShareBps": self.oracle_share_bps,
+                "protocolShareBps": self.protocol_share_bps,
+                "lpCooldown": self.lp_cooldown_secs * 1_000_000,
+                "earlyExitFeeBps": self.early_exit_fee_bps,
+                "makerFeeBps": self.maker_fee_bps,
+                "tiers": self.fee_tiers,
+            },
+            "amm": {
+                "initialLiquidity": self.initial_liquidity.to_string(),
+                "initialYesOddsBps": 0,
+            },
+            "category": self.category,
+            "condition": self.condition,
+            "settlementToken": self.settlement_token.map(|token| token.to_string()),
+            "circuitBreaker": {
+                "maxMoveBps": self.circuit_breaker.max_move_bps,
+                "windowBlocks": self.circuit_breaker.window_blocks,
+                "cooldown": self.circuit_breaker.cooldown_secs * 1_000_000,
+            },
+            "auction": {
+                "openingDuration": self.opening_auction_secs * 1_000_000,
+                "reopenAfterHalt": self.reopen_with_auction,
+            },
+            "lockPeriod": self.lock_period_secs * 1_000_000,
+            "stages": self.stages,
+            "caps": {
+                "maxPool": self.max_pool.map(|amount| amount.to_string()),
+                "maxUserExposure": self.max_user_exposure.map(|amount| amount.to_string()),
+            },
+            "fallback": {
+                "escalateAfter": self.fallback.escalate_after_secs.map(|secs| secs * 1_000_000),
+                "committeeSize": self.fallback.committee_size,
+                "refundAfter": self.fallback.refund_after_secs.map(|secs| secs * 1_000_000),
+            },
+            "yieldConfig": {
+                "source": self.yield_config.source.map(|source| source.to_string()),
+                "depositBps": self.yield_config.deposit_bps,
+                "beneficiary": self.yield_config.beneficiary,
+            },
+        })
+    }
+}
+
+/// Outcome of `create_markets`; every requested market is in exactly one list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkCreationReport {
+    pub created: Vec<String>,
+    /// Market ID and the reason it was not created
+    pub failed: Vec<(String, String)>,
+}
+
+impl OddsStreamSdk {
+    /// Create many markets at once, reporting which ones failed instead of stopping at the first error
+    pub async fn create_markets(
+        &self,
+        markets: Vec<CreateMarketParams>,
+    ) -> Result<BulkCreationReport, SdkError> {
+        let mutation = r#"
+            mutation CreateMarkets($markets: [CreateMarketParams!]!) {
+                createMarkets(markets: $markets)
+            }
+        "#;
+
+        let mut report = BulkCreationReport::default();
+        // The registry would refuse these anyway; fail them without a round trip
+        let mut valid = Vec::with_capacity(markets.len());
+        for params in markets {
+            let checked = validate_stage_schedule(&params.stages)
+                .and_then(|()| {
+                    validate_fee_shares(
+                        params.taker_fee_bps,
+                        params.referral_share_bps,
+                        params.oracle_share_bps,
+                        params.protocol_share_bps,
+                    )
+                })
+                .and_then(|()| params.fallback.validate())
+                .and_then(|()| params.yield_config.validate());
+            match checked {
+                Ok(()) => valid.push(params),
+                Err(e) => report.failed.push((params.market_id, e.to_string())),
+            }
+        }
+        let markets = valid;
+
+        let chunks: Vec<&[CreateMarketParams]> = markets.chunks(MARKETS_PER_BLOCK).collect();
+        let submissions = join_all(chunks.iter().map(|chunk| {
+            let input: Vec<_> = chunk.iter().map(CreateMarketParams::to_registry_input).collect();
+            self.execute_registry_operation(mutation, serde_json::json!({ "markets": input }))
+        }))
+        .await;
+
+        let mut submitted = Vec::new();
+        for (chunk, submission) in chunks.iter().zip(submissions) {
+            match submission {
+                Ok(_) => submitted.extend(chunk.iter().map(|params| params.market_id.clone())),
+                Err(e) => report
+                    .failed
+                    .extend(chunk.iter().map(|params| (params.market_id.clone(), e.to_string()))),
+            }
+        }
+
+        // The block only proves the batch ran; the registry may still have skipped individual markets
+        let lookups = join_all(submitted.iter().map(|market_id| self.market_chain_info(market_id))).await;
+        for (market_id, lookup) in submitted.into_iter().zip(lookups) {
+            match lookup {
+                Ok(_) => report.created.push(market_id),
+                Err(SdkError::MarketNotFound(_)) => {
+                    report.failed.push((market_id, "rejected by the registry".to_string()))
+                }
+                Err(e) => report.failed.push((market_id, e.to_string())),
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_shares_are_checked_like_the_registry() {
+        assert!(validate_fee_shares(30, 2_000, 3_000, 5_000).is_ok());
+        assert!(validate_fee_shares(30, 2_000, 3_000, 5_001).is_err());
+        assert!(validate_fee_shares(10_001, 0, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Running a fleet as a long-lived service: crashed agents are restarted
+//! with backoff, and liveness and readiness are served over HTTP for
+//! systemd or Kubernetes probes
+
+use crate::admin::{read_request, write_response};
+use crate::{AgentConfig, AgentEvent, AgentFleet, AgentStatus, FleetEvent, FleetMetrics, SdkError, Shutdown, TradingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Builds a fresh strategy for an agent, on its first start and every restart
+pub type StrategyFactory = Arc<dyn Fn(&str) -> Result<Box<dyn TradingStrategy>, SdkError> + Send + Sync>;
+
+/// When a crashed agent is started again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RestartPolicy {
+    /// Restarts allowed within `window_secs`; past that the agent is left failed
+    pub max_restarts: u32,
+    pub window_secs: u64,
+    /// Wait before the first restart, doubled for each further one in the window
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self { max_restarts: 5, window_secs: 300, initial_backoff_ms: 1_000, max_backoff_ms: 60_000 }
+    }
+}
+
+impl RestartPolicy {
+    /// Wait before restarting an agent that has already restarted `recent` times in the window
+    pub fn backoff(&self, recent: u32) -> Duration {
+        let backoff = self.initial_backoff_ms.saturating_mul(1u64 << recent.min(32));
+        Duration::from_millis(backoff.min(self.max_backoff_ms))
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+}
+
+/// One agent of a daemon: its strategy by name and its config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonAgent {
+    /// A built-in strategy name, or `wasm:<path>` for a plugin
+    pub strategy: String,
+    #[serde(flatten)]
+    pub config: AgentConfig,
+}
+
+/// What `oddsstream-cli agent daemon` runs, read from a JSON file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonConfig {
+    pub agents: Vec<DaemonAgent>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+}
+
+impl DaemonConfig {
+    pub fn validate(&self) -> Result<(), SdkError> {
+        if self.agents.is_empty() {
+            return Err(SdkError::InvalidInput("daemon config has no agents".to_string()));
+        }
+        let mut names = HashSet::new();
+        for agent in &self.agents {
+            agent.config.validate()?;
+            if !names.insert(agent.config.name.as_str()) {
+                return Err(SdkError::InvalidInput(format!("agent {} is configured twice", agent.config.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a JSON daemon config and validate it
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, SdkError> {
+        let config: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Follow `name`'s events until it finishes: `None` once it stopped, the
+/// error if it crashed. A reloaded config is kept for the next restart.
+async fn wait_for_exit(
+    fleet: &AgentFleet,
+    events: &mut broadcast::Receiver<FleetEvent>,
+    name: &str,
+    config: &mut AgentConfig,
+) -> Option<String> {
+    loop {
+        match events.recv().await {
+            Ok(FleetEvent { agent, event }) if agent == name => match event {
+                AgentEvent::ConfigReloaded { config: reloaded } => *config = reloaded,
+                AgentEvent::Stopped => return None,
+                AgentEvent::Crashed { error } => return Some(error),
+                _ => {}
+            },
+            Ok(_) => {}
+            // The exit may have been among the missed events, so ask the fleet
+            Err(broadcast::error::RecvError::Lagged(_)) => match fleet.agent_metrics(name).map(|metrics| metrics.status) {
+                Some(AgentStatus::Failed(error)) => return Some(error),
+                Some(AgentStatus::Stopped) => return None,
+                _ => {}
+            },
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+/// Start `strategy` with `config` on `fleet` and start it again whenever it
+/// crashes, as `policy` allows. An agent stopped on purpose, by its handle or
+/// the control endpoint, stays stopped; so does everything once `shutdown`
+/// triggers.
+pub fn supervise_agent(
+    fleet: Arc<AgentFleet>,
+    factory: StrategyFactory,
+    strategy: String,
+    config: AgentConfig,
+    policy: RestartPolicy,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let name = config.name.clone();
+        let mut config = config;
+        let mut restarts: VecDeque<Instant> = VecDeque::new();
+        // Subscribed before the first start, so no exit goes unseen
+        let mut events = fleet.events();
+        loop {
+            let started = factory(&strategy).and_then(|built| fleet.start(built, config.clone()));
+            let error = match started {
+                Ok(()) => match wait_for_exit(&fleet, &mut events, &name, &mut config).await {
+                    None => break,
+                    Some(error) => error,
+                },
+                Err(e) => e.to_string(),
+            };
+            if shutdown.is_triggered() {
+                break;
+            }
+
+            let now = Instant::now();
+            restarts.retain(|at| now.duration_since(*at) < policy.window());
+            if restarts.len() >= policy.max_restarts as usize {
+                tracing::error!(agent = %name, %error, restarts = restarts.len(), "agent keeps failing; not restarting");
+                break;
+            }
+            let delay = policy.backoff(restarts.len() as u32);
+            tracing::warn!(agent = %name, %error, delay_ms = delay.as_millis() as u64, "restarting agent");
+            restarts.push_back(now);
+            tokio::select! {
+                _ = shutdown.triggered() => break,
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    })
+}
+
+/// Readiness answer for a fleet: ready while every agent is running or
+/// paused, and not before one is
+fn readiness(metrics: &FleetMetrics) -> (u16, serde_json::Value) {
+    let ready = metrics.failed_agents == 0 && metrics.running + metrics.paused > 0;
+    let status = if ready { 200 } else { 503 };
+    (status, serde_json::json!({ "ready": ready, "fleet": metrics }))
+}
+
+/// Serve probes for `fleet` on `listener`:
+///
+/// - `GET /healthz`: `200` while the process answers at all
+/// - `GET /readyz`: `200` while every agent is running or paused, `503`
+///   while one is failed or waiting to restart
+///
+/// Probes carry no credentials, so bind this apart from the control endpoint.
+pub fn serve_health(listener: TcpListener, fleet: Arc<AgentFleet>, shutdown: Shutdown) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = tokio::select! {
+                _ = shutdown.triggered() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "health accept failed");
+                        continue;
+                    }
+                },
+            };
+            let (status, body) = match read_request(&mut stream).await {
+                Err(e) => (400, serde_json::json!({ "error": e.to_string() })),
+                Ok(request) if request.method != "GET" => (405, serde_json::json!({ "error": "use GET" })),
+                Ok(request) => match request.path.as_str() {
+                    "/healthz" => (200, serde_json::json!({ "status": "ok" })),
+                    "/readyz" => readiness(&fleet.metrics()),
+                    _ => (404, serde_json::json!({ "error": "not found" })),
+                },
+            };
+            if let Err(e) = write_response(&mut stream, status, &body).await {
+                tracing::debug!(%peer, error = %e, "health response not delivered");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RestartPolicy { max_restarts: 5, window_secs: 60, initial_backoff_ms: 500, max_backoff_ms: 3_000 };
+        let waits: Vec<u64> = (0..5).map(|recent| policy.backoff(recent).as_millis() as u64).collect();
+        assert_eq!(waits, vec![500, 1_000, 2_000, 3_000, 3_000]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(3_000));
+    }
+
+    #[test]
+    fn test_ready_only_with_no_failed_agents() {
+        let metrics = |running, paused, failed_agents| FleetMetrics {
+            agents: running + paused + failed_agents,
+            running,
+            paused,
+            failed_agents,
+            ..Default::default()
+        };
+        assert_eq!(readiness(&metrics(2, 1, 0)).0, 200);
+        assert_eq!(readiness(&metrics(0, 1, 0)).0, 200);
+        assert_eq!(readiness(&metrics(2, 0, 1)).0, 503);
+        assert_eq!(readiness(&metrics(0, 0, 0)).0, 503);
+    }
+}
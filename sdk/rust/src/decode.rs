@@ -0,0 +1,211 @@
+//! Fast decoding of subscription frames.
+//!
+//! Market updates arrive as small flat JSON objects, hundreds per second for
+//! agents watching many markets. `MarketUpdateRef` scans them in place,
+//! borrowing strings from the frame instead of building a `serde_json` value
+//! tree. Anything the scanner does not expect (escaped strings, nested values)
+//! falls back to `serde_json`, so the fast path never changes what decodes.
+
+use crate::MarketUpdate;
+
+/// Items the subscription transport can decode from a text frame
+pub trait DecodeFrame: Sized {
+    fn decode_frame(text: &str) -> Option<Self>;
+}
+
+impl DecodeFrame for MarketUpdate {
+    fn decode_frame(text: &str) -> Option<Self> {
+        match MarketUpdateRef::parse(text) {
+            Some(update) => Some(update.to_owned()),
+            None => serde_json::from_str(text).ok(),
+        }
+    }
+}
+
+/// A `MarketUpdate` whose strings borrow from the frame it was parsed from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketUpdateRef<'a> {
+    pub market_id: &'a str,
+    pub yes_odds: f64,
+    pub no_odds: f64,
+    pub volume: f64,
+    pub status: &'a str,
+    pub timestamp: u64,
+    pub sequence: u64,
+}
+
+impl<'a> MarketUpdateRef<'a> {
+    /// Parse without allocating; `None` when the frame needs the full JSON parser
+    pub fn parse(text: &'a str) -> Option<Self> {
+        let mut scanner = Scanner { bytes: text.as_bytes(), text, pos: 0 };
+        let (mut market_id, mut status) = (None, None);
+        let (mut yes_odds, mut no_odds, mut volume) = (None, None, None);
+        let (mut timestamp, mut sequence) = (None, 0);
+
+        scanner.expect(b'{')?;
+        if !scanner.eat(b'}') {
+            loop {
+                let key = scanner.string()?;
+                scanner.expect(b':')?;
+                match key {
+                    "marketId" => market_id = Some(scanner.string()?),
+                    "status" => status = Some(scanner.string()?),
+                    "yesOdds" => yes_odds = Some(scanner.number()?.parse().ok()?),
+                    "noOdds" => no_odds = Some(scanner.number()?.parse().ok()?),
+                    "volume" => volume = Some(scanner.number()?.parse().ok()?),
+                    "timestamp" => timestamp = Some(scanner.number()?.parse().ok()?),
+                    "sequence" => sequence = scanner.number()?.parse().ok()?,
+                    _ => scanner.skip_scalar()?,
+                }
+                if scanner.eat(b'}') {
+                    break;
+                }
+                scanner.expect(b',')?;
+            }
+        }
+        scanner.skip_whitespace();
+        if scanner.pos != scanner.bytes.len() {
+            return None;
+        }
+
+        Some(Self {
+            market_id: market_id?,
+            yes_odds: yes_odds?,
+            no_odds: no_odds?,
+            volume: volume?,
+            status: status?,
+            timestamp: timestamp?,
+            sequence,
+        })
+    }
+
+    pub fn to_owned(&self) -> MarketUpdate {
+        MarketUpdate {
+            market_id: self.market_id.to_string(),
+            yes_odds: self.yes_odds,
+            no_odds: self.no_odds,
+            volume: self.volume,
+            status: self.status.to_string(),
+            timestamp: self.timestamp,
+            sequence: self.sequence,
+        }
+    }
+
+    /// Overwrite `target`, reusing its string buffers so a long-lived scratch
+    /// update stops allocating once it has seen the longest market ID
+    pub fn write_into(&self, target: &mut MarketUpdate) {
+        target.market_id.clear();
+        target.market_id.push_str(self.market_id);
+        target.status.clear();
+        target.status.push_str(self.status);
+        target.yes_odds = self.yes_odds;
+        target.no_odds = self.no_odds;
+        target.volume = self.volume;
+        target.timestamp = self.timestamp;
+        target.sequence = self.sequence;
+    }
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.eat(byte).then_some(())
+    }
+
+    /// A string without escapes
+    fn string(&mut self) -> Option<&'a str> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        loop {
+            match *self.bytes.get(self.pos)? {
+                b'"' => break,
+                b'\\' => return None,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        // Quotes are ASCII, so both ends fall on char boundaries
+        Some(&self.text[start..self.pos - 1])
+    }
+
+    fn number(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|byte| byte.is_ascii_digit() || matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E'))
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| &self.text[start..self.pos])
+    }
+
+    /// Skip a field this decoder does not read; nested values bail out to `serde_json`
+    fn skip_scalar(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        match *self.bytes.get(self.pos)? {
+            b'"' => self.string().map(|_| ()),
+            b't' | b'f' | b'n' => {
+                while self.bytes.get(self.pos).is_some_and(u8::is_ascii_alphabetic) {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+            _ => self.number().map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: &str = r#"{"marketId":"nba-finals","yesOdds":0.62,"noOdds":0.38,"volume":1250.5,"status":"OPEN","timestamp":1700000000000,"sequence":42}"#;
+
+    #[test]
+    fn test_fast_path_matches_serde() {
+        let fast = MarketUpdateRef::parse(FRAME).unwrap().to_owned();
+        let slow: MarketUpdate = serde_json::from_str(FRAME).unwrap();
+        assert_eq!(fast.market_id, slow.market_id);
+        assert_eq!(fast.yes_odds, slow.yes_odds);
+        assert_eq!(fast.volume, slow.volume);
+        assert_eq!(fast.status, slow.status);
+        assert_eq!(fast.timestamp, slow.timestamp);
+        assert_eq!(fast.sequence, slow.sequence);
+    }
+
+    #[test]
+    fn test_unusual_frames_fall_back() {
+        let escaped = r#"{"marketId":"a\"b","yesOdds":0.5,"noOdds":0.5,"volume":0,"status":"OPEN","timestamp":1}"#;
+        assert!(MarketUpdateRef::parse(escaped).is_none());
+        assert_eq!(MarketUpdate::decode_frame(escaped).unwrap().market_id, "a\"b");
+
+        let nested = r#"{"marketId":"m","extra":{"x":1},"yesOdds":0.5,"noOdds":0.5,"volume":0,"status":"OPEN","timestamp":1}"#;
+        assert!(MarketUpdateRef::parse(nested).is_none());
+        assert!(MarketUpdate::decode_frame(nested).is_some());
+
+        assert!(MarketUpdate::decode_frame(r#"{"marketId":"m"}"#).is_none());
+    }
+}
@@ -0,0 +1,313 @@
+//! Order book depth: snapshot plus sequenced deltas, kept in sync on the client
+
+use crate::transport::Transport;
+use crate::{
+    CoalesceKey, DecodeFrame, OddsStreamSdk, OrderSide, ReadOnlyClient, SdkError, SubscriptionHandle,
+    UpdateReceiver,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prices are kept as integer micro-units so levels can be map keys
+const PRICE_SCALE: f64 = 1_000_000.0;
+
+/// Resting size at one price on one side
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthLevel {
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DepthAction {
+    Add,
+    Modify,
+    Remove,
+}
+
+/// A single level change; `size` is the level's new total, ignored for removals
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthChange {
+    pub action: DepthAction,
+    pub side: OrderSide,
+    pub price: f64,
+    #[serde(default)]
+    pub size: f64,
+}
+
+/// Frame of the `marketDepth` subscription. Each market's deltas carry
+/// consecutive sequence numbers following the snapshot they apply to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DepthMessage {
+    #[serde(rename_all = "camelCase")]
+    Snapshot { market_id: String, sequence: u64, levels: Vec<DepthLevel> },
+    #[serde(rename_all = "camelCase")]
+    Delta { market_id: String, sequence: u64, changes: Vec<DepthChange> },
+}
+
+impl CoalesceKey for DepthMessage {
+    fn coalesce_key(&self) -> &str {
+        match self {
+            DepthMessage::Snapshot { market_id, .. } | DepthMessage::Delta { market_id, .. } => market_id,
+        }
+    }
+}
+
+impl DecodeFrame for DepthMessage {
+    fn decode_frame(text: &str) -> Option<Self> {
+        serde_json::from_str(text).ok()
+    }
+}
+
+/// A delta did not follow the book's sequence; the book needs a fresh snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub expected: u64,
+    pub received: u64,
+}
+
+/// Client-side copy of one market's book
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    pub market_id: String,
+    /// Sequence of the last applied snapshot or delta
+    pub sequence: u64,
+    yes: BTreeMap<u64, f64>,
+    no: BTreeMap<u64, f64>,
+    /// Set by a gap; deltas are ignored until the next snapshot
+    stale: bool,
+}
+
+impl OrderBook {
+    pub fn new(market_id: impl Into<String>) -> Self {
+        Self { market_id: market_id.into(), stale: true, ..Default::default() }
+    }
+
+    /// False until the first snapshot, and again after a gap until a resnapshot
+    pub fn is_synced(&self) -> bool {
+        !self.stale
+    }
+
+    /// Apply a snapshot or the next delta. Deltas at or before the current
+    /// sequence are duplicates and skipped; one that skips ahead is a gap.
+    pub fn apply(&mut self, message: &DepthMessage) -> Result<(), SequenceGap> {
+        match message {
+            DepthMessage::Snapshot { sequence, levels, .. } => {
+                self.yes.clear();
+                self.no.clear();
+                for level in levels {
+                    self.set_level(level.side, level.price, level.size);
+                }
+                self.sequence = *sequence;
+                self.stale = false;
+                Ok(())
+            }
+            DepthMessage::Delta { sequence, changes, .. } => {
+                if self.stale || *sequence <= self.sequence {
+                    return Ok(());
+                }
+                if *sequence != self.sequence + 1 {
+                    self.stale = true;
+                    return Err(SequenceGap { expected: self.sequence + 1, received: *sequence });
+                }
+                for change in changes {
+                    match change.action {
+                        DepthAction::Add | DepthAction::Modify => {
+                            self.set_level(change.side, change.price, change.size)
+                        }
+                        DepthAction::Remove => self.set_level(change.side, change.price, 0.0),
+                    }
+                }
+                self.sequence = *sequence;
+                Ok(())
+            }
+        }
+    }
+
+    /// Levels on `side`, best (highest) price first
+    pub fn levels(&self, side: OrderSide) -> impl Iterator<Item = DepthLevel> + '_ {
+        self.side(side).iter().rev().map(move |(&ticks, &size)| DepthLevel {
+            side,
+            price: ticks as f64 / PRICE_SCALE,
+            size,
+        })
+    }
+
+    pub fn best(&self, side: OrderSide) -> Option<DepthLevel> {
+        self.levels(side).next()
+    }
+
+    /// Total size resting on `side` at `price` or better
+    pub fn size_at_or_better(&self, side: OrderSide, price: f64) -> f64 {
+        self.side(side).range(to_ticks(price)..).map(|(_, size)| size).sum()
+    }
+
+    fn side(&self, side: OrderSide) -> &BTreeMap<u64, f64> {
+        match side {
+            OrderSide::Yes => &self.yes,
+            OrderSide::No => &self.no,
+        }
+    }
+
+    fn set_level(&mut self, side: OrderSide, price: f64, size: f64) {
+        let levels = match side {
+            OrderSide::Yes => &mut self.yes,
+            OrderSide::No => &mut self.no,
+        };
+        if size > 0.0 {
+            levels.insert(to_ticks(price), size);
+        } else {
+            levels.remove(&to_ticks(price));
+        }
+    }
+}
+
+fn to_ticks(price: f64) -> u64 {
+    (price * PRICE_SCALE).round() as u64
+}
+
+#[derive(Deserialize)]
+struct DepthSnapshotData {
+    #[serde(rename = "depthSnapshot")]
+    depth_snapshot: Option<DepthMessage>,
+}
+
+/// A live `OrderBook` fed by the depth subscription, resnapshotting on gaps
+pub struct DepthBook {
+    book: OrderBook,
+    receiver: UpdateReceiver<DepthMessage>,
+    transport: Transport,
+    _subscription: SubscriptionHandle,
+}
+
+impl DepthBook {
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    /// Wait for the next change and apply it; `None` once the subscription ends
+    pub async fn next(&mut self) -> Option<Result<&OrderBook, SdkError>> {
+        let message = self.receiver.recv().await?;
+        if let Err(gap) = self.book.apply(&message) {
+            tracing::warn!(
+                market_id = %self.book.market_id,
+                expected = gap.expected,
+                received = gap.received,
+                "depth gap, resnapshotting"
+            );
+            if let Err(e) = self.resnapshot().await {
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(&self.book))
+    }
+
+    async fn resnapshot(&mut self) -> Result<(), SdkError> {
+        let snapshot = self.transport.depth_snapshot(&self.book.market_id).await?;
+        // Cannot gap: snapshots always apply
+        let _ = self.book.apply(&snapshot);
+        Ok(())
+    }
+}
+
+impl Transport {
+    pub(crate) async fn depth_snapshot(&self, market_id: &str) -> Result<DepthMessage, SdkError> {
+        let query = r#"
+            query DepthSnapshot($marketId: String!) {
+                depthSnapshot(marketId: $marketId) {
+                    type
+                    marketId
+                    sequence
+                    levels { side price size }
+                }
+            }
+        "#;
+
+        let data: DepthSnapshotData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.depth_snapshot
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+
+    pub(crate) async fn subscribe_depth(&self, market_id: &str) -> Result<DepthBook, SdkError> {
+        let subscription_query = r#"
+            subscription OnMarketDepth($marketId: String!) {
+                marketDepth(marketId: $marketId) {
+                    type
+                    marketId
+                    sequence
+                    levels { side price size }
+                    changes { action side price size }
+                }
+            }
+        "#;
+
+        // Subscribe before snapshotting so no delta between the two is missed;
+        // anything already covered by the snapshot is skipped by sequence
+        let (subscription, receiver) =
+            self.subscribe(subscription_query, serde_json::json!({ "marketId": market_id }));
+        let mut book = OrderBook::new(market_id);
+        let _ = book.apply(&self.depth_snapshot(market_id).await?);
+
+        Ok(DepthBook { book, receiver, transport: self.clone(), _subscription: subscription })
+    }
+}
+
+impl OddsStreamSdk {
+    /// Follow a market's order book depth; the returned book stays consistent across dropped deltas
+    pub async fn subscribe_depth(&self, market_id: &str) -> Result<DepthBook, SdkError> {
+        self.transport.subscribe_depth(market_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Follow a market's order book depth; the returned book stays consistent across dropped deltas
+    pub async fn subscribe_depth(&self, market_id: &str) -> Result<DepthBook, SdkError> {
+        self.transport.subscribe_depth(market_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(sequence: u64, action: DepthAction, price: f64, size: f64) -> DepthMessage {
+        DepthMessage::Delta {
+            market_id: "m1".to_string(),
+            sequence,
+            changes: vec![DepthChange { action, side: OrderSide::Yes, price, size }],
+        }
+    }
+
+    #[test]
+    fn test_book_applies_deltas_and_detects_gaps() {
+        let mut book = OrderBook::new("m1");
+        assert!(!book.is_synced());
+        book.apply(&DepthMessage::Snapshot {
+            market_id: "m1".to_string(),
+            sequence: 10,
+            levels: vec![DepthLevel { side: OrderSide::Yes, price: 0.55, size: 100.0 }],
+        })
+        .unwrap();
+
+        // Already covered by the snapshot
+        book.apply(&delta(10, DepthAction::Remove, 0.55, 0.0)).unwrap();
+        book.apply(&delta(11, DepthAction::Add, 0.60, 50.0)).unwrap();
+        book.apply(&delta(12, DepthAction::Modify, 0.55, 80.0)).unwrap();
+        assert_eq!(book.best(OrderSide::Yes).unwrap().price, 0.60);
+        assert_eq!(book.size_at_or_better(OrderSide::Yes, 0.55), 130.0);
+
+        let gap = book.apply(&delta(14, DepthAction::Remove, 0.60, 0.0)).unwrap_err();
+        assert_eq!(gap, SequenceGap { expected: 13, received: 14 });
+        assert!(!book.is_synced());
+        // Ignored until resnapshotted
+        book.apply(&delta(15, DepthAction::Remove, 0.60, 0.0)).unwrap();
+        assert_eq!(book.sequence, 12);
+    }
+}
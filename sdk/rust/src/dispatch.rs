@@ -0,0 +1,259 @@
+//! Bounded update queues decoupling the WebSocket read loop from consumers
+
+use crate::{MarketUpdate, SubscriptionHandle};
+use futures::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::sync::Notify;
+
+/// What to do when a consumer falls behind and the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued update to make room
+    #[default]
+    DropOldest,
+    /// Replace a queued update for the same market in place; falls back to
+    /// dropping the oldest when every queued update is for a different market
+    CoalesceByMarket,
+    /// Pause reading from the socket until the consumer catches up
+    Block,
+}
+
+/// Queue sizing for subscription dispatch
+#[derive(Debug, Clone)]
+pub struct DispatchConfig {
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: OverflowPolicy::DropOldest,
+        }
+    }
+}
+
+/// Items that can be coalesced by `OverflowPolicy::CoalesceByMarket`
+pub trait CoalesceKey {
+    fn coalesce_key(&self) -> &str;
+}
+
+impl CoalesceKey for MarketUpdate {
+    fn coalesce_key(&self) -> &str {
+        &self.market_id
+    }
+}
+
+struct Shared<T> {
+    queue: Mutex<VecDeque<T>>,
+    config: DispatchConfig,
+    item_ready: Notify,
+    space_ready: Notify,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+}
+
+/// Producer half, owned by the subscription read loop
+pub struct UpdateSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Consumer half of a subscription queue
+pub struct UpdateReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Create a bounded queue with the given overflow behaviour
+pub fn update_channel<T>(config: DispatchConfig) -> (UpdateSender<T>, UpdateReceiver<T>) {
+    assert!(config.capacity > 0, "dispatch capacity must be positive");
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(config.capacity)),
+        config,
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+        closed: AtomicBool::new(false),
+        dropped: AtomicU64::new(0),
+    });
+    (
+        UpdateSender { shared: shared.clone() },
+        UpdateReceiver { shared },
+    )
+}
+
+impl<T: CoalesceKey> UpdateSender<T> {
+    /// Enqueue an update, applying the overflow policy when full
+    pub async fn send(&self, item: T) {
+        let shared = &self.shared;
+        loop {
+            let space = shared.space_ready.notified();
+            {
+                let mut queue = shared.queue.lock().unwrap();
+                if queue.len() < shared.config.capacity {
+                    queue.push_back(item);
+                    break;
+                }
+                match shared.config.overflow {
+                    OverflowPolicy::Block => {}
+                    OverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                        queue.push_back(item);
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                    OverflowPolicy::CoalesceByMarket => {
+                        let key = item.coalesce_key();
+                        match queue.iter().position(|queued| queued.coalesce_key() == key) {
+                            Some(index) => queue[index] = item,
+                            None => {
+                                queue.pop_front();
+                                queue.push_back(item);
+                            }
+                        }
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                }
+            }
+            space.await;
+        }
+        shared.item_ready.notify_one();
+    }
+}
+
+impl<T> Drop for UpdateSender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.item_ready.notify_one();
+    }
+}
+
+impl<T> UpdateReceiver<T> {
+    /// Next update, or `None` once the subscription has ended and the queue is drained
+    pub async fn recv(&mut self) -> Option<T> {
+        let shared = &self.shared;
+        loop {
+            let ready = shared.item_ready.notified();
+            if let Some(item) = shared.queue.lock().unwrap().pop_front() {
+                shared.space_ready.notify_one();
+                return Some(item);
+            }
+            if shared.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            ready.await;
+        }
+    }
+
+    /// Updates discarded or coalesced because the consumer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of updates waiting to be consumed
+    pub fn len(&self) -> usize {
+        self.shared.queue.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Consume the receiver as a `Stream`
+    pub fn into_stream(self) -> impl Stream<Item = T> {
+        futures::stream::unfold(self, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        })
+    }
+}
+
+/// Subscription exposed as a `Stream`; the connection is closed when it is dropped
+pub struct UpdateStream<T> {
+    inner: Pin<Box<dyn Stream<Item = T> + Send>>,
+    subscription: SubscriptionHandle,
+}
+
+impl<T: Send + 'static> UpdateStream<T> {
+    pub(crate) fn new(subscription: SubscriptionHandle, receiver: UpdateReceiver<T>) -> Self {
+        Self {
+            inner: Box::pin(receiver.into_stream()),
+            subscription,
+        }
+    }
+}
+
+impl<T> Stream for UpdateStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<T> Drop for UpdateStream<T> {
+    fn drop(&mut self) {
+        self.subscription.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(market_id: &str, timestamp: u64) -> MarketUpdate {
+        MarketUpdate {
+            market_id: market_id.to_string(),
+            yes_odds: 0.5,
+            no_odds: 0.5,
+            volume: 0.0,
+            status: "active".to_string(),
+            timestamp,
+            sequence: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let (tx, mut rx) = update_channel(DispatchConfig { capacity: 2, overflow: OverflowPolicy::DropOldest });
+        for ts in 0..3 {
+            tx.send(update("m1", ts)).await;
+        }
+        drop(tx);
+
+        assert_eq!(rx.dropped(), 1);
+        assert_eq!(rx.recv().await.unwrap().timestamp, 1);
+        assert_eq!(rx.recv().await.unwrap().timestamp, 2);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_by_market() {
+        let (tx, mut rx) = update_channel(DispatchConfig { capacity: 2, overflow: OverflowPolicy::CoalesceByMarket });
+        tx.send(update("m1", 0)).await;
+        tx.send(update("m2", 1)).await;
+        tx.send(update("m1", 2)).await;
+        drop(tx);
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!((first.market_id.as_str(), first.timestamp), ("m1", 2));
+        assert_eq!(rx.recv().await.unwrap().market_id, "m2");
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_consumer() {
+        let (tx, mut rx) = update_channel(DispatchConfig { capacity: 1, overflow: OverflowPolicy::Block });
+        tx.send(update("m1", 0)).await;
+
+        let producer = tokio::spawn(async move {
+            tx.send(update("m1", 1)).await;
+        });
+        assert_eq!(rx.recv().await.unwrap().timestamp, 0);
+        producer.await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().timestamp, 1);
+        assert_eq!(rx.dropped(), 0);
+    }
+}
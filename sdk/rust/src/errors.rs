@@ -0,0 +1,54 @@
+use thiserror::Error;
+
+/// Errors returned by the OddsStream SDK
+#[derive(Debug, Error)]
+pub enum SdkError {
+    #[error("HTTP request failed: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Connection error: {0}")]
+    ConnectionError(String),
+
+    #[error("WebSocket error: {0}")]
+    WebSocketError(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("GraphQL error at `{path}`: {message}")]
+    GraphQL {
+        message: String,
+        path: String,
+        extensions: Option<serde_json::Value>,
+    },
+
+    #[error("GraphQL response contained no data")]
+    EmptyResponse,
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Market not found: {0}")]
+    MarketNotFound(String),
+
+    #[error("Storage error: {0}")]
+    StorageError(String),
+
+    #[error("Rate limited by the server (retry after {retry_after_secs:?}s)")]
+    RateLimited { retry_after_secs: Option<u64> },
+
+    #[error("Market {market_id} speaks wire schema {found}, this SDK was built for {expected}")]
+    IncompatibleSchema { market_id: String, expected: String, found: String },
+
+    #[error("Light-client verification failed: {0}")]
+    VerificationFailed(String),
+
+    #[error("Compliance check failed: chain {user_chain_id} is not approved to trade on {market_id}")]
+    ComplianceRejected { market_id: String, user_chain_id: String },
+
+    #[error("Chain {user_chain_id} has trading limits in force; only orders placed through its user application count against them")]
+    TradingLimited { user_chain_id: String },
+}
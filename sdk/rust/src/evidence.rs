@@ -0,0 +1,96 @@
+//! Resolution evidence: hashes on-chain, content off-chain
+
+use crate::{MarketMessage, OddsStreamSdk, SdkError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EvidenceKind {
+    ResolutionSource,
+    DisputeEvidence,
+    OracleObservation,
+}
+
+/// One evidence attachment as recorded by the market chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Evidence {
+    pub kind: EvidenceKind,
+    /// Hex-encoded sha256 of the off-chain content
+    pub content_hash: String,
+    pub uri: String,
+    pub submitter: String,
+    /// Micros timestamp of the block that recorded it
+    pub submitted_at: u64,
+}
+
+impl Evidence {
+    /// Check fetched off-chain content against the hash committed on-chain
+    pub fn matches(&self, content: &[u8]) -> bool {
+        hex::encode(Sha256::digest(content)) == self.content_hash
+    }
+}
+
+#[derive(Deserialize)]
+struct EvidenceData {
+    #[serde(rename = "resolutionEvidence")]
+    resolution_evidence: Vec<Evidence>,
+}
+
+impl OddsStreamSdk {
+    /// Commit to `content` on the market chain; the content itself must be published at `uri`
+    pub async fn attach_evidence(
+        &self,
+        market_id: &str,
+        kind: EvidenceKind,
+        content: &[u8],
+        uri: &str,
+    ) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        let message = MarketMessage::AttachEvidence {
+            kind,
+            content_hash: Sha256::digest(content).into(),
+            uri: uri.to_string(),
+        };
+        self.send_message(market_chain_id, message).await
+    }
+
+    /// Every evidence attachment on a market, oldest first
+    pub async fn resolution_evidence(&self, market_id: &str) -> Result<Vec<Evidence>, SdkError> {
+        let query = r#"
+            query ResolutionEvidence($marketId: String!) {
+                resolutionEvidence(marketId: $marketId) {
+                    kind
+                    contentHash
+                    uri
+                    submitter
+                    submittedAt
+                }
+            }
+        "#;
+
+        let data: EvidenceData = self
+            .graphql_query(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        Ok(data.resolution_evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evidence_matches_content() {
+        let evidence = Evidence {
+            kind: EvidenceKind::ResolutionSource,
+            content_hash: hex::encode(Sha256::digest(b"final score 2-1")),
+            uri: "ipfs://example".to_string(),
+            submitter: "chain".to_string(),
+            submitted_at: 0,
+        };
+        assert!(evidence.matches(b"final score 2-1"));
+        assert!(!evidence.matches(b"final score 1-2"));
+    }
+}
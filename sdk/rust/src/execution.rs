@@ -0,0 +1,154 @@
+//! Execution models for the backtester: how late orders reach the market,
+//! what they pay on the way in, and how limit orders that cannot fill at
+//! once wait in the queue
+
+use crate::SimRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Time from a decision to its order reaching the market
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LatencyModel {
+    /// Orders arrive the moment they are decided
+    #[default]
+    None,
+    Fixed { millis: u64 },
+    Uniform { min_millis: u64, max_millis: u64 },
+    /// Heavy-tailed, like most real network and block-inclusion delays
+    LogNormal { median_millis: f64, sigma: f64 },
+}
+
+impl LatencyModel {
+    /// One draw, in micros
+    pub fn sample(&self, rng: &mut SimRng) -> u64 {
+        let millis = match self {
+            LatencyModel::None => 0.0,
+            LatencyModel::Fixed { millis } => *millis as f64,
+            LatencyModel::Uniform { min_millis, max_millis } => {
+                rng.gen_range(*min_millis..=(*max_millis).max(*min_millis)) as f64
+            }
+            LatencyModel::LogNormal { median_millis, sigma } => {
+                // Box-Muller; 1 - u keeps the logarithm finite
+                let (u1, u2): (f64, f64) = (1.0 - rng.gen::<f64>(), rng.gen());
+                let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                median_millis * (sigma * normal).exp()
+            }
+        };
+        (millis.max(0.0) * 1_000.0) as u64
+    }
+}
+
+/// Cost of crossing into the market on top of the quoted price
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SlippageModel {
+    /// Paid on every fill, in basis points of the price
+    pub fixed_bps: f64,
+    /// Price impact per share filled, in basis points of the price
+    pub impact_bps_per_share: f64,
+}
+
+impl SlippageModel {
+    /// What `shares` actually pay per share when the quote is `price`; a share never costs more than 1
+    pub fn fill_price(&self, price: f64, shares: f64) -> f64 {
+        let bps = self.fixed_bps + self.impact_bps_per_share * shares;
+        (price * (1.0 + bps / 10_000.0)).min(1.0)
+    }
+}
+
+/// How a limit order waits when its price is not available on arrival
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RestingModel {
+    /// Shares assumed ahead of the order at its price; volume fills them first
+    pub queue_ahead_shares: f64,
+    /// Share of the volume traded at or below the limit, after the queue ahead, that reaches the order
+    pub participation: f64,
+    /// Cancel what is left this long after arrival; `None` rests until the run ends
+    pub ttl_secs: Option<u64>,
+}
+
+impl Default for RestingModel {
+    fn default() -> Self {
+        Self { queue_ahead_shares: 0.0, participation: 1.0, ttl_secs: None }
+    }
+}
+
+/// How the backtester turns decisions into fills
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExecutionModel {
+    pub latency: LatencyModel,
+    pub slippage: SlippageModel,
+    /// Trading fee on each fill's cost, in basis points
+    pub fee_bps: u32,
+    /// `None` drops limit orders that cannot fill on arrival, as a fill-or-kill
+    pub resting: Option<RestingModel>,
+    /// Seeds the latency draws, so a run can be repeated exactly
+    pub seed: u64,
+}
+
+impl ExecutionModel {
+    pub fn fee(&self, cost: f64) -> f64 {
+        cost * f64::from(self.fee_bps) / 10_000.0
+    }
+}
+
+/// An order between its decision and the end of its life
+#[derive(Debug, Clone)]
+pub(crate) struct PendingOrder {
+    /// Index of the decision in the backtest report
+    pub(crate) decision: usize,
+    pub(crate) remaining: f64,
+    pub(crate) limit: Option<f64>,
+    pub(crate) arrives_at: u64,
+    pub(crate) queue_ahead: f64,
+    /// Set once the order is resting
+    pub(crate) expires_at: Option<u64>,
+    pub(crate) resting: bool,
+}
+
+impl PendingOrder {
+    /// Shares of the order `traded` shares at an acceptable price fill, working through the queue ahead first
+    pub(crate) fn take_from_volume(&mut self, traded: f64, model: &RestingModel) -> f64 {
+        let past_queue = (traded - self.queue_ahead).max(0.0);
+        self.queue_ahead = (self.queue_ahead - traded).max(0.0);
+        (past_queue * model.participation.clamp(0.0, 1.0)).min(self.remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_ahead_fills_before_the_order() {
+        let model = RestingModel { queue_ahead_shares: 30.0, participation: 0.5, ttl_secs: None };
+        let mut order = PendingOrder {
+            decision: 0,
+            remaining: 20.0,
+            limit: Some(0.4),
+            arrives_at: 0,
+            queue_ahead: model.queue_ahead_shares,
+            expires_at: None,
+            resting: true,
+        };
+        assert_eq!(order.take_from_volume(20.0, &model), 0.0);
+        // 10 more clear the queue, the other 10 trade half with the order
+        assert_eq!(order.take_from_volume(20.0, &model), 5.0);
+        assert_eq!(order.take_from_volume(100.0, &model), 20.0);
+    }
+
+    #[test]
+    fn test_latency_draws_repeat_with_the_seed() {
+        let model = LatencyModel::LogNormal { median_millis: 250.0, sigma: 0.5 };
+        let draws = |seed| {
+            let mut rng = SimRng::seeded(seed);
+            (0..8).map(|_| model.sample(&mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(draws(3), draws(3));
+        assert!(draws(3).iter().all(|micros| *micros > 0));
+        assert_eq!(SlippageModel { fixed_bps: 100.0, impact_bps_per_share: 0.0 }.fill_price(0.995, 1.0), 1.0);
+    }
+}
@@ -0,0 +1,242 @@
+//! Odds from other books and APIs, normalized to implied probabilities with
+//! the bookmaker margin removed, and combined into a consensus per market
+
+use crate::{Clock, OddsFormat, SdkError, Shutdown, SystemClock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Where a feed's response holds the prices for one event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedParser {
+    /// JSON pointer (RFC 6901) to the YES price, e.g. `/outcomes/0/price`
+    pub yes_pointer: String,
+    /// JSON pointer to the NO price. Without it the YES price is taken at
+    /// face value and any margin in it stays in.
+    #[serde(default)]
+    pub no_pointer: Option<String>,
+    /// How the feed quotes prices
+    pub format: OddsFormat,
+}
+
+impl FeedParser {
+    /// Raw implied probabilities of YES and, if configured, NO
+    pub fn parse(&self, body: &serde_json::Value) -> Result<(f64, Option<f64>), SdkError> {
+        let price_at = |pointer: &str| {
+            let value = body
+                .pointer(pointer)
+                .ok_or_else(|| SdkError::InvalidInput(format!("no price at {}", pointer)))?;
+            let text = match value {
+                serde_json::Value::String(text) => text.clone(),
+                serde_json::Value::Number(number) => number.to_string(),
+                other => return Err(SdkError::InvalidInput(format!("price at {} is {}", pointer, other))),
+            };
+            self.format.parse(&text)
+        };
+        let yes = price_at(&self.yes_pointer)?;
+        let no = self.no_pointer.as_deref().map(price_at).transpose()?;
+        Ok((yes, no))
+    }
+}
+
+/// One external source of odds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalFeedConfig {
+    /// Name used in logs and consensus breakdowns
+    pub name: String,
+    /// Request URL; `{event}` is replaced with the feed's ID for the event
+    pub url: String,
+    pub parser: FeedParser,
+    /// OddsStream market ID -> the feed's event ID
+    pub events: HashMap<String, String>,
+    /// Relative weight in the consensus
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Extra request headers, such as an API key
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// One feed's view of a market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalQuote {
+    pub feed: String,
+    pub market_id: String,
+    /// Implied probability of YES, margin removed when both sides are quoted
+    pub yes_probability: f64,
+    /// How far the feed's raw probabilities summed above 1; 0 when only YES is quoted
+    pub overround: f64,
+    /// Micros since the Unix epoch
+    pub fetched_at: u64,
+    pub weight: f64,
+}
+
+/// Normalize raw implied probabilities; returns the fair YES probability and the overround
+pub fn remove_margin(yes: f64, no: Option<f64>) -> (f64, f64) {
+    match no {
+        Some(no) if yes + no > 0.0 => (yes / (yes + no), yes + no - 1.0),
+        _ => (yes, 0.0),
+    }
+}
+
+/// Weighted view of every feed quoting a market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Consensus {
+    pub market_id: String,
+    /// Weighted mean of the feeds' fair YES probabilities
+    pub yes_probability: f64,
+    /// Widest gap between two feeds; large values mean the books disagree
+    pub dispersion: f64,
+    pub quotes: Vec<ExternalQuote>,
+    /// Oldest quote's fetch time, in micros
+    pub as_of: u64,
+}
+
+impl Consensus {
+    /// `None` without any quote carrying weight
+    pub fn from_quotes(market_id: &str, quotes: Vec<ExternalQuote>) -> Option<Self> {
+        let total_weight: f64 = quotes.iter().map(|quote| quote.weight).sum();
+        if quotes.is_empty() || total_weight <= 0.0 {
+            return None;
+        }
+        let yes_probability =
+            quotes.iter().map(|quote| quote.yes_probability * quote.weight).sum::<f64>() / total_weight;
+        let (low, high) = quotes.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), quote| {
+            (low.min(quote.yes_probability), high.max(quote.yes_probability))
+        });
+        Some(Self {
+            market_id: market_id.to_string(),
+            yes_probability,
+            dispersion: high - low,
+            as_of: quotes.iter().map(|quote| quote.fetched_at).min().unwrap_or_default(),
+            quotes,
+        })
+    }
+
+    pub fn no_probability(&self) -> f64 {
+        1.0 - self.yes_probability
+    }
+}
+
+/// Latest consensus per market, shared between a polling task and strategies
+#[derive(Debug, Clone, Default)]
+pub struct ConsensusBoard {
+    inner: Arc<RwLock<HashMap<String, Consensus>>>,
+}
+
+impl ConsensusBoard {
+    pub fn get(&self, market_id: &str) -> Option<Consensus> {
+        self.inner.read().unwrap().get(market_id).cloned()
+    }
+
+    pub fn publish(&self, consensus: Consensus) {
+        self.inner.write().unwrap().insert(consensus.market_id.clone(), consensus);
+    }
+}
+
+/// Fetches quotes from the configured feeds
+pub struct ExternalOddsFeed {
+    feeds: Vec<ExternalFeedConfig>,
+    client: reqwest::Client,
+    clock: Arc<dyn Clock>,
+}
+
+impl ExternalOddsFeed {
+    pub fn new(feeds: Vec<ExternalFeedConfig>) -> Self {
+        Self { feeds, client: reqwest::Client::new(), clock: Arc::new(SystemClock) }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    async fn fetch_one(&self, feed: &ExternalFeedConfig, market_id: &str, event: &str) -> Result<ExternalQuote, SdkError> {
+        let mut request = self.client.get(feed.url.replace("{event}", event));
+        for (name, value) in &feed.headers {
+            request = request.header(name, value);
+        }
+        let body: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+        let (yes, no) = feed.parser.parse(&body)?;
+        let (yes_probability, overround) = remove_margin(yes, no);
+        Ok(ExternalQuote {
+            feed: feed.name.clone(),
+            market_id: market_id.to_string(),
+            yes_probability,
+            overround,
+            fetched_at: self.clock.now_micros(),
+            weight: feed.weight,
+        })
+    }
+
+    /// Quotes from every feed that lists `market_id`; a failing feed is logged and left out
+    pub async fn quotes(&self, market_id: &str) -> Vec<ExternalQuote> {
+        let fetches = self.feeds.iter().filter_map(|feed| {
+            let event = feed.events.get(market_id)?;
+            Some(async move { (feed, self.fetch_one(feed, market_id, event).await) })
+        });
+        let mut quotes = Vec::new();
+        for (feed, result) in futures::future::join_all(fetches).await {
+            match result {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => tracing::warn!(feed = %feed.name, %market_id, error = %e, "external odds fetch failed"),
+            }
+        }
+        quotes
+    }
+
+    pub async fn consensus(&self, market_id: &str) -> Option<Consensus> {
+        Consensus::from_quotes(market_id, self.quotes(market_id).await)
+    }
+
+    /// Refresh the consensus of `market_ids` every `interval` until `shutdown` triggers
+    pub fn watch(self, market_ids: Vec<String>, interval: Duration, shutdown: Shutdown) -> ConsensusBoard {
+        let board = ConsensusBoard::default();
+        let published = board.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = shutdown.triggered() => break,
+                    _ = ticker.tick() => {
+                        for market_id in &market_ids {
+                            if let Some(consensus) = self.consensus(market_id).await {
+                                published.publish(consensus);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_removes_bookmaker_margin() {
+        let parser = FeedParser {
+            yes_pointer: "/outcomes/0/price".to_string(),
+            no_pointer: Some("/outcomes/1/price".to_string()),
+            format: OddsFormat::Decimal,
+        };
+        let body = serde_json::json!({ "outcomes": [{ "price": 1.8 }, { "price": "2.05" }] });
+        let (yes, no) = parser.parse(&body).unwrap();
+        let (fair, overround) = remove_margin(yes, no);
+
+        assert!((overround - (1.0 / 1.8 + 1.0 / 2.05 - 1.0)).abs() < 1e-12);
+        assert!((fair - (1.0 / 1.8) / (1.0 / 1.8 + 1.0 / 2.05)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,124 @@
+//! Volume-tiered trading fees
+//!
+//! A market's fee schedule can discount its rates by what a user chain has
+//! paid for shares in that market over the trailing 30 days. Each fill
+//! counts towards the tier of the orders after it. Rates come in taker and
+//! maker flavours; all fills take liquidity from the AMM today, so only the
+//! taker rate is charged until the order book lands.
+
+use crate::transport::Transport;
+use crate::{Clock, FeeSchedule, OddsStreamSdk, ReadOnlyClient, SdkError, SystemClock};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+
+/// One step of a market's fee schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTier {
+    /// Trailing 30-day volume from which the tier applies
+    pub min_volume: Amount,
+    pub taker_fee_bps: u32,
+    pub maker_fee_bps: u32,
+}
+
+/// Where a user chain stands in a market's fee schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFeeTier {
+    pub trailing_volume: Amount,
+    /// Index into `FeeSchedule::tiers`; `None` at the base rates
+    pub tier: Option<u32>,
+    pub taker_fee_bps: u32,
+    pub maker_fee_bps: u32,
+    /// Volume at which the next tier starts; `None` at the top
+    pub next_tier_volume: Option<Amount>,
+}
+
+impl UserFeeTier {
+    /// Volume still to trade before the next tier applies
+    pub fn volume_to_next_tier(&self) -> Option<Amount> {
+        self.next_tier_volume.map(|next| next.saturating_sub(self.trailing_volume))
+    }
+}
+
+impl FeeSchedule {
+    /// Taker and maker rates for a user chain with trailing `volume`, as the market charges them
+    pub fn rates(&self, volume: Amount) -> (u32, u32) {
+        match self.tiers.iter().rev().find(|tier| volume >= tier.min_volume) {
+            Some(tier) => (tier.taker_fee_bps, tier.maker_fee_bps),
+            None => (self.taker_fee_bps, self.maker_fee_bps),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FeeTierData {
+    #[serde(rename = "feeTier")]
+    fee_tier: Option<UserFeeTier>,
+}
+
+impl Transport {
+    pub(crate) async fn fee_tier(&self, market_id: &str, user_chain_id: ChainId, now: u64) -> Result<UserFeeTier, SdkError> {
+        let query = r#"
+            query FeeTier($marketId: String!, $owner: String!, $now: Int!) {
+                feeTier(marketId: $marketId, owner: $owner, now: $now) {
+                    trailingVolume
+                    tier
+                    takerFeeBps
+                    makerFeeBps
+                    nextTierVolume
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({
+            "marketId": market_id,
+            "owner": user_chain_id.to_string(),
+            "now": now,
+        });
+        let data: FeeTierData = self.graphql_query_fresh(query, variables).await?;
+        data.fee_tier
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+}
+
+impl OddsStreamSdk {
+    /// This SDK's chain's current tier and effective rates in `market_id`
+    pub async fn fee_tier(&self, market_id: &str) -> Result<UserFeeTier, SdkError> {
+        self.transport.fee_tier(market_id, self.chain_id, self.clock.now_micros()).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// `user_chain_id`'s current tier and effective rates in `market_id`
+    pub async fn fee_tier(&self, market_id: &str, user_chain_id: ChainId) -> Result<UserFeeTier, SdkError> {
+        self.transport.fee_tier(market_id, user_chain_id, SystemClock.now_micros()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_follow_the_highest_tier_reached() {
+        let tier = |min_volume, taker_fee_bps| FeeTier {
+            min_volume: Amount::from_tokens(min_volume),
+            taker_fee_bps,
+            maker_fee_bps: 0,
+        };
+        let schedule = FeeSchedule {
+            taker_fee_bps: 30,
+            referral_share_bps: 0,
+            oracle_share_bps: 0,
+            protocol_share_bps: 0,
+            lp_cooldown_secs: 0,
+            early_exit_fee_bps: 0,
+            maker_fee_bps: 10,
+            tiers: vec![tier(1_000, 20), tier(10_000, 10)],
+        };
+        assert_eq!(schedule.rates(Amount::from_tokens(999)), (30, 10));
+        assert_eq!(schedule.rates(Amount::from_tokens(1_000)), (20, 0));
+        assert_eq!(schedule.rates(Amount::from_tokens(50_000)), (10, 0));
+    }
+}
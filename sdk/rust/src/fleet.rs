@@ -0,0 +1,301 @@
+//! Many agents in one process: they share the SDK's connection pool and rate
+//! limit, fail independently, and are started, stopped and reconfigured
+//! while the fleet runs
+
+use crate::{AIAgent, AgentConfig, AgentEvent, AgentHandle, OddsStreamSdk, SdkError, Shutdown, TradingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Fleet events buffered per subscriber before the slowest one starts missing them
+const FLEET_EVENT_CAPACITY: usize = 1_024;
+
+/// Where an agent of the fleet stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AgentStatus {
+    Running,
+    /// Following its markets without deciding
+    Paused,
+    Stopped,
+    /// `run` returned an error or panicked; the rest of the fleet is unaffected
+    Failed(String),
+}
+
+/// Running totals of one agent's events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentMetrics {
+    pub name: String,
+    pub strategy: String,
+    pub status: AgentStatus,
+    pub executed: u64,
+    pub rejected: u64,
+    pub failed: u64,
+    /// Tokens committed by executed decisions
+    pub notional: f64,
+    /// Micros timestamp of the last event
+    pub last_event_at: Option<u64>,
+    /// Times the agent was started again under the same name; counters carry over
+    #[serde(default)]
+    pub restarts: u32,
+}
+
+impl AgentMetrics {
+    fn new(name: &str, strategy: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            strategy: strategy.to_string(),
+            status: AgentStatus::Running,
+            executed: 0,
+            rejected: 0,
+            failed: 0,
+            notional: 0.0,
+            last_event_at: None,
+            restarts: 0,
+        }
+    }
+
+    fn record(&mut self, event: &AgentEvent, now_micros: u64) {
+        match event {
+            AgentEvent::Executed { decision, .. } => {
+                self.executed += 1;
+                self.notional += decision.notional();
+            }
+            AgentEvent::Rejected { .. } => self.rejected += 1,
+            AgentEvent::Failed { .. } => self.failed += 1,
+            AgentEvent::ConfigReloaded { .. } => {}
+            AgentEvent::StrategySwapped { strategy } => self.strategy = strategy.clone(),
+            AgentEvent::Paused => self.status = AgentStatus::Paused,
+            AgentEvent::Resumed => self.status = AgentStatus::Running,
+            AgentEvent::Stopped => self.status = AgentStatus::Stopped,
+            AgentEvent::Crashed { error } => self.status = AgentStatus::Failed(error.clone()),
+        }
+        self.last_event_at = Some(now_micros);
+    }
+}
+
+/// Totals across the fleet
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetMetrics {
+    pub agents: usize,
+    pub running: usize,
+    pub paused: usize,
+    pub failed_agents: usize,
+    pub executed: u64,
+    pub rejected: u64,
+    pub failed: u64,
+    pub notional: f64,
+}
+
+impl FleetMetrics {
+    pub fn from_agents<'a>(agents: impl IntoIterator<Item = &'a AgentMetrics>) -> Self {
+        agents.into_iter().fold(Self::default(), |mut totals, agent| {
+            totals.agents += 1;
+            match agent.status {
+                AgentStatus::Running => totals.running += 1,
+                AgentStatus::Paused => totals.paused += 1,
+                AgentStatus::Failed(_) => totals.failed_agents += 1,
+                AgentStatus::Stopped => {}
+            }
+            totals.executed += agent.executed;
+            totals.rejected += agent.rejected;
+            totals.failed += agent.failed;
+            totals.notional += agent.notional;
+            totals
+        })
+    }
+}
+
+/// An agent's event, tagged with the agent it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetEvent {
+    pub agent: String,
+    pub event: AgentEvent,
+}
+
+struct Member {
+    handle: AgentHandle,
+    task: JoinHandle<()>,
+}
+
+/// Runs and controls a set of `AIAgent`s.
+///
+/// Every agent gets a clone of the fleet's SDK, so they share its HTTP pool,
+/// response cache and any rate limit set with `OddsStreamSdk::with_rate_limit`.
+pub struct AgentFleet {
+    sdk: OddsStreamSdk,
+    shutdown: Shutdown,
+    members: Mutex<HashMap<String, Member>>,
+    metrics: Arc<Mutex<HashMap<String, AgentMetrics>>>,
+    events: broadcast::Sender<FleetEvent>,
+}
+
+impl AgentFleet {
+    /// Agents stop when `shutdown` triggers
+    pub fn new(sdk: OddsStreamSdk, shutdown: Shutdown) -> Self {
+        Self {
+            sdk,
+            shutdown,
+            members: Mutex::new(HashMap::new()),
+            metrics: Arc::default(),
+            events: broadcast::channel(FLEET_EVENT_CAPACITY).0,
+        }
+    }
+
+    /// Start an agent; its config's name must not belong to a running agent
+    pub fn start(&self, strategy: Box<dyn TradingStrategy>, config: AgentConfig) -> Result<(), SdkError> {
+        config.validate()?;
+        let mut members = self.members.lock().unwrap();
+        let name = config.name.clone();
+        if members.get(&name).is_some_and(|member| !member.task.is_finished()) {
+            return Err(SdkError::InvalidInput(format!("agent {} is already running", name)));
+        }
+
+        let agent = AIAgent::new(strategy, config, *self.sdk.chain_id());
+        let handle = agent.handle();
+        let mut agent_events = agent.events();
+        match self.metrics.lock().unwrap().entry(name.clone()) {
+            Entry::Occupied(mut entry) => {
+                let metrics = entry.get_mut();
+                metrics.strategy = agent.strategy_name().to_string();
+                metrics.status = AgentStatus::Running;
+                metrics.restarts += 1;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(AgentMetrics::new(&name, agent.strategy_name()));
+            }
+        }
+
+        let run = tokio::spawn(agent.run(self.sdk.clone(), self.shutdown.clone()));
+        let metrics = self.metrics.clone();
+        let events = self.events.clone();
+        let clock = self.sdk.clock.clone();
+        let agent_name = name.clone();
+        let task = tokio::spawn(async move {
+            // Events end when the agent is dropped, whichever way `run` finished
+            loop {
+                match agent_events.recv().await {
+                    Ok(event) => {
+                        if let Some(metrics) = metrics.lock().unwrap().get_mut(&agent_name) {
+                            metrics.record(&event, clock.now_micros());
+                        }
+                        let _ = events.send(FleetEvent { agent: agent_name.clone(), event });
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(agent = %agent_name, missed, "fleet fell behind agent events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            let error = match run.await {
+                Ok(Ok(())) => {
+                    if let Some(metrics) = metrics.lock().unwrap().get_mut(&agent_name) {
+                        metrics.status = AgentStatus::Stopped;
+                    }
+                    return;
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => format!("agent task ended abnormally: {}", e),
+            };
+            tracing::error!(agent = %agent_name, %error, "agent failed");
+            let event = AgentEvent::Crashed { error };
+            if let Some(metrics) = metrics.lock().unwrap().get_mut(&agent_name) {
+                metrics.record(&event, clock.now_micros());
+            }
+            let _ = events.send(FleetEvent { agent: agent_name, event });
+        });
+
+        members.insert(name, Member { handle, task });
+        Ok(())
+    }
+
+    fn handle(&self, name: &str) -> Result<AgentHandle, SdkError> {
+        self.members
+            .lock()
+            .unwrap()
+            .get(name)
+            .map(|member| member.handle.clone())
+            .ok_or_else(|| SdkError::InvalidInput(format!("no agent named {}", name)))
+    }
+
+    pub fn stop(&self, name: &str) -> Result<(), SdkError> {
+        self.handle(name)?.stop()
+    }
+
+    /// Hand the running agent `name` a new strategy; see `AgentHandle::swap_strategy`
+    pub fn swap_strategy(&self, name: &str, strategy: Box<dyn TradingStrategy>) -> Result<(), SdkError> {
+        self.handle(name)?.swap_strategy(strategy)
+    }
+
+    pub fn pause(&self, name: &str) -> Result<(), SdkError> {
+        self.handle(name)?.pause()
+    }
+
+    pub fn resume(&self, name: &str) -> Result<(), SdkError> {
+        self.handle(name)?.resume()
+    }
+
+    /// Swap the config of the running agent named in `config`
+    pub fn reconfigure(&self, config: AgentConfig) -> Result<(), SdkError> {
+        self.handle(&config.name)?.reconfigure(config)
+    }
+
+    /// Names of every agent started, running or not
+    pub fn agents(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.members.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn agent_metrics(&self, name: &str) -> Option<AgentMetrics> {
+        self.metrics.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn metrics(&self) -> FleetMetrics {
+        FleetMetrics::from_agents(self.metrics.lock().unwrap().values())
+    }
+
+    /// Receive every agent's events from now on
+    pub fn events(&self) -> broadcast::Receiver<FleetEvent> {
+        self.events.subscribe()
+    }
+
+    /// Stop every agent and wait for them to finish
+    pub async fn stop_all(&self) {
+        let members: Vec<Member> = self.members.lock().unwrap().drain().map(|(_, member)| member).collect();
+        for member in &members {
+            // An agent that already finished has nothing to stop
+            let _ = member.handle.stop();
+        }
+        for member in members {
+            let _ = member.task.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AgentDecision;
+
+    #[test]
+    fn test_fleet_totals_keep_failed_agents_separate() {
+        let mut healthy = AgentMetrics::new("a", "lp_vault");
+        let deposit = AgentDecision::AddLiquidity { market_id: "m1".to_string(), amount: 25.0 };
+        healthy.record(&AgentEvent::Executed { decision: deposit.clone(), transaction_id: "t1".to_string() }, 1);
+        healthy.record(&AgentEvent::Rejected { decision: deposit, reason: "limit".to_string() }, 2);
+        let mut broken = AgentMetrics::new("b", "value_betting");
+        broken.status = AgentStatus::Failed("subscription refused".to_string());
+
+        let totals = FleetMetrics::from_agents([&healthy, &broken]);
+        assert_eq!((totals.agents, totals.running, totals.failed_agents), (2, 1, 1));
+        assert_eq!((totals.executed, totals.rejected), (1, 1));
+        assert_eq!(totals.notional, 25.0);
+    }
+}
@@ -0,0 +1,179 @@
+//! Moving funds onto and off a user chain: deposit instructions, inbound
+//! transfer tracking and withdrawals, in the native token or a fungible
+//! settlement token
+
+use crate::{OddsStreamSdk, SdkError, SettlementToken};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Default interval between balance polls while watching for deposits
+pub const DEPOSIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Where and how to send funds so they arrive on a user chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositInstructions {
+    /// The chain to credit; on Linera the chain ID is the deposit address
+    pub chain_id: ChainId,
+    /// Token application to send, or `None` for the native token
+    pub token: Option<String>,
+    /// Node the user chain is served from
+    pub rpc_url: String,
+    /// Steps to show an exchange or wallet user
+    pub instructions: String,
+}
+
+/// Funds that arrived on the watched chain between two polls
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboundTransfer {
+    pub amount: Amount,
+    /// Balance after the transfer landed
+    pub balance: Amount,
+    /// Micros timestamp of the poll that saw it
+    pub observed_at: u64,
+}
+
+#[derive(Deserialize)]
+struct TransferData {
+    transfer: String,
+}
+
+/// Reports balance increases on a chain as inbound transfers.
+///
+/// Linera credits arrive as inbox messages rather than addressable
+/// transactions, so deposits are detected by polling the balance. Transfers
+/// landing between two polls are reported together, and a withdrawal in the
+/// same window hides that much of the deposit.
+pub struct DepositWatcher {
+    sdk: OddsStreamSdk,
+    chain_id: ChainId,
+    token: SettlementToken,
+    interval: Duration,
+    last_balance: Amount,
+}
+
+impl DepositWatcher {
+    pub fn last_balance(&self) -> Amount {
+        self.last_balance
+    }
+
+    /// Wait for the next balance increase
+    pub async fn next(&mut self) -> Result<InboundTransfer, SdkError> {
+        loop {
+            self.sdk.clock.sleep(self.interval).await;
+            let balance = self.sdk.token_balance(self.chain_id, self.token).await?;
+            let previous = std::mem::replace(&mut self.last_balance, balance);
+            if balance > previous {
+                let amount = balance.saturating_sub(previous);
+                tracing::info!(chain_id = %self.chain_id, %amount, "inbound transfer");
+                return Ok(InboundTransfer {
+                    amount,
+                    balance,
+                    observed_at: self.sdk.clock.now_micros(),
+                });
+            }
+        }
+    }
+}
+
+impl OddsStreamSdk {
+    /// How to fund this SDK's chain in `token`
+    pub fn deposit_instructions(&self, token: SettlementToken) -> DepositInstructions {
+        let instructions = match token {
+            SettlementToken::Native => format!(
+                "Transfer native tokens to chain {} (account owner: the chain itself).",
+                self.chain_id
+            ),
+            SettlementToken::Fungible(app_id) => format!(
+                "Call `transfer` on token application {} with target account chain {}.",
+                app_id, self.chain_id
+            ),
+        };
+        DepositInstructions {
+            chain_id: self.chain_id,
+            token: token.application_id().map(|app_id| app_id.to_string()),
+            rpc_url: self.transport.rpc_url.clone(),
+            instructions,
+        }
+    }
+
+    /// Start watching this SDK's chain for inbound transfers of `token`,
+    /// counting from the current balance
+    pub async fn watch_deposits(
+        &self,
+        token: SettlementToken,
+        interval: Duration,
+    ) -> Result<DepositWatcher, SdkError> {
+        let last_balance = self.token_balance(self.chain_id, token).await?;
+        Ok(DepositWatcher {
+            sdk: self.clone(),
+            chain_id: self.chain_id,
+            token,
+            interval,
+            last_balance,
+        })
+    }
+
+    /// Send `amount` of `token` from this SDK's chain to `destination`.
+    /// Returns the hash of the block that executed the transfer.
+    pub async fn withdraw(
+        &self,
+        token: SettlementToken,
+        destination: ChainId,
+        amount: Amount,
+    ) -> Result<String, SdkError> {
+        if amount == Amount::ZERO {
+            return Err(SdkError::InvalidInput("withdrawal amount must be positive".to_string()));
+        }
+        let available = self.token_balance(self.chain_id, token).await?;
+        if amount > available {
+            return Err(SdkError::InvalidInput(format!(
+                "withdrawal of {} exceeds balance {}",
+                amount, available
+            )));
+        }
+
+        tracing::info!(chain_id = %self.chain_id, %destination, %amount, "withdrawing");
+        match token {
+            SettlementToken::Native => {
+                let mutation = r#"
+                    mutation Transfer($chainId: String!, $recipient: String!, $amount: Amount!) {
+                        transfer(chainId: $chainId, recipient: $recipient, amount: $amount)
+                    }
+                "#;
+                let data: TransferData = self
+                    .transport
+                    .graphql_query_fresh(
+                        mutation,
+                        serde_json::json!({
+                            "chainId": self.chain_id.to_string(),
+                            "recipient": destination.to_string(),
+                            "amount": amount.to_string(),
+                        }),
+                    )
+                    .await?;
+                Ok(data.transfer)
+            }
+            SettlementToken::Fungible(app_id) => {
+                let mutation = r#"
+                    mutation Transfer($owner: String!, $amount: Amount!, $targetAccount: Account!) {
+                        transfer(owner: $owner, amount: $amount, targetAccount: $targetAccount)
+                    }
+                "#;
+                self.execute_operation(
+                    self.chain_id,
+                    app_id,
+                    mutation,
+                    serde_json::json!({
+                        "owner": self.chain_id.to_string(),
+                        "amount": amount.to_string(),
+                        "targetAccount": { "chainId": destination.to_string(), "owner": destination.to_string() },
+                    }),
+                )
+                .await
+            }
+        }
+    }
+}
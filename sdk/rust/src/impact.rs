@@ -0,0 +1,223 @@
+//! Market impact of large orders, and how to split them
+//!
+//! An order fills at the price its side is quoted when it lands and then
+//! moves the pools, so every order after it in the market fills at a
+//! different price. Split into children spread over time, each child fills
+//! where the one before left the pools, less whatever other trading has
+//! moved them back in between. [`SplitParams::reversion`] is that
+//! assumption, and [`split_schedule`] picks the number of equal children
+//! that minimizes the total cost under it.
+
+use crate::{AgentContext, AmmPools, ExecutionStrategy, MarketOrder, OddsStreamSdk, OrderSide, SdkError};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+/// Assumptions behind a split schedule
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SplitParams {
+    /// Share of a child order's price move other trading undoes before the next child, 0 to 1
+    pub reversion: f64,
+    /// Time between child orders
+    pub interval_secs: u64,
+    pub max_children: u32,
+    /// One more child order must save at least this much of the cost, in basis points
+    pub min_saving_bps: f64,
+}
+
+impl Default for SplitParams {
+    fn default() -> Self {
+        Self { reversion: 0.5, interval_secs: 60, max_children: 20, min_saving_bps: 5.0 }
+    }
+}
+
+/// One slice of a split order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildOrder {
+    /// Seconds after the first child to send this one
+    pub offset_secs: u64,
+    pub shares: f64,
+    /// Price the child is expected to fill at, given the reversion assumed
+    pub expected_price: f64,
+    /// Fees included
+    pub expected_cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitSchedule {
+    pub children: Vec<ChildOrder>,
+    pub total_cost: f64,
+    /// What the schedule saves over sending the order whole
+    pub saving: f64,
+}
+
+/// What sending an order whole would do to the market, and a cheaper way to send it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactEstimate {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub shares: f64,
+    pub price_before: f64,
+    pub price_after: f64,
+    /// `price_after - price_before`
+    pub price_impact: f64,
+    /// Cost of sending the order whole, fees included
+    pub total_cost: f64,
+    pub schedule: SplitSchedule,
+}
+
+impl ImpactEstimate {
+    pub fn compute(
+        market_id: &str,
+        pools: AmmPools,
+        taker_fee_bps: u32,
+        side: OrderSide,
+        shares: f64,
+        params: &SplitParams,
+    ) -> Self {
+        let mut after = pools;
+        let price_before = pools.odds(side);
+        let amm_cost = after.buy(side, shares);
+        let price_after = after.odds(side);
+        Self {
+            market_id: market_id.to_string(),
+            side,
+            shares,
+            price_before,
+            price_after,
+            price_impact: price_after - price_before,
+            total_cost: with_fee(amm_cost, taker_fee_bps),
+            schedule: split_schedule(pools, taker_fee_bps, side, shares, params),
+        }
+    }
+}
+
+fn with_fee(cost: f64, taker_fee_bps: u32) -> f64 {
+    cost + cost * f64::from(taker_fee_bps) / BPS_DENOMINATOR
+}
+
+/// Fill `count` equal children, letting the price revert between them
+fn children(pools: AmmPools, taker_fee_bps: u32, side: OrderSide, shares: f64, count: u32, params: &SplitParams) -> Vec<ChildOrder> {
+    let start_price = pools.odds(side);
+    let child_shares = shares / f64::from(count);
+    let mut pools = pools;
+    (0..count)
+        .map(|index| {
+            let expected_price = pools.odds(side);
+            let expected_cost = with_fee(pools.buy(side, child_shares), taker_fee_bps);
+            // Flow on the other side undoes part of the move before the next child
+            let moved = pools.odds(side);
+            let target = moved + params.reversion.clamp(0.0, 1.0) * (start_price - moved);
+            pools = reverted(pools, side, target);
+            ChildOrder {
+                offset_secs: u64::from(index) * params.interval_secs,
+                shares: child_shares,
+                expected_price,
+                expected_cost,
+            }
+        })
+        .collect()
+}
+
+/// `pools` with the opposite pool grown until `side` is priced at `target`
+fn reverted(pools: AmmPools, side: OrderSide, target: f64) -> AmmPools {
+    if !(0.0..1.0).contains(&target) || pools.odds(side) >= target {
+        return pools;
+    }
+    match side {
+        OrderSide::Yes => AmmPools::new(pools.yes, pools.yes * target / (1.0 - target)),
+        OrderSide::No => AmmPools::new(pools.no * target / (1.0 - target), pools.no),
+    }
+}
+
+/// Fewest equal children such that one more would save less than `min_saving_bps`
+pub fn split_schedule(pools: AmmPools, taker_fee_bps: u32, side: OrderSide, shares: f64, params: &SplitParams) -> SplitSchedule {
+    let cost = |children: &[ChildOrder]| children.iter().map(|child| child.expected_cost).sum::<f64>();
+    let whole = children(pools, taker_fee_bps, side, shares, 1, params);
+    let whole_cost = cost(&whole);
+    let mut best = whole;
+    let mut best_cost = whole_cost;
+    for count in 2..=params.max_children.max(1) {
+        let candidate = children(pools, taker_fee_bps, side, shares, count, params);
+        let candidate_cost = cost(&candidate);
+        if best_cost - candidate_cost < best_cost * params.min_saving_bps / BPS_DENOMINATOR {
+            break;
+        }
+        best = candidate;
+        best_cost = candidate_cost;
+    }
+    SplitSchedule { children: best, total_cost: best_cost, saving: whole_cost - best_cost }
+}
+
+/// Agent execution that splits orders by [`split_schedule`], pricing them
+/// from the pool depths in the agent's context. Orders into markets whose
+/// depths are unknown go out whole; fees don't change the split, so none are assumed.
+#[derive(Debug, Clone, Default)]
+pub struct ImpactSplitter {
+    pub params: SplitParams,
+}
+
+impl ExecutionStrategy for ImpactSplitter {
+    fn name(&self) -> &str {
+        "impact-splitter"
+    }
+
+    fn slice(&mut self, order: &MarketOrder, context: &AgentContext) -> Vec<(Duration, MarketOrder)> {
+        let whole = vec![(Duration::ZERO, order.clone())];
+        let pools = context
+            .markets
+            .get(&order.market_id)
+            .and_then(|market| Some(AmmPools::new(market.yes_depth?, market.no_depth?)));
+        let (Some(pools), Ok(shares)) = (pools, order.amount.parse::<f64>()) else {
+            return whole;
+        };
+        split_schedule(pools, 0, order.side, shares, &self.params)
+            .children
+            .into_iter()
+            .map(|child| {
+                let order = MarketOrder { amount: format!("{:.9}", child.shares), ..order.clone() };
+                (Duration::from_secs(child.offset_secs), order)
+            })
+            .collect()
+    }
+}
+
+impl OddsStreamSdk {
+    /// Price impact of buying `amount` shares of `side` in one go, and a split
+    /// schedule under the default [`SplitParams`]
+    pub async fn estimate_impact(&self, market_id: &str, side: OrderSide, amount: f64) -> Result<ImpactEstimate, SdkError> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(SdkError::InvalidInput(format!("invalid amount {}", amount)));
+        }
+        let state = self.simulation_state(market_id, self.chain_id).await?;
+        Ok(ImpactEstimate::compute(market_id, state.pools, state.taker_fee_bps, side, amount, &SplitParams::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_depends_on_reversion() {
+        let pools = AmmPools::new(500.0, 500.0);
+        let estimate = ImpactEstimate::compute("m1", pools, 100, OrderSide::Yes, 400.0, &SplitParams::default());
+        assert!(estimate.price_impact < 0.0);
+        assert!((estimate.total_cost - 202.0).abs() < 1e-9);
+        assert!(estimate.schedule.children.len() > 1);
+        assert!(estimate.schedule.saving > 0.0);
+        let shares: f64 = estimate.schedule.children.iter().map(|child| child.shares).sum();
+        assert!((shares - 400.0).abs() < 1e-9);
+
+        // Once the price always comes back, every child pays what the whole order would
+        let full_reversion = SplitParams { reversion: 1.0, ..SplitParams::default() };
+        let schedule = split_schedule(pools, 100, OrderSide::Yes, 400.0, &full_reversion);
+        assert_eq!(schedule.children.len(), 1);
+        assert!(schedule.saving.abs() < 1e-9);
+    }
+}
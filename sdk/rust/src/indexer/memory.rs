@@ -0,0 +1,108 @@
+//! In-process store for bots and tests; nothing survives a restart
+
+use super::IndexStore;
+use crate::{MarketUpdate, SdkError};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+#[derive(Default)]
+pub struct MemoryStore {
+    // Market ID -> timestamp -> update
+    updates: RwLock<BTreeMap<String, BTreeMap<u64, MarketUpdate>>>,
+    checkpoints: RwLock<BTreeMap<String, u64>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IndexStore for MemoryStore {
+    async fn insert_updates(&self, updates: &[MarketUpdate]) -> Result<(), SdkError> {
+        let mut stored = self.updates.write().unwrap();
+        for update in updates {
+            stored
+                .entry(update.market_id.clone())
+                .or_default()
+                .entry(update.timestamp)
+                .or_insert_with(|| update.clone());
+        }
+        Ok(())
+    }
+
+    async fn updates(
+        &self,
+        market_id: &str,
+        from_ms: u64,
+        to_ms: u64,
+        limit: usize,
+    ) -> Result<Vec<MarketUpdate>, SdkError> {
+        let stored = self.updates.read().unwrap();
+        Ok(stored
+            .get(market_id)
+            .map(|history| history.range(from_ms..to_ms).take(limit).map(|(_, u)| u.clone()).collect())
+            .unwrap_or_default())
+    }
+
+    async fn latest(&self, market_id: &str) -> Result<Option<MarketUpdate>, SdkError> {
+        let stored = self.updates.read().unwrap();
+        Ok(stored
+            .get(market_id)
+            .and_then(|history| history.values().next_back().cloned()))
+    }
+
+    async fn market_ids(&self) -> Result<Vec<String>, SdkError> {
+        Ok(self.updates.read().unwrap().keys().cloned().collect())
+    }
+
+    async fn checkpoint(&self, market_id: &str) -> Result<Option<u64>, SdkError> {
+        Ok(self.checkpoints.read().unwrap().get(market_id).copied())
+    }
+
+    async fn save_checkpoint(&self, market_id: &str, sequence: u64) -> Result<(), SdkError> {
+        self.checkpoints.write().unwrap().insert(market_id.to_string(), sequence);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(market_id: &str, timestamp: u64, yes_odds: f64) -> MarketUpdate {
+        MarketUpdate {
+            market_id: market_id.to_string(),
+            yes_odds,
+            no_odds: 1.0 - yes_odds,
+            volume: 0.0,
+            status: "OPEN".to_string(),
+            timestamp,
+            sequence: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_range_and_dedup() {
+        let store = MemoryStore::new();
+        store
+            .insert_updates(&[update("m1", 10, 0.4), update("m1", 20, 0.5), update("m2", 15, 0.9)])
+            .await
+            .unwrap();
+        // Replaying a key keeps the first write
+        store.insert_updates(&[update("m1", 20, 0.7)]).await.unwrap();
+
+        let history = store.price_history("m1", 0, 100, 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].yes_odds, 0.5);
+        assert_eq!(store.updates("m1", 11, 100, 10).await.unwrap().len(), 1);
+        assert_eq!(store.latest("m2").await.unwrap().unwrap().timestamp, 15);
+        assert_eq!(store.market_ids().await.unwrap(), vec!["m1", "m2"]);
+
+        assert_eq!(store.checkpoint("m1").await.unwrap(), None);
+        store.save_checkpoint("m1", 42).await.unwrap();
+        assert_eq!(store.checkpoint("m1").await.unwrap(), Some(42));
+    }
+}
@@ -0,0 +1,226 @@
+//! Local index of market history, fed by subscriptions and kept in a pluggable store
+
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use memory::MemoryStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+use crate::{MarketUpdate, PricePoint, ReadOnlyClient, SdkError, Shutdown};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Updates written to the store per round trip
+const WRITE_BATCH: usize = 64;
+
+/// Updates fetched per page while catching up from a checkpoint
+const SYNC_PAGE: usize = 500;
+
+/// Persistence for indexed market updates.
+///
+/// Updates are keyed by `(market_id, timestamp)`; inserting a key that is
+/// already present is a no-op, so replays are harmless.
+#[async_trait]
+pub trait IndexStore: Send + Sync {
+    async fn insert_updates(&self, updates: &[MarketUpdate]) -> Result<(), SdkError>;
+
+    /// Updates with `from_ms <= timestamp < to_ms`, oldest first, at most `limit`
+    async fn updates(
+        &self,
+        market_id: &str,
+        from_ms: u64,
+        to_ms: u64,
+        limit: usize,
+    ) -> Result<Vec<MarketUpdate>, SdkError>;
+
+    async fn latest(&self, market_id: &str) -> Result<Option<MarketUpdate>, SdkError>;
+
+    async fn market_ids(&self) -> Result<Vec<String>, SdkError>;
+
+    /// Highest sequence number up to which the market's history is stored without gaps
+    async fn checkpoint(&self, market_id: &str) -> Result<Option<u64>, SdkError>;
+
+    async fn save_checkpoint(&self, market_id: &str, sequence: u64) -> Result<(), SdkError>;
+
+    async fn price_history(
+        &self,
+        market_id: &str,
+        from_ms: u64,
+        to_ms: u64,
+        limit: usize,
+    ) -> Result<Vec<PricePoint>, SdkError> {
+        let updates = self.updates(market_id, from_ms, to_ms, limit).await?;
+        Ok(updates
+            .into_iter()
+            .map(|update| PricePoint { timestamp: update.timestamp, yes_odds: update.yes_odds })
+            .collect())
+    }
+}
+
+/// Streams market updates into an `IndexStore`
+pub struct Indexer {
+    client: ReadOnlyClient,
+    store: Arc<dyn IndexStore>,
+}
+
+impl Indexer {
+    pub fn new(client: ReadOnlyClient, store: Arc<dyn IndexStore>) -> Self {
+        Self { client, store }
+    }
+
+    pub fn store(&self) -> &Arc<dyn IndexStore> {
+        &self.store
+    }
+
+    /// Index `market_ids` until the subscription ends or a write fails.
+    ///
+    /// Each market resumes from its stored checkpoint, so a restart only
+    /// fetches what happened while the indexer was down. A live update whose
+    /// sequence skips ahead means subscription messages were lost; the hole
+    /// is refetched before the update is stored.
+    ///
+    /// If the client carries a `Shutdown` token, triggering it ends the
+    /// subscription; updates already received are flushed before returning.
+    pub async fn run(&self, market_ids: Vec<String>) -> Result<(), SdkError> {
+        let shutdown = self.client.transport.shutdown.clone();
+        let _guard = shutdown.as_ref().map(Shutdown::guard);
+
+        // Subscribe before catching up so nothing slips between the two
+        let mut batches = self
+            .client
+            .subscribe_market_updates_stream(market_ids.clone())
+            .ready_chunks(WRITE_BATCH);
+
+        let mut cursors = HashMap::new();
+        for market_id in &market_ids {
+            if shutdown.as_ref().is_some_and(Shutdown::is_triggered) {
+                return Ok(());
+            }
+            self.catch_up(market_id, &mut cursors).await?;
+        }
+
+        let mut pending = Vec::new();
+        let mut touched = BTreeSet::new();
+        while let Some(batch) = batches.next().await {
+            for update in batch {
+                // Unsequenced updates cannot be checked for gaps; store them as they come
+                if update.sequence == 0 {
+                    pending.push(update);
+                    continue;
+                }
+
+                let cursor = cursors.get(&update.market_id).copied().unwrap_or(0);
+                if update.sequence <= cursor {
+                    continue;
+                }
+                if update.sequence > cursor + 1 {
+                    tracing::warn!(
+                        market_id = %update.market_id,
+                        expected = cursor + 1,
+                        received = update.sequence,
+                        "gap in market updates, refetching"
+                    );
+                    self.flush(&mut pending, &mut touched, &cursors).await?;
+                    self.catch_up(&update.market_id, &mut cursors).await?;
+                    if update.sequence <= cursors[&update.market_id] {
+                        continue;
+                    }
+                }
+
+                cursors.insert(update.market_id.clone(), update.sequence);
+                touched.insert(update.market_id.clone());
+                pending.push(update);
+            }
+            self.flush(&mut pending, &mut touched, &cursors).await?;
+        }
+        Ok(())
+    }
+
+    /// Fetch and store everything after the market's cursor
+    async fn catch_up(&self, market_id: &str, cursors: &mut HashMap<String, u64>) -> Result<(), SdkError> {
+        let mut cursor = match cursors.get(market_id) {
+            Some(cursor) => *cursor,
+            None => self.store.checkpoint(market_id).await?.unwrap_or(0),
+        };
+
+        loop {
+            let page = self.updates_since(market_id, cursor, SYNC_PAGE).await?;
+            let last = match page.last() {
+                Some(update) if update.sequence > cursor => update.sequence,
+                _ => break,
+            };
+            self.store.insert_updates(&page).await?;
+            self.store.save_checkpoint(market_id, last).await?;
+            cursor = last;
+            if page.len() < SYNC_PAGE {
+                break;
+            }
+        }
+
+        tracing::debug!(market_id, sequence = cursor, "caught up");
+        cursors.insert(market_id.to_string(), cursor);
+        Ok(())
+    }
+
+    /// Store pending updates, then advance the checkpoints they complete
+    async fn flush(
+        &self,
+        pending: &mut Vec<MarketUpdate>,
+        touched: &mut BTreeSet<String>,
+        cursors: &HashMap<String, u64>,
+    ) -> Result<(), SdkError> {
+        if !pending.is_empty() {
+            self.store.insert_updates(pending).await?;
+            pending.clear();
+        }
+        for market_id in std::mem::take(touched) {
+            self.store.save_checkpoint(&market_id, cursors[&market_id]).await?;
+        }
+        Ok(())
+    }
+
+    async fn updates_since(
+        &self,
+        market_id: &str,
+        after_sequence: u64,
+        limit: usize,
+    ) -> Result<Vec<MarketUpdate>, SdkError> {
+        let query = r#"
+            query UpdatesSince($marketId: String!, $afterSequence: Int!, $limit: Int!) {
+                marketUpdatesSince(marketId: $marketId, afterSequence: $afterSequence, limit: $limit) {
+                    marketId
+                    yesOdds
+                    noOdds
+                    volume
+                    status
+                    timestamp
+                    sequence
+                }
+            }
+        "#;
+
+        let data: UpdatesData = self
+            .client
+            .graphql_query(
+                query,
+                serde_json::json!({ "marketId": market_id, "afterSequence": after_sequence, "limit": limit }),
+            )
+            .await?;
+        Ok(data.market_updates_since)
+    }
+}
+
+#[derive(Deserialize)]
+struct UpdatesData {
+    #[serde(rename = "marketUpdatesSince")]
+    market_updates_since: Vec<MarketUpdate>,
+}
@@ -0,0 +1,136 @@
+//! Shared store for services that index once and serve many readers
+
+use super::IndexStore;
+use crate::{MarketUpdate, SdkError};
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS market_updates (
+        market_id TEXT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        yes_odds DOUBLE PRECISION NOT NULL,
+        no_odds DOUBLE PRECISION NOT NULL,
+        volume DOUBLE PRECISION NOT NULL,
+        status TEXT NOT NULL,
+        sequence BIGINT NOT NULL DEFAULT 0,
+        PRIMARY KEY (market_id, timestamp)
+    );
+    CREATE TABLE IF NOT EXISTS index_checkpoints (
+        market_id TEXT PRIMARY KEY,
+        sequence BIGINT NOT NULL
+    )
+"#;
+
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+impl PostgresStore {
+    /// Connect to `database_url` and create the schema if it is missing
+    pub async fn connect(database_url: &str) -> Result<Self, SdkError> {
+        let pool = PgPool::connect(database_url).await.map_err(storage_error)?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await.map_err(storage_error)?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl IndexStore for PostgresStore {
+    async fn insert_updates(&self, updates: &[MarketUpdate]) -> Result<(), SdkError> {
+        let mut tx = self.pool.begin().await.map_err(storage_error)?;
+        for update in updates {
+            sqlx::query(
+                "INSERT INTO market_updates (market_id, timestamp, yes_odds, no_odds, volume, status, sequence)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING",
+            )
+            .bind(&update.market_id)
+            .bind(update.timestamp as i64)
+            .bind(update.yes_odds)
+            .bind(update.no_odds)
+            .bind(update.volume)
+            .bind(&update.status)
+            .bind(update.sequence as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(storage_error)?;
+        }
+        tx.commit().await.map_err(storage_error)
+    }
+
+    async fn updates(
+        &self,
+        market_id: &str,
+        from_ms: u64,
+        to_ms: u64,
+        limit: usize,
+    ) -> Result<Vec<MarketUpdate>, SdkError> {
+        let rows = sqlx::query(
+            "SELECT * FROM market_updates WHERE market_id = $1 AND timestamp >= $2 AND timestamp < $3
+             ORDER BY timestamp LIMIT $4",
+        )
+        .bind(market_id)
+        .bind(from_ms as i64)
+        .bind(to_ms.min(i64::MAX as u64) as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(storage_error)?;
+        rows.iter().map(decode_update).collect()
+    }
+
+    async fn latest(&self, market_id: &str) -> Result<Option<MarketUpdate>, SdkError> {
+        let row = sqlx::query("SELECT * FROM market_updates WHERE market_id = $1 ORDER BY timestamp DESC LIMIT 1")
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        row.as_ref().map(decode_update).transpose()
+    }
+
+    async fn market_ids(&self) -> Result<Vec<String>, SdkError> {
+        sqlx::query_scalar("SELECT DISTINCT market_id FROM market_updates ORDER BY market_id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(storage_error)
+    }
+
+    async fn checkpoint(&self, market_id: &str) -> Result<Option<u64>, SdkError> {
+        let sequence: Option<i64> = sqlx::query_scalar("SELECT sequence FROM index_checkpoints WHERE market_id = $1")
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        Ok(sequence.map(|sequence| sequence as u64))
+    }
+
+    async fn save_checkpoint(&self, market_id: &str, sequence: u64) -> Result<(), SdkError> {
+        sqlx::query(
+            "INSERT INTO index_checkpoints (market_id, sequence) VALUES ($1, $2)
+             ON CONFLICT (market_id) DO UPDATE SET sequence = excluded.sequence",
+        )
+        .bind(market_id)
+        .bind(sequence as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+fn decode_update(row: &PgRow) -> Result<MarketUpdate, SdkError> {
+    Ok(MarketUpdate {
+        market_id: row.try_get("market_id").map_err(storage_error)?,
+        timestamp: row.try_get::<i64, _>("timestamp").map_err(storage_error)? as u64,
+        yes_odds: row.try_get("yes_odds").map_err(storage_error)?,
+        no_odds: row.try_get("no_odds").map_err(storage_error)?,
+        volume: row.try_get("volume").map_err(storage_error)?,
+        status: row.try_get("status").map_err(storage_error)?,
+        sequence: row.try_get::<i64, _>("sequence").map_err(storage_error)? as u64,
+    })
+}
+
+fn storage_error(e: sqlx::Error) -> SdkError {
+    SdkError::StorageError(e.to_string())
+}
@@ -0,0 +1,140 @@
+//! Single-file store for bots that want history to survive restarts
+
+use super::IndexStore;
+use crate::{MarketUpdate, SdkError};
+use async_trait::async_trait;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use sqlx::Row;
+use std::str::FromStr;
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS market_updates (
+        market_id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        yes_odds REAL NOT NULL,
+        no_odds REAL NOT NULL,
+        volume REAL NOT NULL,
+        status TEXT NOT NULL,
+        sequence BIGINT NOT NULL DEFAULT 0,
+        PRIMARY KEY (market_id, timestamp)
+    );
+    CREATE TABLE IF NOT EXISTS index_checkpoints (
+        market_id TEXT PRIMARY KEY,
+        sequence BIGINT NOT NULL
+    )
+"#;
+
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the database at `path`
+    pub async fn open(path: &str) -> Result<Self, SdkError> {
+        let options = SqliteConnectOptions::from_str(path)
+            .map_err(storage_error)?
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await.map_err(storage_error)?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await.map_err(storage_error)?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl IndexStore for SqliteStore {
+    async fn insert_updates(&self, updates: &[MarketUpdate]) -> Result<(), SdkError> {
+        let mut tx = self.pool.begin().await.map_err(storage_error)?;
+        for update in updates {
+            sqlx::query(
+                "INSERT INTO market_updates (market_id, timestamp, yes_odds, no_odds, volume, status, sequence)
+                 VALUES (?, ?, ?, ?, ?, ?, ?) ON CONFLICT DO NOTHING",
+            )
+            .bind(&update.market_id)
+            .bind(update.timestamp as i64)
+            .bind(update.yes_odds)
+            .bind(update.no_odds)
+            .bind(update.volume)
+            .bind(&update.status)
+            .bind(update.sequence as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(storage_error)?;
+        }
+        tx.commit().await.map_err(storage_error)
+    }
+
+    async fn updates(
+        &self,
+        market_id: &str,
+        from_ms: u64,
+        to_ms: u64,
+        limit: usize,
+    ) -> Result<Vec<MarketUpdate>, SdkError> {
+        let rows = sqlx::query(
+            "SELECT * FROM market_updates WHERE market_id = ? AND timestamp >= ? AND timestamp < ?
+             ORDER BY timestamp LIMIT ?",
+        )
+        .bind(market_id)
+        .bind(from_ms as i64)
+        .bind(to_ms.min(i64::MAX as u64) as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(storage_error)?;
+        rows.iter().map(decode_update).collect()
+    }
+
+    async fn latest(&self, market_id: &str) -> Result<Option<MarketUpdate>, SdkError> {
+        let row = sqlx::query("SELECT * FROM market_updates WHERE market_id = ? ORDER BY timestamp DESC LIMIT 1")
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        row.as_ref().map(decode_update).transpose()
+    }
+
+    async fn market_ids(&self) -> Result<Vec<String>, SdkError> {
+        sqlx::query_scalar("SELECT DISTINCT market_id FROM market_updates ORDER BY market_id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(storage_error)
+    }
+
+    async fn checkpoint(&self, market_id: &str) -> Result<Option<u64>, SdkError> {
+        let sequence: Option<i64> = sqlx::query_scalar("SELECT sequence FROM index_checkpoints WHERE market_id = ?")
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        Ok(sequence.map(|sequence| sequence as u64))
+    }
+
+    async fn save_checkpoint(&self, market_id: &str, sequence: u64) -> Result<(), SdkError> {
+        sqlx::query(
+            "INSERT INTO index_checkpoints (market_id, sequence) VALUES (?, ?)
+             ON CONFLICT (market_id) DO UPDATE SET sequence = excluded.sequence",
+        )
+        .bind(market_id)
+        .bind(sequence as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+fn decode_update(row: &sqlx::sqlite::SqliteRow) -> Result<MarketUpdate, SdkError> {
+    Ok(MarketUpdate {
+        market_id: row.try_get("market_id").map_err(storage_error)?,
+        timestamp: row.try_get::<i64, _>("timestamp").map_err(storage_error)? as u64,
+        yes_odds: row.try_get("yes_odds").map_err(storage_error)?,
+        no_odds: row.try_get("no_odds").map_err(storage_error)?,
+        volume: row.try_get("volume").map_err(storage_error)?,
+        status: row.try_get("status").map_err(storage_error)?,
+        sequence: row.try_get::<i64, _>("sequence").map_err(storage_error)? as u64,
+    })
+}
+
+fn storage_error(e: sqlx::Error) -> SdkError {
+    SdkError::StorageError(e.to_string())
+}
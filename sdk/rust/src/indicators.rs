@@ -0,0 +1,297 @@
+//! Streaming technical indicators over implied probability
+//!
+//! Every indicator is updated one observation at a time in O(1) (O(period) for
+//! Bollinger bands), so strategies can feed ticks as they arrive instead of
+//! recomputing over the full price history.
+
+use std::collections::VecDeque;
+
+/// Common interface for incremental indicators
+pub trait Indicator {
+    type Output;
+
+    /// Feed the next observation, returning the value once warmed up
+    fn update(&mut self, value: f64) -> Option<Self::Output>;
+
+    /// Latest value, if enough observations have been seen
+    fn value(&self) -> Option<Self::Output>;
+
+    /// Forget all history
+    fn reset(&mut self);
+}
+
+/// Exponential moving average
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    current: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "EMA period must be positive");
+        Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            current: None,
+        }
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let next = match self.current {
+            Some(prev) => prev + self.alpha * (value - prev),
+            None => value,
+        };
+        self.current = Some(next);
+        self.current
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+/// Relative strength index using Wilder smoothing, in the range 0-100
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    seen: usize,
+    prev: Option<f64>,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "RSI period must be positive");
+        Self {
+            period,
+            seen: 0,
+            prev: None,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let Some(prev) = self.prev.replace(value) else {
+            return None;
+        };
+        let change = value - prev;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let n = self.period as f64;
+
+        self.seen += 1;
+        if self.seen <= self.period {
+            // Seed with a simple average over the first `period` changes
+            self.avg_gain += gain / n;
+            self.avg_loss += loss / n;
+        } else {
+            self.avg_gain = (self.avg_gain * (n - 1.0) + gain) / n;
+            self.avg_loss = (self.avg_loss * (n - 1.0) + loss) / n;
+        }
+        self.value()
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.seen < self.period {
+            return None;
+        }
+        if self.avg_loss == 0.0 {
+            return Some(if self.avg_gain == 0.0 { 50.0 } else { 100.0 });
+        }
+        let rs = self.avg_gain / self.avg_loss;
+        Some(100.0 - 100.0 / (1.0 + rs))
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+/// Upper, middle and lower Bollinger bands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Simple-moving-average Bollinger bands `k` standard deviations wide
+#[derive(Debug, Clone)]
+pub struct BollingerBands {
+    period: usize,
+    k: f64,
+    window: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, k: f64) -> Self {
+        assert!(period > 0, "Bollinger period must be positive");
+        Self {
+            period,
+            k,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+}
+
+impl Indicator for BollingerBands {
+    type Output = Bands;
+
+    fn update(&mut self, value: f64) -> Option<Bands> {
+        self.window.push_back(value);
+        self.sum += value;
+        self.sum_sq += value * value;
+        if self.window.len() > self.period {
+            let old = self.window.pop_front().unwrap();
+            self.sum -= old;
+            self.sum_sq -= old * old;
+        }
+        self.value()
+    }
+
+    fn value(&self) -> Option<Bands> {
+        if self.window.len() < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let mean = self.sum / n;
+        let std_dev = (self.sum_sq / n - mean * mean).max(0.0).sqrt();
+        Some(Bands {
+            upper: mean + self.k * std_dev,
+            middle: mean,
+            lower: mean - self.k * std_dev,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+    }
+}
+
+/// Volume-weighted average implied probability, cumulative or over a rolling window
+#[derive(Debug, Clone)]
+pub struct Vwap {
+    window: Option<usize>,
+    trades: VecDeque<(f64, f64)>,
+    price_volume: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    /// VWAP over every trade seen
+    pub fn cumulative() -> Self {
+        Self {
+            window: None,
+            trades: VecDeque::new(),
+            price_volume: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    /// VWAP over the last `trades` trades
+    pub fn rolling(trades: usize) -> Self {
+        assert!(trades > 0, "VWAP window must be positive");
+        Self {
+            window: Some(trades),
+            ..Self::cumulative()
+        }
+    }
+
+    /// Record a trade of `volume` at implied probability `price`
+    pub fn update_trade(&mut self, price: f64, volume: f64) -> Option<f64> {
+        self.price_volume += price * volume;
+        self.volume += volume;
+        if let Some(window) = self.window {
+            self.trades.push_back((price, volume));
+            if self.trades.len() > window {
+                let (old_price, old_volume) = self.trades.pop_front().unwrap();
+                self.price_volume -= old_price * old_volume;
+                self.volume -= old_volume;
+            }
+        }
+        self.value()
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    /// Treats each observation as a unit-volume trade
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.update_trade(value, 1.0)
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.price_volume / self.volume)
+    }
+
+    fn reset(&mut self) {
+        self.trades.clear();
+        self.price_volume = 0.0;
+        self.volume = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_converges() {
+        let mut ema = Ema::new(3);
+        assert_eq!(ema.update(0.5), Some(0.5));
+        assert_eq!(ema.update(0.7), Some(0.6));
+        for _ in 0..50 {
+            ema.update(0.8);
+        }
+        assert!((ema.value().unwrap() - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rsi_extremes() {
+        let mut rsi = Rsi::new(3);
+        assert_eq!(rsi.update(0.40), None);
+        rsi.update(0.45);
+        rsi.update(0.50);
+        assert_eq!(rsi.update(0.55), Some(100.0));
+
+        let mut rsi = Rsi::new(2);
+        for p in [0.6, 0.5, 0.4] {
+            rsi.update(p);
+        }
+        assert_eq!(rsi.value(), Some(0.0));
+    }
+
+    #[test]
+    fn test_bollinger_and_vwap() {
+        let mut bands = BollingerBands::new(2, 2.0);
+        assert_eq!(bands.update(0.4), None);
+        let b = bands.update(0.6).unwrap();
+        assert!((b.middle - 0.5).abs() < 1e-12);
+        assert!((b.upper - 0.7).abs() < 1e-12);
+
+        let mut vwap = Vwap::rolling(2);
+        vwap.update_trade(0.2, 100.0);
+        vwap.update_trade(0.4, 100.0);
+        assert!((vwap.update_trade(0.6, 300.0).unwrap() - 0.55).abs() < 1e-12);
+    }
+}
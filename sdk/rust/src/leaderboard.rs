@@ -0,0 +1,78 @@
+//! Trader rankings aggregated by the registry
+
+use crate::{OddsStreamSdk, SdkError};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// Window the leaderboard statistics are aggregated over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LeaderboardPeriod {
+    Day,
+    Week,
+    Month,
+    AllTime,
+}
+
+/// One trader's standing, ranked by realized P&L
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub user_chain_id: String,
+    pub volume: String,
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    /// Share of settled positions that paid out more than they cost (0-1)
+    pub win_rate: f64,
+    pub realized_pnl: f64,
+}
+
+#[derive(Deserialize)]
+struct LeaderboardData {
+    leaderboard: Vec<LeaderboardEntry>,
+}
+
+impl OddsStreamSdk {
+    /// Top traders over a period; chains that opted out are never listed
+    pub async fn leaderboard(&self, period: LeaderboardPeriod) -> Result<Vec<LeaderboardEntry>, SdkError> {
+        let query = r#"
+            query Leaderboard($period: LeaderboardPeriod!) {
+                leaderboard(period: $period) {
+                    rank
+                    userChainId
+                    volume
+                    trades
+                    wins
+                    losses
+                    winRate
+                    realizedPnl
+                }
+            }
+        "#;
+
+        let data: LeaderboardData = self
+            .graphql_query(query, serde_json::json!({ "period": period }))
+            .await?;
+        Ok(data.leaderboard)
+    }
+
+    /// Hide this SDK's chain from the leaderboard, or show it again
+    pub async fn set_leaderboard_privacy(
+        &self,
+        registry_chain_id: ChainId,
+        hidden: bool,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation SetLeaderboardPrivacy($registryChain: String!, $hidden: Boolean!) {
+                setLeaderboardPrivacy(registryChain: $registryChain, hidden: $hidden)
+            }
+        "#;
+        self.execute_user_operation(
+            mutation,
+            serde_json::json!({ "registryChain": registry_chain_id.to_string(), "hidden": hidden }),
+        )
+        .await
+    }
+}
@@ -0,0 +1,100 @@
+//! Ledger hardware wallet signer over USB HID
+//!
+//! Keys stay on the device; the host only sends the order digest, which the
+//! OddsStream Ledger app displays for the user to confirm before signing.
+
+use crate::{SdkError, Signer};
+use async_trait::async_trait;
+use ledger_apdu::APDUCommand;
+use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+use std::sync::Arc;
+
+const CLA: u8 = 0xE0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN_DIGEST: u8 = 0x04;
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+
+/// Hardened BIP32-style derivation path `44'/<coin>'/<account>'`
+const PURPOSE: u32 = 44 | 0x8000_0000;
+const COIN_TYPE: u32 = 1108 | 0x8000_0000;
+
+/// Called with the digest before it is sent to the device, so the host can
+/// show the same value the user is asked to confirm on-screen
+pub type ConfirmHook = Box<dyn Fn(&[u8; 32]) + Send + Sync>;
+
+pub struct LedgerSigner {
+    transport: Arc<TransportNativeHID>,
+    path: Vec<u8>,
+    public_key: Vec<u8>,
+    on_confirm: Option<ConfirmHook>,
+}
+
+impl LedgerSigner {
+    /// Connect to the first attached Ledger and read the key for `account`
+    pub fn connect(account: u32) -> Result<Self, SdkError> {
+        let api = HidApi::new().map_err(|e| SdkError::ConnectionError(format!("HID: {}", e)))?;
+        let transport = TransportNativeHID::new(&api)
+            .map_err(|e| SdkError::ConnectionError(format!("Ledger not found: {}", e)))?;
+        let transport = Arc::new(transport);
+
+        let path = [PURPOSE, COIN_TYPE, account | 0x8000_0000]
+            .iter()
+            .flat_map(|index| index.to_be_bytes())
+            .collect::<Vec<u8>>();
+
+        let public_key = exchange(&transport, INS_GET_PUBLIC_KEY, path.clone())?;
+        if public_key.len() != 32 {
+            return Err(SdkError::ConnectionError(
+                "Ledger returned a malformed public key".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            transport,
+            path,
+            public_key,
+            on_confirm: None,
+        })
+    }
+
+    pub fn with_confirm_hook(mut self, hook: ConfirmHook) -> Self {
+        self.on_confirm = Some(hook);
+        self
+    }
+}
+
+fn exchange(transport: &TransportNativeHID, ins: u8, data: Vec<u8>) -> Result<Vec<u8>, SdkError> {
+    let command = APDUCommand { cla: CLA, ins, p1: 0, p2: 0, data };
+    let answer = transport
+        .exchange(&command)
+        .map_err(|e| SdkError::ConnectionError(format!("Ledger: {}", e)))?;
+
+    match answer.retcode() {
+        SW_OK => Ok(answer.data().to_vec()),
+        SW_USER_REJECTED => Err(SdkError::InvalidInput("signature rejected on Ledger".to_string())),
+        code => Err(SdkError::ConnectionError(format!("Ledger status {:#06x}", code))),
+    }
+}
+
+#[async_trait]
+impl Signer for LedgerSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    async fn sign(&self, digest: &[u8; 32]) -> Result<Vec<u8>, SdkError> {
+        if let Some(hook) = &self.on_confirm {
+            hook(digest);
+        }
+
+        let transport = self.transport.clone();
+        let mut data = self.path.clone();
+        data.extend_from_slice(digest);
+
+        // HID calls block until the user presses a button, keep them off the runtime
+        tokio::task::spawn_blocking(move || exchange(&transport, INS_SIGN_DIGEST, data))
+            .await
+            .map_err(|e| SdkError::ConnectionError(e.to_string()))?
+    }
+}
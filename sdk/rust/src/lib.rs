@@ -5,185 +5,602 @@ mod client;
 mod types;
 mod errors;
 mod utils;
+mod snapshot;
+mod candles;
+mod indicators;
+mod portfolio;
+mod odds_format;
+mod subscription;
+mod dispatch;
+mod chain_info;
+mod signer;
+mod relayer;
+mod session;
+mod multisig;
+#[cfg(feature = "ledger")]
+mod ledger;
+mod account;
+mod referral;
+mod analytics;
+mod stats;
+mod leaderboard;
+mod evidence;
+mod transport;
+mod read_only;
+mod activity;
+mod liquidity;
+mod templates;
+mod creation;
+mod parlay;
+mod indexer;
+mod history;
+mod cache;
+mod decode;
+mod amm;
+mod shutdown;
+mod clock;
+mod rng;
+mod depth;
+mod trades;
+mod tokens;
+mod funding;
+mod quote;
+mod auction;
+mod limits;
+mod compliance;
+mod audit;
+mod proofs;
+mod light_client;
+mod chain_log;
+mod wire;
+mod schema;
+mod rejection;
+mod lifecycle;
+mod stages;
+mod agent;
+mod lp_vault;
+mod external_odds;
+mod value_betting;
+mod research;
+mod fleet;
+mod admin;
+mod config_reload;
+mod backtest;
+mod optimize;
+mod walk_forward;
+mod execution;
+mod shadow;
+mod order_file;
+mod market_details;
+mod amend;
+mod wallet_history;
+mod control;
+mod daemon;
+#[cfg(feature = "plugins")]
+mod plugin;
+mod committee;
+mod transparency;
+mod treasury;
+mod registry_events;
+mod archive;
+mod registrations;
+mod roles;
+mod oracle_keys;
+mod pause;
+mod outbox;
+mod transfers;
+mod atomic;
+mod simulate;
+mod impact;
+mod algo;
+mod fee_tiers;
 
 pub use client::*;
 pub use types::*;
 pub use errors::*;
+pub use snapshot::*;
+pub use candles::*;
+pub use indicators::*;
+pub use portfolio::*;
+pub use odds_format::*;
+pub use subscription::*;
+pub use dispatch::*;
+pub use chain_info::*;
+pub use signer::*;
+pub use relayer::*;
+pub use session::*;
+pub use multisig::*;
+#[cfg(feature = "ledger")]
+pub use ledger::*;
+pub use account::*;
+pub use referral::*;
+pub use analytics::*;
+pub use stats::*;
+pub use leaderboard::*;
+pub use evidence::*;
+pub use transport::{HttpConfig, RateLimiter};
+pub use read_only::*;
+pub use activity::*;
+pub use liquidity::*;
+pub use templates::*;
+pub use creation::*;
+pub use parlay::*;
+pub use indexer::*;
+pub use history::*;
+pub use cache::*;
+pub use decode::*;
+pub use amm::*;
+pub use shutdown::*;
+pub use clock::*;
+pub use rng::*;
+pub use depth::*;
+pub use trades::*;
+pub use tokens::*;
+pub use funding::*;
+pub use quote::*;
+pub use auction::*;
+pub use limits::*;
+pub use compliance::*;
+pub use audit::*;
+pub use proofs::*;
+pub use light_client::*;
+pub use chain_log::*;
+pub use wire::*;
+pub use schema::*;
+pub use rejection::*;
+pub use lifecycle::*;
+pub use stages::*;
+pub use agent::*;
+pub use lp_vault::*;
+pub use external_odds::*;
+pub use value_betting::*;
+pub use research::*;
+pub use fleet::*;
+pub use config_reload::*;
+pub use backtest::*;
+pub use optimize::*;
+pub use walk_forward::*;
+pub use execution::*;
+pub use shadow::*;
+pub use order_file::*;
+pub use market_details::*;
+pub use wallet_history::*;
+pub use control::*;
+pub use daemon::*;
+#[cfg(feature = "plugins")]
+pub use plugin::*;
+pub use committee::*;
+pub use transparency::*;
+pub use treasury::*;
+pub use registry_events::*;
+pub use archive::*;
+pub use registrations::*;
+pub use roles::*;
+pub use oracle_keys::*;
+pub use pause::*;
+pub use outbox::*;
+pub use transfers::*;
+pub use atomic::*;
+pub use simulate::*;
+pub use impact::*;
+pub use algo::*;
+pub use fee_tiers::*;
 
-use linera_sdk::base::ChainId;
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
 use serde::{Deserialize, Serialize};
 use async_trait::async_trait;
+use futures::future::join_all;
+use futures::Stream;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use transport::Transport;
 
-/// Main OddsStream SDK client
+/// Claims `claim_all` sends concurrently
+pub const CLAIMS_PER_BATCH: usize = 16;
+
+/// Main OddsStream SDK client.
+///
+/// Clones share one HTTP connection pool and response cache.
+#[derive(Clone)]
 pub struct OddsStreamSdk {
-    rpc_url: String,
+    transport: Transport,
     chain_id: ChainId,
-    client: reqwest::Client,
+    signer: Option<Arc<dyn Signer>>,
+    user_application_id: Option<ApplicationId>,
+    registry: Option<(ChainId, ApplicationId)>,
+    oracle: Option<(ChainId, ApplicationId)>,
+    // Market ID -> serving chain, filled by `resolve_market_chain`
+    market_chains: Arc<RwLock<HashMap<String, ChainId>>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl OddsStreamSdk {
     /// Create a new SDK instance for Conway testnet
     pub fn new(chain_id: ChainId) -> Self {
         Self {
-            rpc_url: "https://faucet.testnet-conway.linera.net".to_string(),
+            transport: Transport::new(transport::CONWAY_RPC_URL.to_string()),
             chain_id,
-            client: reqwest::Client::new(),
+            signer: None,
+            user_application_id: None,
+            registry: None,
+            oracle: None,
+            market_chains: Arc::default(),
+            clock: Arc::new(SystemClock),
         }
     }
     
     /// Create SDK with custom RPC URL
     pub fn with_rpc_url(chain_id: ChainId, rpc_url: String) -> Self {
         Self {
-            rpc_url,
+            transport: Transport::new(rpc_url),
             chain_id,
-            client: reqwest::Client::new(),
+            signer: None,
+            user_application_id: None,
+            registry: None,
+            oracle: None,
+            market_chains: Arc::default(),
+            clock: Arc::new(SystemClock),
         }
     }
     
+    /// Override heartbeat, staleness and reconnect settings for subscriptions
+    pub fn with_subscription_config(mut self, config: SubscriptionConfig) -> Self {
+        self.transport.subscription_config = config;
+        self
+    }
+    
+    /// Override queue capacity and overflow policy for subscription dispatch
+    pub fn with_dispatch_config(mut self, config: DispatchConfig) -> Self {
+        self.transport.dispatch_config = config;
+        self
+    }
+    
+    /// Configure the response cache; `None` sends every query to the server
+    pub fn with_cache(mut self, config: Option<CacheConfig>) -> Self {
+        self.transport.set_cache(config);
+        self
+    }
+    
+    /// Replace the wall clock, e.g. with a `MockClock` for simulations
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Close subscriptions cleanly when `shutdown` triggers instead of reconnecting
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.transport.shutdown = Some(shutdown);
+        self
+    }
+    
+    /// Rebuild the HTTP client with custom pool, keepalive and HTTP/2 settings
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, SdkError> {
+        self.transport.client = config.build_client()?;
+        Ok(self)
+    }
+    
+    /// Use an existing client, e.g. to share one connection pool between several SDK instances
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.transport.client = client;
+        self
+    }
+    
+    /// Cap outgoing queries at `requests_per_second` across this SDK and its clones; `None` removes the limit
+    pub fn with_rate_limit(mut self, requests_per_second: Option<u32>) -> Self {
+        self.transport.rate_limiter =
+            requests_per_second.map(|rate| Arc::new(RateLimiter::per_second(rate)));
+        self
+    }
+    
+    /// Sign every submitted batch with `signer`
+    pub fn with_signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+    
+    /// Application ID of the OddsStream user application on this SDK's chain
+    pub fn with_user_application(mut self, application_id: ApplicationId) -> Self {
+        self.user_application_id = Some(application_id);
+        self
+    }
+    
+    /// Registry chain and application, needed for market administration
+    pub fn with_registry_application(mut self, chain_id: ChainId, application_id: ApplicationId) -> Self {
+        self.registry = Some((chain_id, application_id));
+        self
+    }
+    
+    /// Oracle chain and application, needed for committee voting
+    pub fn with_oracle_application(mut self, chain_id: ChainId, application_id: ApplicationId) -> Self {
+        self.oracle = Some((chain_id, application_id));
+        self
+    }
+    
     /// Get current chain ID
     pub fn chain_id(&self) -> &ChainId {
         &self.chain_id
     }
     
+    /// Public key of the configured signer, if any
+    pub fn signer_public_key(&self) -> Option<Vec<u8>> {
+        self.signer.as_ref().map(|signer| signer.public_key())
+    }
+    
+    /// Read-only view sharing this SDK's endpoint, HTTP client and subscription settings
+    pub fn read_only(&self) -> ReadOnlyClient {
+        ReadOnlyClient::from_transport(self.transport.clone())
+    }
+    
     /// Submit batched orders to multiple markets
     pub async fn submit_batched_orders(
         &self,
         orders: Vec<MarketOrder>,
         user_chain_id: ChainId,
     ) -> Result<BatchResponse, SdkError> {
-        // Group orders by market chain
-        let mut orders_by_market: std::collections::HashMap<ChainId, Vec<MarketOrder>> = 
-            std::collections::HashMap::new();
-        
-        for order in orders {
-            // In production, you would fetch market chain ID from registry
-            let market_chain_id = self.resolve_market_chain(&order.market_id).await?;
-            orders_by_market
-                .entry(market_chain_id)
-                .or_insert_with(Vec::new)
-                .push(order);
-        }
+        // Resolve each distinct market once, then group orders by market chain
+        let total_orders = orders.len();
+        let chains = self
+            .resolve_market_chains(orders.iter().map(|order| order.market_id.as_str()))
+            .await?;
+        let orders_by_market = group_orders_by_chain(orders, |market_id| chains[market_id]);
         
         // Send batched messages to each market chain
         let mut responses = Vec::new();
         for (market_chain_id, market_orders) in orders_by_market {
-            let message = MarketMessage::BatchedOrders {
-                user_chain_id,
-                orders: market_orders,
-                nonce: self.get_nonce().await?,
+            let nonce = self.get_nonce().await?;
+            let order_count = market_orders.len();
+            let market_ids: std::collections::BTreeSet<String> =
+                market_orders.iter().map(|order| order.market_id.clone()).collect();
+            // Gated markets and markets on another wire schema would drop the batch silently; fail loudly instead
+            for market_id in &market_ids {
+                self.check_schema(market_id).await?;
+                self.check_compliance(market_id, user_chain_id).await?;
+            }
+            let wire = wire_orders(&market_orders, nonce)?;
+            let signature = match &self.signer {
+                Some(signer) => Some(
+                    sign_orders(signer.as_ref(), user_chain_id, market_chain_id, nonce, &wire, None).await?,
+                ),
+                None => None,
             };
             
-            let response = self
-                .send_message(market_chain_id, message)
-                .await?;
+            // With the user application installed, orders go through it so the
+            // chain's trading limits are enforced and sub-accounts debited before anything is sent
+            let response = if self.user_application_id.is_some() && user_chain_id == self.chain_id {
+                let mutation = r#"
+                    mutation PlaceOrders($marketChain: String!, $marketId: String!, $orders: [Order!]!, $nonce: Int!) {
+                        placeOrders(marketChain: $marketChain, marketId: $marketId, orders: $orders, nonce: $nonce)
+                    }
+                "#;
+                self.execute_user_operation(
+                    mutation,
+                    serde_json::json!({
+                        "marketChain": market_chain_id.to_string(),
+                        "marketId": market_orders[0].market_id,
+                        "orders": wire,
+                        "nonce": nonce,
+                    }),
+                )
+                .await?
+            } else {
+                // Markets refuse sub-accounts the user application didn't check
+                if let Some(order) = market_orders.iter().find(|order| order.subaccount.is_some()) {
+                    return Err(SdkError::InvalidInput(format!(
+                        "order on {} names a sub-account, which needs the user application",
+                        order.market_id
+                    )));
+                }
+                self.ensure_not_limited(user_chain_id).await?;
+                let notional = wire.iter().fold(Amount::ZERO, |sum, order| sum + order.amount);
+                self.approve_own_payments(user_chain_id, market_chain_id, notional).await?;
+                let message = MarketMessage::BatchedOrders {
+                    user_chain_id,
+                    orders: wire,
+                    nonce,
+                    signature,
+                    relayer_fee: None,
+                    cosignatures: Vec::new(),
+                };
+                self.send_message(market_chain_id, message).await?
+            };
+            tracing::info!(
+                %market_chain_id,
+                %user_chain_id,
+                nonce,
+                orders = order_count,
+                markets = ?market_ids,
+                transaction_id = %response,
+                "submitted order batch"
+            );
             
             responses.push(response);
         }
         
         Ok(BatchResponse {
             transaction_ids: responses,
-            total_orders: orders.len(),
+            total_orders,
         })
     }
     
-    /// Query active markets with filters
-    pub async fn query_markets(
+    /// Authorize the configured signer's key for orders relayed to these markets
+    pub async fn register_signer(
         &self,
-        filters: MarketFilters,
-    ) -> Result<Vec<MarketInfo>, SdkError> {
-        let query = format!(
-            r#"
-            query GetMarkets($filters: MarketFilters) {{
-                markets(filters: $filters) {{
-                    id
-                    description
-                    yesOdds
-                    noOdds
-                    volume
-                    liquidity
-                    status
-                    oracleType
-                    resolutionTime
-                    createdBlock
-                }}
-            }}
-            "#
-        );
-        
-        let response = self
-            .client
-            .post(&format!("{}/graphql", self.rpc_url))
-            .json(&serde_json::json!({
-                "query": query,
-                "variables": { "filters": filters }
-            }))
-            .send()
-            .await?;
+        market_ids: &[String],
+        user_chain_id: ChainId,
+    ) -> Result<Vec<String>, SdkError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| SdkError::InvalidInput("no signer configured".to_string()))?;
         
-        let data: GraphQLResponse<MarketsData> = response.json().await?;
-        Ok(data.data.markets)
+        let mut transaction_ids = Vec::new();
+        for market_id in market_ids {
+            let market_chain_id = self.resolve_market_chain(market_id).await?;
+            let message = MarketMessage::RegisterSigner {
+                user_chain_id,
+                public_key: signer.public_key(),
+            };
+            transaction_ids.push(self.send_message(market_chain_id, message).await?);
+        }
+        Ok(transaction_ids)
     }
     
-    /// Subscribe to real-time market updates
-    pub async fn subscribe_market_updates(
+    /// Markets resolved since `since` (ms) in which the user holds a position
+    pub async fn get_resolved_markets(
         &self,
-        market_ids: Vec<String>,
-        callback: impl Fn(MarketUpdate) + Send + 'static,
-    ) -> Result<SubscriptionHandle, SdkError> {
-        let subscription_query = format!(
-            r#"
-            subscription OnMarketUpdates($marketIds: [String!]) {{
-                marketUpdates(marketIds: $marketIds) {{
+        user_chain_id: ChainId,
+        since: u64,
+    ) -> Result<Vec<ResolvedMarket>, SdkError> {
+        let query = r#"
+            query ResolvedMarkets($userChainId: String!, $since: Int!) {
+                resolvedMarkets(userChainId: $userChainId, since: $since) {
                     marketId
-                    yesOdds
-                    noOdds
-                    volume
-                    status
-                    timestamp
-                }}
-            }}
-            "#
-        );
+                    outcome
+                    resolvedAt
+                    payout
+                    claimed
+                }
+            }
+        "#;
         
-        // Establish WebSocket connection
-        let ws_url = self.rpc_url.replace("https://", "wss://").replace("http://", "ws://");
-        let (mut ws_stream, _) = tokio_tungstenite::connect_async(&format!("{}/ws", ws_url))
-            .await
-            .map_err(|e| SdkError::ConnectionError(e.to_string()))?;
+        #[derive(Deserialize)]
+        struct ResolvedData {
+            #[serde(rename = "resolvedMarkets")]
+            resolved_markets: Vec<ResolvedMarket>,
+        }
         
-        // Send subscription
-        let subscribe_msg = serde_json::json!({
-            "type": "subscribe",
-            "query": subscription_query,
-            "variables": { "marketIds": market_ids }
-        });
+        let data: ResolvedData = self
+            .graphql_query(query, serde_json::json!({
+                "userChainId": user_chain_id.to_string(),
+                "since": since,
+            }))
+            .await?;
+        Ok(data.resolved_markets)
+    }
+    
+    /// Claim winnings from a single resolved market
+    pub async fn claim(&self, market_id: &str, user_chain_id: ChainId) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        let message = MarketMessage::Claim { user_chain_id };
         
-        ws_stream
-            .send(tokio_tungstenite::tungstenite::Message::Text(
-                subscribe_msg.to_string(),
-            ))
-            .await
-            .map_err(|e| SdkError::WebSocketError(e.to_string()))?;
+        self.send_message(market_chain_id, message).await
+    }
+    
+    /// Claim winnings from every resolved market holding an unclaimed winning position.
+    /// Claims go out `CLAIMS_PER_BATCH` at a time; one that fails is reported in the summary
+    /// without stopping the others.
+    pub async fn claim_all(&self, user_chain_id: ChainId) -> Result<ClaimSummary, SdkError> {
+        let claimable: Vec<ResolvedMarket> = self
+            .get_resolved_markets(user_chain_id, 0)
+            .await?
+            .into_iter()
+            .filter(|market| !market.claimed && market.payout > 0.0)
+            .collect();
         
-        // Spawn task to handle incoming messages
-        let handle = tokio::spawn(async move {
-            while let Some(msg) = ws_stream.next().await {
-                match msg {
-                    Ok(tokio_tungstenite::tungstenite::Message::Text(text)) => {
-                        if let Ok(update) = serde_json::from_str::<MarketUpdate>(&text) {
-                            callback(update);
-                        }
+        let mut summary = ClaimSummary::default();
+        for batch in claimable.chunks(CLAIMS_PER_BATCH) {
+            let claims = join_all(batch.iter().map(|market| self.claim(&market.market_id, user_chain_id))).await;
+            for (market, claim) in batch.iter().zip(claims) {
+                match claim {
+                    Ok(transaction_id) => {
+                        tracing::info!(market_id = %market.market_id, payout = market.payout, %transaction_id, "claimed payout");
+                        summary.transaction_ids.push(transaction_id);
+                        summary.total_payout += market.payout;
+                        summary.claimed_markets.push(market.market_id.clone());
                     }
                     Err(e) => {
-                        eprintln!("WebSocket error: {}", e);
-                        break;
+                        tracing::warn!(market_id = %market.market_id, error = %e, "claim failed");
+                        summary.failed.push((market.market_id.clone(), e.to_string()));
                     }
-                    _ => {}
                 }
             }
+        }
+        
+        Ok(summary)
+    }
+    
+    /// Query active markets with filters
+    pub async fn query_markets(
+        &self,
+        filters: MarketFilters,
+    ) -> Result<Vec<MarketInfo>, SdkError> {
+        self.transport.query_markets(filters).await
+    }
+    
+    /// Run a GraphQL query against the service endpoint and decode its `data`.
+    /// Any entry in the `errors` array fails the call.
+    pub(crate) async fn graphql_query<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.transport.graphql_query(query, variables).await
+    }
+    
+    /// `graphql_query` for rarely-changing data; may be answered from the local cache
+    pub(crate) async fn graphql_query_cached<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.transport.graphql_query_cached(query, variables).await
+    }
+    
+    /// `graphql_query` that never uses a cached response; for reads that drive trades
+    pub(crate) async fn graphql_query_fresh<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.transport.graphql_query_fresh(query, variables).await
+    }
+    
+    /// Run a GraphQL query, returning partial `data` alongside any field errors
+    pub async fn graphql_query_partial<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<GraphQLResponse<T>, SdkError> {
+        self.transport.graphql_query_partial(query, variables).await
+    }
+    
+    /// Subscribe to real-time market updates
+    pub async fn subscribe_market_updates(
+        &self,
+        market_ids: Vec<String>,
+        callback: impl Fn(MarketUpdate) + Send + 'static,
+    ) -> Result<SubscriptionHandle, SdkError> {
+        let (handle, mut receiver) = self.subscribe_market_updates_receiver(market_ids).await?;
+        
+        // Callbacks run on their own task so a slow consumer never stalls the read loop
+        tokio::spawn(async move {
+            while let Some(update) = receiver.recv().await {
+                callback(update);
+            }
         });
         
-        Ok(SubscriptionHandle { handle })
+        Ok(handle)
+    }
+    
+    /// Subscribe to real-time market updates as a `Stream`.
+    ///
+    /// The stream can be polled with `.next().await`, used in `select!` or
+    /// combined with other streams; dropping it closes the subscription.
+    pub async fn subscribe_market_updates_stream(
+        &self,
+        market_ids: Vec<String>,
+    ) -> Result<impl Stream<Item = MarketUpdate> + Send + Unpin, SdkError> {
+        let (handle, receiver) = self.subscribe_market_updates_receiver(market_ids).await?;
+        Ok(UpdateStream::new(handle, receiver))
+    }
+    
+    /// Subscribe to real-time market updates, consuming them from a bounded queue.
+    /// Use `UpdateReceiver::into_stream` to get a `Stream` instead.
+    pub async fn subscribe_market_updates_receiver(
+        &self,
+        market_ids: Vec<String>,
+    ) -> Result<(SubscriptionHandle, UpdateReceiver<MarketUpdate>), SdkError> {
+        Ok(self.transport.subscribe_market_updates(market_ids))
     }
     
     /// Create AI agent instance
@@ -196,6 +613,21 @@ impl OddsStreamSdk {
     }
 }
 
+/// Split orders into per-chain batches, keeping each batch in submission order
+pub fn group_orders_by_chain(
+    orders: Vec<MarketOrder>,
+    chain_of: impl Fn(&str) -> ChainId,
+) -> HashMap<ChainId, Vec<MarketOrder>> {
+    let mut orders_by_market: HashMap<ChainId, Vec<MarketOrder>> = HashMap::new();
+    for order in orders {
+        orders_by_market
+            .entry(chain_of(&order.market_id))
+            .or_default()
+            .push(order);
+    }
+    orders_by_market
+}
+
 // ... Additional types and implementations
 
 #[cfg(test)]
@@ -208,7 +640,7 @@ mod tests {
         let sdk = OddsStreamSdk::new(chain_id);
         
         assert_eq!(sdk.chain_id(), &chain_id);
-        assert!(!sdk.rpc_url.is_empty());
+        assert!(!sdk.transport.rpc_url.is_empty());
     }
     
     #[tokio::test]
@@ -0,0 +1,226 @@
+//! Market lifecycle: Created → Open → Locked → Resolving → Resolved, or
+//! Cancelled at any point before resolution
+
+use crate::transport::Transport;
+use crate::{MarketInfo, OddsStreamSdk, ReadOnlyClient, SdkError};
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle stage of a market
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MarketStatus {
+    /// Instantiated; only an opening call auction takes orders
+    Created,
+    Open,
+    /// Trading closed ahead of the resolution time
+    Locked,
+    /// Resolution time reached; waiting for the oracle
+    Resolving,
+    Resolved,
+    /// Voided; positions are refunded
+    Cancelled,
+}
+
+impl MarketStatus {
+    /// Whether the market fills orders, or queues them in its opening auction
+    pub fn accepts_orders(self) -> bool {
+        matches!(self, MarketStatus::Created | MarketStatus::Open)
+    }
+
+    pub fn is_final(self) -> bool {
+        matches!(self, MarketStatus::Resolved | MarketStatus::Cancelled)
+    }
+}
+
+/// One lifecycle transition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub status: MarketStatus,
+    /// Winning side, for the `Resolved` transition
+    #[serde(default)]
+    pub outcome: Option<bool>,
+    /// Micros timestamp of the transition
+    pub at: u64,
+}
+
+/// A deadline the market acts on once block time passes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerKind {
+    /// A call auction's collection period ends
+    UncrossAuction,
+    Lock,
+    Resolve,
+    /// The oracle is overdue; the fallback committee may resolve
+    EscalateOracle,
+    /// Nobody resolved in time; the market is voided
+    VoidUnresolved,
+    /// The claim window after settlement is over; the market is archived and its chain retired
+    Archive,
+    /// A FastTee key rotation's overlap window ends
+    CompleteKeyRotation,
+    /// An unacknowledged confirmation or payout is resent
+    RetryOutbox,
+    /// An atomic batch coordinated by this chain gives up on missing votes
+    AbortSaga,
+    /// Reservations held for atomic batches lapse
+    ExpireReservations,
+}
+
+/// A timer the market has yet to fire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTimer {
+    pub kind: TimerKind,
+    /// Micros timestamp it fires at
+    pub at: u64,
+}
+
+/// Where a market is in its lifecycle.
+///
+/// The market fires its timers (locking, reaching the resolution time,
+/// fallback deadlines) only when its next message arrives, so `status` can
+/// lag; use [`MarketLifecycle::effective_status`] for the stage at a given time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketLifecycle {
+    pub status: MarketStatus,
+    /// Micros timestamp orders are refused from
+    pub locks_at: u64,
+    pub resolution_time: u64,
+    pub history: Vec<StatusChange>,
+    /// Timers still to fire, in firing order
+    #[serde(default)]
+    pub pending_timers: Vec<PendingTimer>,
+    /// Micros timestamp the market retired its chain at; see `export_market_archive`
+    #[serde(default)]
+    pub archived_at: Option<u64>,
+    /// The registry's emergency pause has reached this market; see `protocol_status`
+    #[serde(default)]
+    pub protocol_paused: bool,
+}
+
+impl MarketLifecycle {
+    /// Stage at `now` (micros), applying the transitions the clock has made due
+    pub fn effective_status(&self, now: u64) -> MarketStatus {
+        if self.pending_timers.is_empty() {
+            // Services that predate timers only report the deadlines
+            return match self.status {
+                MarketStatus::Open | MarketStatus::Locked if now >= self.resolution_time => MarketStatus::Resolving,
+                MarketStatus::Open if now >= self.locks_at => MarketStatus::Locked,
+                status => status,
+            };
+        }
+        // Replay the due timers the way the market will fire them
+        self.pending_timers
+            .iter()
+            .take_while(|timer| timer.at <= now)
+            .fold(self.status, |status, timer| match (timer.kind, status) {
+                (TimerKind::UncrossAuction, MarketStatus::Created) => MarketStatus::Open,
+                (TimerKind::Lock, MarketStatus::Open) => MarketStatus::Locked,
+                (TimerKind::Resolve, MarketStatus::Open | MarketStatus::Locked) => MarketStatus::Resolving,
+                (TimerKind::VoidUnresolved, MarketStatus::Resolving) => MarketStatus::Cancelled,
+                (_, status) => status,
+            })
+    }
+
+    /// When the market entered its current stage
+    pub fn status_since(&self) -> Option<u64> {
+        self.history.last().map(|change| change.at)
+    }
+}
+
+impl MarketInfo {
+    /// When the market entered its current stage, if the service reported its history
+    pub fn status_since(&self) -> Option<u64> {
+        self.status_history.last().map(|change| change.at)
+    }
+}
+
+#[derive(Deserialize)]
+struct LifecycleData {
+    lifecycle: Option<MarketLifecycle>,
+}
+
+impl Transport {
+    pub(crate) async fn market_lifecycle(&self, market_id: &str) -> Result<MarketLifecycle, SdkError> {
+        let query = r#"
+            query Lifecycle($marketId: String!) {
+                lifecycle(marketId: $marketId) {
+                    status
+                    locksAt
+                    resolutionTime
+                    history { status outcome at }
+                    pendingTimers { kind at }
+                    archivedAt
+                    protocolPaused
+                }
+            }
+        "#;
+
+        let data: LifecycleData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.lifecycle
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+}
+
+impl OddsStreamSdk {
+    /// The market's lifecycle stage and every transition so far
+    pub async fn market_lifecycle(&self, market_id: &str) -> Result<MarketLifecycle, SdkError> {
+        self.transport.market_lifecycle(market_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The market's lifecycle stage and every transition so far
+    pub async fn market_lifecycle(&self, market_id: &str) -> Result<MarketLifecycle, SdkError> {
+        self.transport.market_lifecycle(market_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_status_follows_the_clock() {
+        let lifecycle = MarketLifecycle {
+            status: MarketStatus::Open,
+            locks_at: 900,
+            resolution_time: 1_000,
+            history: Vec::new(),
+            pending_timers: Vec::new(),
+            archived_at: None,
+            protocol_paused: false,
+        };
+        assert_eq!(lifecycle.effective_status(899), MarketStatus::Open);
+        assert_eq!(lifecycle.effective_status(900), MarketStatus::Locked);
+        assert_eq!(lifecycle.effective_status(1_000), MarketStatus::Resolving);
+
+        let resolved = MarketLifecycle { status: MarketStatus::Resolved, ..lifecycle };
+        assert_eq!(resolved.effective_status(2_000), MarketStatus::Resolved);
+    }
+
+    #[test]
+    fn test_effective_status_replays_pending_timers() {
+        let timer = |kind, at| PendingTimer { kind, at };
+        let lifecycle = MarketLifecycle {
+            status: MarketStatus::Open,
+            locks_at: 900,
+            resolution_time: 1_000,
+            history: Vec::new(),
+            pending_timers: vec![
+                timer(TimerKind::Lock, 900),
+                timer(TimerKind::Resolve, 1_000),
+                timer(TimerKind::EscalateOracle, 1_500),
+                timer(TimerKind::VoidUnresolved, 2_000),
+            ],
+            archived_at: None,
+            protocol_paused: false,
+        };
+        assert_eq!(lifecycle.effective_status(950), MarketStatus::Locked);
+        assert_eq!(lifecycle.effective_status(1_999), MarketStatus::Resolving);
+        assert_eq!(lifecycle.effective_status(2_000), MarketStatus::Cancelled);
+    }
+}
@@ -0,0 +1,586 @@
+//! Optional light-client verification of chain heads.
+//!
+//! By default every answer from the GraphQL gateway is taken on trust. With a
+//! [`Committee`] configured, the SDK fetches the certificate for a chain's
+//! tip, recomputes the block hash from the block header and checks that
+//! validators holding a quorum of the voting weight signed that hash. The
+//! gateway only relays the certificate: it can withhold newer blocks, but it
+//! can't forge a height or state hash a quorum didn't sign, or roll a client
+//! back past a tip it has already verified.
+//!
+//! Application state isn't covered. Linera serves no proofs of application
+//! state under a block's `state_hash`, so answers to application queries are
+//! still trusted; [`VerifiedHead`] only says which certified block the chain
+//! was at.
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Prefix Linera adds to the BCS encoding of a signed vote
+const VOTE_DOMAIN: &[u8] = b"VoteValue::";
+
+/// Prefix Linera adds to the BCS encoding of a block header before hashing it
+const HEADER_DOMAIN: &[u8] = b"BlockHeader::";
+
+/// One validator and its voting weight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorInfo {
+    /// Hex ed25519 public key
+    pub public_key: String,
+    pub votes: u64,
+}
+
+/// The validator set certificates are checked against, as published in the
+/// network's genesis configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Committee {
+    pub epoch: u32,
+    pub validators: Vec<ValidatorInfo>,
+}
+
+/// Round a certificate was produced in; mirrors Linera's `Round`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Round {
+    Fast,
+    MultiLeader(u32),
+    SingleLeader(u32),
+    Validator(u32),
+}
+
+/// Mirrors Linera's `CertificateKind`; only confirmed blocks are final
+#[derive(Serialize)]
+enum CertificateKind {
+    #[allow(dead_code)]
+    Timeout,
+    #[allow(dead_code)]
+    Validated,
+    Confirmed,
+}
+
+/// Signer a block was proposed by; mirrors Linera's `AccountOwner`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountOwner {
+    Reserved(u8),
+    Address32([u8; 32]),
+    Address20([u8; 20]),
+}
+
+impl FromStr for AccountOwner {
+    type Err = SdkError;
+
+    /// Parse the `0x`-prefixed hex form the node prints
+    fn from_str(raw: &str) -> Result<Self, SdkError> {
+        let hex = raw.trim_start_matches("0x");
+        match hex.len() {
+            2 => Ok(AccountOwner::Reserved(decode_hex::<1>(hex, "owner")?[0])),
+            40 => Ok(AccountOwner::Address20(decode_hex(hex, "owner")?)),
+            64 => Ok(AccountOwner::Address32(decode_hex(hex, "owner")?)),
+            len => Err(SdkError::InvalidInput(format!("owner {} has {} hex digits", raw, len))),
+        }
+    }
+}
+
+/// Header of a confirmed block; mirrors Linera's `BlockHeader`, whose hash
+/// is the block hash validators sign. The body is committed to by the
+/// `*_hash` fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub chain_id: ChainId,
+    pub epoch: u32,
+    pub height: u64,
+    pub timestamp: u64,
+    /// Hash of the chain's execution state after the block
+    pub state_hash: [u8; 32],
+    pub previous_block_hash: Option<[u8; 32]>,
+    pub authenticated_signer: Option<AccountOwner>,
+    pub bundles_hash: [u8; 32],
+    pub operations_hash: [u8; 32],
+    pub messages_hash: [u8; 32],
+    pub previous_message_blocks_hash: [u8; 32],
+    pub previous_event_blocks_hash: [u8; 32],
+    pub oracle_responses_hash: [u8; 32],
+    pub events_hash: [u8; 32],
+    pub blobs_hash: [u8; 32],
+    pub operation_results_hash: [u8; 32],
+}
+
+impl BlockHeader {
+    /// The block hash, as Linera computes it
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(HEADER_DOMAIN);
+        hasher.update(bcs::to_bytes(self).expect("block header is serializable"));
+        hasher.finalize().into()
+    }
+}
+
+/// A confirmed block and the validator signatures on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockCertificate {
+    pub block_hash: [u8; 32],
+    pub header: BlockHeader,
+    pub round: Round,
+    /// (Hex public key, hex signature)
+    pub signatures: Vec<(String, String)>,
+}
+
+/// A chain tip whose certificate checked out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedHead {
+    pub chain_id: ChainId,
+    pub height: u64,
+    pub block_hash: [u8; 32],
+    pub state_hash: [u8; 32],
+}
+
+fn decode_hex<const N: usize>(raw: &str, what: &str) -> Result<[u8; N], SdkError> {
+    let bytes = hex::decode(raw).map_err(|e| SdkError::InvalidInput(format!("{}: {}", what, e)))?;
+    <[u8; N]>::try_from(bytes.as_slice())
+        .map_err(|_| SdkError::InvalidInput(format!("{} has {} bytes, expected {}", what, bytes.len(), N)))
+}
+
+impl Committee {
+    pub fn total_votes(&self) -> u64 {
+        self.validators.iter().map(|validator| validator.votes).sum()
+    }
+
+    /// Votes needed for a certificate: more than two thirds of the total
+    pub fn quorum_threshold(&self) -> u64 {
+        2 * self.total_votes() / 3 + 1
+    }
+
+    /// Check that validators holding a quorum signed `certificate`. Unknown
+    /// signers and repeated signatures add no weight.
+    pub fn verify(&self, certificate: &BlockCertificate) -> Result<(), SdkError> {
+        let header = &certificate.header;
+        // Signatures only cover the hash; the header must be the one that hashes to it
+        if header.hash() != certificate.block_hash {
+            return Err(SdkError::VerificationFailed(format!(
+                "header of block {} of {} doesn't match its hash",
+                header.height, header.chain_id
+            )));
+        }
+        let message = vote_message(&certificate.block_hash, certificate.round);
+        let weights: HashMap<&str, u64> = self
+            .validators
+            .iter()
+            .map(|validator| (validator.public_key.as_str(), validator.votes))
+            .collect();
+
+        let mut counted = HashSet::new();
+        let mut votes = 0;
+        for (public_key, signature) in &certificate.signatures {
+            let Some(weight) = weights.get(public_key.as_str()) else {
+                continue;
+            };
+            if !counted.insert(public_key.as_str()) {
+                continue;
+            }
+            let key = VerifyingKey::from_bytes(&decode_hex(public_key, "validator key")?)
+                .map_err(|e| SdkError::VerificationFailed(format!("validator key {}: {}", public_key, e)))?;
+            let signature = Signature::from_bytes(&decode_hex(signature, "validator signature")?);
+            key.verify(&message, &signature).map_err(|_| {
+                SdkError::VerificationFailed(format!(
+                    "bad signature from {} on block {} of {}",
+                    public_key, header.height, header.chain_id
+                ))
+            })?;
+            votes += weight;
+        }
+
+        if votes < self.quorum_threshold() {
+            return Err(SdkError::VerificationFailed(format!(
+                "block {} of {} carries {} of the {} votes needed",
+                header.height,
+                header.chain_id,
+                votes,
+                self.quorum_threshold()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Bytes a validator signs to confirm `block_hash`
+pub fn vote_message(block_hash: &[u8; 32], round: Round) -> Vec<u8> {
+    let mut message = VOTE_DOMAIN.to_vec();
+    message.extend(bcs::to_bytes(&(block_hash, round, CertificateKind::Confirmed)).expect("vote is serializable"));
+    message
+}
+
+/// Checks certificates against a committee and remembers the latest verified
+/// tip of every chain, so a node can't roll a client back to older state
+pub struct LightClient {
+    committee: Committee,
+    heads: Mutex<HashMap<ChainId, VerifiedHead>>,
+}
+
+impl LightClient {
+    pub fn new(committee: Committee) -> Self {
+        Self { committee, heads: Mutex::default() }
+    }
+
+    pub fn committee(&self) -> &Committee {
+        &self.committee
+    }
+
+    /// The latest tip verified for `chain_id`
+    pub fn head(&self, chain_id: ChainId) -> Option<VerifiedHead> {
+        self.heads.lock().unwrap().get(&chain_id).copied()
+    }
+
+    /// Verify `certificate` and record it as the chain's tip
+    pub fn accept(&self, certificate: &BlockCertificate) -> Result<VerifiedHead, SdkError> {
+        self.committee.verify(certificate)?;
+        let head = VerifiedHead {
+            chain_id: certificate.header.chain_id,
+            height: certificate.header.height,
+            block_hash: certificate.block_hash,
+            state_hash: certificate.header.state_hash,
+        };
+        let mut heads = self.heads.lock().unwrap();
+        if let Some(known) = heads.get(&head.chain_id) {
+            if head.height < known.height {
+                return Err(SdkError::VerificationFailed(format!(
+                    "node served block {} of {} after block {} was verified",
+                    head.height, head.chain_id, known.height
+                )));
+            }
+            if head.height == known.height && head.block_hash != known.block_hash {
+                return Err(SdkError::VerificationFailed(format!(
+                    "conflicting certificates for block {} of {}",
+                    head.height, head.chain_id
+                )));
+            }
+        }
+        heads.insert(head.chain_id, head);
+        Ok(head)
+    }
+}
+
+#[derive(Deserialize)]
+struct TipData {
+    chain: RawChain,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawChain {
+    tip_state: RawTipState,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTipState {
+    block_hash: Option<String>,
+    next_block_height: u64,
+}
+
+#[derive(Deserialize)]
+struct CertificateData {
+    certificate: Option<RawCertificate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCertificate {
+    hash: String,
+    round: Round,
+    signatures: Vec<RawSignature>,
+    block: RawBlock,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSignature {
+    validator: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct RawBlock {
+    header: RawHeader,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawHeader {
+    chain_id: ChainId,
+    epoch: u32,
+    height: u64,
+    timestamp: u64,
+    state_hash: String,
+    previous_block_hash: Option<String>,
+    authenticated_signer: Option<String>,
+    bundles_hash: String,
+    operations_hash: String,
+    messages_hash: String,
+    previous_message_blocks_hash: String,
+    previous_event_blocks_hash: String,
+    oracle_responses_hash: String,
+    events_hash: String,
+    blobs_hash: String,
+    operation_results_hash: String,
+}
+
+impl RawHeader {
+    fn decode(self) -> Result<BlockHeader, SdkError> {
+        let hash = |raw: &str| decode_hex::<32>(raw, "header hash");
+        Ok(BlockHeader {
+            chain_id: self.chain_id,
+            epoch: self.epoch,
+            height: self.height,
+            timestamp: self.timestamp,
+            state_hash: hash(&self.state_hash)?,
+            previous_block_hash: self.previous_block_hash.as_deref().map(hash).transpose()?,
+            authenticated_signer: self.authenticated_signer.as_deref().map(AccountOwner::from_str).transpose()?,
+            bundles_hash: hash(&self.bundles_hash)?,
+            operations_hash: hash(&self.operations_hash)?,
+            messages_hash: hash(&self.messages_hash)?,
+            previous_message_blocks_hash: hash(&self.previous_message_blocks_hash)?,
+            previous_event_blocks_hash: hash(&self.previous_event_blocks_hash)?,
+            oracle_responses_hash: hash(&self.oracle_responses_hash)?,
+            events_hash: hash(&self.events_hash)?,
+            blobs_hash: hash(&self.blobs_hash)?,
+            operation_results_hash: hash(&self.operation_results_hash)?,
+        })
+    }
+}
+
+impl Transport {
+    fn light_client(&self) -> Result<&Arc<LightClient>, SdkError> {
+        self.light_client
+            .as_ref()
+            .ok_or_else(|| SdkError::InvalidInput("no light-client committee configured".to_string()))
+    }
+
+    /// Hash of the chain's latest block, `None` before its first block
+    async fn chain_tip(&self, chain_id: ChainId) -> Result<Option<[u8; 32]>, SdkError> {
+        let query = r#"
+            query ChainTip($chainId: ChainId!) {
+                chain(chainId: $chainId) { tipState { blockHash nextBlockHeight } }
+            }
+        "#;
+
+        let data: TipData = self
+            .graphql_query_fresh(query, serde_json::json!({ "chainId": chain_id.to_string() }))
+            .await?;
+        data.chain
+            .tip_state
+            .block_hash
+            .filter(|_| data.chain.tip_state.next_block_height > 0)
+            .map(|hash| decode_hex(&hash, "block hash"))
+            .transpose()
+    }
+
+    async fn block_certificate(
+        &self,
+        chain_id: ChainId,
+        block_hash: [u8; 32],
+    ) -> Result<BlockCertificate, SdkError> {
+        let query = r#"
+            query Certificate($chainId: ChainId!, $hash: CryptoHash!) {
+                certificate(chainId: $chainId, hash: $hash) {
+                    hash
+                    round
+                    signatures { validator signature }
+                    block {
+                        header {
+                            chainId epoch height timestamp stateHash previousBlockHash authenticatedSigner
+                            bundlesHash operationsHash messagesHash previousMessageBlocksHash
+                            previousEventBlocksHash oracleResponsesHash eventsHash blobsHash
+                            operationResultsHash
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let data: CertificateData = self
+            .graphql_query_fresh(
+                query,
+                serde_json::json!({ "chainId": chain_id.to_string(), "hash": hex::encode(block_hash) }),
+            )
+            .await?;
+        let raw = data.certificate.ok_or_else(|| {
+            SdkError::VerificationFailed(format!("no certificate for block {} of {}", hex::encode(block_hash), chain_id))
+        })?;
+        // The node must not hand back a certificate for a different block than asked for
+        let hash = decode_hex(&raw.hash, "certificate hash")?;
+        if hash != block_hash {
+            return Err(SdkError::VerificationFailed(format!("certificate for the wrong block on {}", chain_id)));
+        }
+        let header = raw.block.header.decode()?;
+        if header.chain_id != chain_id {
+            return Err(SdkError::VerificationFailed(format!("certificate for a block of {} on {}", header.chain_id, chain_id)));
+        }
+        Ok(BlockCertificate {
+            block_hash,
+            header,
+            round: raw.round,
+            signatures: raw
+                .signatures
+                .into_iter()
+                .map(|sig| (sig.validator, sig.signature))
+                .collect(),
+        })
+    }
+
+    pub(crate) async fn verify_chain_head(&self, chain_id: ChainId) -> Result<VerifiedHead, SdkError> {
+        let light_client = self.light_client()?;
+        let tip = self
+            .chain_tip(chain_id)
+            .await?
+            .ok_or_else(|| SdkError::VerificationFailed(format!("{} has no blocks yet", chain_id)))?;
+        let certificate = self.block_certificate(chain_id, tip).await?;
+        light_client.accept(&certificate)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Check certificates against `committee` in `verify_chain_head`
+    pub fn with_light_client(mut self, committee: Committee) -> Self {
+        self.transport.light_client = Some(Arc::new(LightClient::new(committee)));
+        self
+    }
+
+    /// Fetch and verify the certificate of `chain_id`'s latest block
+    pub async fn verify_chain_head(&self, chain_id: ChainId) -> Result<VerifiedHead, SdkError> {
+        self.transport.verify_chain_head(chain_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Check certificates against `committee` in `verify_chain_head`
+    pub fn with_light_client(mut self, committee: Committee) -> Self {
+        self.transport.light_client = Some(Arc::new(LightClient::new(committee)));
+        self
+    }
+
+    /// Fetch and verify the certificate of `chain_id`'s latest block
+    pub async fn verify_chain_head(&self, chain_id: ChainId) -> Result<VerifiedHead, SdkError> {
+        self.transport.verify_chain_head(chain_id).await
+    }
+}
+
+impl FromStr for Committee {
+    type Err = SdkError;
+
+    /// Parse the committee section of a genesis configuration
+    fn from_str(json: &str) -> Result<Self, SdkError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn header(height: u64) -> BlockHeader {
+        BlockHeader {
+            chain_id: ChainId::from([1u8; 32]),
+            epoch: 0,
+            height,
+            timestamp: 1_000,
+            state_hash: [4u8; 32],
+            previous_block_hash: None,
+            authenticated_signer: Some(AccountOwner::Address32([2u8; 32])),
+            bundles_hash: [0u8; 32],
+            operations_hash: [0u8; 32],
+            messages_hash: [0u8; 32],
+            previous_message_blocks_hash: [0u8; 32],
+            previous_event_blocks_hash: [0u8; 32],
+            oracle_responses_hash: [0u8; 32],
+            events_hash: [0u8; 32],
+            blobs_hash: [0u8; 32],
+            operation_results_hash: [0u8; 32],
+        }
+    }
+
+    fn certificate_at(keys: &[SigningKey], signers: &[usize], height: u64) -> BlockCertificate {
+        let header = header(height);
+        let block_hash = header.hash();
+        let round = Round::MultiLeader(0);
+        let message = vote_message(&block_hash, round);
+        BlockCertificate {
+            block_hash,
+            header,
+            round,
+            signatures: signers
+                .iter()
+                .map(|&i| {
+                    (
+                        hex::encode(keys[i].verifying_key().to_bytes()),
+                        hex::encode(keys[i].sign(&message).to_bytes()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn certificate(keys: &[SigningKey], signers: &[usize]) -> BlockCertificate {
+        certificate_at(keys, signers, 5)
+    }
+
+    #[test]
+    fn test_certificate_needs_quorum() {
+        let keys: Vec<SigningKey> = (1..=4).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let committee = Committee {
+            epoch: 0,
+            validators: keys
+                .iter()
+                .map(|key| ValidatorInfo { public_key: hex::encode(key.verifying_key().to_bytes()), votes: 1 })
+                .collect(),
+        };
+        assert_eq!(committee.quorum_threshold(), 3);
+
+        assert!(committee.verify(&certificate(&keys, &[0, 1, 2])).is_ok());
+        assert!(committee.verify(&certificate(&keys, &[0, 1])).is_err());
+        // A repeated signature counts once
+        assert!(committee.verify(&certificate(&keys, &[0, 1, 1])).is_err());
+
+        // A signature over another round doesn't carry over
+        let mut moved = certificate(&keys, &[0, 1, 2]);
+        moved.round = Round::MultiLeader(1);
+        assert!(committee.verify(&moved).is_err());
+    }
+
+    #[test]
+    fn test_light_client_rejects_rollback() {
+        let keys: Vec<SigningKey> = (1..=4).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let committee = Committee {
+            epoch: 0,
+            validators: keys
+                .iter()
+                .map(|key| ValidatorInfo { public_key: hex::encode(key.verifying_key().to_bytes()), votes: 1 })
+                .collect(),
+        };
+        let client = LightClient::new(committee);
+        let cert = certificate(&keys, &[0, 1, 2, 3]);
+        client.accept(&cert).unwrap();
+
+        // A genuine older block can't replace the tip
+        assert!(client.accept(&certificate_at(&keys, &[0, 1, 2, 3], 4)).is_err());
+        // Nor can the tip's signatures vouch for a header edited to another height or state
+        let mut relabeled = cert.clone();
+        relabeled.header.height = 6;
+        assert!(client.accept(&relabeled).is_err());
+        let mut restated = cert.clone();
+        restated.header.state_hash = [5u8; 32];
+        assert!(client.accept(&restated).is_err());
+        assert_eq!(client.head(cert.header.chain_id).map(|head| head.height), Some(5));
+    }
+}
@@ -0,0 +1,164 @@
+//! Responsible-gaming controls enforced by the user chain: daily stake and
+//! loss limits and self-exclusion
+//!
+//! Only orders the user application places itself are checked and counted.
+//! With a user application configured, the SDK routes its own chain's plain
+//! batches through it and refuses every other way of trading (signed batches
+//! for another chain, relayed, atomic and multisig batches, amendments and
+//! parlays) while the chain has a limit or self-exclusion in force. If the
+//! limits can't be read, those orders are refused too.
+
+use crate::{OddsStreamSdk, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A user chain's limits and how much of today's allowance is used.
+///
+/// Tightening a limit applies immediately; loosening or removing one waits a
+/// day in `pending_*` first. Days are UTC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradingLimits {
+    pub daily_stake_limit: Option<Amount>,
+    pub daily_loss_limit: Option<Amount>,
+    /// Micros timestamp before which every order is refused; 0 if not excluded
+    pub excluded_until: u64,
+    #[serde(default)]
+    pub pending_stake_limit: Option<Amount>,
+    #[serde(default)]
+    pub pending_loss_limit: Option<Amount>,
+    /// When the pending limits replace the current ones
+    #[serde(default)]
+    pub pending_effective_at: Option<u64>,
+    /// Worst-case stake of today's orders
+    pub staked_today: Amount,
+    /// Today's stakes less payouts credited today
+    pub net_loss_today: Amount,
+}
+
+impl TradingLimits {
+    pub fn is_excluded(&self, now_micros: u64) -> bool {
+        now_micros < self.excluded_until
+    }
+
+    /// Whether any limit or self-exclusion applies at `now_micros`
+    pub fn in_force(&self, now_micros: u64) -> bool {
+        self.is_excluded(now_micros) || self.daily_stake_limit.is_some() || self.daily_loss_limit.is_some()
+    }
+
+    /// What can still be staked today under both limits; `None` if unlimited
+    pub fn remaining_stake(&self) -> Option<Amount> {
+        let by_stake = self
+            .daily_stake_limit
+            .map(|limit| limit.saturating_sub(self.staked_today));
+        let by_loss = self
+            .daily_loss_limit
+            .map(|limit| limit.saturating_sub(self.net_loss_today));
+        match (by_stake, by_loss) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LimitsData {
+    #[serde(rename = "tradingLimits")]
+    trading_limits: TradingLimits,
+}
+
+impl OddsStreamSdk {
+    /// Limits and today's usage for a user chain, read from its user application
+    pub async fn trading_limits(&self, user_chain_id: ChainId) -> Result<TradingLimits, SdkError> {
+        let query = r#"
+            query TradingLimits {
+                tradingLimits {
+                    dailyStakeLimit
+                    dailyLossLimit
+                    excludedUntil
+                    pendingStakeLimit
+                    pendingLossLimit
+                    pendingEffectiveAt
+                    stakedToday
+                    netLossToday
+                }
+            }
+        "#;
+        let data: LimitsData = self.query_user_application(user_chain_id, query).await?;
+        Ok(data.trading_limits)
+    }
+
+    /// Fails closed for orders that bypass the user application: refused while
+    /// `user_chain_id` has limits in force, or when they can't be read
+    pub(crate) async fn ensure_not_limited(&self, user_chain_id: ChainId) -> Result<(), SdkError> {
+        if self.user_application_id.is_none() {
+            return Ok(());
+        }
+        let limits = self.trading_limits(user_chain_id).await?;
+        if limits.in_force(self.clock.now_micros()) {
+            return Err(SdkError::TradingLimited { user_chain_id: user_chain_id.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Set this chain's daily limits; `None` removes a limit (after the cooling-off period)
+    pub async fn set_trading_limits(
+        &self,
+        daily_stake_limit: Option<Amount>,
+        daily_loss_limit: Option<Amount>,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation SetTradingLimits($dailyStakeLimit: Amount, $dailyLossLimit: Amount) {
+                setTradingLimits(dailyStakeLimit: $dailyStakeLimit, dailyLossLimit: $dailyLossLimit)
+            }
+        "#;
+        self.execute_user_operation(
+            mutation,
+            serde_json::json!({
+                "dailyStakeLimit": daily_stake_limit.map(|limit| limit.to_string()),
+                "dailyLossLimit": daily_loss_limit.map(|limit| limit.to_string()),
+            }),
+        )
+        .await
+    }
+
+    /// Refuse every order from this chain for `duration`. Cannot be undone or shortened.
+    pub async fn self_exclude(&self, duration: Duration) -> Result<String, SdkError> {
+        let until = self.clock.now_micros() + duration.as_micros() as u64;
+        let mutation = r#"
+            mutation SelfExclude($until: Int!) {
+                selfExclude(until: $until)
+            }
+        "#;
+        self.execute_user_operation(mutation, serde_json::json!({ "until": until }))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_stake_takes_the_tighter_limit() {
+        let mut limits = TradingLimits {
+            daily_stake_limit: Some(Amount::from_tokens(100)),
+            daily_loss_limit: None,
+            excluded_until: 0,
+            pending_stake_limit: None,
+            pending_loss_limit: None,
+            pending_effective_at: None,
+            staked_today: Amount::from_tokens(60),
+            net_loss_today: Amount::from_tokens(50),
+        };
+        assert_eq!(limits.remaining_stake(), Some(Amount::from_tokens(40)));
+
+        limits.daily_loss_limit = Some(Amount::from_tokens(70));
+        assert_eq!(limits.remaining_stake(), Some(Amount::from_tokens(20)));
+
+        limits.daily_stake_limit = None;
+        limits.daily_loss_limit = None;
+        assert_eq!(limits.remaining_stake(), None);
+    }
+}
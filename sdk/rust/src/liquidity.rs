@@ -0,0 +1,222 @@
+//! Liquidity provision and the fee schedule that governs withdrawals
+
+use crate::{FeeTier, MarketMessage, OddsStreamSdk, SdkError, YieldBeneficiary};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Fees and LP withdrawal rules fixed at market creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeSchedule {
+    pub taker_fee_bps: u32,
+    pub referral_share_bps: u32,
+    /// Portion of the taker fee that pays the oracle
+    #[serde(default)]
+    pub oracle_share_bps: u32,
+    /// Portion of the taker fee owed to the protocol
+    #[serde(default)]
+    pub protocol_share_bps: u32,
+    /// Seconds after each deposit during which liquidity is locked
+    pub lp_cooldown_secs: u64,
+    /// Fee for leaving during the cooldown; 0 means the cooldown cannot be broken
+    pub early_exit_fee_bps: u32,
+    /// Fee on fills against resting orders; every fill is a taker until the order book lands
+    #[serde(default)]
+    pub maker_fee_bps: u32,
+    /// Discounts by 30-day volume, ascending; the base rates apply below the first
+    #[serde(default)]
+    pub tiers: Vec<FeeTier>,
+}
+
+/// Liquidity a provider holds in one market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LpPosition {
+    pub market_id: String,
+    pub shares: Amount,
+    /// The shares' slice of the pools now; trading fees accrue here
+    pub value: f64,
+    /// Micros timestamp of the last deposit, which restarted the cooldown
+    pub last_deposit_at: u64,
+    /// Yield from the market's yield source credited to the provider and not yet claimed
+    #[serde(default)]
+    pub yield_earned: f64,
+}
+
+/// Where a market parks its idle pool funds while it trades
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketYield {
+    /// Yield source application, whitelisted by the registry
+    pub source: String,
+    /// Share of the pools kept deposited
+    pub deposit_bps: u32,
+    pub beneficiary: YieldBeneficiary,
+    /// Principal the source holds now; 0 once trading has stopped
+    pub deposited: f64,
+    /// Yield collected over the market's life
+    pub harvested: f64,
+}
+
+#[derive(Deserialize)]
+struct FeeScheduleData {
+    #[serde(rename = "feeSchedule")]
+    fee_schedule: Option<FeeSchedule>,
+}
+
+#[derive(Deserialize)]
+struct LpPositionData {
+    #[serde(rename = "lpPosition")]
+    lp_position: Option<RawLpPosition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLpPosition {
+    shares: String,
+    value: String,
+    last_deposit_at: u64,
+    #[serde(default)]
+    yield_earned: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct YieldStatusData {
+    #[serde(rename = "yieldStatus")]
+    yield_status: Option<RawMarketYield>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMarketYield {
+    source: String,
+    deposit_bps: u32,
+    beneficiary: YieldBeneficiary,
+    deposited: String,
+    harvested: String,
+}
+
+fn parse_tokens(field: &str, value: &str) -> Result<f64, SdkError> {
+    value
+        .parse::<f64>()
+        .map_err(|e| SdkError::InvalidInput(format!("invalid {} {}: {}", field, value, e)))
+}
+
+impl OddsStreamSdk {
+    pub async fn fee_schedule(&self, market_id: &str) -> Result<FeeSchedule, SdkError> {
+        let query = r#"
+            query FeeSchedule($marketId: String!) {
+                feeSchedule(marketId: $marketId) {
+                    takerFeeBps
+                    referralShareBps
+                    oracleShareBps
+                    protocolShareBps
+                    lpCooldownSecs
+                    earlyExitFeeBps
+                    makerFeeBps
+                    tiers { minVolume takerFeeBps makerFeeBps }
+                }
+            }
+        "#;
+
+        let data: FeeScheduleData = self
+            .graphql_query_cached(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.fee_schedule
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+
+    /// Deposit liquidity into both pools at the current odds; restarts the withdrawal cooldown
+    pub async fn add_liquidity(
+        &self,
+        market_id: &str,
+        provider: ChainId,
+        amount: Amount,
+    ) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.approve_own_payments(provider, market_chain_id, amount).await?;
+        let message = MarketMessage::AddLiquidity { provider, amount };
+        self.send_message(market_chain_id, message).await
+    }
+
+    /// `provider`'s liquidity in `market_id`; `None` if it has never deposited
+    pub async fn lp_position(&self, market_id: &str, provider: ChainId) -> Result<Option<LpPosition>, SdkError> {
+        let query = r#"
+            query LpPosition($marketId: String!, $provider: String!) {
+                lpPosition(marketId: $marketId, provider: $provider) { shares value lastDepositAt yieldEarned }
+            }
+        "#;
+
+        let data: LpPositionData = self
+            .graphql_query_fresh(
+                query,
+                serde_json::json!({ "marketId": market_id, "provider": provider.to_string() }),
+            )
+            .await?;
+        let Some(raw) = data.lp_position else {
+            return Ok(None);
+        };
+        let shares = Amount::from_str(&raw.shares)
+            .map_err(|e| SdkError::InvalidInput(format!("invalid shares {}: {}", raw.shares, e)))?;
+        let value = parse_tokens("value", &raw.value)?;
+        let yield_earned = match &raw.yield_earned {
+            Some(earned) => parse_tokens("yield", earned)?,
+            None => 0.0,
+        };
+        Ok(Some(LpPosition {
+            market_id: market_id.to_string(),
+            shares,
+            value,
+            last_deposit_at: raw.last_deposit_at,
+            yield_earned,
+        }))
+    }
+
+    /// The market's yield source and what it has earned; `None` if the market keeps its funds on-chain
+    pub async fn market_yield(&self, market_id: &str) -> Result<Option<MarketYield>, SdkError> {
+        let query = r#"
+            query YieldStatus($marketId: String!) {
+                yieldStatus(marketId: $marketId) { source depositBps beneficiary deposited harvested }
+            }
+        "#;
+
+        let data: YieldStatusData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let Some(raw) = data.yield_status else {
+            return Ok(None);
+        };
+        Ok(Some(MarketYield {
+            source: raw.source,
+            deposit_bps: raw.deposit_bps,
+            beneficiary: raw.beneficiary,
+            deposited: parse_tokens("deposit", &raw.deposited)?,
+            harvested: parse_tokens("harvest", &raw.harvested)?,
+        }))
+    }
+
+    /// Collect the yield accrued so far; the market only harvests while it trades
+    pub async fn harvest_yield(&self, market_id: &str) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.send_message(market_chain_id, MarketMessage::HarvestYield).await
+    }
+
+    /// Withdraw the harvested yield credited to `provider`
+    pub async fn claim_yield(&self, market_id: &str, provider: ChainId) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.send_message(market_chain_id, MarketMessage::ClaimYield { provider }).await
+    }
+
+    /// Burn LP shares; ignored by the market while locked, charged the early-exit fee if breakable
+    pub async fn remove_liquidity(
+        &self,
+        market_id: &str,
+        provider: ChainId,
+        shares: Amount,
+    ) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        let message = MarketMessage::RemoveLiquidity { provider, shares };
+        self.send_message(market_chain_id, message).await
+    }
+}
@@ -0,0 +1,294 @@
+//! Auto-compounding liquidity vault: spreads capital across markets in
+//! proportion to their trading volume and leaves each market before it locks.
+//!
+//! Trading fees accrue inside the pools, so they compound without any action;
+//! they are realized when the vault withdraws, and the proceeds are redeployed
+//! on the next rebalance.
+
+use crate::{AgentContext, AgentDecision, MarketUpdate, SdkError, TradingStrategy};
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tuning of an `LpVaultStrategy`, read from `AgentConfig::strategy_params`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LpVaultParams {
+    /// Tokens the vault deploys across its markets
+    pub capital: f64,
+    /// Withdraw from a market this long before it locks
+    pub exit_before_lock_secs: u64,
+    /// Rebalance a market once it is off target by this share of the vault's value
+    pub rebalance_threshold: f64,
+    /// Smallest weight an eligible market gets, so quiet markets keep some depth
+    pub min_weight: f64,
+    /// Weight of the latest interval in the smoothed volume, in (0, 1]
+    pub volume_smoothing: f64,
+}
+
+impl Default for LpVaultParams {
+    fn default() -> Self {
+        Self {
+            capital: 0.0,
+            exit_before_lock_secs: 3_600,
+            rebalance_threshold: 0.05,
+            min_weight: 0.05,
+            volume_smoothing: 0.3,
+        }
+    }
+}
+
+impl LpVaultParams {
+    pub fn validate(&self) -> Result<(), SdkError> {
+        let invalid = |what: &str| Err(SdkError::InvalidInput(format!("LP vault {}", what)));
+        if self.capital.is_nan() || self.capital < 0.0 {
+            return invalid("capital must not be negative");
+        }
+        if !(0.0..1.0).contains(&self.rebalance_threshold) {
+            return invalid("rebalance threshold must be in [0, 1)");
+        }
+        if !(0.0..=1.0).contains(&self.min_weight) {
+            return invalid("minimum weight must be in [0, 1]");
+        }
+        if !(self.volume_smoothing > 0.0 && self.volume_smoothing <= 1.0) {
+            return invalid("volume smoothing must be in (0, 1]");
+        }
+        Ok(())
+    }
+}
+
+/// Liquidity provision across the agent's markets, weighted toward the ones that trade most
+#[derive(Debug, Clone, Default)]
+pub struct LpVaultStrategy {
+    params: LpVaultParams,
+    /// Tokens deposited and not yet withdrawn, by market
+    deposited: HashMap<String, f64>,
+    /// Cumulative volume at the previous tick, and the smoothed volume per tick since
+    last_volume: HashMap<String, f64>,
+    volume_rate: HashMap<String, f64>,
+    /// Gains (fees, less any divergence loss) realized by withdrawals
+    realized: f64,
+}
+
+impl LpVaultStrategy {
+    pub fn new(params: LpVaultParams) -> Result<Self, SdkError> {
+        params.validate()?;
+        Ok(Self { params, ..Default::default() })
+    }
+
+    pub fn params(&self) -> &LpVaultParams {
+        &self.params
+    }
+
+    /// Tokens not deployed in any market
+    pub fn idle(&self) -> f64 {
+        (self.params.capital + self.realized - self.deposited.values().sum::<f64>()).max(0.0)
+    }
+
+    pub fn realized(&self) -> f64 {
+        self.realized
+    }
+
+    /// Fees earned and still in the pools: position value above what was deposited
+    pub fn unrealized(&self, context: &AgentContext) -> f64 {
+        context
+            .lp_positions
+            .iter()
+            .map(|(market_id, position)| position.value - self.deposited.get(market_id).copied().unwrap_or_default())
+            .sum()
+    }
+
+    fn track_volume(&mut self, context: &AgentContext) {
+        for (market_id, market) in &context.markets {
+            let previous = self.last_volume.insert(market_id.clone(), market.volume);
+            let traded = previous.map_or(0.0, |previous| (market.volume - previous).max(0.0));
+            let smoothing = self.params.volume_smoothing;
+            let rate = self.volume_rate.entry(market_id.clone()).or_insert(traded);
+            *rate = smoothing * traded + (1.0 - smoothing) * *rate;
+        }
+    }
+
+    /// Whether the vault may hold liquidity in `market_id` at `now_micros`
+    fn is_eligible(&self, context: &AgentContext, market_id: &str) -> bool {
+        let Some(market) = context.markets.get(market_id) else {
+            return false;
+        };
+        let locks_at = market.locks_at.unwrap_or(market.resolution_time);
+        let exit_at = locks_at.saturating_sub(self.params.exit_before_lock_secs * 1_000_000);
+        context.now_micros < exit_at
+    }
+
+    /// Target share of the vault per eligible market
+    pub fn weights(&self, eligible: &[&str]) -> HashMap<String, f64> {
+        if eligible.is_empty() {
+            return HashMap::new();
+        }
+        let rates: Vec<f64> = eligible
+            .iter()
+            .map(|market_id| self.volume_rate.get(*market_id).copied().unwrap_or_default())
+            .collect();
+        let total: f64 = rates.iter().sum();
+        let floored: Vec<f64> = rates
+            .iter()
+            .map(|rate| {
+                let share = if total > 0.0 { rate / total } else { 1.0 / eligible.len() as f64 };
+                share.max(self.params.min_weight)
+            })
+            .collect();
+        let norm: f64 = floored.iter().sum();
+        eligible
+            .iter()
+            .zip(floored)
+            .map(|(market_id, weight)| (market_id.to_string(), weight / norm))
+            .collect()
+    }
+
+    fn withdraw(&mut self, context: &AgentContext, market_id: &str, fraction: f64) -> Option<AgentDecision> {
+        let position = context.lp_positions.get(market_id)?;
+        let held = u128::from(position.shares);
+        let shares = ((held as f64) * fraction.clamp(0.0, 1.0)) as u128;
+        if shares == 0 {
+            return None;
+        }
+        let shares = if fraction >= 1.0 { held } else { shares.min(held) };
+        // Booked when decided; a withdrawal the market ignores shows up as value again on the next refresh
+        let deposited = self.deposited.entry(market_id.to_string()).or_default();
+        let returned_cost = *deposited * fraction.min(1.0);
+        *deposited -= returned_cost;
+        self.realized += position.value * fraction.min(1.0) - returned_cost;
+        Some(AgentDecision::RemoveLiquidity { market_id: market_id.to_string(), shares: Amount::from_attos(shares) })
+    }
+}
+
+impl TradingStrategy for LpVaultStrategy {
+    fn name(&self) -> &str {
+        "lp_vault"
+    }
+
+    fn on_update(&mut self, _update: &MarketUpdate, _context: &AgentContext) -> Vec<AgentDecision> {
+        // Allocation only changes on the decision interval
+        Vec::new()
+    }
+
+    fn on_tick(&mut self, context: &AgentContext) -> Vec<AgentDecision> {
+        self.track_volume(context);
+        let mut decisions = Vec::new();
+
+        let mut market_ids: Vec<&String> = context.markets.keys().collect();
+        market_ids.sort();
+        let (eligible, exiting): (Vec<&str>, Vec<&str>) = market_ids
+            .iter()
+            .map(|market_id| market_id.as_str())
+            .partition(|market_id| self.is_eligible(context, market_id));
+
+        // Out before the lock, so the liquidity never sits through resolution
+        for market_id in exiting {
+            decisions.extend(self.withdraw(context, market_id, 1.0));
+        }
+
+        let value_of = |market_id: &str| context.lp_positions.get(market_id).map_or(0.0, |position| position.value);
+        // Deposits only draw on cash already idle; this tick's withdrawals fund the next one
+        let mut idle = self.idle();
+        let vault_value = idle + eligible.iter().map(|market_id| value_of(market_id)).sum::<f64>();
+        if vault_value <= 0.0 {
+            return decisions;
+        }
+        let threshold = self.params.rebalance_threshold * vault_value;
+        let weights = self.weights(&eligible);
+
+        let mut deposits = Vec::new();
+        for market_id in eligible {
+            let current = value_of(market_id);
+            let target = vault_value * weights[market_id];
+            if (target - current).abs() <= threshold {
+                continue;
+            }
+            if target > current {
+                deposits.push((market_id, target - current));
+            } else {
+                decisions.extend(self.withdraw(context, market_id, (current - target) / current));
+            }
+        }
+        for (market_id, shortfall) in deposits {
+            let amount = shortfall.min(idle);
+            if amount <= 0.0 {
+                break;
+            }
+            idle -= amount;
+            *self.deposited.entry(market_id.to_string()).or_default() += amount;
+            decisions.push(AgentDecision::AddLiquidity { market_id: market_id.to_string(), amount });
+        }
+        decisions
+    }
+
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), SdkError> {
+        if params.is_null() {
+            return Ok(());
+        }
+        let params: LpVaultParams = serde_json::from_value(params.clone())?;
+        params.validate()?;
+        self.params = params;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LpPosition, MarketInfo};
+
+    const HOUR: u64 = 3_600_000_000;
+
+    fn market(id: &str, volume: f64, locks_at: u64) -> MarketInfo {
+        MarketInfo {
+            id: id.to_string(),
+            description: String::new(),
+            yes_odds: 0.5,
+            no_odds: 0.5,
+            volume,
+            liquidity: 0.0,
+            status: "Open".to_string(),
+            oracle_type: "FAST_TEE".to_string(),
+            resolution_time: locks_at,
+            created_block: 0,
+            realized_volatility: None,
+            yes_depth: None,
+            no_depth: None,
+            average_trade_size: None,
+            last_trade_at: None,
+            locks_at: Some(locks_at),
+            status_history: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_vault_follows_volume_and_exits_before_lock() {
+        let params = LpVaultParams { capital: 100.0, min_weight: 0.0, volume_smoothing: 1.0, ..Default::default() };
+        let mut vault = LpVaultStrategy::new(params).unwrap();
+        let mut context = AgentContext { now_micros: 0, ..Default::default() };
+        context.markets.insert("busy".to_string(), market("busy", 0.0, 10 * HOUR));
+        context.markets.insert("quiet".to_string(), market("quiet", 0.0, 10 * HOUR));
+        vault.on_tick(&context);
+
+        // One interval later "busy" traded three times as much as "quiet"
+        context.markets.insert("busy".to_string(), market("busy", 300.0, 10 * HOUR));
+        context.markets.insert("quiet".to_string(), market("quiet", 100.0, 10 * HOUR));
+        let weights = {
+            vault.track_volume(&context);
+            vault.weights(&["busy", "quiet"])
+        };
+        assert!((weights["busy"] - 0.75).abs() < 1e-9);
+
+        // Close to the lock the whole position comes out
+        context.now_micros = 9 * HOUR + 1;
+        context.lp_positions.insert(
+            "busy".to_string(),
+            LpPosition { market_id: "busy".to_string(), shares: Amount::from_tokens(50), value: 55.0, last_deposit_at: 0, yield_earned: 0.0 },
+        );
+        let decisions = vault.on_tick(&context);
+        assert!(decisions.contains(&AgentDecision::RemoveLiquidity {
+            market_id: "busy".to_string(),
+            shares: Amount::from_tokens(50),
+        }));
+    }
+}
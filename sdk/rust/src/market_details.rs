@@ -0,0 +1,110 @@
+//! Everything about one market in a single call: its creation settings,
+//! fees, lifecycle, pools and latest trades
+
+use crate::{FeeSchedule, MarketLifecycle, MarketStats, OddsStreamSdk, SdkError, Trade};
+use linera_sdk::base::Amount;
+use serde::{Deserialize, Serialize};
+
+/// Who signs a market's resolution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OracleKind {
+    FastTee,
+    Committee,
+    Hybrid,
+}
+
+/// Settings fixed when the market was created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketConfig {
+    pub market_id: String,
+    pub description: String,
+    /// Pricing curve, e.g. `CONSTANT_PRODUCT`
+    pub amm_kind: String,
+    pub oracle_kind: OracleKind,
+    /// Hex key a `FastTee` oracle signs with; cached, so see `oracle_info` for rotations
+    pub oracle_public_key: Option<String>,
+    /// Members of a `Committee` oracle
+    pub committee_size: Option<u32>,
+    pub registry_chain: String,
+    /// Token application stakes settle in; `None` is the native token
+    pub settlement_token: Option<String>,
+    /// Compliance allowlist orders are checked against
+    pub allowlist: Option<String>,
+    /// Set for a conditional market: it only settles if this market resolves to `activates_on`
+    pub parent_market_id: Option<String>,
+    pub activates_on: Option<bool>,
+    /// Partial-payout stages ahead of final resolution; 0 for single-shot markets
+    pub stage_count: u32,
+    /// Length of the opening call auction; 0 opens straight into continuous trading
+    pub opening_auction_secs: u64,
+    pub reopen_after_halt: bool,
+    pub max_pool: Option<Amount>,
+    pub max_user_exposure: Option<Amount>,
+}
+
+/// Full picture of a market, as shown by `oddsstream-cli market show`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketDetails {
+    pub config: MarketConfig,
+    pub fee_schedule: FeeSchedule,
+    pub lifecycle: MarketLifecycle,
+    pub stats: MarketStats,
+    /// Latest fills, oldest first
+    pub recent_trades: Vec<Trade>,
+}
+
+#[derive(Deserialize)]
+struct ConfigData {
+    #[serde(rename = "marketConfig")]
+    market_config: Option<MarketConfig>,
+}
+
+impl OddsStreamSdk {
+    pub async fn market_config(&self, market_id: &str) -> Result<MarketConfig, SdkError> {
+        let query = r#"
+            query MarketConfig($marketId: String!) {
+                marketConfig(marketId: $marketId) {
+                    marketId
+                    description
+                    ammKind
+                    oracleKind
+                    oraclePublicKey
+                    committeeSize
+                    registryChain
+                    settlementToken
+                    allowlist
+                    parentMarketId
+                    activatesOn
+                    stageCount
+                    openingAuctionSecs
+                    reopenAfterHalt
+                    maxPool
+                    maxUserExposure
+                }
+            }
+        "#;
+
+        // Creation settings never change, so a cached answer is as good as a fresh one
+        let data: ConfigData = self
+            .graphql_query_cached(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.market_config
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+
+    /// Config, fees, lifecycle and stats of a market with its last `trades` fills, fetched concurrently
+    pub async fn market_details(&self, market_id: &str, trades: usize) -> Result<MarketDetails, SdkError> {
+        let (config, fee_schedule, lifecycle, stats, mut recent_trades) = futures::try_join!(
+            self.market_config(market_id),
+            self.fee_schedule(market_id),
+            self.market_lifecycle(market_id),
+            self.market_stats(market_id),
+            self.recent_trades(market_id, None),
+        )?;
+        recent_trades.drain(..recent_trades.len().saturating_sub(trades));
+        Ok(MarketDetails { config, fee_schedule, lifecycle, stats, recent_trades })
+    }
+}
@@ -0,0 +1,303 @@
+//! M-of-N co-signing for team-managed user chains
+//!
+//! Once a policy is installed on a market, a chain that has traded more than
+//! its threshold over the trailing 24 hours without co-signatures only gets
+//! batches accepted with signatures from `required` distinct policy signers.
+//! A proposal is created by one desk member, passed around (it serializes to
+//! JSON) for approvals, and executed once enough signatures are collected.
+//!
+//! [`OddsStreamSdk::set_multisig_policy`] only installs a first policy.
+//! Replacing or removing one is itself a proposal, a [`PolicyChange`], which
+//! needs the current policy's quorum.
+
+use crate::{order_digest, wire_orders, MarketMessage, MarketOrder, OddsStreamSdk, Order, OrderSignature, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Must match the market contract's policy-change domain
+const POLICY_DOMAIN: &[u8] = b"oddsstream-multisig-policy-v1";
+
+/// Co-signing policy installed on markets for a user chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigPolicy {
+    /// ed25519 public keys of the desk members
+    pub signers: Vec<Vec<u8>>,
+    pub required: u32,
+    /// Notional a chain may trade without co-signatures over the trailing 24 hours
+    pub threshold_amount: Amount,
+}
+
+/// Digest the current policy's signers sign to replace it with `policy`, or
+/// remove it with `None`
+pub fn policy_digest(
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    policy: Option<&MultisigPolicy>,
+) -> [u8; 32] {
+    let payload = (user_chain_id, market_chain_id, nonce, policy);
+    let mut hasher = Sha256::new();
+    hasher.update(POLICY_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("policy payload is serializable"));
+    hasher.finalize().into()
+}
+
+fn distinct_approvals(approvals: &[OrderSignature], policy: &MultisigPolicy) -> u32 {
+    let mut seen: Vec<&Vec<u8>> = Vec::new();
+    for approval in approvals {
+        if policy.signers.contains(&approval.public_key) && !seen.contains(&&approval.public_key) {
+            seen.push(&approval.public_key);
+        }
+    }
+    seen.len() as u32
+}
+
+/// A batch awaiting approvals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigProposal {
+    pub market_id: String,
+    pub user_chain_id: ChainId,
+    pub market_chain_id: ChainId,
+    /// As approvers sign them
+    pub orders: Vec<Order>,
+    pub nonce: u64,
+    pub approvals: Vec<OrderSignature>,
+}
+
+impl MultisigProposal {
+    /// Digest every approver signs
+    pub fn digest(&self) -> [u8; 32] {
+        order_digest(self.user_chain_id, self.market_chain_id, self.nonce, &self.orders, None)
+    }
+
+    /// Hex identifier for passing proposals around
+    pub fn id(&self) -> String {
+        hex::encode(self.digest())
+    }
+
+    /// Distinct approvals from signers listed in `policy`
+    pub fn approval_count(&self, policy: &MultisigPolicy) -> u32 {
+        distinct_approvals(&self.approvals, policy)
+    }
+}
+
+/// A replacement (or with `None`, removal) of one market's policy awaiting
+/// approvals from the policy it replaces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyChange {
+    pub market_id: String,
+    pub user_chain_id: ChainId,
+    pub market_chain_id: ChainId,
+    pub policy: Option<MultisigPolicy>,
+    pub nonce: u64,
+    pub approvals: Vec<OrderSignature>,
+}
+
+impl PolicyChange {
+    /// Digest every approver signs
+    pub fn digest(&self) -> [u8; 32] {
+        policy_digest(self.user_chain_id, self.market_chain_id, self.nonce, self.policy.as_ref())
+    }
+
+    /// Distinct approvals from signers listed in `current`, the installed policy
+    pub fn approval_count(&self, current: &MultisigPolicy) -> u32 {
+        distinct_approvals(&self.approvals, current)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Install a co-signing policy for `user_chain_id` on each market. Markets
+    /// that already hold a policy ignore this; change it with
+    /// [`propose_policy_change`](Self::propose_policy_change).
+    pub async fn set_multisig_policy(
+        &self,
+        user_chain_id: ChainId,
+        market_ids: &[String],
+        policy: MultisigPolicy,
+    ) -> Result<(), SdkError> {
+        for market_id in market_ids {
+            let market_chain_id = self.resolve_market_chain(market_id).await?;
+            let message = MarketMessage::SetMultisigPolicy {
+                user_chain_id,
+                policy: Some(policy.clone()),
+            };
+            self.send_message(market_chain_id, message).await?;
+        }
+        Ok(())
+    }
+
+    /// Start a proposal for orders on one market, approved by this SDK's signer
+    pub async fn propose(
+        &self,
+        market_id: &str,
+        orders: Vec<MarketOrder>,
+        user_chain_id: ChainId,
+    ) -> Result<MultisigProposal, SdkError> {
+        let nonce = self.get_nonce().await?;
+        let mut proposal = MultisigProposal {
+            market_id: market_id.to_string(),
+            user_chain_id,
+            market_chain_id: self.resolve_market_chain(market_id).await?,
+            orders: wire_orders(&orders, nonce)?,
+            nonce,
+            approvals: Vec::new(),
+        };
+        self.approve(&mut proposal).await?;
+        Ok(proposal)
+    }
+
+    /// Add this SDK's signer's approval to a proposal
+    pub async fn approve(&self, proposal: &mut MultisigProposal) -> Result<(), SdkError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| SdkError::InvalidInput("approving requires a signer".to_string()))?;
+
+        let public_key = signer.public_key();
+        if proposal.approvals.iter().any(|a| a.public_key == public_key) {
+            return Ok(());
+        }
+        let signature = signer.sign(&proposal.digest()).await?;
+        proposal.approvals.push(OrderSignature { public_key, signature });
+        Ok(())
+    }
+
+    /// Start replacing (or with `None`, removing) `user_chain_id`'s policy on
+    /// one market, approved by this SDK's signer
+    pub async fn propose_policy_change(
+        &self,
+        market_id: &str,
+        user_chain_id: ChainId,
+        policy: Option<MultisigPolicy>,
+    ) -> Result<PolicyChange, SdkError> {
+        let mut change = PolicyChange {
+            market_id: market_id.to_string(),
+            user_chain_id,
+            market_chain_id: self.resolve_market_chain(market_id).await?,
+            policy,
+            nonce: self.get_nonce().await?,
+            approvals: Vec::new(),
+        };
+        self.approve_policy_change(&mut change).await?;
+        Ok(change)
+    }
+
+    /// Add this SDK's signer's approval to a policy change
+    pub async fn approve_policy_change(&self, change: &mut PolicyChange) -> Result<(), SdkError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| SdkError::InvalidInput("approving requires a signer".to_string()))?;
+
+        let public_key = signer.public_key();
+        if change.approvals.iter().any(|a| a.public_key == public_key) {
+            return Ok(());
+        }
+        let signature = signer.sign(&change.digest()).await?;
+        change.approvals.push(OrderSignature { public_key, signature });
+        Ok(())
+    }
+
+    /// Submit a policy change once it carries enough approvals from `current`,
+    /// the policy installed on the market
+    pub async fn execute_policy_change(
+        &self,
+        change: PolicyChange,
+        current: &MultisigPolicy,
+    ) -> Result<String, SdkError> {
+        let approvals = change.approval_count(current);
+        if approvals < current.required {
+            return Err(SdkError::InvalidInput(format!(
+                "policy change for {} has {} of {} required approvals",
+                change.market_id, approvals, current.required
+            )));
+        }
+
+        let message = MarketMessage::UpdateMultisigPolicy {
+            user_chain_id: change.user_chain_id,
+            policy: change.policy,
+            nonce: change.nonce,
+            signatures: change.approvals,
+        };
+        self.send_message(change.market_chain_id, message).await
+    }
+
+    /// Submit a proposal once it carries enough approvals for `policy`
+    pub async fn execute(
+        &self,
+        proposal: MultisigProposal,
+        policy: &MultisigPolicy,
+    ) -> Result<String, SdkError> {
+        let approvals = proposal.approval_count(policy);
+        if approvals < policy.required {
+            return Err(SdkError::InvalidInput(format!(
+                "proposal {} has {} of {} required approvals",
+                proposal.id(),
+                approvals,
+                policy.required
+            )));
+        }
+
+        self.ensure_not_limited(proposal.user_chain_id).await?;
+        let notional = proposal.orders.iter().fold(Amount::ZERO, |sum, order| sum + order.amount);
+        self.approve_own_payments(proposal.user_chain_id, proposal.market_chain_id, notional).await?;
+
+        let mut signatures = proposal.approvals.into_iter();
+        let message = MarketMessage::BatchedOrders {
+            user_chain_id: proposal.user_chain_id,
+            orders: proposal.orders,
+            nonce: proposal.nonce,
+            signature: signatures.next(),
+            relayer_fee: None,
+            cosignatures: signatures.collect(),
+        };
+        self.send_message(proposal.market_chain_id, message).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LocalSigner, Signer};
+
+    #[tokio::test]
+    async fn test_market_contract_accepts_policy_change_approvals() {
+        let signers: Vec<LocalSigner> = (1..=3).map(|seed| LocalSigner::from_bytes(&[seed; 32])).collect();
+        let user = ChainId::from([1u8; 32]);
+        let market = ChainId::from([2u8; 32]);
+        let replacement = MultisigPolicy {
+            signers: signers.iter().map(|s| s.public_key()).collect(),
+            required: 3,
+            threshold_amount: Amount::from_tokens(50),
+        };
+
+        let contract_replacement = oddsstream_market::multisig::MultisigPolicy {
+            signers: replacement.signers.clone(),
+            required: 3,
+            threshold_amount: replacement.threshold_amount,
+        };
+        let current = oddsstream_market::multisig::MultisigPolicy { required: 2, ..contract_replacement.clone() };
+        for (policy, contract_policy) in [(Some(&replacement), Some(&contract_replacement)), (None, None)] {
+            let digest = policy_digest(user, market, 9, policy);
+            assert_eq!(digest, oddsstream_market::multisig::policy_digest(user, market, 9, contract_policy));
+
+            let mut approvals = Vec::new();
+            for signer in &signers[..2] {
+                let signature = signer.sign(&digest).await.unwrap();
+                approvals.push(oddsstream_market::signing::OrderSignature { public_key: signer.public_key(), signature });
+            }
+            assert_eq!(current.approvals(&digest, approvals.iter()), 2);
+        }
+
+        // An approval of a batch doesn't count towards a policy change
+        let batch_digest = order_digest(user, market, 9, &[], None);
+        let signature = signers[0].sign(&batch_digest).await.unwrap();
+        let stray = oddsstream_market::signing::OrderSignature { public_key: signers[0].public_key(), signature };
+        let digest = policy_digest(user, market, 9, None);
+        assert_eq!(current.approvals(&digest, std::iter::once(&stray)), 0);
+    }
+}
@@ -0,0 +1,173 @@
+//! Conversions between implied probability and sportsbook odds formats
+
+use crate::{MarketInfo, SdkError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How odds are displayed to and entered by users
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OddsFormat {
+    /// Implied probability, e.g. `40.0%`
+    #[default]
+    Probability,
+    /// European decimal odds, e.g. `2.50`
+    Decimal,
+    /// US moneyline odds, e.g. `+150` / `-200`
+    American,
+    /// UK fractional odds, e.g. `3/2`
+    Fractional,
+}
+
+impl OddsFormat {
+    /// Render an implied probability (0-1, exclusive) in this format
+    pub fn format(&self, probability: f64) -> String {
+        if !(probability > 0.0 && probability < 1.0) {
+            return "-".to_string();
+        }
+        let decimal = 1.0 / probability;
+        match self {
+            OddsFormat::Probability => format!("{:.1}%", probability * 100.0),
+            OddsFormat::Decimal => format!("{:.2}", decimal),
+            OddsFormat::American => {
+                if decimal >= 2.0 {
+                    format!("+{:.0}", (decimal - 1.0) * 100.0)
+                } else {
+                    format!("-{:.0}", 100.0 / (decimal - 1.0))
+                }
+            }
+            OddsFormat::Fractional => {
+                let (num, den) = approximate_fraction(decimal - 1.0, 100);
+                format!("{}/{}", num, den)
+            }
+        }
+    }
+
+    /// Parse user-entered odds in this format into an implied probability
+    pub fn parse(&self, input: &str) -> Result<f64, SdkError> {
+        let input = input.trim();
+        let invalid = || SdkError::InvalidInput(format!("invalid {} odds: {}", self, input));
+
+        let probability = match self {
+            OddsFormat::Probability => match input.strip_suffix('%') {
+                Some(pct) => pct.trim().parse::<f64>().map_err(|_| invalid())? / 100.0,
+                None => input.parse::<f64>().map_err(|_| invalid())?,
+            },
+            OddsFormat::Decimal => {
+                let decimal: f64 = input.parse().map_err(|_| invalid())?;
+                if decimal <= 1.0 {
+                    return Err(invalid());
+                }
+                1.0 / decimal
+            }
+            OddsFormat::American => {
+                let line: f64 = input.parse().map_err(|_| invalid())?;
+                if line >= 100.0 {
+                    100.0 / (line + 100.0)
+                } else if line <= -100.0 {
+                    -line / (-line + 100.0)
+                } else {
+                    return Err(invalid());
+                }
+            }
+            OddsFormat::Fractional => {
+                let (num, den) = input.split_once('/').ok_or_else(invalid)?;
+                let num: f64 = num.trim().parse().map_err(|_| invalid())?;
+                let den: f64 = den.trim().parse().map_err(|_| invalid())?;
+                if num <= 0.0 || den <= 0.0 {
+                    return Err(invalid());
+                }
+                den / (num + den)
+            }
+        };
+
+        if probability > 0.0 && probability < 1.0 {
+            Ok(probability)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for OddsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OddsFormat::Probability => "probability",
+            OddsFormat::Decimal => "decimal",
+            OddsFormat::American => "american",
+            OddsFormat::Fractional => "fractional",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for OddsFormat {
+    type Err = SdkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "probability" | "prob" | "implied" => Ok(OddsFormat::Probability),
+            "decimal" | "eu" => Ok(OddsFormat::Decimal),
+            "american" | "us" | "moneyline" => Ok(OddsFormat::American),
+            "fractional" | "uk" => Ok(OddsFormat::Fractional),
+            other => Err(SdkError::InvalidInput(format!("unknown odds format: {}", other))),
+        }
+    }
+}
+
+impl MarketInfo {
+    /// YES price rendered in `format`
+    pub fn yes_odds_in(&self, format: OddsFormat) -> String {
+        format.format(self.yes_odds)
+    }
+
+    /// NO price rendered in `format`
+    pub fn no_odds_in(&self, format: OddsFormat) -> String {
+        format.format(self.no_odds)
+    }
+}
+
+/// Closest fraction to `value` with a denominator no larger than `max_den`
+fn approximate_fraction(value: f64, max_den: u64) -> (u64, u64) {
+    let mut best = (value.round() as u64, 1);
+    let mut best_err = (value - best.0 as f64).abs();
+    for den in 2..=max_den {
+        let num = (value * den as f64).round() as u64;
+        let err = (value - num as f64 / den as f64).abs();
+        if err + 1e-12 < best_err {
+            best = (num, den);
+            best_err = err;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_conversions() {
+        assert_eq!(OddsFormat::Probability.format(0.4), "40.0%");
+        assert_eq!(OddsFormat::Decimal.format(0.4), "2.50");
+        assert_eq!(OddsFormat::American.format(0.4), "+150");
+        assert_eq!(OddsFormat::American.format(2.0 / 3.0), "-200");
+        assert_eq!(OddsFormat::Fractional.format(0.4), "3/2");
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        for format in [
+            OddsFormat::Probability,
+            OddsFormat::Decimal,
+            OddsFormat::American,
+            OddsFormat::Fractional,
+        ] {
+            let parsed = format.parse(&format.format(0.4)).unwrap();
+            assert!((parsed - 0.4).abs() < 1e-9, "{} round trip", format);
+        }
+        assert!(OddsFormat::American.parse("+50").is_err());
+        assert!(OddsFormat::Decimal.parse("0.9").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Strategy parameter search: backtest every point of a grid or a random
+//! sample of a parameter space in parallel, and keep the runs no other run
+//! beats on both return and drawdown
+
+use crate::{AgentConfig, Backtester, MarketUpdate, SdkError, SimRng, TradingStrategy};
+use rand::Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Values one parameter takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParamRange {
+    /// Exactly these values
+    Values(Vec<serde_json::Value>),
+    /// `steps` evenly spaced values from `min` to `max` inclusive; random
+    /// search draws uniformly from the interval instead
+    Linear { min: f64, max: f64, steps: usize },
+    /// Every integer from `min` to `max` inclusive
+    Integers { min: i64, max: i64 },
+}
+
+impl ParamRange {
+    fn grid(&self) -> Vec<serde_json::Value> {
+        match self {
+            ParamRange::Values(values) => values.clone(),
+            ParamRange::Linear { min, max, steps } => match steps {
+                0 => Vec::new(),
+                1 => vec![serde_json::json!(min)],
+                steps => (0..*steps)
+                    .map(|step| serde_json::json!(min + (max - min) * step as f64 / (*steps - 1) as f64))
+                    .collect(),
+            },
+            ParamRange::Integers { min, max } => (*min..=*max).map(|value| serde_json::json!(value)).collect(),
+        }
+    }
+
+    fn sample(&self, rng: &mut SimRng) -> Option<serde_json::Value> {
+        match self {
+            ParamRange::Values(values) if values.is_empty() => None,
+            ParamRange::Values(values) => Some(values[rng.gen_range(0..values.len())].clone()),
+            ParamRange::Linear { min, max, .. } if min <= max => Some(serde_json::json!(rng.gen_range(*min..=*max))),
+            ParamRange::Integers { min, max } if min <= max => Some(serde_json::json!(rng.gen_range(*min..=*max))),
+            _ => None,
+        }
+    }
+}
+
+/// Parameter name -> the values to try; names are `strategy_params` keys
+pub type ParamSpace = BTreeMap<String, ParamRange>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMode {
+    /// Every combination
+    Grid,
+    /// `samples` draws; the seed makes the sample reproducible
+    Random { samples: usize, seed: u64 },
+}
+
+/// Candidate parameter sets for `space`
+pub fn candidates(space: &ParamSpace, mode: &SearchMode) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    match mode {
+        SearchMode::Grid => space.iter().fold(vec![serde_json::Map::new()], |sets, (name, range)| {
+            let values = range.grid();
+            sets.iter()
+                .flat_map(|set| {
+                    values.iter().map(move |value| {
+                        let mut set = set.clone();
+                        set.insert(name.clone(), value.clone());
+                        set
+                    })
+                })
+                .collect()
+        }),
+        SearchMode::Random { samples, seed } => {
+            let mut rng = SimRng::seeded(*seed);
+            (0..*samples)
+                .filter_map(|_| {
+                    space
+                        .iter()
+                        .map(|(name, range)| range.sample(&mut rng).map(|value| (name.clone(), value)))
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
+/// One backtest of the sweep
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizationRun {
+    /// The swept parameters only; the rest come from the base config
+    pub params: serde_json::Map<String, serde_json::Value>,
+    pub total_return: f64,
+    pub max_drawdown: f64,
+    pub trades: usize,
+    pub final_equity: f64,
+    /// The strategy refused the parameters; the run has no results
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizationReport {
+    pub runs: Vec<OptimizationRun>,
+    /// Indices into `runs` of the Pareto frontier, by rising drawdown
+    pub frontier: Vec<usize>,
+}
+
+impl OptimizationReport {
+    pub fn frontier_runs(&self) -> impl Iterator<Item = &OptimizationRun> {
+        self.frontier.iter().map(|index| &self.runs[*index])
+    }
+
+    /// One row per run: the swept parameters, the results, and whether the run is on the frontier
+    pub fn write_csv(&self, mut writer: impl Write) -> Result<(), SdkError> {
+        let names: Vec<&String> = self.runs.first().map(|run| run.params.keys().collect()).unwrap_or_default();
+        let header: Vec<String> = names
+            .iter()
+            .map(|name| csv_field(name))
+            .chain(["total_return", "max_drawdown", "trades", "final_equity", "on_frontier", "error"].map(String::from))
+            .collect();
+        writeln!(writer, "{}", header.join(","))?;
+        for (index, run) in self.runs.iter().enumerate() {
+            let params = names.iter().map(|name| match run.params.get(*name) {
+                Some(serde_json::Value::String(text)) => csv_field(text),
+                Some(value) => csv_field(&value.to_string()),
+                None => String::new(),
+            });
+            let row: Vec<String> = params
+                .chain([
+                    run.total_return.to_string(),
+                    run.max_drawdown.to_string(),
+                    run.trades.to_string(),
+                    run.final_equity.to_string(),
+                    self.frontier.contains(&index).to_string(),
+                    csv_field(run.error.as_deref().unwrap_or_default()),
+                ])
+                .collect();
+            writeln!(writer, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Quote a CSV field when it needs it
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Runs not beaten by another run on both return and drawdown, by rising drawdown.
+/// Runs that errored are never on it.
+pub fn pareto_frontier(runs: &[OptimizationRun]) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..runs.len()).filter(|index| runs[*index].error.is_none()).collect();
+    ranked.sort_by(|a, b| {
+        let (a, b) = (&runs[*a], &runs[*b]);
+        a.max_drawdown.total_cmp(&b.max_drawdown).then(b.total_return.total_cmp(&a.total_return))
+    });
+    let mut frontier = Vec::new();
+    let mut best_return = f64::NEG_INFINITY;
+    for index in ranked {
+        // Anything with a lower drawdown came first, so only a higher return earns a place
+        if runs[index].total_return > best_return {
+            best_return = runs[index].total_return;
+            frontier.push(index);
+        }
+    }
+    frontier
+}
+
+/// Backtest `base` with each candidate from `space` merged into its
+/// `strategy_params`, one fresh strategy per run, across rayon's thread pool
+pub fn optimize<F>(
+    strategy: F,
+    base: &AgentConfig,
+    space: &ParamSpace,
+    mode: &SearchMode,
+    backtester: &Backtester,
+    updates: &[MarketUpdate],
+) -> OptimizationReport
+where
+    F: Fn() -> Box<dyn TradingStrategy> + Sync,
+{
+    let runs: Vec<OptimizationRun> = candidates(space, mode)
+        .into_par_iter()
+        .map(|params| {
+            let mut config = base.clone();
+            let mut merged = config.strategy_params.as_object().cloned().unwrap_or_default();
+            merged.extend(params.clone());
+            config.strategy_params = serde_json::Value::Object(merged);
+
+            let mut strategy = strategy();
+            if let Err(e) = strategy.configure(&config.strategy_params) {
+                return OptimizationRun {
+                    params,
+                    total_return: 0.0,
+                    max_drawdown: 0.0,
+                    trades: 0,
+                    final_equity: 0.0,
+                    error: Some(e.to_string()),
+                };
+            }
+            let report = backtester.run(strategy.as_mut(), &config, updates);
+            OptimizationRun {
+                params,
+                total_return: report.total_return,
+                max_drawdown: report.max_drawdown,
+                trades: report.trades(),
+                final_equity: report.final_equity,
+                error: None,
+            }
+        })
+        .collect();
+    let frontier = pareto_frontier(&runs);
+    OptimizationReport { runs, frontier }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(total_return: f64, max_drawdown: f64) -> OptimizationRun {
+        OptimizationRun {
+            params: serde_json::Map::new(),
+            total_return,
+            max_drawdown,
+            trades: 1,
+            final_equity: 0.0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_grid_covers_every_combination() {
+        let space = ParamSpace::from([
+            ("stake".to_string(), ParamRange::Linear { min: 5.0, max: 15.0, steps: 3 }),
+            ("cooldownSecs".to_string(), ParamRange::Integers { min: 30, max: 31 }),
+        ]);
+        let sets = candidates(&space, &SearchMode::Grid);
+        assert_eq!(sets.len(), 6);
+        assert!(sets.iter().any(|set| set["stake"] == 10.0 && set["cooldownSecs"] == 31));
+
+        let random = candidates(&space, &SearchMode::Random { samples: 4, seed: 7 });
+        assert_eq!(random, candidates(&space, &SearchMode::Random { samples: 4, seed: 7 }));
+    }
+
+    #[test]
+    fn test_frontier_drops_dominated_runs() {
+        let mut failed = run(1.0, 0.0);
+        failed.error = Some("bad params".to_string());
+        let runs = vec![run(0.10, 0.05), run(0.08, 0.06), run(0.20, 0.15), run(0.05, 0.01), failed];
+        assert_eq!(pareto_frontier(&runs), vec![3, 0, 2]);
+    }
+}
@@ -0,0 +1,191 @@
+//! FastTee oracle key rotation
+//!
+//! An oracle moves to a new key by announcing it with an overlap window,
+//! during which markets accept resolutions signed by either key. The
+//! announcement is signed by the current key, or by the registry's oracle
+//! guardian when the current key is compromised. Markets apply it on their
+//! own; anyone can relay it to them.
+
+use crate::transport::Transport;
+use crate::{MarketMessage, OddsStreamSdk, OracleKind, ReadOnlyClient, SdkError, Signer};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Must match the market contract's domain separator
+const ROTATION_DOMAIN: &[u8] = b"oddsstream-key-rotation-v1";
+
+/// Who signs a market's resolutions right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleInfo {
+    pub oracle_kind: OracleKind,
+    /// Hex key a `FastTee` oracle signs with
+    pub public_key: Option<String>,
+    /// The announced next key, while both are accepted
+    pub next_public_key: Option<String>,
+    pub rotation_announced_at: Option<u64>,
+    /// Micros timestamp only `next_public_key` is accepted from
+    pub rotation_completes_at: Option<u64>,
+    /// Hex key able to rotate the oracle key without the current one
+    pub guardian_key: Option<String>,
+    /// The fallback committee may resolve too
+    pub escalated: bool,
+}
+
+impl OracleInfo {
+    /// Keys a resolution may be signed with at `now` (micros)
+    pub fn accepted_keys(&self, now: u64) -> Vec<&str> {
+        let rotated = self.rotation_completes_at.is_some_and(|completes_at| now >= completes_at);
+        let current = self.public_key.as_deref().filter(|_| !rotated);
+        current.into_iter().chain(self.next_public_key.as_deref()).collect()
+    }
+}
+
+/// A signed announcement, ready to relay with `announce_oracle_key`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRotation {
+    pub new_key: String,
+    /// Markets ignore announcements no newer than the last one they applied
+    pub announced_at: u64,
+    /// Micros both keys stay accepted for; 0 drops the old key at once
+    pub overlap: u64,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct RotationPayload<'a> {
+    old_key: &'a str,
+    new_key: &'a str,
+    announced_at: u64,
+    overlap: u64,
+}
+
+/// Digest the current key or the guardian signs
+pub fn rotation_digest(old_key: &str, new_key: &str, announced_at: u64, overlap: u64) -> [u8; 32] {
+    let payload = RotationPayload { old_key, new_key, announced_at, overlap };
+    let mut hasher = Sha256::new();
+    hasher.update(ROTATION_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("rotation payload is serializable"));
+    hasher.finalize().into()
+}
+
+/// Sign the move from `old_key` to `new_key` (both hex) with the old key or the guardian
+pub async fn sign_key_rotation(
+    signer: &dyn Signer,
+    old_key: &str,
+    new_key: &str,
+    announced_at: u64,
+    overlap: u64,
+) -> Result<KeyRotation, SdkError> {
+    let digest = rotation_digest(old_key, new_key, announced_at, overlap);
+    Ok(KeyRotation {
+        new_key: new_key.to_string(),
+        announced_at,
+        overlap,
+        signature: signer.sign(&digest).await?,
+    })
+}
+
+#[derive(Deserialize)]
+struct OracleInfoData {
+    #[serde(rename = "oracleInfo")]
+    oracle_info: Option<OracleInfo>,
+}
+
+impl Transport {
+    pub(crate) async fn oracle_info(&self, market_id: &str) -> Result<OracleInfo, SdkError> {
+        let query = r#"
+            query OracleInfo($marketId: String!) {
+                oracleInfo(marketId: $marketId) {
+                    oracleKind
+                    publicKey
+                    nextPublicKey
+                    rotationAnnouncedAt
+                    rotationCompletesAt
+                    guardianKey
+                    escalated
+                }
+            }
+        "#;
+
+        let data: OracleInfoData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.oracle_info
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+}
+
+impl OddsStreamSdk {
+    /// The market's oracle and its keys, including a rotation in progress
+    pub async fn oracle_info(&self, market_id: &str) -> Result<OracleInfo, SdkError> {
+        self.transport.oracle_info(market_id).await
+    }
+
+    /// Relay a signed rotation to every market the oracle serves
+    pub async fn announce_oracle_key(&self, rotation: &KeyRotation, market_ids: &[String]) -> Result<(), SdkError> {
+        let chains = self.resolve_market_chains(market_ids.iter().map(String::as_str)).await?;
+        for market_id in market_ids {
+            let message = MarketMessage::RotateOracleKey {
+                new_key: rotation.new_key.clone(),
+                announced_at: rotation.announced_at,
+                overlap: rotation.overlap,
+                signature: rotation.signature.clone(),
+            };
+            self.send_message(chains[market_id.as_str()], message).await?;
+        }
+        Ok(())
+    }
+
+    /// Hex key that may rotate the oracle key of markets created from now on;
+    /// the registry refuses signers without [`Role::Owner`](crate::Role::Owner)
+    pub async fn set_oracle_guardian(&self, public_key: Option<&str>) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation SetOracleGuardian($publicKey: String) {
+                setOracleGuardian(publicKey: $publicKey)
+            }
+        "#;
+        self.execute_registry_operation(mutation, serde_json::json!({ "publicKey": public_key }))
+            .await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The market's oracle and its keys, including a rotation in progress
+    pub async fn oracle_info(&self, market_id: &str) -> Result<OracleInfo, SdkError> {
+        self.transport.oracle_info(market_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalSigner;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    #[tokio::test]
+    async fn test_rotation_is_signed_over_both_keys() {
+        let signer = LocalSigner::from_bytes(&[4u8; 32]);
+        let old_key = hex::encode(signer.public_key());
+        let rotation = sign_key_rotation(&signer, &old_key, "ab12", 1_000, 60).await.unwrap();
+
+        let key = VerifyingKey::from_bytes(&signer.public_key().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&rotation.signature).unwrap();
+        assert!(key.verify(&rotation_digest(&old_key, "ab12", 1_000, 60), &signature).is_ok());
+        // The overlap is signed too, so a relay can't stretch it
+        assert!(key.verify(&rotation_digest(&old_key, "ab12", 1_000, 600), &signature).is_err());
+
+        let info = OracleInfo {
+            oracle_kind: OracleKind::FastTee,
+            public_key: Some(old_key.clone()),
+            next_public_key: Some("ab12".to_string()),
+            rotation_announced_at: Some(1_000),
+            rotation_completes_at: Some(1_060),
+            guardian_key: None,
+            escalated: false,
+        };
+        assert_eq!(info.accepted_keys(1_030), vec![old_key.as_str(), "ab12"]);
+        assert_eq!(info.accepted_keys(1_060), vec!["ab12"]);
+    }
+}
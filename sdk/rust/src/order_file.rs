@@ -0,0 +1,208 @@
+//! Order lists read from CSV or JSON files, for batch submission. Every row
+//! is checked before anything is sent, and all bad rows are reported at once
+//! so a file can be fixed in one pass.
+
+use crate::{MarketOrder, OddsFormat, OrderSide, SdkError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Columns an order file must have
+pub const REQUIRED_ORDER_COLUMNS: [&str; 3] = ["market_id", "side", "amount"];
+/// Columns an order file may have
+pub const OPTIONAL_ORDER_COLUMNS: [&str; 3] = ["max_price", "subaccount", "referral_code"];
+
+/// A row that could not be turned into an order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRowError {
+    /// 1-based; for CSV, the line number in the file
+    pub row: usize,
+    pub message: String,
+}
+
+/// What an order file held
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderFile {
+    pub orders: Vec<MarketOrder>,
+    pub errors: Vec<OrderRowError>,
+}
+
+/// Read orders from `path`: JSON if it ends in `.json`, CSV otherwise.
+/// `max_price` values are in `odds_format`.
+pub fn load_order_file(path: impl AsRef<Path>, odds_format: OddsFormat) -> Result<OrderFile, SdkError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json")) {
+        parse_order_json(&text, odds_format)
+    } else {
+        parse_order_csv(&text, odds_format)
+    }
+}
+
+/// Orders from CSV with a header row. An unknown or missing column fails
+/// the whole file; a bad value only fails its row.
+pub fn parse_order_csv(text: &str, odds_format: OddsFormat) -> Result<OrderFile, SdkError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Err(SdkError::InvalidInput("order file is empty".to_string()));
+    };
+    let columns: Vec<String> = csv_record(header).into_iter().map(|name| name.trim().to_lowercase()).collect();
+    check_columns(&columns).map_err(SdkError::InvalidInput)?;
+
+    let mut file = OrderFile::default();
+    for (index, line) in lines {
+        let values = csv_record(line);
+        let row = index + 1;
+        if values.len() != columns.len() {
+            file.errors.push(OrderRowError {
+                row,
+                message: format!("expected {} fields, found {}", columns.len(), values.len()),
+            });
+            continue;
+        }
+        let fields = columns.iter().cloned().zip(values).collect();
+        file.push(row, order_from_fields(&fields, odds_format));
+    }
+    Ok(file)
+}
+
+/// Orders from a JSON array of objects keyed by the CSV column names.
+/// Amounts and prices may be numbers or strings.
+pub fn parse_order_json(text: &str, odds_format: OddsFormat) -> Result<OrderFile, SdkError> {
+    let rows: Vec<serde_json::Value> = serde_json::from_str(text)?;
+    let mut file = OrderFile::default();
+    for (index, value) in rows.into_iter().enumerate() {
+        let row = index + 1;
+        let serde_json::Value::Object(object) = value else {
+            file.errors.push(OrderRowError { row, message: "not an object".to_string() });
+            continue;
+        };
+        let mut fields = BTreeMap::new();
+        let mut error = None;
+        for (key, value) in object {
+            match value {
+                serde_json::Value::String(text) => fields.insert(key, text),
+                serde_json::Value::Number(number) => fields.insert(key, number.to_string()),
+                serde_json::Value::Null => None,
+                other => {
+                    error = Some(format!("{} must be a string or number, not {}", key, other));
+                    break;
+                }
+            };
+        }
+        let columns: Vec<String> = fields.keys().cloned().collect();
+        let parsed = match (error, check_columns(&columns)) {
+            (Some(message), _) | (None, Err(message)) => Err(message),
+            (None, Ok(())) => order_from_fields(&fields, odds_format),
+        };
+        file.push(row, parsed);
+    }
+    Ok(file)
+}
+
+impl OrderFile {
+    fn push(&mut self, row: usize, parsed: Result<MarketOrder, String>) {
+        match parsed {
+            Ok(order) => self.orders.push(order),
+            Err(message) => self.errors.push(OrderRowError { row, message }),
+        }
+    }
+}
+
+fn check_columns(columns: &[String]) -> Result<(), String> {
+    let unknown: Vec<&str> = columns
+        .iter()
+        .map(String::as_str)
+        .filter(|column| !REQUIRED_ORDER_COLUMNS.contains(column) && !OPTIONAL_ORDER_COLUMNS.contains(column))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("unknown order column(s): {}", unknown.join(", ")));
+    }
+    let missing: Vec<&str> =
+        REQUIRED_ORDER_COLUMNS.into_iter().filter(|required| !columns.iter().any(|column| column == required)).collect();
+    if !missing.is_empty() {
+        return Err(format!("missing order column(s): {}", missing.join(", ")));
+    }
+    Ok(())
+}
+
+fn order_from_fields(fields: &BTreeMap<String, String>, odds_format: OddsFormat) -> Result<MarketOrder, String> {
+    let field = |name: &str| fields.get(name).map(|value| value.trim()).filter(|value| !value.is_empty());
+
+    let market_id = field("market_id").ok_or("market_id is empty")?.to_string();
+    let side = match field("side").map(str::to_lowercase).as_deref() {
+        Some("yes") => OrderSide::Yes,
+        Some("no") => OrderSide::No,
+        Some(other) => return Err(format!("side must be yes or no, not {}", other)),
+        None => return Err("side is empty".to_string()),
+    };
+    let amount = field("amount").ok_or("amount is empty")?;
+    match amount.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => {}
+        _ => return Err(format!("amount must be a positive number, not {}", amount)),
+    }
+    let max_price = field("max_price")
+        .map(|price| odds_format.parse(price).map_err(|e| e.to_string()))
+        .transpose()?;
+
+    Ok(MarketOrder {
+        market_id,
+        side,
+        amount: amount.to_string(),
+        max_price: max_price.map(|price| price.to_string()),
+        subaccount: field("subaccount").map(str::to_string),
+        referral_code: field("referral_code").map(str::to_string),
+    })
+}
+
+/// Fields of one CSV line; double quotes group and `""` escapes a quote
+fn csv_record(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_rows_fail_alone_and_bad_headers_fail_the_file() {
+        let text = "market_id,side,amount,max_price\n\
+                    m1,YES,10,0.6\n\
+                    m2,maybe,5,\n\
+                    \"m,3\",no,-1,\n\
+                    m4,no,2.5,\n";
+        let file = parse_order_csv(text, OddsFormat::Probability).unwrap();
+        assert_eq!(file.orders.len(), 2);
+        assert_eq!(file.orders[0].side, OrderSide::Yes);
+        assert_eq!(file.orders[0].max_price.as_deref(), Some("0.6"));
+        assert_eq!(file.orders[1].market_id, "m4");
+        assert_eq!(file.errors.iter().map(|error| error.row).collect::<Vec<_>>(), vec![3, 4]);
+
+        assert!(parse_order_csv("market_id,side,ammount\nm1,yes,1\n", OddsFormat::Probability).is_err());
+        assert!(parse_order_csv("market_id,amount\nm1,1\n", OddsFormat::Probability).is_err());
+    }
+
+    #[test]
+    fn test_json_accepts_numbers_and_reports_rows() {
+        let text = r#"[{"market_id": "m1", "side": "no", "amount": 3}, {"market_id": "m2", "side": "yes"}]"#;
+        let file = parse_order_json(text, OddsFormat::Probability).unwrap();
+        assert_eq!(file.orders[0].amount, "3");
+        assert_eq!(file.errors, vec![OrderRowError { row: 2, message: "missing order column(s): amount".to_string() }]);
+    }
+}
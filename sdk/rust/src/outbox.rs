@@ -0,0 +1,122 @@
+//! Delivery tracking for confirmations and payouts
+//!
+//! A market wraps every `BatchConfirmed` and every payout `Transfer` it
+//! sends to a user chain in a `Tracked` envelope and keeps it in its outbox
+//! until the receiving chain acknowledges it. Unacknowledged messages are
+//! resent with doubling delays, then left for a manual [`retry_message`].
+//! Since a retry can arrive after a slow original, the same message may be
+//! delivered twice.
+//!
+//! [`retry_message`]: OddsStreamSdk::retry_message
+
+use crate::transport::Transport;
+use crate::{MarketMessage, OddsStreamSdk, ReadOnlyClient, SdkError, WireFormat};
+use serde::{Deserialize, Serialize};
+
+/// A message a market sent that the receiving chain hasn't acknowledged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StuckMessage {
+    pub outbox_id: u64,
+    pub destination: String,
+    /// `BatchConfirmed` or `Transfer`
+    pub kind: String,
+    pub attempts: u32,
+    pub first_sent_at: u64,
+    pub last_sent_at: u64,
+    /// `None` once the market has given up resending on its own
+    pub next_retry_at: Option<u64>,
+}
+
+impl StuckMessage {
+    /// Only a manual `retry_message` will resend it
+    pub fn needs_manual_retry(&self) -> bool {
+        self.next_retry_at.is_none()
+    }
+}
+
+/// BCS-decode a message a market sent, unwrapping its delivery tracking
+pub fn decode_market_message(payload: &[u8]) -> Result<MarketMessage, SdkError> {
+    match WireFormat::Bcs.decode_untagged(payload)? {
+        MarketMessage::Tracked { payload, .. } => WireFormat::Bcs.decode_untagged(&payload),
+        message => Ok(message),
+    }
+}
+
+#[derive(Deserialize)]
+struct StuckMessagesData {
+    #[serde(rename = "stuckMessages")]
+    stuck_messages: Vec<StuckMessage>,
+}
+
+impl Transport {
+    pub(crate) async fn stuck_messages(
+        &self,
+        market_id: &str,
+        min_attempts: Option<u32>,
+    ) -> Result<Vec<StuckMessage>, SdkError> {
+        let query = r#"
+            query StuckMessages($marketId: String!, $minAttempts: Int) {
+                stuckMessages(marketId: $marketId, minAttempts: $minAttempts) {
+                    outboxId
+                    destination
+                    kind
+                    attempts
+                    firstSentAt
+                    lastSentAt
+                    nextRetryAt
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({ "marketId": market_id, "minAttempts": min_attempts });
+        let data: StuckMessagesData = self.graphql_query_fresh(query, variables).await?;
+        Ok(data.stuck_messages)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Unacknowledged messages the market has sent at least `min_attempts` times (default 2)
+    pub async fn stuck_messages(
+        &self,
+        market_id: &str,
+        min_attempts: Option<u32>,
+    ) -> Result<Vec<StuckMessage>, SdkError> {
+        self.transport.stuck_messages(market_id, min_attempts).await
+    }
+
+    /// Have the market resend an outbox message now, even one it gave up on. The market only
+    /// takes this from the message's recipient, so send it from that user chain.
+    pub async fn retry_message(&self, market_id: &str, outbox_id: u64) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.send_message(market_chain_id, MarketMessage::RetryMessage { outbox_id }).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Unacknowledged messages the market has sent at least `min_attempts` times (default 2)
+    pub async fn stuck_messages(
+        &self,
+        market_id: &str,
+        min_attempts: Option<u32>,
+    ) -> Result<Vec<StuckMessage>, SdkError> {
+        self.transport.stuck_messages(market_id, min_attempts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linera_sdk::base::ChainId;
+
+    #[test]
+    fn test_tracked_messages_decode_as_their_payload() {
+        let claim = MarketMessage::Claim { user_chain_id: ChainId::from([3u8; 32]) };
+        let inner = WireFormat::Bcs.encode_untagged(&claim).unwrap();
+        let tracked = WireFormat::Bcs
+            .encode_untagged(&MarketMessage::Tracked { outbox_id: 7, payload: inner.clone() })
+            .unwrap();
+        assert!(matches!(decode_market_message(&tracked).unwrap(), MarketMessage::Claim { .. }));
+        assert!(matches!(decode_market_message(&inner).unwrap(), MarketMessage::Claim { .. }));
+    }
+}
@@ -0,0 +1,142 @@
+//! Parlay orders: one stake across several markets, paid only if every leg wins
+//!
+//! Winnings come out of the coordinating market's parlay reserve, which
+//! funders bankroll and lost stakes grow. Once every leg is quoted the market
+//! sets the winnings aside; a parlay the reserve can't cover is voided and its
+//! stake refunded.
+
+use crate::{MarketMessage, MarketInfo, OddsStreamSdk, OrderSide, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+
+/// Leg limit enforced by the coordinating market
+pub const MAX_PARLAY_LEGS: usize = 8;
+
+/// One leg as sent to the coordinating market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParlayLeg {
+    pub market_id: String,
+    pub market_chain: ChainId,
+    /// true = YES
+    pub outcome: bool,
+}
+
+/// A parlay ready to submit; build with `ParlayBuilder`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParlayOrder {
+    pub stake: Amount,
+    pub legs: Vec<(String, OrderSide)>,
+}
+
+impl ParlayOrder {
+    /// Payout multiplier at the given markets' current odds; the market locks its own quote on placement
+    pub fn estimated_multiplier(&self, markets: &[MarketInfo]) -> Option<f64> {
+        self.legs
+            .iter()
+            .map(|(market_id, side)| {
+                let market = markets.iter().find(|m| &m.id == market_id)?;
+                let probability = match side {
+                    OrderSide::Yes => market.yes_odds,
+                    OrderSide::No => market.no_odds,
+                };
+                (probability > 0.0).then(|| 1.0 / probability)
+            })
+            .product()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParlayBuilder {
+    stake: Amount,
+    legs: Vec<(String, OrderSide)>,
+}
+
+impl ParlayBuilder {
+    pub fn new(stake: Amount) -> Self {
+        Self { stake, legs: Vec::new() }
+    }
+
+    pub fn leg(mut self, market_id: impl Into<String>, side: OrderSide) -> Self {
+        self.legs.push((market_id.into(), side));
+        self
+    }
+
+    pub fn build(self) -> Result<ParlayOrder, SdkError> {
+        if self.legs.len() < 2 || self.legs.len() > MAX_PARLAY_LEGS {
+            return Err(SdkError::InvalidInput(format!(
+                "a parlay needs 2 to {} legs, got {}",
+                MAX_PARLAY_LEGS,
+                self.legs.len()
+            )));
+        }
+        let mut market_ids: Vec<&String> = self.legs.iter().map(|(id, _)| id).collect();
+        market_ids.sort();
+        market_ids.dedup();
+        if market_ids.len() != self.legs.len() {
+            return Err(SdkError::InvalidInput("a parlay cannot use a market twice".to_string()));
+        }
+        Ok(ParlayOrder { stake: self.stake, legs: self.legs })
+    }
+}
+
+impl OddsStreamSdk {
+    /// Submit a parlay; the first leg's market coordinates quoting and settlement
+    pub async fn place_parlay(
+        &self,
+        order: &ParlayOrder,
+        user_chain_id: ChainId,
+    ) -> Result<String, SdkError> {
+        self.ensure_not_limited(user_chain_id).await?;
+        let mut legs = Vec::with_capacity(order.legs.len());
+        for (market_id, side) in &order.legs {
+            legs.push(ParlayLeg {
+                market_id: market_id.clone(),
+                market_chain: self.resolve_market_chain(market_id).await?,
+                outcome: matches!(side, OrderSide::Yes),
+            });
+        }
+
+        let coordinator = legs[0].market_chain;
+        self.approve_own_payments(user_chain_id, coordinator, order.stake).await?;
+        let message = MarketMessage::PlaceParlay {
+            user_chain_id,
+            stake: order.stake,
+            legs,
+        };
+        self.send_message(coordinator, message).await
+    }
+
+    /// Bankroll parlays coordinated by `market_id`; funders share what is left
+    /// of the reserve when the market is archived
+    pub async fn fund_parlay_reserve(&self, market_id: &str, amount: Amount) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.approve_payments(market_chain_id, amount).await?;
+        let message = MarketMessage::FundParlayReserve { funder: self.chain_id, amount };
+        self.send_message(market_chain_id, message).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parlay_builder() {
+        let single = ParlayBuilder::new(Amount::ONE).leg("m1", OrderSide::Yes).build();
+        assert!(single.is_err());
+
+        let repeated = ParlayBuilder::new(Amount::ONE)
+            .leg("m1", OrderSide::Yes)
+            .leg("m1", OrderSide::No)
+            .build();
+        assert!(repeated.is_err());
+
+        let order = ParlayBuilder::new(Amount::ONE)
+            .leg("m1", OrderSide::Yes)
+            .leg("m2", OrderSide::No)
+            .build()
+            .unwrap();
+        assert_eq!(order.legs.len(), 2);
+    }
+}
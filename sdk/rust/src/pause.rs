@@ -0,0 +1,94 @@
+//! Protocol-wide emergency pause
+//!
+//! Registry owners can flip a switch that every market follows: while it is
+//! on, markets refuse new orders, liquidity and parlays with
+//! [`RejectionReason::ProtocolPaused`](crate::RejectionReason::ProtocolPaused),
+//! but cancels, order reductions and claims keep working. Markets learn of a
+//! flip from the registry's event stream, so one may lag by a block; its own
+//! view is [`MarketLifecycle::protocol_paused`](crate::MarketLifecycle::protocol_paused).
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use serde::{Deserialize, Serialize};
+
+/// The registry's pause switch
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolStatus {
+    pub paused: bool,
+    /// Why governance paused, as given to `set_protocol_paused`
+    pub reason: Option<String>,
+    /// Micros timestamp of the pause
+    pub paused_at: Option<u64>,
+}
+
+impl ProtocolStatus {
+    /// One line for the top of any listing, or `None` while trading normally
+    pub fn banner(&self) -> Option<String> {
+        if !self.paused {
+            return None;
+        }
+        Some(match self.reason.as_deref().filter(|reason| !reason.is_empty()) {
+            Some(reason) => format!("PROTOCOL PAUSED: {} (cancels and claims still work)", reason),
+            None => "PROTOCOL PAUSED (cancels and claims still work)".to_string(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ProtocolStatusData {
+    #[serde(rename = "protocolStatus")]
+    protocol_status: ProtocolStatus,
+}
+
+impl Transport {
+    pub(crate) async fn protocol_status(&self) -> Result<ProtocolStatus, SdkError> {
+        let query = r#"
+            query ProtocolStatus {
+                protocolStatus { paused reason pausedAt }
+            }
+        "#;
+
+        // Never cached: a stale "not paused" is exactly what this must not return
+        let data: ProtocolStatusData = self.graphql_query_fresh(query, serde_json::json!({})).await?;
+        Ok(data.protocol_status)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Whether governance has paused trading on every market
+    pub async fn protocol_status(&self) -> Result<ProtocolStatus, SdkError> {
+        self.transport.protocol_status().await
+    }
+
+    /// Pause or resume every market; the registry refuses signers without [`Role::Owner`](crate::Role::Owner)
+    pub async fn set_protocol_paused(&self, paused: bool, reason: &str) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation SetPaused($paused: Boolean!, $reason: String!) {
+                setPaused(paused: $paused, reason: $reason)
+            }
+        "#;
+        self.execute_registry_operation(mutation, serde_json::json!({ "paused": paused, "reason": reason }))
+            .await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Whether governance has paused trading on every market
+    pub async fn protocol_status(&self) -> Result<ProtocolStatus, SdkError> {
+        self.transport.protocol_status().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner_only_while_paused() {
+        let status: ProtocolStatus =
+            serde_json::from_str(r#"{"paused":true,"reason":"oracle incident","pausedAt":5}"#).unwrap();
+        assert_eq!(status.banner().unwrap(), "PROTOCOL PAUSED: oracle incident (cancels and claims still work)");
+        assert_eq!(ProtocolStatus::default().banner(), None);
+    }
+}
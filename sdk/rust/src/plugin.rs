@@ -0,0 +1,267 @@
+//! Strategies compiled to WebAssembly and loaded at runtime, so a daemon can
+//! pick up a new build of a strategy without restarting its agents.
+//!
+//! A plugin runs sandboxed: it may not import anything, each call gets a
+//! fixed fuel budget and memory is capped. It talks JSON through its own
+//! memory and exports:
+//!
+//! - `memory`
+//! - `oddsstream_alloc(len: i32) -> i32`: space for an input of `len` bytes
+//! - `configure(ptr: i32, len: i32) -> i64`: takes the strategy parameters
+//!   and returns `null`, or an error message as a JSON string
+//! - `on_update(ptr: i32, len: i32) -> i64`: takes `{"update", "context"}`
+//!   and returns a JSON array of `AgentDecision`s
+//! - `on_tick(ptr: i32, len: i32) -> i64`: takes `{"context"}`, returns the same
+//!
+//! Outputs are returned as `ptr << 32 | len`.
+
+use crate::{
+    AgentContext, AgentDecision, AgentFleet, LpPosition, MarketInfo, MarketUpdate, ResearchSignal, SdkError, Shutdown,
+    TradingStrategy,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use wasmtime::{Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+
+/// Largest output a plugin call may return
+const MAX_PLUGIN_OUTPUT: usize = 1024 * 1024;
+
+/// Sandbox bounds of a plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginLimits {
+    /// Fuel for one hook call; running out traps the call
+    pub fuel_per_call: u64,
+    pub max_memory_bytes: usize,
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self { fuel_per_call: 50_000_000, max_memory_bytes: 64 * 1024 * 1024 }
+    }
+}
+
+/// What a plugin sees of the `AgentContext`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContextView<'a> {
+    chain_id: Option<String>,
+    now_micros: u64,
+    updates: &'a HashMap<String, MarketUpdate>,
+    markets: &'a HashMap<String, MarketInfo>,
+    lp_positions: &'a HashMap<String, LpPosition>,
+    exposure: f64,
+    research: &'a HashMap<String, ResearchSignal>,
+}
+
+impl<'a> From<&'a AgentContext> for ContextView<'a> {
+    fn from(context: &'a AgentContext) -> Self {
+        Self {
+            chain_id: context.chain_id.map(|chain_id| chain_id.to_string()),
+            now_micros: context.now_micros,
+            updates: &context.updates,
+            markets: &context.markets,
+            lp_positions: &context.lp_positions,
+            exposure: context.exposure,
+            research: &context.research,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Hook {
+    Configure,
+    OnUpdate,
+    OnTick,
+}
+
+/// A strategy running in a WebAssembly sandbox, named after its file
+pub struct WasmStrategy {
+    name: String,
+    limits: PluginLimits,
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    configure: TypedFunc<(i32, i32), i64>,
+    on_update: TypedFunc<(i32, i32), i64>,
+    on_tick: TypedFunc<(i32, i32), i64>,
+}
+
+fn plugin_error(name: &str, error: impl std::fmt::Display) -> SdkError {
+    SdkError::InvalidInput(format!("plugin {}: {}", name, error))
+}
+
+impl WasmStrategy {
+    /// Compile and instantiate the module in `bytes`; fails on imports or missing exports
+    pub fn new(name: &str, bytes: &[u8], limits: PluginLimits) -> Result<Self, SdkError> {
+        let error = |e: wasmtime::Error| plugin_error(name, e);
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(error)?;
+        let module = Module::new(&engine, bytes).map_err(error)?;
+        if let Some(import) = module.imports().next() {
+            return Err(plugin_error(name, format!("imports {}::{}; plugins may not import", import.module(), import.name())));
+        }
+
+        let store_limits = StoreLimitsBuilder::new().memory_size(limits.max_memory_bytes).instances(1).build();
+        let mut store = Store::new(&engine, store_limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(limits.fuel_per_call).map_err(error)?;
+        let instance = Instance::new(&mut store, &module, &[]).map_err(error)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| plugin_error(name, "exports no memory"))?;
+        Ok(Self {
+            name: name.to_string(),
+            limits,
+            alloc: instance.get_typed_func(&mut store, "oddsstream_alloc").map_err(error)?,
+            configure: instance.get_typed_func(&mut store, "configure").map_err(error)?,
+            on_update: instance.get_typed_func(&mut store, "on_update").map_err(error)?,
+            on_tick: instance.get_typed_func(&mut store, "on_tick").map_err(error)?,
+            store,
+            memory,
+        })
+    }
+
+    /// Load the plugin at `path`, named after the file's stem
+    pub fn load(path: impl AsRef<Path>, limits: PluginLimits) -> Result<Self, SdkError> {
+        let path = path.as_ref();
+        let name = path.file_stem().map_or("plugin".to_string(), |stem| stem.to_string_lossy().into_owned());
+        Self::new(&name, &std::fs::read(path)?, limits)
+    }
+
+    /// Trial run in the sandbox: configure with `params`, then one tick on an
+    /// empty context, which must return well-formed decisions
+    pub fn validate(&mut self, params: &serde_json::Value) -> Result<(), SdkError> {
+        TradingStrategy::configure(self, params)?;
+        let context = AgentContext::default();
+        let input = serde_json::json!({ "context": ContextView::from(&context) });
+        self.decisions(Hook::OnTick, &input)?;
+        Ok(())
+    }
+
+    fn call(&mut self, hook: Hook, input: &serde_json::Value) -> Result<serde_json::Value, SdkError> {
+        let error = |e: wasmtime::Error| plugin_error(&self.name, e);
+        let input = serde_json::to_vec(input)?;
+        let length = i32::try_from(input.len()).map_err(|_| plugin_error(&self.name, "input too large"))?;
+        self.store.set_fuel(self.limits.fuel_per_call).map_err(error)?;
+
+        let pointer = self.alloc.call(&mut self.store, length).map_err(error)?;
+        self.memory
+            .write(&mut self.store, pointer as u32 as usize, &input)
+            .map_err(|e| plugin_error(&self.name, e))?;
+        let function = match hook {
+            Hook::Configure => &self.configure,
+            Hook::OnUpdate => &self.on_update,
+            Hook::OnTick => &self.on_tick,
+        };
+        let packed = function.call(&mut self.store, (pointer, length)).map_err(error)?;
+
+        let (start, length) = ((packed as u64 >> 32) as usize, packed as u32 as usize);
+        if length > MAX_PLUGIN_OUTPUT {
+            return Err(plugin_error(&self.name, format!("output of {} bytes is too large", length)));
+        }
+        let mut output = vec![0; length];
+        self.memory
+            .read(&self.store, start, &mut output)
+            .map_err(|e| plugin_error(&self.name, e))?;
+        Ok(serde_json::from_slice(&output)?)
+    }
+
+    fn decisions(&mut self, hook: Hook, input: &serde_json::Value) -> Result<Vec<AgentDecision>, SdkError> {
+        Ok(serde_json::from_value(self.call(hook, input)?)?)
+    }
+
+    /// Decisions from a hook; a plugin that traps or answers badly decides nothing this time
+    fn decide(&mut self, hook: Hook, input: serde_json::Value) -> Vec<AgentDecision> {
+        match self.decisions(hook, &input) {
+            Ok(decisions) => decisions,
+            Err(e) => {
+                tracing::warn!(plugin = %self.name, error = %e, "plugin call failed");
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl TradingStrategy for WasmStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_update(&mut self, update: &MarketUpdate, context: &AgentContext) -> Vec<AgentDecision> {
+        self.decide(Hook::OnUpdate, serde_json::json!({ "update": update, "context": ContextView::from(context) }))
+    }
+
+    fn on_tick(&mut self, context: &AgentContext) -> Vec<AgentDecision> {
+        self.decide(Hook::OnTick, serde_json::json!({ "context": ContextView::from(context) }))
+    }
+
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), SdkError> {
+        match self.call(Hook::Configure, params)? {
+            serde_json::Value::Null => Ok(()),
+            serde_json::Value::String(error) => Err(plugin_error(&self.name, error)),
+            other => Err(plugin_error(&self.name, format!("configure returned {}", other))),
+        }
+    }
+}
+
+/// Swap the plugin at `path` into agent `agent` of `fleet` whenever the file
+/// changes, checking every `poll`. A new build that fails to load or to pass
+/// `WasmStrategy::validate` with `params` is logged and the running strategy
+/// keeps trading; the agent configures one that passes with its own current
+/// parameters as it takes over.
+pub fn watch_strategy_plugin(
+    path: impl Into<PathBuf>,
+    agent: String,
+    fleet: Arc<AgentFleet>,
+    params: serde_json::Value,
+    limits: PluginLimits,
+    poll: Duration,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    let path = path.into();
+    tokio::spawn(async move {
+        let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        // The build in place at startup is the one the agent was started with
+        let mut seen: Option<SystemTime> = modified(&path);
+        let mut ticker = tokio::time::interval(poll);
+        loop {
+            tokio::select! {
+                _ = shutdown.triggered() => break,
+                _ = ticker.tick() => {}
+            }
+            let current = modified(&path);
+            if current.is_none() || current == seen {
+                continue;
+            }
+            seen = current;
+            let swapped = WasmStrategy::load(&path, limits)
+                .and_then(|mut strategy| strategy.validate(&params).map(|()| strategy))
+                .and_then(|strategy| fleet.swap_strategy(&agent, Box::new(strategy)));
+            match swapped {
+                // The agent reports whether it took the strategy as an event
+                Ok(()) => tracing::info!(%agent, path = %path.display(), "plugin handed to agent"),
+                Err(e) => tracing::warn!(%agent, path = %path.display(), error = %e, "plugin rejected"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_refuses_imports_and_missing_hooks() {
+        let importing = br#"(module (import "env" "now" (func)) (memory (export "memory") 1))"#;
+        let error = WasmStrategy::new("clock", importing, PluginLimits::default()).err().unwrap();
+        assert!(error.to_string().contains("may not import"));
+
+        let incomplete = br#"(module (memory (export "memory") 1))"#;
+        assert!(WasmStrategy::new("empty", incomplete, PluginLimits::default()).is_err());
+    }
+}
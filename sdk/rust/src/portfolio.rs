@@ -0,0 +1,180 @@
+//! Portfolio-level P&L and exposure analytics across markets
+
+use crate::{OddsStreamSdk, SdkError};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A user's holding in one market together with the market's current odds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub market_id: String,
+    /// Sub-account the shares are attributed to
+    #[serde(default)]
+    pub subaccount: Option<String>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub yes_shares: f64,
+    pub no_shares: f64,
+    /// Amount paid for the shares still held
+    pub cost_basis: f64,
+    /// P&L already locked in by claims and closed positions
+    pub realized_pnl: f64,
+    pub yes_odds: f64,
+    pub no_odds: f64,
+}
+
+impl Position {
+    /// Mark-to-market value: each share pays 1 if its side wins
+    pub fn market_value(&self) -> f64 {
+        self.yes_shares * self.yes_odds + self.no_shares * self.no_odds
+    }
+
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.market_value() - self.cost_basis
+    }
+}
+
+/// Aggregated view over every position held by a user chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioReport {
+    pub positions: Vec<Position>,
+    pub total_cost: f64,
+    pub market_value: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    /// Market value per category; uncategorized markets are under "uncategorized"
+    pub exposure_by_category: BTreeMap<String, f64>,
+    /// Market value per tag; a market counts towards each of its tags
+    pub exposure_by_tag: BTreeMap<String, f64>,
+    /// Share of market value held in the single largest position (0-1)
+    pub largest_position_share: f64,
+    /// Herfindahl-Hirschman index of position weights (1/n for equal weights, 1 for a single market)
+    pub herfindahl_index: f64,
+}
+
+impl PortfolioReport {
+    pub fn from_positions(positions: Vec<Position>) -> Self {
+        let mut exposure_by_category = BTreeMap::new();
+        let mut exposure_by_tag = BTreeMap::new();
+        let mut total_cost = 0.0;
+        let mut market_value = 0.0;
+        let mut realized_pnl = 0.0;
+
+        for position in &positions {
+            let value = position.market_value();
+            total_cost += position.cost_basis;
+            market_value += value;
+            realized_pnl += position.realized_pnl;
+
+            let category = position
+                .category
+                .clone()
+                .unwrap_or_else(|| "uncategorized".to_string());
+            *exposure_by_category.entry(category).or_insert(0.0) += value;
+            for tag in &position.tags {
+                *exposure_by_tag.entry(tag.clone()).or_insert(0.0) += value;
+            }
+        }
+
+        let (largest_position_share, herfindahl_index) = if market_value > 0.0 {
+            let weights = positions.iter().map(|p| p.market_value() / market_value);
+            weights.fold((0.0_f64, 0.0), |(max, hhi), w| (max.max(w), hhi + w * w))
+        } else {
+            (0.0, 0.0)
+        };
+
+        Self {
+            total_cost,
+            market_value,
+            realized_pnl,
+            unrealized_pnl: market_value - total_cost,
+            exposure_by_category,
+            exposure_by_tag,
+            largest_position_share,
+            herfindahl_index,
+            positions,
+        }
+    }
+
+    pub fn total_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl
+    }
+}
+
+#[derive(Deserialize)]
+struct PositionsData {
+    #[serde(rename = "userPositions")]
+    user_positions: Vec<Position>,
+}
+
+impl OddsStreamSdk {
+    /// Fetch all positions held by a user chain
+    pub async fn user_positions(&self, user_chain_id: ChainId) -> Result<Vec<Position>, SdkError> {
+        let query = r#"
+            query UserPositions($userChainId: String!) {
+                userPositions(userChainId: $userChainId) {
+                    marketId
+                    subaccount
+                    category
+                    tags
+                    yesShares
+                    noShares
+                    costBasis
+                    realizedPnl
+                    yesOdds
+                    noOdds
+                }
+            }
+        "#;
+
+        let data: PositionsData = self
+            .graphql_query_fresh(query, serde_json::json!({ "userChainId": user_chain_id.to_string() }))
+            .await?;
+        Ok(data.user_positions)
+    }
+
+    /// Build a P&L and exposure report over every position held by a user chain
+    pub async fn portfolio_report(&self, user_chain_id: ChainId) -> Result<PortfolioReport, SdkError> {
+        let positions = self.user_positions(user_chain_id).await?;
+        Ok(PortfolioReport::from_positions(positions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(market_id: &str, category: &str, yes_shares: f64, cost_basis: f64) -> Position {
+        Position {
+            market_id: market_id.to_string(),
+            subaccount: None,
+            category: Some(category.to_string()),
+            tags: vec!["live".to_string()],
+            yes_shares,
+            no_shares: 0.0,
+            cost_basis,
+            realized_pnl: 0.0,
+            yes_odds: 0.5,
+            no_odds: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_portfolio_aggregation() {
+        let report = PortfolioReport::from_positions(vec![
+            position("m1", "sports", 300.0, 120.0),
+            position("m2", "politics", 100.0, 60.0),
+        ]);
+
+        assert_eq!(report.market_value, 200.0);
+        assert_eq!(report.unrealized_pnl, 20.0);
+        assert_eq!(report.exposure_by_category["sports"], 150.0);
+        assert_eq!(report.exposure_by_tag["live"], 200.0);
+        assert_eq!(report.largest_position_share, 0.75);
+        assert!((report.herfindahl_index - 0.625).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,259 @@
+//! Merkle proofs of positions, so a holder can check their shares and payout
+//! entitlement against a market's position root instead of trusting the
+//! indexer that served them
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+// Must match the market contract's tree
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// A position together with its authentication path in the market's tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionProof {
+    pub market_id: String,
+    pub owner: ChainId,
+    pub yes_shares: Amount,
+    pub no_shares: Amount,
+    pub cost_basis: Amount,
+    pub claimed: bool,
+    pub index: u64,
+    pub leaf_count: u64,
+    /// Bottom-up; levels where the node has no sibling are skipped
+    pub siblings: Vec<[u8; 32]>,
+    /// Root the serving node claims; only as trustworthy as that node
+    pub root: [u8; 32],
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+impl PositionProof {
+    fn leaf_hash(&self) -> [u8; 32] {
+        let position = (self.yes_shares, self.no_shares, self.cost_basis, self.claimed);
+        let mut hasher = Sha256::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(bcs::to_bytes(&(self.owner, position)).expect("position is serializable"));
+        hasher.finalize().into()
+    }
+
+    /// Fold the leaf up the path. `None` if the path doesn't fit the tree shape.
+    pub fn computed_root(&self) -> Option<[u8; 32]> {
+        if self.index >= self.leaf_count {
+            return None;
+        }
+        let mut hash = self.leaf_hash();
+        let mut node = self.index;
+        let mut width = self.leaf_count;
+        let mut siblings = self.siblings.iter();
+        while width > 1 {
+            // The last node of an odd level is carried up as is
+            if !(node == width - 1 && width % 2 == 1) {
+                let sibling = siblings.next()?;
+                hash = if node % 2 == 0 { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+            }
+            node /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none().then_some(hash)
+    }
+
+    /// Whether the position is committed to by `trusted_root`, obtained from
+    /// a node other than the one that served the proof
+    pub fn verify(&self, trusted_root: &[u8; 32]) -> bool {
+        self.computed_root().as_ref() == Some(trusted_root)
+    }
+
+    /// What the position pays if `outcome` wins, given the market's final pools
+    pub fn payout(&self, outcome: bool, pool_yes: Amount, pool_no: Amount) -> Amount {
+        let winning_pool = if outcome { pool_yes } else { pool_no };
+        let shares = if outcome { self.yes_shares } else { self.no_shares };
+        if winning_pool == Amount::ZERO {
+            return Amount::ZERO;
+        }
+        let total = u128::from(pool_yes) + u128::from(pool_no);
+        Amount::from_attos(total * u128::from(shares) / u128::from(winning_pool))
+    }
+}
+
+#[derive(Deserialize)]
+struct PositionProofData {
+    #[serde(rename = "positionProof")]
+    position_proof: Option<RawPositionProof>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPositionProof {
+    yes_shares: String,
+    no_shares: String,
+    cost_basis: String,
+    claimed: bool,
+    index: u64,
+    leaf_count: u64,
+    siblings: Vec<String>,
+    root: String,
+}
+
+#[derive(Deserialize)]
+struct PositionRootData {
+    #[serde(rename = "positionRoot")]
+    position_root: Option<String>,
+}
+
+fn decode_hash(hex_hash: &str) -> Result<[u8; 32], SdkError> {
+    let bytes = hex::decode(hex_hash).map_err(|e| SdkError::InvalidInput(format!("merkle hash: {}", e)))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| SdkError::InvalidInput(format!("merkle hash has {} bytes", bytes.len())))
+}
+
+fn parse_amount(raw: &str) -> Result<Amount, SdkError> {
+    Amount::from_str(raw).map_err(|e| SdkError::InvalidInput(format!("invalid amount {}: {}", raw, e)))
+}
+
+impl Transport {
+    pub(crate) async fn position_root(&self, market_id: &str) -> Result<[u8; 32], SdkError> {
+        let query = r#"
+            query PositionRoot($marketId: String!) {
+                positionRoot(marketId: $marketId)
+            }
+        "#;
+
+        let data: PositionRootData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let root = data
+            .position_root
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+        decode_hash(&root)
+    }
+
+    pub(crate) async fn get_position_proof(
+        &self,
+        market_id: &str,
+        owner: ChainId,
+    ) -> Result<Option<PositionProof>, SdkError> {
+        let query = r#"
+            query PositionProof($marketId: String!, $owner: String!) {
+                positionProof(marketId: $marketId, owner: $owner) {
+                    yesShares
+                    noShares
+                    costBasis
+                    claimed
+                    index
+                    leafCount
+                    siblings
+                    root
+                }
+            }
+        "#;
+
+        let data: PositionProofData = self
+            .graphql_query_fresh(
+                query,
+                serde_json::json!({ "marketId": market_id, "owner": owner.to_string() }),
+            )
+            .await?;
+        let Some(raw) = data.position_proof else {
+            return Ok(None);
+        };
+        Ok(Some(PositionProof {
+            market_id: market_id.to_string(),
+            owner,
+            yes_shares: parse_amount(&raw.yes_shares)?,
+            no_shares: parse_amount(&raw.no_shares)?,
+            cost_basis: parse_amount(&raw.cost_basis)?,
+            claimed: raw.claimed,
+            index: raw.index,
+            leaf_count: raw.leaf_count,
+            siblings: raw.siblings.iter().map(|s| decode_hash(s)).collect::<Result<_, _>>()?,
+            root: decode_hash(&raw.root)?,
+        }))
+    }
+}
+
+impl OddsStreamSdk {
+    /// The market's current position root, as reported by the connected node
+    pub async fn position_root(&self, market_id: &str) -> Result<[u8; 32], SdkError> {
+        self.transport.position_root(market_id).await
+    }
+
+    /// Proof of `owner`'s position in `market_id`, or `None` if it holds none.
+    /// Check it with [`PositionProof::verify`] against a root you trust.
+    pub async fn get_position_proof(
+        &self,
+        market_id: &str,
+        owner: ChainId,
+    ) -> Result<Option<PositionProof>, SdkError> {
+        self.transport.get_position_proof(market_id, owner).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The market's current position root, as reported by the connected node
+    pub async fn position_root(&self, market_id: &str) -> Result<[u8; 32], SdkError> {
+        self.transport.position_root(market_id).await
+    }
+
+    /// Proof of `owner`'s position in `market_id`, or `None` if it holds none.
+    /// Check it with [`PositionProof::verify`] against a root you trust.
+    pub async fn get_position_proof(
+        &self,
+        market_id: &str,
+        owner: ChainId,
+    ) -> Result<Option<PositionProof>, SdkError> {
+        self.transport.get_position_proof(market_id, owner).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof(index: u64, leaf_count: u64, yes_shares: u128) -> PositionProof {
+        PositionProof {
+            market_id: "m1".to_string(),
+            owner: ChainId::default(),
+            yes_shares: Amount::from_tokens(yes_shares),
+            no_shares: Amount::ZERO,
+            cost_basis: Amount::from_tokens(yes_shares / 2),
+            claimed: false,
+            index,
+            leaf_count,
+            siblings: Vec::new(),
+            root: [0; 32],
+        }
+    }
+
+    #[test]
+    fn test_proof_verifies_in_three_leaf_tree() {
+        let leaves: Vec<[u8; 32]> = (0..3).map(|i| proof(i, 3, 10 * (i as u128 + 1)).leaf_hash()).collect();
+        // The third leaf is unpaired and carried up to the root level
+        let root = node_hash(&node_hash(&leaves[0], &leaves[1]), &leaves[2]);
+
+        let mut second = proof(1, 3, 20);
+        second.siblings = vec![leaves[0], leaves[2]];
+        assert!(second.verify(&root));
+
+        let mut third = proof(2, 3, 30);
+        third.siblings = vec![node_hash(&leaves[0], &leaves[1])];
+        assert!(third.verify(&root));
+
+        // Inflating the shares, or a path for a different tree size, breaks the proof
+        second.yes_shares = Amount::from_tokens(200);
+        assert!(!second.verify(&root));
+        third.leaf_count = 4;
+        assert!(!third.verify(&root));
+    }
+}
@@ -0,0 +1,273 @@
+//! Order previews: what an order would cost, line by line, before it is sent
+
+use crate::{AmmPools, FeeSchedule, MarketOrder, OddsStreamSdk, OrderSide, Position, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+/// Cost breakdown of a single order at the market's current state.
+///
+/// `oracle_fee` and `protocol_fee` are the parts of `taker_fee` passed on to
+/// the oracle and the protocol; they are shown for transparency and are not
+/// charged on top of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderQuote {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub shares: f64,
+    /// Implied probability the order fills at
+    pub price: f64,
+    /// Implied probability once the order has moved the pools
+    pub price_after: f64,
+    /// `price_after - price`: how far the order moves its own side
+    pub price_impact: f64,
+    pub amm_cost: f64,
+    pub taker_fee: f64,
+    pub oracle_fee: f64,
+    pub protocol_fee: f64,
+    pub relayer_fee: f64,
+    /// Everything debited from the user chain
+    pub total_cost: f64,
+    /// `total_cost` per share, fees included
+    pub effective_price: f64,
+}
+
+impl OrderQuote {
+    /// Price `shares` of `side` against `pools` the way the market contract fills it
+    pub fn compute(
+        market_id: &str,
+        mut pools: AmmPools,
+        schedule: &FeeSchedule,
+        side: OrderSide,
+        shares: f64,
+        relayer_fee: f64,
+    ) -> Self {
+        let price = pools.odds(side);
+        let amm_cost = pools.buy(side, shares);
+        let price_after = pools.odds(side);
+
+        let taker_fee = amm_cost * f64::from(schedule.taker_fee_bps) / BPS_DENOMINATOR;
+        let oracle_fee = taker_fee * f64::from(schedule.oracle_share_bps) / BPS_DENOMINATOR;
+        let protocol_fee = taker_fee * f64::from(schedule.protocol_share_bps) / BPS_DENOMINATOR;
+        let total_cost = amm_cost + taker_fee + relayer_fee;
+
+        Self {
+            market_id: market_id.to_string(),
+            side,
+            shares,
+            price,
+            price_after,
+            price_impact: price_after - price,
+            amm_cost,
+            taker_fee,
+            oracle_fee,
+            protocol_fee,
+            relayer_fee,
+            total_cost,
+            effective_price: if shares > 0.0 { total_cost / shares } else { price },
+        }
+    }
+
+    /// How far above `price` the shares fill on average, before fees
+    pub fn slippage(&self) -> f64 {
+        if self.shares > 0.0 {
+            self.amm_cost / self.shares - self.price
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A quote with the position it leaves behind, for a last look before sending
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRisk {
+    pub quote: OrderQuote,
+    /// Sub-account the shares go to
+    pub subaccount: Option<String>,
+    pub yes_shares_before: f64,
+    pub no_shares_before: f64,
+    pub yes_shares_after: f64,
+    pub no_shares_after: f64,
+    /// Cost basis of the position once the order's total cost is added
+    pub cost_basis_after: f64,
+}
+
+impl OrderRisk {
+    /// Apply `quote` to a holding of `(yes_shares, no_shares, cost_basis)`
+    pub fn new(quote: OrderQuote, subaccount: Option<String>, held: (f64, f64, f64)) -> Self {
+        let (yes_shares, no_shares, cost_basis) = held;
+        let (yes_bought, no_bought) = match quote.side {
+            OrderSide::Yes => (quote.shares, 0.0),
+            OrderSide::No => (0.0, quote.shares),
+        };
+        Self {
+            subaccount,
+            yes_shares_before: yes_shares,
+            no_shares_before: no_shares,
+            yes_shares_after: yes_shares + yes_bought,
+            no_shares_after: no_shares + no_bought,
+            cost_basis_after: cost_basis + quote.total_cost,
+            quote,
+        }
+    }
+
+    fn held_after(&self) -> (f64, f64, f64) {
+        (self.yes_shares_after, self.no_shares_after, self.cost_basis_after)
+    }
+}
+
+/// Risk of each quoted order against `positions`. Orders into the same
+/// market and sub-account build on each other, so the last one shows the
+/// position the whole list leaves.
+pub fn order_risks(quotes: Vec<(OrderQuote, Option<String>)>, positions: &[Position]) -> Vec<OrderRisk> {
+    let mut held: HashMap<(String, Option<String>), (f64, f64, f64)> = positions
+        .iter()
+        .map(|position| {
+            let key = (position.market_id.clone(), position.subaccount.clone());
+            (key, (position.yes_shares, position.no_shares, position.cost_basis))
+        })
+        .collect();
+    quotes
+        .into_iter()
+        .map(|(quote, subaccount)| {
+            let key = (quote.market_id.clone(), subaccount.clone());
+            let risk = OrderRisk::new(quote, subaccount, held.get(&key).copied().unwrap_or_default());
+            held.insert(key, risk.held_after());
+            risk
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct PoolsData {
+    #[serde(rename = "marketStats")]
+    market_stats: Option<RawPools>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPools {
+    yes_depth: String,
+    no_depth: String,
+}
+
+impl OddsStreamSdk {
+    /// Preview `order` without submitting it, at this SDK's chain's fee tier.
+    /// Pass the fee a relayer would charge when the order is to be relayed.
+    /// Orders filled ahead of this one can still move the price before it lands.
+    pub async fn quote_order(
+        &self,
+        order: &MarketOrder,
+        relayer_fee: Option<Amount>,
+    ) -> Result<OrderQuote, SdkError> {
+        let parse = |value: &str, what: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|e| SdkError::InvalidInput(format!("invalid {what} {value}: {e}")))
+        };
+        let shares = parse(&order.amount, "amount")?;
+        let relayer_fee = relayer_fee
+            .map(|fee| parse(&fee.to_string(), "relayer fee"))
+            .transpose()?
+            .unwrap_or_default();
+
+        let query = r#"
+            query QuotePools($marketId: String!) {
+                marketStats(marketId: $marketId) { yesDepth noDepth }
+            }
+        "#;
+        let (pools, schedule, tier) = futures::join!(
+            self.graphql_query_fresh::<PoolsData>(query, serde_json::json!({ "marketId": order.market_id })),
+            self.fee_schedule(&order.market_id),
+            self.fee_tier(&order.market_id),
+        );
+        let raw = pools?
+            .market_stats
+            .ok_or_else(|| SdkError::MarketNotFound(order.market_id.clone()))?;
+        let pools = AmmPools::new(parse(&raw.yes_depth, "depth")?, parse(&raw.no_depth, "depth")?);
+
+        let schedule = FeeSchedule { taker_fee_bps: tier?.taker_fee_bps, ..schedule? };
+        Ok(OrderQuote::compute(&order.market_id, pools, &schedule, order.side, shares, relayer_fee))
+    }
+
+    /// Quote `orders` and show the position each leaves on `user_chain_id`.
+    /// Every order is priced against the current pools, so several orders
+    /// into one market understate the later ones' slippage.
+    pub async fn order_risks(&self, orders: &[MarketOrder], user_chain_id: ChainId) -> Result<Vec<OrderRisk>, SdkError> {
+        let (quotes, positions) = futures::try_join!(
+            futures::future::try_join_all(orders.iter().map(|order| self.quote_order(order, None))),
+            self.user_positions(user_chain_id),
+        )?;
+        let quotes = quotes.into_iter().zip(orders.iter().map(|order| order.subaccount.clone())).collect();
+        Ok(order_risks(quotes, &positions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_breaks_down_fees() {
+        let schedule = FeeSchedule {
+            taker_fee_bps: 200,
+            referral_share_bps: 0,
+            oracle_share_bps: 2_500,
+            protocol_share_bps: 1_000,
+            lp_cooldown_secs: 0,
+            early_exit_fee_bps: 0,
+            maker_fee_bps: 0,
+            tiers: Vec::new(),
+        };
+        let quote = OrderQuote::compute("m1", AmmPools::new(600.0, 400.0), &schedule, OrderSide::Yes, 100.0, 0.5);
+
+        assert!((quote.price - 0.4).abs() < 1e-12);
+        assert!((quote.amm_cost - 40.0).abs() < 1e-9);
+        assert!((quote.taker_fee - 0.8).abs() < 1e-9);
+        assert!((quote.oracle_fee - 0.2).abs() < 1e-9);
+        assert!((quote.protocol_fee - 0.08).abs() < 1e-9);
+        assert!((quote.total_cost - 41.3).abs() < 1e-9);
+        assert!((quote.effective_price - 0.413).abs() < 1e-9);
+        assert!(quote.price_impact < 0.0);
+    }
+
+    #[test]
+    fn test_risks_build_on_held_position() {
+        let schedule = FeeSchedule {
+            taker_fee_bps: 0,
+            referral_share_bps: 0,
+            oracle_share_bps: 0,
+            protocol_share_bps: 0,
+            lp_cooldown_secs: 0,
+            early_exit_fee_bps: 0,
+            maker_fee_bps: 0,
+            tiers: Vec::new(),
+        };
+        let quote = |side| OrderQuote::compute("m1", AmmPools::new(500.0, 500.0), &schedule, side, 10.0, 0.0);
+        let held = Position {
+            market_id: "m1".to_string(),
+            subaccount: None,
+            category: None,
+            tags: Vec::new(),
+            yes_shares: 4.0,
+            no_shares: 0.0,
+            cost_basis: 2.0,
+            realized_pnl: 0.0,
+            yes_odds: 0.5,
+            no_odds: 0.5,
+        };
+        let risks = order_risks(
+            vec![(quote(OrderSide::Yes), None), (quote(OrderSide::No), None), (quote(OrderSide::Yes), Some("hedge".to_string()))],
+            &[held],
+        );
+
+        assert_eq!((risks[0].yes_shares_before, risks[0].yes_shares_after), (4.0, 14.0));
+        assert_eq!((risks[1].yes_shares_after, risks[1].no_shares_after), (14.0, 10.0));
+        assert!((risks[1].cost_basis_after - 2.0 - risks[0].quote.total_cost - risks[1].quote.total_cost).abs() < 1e-9);
+        assert_eq!((risks[2].yes_shares_before, risks[2].yes_shares_after), (0.0, 10.0));
+    }
+}
@@ -0,0 +1,125 @@
+//! Read-only client for dashboards and explorers: no wallet, no chain identity
+
+use crate::transport::{RateLimiter, Transport, CONWAY_RPC_URL};
+use crate::{
+    CacheConfig, DispatchConfig, GraphQLResponse, HttpConfig, MarketFilters, MarketInfo, MarketUpdate,
+    SdkError, Shutdown, SubscriptionConfig, SubscriptionHandle, UpdateReceiver, UpdateStream,
+};
+use futures::Stream;
+use std::sync::Arc;
+
+/// Requests per second a public client sends unless configured otherwise
+pub const DEFAULT_READ_RATE_LIMIT: u32 = 10;
+
+/// Queries and subscriptions over the same transport as `OddsStreamSdk`,
+/// throttled so shared public endpoints are not hammered
+#[derive(Clone)]
+pub struct ReadOnlyClient {
+    pub(crate) transport: Transport,
+}
+
+impl Default for ReadOnlyClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadOnlyClient {
+    /// Read-only client for Conway testnet
+    pub fn new() -> Self {
+        Self::with_rpc_url(CONWAY_RPC_URL.to_string())
+    }
+
+    pub fn with_rpc_url(rpc_url: String) -> Self {
+        let mut transport = Transport::new(rpc_url);
+        transport.rate_limiter = Some(Arc::new(RateLimiter::per_second(DEFAULT_READ_RATE_LIMIT)));
+        Self { transport }
+    }
+
+    /// Wraps an existing transport without adding a rate limit
+    pub(crate) fn from_transport(transport: Transport) -> Self {
+        Self { transport }
+    }
+
+    /// Cap outgoing queries at `requests_per_second`; `None` removes the limit
+    pub fn with_rate_limit(mut self, requests_per_second: Option<u32>) -> Self {
+        self.transport.rate_limiter =
+            requests_per_second.map(|rate| Arc::new(RateLimiter::per_second(rate)));
+        self
+    }
+
+    pub fn with_subscription_config(mut self, config: SubscriptionConfig) -> Self {
+        self.transport.subscription_config = config;
+        self
+    }
+
+    pub fn with_dispatch_config(mut self, config: DispatchConfig) -> Self {
+        self.transport.dispatch_config = config;
+        self
+    }
+
+    /// Configure the response cache; `None` sends every query to the server
+    pub fn with_cache(mut self, config: Option<CacheConfig>) -> Self {
+        self.transport.set_cache(config);
+        self
+    }
+
+    /// Close subscriptions cleanly when `shutdown` triggers instead of reconnecting
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.transport.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Rebuild the HTTP client with custom pool, keepalive and HTTP/2 settings
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, SdkError> {
+        self.transport.client = config.build_client()?;
+        Ok(self)
+    }
+
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.transport.client = client;
+        self
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.transport.rpc_url
+    }
+
+    /// Run a GraphQL query and decode its `data`; any entry in `errors` fails the call
+    pub async fn graphql_query<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.transport.graphql_query(query, variables).await
+    }
+
+    /// Run a GraphQL query, returning partial `data` alongside any field errors
+    pub async fn graphql_query_partial<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<GraphQLResponse<T>, SdkError> {
+        self.transport.graphql_query_partial(query, variables).await
+    }
+
+    pub async fn query_markets(&self, filters: MarketFilters) -> Result<Vec<MarketInfo>, SdkError> {
+        self.transport.query_markets(filters).await
+    }
+
+    pub fn subscribe_market_updates_receiver(
+        &self,
+        market_ids: Vec<String>,
+    ) -> (SubscriptionHandle, UpdateReceiver<MarketUpdate>) {
+        self.transport.subscribe_market_updates(market_ids)
+    }
+
+    /// Dropping the stream closes the subscription
+    pub fn subscribe_market_updates_stream(
+        &self,
+        market_ids: Vec<String>,
+    ) -> impl Stream<Item = MarketUpdate> + Send + Unpin {
+        let (handle, receiver) = self.transport.subscribe_market_updates(market_ids);
+        UpdateStream::new(handle, receiver)
+    }
+}
@@ -0,0 +1,70 @@
+//! Referral codes and accrued referral earnings
+
+use crate::{MarketMessage, OddsStreamSdk, SdkError};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// Unclaimed referral fees held by one market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralEarning {
+    pub market_id: String,
+    pub amount: f64,
+}
+
+#[derive(Deserialize)]
+struct EarningsData {
+    #[serde(rename = "referralEarnings")]
+    referral_earnings: Vec<ReferralEarning>,
+}
+
+impl OddsStreamSdk {
+    /// Claim `code` on each market so referred orders credit `referrer`
+    pub async fn register_referral_code(
+        &self,
+        code: &str,
+        referrer: ChainId,
+        market_ids: &[String],
+    ) -> Result<(), SdkError> {
+        for market_id in market_ids {
+            let market_chain_id = self.resolve_market_chain(market_id).await?;
+            let message = MarketMessage::RegisterReferralCode {
+                code: code.to_string(),
+                referrer,
+            };
+            self.send_message(market_chain_id, message).await?;
+        }
+        Ok(())
+    }
+
+    /// Referral fees accrued to `referrer` across all markets
+    pub async fn referral_earnings(&self, referrer: ChainId) -> Result<Vec<ReferralEarning>, SdkError> {
+        let query = r#"
+            query ReferralEarnings($referrer: String!) {
+                referralEarnings(referrer: $referrer) {
+                    marketId
+                    amount
+                }
+            }
+        "#;
+
+        let data: EarningsData = self
+            .graphql_query(query, serde_json::json!({ "referrer": referrer.to_string() }))
+            .await?;
+        Ok(data.referral_earnings)
+    }
+
+    /// Withdraw accrued referral fees from every market holding some
+    pub async fn claim_referral_earnings(&self, referrer: ChainId) -> Result<Vec<String>, SdkError> {
+        let mut transaction_ids = Vec::new();
+        for earning in self.referral_earnings(referrer).await? {
+            if earning.amount <= 0.0 {
+                continue;
+            }
+            let market_chain_id = self.resolve_market_chain(&earning.market_id).await?;
+            let message = MarketMessage::ClaimReferralEarnings { referrer };
+            transaction_ids.push(self.send_message(market_chain_id, message).await?);
+        }
+        Ok(transaction_ids)
+    }
+}
@@ -0,0 +1,233 @@
+//! Which markets a user chain trades in, and who trades in a market
+//!
+//! The registry records a participant the first time a market reports a
+//! fill for it and keeps both directions ordered, so either list can be
+//! paged through or streamed however large it grows.
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use futures::{Stream, TryStreamExt};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Entries fetched per request while streaming a whole list
+const REGISTRATION_PAGE: usize = 500;
+
+/// Markets a user chain has traded in, ascending by ID; pass `next_cursor` back to continue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserMarketsPage {
+    pub market_ids: Vec<String>,
+    pub next_cursor: Option<String>,
+}
+
+/// User chains that have traded in a market, ascending; pass `next_cursor` back to continue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantsPage {
+    pub user_chain_ids: Vec<ChainId>,
+    pub next_cursor: Option<ChainId>,
+}
+
+#[derive(Deserialize)]
+struct UserMarketsData {
+    #[serde(rename = "userMarkets")]
+    user_markets: UserMarketsPage,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawParticipantsPage {
+    user_chain_ids: Vec<String>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ParticipantsData {
+    #[serde(rename = "marketParticipants")]
+    market_participants: RawParticipantsPage,
+}
+
+fn parse_chain_id(raw: &str) -> Result<ChainId, SdkError> {
+    ChainId::from_str(raw).map_err(|e| SdkError::InvalidInput(format!("chain ID {}: {}", raw, e)))
+}
+
+fn parse_participants(raw: RawParticipantsPage) -> Result<ParticipantsPage, SdkError> {
+    Ok(ParticipantsPage {
+        user_chain_ids: raw.user_chain_ids.iter().map(|id| parse_chain_id(id)).collect::<Result<_, _>>()?,
+        next_cursor: raw.next_cursor.as_deref().map(parse_chain_id).transpose()?,
+    })
+}
+
+impl Transport {
+    pub(crate) async fn user_markets(
+        &self,
+        user_chain_id: ChainId,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<UserMarketsPage, SdkError> {
+        let query = r#"
+            query UserMarkets($userChainId: String!, $limit: Int!, $cursor: String) {
+                userMarkets(userChainId: $userChainId, limit: $limit, cursor: $cursor) {
+                    marketIds
+                    nextCursor
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({
+            "userChainId": user_chain_id.to_string(),
+            "limit": limit,
+            "cursor": cursor,
+        });
+        let data: UserMarketsData = self.graphql_query(query, variables).await?;
+        Ok(data.user_markets)
+    }
+
+    pub(crate) async fn market_participants(
+        &self,
+        market_id: &str,
+        limit: usize,
+        cursor: Option<ChainId>,
+    ) -> Result<ParticipantsPage, SdkError> {
+        let query = r#"
+            query MarketParticipants($marketId: String!, $limit: Int!, $cursor: String) {
+                marketParticipants(marketId: $marketId, limit: $limit, cursor: $cursor) {
+                    userChainIds
+                    nextCursor
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({
+            "marketId": market_id,
+            "limit": limit,
+            "cursor": cursor.map(|cursor| cursor.to_string()),
+        });
+        let data: ParticipantsData = self.graphql_query(query, variables).await?;
+        parse_participants(data.market_participants)
+    }
+
+    pub(crate) fn stream_user_markets(
+        &self,
+        user_chain_id: ChainId,
+    ) -> impl Stream<Item = Result<String, SdkError>> + Send + '_ {
+        // `None` once the last page has been fetched
+        futures::stream::try_unfold(Some(None), move |cursor| async move {
+            let Some(cursor) = cursor else {
+                return Ok(None);
+            };
+            let page = self.user_markets(user_chain_id, REGISTRATION_PAGE, cursor).await?;
+            let market_ids = futures::stream::iter(page.market_ids.into_iter().map(Ok));
+            Ok(Some((market_ids, page.next_cursor.map(Some))))
+        })
+        .try_flatten()
+    }
+
+    pub(crate) fn stream_market_participants<'a>(
+        &'a self,
+        market_id: &'a str,
+    ) -> impl Stream<Item = Result<ChainId, SdkError>> + Send + 'a {
+        futures::stream::try_unfold(Some(None), move |cursor| async move {
+            let Some(cursor) = cursor else {
+                return Ok(None);
+            };
+            let page = self.market_participants(market_id, REGISTRATION_PAGE, cursor).await?;
+            let user_chain_ids = futures::stream::iter(page.user_chain_ids.into_iter().map(Ok));
+            Ok(Some((user_chain_ids, page.next_cursor.map(Some))))
+        })
+        .try_flatten()
+    }
+}
+
+impl OddsStreamSdk {
+    /// One page of the markets `user_chain_id` has traded in
+    pub async fn user_markets(
+        &self,
+        user_chain_id: ChainId,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<UserMarketsPage, SdkError> {
+        self.transport.user_markets(user_chain_id, limit, cursor).await
+    }
+
+    /// One page of the user chains that have traded in `market_id`
+    pub async fn market_participants(
+        &self,
+        market_id: &str,
+        limit: usize,
+        cursor: Option<ChainId>,
+    ) -> Result<ParticipantsPage, SdkError> {
+        self.transport.market_participants(market_id, limit, cursor).await
+    }
+
+    /// Every market `user_chain_id` has traded in, fetched a page at a time as the stream is read
+    pub fn stream_user_markets(&self, user_chain_id: ChainId) -> impl Stream<Item = Result<String, SdkError>> + Send + '_ {
+        self.transport.stream_user_markets(user_chain_id)
+    }
+
+    /// Every user chain that has traded in `market_id`, fetched a page at a time as the stream is read
+    pub fn stream_market_participants<'a>(
+        &'a self,
+        market_id: &'a str,
+    ) -> impl Stream<Item = Result<ChainId, SdkError>> + Send + 'a {
+        self.transport.stream_market_participants(market_id)
+    }
+}
+
+impl ReadOnlyClient {
+    /// One page of the markets `user_chain_id` has traded in
+    pub async fn user_markets(
+        &self,
+        user_chain_id: ChainId,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<UserMarketsPage, SdkError> {
+        self.transport.user_markets(user_chain_id, limit, cursor).await
+    }
+
+    /// One page of the user chains that have traded in `market_id`
+    pub async fn market_participants(
+        &self,
+        market_id: &str,
+        limit: usize,
+        cursor: Option<ChainId>,
+    ) -> Result<ParticipantsPage, SdkError> {
+        self.transport.market_participants(market_id, limit, cursor).await
+    }
+
+    /// Every market `user_chain_id` has traded in, fetched a page at a time as the stream is read
+    pub fn stream_user_markets(&self, user_chain_id: ChainId) -> impl Stream<Item = Result<String, SdkError>> + Send + '_ {
+        self.transport.stream_user_markets(user_chain_id)
+    }
+
+    /// Every user chain that has traded in `market_id`, fetched a page at a time as the stream is read
+    pub fn stream_market_participants<'a>(
+        &'a self,
+        market_id: &'a str,
+    ) -> impl Stream<Item = Result<ChainId, SdkError>> + Send + 'a {
+        self.transport.stream_market_participants(market_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_participants_keeps_the_cursor() {
+        let first = ChainId::from([1u8; 32]);
+        let last = ChainId::from([2u8; 32]);
+        let raw = RawParticipantsPage {
+            user_chain_ids: vec![first.to_string(), last.to_string()],
+            next_cursor: Some(last.to_string()),
+        };
+        let page = parse_participants(raw).unwrap();
+        assert_eq!(page.user_chain_ids, vec![first, last]);
+        assert_eq!(page.next_cursor, Some(last));
+
+        let garbled = RawParticipantsPage { user_chain_ids: vec!["not-a-chain".to_string()], next_cursor: None };
+        assert!(parse_participants(garbled).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Market lifecycle announcements from the registry
+//!
+//! The registry emits every activity feed entry as it records it; the
+//! lifecycle ones let agents react to a market the moment it is created,
+//! resolved, cancelled, moved to another oracle or archived, without polling.
+
+use crate::transport::Transport;
+use crate::{
+    ActivityEvent, ActivityKind, CoalesceKey, DecodeFrame, OddsStreamSdk, ReadOnlyClient, SubscriptionHandle,
+    UpdateReceiver, UpdateStream,
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RegistryEventKind {
+    MarketCreated,
+    #[serde(rename = "RESOLVED")]
+    MarketResolved,
+    #[serde(rename = "CANCELLED")]
+    MarketCancelled,
+    OracleUpdated,
+    #[serde(rename = "ARCHIVED")]
+    MarketArchived,
+}
+
+/// One lifecycle announcement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryEvent {
+    /// Position in the registry's activity feed; use it as the `recent_activity` cursor to backfill
+    pub sequence: u64,
+    pub kind: RegistryEventKind,
+    pub market_id: String,
+    /// `YES`/`NO` for resolutions, the new oracle for oracle updates, the hex
+    /// snapshot hash for archivals
+    pub detail: String,
+    /// Micros timestamp of the registry block that recorded it
+    pub timestamp: u64,
+}
+
+impl RegistryEvent {
+    /// Winning side, for `MarketResolved`
+    pub fn outcome(&self) -> Option<bool> {
+        match (self.kind, self.detail.as_str()) {
+            (RegistryEventKind::MarketResolved, "YES") => Some(true),
+            (RegistryEventKind::MarketResolved, "NO") => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The lifecycle event an activity feed entry announces, if it is one
+    pub fn from_activity(event: &ActivityEvent) -> Option<Self> {
+        let kind = match event.kind {
+            ActivityKind::MarketCreated => RegistryEventKind::MarketCreated,
+            ActivityKind::Resolved => RegistryEventKind::MarketResolved,
+            ActivityKind::Cancelled => RegistryEventKind::MarketCancelled,
+            ActivityKind::OracleUpdated => RegistryEventKind::OracleUpdated,
+            ActivityKind::Archived => RegistryEventKind::MarketArchived,
+            ActivityKind::LargeTrade | ActivityKind::Disputed => return None,
+        };
+        Some(RegistryEvent {
+            sequence: event.sequence,
+            kind,
+            market_id: event.market_id.clone(),
+            detail: event.detail.clone(),
+            timestamp: event.timestamp,
+        })
+    }
+}
+
+impl CoalesceKey for RegistryEvent {
+    fn coalesce_key(&self) -> &str {
+        &self.market_id
+    }
+}
+
+impl DecodeFrame for RegistryEvent {
+    // The feed also carries trades and disputes; those frames are skipped
+    fn decode_frame(text: &str) -> Option<Self> {
+        let event: ActivityEvent = serde_json::from_str(text).ok()?;
+        RegistryEvent::from_activity(&event)
+    }
+}
+
+impl Transport {
+    pub(crate) fn subscribe_registry_events(&self) -> (SubscriptionHandle, UpdateReceiver<RegistryEvent>) {
+        let subscription_query = r#"
+            subscription OnRegistryEvents($kinds: [String!]) {
+                activity(kinds: $kinds) {
+                    sequence
+                    kind
+                    marketId
+                    actor
+                    amount
+                    detail
+                    timestamp
+                }
+            }
+        "#;
+
+        let kinds = ["MARKET_CREATED", "RESOLVED", "CANCELLED", "ORACLE_UPDATED", "ARCHIVED"];
+        self.subscribe(subscription_query, serde_json::json!({ "kinds": kinds }))
+    }
+}
+
+impl OddsStreamSdk {
+    /// Stream market creations, resolutions, cancellations, oracle updates
+    /// and archivals as the registry records them. Gaps in `sequence` beyond the
+    /// feed's other kinds can be backfilled from `recent_activity`.
+    pub fn subscribe_registry_events(&self) -> impl Stream<Item = RegistryEvent> + Send + Unpin {
+        let (handle, receiver) = self.transport.subscribe_registry_events();
+        UpdateStream::new(handle, receiver)
+    }
+}
+
+impl ReadOnlyClient {
+    /// Stream market lifecycle announcements; see `OddsStreamSdk::subscribe_registry_events`
+    pub fn subscribe_registry_events(&self) -> impl Stream<Item = RegistryEvent> + Send + Unpin {
+        let (handle, receiver) = self.transport.subscribe_registry_events();
+        UpdateStream::new(handle, receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_frame_keeps_lifecycle_events_only() {
+        let resolved = r#"{"sequence":7,"kind":"RESOLVED","marketId":"btc-100k","actor":null,"amount":null,"detail":"NO","timestamp":42}"#;
+        let event = RegistryEvent::decode_frame(resolved).unwrap();
+        assert_eq!(event.kind, RegistryEventKind::MarketResolved);
+        assert_eq!(event.outcome(), Some(false));
+
+        let trade = r#"{"sequence":8,"kind":"LARGE_TRADE","marketId":"btc-100k","actor":"ab","amount":"5000","detail":"3 orders","timestamp":43}"#;
+        assert!(RegistryEvent::decode_frame(trade).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! Why orders were not filled.
+//!
+//! A market answers every batch with a `BatchConfirmed` message on the user
+//! chain listing the orders it filled and a typed reason for each one it
+//! didn't. Batches whose signature didn't check out are answered to the
+//! chain that sent them instead, since they can't be attributed to the user.
+
+use crate::{decode_market_message, MarketMessage, OddsStreamSdk, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Mirrors the market contract's `RejectionReason`; variant order is the BCS tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectionReason {
+    /// The session key's remaining spend cap is below the batch's notional
+    InsufficientFunds { required: Amount, available: Amount },
+    /// Resolved, or a conditional market that was voided
+    MarketClosed,
+    /// The circuit breaker tripped; orders are refused until `until` (micros)
+    Halted { until: u64 },
+    /// The batch nonce was not above the last one the market accepted
+    StaleNonce { last_accepted: u64 },
+    /// The fill price was above the order's `max_price`
+    PriceProtection { limit: Amount, price: Amount },
+    /// Bad signature, expired session key, missing multisig approvals or a misattributed relayer fee
+    Unauthorized,
+    /// The market's compliance allowlist refused the user chain
+    NotApproved,
+    /// Filling would take the market's pools past their cap; `remaining` is what is left
+    PoolCapReached { remaining: Amount },
+    /// Filling would take the user chain past the market's per-user exposure cap
+    ExposureCapReached { remaining: Amount },
+    /// An amendment named an order that is not queued: it filled, never existed, or its auction ended
+    OrderNotFound,
+    /// Governance paused the protocol; cancels and claims still go through
+    ProtocolPaused,
+    /// An atomic batch's reservation lapsed before the user chain committed it
+    ReservationExpired,
+    /// Named a sub-account outside the user application, the only place sub-accounts are checked and funded
+    UnknownSubaccount,
+    /// The user chain excluded itself from trading until `until` (micros)
+    SelfExcluded { until: u64 },
+}
+
+impl RejectionReason {
+    /// Whether resubmitting the same order later can succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            RejectionReason::Halted { .. }
+                | RejectionReason::PriceProtection { .. }
+                | RejectionReason::StaleNonce { .. }
+                | RejectionReason::ProtocolPaused
+                | RejectionReason::ReservationExpired
+        )
+    }
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectionReason::InsufficientFunds { required, available } => {
+                write!(f, "insufficient funds: {} required, {} available", required, available)
+            }
+            RejectionReason::MarketClosed => write!(f, "market closed"),
+            RejectionReason::Halted { until } => write!(f, "trading halted until {}", until),
+            RejectionReason::StaleNonce { last_accepted } => {
+                write!(f, "stale nonce: last accepted was {}", last_accepted)
+            }
+            RejectionReason::PriceProtection { limit, price } => {
+                write!(f, "price {} above limit {}", price, limit)
+            }
+            RejectionReason::Unauthorized => write!(f, "unauthorized"),
+            RejectionReason::NotApproved => write!(f, "not approved by the market's allowlist"),
+            RejectionReason::PoolCapReached { remaining } => {
+                write!(f, "market pool cap reached: {} capacity left", remaining)
+            }
+            RejectionReason::ExposureCapReached { remaining } => {
+                write!(f, "per-user exposure cap reached: {} left to spend", remaining)
+            }
+            RejectionReason::OrderNotFound => write!(f, "order is not queued"),
+            RejectionReason::ProtocolPaused => write!(f, "protocol is paused"),
+            RejectionReason::ReservationExpired => write!(f, "reservation expired before commit"),
+            RejectionReason::UnknownSubaccount => write!(f, "sub-account not placed through the user application"),
+            RejectionReason::SelfExcluded { until } => write!(f, "self-excluded until {}", until),
+        }
+    }
+}
+
+/// One order a market refused
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderRejection {
+    pub order_id: u64,
+    pub reason: RejectionReason,
+}
+
+/// A market's answer to one batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchConfirmation {
+    pub market_chain: ChainId,
+    /// Height of the user-chain block that received it
+    pub height: u64,
+    pub filled_order_ids: Vec<u64>,
+    pub total_cost: Amount,
+    pub rejections: Vec<OrderRejection>,
+}
+
+impl BatchConfirmation {
+    /// Decode a `BatchConfirmed` message sent by `market_chain`; `None` for any other message
+    pub fn decode(market_chain: ChainId, height: u64, payload: &[u8]) -> Option<Self> {
+        match decode_market_message(payload).ok()? {
+            MarketMessage::BatchConfirmed { order_ids, total_cost, rejections, .. } => Some(Self {
+                market_chain,
+                height,
+                filled_order_ids: order_ids,
+                total_cost,
+                rejections,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl OddsStreamSdk {
+    /// Batch confirmations received on this SDK's chain from `from_height` on,
+    /// read from the chain's blocks, oldest first
+    pub async fn batch_confirmations(&self, from_height: u64, limit: u64) -> Result<Vec<BatchConfirmation>, SdkError> {
+        let blocks = self.transport.chain_blocks(self.chain_id, from_height, limit).await?;
+        Ok(blocks
+            .iter()
+            .flat_map(|block| {
+                block
+                    .incoming
+                    .iter()
+                    .filter_map(|message| BatchConfirmation::decode(message.origin, block.height, &message.payload))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WireFormat;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_confirmation_decodes_rejections() {
+        let market = ChainId::from([2u8; 32]);
+        let message = MarketMessage::BatchConfirmed {
+            user_chain_id: ChainId::from([1u8; 32]),
+            order_ids: vec![1],
+            total_cost: Amount::from_tokens(4),
+            rejections: vec![OrderRejection {
+                order_id: 2,
+                reason: RejectionReason::PriceProtection {
+                    limit: Amount::from_str("0.4").unwrap(),
+                    price: Amount::from_str("0.45").unwrap(),
+                },
+            }],
+        };
+        let payload = WireFormat::Bcs.encode_untagged(&message).unwrap();
+
+        let confirmation = BatchConfirmation::decode(market, 9, &payload).unwrap();
+        assert_eq!(confirmation.filled_order_ids, vec![1]);
+        assert_eq!(confirmation.rejections.len(), 1);
+        assert!(confirmation.rejections[0].reason.is_retryable());
+
+        let other = WireFormat::Bcs.encode_untagged(&MarketMessage::UncrossAuction).unwrap();
+        assert_eq!(BatchConfirmation::decode(market, 9, &other), None);
+    }
+
+    #[test]
+    fn test_cap_rejections_are_final() {
+        let reason = RejectionReason::ExposureCapReached { remaining: Amount::from_tokens(3) };
+        assert!(!reason.is_retryable());
+        assert!(reason.to_string().starts_with("per-user exposure cap reached"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Gasless order submission through third-party relayers
+//!
+//! The user signs a batch off-chain and hands the resulting [`RelayedBatch`]
+//! to a relayer, which submits it from its own chain. The market contract
+//! checks the user's signature and pays the relayer the fee the user signed for.
+
+use crate::{sign_orders, wire_orders, MarketMessage, MarketOrder, OddsStreamSdk, Order, OrderSignature, RelayerFee, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+
+/// A signed batch for a single market, ready to be relayed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayedBatch {
+    pub market_id: String,
+    pub user_chain_id: ChainId,
+    /// As signed, so the relayer submits exactly these
+    pub orders: Vec<Order>,
+    pub nonce: u64,
+    pub relayer_fee: Option<RelayerFee>,
+    pub signature: OrderSignature,
+}
+
+impl OddsStreamSdk {
+    /// User side: sign orders for one market so `relayer` can submit them for `fee`
+    pub async fn sign_relayed_batch(
+        &self,
+        market_id: &str,
+        orders: Vec<MarketOrder>,
+        user_chain_id: ChainId,
+        relayer: ChainId,
+        fee: Amount,
+    ) -> Result<RelayedBatch, SdkError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| SdkError::InvalidInput("relayed batches require a signer".to_string()))?;
+        if let Some(order) = orders.iter().find(|order| order.market_id != market_id) {
+            return Err(SdkError::InvalidInput(format!(
+                "order for {} in a batch for {}",
+                order.market_id, market_id
+            )));
+        }
+
+        self.check_schema(market_id).await?;
+        self.check_compliance(market_id, user_chain_id).await?;
+        self.ensure_not_limited(user_chain_id).await?;
+
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        let nonce = self.get_nonce().await?;
+        let orders = wire_orders(&orders, nonce)?;
+        let relayer_fee = (fee > Amount::ZERO).then_some(RelayerFee { relayer, amount: fee });
+        let notional = orders.iter().fold(fee, |sum, order| sum + order.amount);
+        self.approve_own_payments(user_chain_id, market_chain_id, notional).await?;
+        let signature = sign_orders(
+            signer.as_ref(),
+            user_chain_id,
+            market_chain_id,
+            nonce,
+            &orders,
+            relayer_fee.as_ref(),
+        )
+        .await?;
+
+        Ok(RelayedBatch {
+            market_id: market_id.to_string(),
+            user_chain_id,
+            orders,
+            nonce,
+            relayer_fee,
+            signature,
+        })
+    }
+
+    /// Relayer side: submit a user's signed batch from this SDK's chain
+    pub async fn relay_batch(&self, batch: RelayedBatch) -> Result<String, SdkError> {
+        // The market only pays fees to the chain that actually relayed the batch
+        if let Some(fee) = &batch.relayer_fee {
+            if fee.relayer != self.chain_id {
+                return Err(SdkError::InvalidInput(
+                    "batch fee is payable to a different relayer".to_string(),
+                ));
+            }
+        }
+
+        let market_chain_id = self.resolve_market_chain(&batch.market_id).await?;
+        let message = MarketMessage::BatchedOrders {
+            user_chain_id: batch.user_chain_id,
+            orders: batch.orders,
+            nonce: batch.nonce,
+            signature: Some(batch.signature),
+            relayer_fee: batch.relayer_fee,
+            cosignatures: Vec::new(),
+        };
+
+        self.send_message(market_chain_id, message).await
+    }
+}
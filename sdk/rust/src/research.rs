@@ -0,0 +1,224 @@
+//! Qualitative research for strategies: a `ResearchProvider` turns the event
+//! behind a market into a sentiment score, and `CachedResearch` keeps calls
+//! to it cached and within a rate limit
+
+use crate::{Clock, MarketInfo, RateLimiter, SdkError, SystemClock};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Prompt sent when the config gives none. `{description}`, `{yes_odds}` and
+/// `{resolution_time}` are filled in from the market.
+pub const DEFAULT_RESEARCH_PROMPT: &str = "Prediction market: \"{description}\". \
+The market prices YES at {yes_odds}. It resolves at {resolution_time} (micros since the Unix epoch). \
+Summarize recent news bearing on the outcome and answer with a JSON object: \
+{\"sentiment\": number from -1 (NO) to 1 (YES), \"confidence\": number from 0 to 1, \
+\"summary\": string, \"sources\": [string]}";
+
+/// A provider's read of a market's event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResearchSignal {
+    pub market_id: String,
+    /// From -1 (points to NO) to 1 (points to YES)
+    pub sentiment: f64,
+    /// How much the provider trusts its sentiment, from 0 to 1
+    pub confidence: f64,
+    pub summary: String,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Micros since the Unix epoch
+    #[serde(default)]
+    pub produced_at: u64,
+}
+
+impl ResearchSignal {
+    /// Sentiment weighted by confidence, for strategies that want one number
+    pub fn score(&self) -> f64 {
+        self.sentiment * self.confidence
+    }
+}
+
+/// Source of research signals, such as an LLM summarizing news
+#[async_trait]
+pub trait ResearchProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn research(&self, market: &MarketInfo) -> Result<ResearchSignal, SdkError>;
+}
+
+fn default_content_pointer() -> String {
+    "/choices/0/message/content".to_string()
+}
+
+fn default_prompt() -> String {
+    DEFAULT_RESEARCH_PROMPT.to_string()
+}
+
+/// Chat-completions endpoint an `LlmResearchProvider` calls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmResearchConfig {
+    pub endpoint: String,
+    /// Sent as a bearer token
+    #[serde(default)]
+    pub api_key: Option<String>,
+    pub model: String,
+    #[serde(default = "default_prompt")]
+    pub prompt_template: String,
+    /// JSON pointer to the model's reply in the response
+    #[serde(default = "default_content_pointer")]
+    pub content_pointer: String,
+}
+
+/// What the model is asked to reply with
+#[derive(Deserialize)]
+struct Verdict {
+    sentiment: f64,
+    confidence: f64,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    sources: Vec<String>,
+}
+
+/// Read a model's reply, tolerating a Markdown code fence around the JSON
+fn parse_verdict(reply: &str) -> Result<Verdict, SdkError> {
+    let reply = reply.trim();
+    let json = reply
+        .strip_prefix("```json")
+        .or_else(|| reply.strip_prefix("```"))
+        .and_then(|inner| inner.strip_suffix("```"))
+        .unwrap_or(reply);
+    let verdict: Verdict = serde_json::from_str(json.trim())?;
+    if !verdict.sentiment.is_finite() || !verdict.confidence.is_finite() {
+        return Err(SdkError::InvalidInput("research reply has a non-finite score".to_string()));
+    }
+    Ok(verdict)
+}
+
+/// Research from an OpenAI-compatible chat-completions API
+pub struct LlmResearchProvider {
+    config: LlmResearchConfig,
+    client: reqwest::Client,
+    clock: Arc<dyn Clock>,
+}
+
+impl LlmResearchProvider {
+    pub fn new(config: LlmResearchConfig) -> Self {
+        Self { config, client: reqwest::Client::new(), clock: Arc::new(SystemClock) }
+    }
+
+    fn prompt(&self, market: &MarketInfo) -> String {
+        self.config
+            .prompt_template
+            .replace("{description}", &market.description)
+            .replace("{yes_odds}", &format!("{:.3}", market.yes_odds))
+            .replace("{resolution_time}", &market.resolution_time.to_string())
+    }
+}
+
+#[async_trait]
+impl ResearchProvider for LlmResearchProvider {
+    fn name(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn research(&self, market: &MarketInfo) -> Result<ResearchSignal, SdkError> {
+        let body = serde_json::json!({
+            "model": self.config.model,
+            "messages": [{ "role": "user", "content": self.prompt(market) }],
+            "response_format": { "type": "json_object" },
+        });
+        let mut request = self.client.post(&self.config.endpoint).json(&body);
+        if let Some(api_key) = &self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+        let reply = response
+            .pointer(&self.config.content_pointer)
+            .and_then(|reply| reply.as_str())
+            .ok_or_else(|| SdkError::InvalidInput(format!("no reply at {}", self.config.content_pointer)))?;
+        let verdict = parse_verdict(reply)?;
+        Ok(ResearchSignal {
+            market_id: market.id.clone(),
+            sentiment: verdict.sentiment.clamp(-1.0, 1.0),
+            confidence: verdict.confidence.clamp(0.0, 1.0),
+            summary: verdict.summary,
+            sources: verdict.sources,
+            produced_at: self.clock.now_micros(),
+        })
+    }
+}
+
+/// A `ResearchProvider` behind a per-market cache and a request budget
+pub struct CachedResearch {
+    provider: Arc<dyn ResearchProvider>,
+    ttl: Duration,
+    limiter: RateLimiter,
+    cache: Mutex<HashMap<String, ResearchSignal>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl CachedResearch {
+    /// Reuse a market's signal for `ttl` and call `provider` at most `requests_per_minute` times a minute
+    pub fn new(provider: Arc<dyn ResearchProvider>, ttl: Duration, requests_per_minute: u32) -> Self {
+        Self {
+            provider,
+            ttl,
+            limiter: RateLimiter::per_minute(requests_per_minute),
+            cache: Mutex::new(HashMap::new()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn provider_name(&self) -> &str {
+        self.provider.name()
+    }
+
+    /// Latest signal for `market_id`, however old
+    pub fn cached(&self, market_id: &str) -> Option<ResearchSignal> {
+        self.cache.lock().unwrap().get(market_id).cloned()
+    }
+
+    fn is_fresh(&self, signal: &ResearchSignal) -> bool {
+        let age = self.clock.now_micros().saturating_sub(signal.produced_at);
+        age < self.ttl.as_micros() as u64
+    }
+
+    /// The cached signal while it is fresh, otherwise a new one if the budget
+    /// allows. Over budget the stale signal is returned, or `None` if there is none yet;
+    /// this never waits, so a slow provider cannot stall the agent.
+    pub async fn research(&self, market: &MarketInfo) -> Result<Option<ResearchSignal>, SdkError> {
+        let cached = self.cached(&market.id);
+        if cached.as_ref().is_some_and(|signal| self.is_fresh(signal)) || !self.limiter.try_acquire() {
+            return Ok(cached);
+        }
+        let signal = self.provider.research(market).await?;
+        self.cache.lock().unwrap().insert(market.id.clone(), signal.clone());
+        Ok(Some(signal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict_reads_fenced_json() {
+        let reply = "```json\n{\"sentiment\": 0.4, \"confidence\": 0.5, \"summary\": \"Polls tighten\"}\n```";
+        let verdict = parse_verdict(reply).unwrap();
+        assert_eq!(verdict.sentiment, 0.4);
+        assert_eq!(verdict.summary, "Polls tighten");
+        assert!(verdict.sources.is_empty());
+
+        assert!(parse_verdict("The outlook is positive").is_err());
+    }
+}
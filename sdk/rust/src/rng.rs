@@ -0,0 +1,99 @@
+//! Seedable randomness for strategies and simulations.
+//!
+//! Randomized strategies take a `SimRng` instead of reaching for
+//! `thread_rng`, so a backtest can record the seed and replay the exact same
+//! decisions later. Key material must keep using `OsRng`.
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// What a report needs to reproduce a run's random draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RngRecord {
+    pub seed: u64,
+}
+
+/// Deterministic random source; the same seed yields the same sequence
+#[derive(Debug, Clone)]
+pub struct SimRng {
+    seed: u64,
+    inner: StdRng,
+}
+
+impl SimRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self { seed, inner: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Random seed for live runs; still recorded so the run can be replayed
+    pub fn from_entropy() -> Self {
+        Self::seeded(rand::rngs::OsRng.next_u64())
+    }
+
+    pub fn replay(record: RngRecord) -> Self {
+        Self::seeded(record.seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn record(&self) -> RngRecord {
+        RngRecord { seed: self.seed }
+    }
+
+    /// Independent stream for one component, derived from this seed and `label`.
+    ///
+    /// Giving each strategy its own fork keeps their draws stable when
+    /// strategies are added, removed or reordered.
+    pub fn fork(&self, label: &str) -> SimRng {
+        let digest = Sha256::new()
+            .chain_update(self.seed.to_le_bytes())
+            .chain_update(label.as_bytes())
+            .finalize();
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&digest[..8]);
+        Self::seeded(u64::from_le_bytes(seed))
+    }
+}
+
+impl RngCore for SimRng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_replay_and_forks_are_deterministic() {
+        let mut original = SimRng::seeded(7);
+        let draws: Vec<f64> = (0..5).map(|_| original.gen()).collect();
+
+        let mut replayed = SimRng::replay(original.record());
+        let again: Vec<f64> = (0..5).map(|_| replayed.gen()).collect();
+        assert_eq!(draws, again);
+
+        let root = SimRng::seeded(7);
+        assert_eq!(root.fork("momentum").seed(), root.fork("momentum").seed());
+        assert_ne!(root.fork("momentum").seed(), root.fork("mean-reversion").seed());
+    }
+}
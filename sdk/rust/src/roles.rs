@@ -0,0 +1,107 @@
+//! Registry admin roles
+//!
+//! The registry chain's owners may run every admin operation. Other signers
+//! need a role granted by a holder of [`Role::Owner`]: operators create markets
+//! and configure templates, the allowlist and yield sources, oracle admins move
+//! markets that haven't settled to another oracle, and owners manage roles, the
+//! protocol pause and the treasury.
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::Owner;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Role {
+    /// Implies every other role
+    Owner,
+    Operator,
+    OracleAdmin,
+}
+
+/// Roles granted to one signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleGrant {
+    pub owner: String,
+    pub roles: Vec<Role>,
+}
+
+impl RoleGrant {
+    /// Whether the registry lets this signer act as `role`
+    pub fn allows(&self, role: Role) -> bool {
+        self.roles.iter().any(|held| *held == role || *held == Role::Owner)
+    }
+}
+
+#[derive(Deserialize)]
+struct RoleGrantsData {
+    #[serde(rename = "roleGrants")]
+    role_grants: Vec<RoleGrant>,
+}
+
+impl Transport {
+    pub(crate) async fn role_grants(&self) -> Result<Vec<RoleGrant>, SdkError> {
+        let query = r#"
+            query RoleGrants {
+                roleGrants { owner roles }
+            }
+        "#;
+
+        let data: RoleGrantsData = self.graphql_query_fresh(query, serde_json::json!({})).await?;
+        Ok(data.role_grants)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Every role granted beyond the registry chain's owners
+    pub async fn role_grants(&self) -> Result<Vec<RoleGrant>, SdkError> {
+        self.transport.role_grants().await
+    }
+
+    /// Give `owner` a role; the registry refuses signers without [`Role::Owner`]
+    pub async fn grant_role(&self, owner: Owner, role: Role) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation GrantRole($owner: String!, $role: Role!) {
+                grantRole(owner: $owner, role: $role)
+            }
+        "#;
+        self.execute_registry_operation(mutation, serde_json::json!({ "owner": owner.to_string(), "role": role }))
+            .await
+    }
+
+    /// Take a role away from `owner`; the registry refuses signers without [`Role::Owner`]
+    pub async fn revoke_role(&self, owner: Owner, role: Role) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation RevokeRole($owner: String!, $role: Role!) {
+                revokeRole(owner: $owner, role: $role)
+            }
+        "#;
+        self.execute_registry_operation(mutation, serde_json::json!({ "owner": owner.to_string(), "role": role }))
+            .await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Every role granted beyond the registry chain's owners
+    pub async fn role_grants(&self) -> Result<Vec<RoleGrant>, SdkError> {
+        self.transport.role_grants().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_role_allows_everything() {
+        let grant: RoleGrant = serde_json::from_str(r#"{"owner":"ab","roles":["ORACLE_ADMIN"]}"#).unwrap();
+        assert!(grant.allows(Role::OracleAdmin));
+        assert!(!grant.allows(Role::Operator));
+
+        let owner = RoleGrant { owner: "cd".to_string(), roles: vec![Role::Owner] };
+        assert!(owner.allows(Role::Operator));
+        assert!(owner.allows(Role::OracleAdmin));
+    }
+}
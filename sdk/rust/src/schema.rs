@@ -0,0 +1,130 @@
+//! Wire-schema compatibility between this SDK and the markets it talks to
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use serde::Deserialize;
+
+/// Hash of the wire schema this SDK was built against
+pub fn sdk_schema_hash() -> String {
+    oddsstream_schema::schema_hash_hex()
+}
+
+#[derive(Deserialize)]
+struct SchemaHashData {
+    #[serde(rename = "schemaHash")]
+    schema_hash: Option<String>,
+}
+
+impl Transport {
+    pub(crate) async fn market_schema_hash(&self, market_id: &str) -> Result<String, SdkError> {
+        let query = r#"
+            query SchemaHash($marketId: String!) {
+                schemaHash(marketId: $marketId)
+            }
+        "#;
+
+        // Fixed for the life of the market's bytecode, so a cached answer is fine
+        let data: SchemaHashData = self
+            .graphql_query_cached(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.schema_hash
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+
+    pub(crate) async fn check_schema(&self, market_id: &str) -> Result<(), SdkError> {
+        let found = self.market_schema_hash(market_id).await?;
+        let expected = sdk_schema_hash();
+        if found != expected {
+            return Err(SdkError::IncompatibleSchema { market_id: market_id.to_string(), expected, found });
+        }
+        Ok(())
+    }
+}
+
+impl OddsStreamSdk {
+    /// Hash of the wire schema `market_id`'s contract was built against
+    pub async fn market_schema_hash(&self, market_id: &str) -> Result<String, SdkError> {
+        self.transport.market_schema_hash(market_id).await
+    }
+
+    /// Fail with `IncompatibleSchema` unless the market decodes messages the way this SDK encodes them
+    pub async fn check_schema(&self, market_id: &str) -> Result<(), SdkError> {
+        self.transport.check_schema(market_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Hash of the wire schema `market_id`'s contract was built against
+    pub async fn market_schema_hash(&self, market_id: &str) -> Result<String, SdkError> {
+        self.transport.market_schema_hash(market_id).await
+    }
+
+    /// Fail with `IncompatibleSchema` unless the market decodes messages the way this SDK encodes them
+    pub async fn check_schema(&self, market_id: &str) -> Result<(), SdkError> {
+        self.transport.check_schema(market_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::base::{Amount, ChainId};
+    use oddsstream_schema::generate::{render, shape};
+    use oddsstream_schema::MARKET_SCHEMA;
+    use serde_reflection::{Registry, Samples, Tracer, TracerConfig};
+
+    // Every type the schema lists is reached from one of these
+    const ROOTS: &[&str] = &["MarketMessage", "AuditEvent"];
+
+    fn tracer() -> (Tracer, Samples) {
+        let mut tracer = Tracer::new(TracerConfig::default());
+        let mut samples = Samples::new();
+        tracer.trace_value(&mut samples, &ChainId::from([1u8; 32])).unwrap();
+        tracer.trace_value(&mut samples, &Amount::from_tokens(1)).unwrap();
+        (tracer, samples)
+    }
+
+    // Nested enums are traced on their own first, or only the variants a sample happened to use would be recorded
+    fn contract_registry() -> Registry {
+        use oddsstream_market::{audit, validation};
+
+        let (mut tracer, samples) = tracer();
+        tracer.trace_type::<oddsstream_market::OrderSide>(&samples).unwrap();
+        tracer.trace_type::<validation::RejectionReason>(&samples).unwrap();
+        tracer.trace_type::<oddsstream_market::EvidenceKind>(&samples).unwrap();
+        tracer.trace_type::<oddsstream_market::OracleType>(&samples).unwrap();
+        tracer.trace_type::<oddsstream_market::MarketMessage>(&samples).unwrap();
+        tracer.trace_type::<audit::AuditEvent>(&samples).unwrap();
+        tracer.registry().unwrap()
+    }
+
+    fn sdk_registry() -> Registry {
+        let (mut tracer, samples) = tracer();
+        tracer.trace_type::<crate::WireSide>(&samples).unwrap();
+        tracer.trace_type::<crate::RejectionReason>(&samples).unwrap();
+        tracer.trace_type::<crate::EvidenceKind>(&samples).unwrap();
+        tracer.trace_type::<crate::OracleType>(&samples).unwrap();
+        tracer.trace_type::<crate::MarketMessage>(&samples).unwrap();
+        tracer.trace_type::<crate::AuditEvent>(&samples).unwrap();
+        tracer.registry().unwrap()
+    }
+
+    #[test]
+    fn test_schema_is_generated_from_contract_types() {
+        let generated = render(&contract_registry(), ROOTS);
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../contract/schema/market.schema");
+            std::fs::write(path, &generated).unwrap();
+            return;
+        }
+        assert_eq!(MARKET_SCHEMA, generated, "market.schema is stale; regenerate it with UPDATE_SCHEMA=1");
+    }
+
+    // The SDK names some types and fields differently; only the bytes have to agree
+    #[test]
+    fn test_sdk_types_encode_like_the_schema() {
+        let (contract, sdk) = (contract_registry(), sdk_registry());
+        for root in ROOTS {
+            assert_eq!(shape(&sdk, root), shape(&contract, root), "{} differs from the contract's", root);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Scoped session keys for AI agents
+//!
+//! A session key is a fresh ed25519 key the user authorizes on a set of
+//! markets with a spend cap and an expiry. The agent signs orders with it
+//! and submits them from its own chain; markets outside the grant, expired
+//! keys and batches past the cap are rejected by the market contract. The
+//! cap covers all of the key's markets: the user chain's own market app adds
+//! up their spend and keeps each of them informed.
+
+use crate::{Clock, LocalSigner, MarketMessage, OddsStreamSdk, SdkError, Signer};
+use ed25519_dalek::SigningKey;
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Most markets one session key can be granted on
+pub const MAX_SESSION_KEY_MARKETS: usize = 64;
+
+/// Public description of a minted session key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionKeyGrant {
+    pub public_key: Vec<u8>,
+    pub allowed_markets: Vec<String>,
+    /// Maximum notional the key may trade, across all its markets
+    pub spend_cap: Amount,
+    /// Microseconds since the Unix epoch, matching Linera block timestamps
+    pub expires_at: u64,
+}
+
+impl SessionKeyGrant {
+    pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+        clock.now_micros() >= self.expires_at
+    }
+
+    /// Time left before markets start rejecting the key
+    pub fn remaining(&self, clock: &dyn Clock) -> Duration {
+        Duration::from_micros(self.expires_at.saturating_sub(clock.now_micros()))
+    }
+}
+
+impl OddsStreamSdk {
+    /// Mint a session key valid on `allowed_markets` for `ttl`.
+    ///
+    /// Returns the signer to hand to the agent (e.g. via `with_signer`) and
+    /// the grant, which is needed to revoke the key later.
+    pub async fn mint_session_key(
+        &self,
+        user_chain_id: ChainId,
+        allowed_markets: Vec<String>,
+        spend_cap: Amount,
+        ttl: Duration,
+    ) -> Result<(LocalSigner, SessionKeyGrant), SdkError> {
+        if allowed_markets.is_empty() {
+            return Err(SdkError::InvalidInput(
+                "session key must allow at least one market".to_string(),
+            ));
+        }
+        if allowed_markets.len() > MAX_SESSION_KEY_MARKETS {
+            return Err(SdkError::InvalidInput(format!(
+                "session key may allow at most {MAX_SESSION_KEY_MARKETS} markets"
+            )));
+        }
+
+        let secret = SigningKey::generate(&mut rand::rngs::OsRng).to_bytes();
+        let signer = LocalSigner::from_bytes(&secret);
+        let expires_at = self.clock.now_micros() + ttl.as_micros() as u64;
+
+        let grant = SessionKeyGrant {
+            public_key: signer.public_key(),
+            allowed_markets,
+            spend_cap,
+            expires_at,
+        };
+
+        let mut markets = Vec::with_capacity(grant.allowed_markets.len());
+        for market_id in &grant.allowed_markets {
+            markets.push(self.resolve_market_chain(market_id).await?);
+        }
+        // The user chain's own market app keeps the key's spend across the markets
+        for chain_id in markets.iter().copied().chain([user_chain_id]) {
+            let message = MarketMessage::GrantSessionKey {
+                user_chain_id,
+                public_key: grant.public_key.clone(),
+                spend_cap,
+                expires_at,
+                markets: markets.clone(),
+            };
+            self.send_message(chain_id, message).await?;
+        }
+
+        Ok((signer, grant))
+    }
+
+    /// Revoke a session key on every market it was granted for, and on the user chain
+    pub async fn revoke_session_key(
+        &self,
+        user_chain_id: ChainId,
+        grant: &SessionKeyGrant,
+    ) -> Result<(), SdkError> {
+        for market_id in &grant.allowed_markets {
+            let market_chain_id = self.resolve_market_chain(market_id).await?;
+            let message = MarketMessage::RevokeSessionKey {
+                user_chain_id,
+                public_key: grant.public_key.clone(),
+            };
+            self.send_message(market_chain_id, message).await?;
+        }
+        let message = MarketMessage::RevokeSessionKey { user_chain_id, public_key: grant.public_key.clone() };
+        self.send_message(user_chain_id, message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockClock;
+
+    #[test]
+    fn test_grant_expiry_follows_clock() {
+        let clock = MockClock::new(0);
+        let grant = SessionKeyGrant {
+            public_key: Vec::new(),
+            allowed_markets: vec!["m1".to_string()],
+            spend_cap: Amount::ZERO,
+            expires_at: 60_000_000,
+        };
+        assert_eq!(grant.remaining(&clock), Duration::from_secs(60));
+
+        clock.advance(Duration::from_secs(60));
+        assert!(grant.is_expired(&clock));
+        assert_eq!(grant.remaining(&clock), Duration::ZERO);
+    }
+}
@@ -0,0 +1,325 @@
+//! Shadow mode: run a strategy on live data without sending anything, then
+//! hold its decisions up against a backtest of the same strategy and
+//! parameters over the same period. Divergence points at latency (decisions
+//! made late) or data (updates the live feed missed or saw differently).
+
+use crate::agent::limit_violation;
+use crate::{
+    AgentConfig, AgentContext, AgentDecision, BacktestDecision, Backtester, MarketUpdate, OddsStreamSdk, SdkError,
+    Shutdown, TradingStrategy,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::time::MissedTickBehavior;
+
+/// A decision the shadowed strategy made on live data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowDecision {
+    /// Time of the data the decision was made on: the update's timestamp, or the tick. Micros.
+    pub data_at: u64,
+    /// Wall-clock time the strategy returned the decision, in micros
+    pub decided_at: u64,
+    pub decision: AgentDecision,
+    /// Why a live agent would have dropped it, if it would have
+    pub rejected: Option<String>,
+}
+
+impl ShadowDecision {
+    /// How long after its data the decision was made
+    pub fn lag_micros(&self) -> u64 {
+        self.decided_at.saturating_sub(self.data_at)
+    }
+}
+
+/// Updates the live feed skipped, by per-market sequence number
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceGap {
+    pub market_id: String,
+    /// Last sequence seen before the gap
+    pub after: u64,
+    /// First sequence seen after it
+    pub resumed_at: u64,
+}
+
+/// A shadow run: what the strategy decided and the data it saw
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowSession {
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub decisions: Vec<ShadowDecision>,
+    /// Every live update, in arrival order
+    pub tape: Vec<MarketUpdate>,
+    pub gaps: Vec<SequenceGap>,
+}
+
+/// How strictly live and backtest decisions are paired
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShadowTolerance {
+    /// Largest gap between the data times of two decisions that still pair
+    /// them; at least the decision interval, so ticks can pair
+    pub match_window_micros: u64,
+    /// Paired decisions made later than this after their data are flagged late
+    pub max_lag_micros: u64,
+}
+
+impl Default for ShadowTolerance {
+    fn default() -> Self {
+        Self { match_window_micros: 60_000_000, max_lag_micros: 2_000_000 }
+    }
+}
+
+/// A way live and backtest disagreed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Divergence {
+    /// The backtest decided this and live did not
+    MissedLive { expected: BacktestDecision },
+    /// Live decided this and the backtest did not
+    ExtraLive { live: ShadowDecision },
+    /// Both decided on the same market at about the same time, differently
+    Mismatch { live: ShadowDecision, expected: BacktestDecision },
+    /// The same decision, made too long after its data
+    Late { live: ShadowDecision, lag_micros: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowReport {
+    pub live_decisions: usize,
+    pub expected_decisions: usize,
+    /// Decisions both sides made, late ones included
+    pub matched: usize,
+    pub divergences: Vec<Divergence>,
+    /// Feed gaps seen live; decisions near them likely diverge for data reasons
+    pub gaps: Vec<SequenceGap>,
+}
+
+impl ShadowReport {
+    /// Share of all decisions, live or expected, that paired up
+    pub fn agreement(&self) -> f64 {
+        let total = self.live_decisions.max(self.expected_decisions);
+        if total == 0 {
+            1.0
+        } else {
+            self.matched as f64 / total as f64
+        }
+    }
+}
+
+/// Pair live decisions with the backtest's and list where they part ways
+pub fn compare_decisions(
+    live: &[ShadowDecision],
+    expected: &[BacktestDecision],
+    tolerance: &ShadowTolerance,
+) -> Vec<Divergence> {
+    let near = |live: &ShadowDecision, expected: &BacktestDecision| {
+        live.data_at.abs_diff(expected.at) <= tolerance.match_window_micros
+    };
+    let mut paired = vec![false; expected.len()];
+    let mut divergences = Vec::new();
+    let mut unpaired = Vec::new();
+
+    for decision in live {
+        let exact = (0..expected.len())
+            .find(|index| !paired[*index] && expected[*index].decision == decision.decision && near(decision, &expected[*index]));
+        match exact {
+            Some(index) => {
+                paired[index] = true;
+                if decision.lag_micros() > tolerance.max_lag_micros {
+                    divergences.push(Divergence::Late { live: decision.clone(), lag_micros: decision.lag_micros() });
+                }
+            }
+            None => unpaired.push(decision),
+        }
+    }
+    // What is left on both sides pairs up by market and time as a mismatch
+    for decision in unpaired {
+        let similar = (0..expected.len()).find(|index| {
+            !paired[*index]
+                && expected[*index].decision.market_id() == decision.decision.market_id()
+                && near(decision, &expected[*index])
+        });
+        match similar {
+            Some(index) => {
+                paired[index] = true;
+                divergences.push(Divergence::Mismatch { live: decision.clone(), expected: expected[index].clone() });
+            }
+            None => divergences.push(Divergence::ExtraLive { live: decision.clone() }),
+        }
+    }
+    for (index, expected) in expected.iter().enumerate() {
+        if !paired[index] {
+            divergences.push(Divergence::MissedLive { expected: expected.clone() });
+        }
+    }
+    divergences
+}
+
+impl ShadowSession {
+    /// Backtest a freshly configured `strategy` over `history` (the
+    /// authoritative record of the session's period, e.g. from
+    /// `download_history`) and compare. Without `history` the session's own
+    /// tape is replayed, which isolates divergence caused by latency.
+    pub fn compare(
+        &self,
+        strategy: &mut dyn TradingStrategy,
+        config: &AgentConfig,
+        backtester: &Backtester,
+        history: Option<&[MarketUpdate]>,
+        tolerance: &ShadowTolerance,
+    ) -> Result<ShadowReport, SdkError> {
+        strategy.configure(&config.strategy_params)?;
+        let window = self.started_at..=self.ended_at;
+        let replayed: Vec<MarketUpdate> = history
+            .unwrap_or(&self.tape)
+            .iter()
+            .filter(|update| window.contains(&(update.timestamp * 1_000)))
+            .cloned()
+            .collect();
+        let expected = backtester.run(strategy, config, &replayed).decisions;
+        let divergences = compare_decisions(&self.decisions, &expected, tolerance);
+        let unmatched_live = divergences
+            .iter()
+            .filter(|divergence| matches!(divergence, Divergence::ExtraLive { .. } | Divergence::Mismatch { .. }))
+            .count();
+        Ok(ShadowReport {
+            live_decisions: self.decisions.len(),
+            expected_decisions: expected.len(),
+            matched: self.decisions.len() - unmatched_live,
+            divergences,
+            gaps: self.gaps.clone(),
+        })
+    }
+}
+
+/// Runs a strategy on live updates and records its decisions instead of sending them
+pub struct ShadowMode {
+    strategy: Box<dyn TradingStrategy>,
+    config: AgentConfig,
+    context: AgentContext,
+    session: ShadowSession,
+}
+
+impl ShadowMode {
+    pub fn new(strategy: Box<dyn TradingStrategy>, config: AgentConfig) -> Result<Self, SdkError> {
+        config.validate()?;
+        let mut strategy = strategy;
+        strategy.configure(&config.strategy_params)?;
+        Ok(Self { strategy, config, context: AgentContext::default(), session: ShadowSession::default() })
+    }
+
+    pub fn session(&self) -> &ShadowSession {
+        &self.session
+    }
+
+    fn record(&mut self, data_at: u64, decided_at: u64, decisions: Vec<AgentDecision>) {
+        for decision in decisions {
+            // Count exposure as a live agent would, so later limit checks agree with the backtest's
+            let rejected = limit_violation(&self.config, self.context.exposure, &decision);
+            if rejected.is_none() {
+                self.context.exposure += decision.notional();
+            }
+            tracing::info!(
+                agent = %self.config.name,
+                market_id = %decision.market_id(),
+                lag_micros = decided_at.saturating_sub(data_at),
+                rejected = rejected.as_deref().unwrap_or(""),
+                "shadow decision"
+            );
+            self.session.decisions.push(ShadowDecision { data_at, decided_at, decision, rejected });
+        }
+    }
+
+    /// Feed one live update received at `received_at` (micros)
+    pub fn observe(&mut self, update: MarketUpdate, received_at: u64) {
+        if self.session.started_at == 0 {
+            self.session.started_at = update.timestamp * 1_000;
+        }
+        self.session.ended_at = self.session.ended_at.max(update.timestamp * 1_000);
+        if let Some(previous) = self.context.updates.get(&update.market_id) {
+            // Sequence 0 comes from servers that do not number updates
+            if previous.sequence > 0 && update.sequence > previous.sequence + 1 {
+                self.session.gaps.push(SequenceGap {
+                    market_id: update.market_id.clone(),
+                    after: previous.sequence,
+                    resumed_at: update.sequence,
+                });
+            }
+        }
+        self.context.now_micros = update.timestamp * 1_000;
+        self.context.updates.insert(update.market_id.clone(), update.clone());
+        let decisions = self.strategy.on_update(&update, &self.context);
+        let data_at = update.timestamp * 1_000;
+        self.session.tape.push(update);
+        self.record(data_at, received_at, decisions);
+    }
+
+    /// Run the decision-interval hook at `now` (micros)
+    pub fn tick(&mut self, now: u64) {
+        self.context.now_micros = now;
+        let decisions = self.strategy.on_tick(&self.context);
+        self.record(now, now, decisions);
+    }
+
+    /// Shadow the config's markets until `shutdown` triggers or the feed ends
+    pub async fn run(mut self, sdk: &OddsStreamSdk, shutdown: Shutdown) -> Result<ShadowSession, SdkError> {
+        let mut updates = sdk.subscribe_market_updates_stream(self.config.market_ids.clone()).await?;
+        let mut ticker = tokio::time::interval(self.config.decision_interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = shutdown.triggered() => break,
+                update = updates.next() => {
+                    let Some(update) = update else { break };
+                    self.observe(update, sdk.clock.now_micros());
+                }
+                _ = ticker.tick() => self.tick(sdk.clock.now_micros()),
+            }
+        }
+        Ok(self.session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(market_id: &str) -> AgentDecision {
+        AgentDecision::Claim { market_id: market_id.to_string() }
+    }
+
+    fn live(decision: AgentDecision, data_at: u64, decided_at: u64) -> ShadowDecision {
+        ShadowDecision { data_at, decided_at, decision, rejected: None }
+    }
+
+    fn expected(decision: AgentDecision, at: u64) -> BacktestDecision {
+        BacktestDecision { at, decision, filled: false, filled_shares: 0.0, note: None }
+    }
+
+    #[test]
+    fn test_divergences_separate_latency_from_different_decisions() {
+        let tolerance = ShadowTolerance { match_window_micros: 1_000, max_lag_micros: 100 };
+        let deposit = AgentDecision::AddLiquidity { market_id: "m2".to_string(), amount: 5.0 };
+        let live_decisions = [
+            live(claim("m1"), 0, 50),
+            live(claim("m1"), 5_000, 5_500),
+            live(deposit.clone(), 9_000, 9_000),
+        ];
+        let expected_decisions = [
+            expected(claim("m1"), 0),
+            expected(claim("m1"), 5_200),
+            expected(AgentDecision::AddLiquidity { market_id: "m2".to_string(), amount: 7.0 }, 9_100),
+            expected(claim("m3"), 12_000),
+        ];
+
+        let divergences = compare_decisions(&live_decisions, &expected_decisions, &tolerance);
+        assert_eq!(divergences.len(), 3);
+        assert!(matches!(&divergences[0], Divergence::Late { lag_micros: 500, .. }));
+        assert!(matches!(&divergences[1], Divergence::Mismatch { live, .. } if live.decision == deposit));
+        assert!(matches!(&divergences[2], Divergence::MissedLive { expected } if expected.decision == claim("m3")));
+    }
+}
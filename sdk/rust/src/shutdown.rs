@@ -0,0 +1,184 @@
+//! Cooperative shutdown shared by subscriptions, the indexer and agent loops
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+
+type ShutdownHook = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// Cloneable token that tells long-running tasks to wind down.
+///
+/// Tasks watch `triggered()` and hold a `ShutdownGuard` while they finish up
+/// (closing sockets, flushing writes); `shutdown()` waits for those guards.
+/// Work that must happen exactly once on the way out, such as cancelling
+/// resting orders or persisting agent state, is registered with `on_shutdown`.
+#[derive(Clone)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    triggered: watch::Sender<bool>,
+    active: AtomicUsize,
+    drained: Notify,
+    hooks: Mutex<Vec<ShutdownHook>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                triggered: watch::Sender::new(false),
+                active: AtomicUsize::new(0),
+                drained: Notify::new(),
+                hooks: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Signal every holder of this token; idempotent
+    pub fn trigger(&self) {
+        self.inner.triggered.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.inner.triggered.borrow()
+    }
+
+    /// Resolves once shutdown has been triggered
+    pub async fn triggered(&self) {
+        let mut receiver = self.inner.triggered.subscribe();
+        // The sender lives in `self`, so this cannot fail
+        let _ = receiver.wait_for(|triggered| *triggered).await;
+    }
+
+    /// Mark work that `shutdown()` should wait for; released on drop
+    pub fn guard(&self) -> ShutdownGuard {
+        self.inner.active.fetch_add(1, Ordering::AcqRel);
+        ShutdownGuard { inner: self.inner.clone() }
+    }
+
+    /// Run `hook` once when `shutdown()` is called, after triggering and before draining
+    pub fn on_shutdown<F, Fut>(&self, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.inner.hooks.lock().unwrap().push(Box::new(move || hook().boxed()));
+    }
+
+    /// Trigger, run the registered hooks in order, then wait up to `grace` for
+    /// guarded work to finish. Returns `false` if the grace period ran out.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        self.trigger();
+
+        let hooks = std::mem::take(&mut *self.inner.hooks.lock().unwrap());
+        for hook in hooks {
+            hook().await;
+        }
+
+        let drained = async {
+            loop {
+                let notified = self.inner.drained.notified();
+                if self.inner.active.load(Ordering::Acquire) == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(grace, drained).await.is_ok()
+    }
+
+    /// Trigger on SIGINT or SIGTERM
+    pub fn listen_for_signals(&self) -> JoinHandle<()> {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+                match signal(SignalKind::terminate()) {
+                    Ok(mut terminate) => {
+                        tokio::select! {
+                            _ = tokio::signal::ctrl_c() => {}
+                            _ = terminate.recv() => {}
+                        }
+                    }
+                    Err(_) => {
+                        let _ = tokio::signal::ctrl_c().await;
+                    }
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+            tracing::info!("shutdown signal received");
+            shutdown.trigger();
+        })
+    }
+}
+
+/// Held by a task that is still winding down
+pub struct ShutdownGuard {
+    inner: Arc<Inner>,
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        if self.inner.active.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.drained.notify_waiters();
+        }
+    }
+}
+
+/// Resolves when `shutdown` triggers; never, if there is no token
+pub(crate) async fn until_shutdown(shutdown: Option<&Shutdown>) {
+    match shutdown {
+        Some(shutdown) => shutdown.triggered().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    #[tokio::test]
+    async fn test_shutdown_runs_hooks_and_waits_for_guards() {
+        let shutdown = Shutdown::new();
+        let flushed = Arc::new(AtomicBool::new(false));
+        let flag = flushed.clone();
+        shutdown.on_shutdown(move || async move { flag.store(true, Ordering::SeqCst) });
+
+        let guard = shutdown.guard();
+        let worker = shutdown.clone();
+        tokio::spawn(async move {
+            worker.triggered().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+
+        assert!(shutdown.shutdown(Duration::from_secs(1)).await);
+        assert!(shutdown.is_triggered());
+        assert!(flushed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_times_out_on_stuck_work() {
+        let shutdown = Shutdown::new();
+        let _stuck = shutdown.guard();
+        assert!(!shutdown.shutdown(Duration::from_millis(10)).await);
+    }
+}
@@ -0,0 +1,193 @@
+//! Client-side order signing
+
+use crate::{Order, RelayerFee, SdkError};
+use async_trait::async_trait;
+use ed25519_dalek::{Signer as _, SigningKey};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Must match the market contract's domain separator
+const ORDER_DOMAIN: &[u8] = b"oddsstream-order-v1";
+
+/// Anything able to produce ed25519 signatures over order digests
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Raw 32-byte ed25519 public key
+    fn public_key(&self) -> Vec<u8>;
+
+    /// Sign a 32-byte order digest
+    async fn sign(&self, digest: &[u8; 32]) -> Result<Vec<u8>, SdkError>;
+}
+
+/// Signer holding the private key in process memory
+pub struct LocalSigner {
+    key: SigningKey,
+}
+
+impl LocalSigner {
+    pub fn from_bytes(secret: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    /// Parse a hex-encoded 32-byte secret key, with or without `0x`
+    pub fn from_hex(secret: &str) -> Result<Self, SdkError> {
+        let bytes = hex::decode(secret.trim_start_matches("0x"))
+            .map_err(|e| SdkError::InvalidInput(format!("private key: {}", e)))?;
+        let secret: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| SdkError::InvalidInput("private key must be 32 bytes".to_string()))?;
+        Ok(Self::from_bytes(&secret))
+    }
+}
+
+#[async_trait]
+impl Signer for LocalSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.key.verifying_key().to_bytes().to_vec()
+    }
+
+    async fn sign(&self, digest: &[u8; 32]) -> Result<Vec<u8>, SdkError> {
+        Ok(self.key.sign(digest).to_bytes().to_vec())
+    }
+}
+
+/// Signature attached to a `BatchedOrders` message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderSignature {
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct OrderPayload<'a> {
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    orders: &'a [Order],
+    relayer_fee: Option<&'a RelayerFee>,
+}
+
+/// Digest the market contract recomputes when verifying a batch; `orders`
+/// are hashed as sent, see [`wire_orders`](crate::wire_orders)
+pub fn order_digest(
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    orders: &[Order],
+    relayer_fee: Option<&RelayerFee>,
+) -> [u8; 32] {
+    let payload = OrderPayload { user_chain_id, market_chain_id, nonce, orders, relayer_fee };
+    let mut hasher = Sha256::new();
+    hasher.update(ORDER_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("order payload is serializable"));
+    hasher.finalize().into()
+}
+
+/// Must match the market contract's amendment domain separator
+const AMEND_DOMAIN: &[u8] = b"oddsstream-amend-v1";
+
+#[derive(Serialize)]
+struct AmendPayload {
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    order_id: u64,
+    amount: Amount,
+    max_price: Option<Amount>,
+}
+
+/// Digest the market contract recomputes when verifying an `AmendOrder`
+pub fn amend_digest(
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    order_id: u64,
+    amount: Amount,
+    max_price: Option<Amount>,
+) -> [u8; 32] {
+    let payload = AmendPayload { user_chain_id, market_chain_id, nonce, order_id, amount, max_price };
+    let mut hasher = Sha256::new();
+    hasher.update(AMEND_DOMAIN);
+    hasher.update(bcs::to_bytes(&payload).expect("amend payload is serializable"));
+    hasher.finalize().into()
+}
+
+/// Sign a batch destined for `market_chain_id`
+pub async fn sign_orders(
+    signer: &dyn Signer,
+    user_chain_id: ChainId,
+    market_chain_id: ChainId,
+    nonce: u64,
+    orders: &[Order],
+    relayer_fee: Option<&RelayerFee>,
+) -> Result<OrderSignature, SdkError> {
+    let digest = order_digest(user_chain_id, market_chain_id, nonce, orders, relayer_fee);
+    Ok(OrderSignature {
+        public_key: signer.public_key(),
+        signature: signer.sign(&digest).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{order_id, wire_orders, MarketOrder, OrderSide};
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    fn order() -> MarketOrder {
+        MarketOrder {
+            market_id: "m1".to_string(),
+            side: OrderSide::Yes,
+            amount: "10".to_string(),
+            max_price: Some("0.6".to_string()),
+            subaccount: None,
+            referral_code: Some("ref".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_verifies_against_digest() {
+        let signer = LocalSigner::from_bytes(&[7u8; 32]);
+        let orders = wire_orders(&[order()], 3).unwrap();
+        let user = ChainId::from([1u8; 32]);
+        let market = ChainId::from([2u8; 32]);
+
+        let sig = sign_orders(&signer, user, market, 3, &orders, None).await.unwrap();
+        let key = VerifyingKey::from_bytes(&sig.public_key.clone().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&sig.signature).unwrap();
+
+        assert!(key.verify(&order_digest(user, market, 3, &orders, None), &signature).is_ok());
+        // Same orders signed for another market must not verify
+        let other = ChainId::from([3u8; 32]);
+        assert!(key.verify(&order_digest(user, other, 3, &orders, None), &signature).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_market_contract_verifies_sdk_signature() {
+        use oddsstream_market::signing;
+
+        let signer = LocalSigner::from_bytes(&[7u8; 32]);
+        let user = ChainId::from([1u8; 32]);
+        let market = ChainId::from([2u8; 32]);
+        let fee = RelayerFee { relayer: ChainId::from([4u8; 32]), amount: Amount::from_tokens(1) };
+        let orders = wire_orders(&[order()], 3).unwrap();
+        let sig = sign_orders(&signer, user, market, 3, &orders, Some(&fee)).await.unwrap();
+
+        // The same batch as the contract's own types describe it
+        let contract_orders = vec![oddsstream_market::Order {
+            id: order_id(3, 0).unwrap(),
+            side: oddsstream_market::OrderSide::BuyYes,
+            amount: Amount::from_tokens(10),
+            max_price: Some("0.6".parse().unwrap()),
+            subaccount: None,
+            referral_code: Some("ref".to_string()),
+        }];
+        let contract_fee = signing::RelayerFee { relayer: fee.relayer, amount: fee.amount };
+        let digest = signing::order_digest(user, market, 3, &contract_orders, Some(&contract_fee));
+        let signature = signing::OrderSignature { public_key: sig.public_key, signature: sig.signature };
+        assert!(signing::verify_order_signature(&digest, &signature));
+    }
+}
@@ -0,0 +1,349 @@
+//! Pre-flight simulation of a batch against the markets' current state
+//!
+//! [`simulate_batch`] replays the checks and fills a market chain runs on a
+//! `BatchedOrders` message: market status, protocol pause, circuit breaker
+//! halts, price protection, pool and exposure caps, then each fill moving the
+//! pools and possibly tripping the breaker for the rest of the batch. Pricing
+//! goes through [`AmmPools`], the client-side mirror of the contract's curve.
+//!
+//! Signatures, nonces and compliance allowlists are not simulated, nor is
+//! anything that lands on the market between the snapshot and the batch.
+//!
+//! [`simulate_batch`]: OddsStreamSdk::simulate_batch
+
+use crate::agent::tokens_to_amount;
+use crate::{AmmPools, MarketOrder, OddsStreamSdk, OrderSide, RejectionReason, SdkError};
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+/// What a batch is checked against on one market, as read from its chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationState {
+    /// Whether the market takes orders at all: open, or collecting its opening auction
+    pub accepting: bool,
+    pub in_auction: bool,
+    pub protocol_paused: bool,
+    /// Micros timestamp trading resumes at, while halted
+    pub halted_until: Option<u64>,
+    pub reopen_after_halt: bool,
+    pub pools: AmmPools,
+    /// The user chain's rate at its current fee tier
+    pub taker_fee_bps: u32,
+    /// `None` if the pool is uncapped
+    pub remaining_pool_capacity: Option<f64>,
+    /// `None` if exposure is uncapped
+    pub remaining_user_exposure: Option<f64>,
+    /// 0 when the market has no breaker
+    pub breaker_max_move_bps: u32,
+    pub breaker_cooldown_secs: u64,
+    /// YES odds the breaker measures moves from; the market's current odds if no window is open
+    pub breaker_reference_odds: Option<f64>,
+}
+
+/// How the market would handle one order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SimulatedOutcome {
+    /// Filled at `price`; `cost` includes the taker fee
+    Filled { price: f64, cost: f64 },
+    /// Queued for the market's call auction, priced when it uncrosses
+    Queued,
+    Rejected(RejectionReason),
+}
+
+/// One order of the batch, in the order it was given
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedOrder {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub shares: f64,
+    pub outcome: SimulatedOutcome,
+}
+
+/// Where a market would stand once its part of the batch filled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSimulation {
+    pub market_id: String,
+    pub yes_odds_before: f64,
+    pub yes_odds_after: f64,
+    pub no_odds_after: f64,
+    pub total_cost: f64,
+    /// Whether the batch would trip the market's circuit breaker
+    pub trips_breaker: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSimulation {
+    pub orders: Vec<SimulatedOrder>,
+    pub markets: Vec<MarketSimulation>,
+    /// What the user chain would pay across all markets, fees included
+    pub total_cost: f64,
+}
+
+impl BatchSimulation {
+    /// Orders the markets would refuse, with their index in the batch
+    pub fn rejections(&self) -> impl Iterator<Item = (usize, &RejectionReason)> {
+        self.orders.iter().enumerate().filter_map(|(index, order)| match &order.outcome {
+            SimulatedOutcome::Rejected(reason) => Some((index, reason)),
+            _ => None,
+        })
+    }
+
+    /// Whether every order would fill or queue
+    pub fn is_clean(&self) -> bool {
+        self.rejections().next().is_none()
+    }
+}
+
+/// Run one market's orders through the contract's batch logic at `now` (micros)
+pub fn simulate_market(
+    market_id: &str,
+    state: &SimulationState,
+    orders: &[(OrderSide, f64, Option<f64>)],
+    now: u64,
+) -> (Vec<SimulatedOutcome>, MarketSimulation) {
+    let mut pools = state.pools;
+    let yes_odds_before = pools.odds(OrderSide::Yes);
+    let mut summary = MarketSimulation {
+        market_id: market_id.to_string(),
+        yes_odds_before,
+        yes_odds_after: yes_odds_before,
+        no_odds_after: pools.odds(OrderSide::No),
+        total_cost: 0.0,
+        trips_breaker: false,
+    };
+    let reject_all = |reason: RejectionReason| vec![SimulatedOutcome::Rejected(reason); orders.len()];
+
+    if !state.accepting {
+        return (reject_all(RejectionReason::MarketClosed), summary);
+    }
+    if state.protocol_paused {
+        return (reject_all(RejectionReason::ProtocolPaused), summary);
+    }
+    let halted_until = state.halted_until.filter(|until| now < *until);
+    if state.in_auction || (halted_until.is_some() && state.reopen_after_halt) {
+        return (vec![SimulatedOutcome::Queued; orders.len()], summary);
+    }
+    if let Some(until) = halted_until {
+        return (reject_all(RejectionReason::Halted { until }), summary);
+    }
+
+    let reference_odds = state.breaker_reference_odds.unwrap_or(yes_odds_before);
+    let max_move = f64::from(state.breaker_max_move_bps) / BPS_DENOMINATOR;
+    let mut remaining_pool = state.remaining_pool_capacity;
+    let mut remaining_exposure = state.remaining_user_exposure;
+    let mut outcomes = Vec::with_capacity(orders.len());
+    for &(side, shares, max_price) in orders {
+        if summary.trips_breaker {
+            let until = now.saturating_add(state.breaker_cooldown_secs * 1_000_000);
+            outcomes.push(SimulatedOutcome::Rejected(RejectionReason::Halted { until }));
+            continue;
+        }
+        let price = pools.odds(side);
+        let cost = shares * price;
+        let rejection = match max_price {
+            Some(limit) if price > limit => Some(RejectionReason::PriceProtection {
+                limit: tokens_to_amount(limit).unwrap_or_default(),
+                price: tokens_to_amount(price).unwrap_or_default(),
+            }),
+            _ => match (remaining_pool, remaining_exposure) {
+                (Some(remaining), _) if shares > remaining => Some(RejectionReason::PoolCapReached {
+                    remaining: tokens_to_amount(remaining).unwrap_or_default(),
+                }),
+                (_, Some(remaining)) if cost > remaining => Some(RejectionReason::ExposureCapReached {
+                    remaining: tokens_to_amount(remaining).unwrap_or_default(),
+                }),
+                _ => None,
+            },
+        };
+        if let Some(reason) = rejection {
+            outcomes.push(SimulatedOutcome::Rejected(reason));
+            continue;
+        }
+
+        pools.buy(side, shares);
+        let cost = cost + cost * f64::from(state.taker_fee_bps) / BPS_DENOMINATOR;
+        remaining_pool = remaining_pool.map(|remaining| remaining - shares);
+        remaining_exposure = remaining_exposure.map(|remaining| remaining - shares * price);
+        summary.total_cost += cost;
+        outcomes.push(SimulatedOutcome::Filled { price, cost });
+        summary.trips_breaker = max_move > 0.0 && (pools.odds(OrderSide::Yes) - reference_odds).abs() > max_move;
+    }
+    summary.yes_odds_after = pools.odds(OrderSide::Yes);
+    summary.no_odds_after = pools.odds(OrderSide::No);
+    (outcomes, summary)
+}
+
+#[derive(Deserialize)]
+struct SimulationStateData {
+    #[serde(rename = "simulationState")]
+    simulation_state: Option<RawSimulationState>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSimulationState {
+    accepting: bool,
+    in_auction: bool,
+    protocol_paused: bool,
+    halted_until: Option<u64>,
+    reopen_after_halt: bool,
+    pool_yes: String,
+    pool_no: String,
+    taker_fee_bps: u32,
+    remaining_pool_capacity: Option<String>,
+    remaining_user_exposure: Option<String>,
+    breaker_max_move_bps: u32,
+    breaker_cooldown_secs: u64,
+    breaker_reference_odds: Option<f64>,
+}
+
+impl OddsStreamSdk {
+    /// The state `simulate_batch` checks orders into `market_id` from `user_chain_id` against
+    pub async fn simulation_state(&self, market_id: &str, user_chain_id: ChainId) -> Result<SimulationState, SdkError> {
+        let query = r#"
+            query SimulationState($marketId: String!, $owner: String, $now: Int) {
+                simulationState(marketId: $marketId, owner: $owner, now: $now) {
+                    accepting
+                    inAuction
+                    protocolPaused
+                    haltedUntil
+                    reopenAfterHalt
+                    poolYes
+                    poolNo
+                    takerFeeBps
+                    remainingPoolCapacity
+                    remainingUserExposure
+                    breakerMaxMoveBps
+                    breakerCooldownSecs
+                    breakerReferenceOdds
+                }
+            }
+        "#;
+        let variables = serde_json::json!({
+            "marketId": market_id,
+            "owner": user_chain_id.to_string(),
+            "now": self.clock.now_micros(),
+        });
+        let data: SimulationStateData = self.graphql_query_fresh(query, variables).await?;
+        let raw = data
+            .simulation_state
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+
+        let parse = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|e| SdkError::InvalidInput(format!("invalid amount {value}: {e}")))
+        };
+        Ok(SimulationState {
+            accepting: raw.accepting,
+            in_auction: raw.in_auction,
+            protocol_paused: raw.protocol_paused,
+            halted_until: raw.halted_until,
+            reopen_after_halt: raw.reopen_after_halt,
+            pools: AmmPools::new(parse(&raw.pool_yes)?, parse(&raw.pool_no)?),
+            taker_fee_bps: raw.taker_fee_bps,
+            remaining_pool_capacity: raw.remaining_pool_capacity.as_deref().map(parse).transpose()?,
+            remaining_user_exposure: raw.remaining_user_exposure.as_deref().map(parse).transpose()?,
+            breaker_max_move_bps: raw.breaker_max_move_bps,
+            breaker_cooldown_secs: raw.breaker_cooldown_secs,
+            breaker_reference_odds: raw.breaker_reference_odds,
+        })
+    }
+
+    /// Predict what `submit_batched_orders` would fill, refuse and cost, without sending anything
+    pub async fn simulate_batch(&self, orders: &[MarketOrder], user_chain_id: ChainId) -> Result<BatchSimulation, SdkError> {
+        let parse = |value: &str, what: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|e| SdkError::InvalidInput(format!("invalid {what} {value}: {e}")))
+        };
+        let mut by_market: BTreeMap<&str, Vec<(usize, (OrderSide, f64, Option<f64>))>> = BTreeMap::new();
+        for (index, order) in orders.iter().enumerate() {
+            let shares = parse(&order.amount, "amount")?;
+            let max_price = order.max_price.as_deref().map(|price| parse(price, "max price")).transpose()?;
+            by_market.entry(order.market_id.as_str()).or_default().push((index, (order.side, shares, max_price)));
+        }
+        let states = futures::future::try_join_all(
+            by_market.keys().map(|market_id| self.simulation_state(market_id, user_chain_id)),
+        )
+        .await?;
+
+        let now = self.clock.now_micros();
+        let mut outcomes = vec![SimulatedOutcome::Queued; orders.len()];
+        let mut markets = Vec::with_capacity(by_market.len());
+        for ((market_id, indexed), state) in by_market.into_iter().zip(states) {
+            let (indexes, market_orders): (Vec<usize>, Vec<_>) = indexed.into_iter().unzip();
+            let (market_outcomes, summary) = simulate_market(market_id, &state, &market_orders, now);
+            for (index, outcome) in indexes.into_iter().zip(market_outcomes) {
+                outcomes[index] = outcome;
+            }
+            markets.push(summary);
+        }
+
+        let orders = orders
+            .iter()
+            .zip(outcomes)
+            .map(|(order, outcome)| SimulatedOrder {
+                market_id: order.market_id.clone(),
+                side: order.side,
+                shares: parse(&order.amount, "amount").unwrap_or_default(),
+                outcome,
+            })
+            .collect();
+        let total_cost = markets.iter().map(|market| market.total_cost).sum();
+        Ok(BatchSimulation { orders, markets, total_cost })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> SimulationState {
+        SimulationState {
+            accepting: true,
+            in_auction: false,
+            protocol_paused: false,
+            halted_until: None,
+            reopen_after_halt: false,
+            pools: AmmPools::new(500.0, 500.0),
+            taker_fee_bps: 100,
+            remaining_pool_capacity: Some(150.0),
+            remaining_user_exposure: None,
+            breaker_max_move_bps: 0,
+            breaker_cooldown_secs: 60,
+            breaker_reference_odds: None,
+        }
+    }
+
+    #[test]
+    fn test_fills_move_the_pools_until_a_cap_refuses() {
+        let orders = [(OrderSide::Yes, 100.0, None), (OrderSide::Yes, 100.0, None), (OrderSide::No, 10.0, Some(0.1))];
+        let (outcomes, summary) = simulate_market("m1", &state(), &orders, 0);
+
+        assert_eq!(outcomes[0], SimulatedOutcome::Filled { price: 0.5, cost: 50.5 });
+        assert!(matches!(outcomes[1], SimulatedOutcome::Rejected(RejectionReason::PoolCapReached { .. })));
+        assert!(matches!(outcomes[2], SimulatedOutcome::Rejected(RejectionReason::PriceProtection { .. })));
+        assert!((summary.total_cost - 50.5).abs() < 1e-9);
+        assert!(summary.yes_odds_after < summary.yes_odds_before);
+    }
+
+    #[test]
+    fn test_breaker_halts_the_rest_of_the_batch() {
+        let state = SimulationState { breaker_max_move_bps: 500, remaining_pool_capacity: None, ..state() };
+        let orders = [(OrderSide::No, 200.0, None), (OrderSide::No, 1.0, None)];
+        let (outcomes, summary) = simulate_market("m1", &state, &orders, 1_000);
+
+        assert!(summary.trips_breaker);
+        assert!(matches!(outcomes[0], SimulatedOutcome::Filled { .. }));
+        assert_eq!(outcomes[1], SimulatedOutcome::Rejected(RejectionReason::Halted { until: 60_001_000 }));
+    }
+}
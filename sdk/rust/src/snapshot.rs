@@ -0,0 +1,94 @@
+//! Point-in-time market state snapshots for audits and local simulation
+
+use crate::{OddsStreamSdk, OrderSide, SdkError};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Complete state of a single market at a given block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSnapshot {
+    pub market_id: String,
+    pub description: String,
+    pub status: String,
+    pub pool_yes: f64,
+    pub pool_no: f64,
+    pub yes_odds: f64,
+    pub no_odds: f64,
+    pub resolution_time: u64,
+    pub block_height: u64,
+    pub resting_orders: Vec<RestingOrder>,
+    pub positions: Vec<PositionSnapshot>,
+}
+
+/// A limit order waiting on the market's book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestingOrder {
+    pub order_id: String,
+    pub owner: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub remaining: f64,
+}
+
+/// Shares held by a single user chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionSnapshot {
+    pub owner: String,
+    pub yes_shares: f64,
+    pub no_shares: f64,
+    pub cost_basis: f64,
+}
+
+#[derive(Deserialize)]
+struct SnapshotData {
+    #[serde(rename = "marketSnapshot")]
+    market_snapshot: Option<MarketSnapshot>,
+}
+
+impl MarketSnapshot {
+    /// Write the snapshot as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SdkError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Load a snapshot previously written with [`MarketSnapshot::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SdkError> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Fetch the full state of a market (pools, resting orders, positions, status)
+    pub async fn snapshot_market(&self, market_id: &str) -> Result<MarketSnapshot, SdkError> {
+        let query = r#"
+            query MarketSnapshot($marketId: String!) {
+                marketSnapshot(marketId: $marketId) {
+                    marketId
+                    description
+                    status
+                    poolYes
+                    poolNo
+                    yesOdds
+                    noOdds
+                    resolutionTime
+                    blockHeight
+                    restingOrders { orderId owner side price remaining }
+                    positions { owner yesShares noShares costBasis }
+                }
+            }
+        "#;
+
+        let data: SnapshotData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+
+        data.market_snapshot
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+}
@@ -0,0 +1,227 @@
+//! Staged resolution for long-running markets: each stage the oracle settles
+//! releases a share of the payout to that stage's winning side, and whatever
+//! the stages leave is paid at final resolution
+
+use crate::transport::Transport;
+use crate::{OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A stage schedule divides up this much of the payout
+pub const FULL_PAYOUT_BPS: u32 = 10_000;
+
+/// Most stages a market accepts
+pub const MAX_STAGES: usize = 32;
+
+/// One stage of a schedule, as given at market creation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageSpec {
+    /// Label shown to traders, such as "Quarter-finals"
+    pub name: String,
+    /// Share of the payout released when the stage settles, in basis points
+    pub share_bps: u32,
+}
+
+/// Check a schedule the way the registry will, so a bad one fails before it is sent
+pub fn validate_stage_schedule(stages: &[StageSpec]) -> Result<(), SdkError> {
+    if stages.len() > MAX_STAGES {
+        return Err(SdkError::InvalidInput(format!(
+            "{} stages exceeds the maximum of {}",
+            stages.len(),
+            MAX_STAGES
+        )));
+    }
+    let released: u64 = stages.iter().map(|stage| u64::from(stage.share_bps)).sum();
+    if released > u64::from(FULL_PAYOUT_BPS) {
+        return Err(SdkError::InvalidInput(format!(
+            "stages release {} bps, more than the whole payout",
+            released
+        )));
+    }
+    Ok(())
+}
+
+/// A stage of a live market and how it settled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketStage {
+    pub index: u32,
+    pub name: String,
+    pub share_bps: u32,
+    /// Winning side; `None` until the oracle reports the stage
+    pub outcome: Option<bool>,
+    /// Micros timestamp the stage settled at
+    pub resolved_at: Option<u64>,
+    /// Total credited to the stage's winners
+    pub paid_out: Amount,
+}
+
+impl MarketStage {
+    pub fn is_settled(&self) -> bool {
+        self.outcome.is_some()
+    }
+}
+
+/// A market's stage schedule, in settlement order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageSchedule {
+    pub stages: Vec<MarketStage>,
+}
+
+impl StageSchedule {
+    /// Basis points of the payout settled stages have released
+    pub fn released_bps(&self) -> u32 {
+        self.stages
+            .iter()
+            .filter(|stage| stage.is_settled())
+            .map(|stage| stage.share_bps)
+            .sum()
+    }
+
+    /// Basis points of the payout left for final resolution
+    pub fn remaining_bps(&self) -> u32 {
+        FULL_PAYOUT_BPS.saturating_sub(self.released_bps())
+    }
+
+    /// First stage the oracle has not reported yet
+    pub fn next_stage(&self) -> Option<&MarketStage> {
+        self.stages.iter().find(|stage| !stage.is_settled())
+    }
+}
+
+#[derive(Deserialize)]
+struct StagesData {
+    stages: Option<Vec<RawStage>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawStage {
+    index: u32,
+    name: String,
+    share_bps: u32,
+    outcome: Option<bool>,
+    resolved_at: Option<u64>,
+    paid_out: String,
+}
+
+#[derive(Deserialize)]
+struct StageCreditData {
+    #[serde(rename = "stageCredit")]
+    stage_credit: Option<String>,
+}
+
+fn parse_amount(raw: &str) -> Result<Amount, SdkError> {
+    Amount::from_str(raw).map_err(|e| SdkError::InvalidInput(format!("invalid amount {}: {}", raw, e)))
+}
+
+impl Transport {
+    pub(crate) async fn market_stages(&self, market_id: &str) -> Result<StageSchedule, SdkError> {
+        let query = r#"
+            query Stages($marketId: String!) {
+                stages(marketId: $marketId) { index name shareBps outcome resolvedAt paidOut }
+            }
+        "#;
+
+        let data: StagesData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let raw = data
+            .stages
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+        let stages = raw
+            .into_iter()
+            .map(|stage| {
+                Ok(MarketStage {
+                    index: stage.index,
+                    name: stage.name,
+                    share_bps: stage.share_bps,
+                    outcome: stage.outcome,
+                    resolved_at: stage.resolved_at,
+                    paid_out: parse_amount(&stage.paid_out)?,
+                })
+            })
+            .collect::<Result<_, SdkError>>()?;
+        Ok(StageSchedule { stages })
+    }
+
+    pub(crate) async fn stage_credit(&self, market_id: &str, owner: ChainId) -> Result<Amount, SdkError> {
+        let query = r#"
+            query StageCredit($marketId: String!, $owner: String!) {
+                stageCredit(marketId: $marketId, owner: $owner)
+            }
+        "#;
+
+        let data: StageCreditData = self
+            .graphql_query_fresh(
+                query,
+                serde_json::json!({ "marketId": market_id, "owner": owner.to_string() }),
+            )
+            .await?;
+        let credit = data
+            .stage_credit
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+        parse_amount(&credit)
+    }
+}
+
+impl OddsStreamSdk {
+    /// The market's stage schedule; empty for markets that resolve in one go
+    pub async fn market_stages(&self, market_id: &str) -> Result<StageSchedule, SdkError> {
+        self.transport.market_stages(market_id).await
+    }
+
+    /// Stage winnings this SDK's chain can claim from `market_id` now.
+    /// `claim` pays them together with any final payout.
+    pub async fn stage_credit(&self, market_id: &str) -> Result<Amount, SdkError> {
+        self.transport.stage_credit(market_id, self.chain_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// The market's stage schedule; empty for markets that resolve in one go
+    pub async fn market_stages(&self, market_id: &str) -> Result<StageSchedule, SdkError> {
+        self.transport.market_stages(market_id).await
+    }
+
+    /// Stage winnings `owner` can claim from `market_id` now
+    pub async fn stage_credit(&self, market_id: &str, owner: ChainId) -> Result<Amount, SdkError> {
+        self.transport.stage_credit(market_id, owner).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, share_bps: u32) -> StageSpec {
+        StageSpec { name: name.to_string(), share_bps }
+    }
+
+    #[test]
+    fn test_schedule_cannot_release_more_than_the_payout() {
+        assert!(validate_stage_schedule(&[spec("Groups", 2_500), spec("Semis", 2_500)]).is_ok());
+        assert!(validate_stage_schedule(&[spec("Groups", 6_000), spec("Semis", 4_001)]).is_err());
+    }
+
+    #[test]
+    fn test_remaining_share_counts_only_settled_stages() {
+        let stage = |index, share_bps, outcome| MarketStage {
+            index,
+            name: format!("stage {}", index),
+            share_bps,
+            outcome,
+            resolved_at: outcome.map(|_| 1_000),
+            paid_out: Amount::ZERO,
+        };
+        let schedule = StageSchedule {
+            stages: vec![stage(0, 2_000, Some(true)), stage(1, 3_000, None)],
+        };
+        assert_eq!(schedule.released_bps(), 2_000);
+        assert_eq!(schedule.remaining_bps(), 8_000);
+        assert_eq!(schedule.next_stage().map(|stage| stage.index), Some(1));
+    }
+}
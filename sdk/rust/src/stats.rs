@@ -0,0 +1,137 @@
+//! Per-market volatility and liquidity metrics
+
+use crate::{OddsStreamSdk, PricePoint, SdkError};
+use serde::{Deserialize, Serialize};
+
+/// Odds samples used to estimate realized volatility
+const VOLATILITY_SAMPLES: usize = 240;
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// Volatility and liquidity picture of one market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketStats {
+    pub market_id: String,
+    /// Daily standard deviation of the YES implied probability
+    pub realized_volatility: Option<f64>,
+    pub yes_depth: f64,
+    pub no_depth: f64,
+    pub trade_count: u64,
+    pub average_trade_size: f64,
+    /// `None` if the market has never traded
+    pub seconds_since_last_trade: Option<u64>,
+    /// What orders can still add to the pools; `None` if the pool is uncapped
+    pub remaining_pool_capacity: Option<f64>,
+    /// What this SDK's chain can still pay for shares; `None` if exposure is uncapped
+    pub remaining_user_exposure: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMarketStats {
+    yes_depth: String,
+    no_depth: String,
+    trade_count: u64,
+    average_trade_size: f64,
+    last_trade_at: Option<u64>,
+    remaining_pool_capacity: Option<String>,
+    remaining_user_exposure: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StatsData {
+    #[serde(rename = "marketStats")]
+    market_stats: Option<RawMarketStats>,
+}
+
+/// Standard deviation of probability changes, scaled to one day by the average sample spacing
+pub fn realized_volatility(history: &[PricePoint]) -> Option<f64> {
+    if history.len() < 3 {
+        return None;
+    }
+    let changes: Vec<f64> = history.windows(2).map(|w| w[1].yes_odds - w[0].yes_odds).collect();
+    let mean = changes.iter().sum::<f64>() / changes.len() as f64;
+    let variance = changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / (changes.len() - 1) as f64;
+
+    let span_ms = history.last()?.timestamp.saturating_sub(history.first()?.timestamp);
+    if span_ms == 0 {
+        return None;
+    }
+    let samples_per_day = MS_PER_DAY / (span_ms as f64 / changes.len() as f64);
+    Some((variance * samples_per_day).sqrt())
+}
+
+impl OddsStreamSdk {
+    /// Combine the market chain's trade statistics with volatility from the odds history
+    pub async fn market_stats(&self, market_id: &str) -> Result<MarketStats, SdkError> {
+        let query = r#"
+            query MarketStats($marketId: String!, $owner: String) {
+                marketStats(marketId: $marketId, owner: $owner) {
+                    yesDepth
+                    noDepth
+                    tradeCount
+                    averageTradeSize
+                    lastTradeAt
+                    remainingPoolCapacity
+                    remainingUserExposure
+                }
+            }
+        "#;
+
+        let data: StatsData = self
+            .graphql_query(
+                query,
+                serde_json::json!({ "marketId": market_id, "owner": self.chain_id.to_string() }),
+            )
+            .await?;
+        let raw = data
+            .market_stats
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))?;
+        let history = self.price_history(market_id, VOLATILITY_SAMPLES).await?;
+
+        let parse_amount = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|e| SdkError::InvalidInput(format!("invalid amount {value}: {e}")))
+        };
+        let now_micros = self.clock.now_micros();
+
+        Ok(MarketStats {
+            market_id: market_id.to_string(),
+            realized_volatility: realized_volatility(&history),
+            yes_depth: parse_amount(&raw.yes_depth)?,
+            no_depth: parse_amount(&raw.no_depth)?,
+            trade_count: raw.trade_count,
+            average_trade_size: raw.average_trade_size,
+            seconds_since_last_trade: raw
+                .last_trade_at
+                .map(|at| now_micros.saturating_sub(at) / 1_000_000),
+            remaining_pool_capacity: raw.remaining_pool_capacity.as_deref().map(parse_amount).transpose()?,
+            remaining_user_exposure: raw.remaining_user_exposure.as_deref().map(parse_amount).transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realized_volatility() {
+        // Alternating +/-0.01 per minute
+        let history: Vec<PricePoint> = (0..11)
+            .map(|i| PricePoint {
+                timestamp: i * 60_000,
+                yes_odds: if i % 2 == 0 { 0.50 } else { 0.51 },
+            })
+            .collect();
+
+        let vol = realized_volatility(&history).unwrap();
+        let per_minute = (0.0001_f64 * 10.0 / 9.0).sqrt();
+        assert!((vol - per_minute * 1440_f64.sqrt()).abs() < 1e-9);
+
+        let flat = vec![history[0]; 3];
+        assert_eq!(realized_volatility(&flat), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! WebSocket subscription transport with heartbeats and reconnects
+
+use crate::shutdown::until_shutdown;
+use crate::{CoalesceKey, DecodeFrame, Shutdown, UpdateSender};
+use futures::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::time::{interval, sleep, Instant, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Liveness and reconnect settings for subscriptions
+#[derive(Debug, Clone)]
+pub struct SubscriptionConfig {
+    /// How often a ping is sent on an otherwise idle connection
+    pub heartbeat_interval: Duration,
+    /// Reconnect when neither a message nor a pong arrives within this window
+    pub stale_timeout: Duration,
+    /// First delay before reconnecting; doubled on each consecutive failure
+    pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(15),
+            stale_timeout: Duration::from_secs(45),
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Why a connection was torn down
+#[derive(Debug)]
+enum Disconnect {
+    Stale,
+    Closed,
+    Error(String),
+    Shutdown,
+}
+
+/// Keep a subscription alive until the task is aborted.
+///
+/// Each (re)connection sends `subscribe_msg` and pushes decoded text frames
+/// into `sender`. A watchdog forces a reconnect when the socket goes quiet for
+/// longer than `stale_timeout`, which catches stalls that never surface an error.
+/// While `sender` is full under the `Block` policy the socket isn't read, but
+/// heartbeats and shutdown still go through; the watchdog restarts once the
+/// consumer takes the update.
+/// When `shutdown` triggers, the socket is closed cleanly and the task returns,
+/// dropping `sender` so consumers see the end of the stream.
+pub(crate) async fn run_subscription<T: DecodeFrame + CoalesceKey>(
+    ws_url: String,
+    subscribe_msg: serde_json::Value,
+    config: SubscriptionConfig,
+    sender: UpdateSender<T>,
+    shutdown: Option<Shutdown>,
+) {
+    let mut backoff = config.reconnect_delay;
+    // Held until the socket is closed so graceful shutdown waits for it
+    let _guard = shutdown.as_ref().map(Shutdown::guard);
+
+    loop {
+        let connected = tokio::select! {
+            connected = tokio_tungstenite::connect_async(&ws_url) => connected,
+            _ = until_shutdown(shutdown.as_ref()) => return,
+        };
+        match connected {
+            Ok((mut ws_stream, _)) => {
+                let sent = ws_stream
+                    .send(Message::Text(subscribe_msg.to_string().into()))
+                    .await;
+
+                let reason = match sent {
+                    Ok(()) => {
+                        tracing::debug!(url = %ws_url, "subscription connected");
+                        backoff = config.reconnect_delay;
+                        pump(&mut ws_stream, &config, &sender, shutdown.as_ref()).await
+                    }
+                    Err(e) => Disconnect::Error(e.to_string()),
+                };
+                let _ = ws_stream.close(None).await;
+                if matches!(reason, Disconnect::Shutdown) {
+                    tracing::debug!(url = %ws_url, "subscription closed for shutdown");
+                    return;
+                }
+                tracing::warn!(url = %ws_url, ?reason, "subscription dropped");
+            }
+            Err(e) => {
+                tracing::warn!(url = %ws_url, error = %e, "subscription connect failed");
+            }
+        }
+
+        tracing::debug!(url = %ws_url, delay_ms = backoff.as_millis() as u64, "reconnecting");
+        tokio::select! {
+            _ = sleep(backoff) => {}
+            _ = until_shutdown(shutdown.as_ref()) => return,
+        }
+        backoff = (backoff * 2).min(config.max_reconnect_delay);
+    }
+}
+
+async fn pump<S, T: DecodeFrame + CoalesceKey>(
+    ws_stream: &mut S,
+    config: &SubscriptionConfig,
+    sender: &UpdateSender<T>,
+    shutdown: Option<&Shutdown>,
+) -> Disconnect
+where
+    S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>>
+        + SinkExt<Message>
+        + Unpin,
+{
+    let mut heartbeat = interval(config.heartbeat_interval);
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_seen = Instant::now();
+    let mut pending: Option<T> = None;
+
+    loop {
+        if let Some(item) = pending.take() {
+            let send = sender.send(item);
+            tokio::pin!(send);
+            loop {
+                tokio::select! {
+                    _ = &mut send => break,
+                    _ = heartbeat.tick() => {
+                        if ws_stream.send(Message::Ping(Vec::new().into())).await.is_err() {
+                            return Disconnect::Error("failed to send heartbeat".to_string());
+                        }
+                    }
+                    _ = until_shutdown(shutdown) => {
+                        return Disconnect::Shutdown;
+                    }
+                }
+            }
+            // Time spent waiting on the consumer isn't the connection going quiet
+            last_seen = Instant::now();
+        }
+
+        let deadline = last_seen + config.stale_timeout;
+
+        tokio::select! {
+            msg = ws_stream.next() => {
+                last_seen = Instant::now();
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        pending = T::decode_frame(&text);
+                    }
+                    Some(Ok(Message::Ping(payload))) => {
+                        let _ = ws_stream.send(Message::Pong(payload)).await;
+                    }
+                    Some(Ok(Message::Close(_))) | None => return Disconnect::Closed,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Disconnect::Error(e.to_string()),
+                }
+            }
+            _ = heartbeat.tick() => {
+                if ws_stream.send(Message::Ping(Vec::new().into())).await.is_err() {
+                    return Disconnect::Error("failed to send heartbeat".to_string());
+                }
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                return Disconnect::Stale;
+            }
+            _ = until_shutdown(shutdown) => {
+                return Disconnect::Shutdown;
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Market templates for recurring events such as weekly fixtures
+
+use crate::{validate_fee_shares, OddsStreamSdk, SdkError};
+use linera_sdk::base::{Amount, ApplicationId};
+use serde::{Deserialize, Serialize};
+
+/// Oracle a templated market resolves through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OracleConfig {
+    FastTee { public_key: String },
+    Committee { member_count: u32 },
+    Hybrid,
+}
+
+/// Settings shared by every market created from a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketTemplate {
+    /// `{fixture}` is replaced with the fixture name
+    pub description: String,
+    pub oracle: OracleConfig,
+    pub category: String,
+    pub taker_fee_bps: u32,
+    #[serde(default)]
+    pub referral_share_bps: u32,
+    #[serde(default)]
+    pub oracle_share_bps: u32,
+    #[serde(default)]
+    pub protocol_share_bps: u32,
+    #[serde(default)]
+    pub lp_cooldown_secs: u64,
+    #[serde(default)]
+    pub early_exit_fee_bps: u32,
+    #[serde(default)]
+    pub initial_liquidity: Amount,
+    /// Opening YES probability in basis points; 0 means even odds
+    #[serde(default)]
+    pub initial_yes_odds_bps: u32,
+    /// Time between the fixture start and resolution
+    pub resolution_delay_secs: u64,
+    /// Fungible token every instance settles in; `None` for the native token
+    #[serde(default)]
+    pub settlement_token: Option<ApplicationId>,
+}
+
+/// The one thing that changes between instances of a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+    pub name: String,
+    /// Micros since the Unix epoch
+    pub starts_at: u64,
+}
+
+/// Stored template as listed by the registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub oracle: String,
+    pub taker_fee_bps: u32,
+    pub initial_liquidity: String,
+    pub resolution_delay_secs: u64,
+}
+
+#[derive(Deserialize)]
+struct TemplatesData {
+    #[serde(rename = "marketTemplates")]
+    market_templates: Vec<TemplateInfo>,
+}
+
+impl OddsStreamSdk {
+    /// Run a mutation against the registry application
+    pub(crate) async fn execute_registry_operation(
+        &self,
+        mutation: &str,
+        variables: serde_json::Value,
+    ) -> Result<String, SdkError> {
+        let (chain_id, app_id) = self
+            .registry
+            .ok_or_else(|| SdkError::InvalidInput("no registry application configured".to_string()))?;
+        self.execute_operation(chain_id, app_id, mutation, variables).await
+    }
+
+    /// Store (or replace) a template on the registry; needs the operator role
+    pub async fn save_market_template(
+        &self,
+        name: &str,
+        template: &MarketTemplate,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation SaveTemplate($name: String!, $template: MarketTemplate!) {
+                saveTemplate(name: $name, template: $template)
+            }
+        "#;
+        validate_fee_shares(
+            template.taker_fee_bps,
+            template.referral_share_bps,
+            template.oracle_share_bps,
+            template.protocol_share_bps,
+        )?;
+        let template = serde_json::json!({
+            "description": template.description,
+            "oracleType": template.oracle,
+            "feeSchedule": {
+                "takerFeeBps": template.taker_fee_bps,
+                "referralShareBps": template.referral_share_bps,
+                "oracleShareBps": template.oracle_share_bps,
+                "protocolShareBps": template.protocol_share_bps,
+                "lpCooldown": template.lp_cooldown_secs * 1_000_000,
+                "earlyExitFeeBps": template.early_exit_fee_bps,
+            },
+            "amm": {
+                "initialLiquidity": template.initial_liquidity.to_string(),
+                "initialYesOddsBps": template.initial_yes_odds_bps,
+            },
+            "category": template.category,
+            "resolutionDelay": template.resolution_delay_secs * 1_000_000,
+            "settlementToken": template.settlement_token.map(|token| token.to_string()),
+        });
+        self.execute_registry_operation(mutation, serde_json::json!({ "name": name, "template": template }))
+            .await
+    }
+
+    pub async fn market_templates(&self) -> Result<Vec<TemplateInfo>, SdkError> {
+        let query = r#"
+            query MarketTemplates {
+                marketTemplates {
+                    name
+                    description
+                    category
+                    oracle
+                    takerFeeBps
+                    initialLiquidity
+                    resolutionDelaySecs
+                }
+            }
+        "#;
+
+        let data: TemplatesData = self.graphql_query_cached(query, serde_json::json!({})).await?;
+        Ok(data.market_templates)
+    }
+
+    /// Create a market from a stored template; its ID is `<template>-<fixture slug>`
+    pub async fn create_market_from_template(
+        &self,
+        template: &str,
+        fixture: &Fixture,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation CreateMarketFromTemplate($template: String!, $fixture: Fixture!) {
+                createMarketFromTemplate(template: $template, fixture: $fixture)
+            }
+        "#;
+        self.execute_registry_operation(mutation, serde_json::json!({ "template": template, "fixture": fixture }))
+            .await
+    }
+}
@@ -0,0 +1,190 @@
+//! Settlement tokens: which asset a market's stakes and payouts move in, and balances per token
+
+use crate::transport::Transport;
+use crate::{GraphQLResponse, OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Asset a market settles in, fixed at creation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SettlementToken {
+    /// The chain's native token
+    #[default]
+    Native,
+    /// An application-level fungible token
+    Fungible(ApplicationId),
+}
+
+impl SettlementToken {
+    /// The token application, or `None` for the native token, as markets and the registry store it
+    pub fn application_id(&self) -> Option<ApplicationId> {
+        match self {
+            SettlementToken::Native => None,
+            SettlementToken::Fungible(app_id) => Some(*app_id),
+        }
+    }
+}
+
+impl From<Option<ApplicationId>> for SettlementToken {
+    fn from(app_id: Option<ApplicationId>) -> Self {
+        app_id.map_or(SettlementToken::Native, SettlementToken::Fungible)
+    }
+}
+
+#[derive(Deserialize)]
+struct SettlementTokenData {
+    #[serde(rename = "settlementToken")]
+    settlement_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NativeBalanceData {
+    chain: NativeChain,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeChain {
+    execution_state: NativeExecutionState,
+}
+
+#[derive(Deserialize)]
+struct NativeExecutionState {
+    system: NativeSystem,
+}
+
+#[derive(Deserialize)]
+struct NativeSystem {
+    balance: Amount,
+}
+
+#[derive(Deserialize)]
+struct FungibleBalanceData {
+    accounts: FungibleAccounts,
+}
+
+#[derive(Deserialize)]
+struct FungibleAccounts {
+    entry: FungibleEntry,
+}
+
+#[derive(Deserialize)]
+struct FungibleEntry {
+    value: Option<Amount>,
+}
+
+impl Transport {
+    /// Query an application's own GraphQL service on `chain_id`; never cached
+    pub(crate) async fn application_query<T: serde::de::DeserializeOwned>(
+        &self,
+        chain_id: ChainId,
+        app_id: ApplicationId,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+        let response = self
+            .client
+            .post(format!("{}/chains/{}/applications/{}", self.rpc_url, chain_id, app_id))
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send()
+            .await?;
+
+        let body: GraphQLResponse<T> = response.json().await?;
+        if let Some(error) = body.errors.into_iter().next() {
+            return Err(error.into());
+        }
+        body.data.ok_or(SdkError::EmptyResponse)
+    }
+
+    pub(crate) async fn token_balance(
+        &self,
+        chain_id: ChainId,
+        token: SettlementToken,
+    ) -> Result<Amount, SdkError> {
+        match token {
+            SettlementToken::Native => {
+                let query = r#"
+                    query NativeBalance($chainId: String!) {
+                        chain(chainId: $chainId) {
+                            executionState { system { balance } }
+                        }
+                    }
+                "#;
+                let data: NativeBalanceData = self
+                    .graphql_query_fresh(query, serde_json::json!({ "chainId": chain_id.to_string() }))
+                    .await?;
+                Ok(data.chain.execution_state.system.balance)
+            }
+            SettlementToken::Fungible(app_id) => {
+                // User chains hold settlement tokens under their chain account
+                let query = r#"
+                    query TokenBalance($owner: String!) {
+                        accounts { entry(key: $owner) { value } }
+                    }
+                "#;
+                let data: FungibleBalanceData = self
+                    .application_query(
+                        chain_id,
+                        app_id,
+                        query,
+                        serde_json::json!({ "owner": chain_id.to_string() }),
+                    )
+                    .await?;
+                Ok(data.accounts.entry.value.unwrap_or_default())
+            }
+        }
+    }
+
+    pub(crate) async fn market_settlement_token(&self, market_id: &str) -> Result<SettlementToken, SdkError> {
+        let query = r#"
+            query SettlementToken($marketId: String!) {
+                settlementToken(marketId: $marketId)
+            }
+        "#;
+
+        // Fixed at creation, so a cached answer is always correct
+        let data: SettlementTokenData = self
+            .graphql_query_cached(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        let app_id = data
+            .settlement_token
+            .map(|raw| ApplicationId::from_str(&raw))
+            .transpose()
+            .map_err(|e| SdkError::InvalidInput(format!("settlement token: {}", e)))?;
+        Ok(app_id.into())
+    }
+}
+
+impl OddsStreamSdk {
+    /// Balance of `chain_id` in `token`
+    pub async fn token_balance(&self, chain_id: ChainId, token: SettlementToken) -> Result<Amount, SdkError> {
+        self.transport.token_balance(chain_id, token).await
+    }
+
+    /// This SDK's chain balance in the token `market_id` settles in, i.e. what it can stake there
+    pub async fn market_balance(&self, market_id: &str) -> Result<Amount, SdkError> {
+        let token = self.transport.market_settlement_token(market_id).await?;
+        self.transport.token_balance(self.chain_id, token).await
+    }
+
+    /// The token a market's stakes and payouts are denominated in
+    pub async fn market_settlement_token(&self, market_id: &str) -> Result<SettlementToken, SdkError> {
+        self.transport.market_settlement_token(market_id).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Balance of `chain_id` in `token`
+    pub async fn token_balance(&self, chain_id: ChainId, token: SettlementToken) -> Result<Amount, SdkError> {
+        self.transport.token_balance(chain_id, token).await
+    }
+
+    /// The token a market's stakes and payouts are denominated in
+    pub async fn market_settlement_token(&self, market_id: &str) -> Result<SettlementToken, SdkError> {
+        self.transport.market_settlement_token(market_id).await
+    }
+}
@@ -0,0 +1,125 @@
+//! Time & sales: every fill on the watched markets as it happens
+
+use crate::transport::Transport;
+use crate::{
+    CoalesceKey, DecodeFrame, OddsStreamSdk, OrderSide, ReadOnlyClient, SdkError, SubscriptionHandle,
+    UpdateReceiver, UpdateStream,
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+
+/// One executed trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trade {
+    pub market_id: String,
+    /// Per-market fill counter, contiguous from 1
+    pub sequence: u64,
+    /// Odds the order filled at
+    pub price: f64,
+    pub size: f64,
+    /// Side the taker bought
+    pub aggressor: OrderSide,
+    /// Micros timestamp of the block that executed the fill
+    pub timestamp: u64,
+}
+
+impl CoalesceKey for Trade {
+    fn coalesce_key(&self) -> &str {
+        &self.market_id
+    }
+}
+
+impl DecodeFrame for Trade {
+    fn decode_frame(text: &str) -> Option<Self> {
+        serde_json::from_str(text).ok()
+    }
+}
+
+#[derive(Deserialize)]
+struct TradesData {
+    #[serde(rename = "recentTrades")]
+    recent_trades: Vec<Trade>,
+}
+
+impl Transport {
+    pub(crate) fn subscribe_trades(&self, market_ids: Vec<String>) -> (SubscriptionHandle, UpdateReceiver<Trade>) {
+        let subscription_query = r#"
+            subscription OnTrades($marketIds: [String!]) {
+                trades(marketIds: $marketIds) {
+                    marketId
+                    sequence
+                    price
+                    size
+                    aggressor
+                    timestamp
+                }
+            }
+        "#;
+
+        self.subscribe(subscription_query, serde_json::json!({ "marketIds": market_ids }))
+    }
+
+    pub(crate) async fn recent_trades(
+        &self,
+        market_id: &str,
+        after_sequence: Option<u64>,
+    ) -> Result<Vec<Trade>, SdkError> {
+        let query = r#"
+            query RecentTrades($marketId: String!, $afterSequence: Int) {
+                recentTrades(marketId: $marketId, afterSequence: $afterSequence) {
+                    marketId
+                    sequence
+                    price
+                    size
+                    aggressor
+                    timestamp
+                }
+            }
+        "#;
+
+        let data: TradesData = self
+            .graphql_query_fresh(
+                query,
+                serde_json::json!({ "marketId": market_id, "afterSequence": after_sequence }),
+            )
+            .await?;
+        Ok(data.recent_trades)
+    }
+}
+
+impl OddsStreamSdk {
+    /// Stream every fill on `market_ids`. Dispatch overflow drops trades, so
+    /// tape consumers should use `OverflowPolicy::Block` or watch `sequence`
+    /// and backfill from `recent_trades`.
+    pub fn subscribe_trades(&self, market_ids: Vec<String>) -> impl Stream<Item = Trade> + Send + Unpin {
+        let (handle, receiver) = self.transport.subscribe_trades(market_ids);
+        UpdateStream::new(handle, receiver)
+    }
+
+    /// The market's latest fills after `after_sequence`, oldest first
+    pub async fn recent_trades(
+        &self,
+        market_id: &str,
+        after_sequence: Option<u64>,
+    ) -> Result<Vec<Trade>, SdkError> {
+        self.transport.recent_trades(market_id, after_sequence).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// Stream every fill on `market_ids`; see `OddsStreamSdk::subscribe_trades`
+    pub fn subscribe_trades(&self, market_ids: Vec<String>) -> impl Stream<Item = Trade> + Send + Unpin {
+        let (handle, receiver) = self.transport.subscribe_trades(market_ids);
+        UpdateStream::new(handle, receiver)
+    }
+
+    /// The market's latest fills after `after_sequence`, oldest first
+    pub async fn recent_trades(
+        &self,
+        market_id: &str,
+        after_sequence: Option<u64>,
+    ) -> Result<Vec<Trade>, SdkError> {
+        self.transport.recent_trades(market_id, after_sequence).await
+    }
+}
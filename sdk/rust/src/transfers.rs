@@ -0,0 +1,238 @@
+//! Exactly-once transfers and reconciliation
+//!
+//! Every `Transfer` a market sends carries a [`TransferId`]: the market's
+//! chain and its own counter. The receiving chain applies each ID once, so a
+//! transfer resent by the market's outbox or replayed by a relay can't pay
+//! or charge twice. Markets pay out on their own chain; a market's charge on
+//! a user chain is only paid within what that chain approved with
+//! [`OddsStreamSdk::approve_payments`], which the SDK does for orders,
+//! liquidity and parlays it sends from the user chain itself. The market keeps a ledger of everything it issued, and
+//! [`reconcile_transfers`] matches it against what arrived on the user chain.
+//! The ledger keeps only the market's most recent transfers, so receipts for
+//! transfers older than what it still holds aren't reconciled.
+
+use crate::transport::Transport;
+use crate::{MarketMessage, OddsStreamSdk, ReadOnlyClient, SdkError, TransferReceipt};
+use linera_sdk::base::{Amount, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Mirrors the market contract's `TransferId`; unique across chains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferId {
+    pub issuer: ChainId,
+    pub sequence: u64,
+}
+
+/// A transfer a market issued, from its ledger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedTransfer {
+    pub sequence: u64,
+    pub from: String,
+    pub to: String,
+    pub amount: Amount,
+    /// Token application; `None` for the native token
+    pub token: Option<String>,
+    /// Micros timestamp of the block that issued it
+    pub issued_at: u64,
+}
+
+/// The market's ledger against the user chain's receipts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferReconciliation {
+    /// Issued and applied on the user chain
+    pub settled: Vec<IssuedTransfer>,
+    /// Issued but not yet applied; a stuck one can be resent with `retry_message`
+    pub missing: Vec<IssuedTransfer>,
+    /// Applied on the user chain under an ID the market never issued
+    pub unknown: Vec<TransferReceipt>,
+}
+
+impl TransferReconciliation {
+    pub fn is_balanced(&self) -> bool {
+        self.missing.is_empty() && self.unknown.is_empty()
+    }
+}
+
+/// Match the transfers `market_chain` issued against receipts on the user chain. Receipts for
+/// sequences below `retained_from` were dropped from the market's ledger and are skipped.
+pub fn reconcile_transfers(
+    market_chain: ChainId,
+    retained_from: u64,
+    issued: Vec<IssuedTransfer>,
+    receipts: &[TransferReceipt],
+) -> TransferReconciliation {
+    let receipts: Vec<&TransferReceipt> = receipts
+        .iter()
+        .filter(|receipt| receipt.transfer_id.map_or(true, |id| id.sequence >= retained_from))
+        .collect();
+    let applied: BTreeSet<u64> = receipts
+        .iter()
+        .filter_map(|receipt| receipt.transfer_id)
+        .filter(|id| id.issuer == market_chain)
+        .map(|id| id.sequence)
+        .collect();
+    let issued_sequences: BTreeSet<u64> = issued.iter().map(|transfer| transfer.sequence).collect();
+    let (settled, missing) = issued
+        .into_iter()
+        .partition(|transfer| applied.contains(&transfer.sequence));
+    let unknown = receipts
+        .iter()
+        .filter(|receipt| {
+            receipt
+                .transfer_id
+                .is_some_and(|id| id.issuer == market_chain && !issued_sequences.contains(&id.sequence))
+        })
+        .map(|receipt| (*receipt).clone())
+        .collect();
+    TransferReconciliation { settled, missing, unknown }
+}
+
+#[derive(Deserialize)]
+struct IssuedTransfersData {
+    #[serde(rename = "issuedTransfers")]
+    issued_transfers: Vec<IssuedTransfer>,
+}
+
+#[derive(Deserialize)]
+struct RetainedFromData {
+    #[serde(rename = "transfersRetainedFrom")]
+    transfers_retained_from: Option<u64>,
+}
+
+impl Transport {
+    pub(crate) async fn issued_transfers(
+        &self,
+        market_id: &str,
+        chain_id: Option<ChainId>,
+    ) -> Result<Vec<IssuedTransfer>, SdkError> {
+        let query = r#"
+            query IssuedTransfers($marketId: String!, $chainId: String) {
+                issuedTransfers(marketId: $marketId, chainId: $chainId) {
+                    sequence
+                    from
+                    to
+                    amount
+                    token
+                    issuedAt
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({
+            "marketId": market_id,
+            "chainId": chain_id.map(|chain_id| chain_id.to_string()),
+        });
+        let data: IssuedTransfersData = self.graphql_query_fresh(query, variables).await?;
+        Ok(data.issued_transfers)
+    }
+
+    pub(crate) async fn transfers_retained_from(&self, market_id: &str) -> Result<u64, SdkError> {
+        let query = r#"
+            query TransfersRetainedFrom($marketId: String!) {
+                transfersRetainedFrom(marketId: $marketId)
+            }
+        "#;
+
+        let data: RetainedFromData = self
+            .graphql_query_fresh(query, serde_json::json!({ "marketId": market_id }))
+            .await?;
+        data.transfers_retained_from
+            .ok_or_else(|| SdkError::MarketNotFound(market_id.to_string()))
+    }
+}
+
+impl OddsStreamSdk {
+    /// Let `market_chain_id` charge this chain up to `amount` more. Sent by the SDK before
+    /// anything it submits from this chain that the market charges for; a batch another chain
+    /// submits for this one, e.g. through a relayer, needs its own approval from this chain.
+    pub async fn approve_payments(&self, market_chain_id: ChainId, amount: Amount) -> Result<String, SdkError> {
+        let message = MarketMessage::ApprovePayments { market_chain: market_chain_id, amount };
+        self.send_message(self.chain_id, message).await
+    }
+
+    /// `approve_payments`, when `payer` is this SDK's chain; no other chain's approval can be sent from here
+    pub(crate) async fn approve_own_payments(
+        &self,
+        payer: ChainId,
+        market_chain_id: ChainId,
+        amount: Amount,
+    ) -> Result<(), SdkError> {
+        if payer == self.chain_id {
+            self.approve_payments(market_chain_id, amount).await?;
+        }
+        Ok(())
+    }
+
+    /// Transfers the market issued, oldest first; only those involving `chain_id` if given
+    pub async fn issued_transfers(
+        &self,
+        market_id: &str,
+        chain_id: Option<ChainId>,
+    ) -> Result<Vec<IssuedTransfer>, SdkError> {
+        self.transport.issued_transfers(market_id, chain_id).await
+    }
+
+    /// Check that every transfer the market issued to or from this SDK's chain was applied
+    /// here exactly once, reading the chain's whole history
+    pub async fn reconcile_transfers(&self, market_id: &str) -> Result<TransferReconciliation, SdkError> {
+        let market_chain = self.resolve_market_chain(market_id).await?;
+        let retained_from = self.transport.transfers_retained_from(market_id).await?;
+        let issued = self.issued_transfers(market_id, Some(self.chain_id)).await?;
+        let receipts = self.wallet_history(0).await?;
+        Ok(reconcile_transfers(market_chain, retained_from, issued, &receipts))
+    }
+}
+
+impl ReadOnlyClient {
+    /// Transfers the market issued, oldest first; only those involving `chain_id` if given
+    pub async fn issued_transfers(
+        &self,
+        market_id: &str,
+        chain_id: Option<ChainId>,
+    ) -> Result<Vec<IssuedTransfer>, SdkError> {
+        self.transport.issued_transfers(market_id, chain_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReceiptKind;
+
+    #[test]
+    fn test_reconcile_splits_settled_missing_and_unknown() {
+        let (user, market) = (ChainId::from([1u8; 32]), ChainId::from([2u8; 32]));
+        let issued = |sequence| IssuedTransfer {
+            sequence,
+            from: market.to_string(),
+            to: user.to_string(),
+            amount: Amount::from_tokens(1),
+            token: None,
+            issued_at: 0,
+        };
+        let receipt = |sequence| TransferReceipt {
+            height: sequence,
+            timestamp: 0,
+            kind: ReceiptKind::Payout,
+            counterparty: market,
+            amount: Amount::from_tokens(1),
+            token: None,
+            transfer_id: Some(TransferId { issuer: market, sequence }),
+        };
+
+        let report = reconcile_transfers(market, 0, vec![issued(0), issued(1)], &[receipt(0), receipt(7)]);
+        assert_eq!(report.settled, vec![issued(0)]);
+        assert_eq!(report.missing, vec![issued(1)]);
+        assert_eq!(report.unknown, vec![receipt(7)]);
+        assert!(!report.is_balanced());
+
+        // Receipts older than the ledger's oldest transfer aren't flagged as unknown
+        let report = reconcile_transfers(market, 5, vec![issued(5)], &[receipt(2), receipt(5)]);
+        assert_eq!(report.settled, vec![issued(5)]);
+        assert!(report.is_balanced());
+    }
+}
@@ -0,0 +1,180 @@
+//! Auditing the oracle's transparency log
+//!
+//! The oracle operator records every outcome it reads from its data sources
+//! in a hash chain per market and periodically anchors the chain's head on
+//! the market as `OracleObservation` evidence. A log that verifies and
+//! contains every anchored head is the one the operator had committed to,
+//! whichever oracle path resolved the market.
+
+use crate::{AuditViolation, Evidence, EvidenceKind, OddsStreamSdk, SdkError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Must match the oracle operator's domain separator
+const OBSERVATION_DOMAIN: &[u8] = b"oddsstream-observation-v1";
+
+/// One read of an event's outcome from an oracle data source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Observation {
+    pub market_id: String,
+    /// Data adapter the operator read through
+    pub adapter: String,
+    pub event_id: String,
+    /// Micros timestamp of the read
+    pub observed_at: u64,
+    /// `None` while the source had no result yet
+    pub outcome: Option<bool>,
+}
+
+/// One link of a market's observation chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservationRecord {
+    pub sequence: u64,
+    pub observation: Observation,
+    pub prev_hash: [u8; 32],
+    pub hash: [u8; 32],
+}
+
+/// Outcome of `verify_observation_log`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservationVerification {
+    pub records_checked: usize,
+    pub violation: Option<AuditViolation>,
+    /// Anchors on the market that no record of the log hashes to: the log was
+    /// rewritten or truncated after they were committed
+    pub unmatched_anchors: Vec<String>,
+    /// Last record covered by an anchor; later ones are not committed on-chain yet
+    pub anchored_through: Option<u64>,
+}
+
+impl ObservationVerification {
+    pub fn is_valid(&self) -> bool {
+        self.violation.is_none() && self.unmatched_anchors.is_empty()
+    }
+}
+
+/// Hash of a record as the oracle operator computes it
+pub fn observation_hash(prev_hash: &[u8; 32], sequence: u64, observation: &Observation) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(OBSERVATION_DOMAIN);
+    hasher.update(prev_hash);
+    hasher.update(bcs::to_bytes(&(sequence, observation)).expect("observation is serializable"));
+    hasher.finalize().into()
+}
+
+/// Check `records` form one market's chain from its first record. Each
+/// anchor in `anchors` (the market's `OracleObservation` evidence) must hash
+/// to a record of the chain.
+pub fn verify_observation_log(records: &[ObservationRecord], anchors: &[Evidence]) -> ObservationVerification {
+    let mut violation = None;
+    let mut prev_hash = [0; 32];
+    for (index, record) in records.iter().enumerate() {
+        let expected = index as u64 + 1;
+        if record.sequence != expected {
+            violation = Some(AuditViolation::SequenceGap { expected, found: record.sequence });
+        } else if record.prev_hash != prev_hash || record.observation.market_id != records[0].observation.market_id {
+            violation = Some(AuditViolation::BrokenLink { sequence: record.sequence });
+        } else if observation_hash(&record.prev_hash, record.sequence, &record.observation) != record.hash {
+            violation = Some(AuditViolation::HashMismatch { sequence: record.sequence });
+        }
+        if violation.is_some() {
+            break;
+        }
+        prev_hash = record.hash;
+    }
+
+    let mut unmatched_anchors = Vec::new();
+    let mut anchored_through = None;
+    for anchor in anchors.iter().filter(|evidence| evidence.kind == EvidenceKind::OracleObservation) {
+        match records.iter().find(|record| hex::encode(record.hash) == anchor.content_hash) {
+            Some(record) => anchored_through = anchored_through.max(Some(record.sequence)),
+            None => unmatched_anchors.push(anchor.content_hash.clone()),
+        }
+    }
+
+    ObservationVerification { records_checked: records.len(), violation, unmatched_anchors, anchored_through }
+}
+
+impl OddsStreamSdk {
+    /// Download the market's observation log from where its latest anchor says it is published
+    pub async fn observation_log(&self, market_id: &str) -> Result<Vec<ObservationRecord>, SdkError> {
+        let evidence = self.resolution_evidence(market_id).await?;
+        let anchor = evidence
+            .iter()
+            .rev()
+            .find(|evidence| evidence.kind == EvidenceKind::OracleObservation)
+            .ok_or_else(|| SdkError::InvalidInput(format!("market {} has no anchored observations", market_id)))?;
+        // Anchors point into the log with a `#<length>` fragment
+        let url = anchor.uri.split('#').next().unwrap_or_default();
+        let records = self.transport.client.get(url).send().await?.error_for_status()?.json().await?;
+        Ok(records)
+    }
+
+    /// Download the market's observation log and check it against every anchor on the market
+    pub async fn verify_observations(&self, market_id: &str) -> Result<ObservationVerification, SdkError> {
+        let records = self.observation_log(market_id).await?;
+        let anchors = self.resolution_evidence(market_id).await?;
+        Ok(verify_observation_log(&records, &anchors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(outcomes: &[Option<bool>]) -> Vec<ObservationRecord> {
+        let mut prev_hash = [0; 32];
+        outcomes
+            .iter()
+            .enumerate()
+            .map(|(i, outcome)| {
+                let sequence = i as u64 + 1;
+                let observation = Observation {
+                    market_id: "final".to_string(),
+                    adapter: "scores".to_string(),
+                    event_id: "match-7".to_string(),
+                    observed_at: 1_000 * sequence,
+                    outcome: *outcome,
+                };
+                let hash = observation_hash(&prev_hash, sequence, &observation);
+                let record = ObservationRecord { sequence, observation, prev_hash, hash };
+                prev_hash = hash;
+                record
+            })
+            .collect()
+    }
+
+    fn anchor(hash: [u8; 32]) -> Evidence {
+        Evidence {
+            kind: EvidenceKind::OracleObservation,
+            content_hash: hex::encode(hash),
+            uri: "http://oracle/observations/final#2".to_string(),
+            submitter: "oracle".to_string(),
+            submitted_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_rewritten_observation_breaks_chain_and_anchor() {
+        let log = chain(&[None, None, Some(true)]);
+        let verification = verify_observation_log(&log, &[anchor(log[1].hash)]);
+        assert!(verification.is_valid());
+        assert_eq!(verification.anchored_through, Some(2));
+
+        // Flipping a read and recomputing the chain still leaves the old anchor dangling
+        let mut rewritten = log.clone();
+        rewritten[1].observation.outcome = Some(false);
+        assert_eq!(
+            verify_observation_log(&rewritten, &[]).violation,
+            Some(AuditViolation::HashMismatch { sequence: 2 })
+        );
+        let mut observations: Vec<Option<bool>> = log.iter().map(|record| record.observation.outcome).collect();
+        observations[1] = Some(false);
+        let verification = verify_observation_log(&chain(&observations), &[anchor(log[1].hash)]);
+        assert!(!verification.is_valid());
+        assert_eq!(verification.unmatched_anchors.len(), 1);
+    }
+}
@@ -0,0 +1,393 @@
+//! HTTP and WebSocket plumbing shared by the full SDK and the read-only client
+
+use crate::cache::{CacheMode, Freshness, Lookup, ResponseCache};
+use crate::{
+    subscription, update_channel, CacheConfig, CoalesceKey, DecodeFrame, DispatchConfig, GraphQLResponse,
+    LightClient, MarketFilters, MarketInfo, MarketUpdate, MarketsData, SdkError, Shutdown, SubscriptionConfig,
+    SubscriptionHandle, UpdateReceiver,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub(crate) const CONWAY_RPC_URL: &str = "https://faucet.testnet-conway.linera.net";
+
+/// Connection pool and protocol settings for the HTTP client.
+///
+/// Agents issuing hundreds of concurrent quotes should keep connections
+/// alive and, where the endpoint supports it, multiplex over HTTP/2 instead
+/// of opening a socket per request.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: usize,
+    /// Idle connections are closed after this long; `None` keeps them forever
+    pub pool_idle_timeout: Option<Duration>,
+    pub tcp_keepalive: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    /// Whole-request deadline
+    pub request_timeout: Option<Duration>,
+    /// Speak HTTP/2 without ALPN negotiation; only for endpoints known to support it
+    pub http2_prior_knowledge: bool,
+    /// Let HTTP/2 flow-control windows grow with measured bandwidth
+    pub http2_adaptive_window: bool,
+    /// Ping idle HTTP/2 connections so intermediaries do not drop them
+    pub http2_keep_alive_interval: Option<Duration>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            request_timeout: Some(Duration::from_secs(30)),
+            http2_prior_knowledge: false,
+            http2_adaptive_window: true,
+            http2_keep_alive_interval: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+impl HttpConfig {
+    pub(crate) fn build_client(&self) -> Result<reqwest::Client, SdkError> {
+        let mut builder = reqwest::Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_adaptive_window(self.http2_adaptive_window)
+            .http2_keep_alive_interval(self.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(self.http2_keep_alive_interval.is_some());
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// Spaces outgoing requests so a client never exceeds a fixed rate
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn per_second(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / requests.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Take the next request slot if it is free now, without waiting
+    pub fn try_acquire(&self) -> bool {
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let now = Instant::now();
+        if *next_slot > now {
+            return false;
+        }
+        *next_slot = now + self.interval;
+        true
+    }
+
+    /// Wait until the next request slot is free
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Transport {
+    pub(crate) rpc_url: String,
+    pub(crate) client: reqwest::Client,
+    pub(crate) subscription_config: SubscriptionConfig,
+    pub(crate) dispatch_config: DispatchConfig,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) cache: Option<Arc<Mutex<ResponseCache>>>,
+    pub(crate) shutdown: Option<Shutdown>,
+    // Verifies block certificates for the light-client queries; `None` trusts the gateway
+    pub(crate) light_client: Option<Arc<LightClient>>,
+}
+
+impl Transport {
+    pub(crate) fn new(rpc_url: String) -> Self {
+        Self {
+            rpc_url,
+            // Default settings only fail if the TLS backend cannot start, which `Client::new` would also panic on
+            client: HttpConfig::default().build_client().expect("failed to build HTTP client"),
+            subscription_config: SubscriptionConfig::default(),
+            dispatch_config: DispatchConfig::default(),
+            rate_limiter: None,
+            cache: Some(Arc::new(Mutex::new(ResponseCache::new(CacheConfig::default())))),
+            shutdown: None,
+            light_client: None,
+        }
+    }
+
+    /// Replace the response cache; `None` disables caching entirely
+    pub(crate) fn set_cache(&mut self, config: Option<CacheConfig>) {
+        self.cache = config.map(|config| Arc::new(Mutex::new(ResponseCache::new(config))));
+    }
+
+    /// POST a query and return the raw response body, consulting the cache per `mode`
+    async fn fetch_graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+        mode: CacheMode,
+    ) -> Result<serde_json::Value, SdkError> {
+        let key = ResponseCache::key(query, &variables);
+        let mut etag = None;
+        if let (Some(cache), false) = (&self.cache, mode == CacheMode::Bypass) {
+            match cache.lock().unwrap().lookup(&key, mode) {
+                Lookup::Fresh(body) => return Ok(body),
+                Lookup::Stale(tag) => etag = tag,
+                Lookup::Miss => {}
+            }
+        }
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+
+        let mut request = self
+            .client
+            .post(&format!("{}/graphql", self.rpc_url))
+            .json(&serde_json::json!({
+                "query": query,
+                "variables": variables
+            }));
+        if let Some(etag) = &etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if mode == CacheMode::Bypass {
+            request = request.header(reqwest::header::CACHE_CONTROL, "no-cache");
+        }
+        let response = request.send().await?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after_secs = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()?.parse().ok());
+            tracing::warn!(url = %self.rpc_url, ?retry_after_secs, "rate limited by endpoint");
+            return Err(SdkError::RateLimited { retry_after_secs });
+        }
+
+        let freshness = Freshness::from_header(response.headers().get(reqwest::header::CACHE_CONTROL));
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            let cached = self
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.lock().unwrap().revalidated(&key, freshness));
+            return match cached {
+                Some(body) => Ok(body),
+                // Evicted while the request was in flight; fetch it whole
+                None => Box::pin(self.fetch_graphql(query, variables, CacheMode::Bypass)).await,
+            };
+        }
+
+        let new_etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body: serde_json::Value = response.json().await?;
+
+        // Error responses are never cached; the next call should retry
+        let has_errors = body.get("errors").is_some_and(|errors| !errors.is_null());
+        if let (Some(cache), false) = (&self.cache, has_errors) {
+            cache.lock().unwrap().store(key, body.clone(), new_etag, freshness);
+        }
+        Ok(body)
+    }
+
+    pub(crate) async fn graphql_query_partial<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<GraphQLResponse<T>, SdkError> {
+        self.graphql_query_with(query, variables, CacheMode::Revalidate).await
+    }
+
+    async fn graphql_query_with<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+        mode: CacheMode,
+    ) -> Result<GraphQLResponse<T>, SdkError> {
+        let body = self.fetch_graphql(query, variables, mode).await?;
+
+        // Decode as untyped JSON first so a schema error surfaces as the
+        // server's message rather than a confusing deserialization failure
+        let raw: GraphQLResponse<serde_json::Value> = serde_json::from_value(body)?;
+        let data = match raw.data {
+            Some(serde_json::Value::Null) | None => None,
+            Some(value) => match serde_json::from_value(value) {
+                Ok(data) => Some(data),
+                Err(e) if raw.errors.is_empty() => return Err(e.into()),
+                // Fields nulled out by errors may not fit `T`; report the errors instead
+                Err(_) => None,
+            },
+        };
+
+        Ok(GraphQLResponse { data, errors: raw.errors })
+    }
+
+    pub(crate) async fn graphql_query<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.graphql_query_strict(query, variables, CacheMode::Revalidate).await
+    }
+
+    /// `graphql_query` for data that rarely changes; may serve a local copy for `default_ttl`
+    pub(crate) async fn graphql_query_cached<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.graphql_query_strict(query, variables, CacheMode::Reuse).await
+    }
+
+    /// `graphql_query` that always reaches the server, for trading-critical reads
+    pub(crate) async fn graphql_query_fresh<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SdkError> {
+        self.graphql_query_strict(query, variables, CacheMode::Bypass).await
+    }
+
+    async fn graphql_query_strict<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+        mode: CacheMode,
+    ) -> Result<T, SdkError> {
+        let response = self.graphql_query_with::<T>(query, variables, mode).await?;
+
+        if let Some(error) = response.errors.into_iter().next() {
+            return Err(error.into());
+        }
+        response.data.ok_or(SdkError::EmptyResponse)
+    }
+
+    pub(crate) async fn query_markets(&self, filters: MarketFilters) -> Result<Vec<MarketInfo>, SdkError> {
+        let query = r#"
+            query GetMarkets($filters: MarketFilters) {
+                markets(filters: $filters) {
+                    id
+                    description
+                    yesOdds
+                    noOdds
+                    volume
+                    liquidity
+                    status
+                    oracleType
+                    resolutionTime
+                    createdBlock
+                    realizedVolatility
+                    yesDepth
+                    noDepth
+                    averageTradeSize
+                    lastTradeAt
+                    locksAt
+                    statusHistory { status outcome at }
+                }
+            }
+        "#;
+
+        let data: MarketsData = self
+            .graphql_query(query, serde_json::json!({ "filters": filters }))
+            .await?;
+        Ok(data.markets)
+    }
+
+    pub(crate) fn subscribe_market_updates(
+        &self,
+        market_ids: Vec<String>,
+    ) -> (SubscriptionHandle, UpdateReceiver<MarketUpdate>) {
+        let subscription_query = r#"
+            subscription OnMarketUpdates($marketIds: [String!]) {
+                marketUpdates(marketIds: $marketIds) {
+                    marketId
+                    yesOdds
+                    noOdds
+                    volume
+                    status
+                    timestamp
+                    sequence
+                }
+            }
+        "#;
+
+        self.subscribe(subscription_query, serde_json::json!({ "marketIds": market_ids }))
+    }
+
+    /// Start a subscription task feeding decoded frames into a dispatch queue
+    pub(crate) fn subscribe<T: DecodeFrame + CoalesceKey + Send + 'static>(
+        &self,
+        subscription_query: &str,
+        variables: serde_json::Value,
+    ) -> (SubscriptionHandle, UpdateReceiver<T>) {
+        let ws_url = self.rpc_url.replace("https://", "wss://").replace("http://", "ws://");
+        let subscribe_msg = serde_json::json!({
+            "type": "subscribe",
+            "query": subscription_query,
+            "variables": variables
+        });
+
+        // Spawn task that keeps the connection alive and queues updates
+        let (sender, receiver) = update_channel(self.dispatch_config.clone());
+        let handle = tokio::spawn(subscription::run_subscription(
+            format!("{}/ws", ws_url),
+            subscribe_msg,
+            self.subscription_config.clone(),
+            sender,
+            self.shutdown.clone(),
+        ));
+
+        (SubscriptionHandle { handle }, receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_requests() {
+        let limiter = RateLimiter::per_second(20);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        // First slot is immediate, the next two wait 50ms each
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,243 @@
+//! The protocol treasury held by the registry
+//!
+//! Markets sweep their protocol share of trading fees (and yield credited to
+//! the treasury) to the registry when they settle, or earlier on request.
+//! Withdrawals need the registry's `Owner` role.
+
+use crate::transport::Transport;
+use crate::{MarketMessage, OddsStreamSdk, ReadOnlyClient, SdkError};
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Treasury holdings in one settlement token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasuryBalance {
+    /// Token application; `None` for the native token
+    pub token: Option<String>,
+    pub balance: Amount,
+    /// Everything ever swept in
+    pub inflows: Amount,
+    pub withdrawn: Amount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TreasuryEntryKind {
+    Inflow,
+    Withdrawal,
+}
+
+/// One movement of treasury funds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasuryEntry {
+    pub sequence: u64,
+    pub kind: TreasuryEntryKind,
+    /// Market the fees came from, for inflows
+    pub market_id: Option<String>,
+    /// Chain paid, for withdrawals
+    pub recipient: Option<String>,
+    pub token: Option<String>,
+    pub amount: Amount,
+    /// Micros timestamp of the registry block that recorded it
+    pub timestamp: u64,
+}
+
+/// Newest-first page of treasury history; pass `next_cursor` back to fetch older entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasuryPage {
+    pub entries: Vec<TreasuryEntry>,
+    pub next_cursor: Option<u64>,
+}
+
+/// Total inflow per market over `entries`, e.g. a few pages of history
+pub fn inflows_by_market(entries: &[TreasuryEntry]) -> BTreeMap<String, Amount> {
+    let mut totals = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.kind == TreasuryEntryKind::Inflow) {
+        if let Some(market_id) = &entry.market_id {
+            *totals.entry(market_id.clone()).or_insert(Amount::ZERO) += entry.amount;
+        }
+    }
+    totals
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTreasuryBalance {
+    token: Option<String>,
+    balance: String,
+    inflows: String,
+    withdrawn: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTreasuryEntry {
+    sequence: u64,
+    kind: TreasuryEntryKind,
+    market_id: Option<String>,
+    recipient: Option<String>,
+    token: Option<String>,
+    amount: String,
+    timestamp: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTreasuryPage {
+    entries: Vec<RawTreasuryEntry>,
+    next_cursor: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TreasuryData {
+    treasury: Vec<RawTreasuryBalance>,
+}
+
+#[derive(Deserialize)]
+struct TreasuryHistoryData {
+    #[serde(rename = "treasuryHistory")]
+    treasury_history: RawTreasuryPage,
+}
+
+fn parse_amount(value: &str) -> Result<Amount, SdkError> {
+    Amount::from_str(value).map_err(|e| SdkError::InvalidInput(format!("invalid amount {}: {}", value, e)))
+}
+
+impl Transport {
+    pub(crate) async fn treasury_balances(&self) -> Result<Vec<TreasuryBalance>, SdkError> {
+        let query = r#"
+            query Treasury {
+                treasury { token balance inflows withdrawn }
+            }
+        "#;
+
+        let data: TreasuryData = self.graphql_query_fresh(query, serde_json::json!({})).await?;
+        data.treasury
+            .into_iter()
+            .map(|raw| {
+                Ok(TreasuryBalance {
+                    token: raw.token,
+                    balance: parse_amount(&raw.balance)?,
+                    inflows: parse_amount(&raw.inflows)?,
+                    withdrawn: parse_amount(&raw.withdrawn)?,
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) async fn treasury_history(&self, limit: usize, cursor: Option<u64>) -> Result<TreasuryPage, SdkError> {
+        let query = r#"
+            query TreasuryHistory($limit: Int!, $cursor: Int) {
+                treasuryHistory(limit: $limit, cursor: $cursor) {
+                    entries { sequence kind marketId recipient token amount timestamp }
+                    nextCursor
+                }
+            }
+        "#;
+
+        let data: TreasuryHistoryData = self
+            .graphql_query(query, serde_json::json!({ "limit": limit, "cursor": cursor }))
+            .await?;
+        let page = data.treasury_history;
+        let entries = page
+            .entries
+            .into_iter()
+            .map(|raw| {
+                Ok(TreasuryEntry {
+                    sequence: raw.sequence,
+                    kind: raw.kind,
+                    market_id: raw.market_id,
+                    recipient: raw.recipient,
+                    token: raw.token,
+                    amount: parse_amount(&raw.amount)?,
+                    timestamp: raw.timestamp,
+                })
+            })
+            .collect::<Result<_, SdkError>>()?;
+        Ok(TreasuryPage { entries, next_cursor: page.next_cursor })
+    }
+}
+
+impl OddsStreamSdk {
+    /// What the treasury holds, per settlement token
+    pub async fn treasury_balances(&self) -> Result<Vec<TreasuryBalance>, SdkError> {
+        self.transport.treasury_balances().await
+    }
+
+    /// Fee sweeps and withdrawals, newest first
+    pub async fn treasury_history(&self, limit: usize, cursor: Option<u64>) -> Result<TreasuryPage, SdkError> {
+        self.transport.treasury_history(limit, cursor).await
+    }
+
+    /// Pay `amount` of `token` (`None` for the native token) out of the
+    /// treasury; the registry refuses signers without [`Role::Owner`](crate::Role::Owner)
+    pub async fn withdraw_treasury(
+        &self,
+        token: Option<ApplicationId>,
+        amount: Amount,
+        recipient: ChainId,
+    ) -> Result<String, SdkError> {
+        let mutation = r#"
+            mutation WithdrawTreasury($token: String, $amount: String!, $recipient: String!) {
+                withdrawTreasury(token: $token, amount: $amount, recipient: $recipient)
+            }
+        "#;
+        let variables = serde_json::json!({
+            "token": token.map(|token| token.to_string()),
+            "amount": amount.to_string(),
+            "recipient": recipient.to_string(),
+        });
+        self.execute_registry_operation(mutation, variables).await
+    }
+
+    /// Send a market's accrued protocol fees to the treasury without waiting for it to settle
+    pub async fn sweep_protocol_fees(&self, market_id: &str) -> Result<String, SdkError> {
+        let market_chain_id = self.resolve_market_chain(market_id).await?;
+        self.send_message(market_chain_id, MarketMessage::SweepProtocolFees).await
+    }
+}
+
+impl ReadOnlyClient {
+    /// What the treasury holds, per settlement token
+    pub async fn treasury_balances(&self) -> Result<Vec<TreasuryBalance>, SdkError> {
+        self.transport.treasury_balances().await
+    }
+
+    /// Fee sweeps and withdrawals, newest first
+    pub async fn treasury_history(&self, limit: usize, cursor: Option<u64>) -> Result<TreasuryPage, SdkError> {
+        self.transport.treasury_history(limit, cursor).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inflows_by_market_ignores_withdrawals() {
+        let entry = |sequence, kind, market_id: Option<&str>, tokens| TreasuryEntry {
+            sequence,
+            kind,
+            market_id: market_id.map(str::to_string),
+            recipient: None,
+            token: None,
+            amount: Amount::from_tokens(tokens),
+            timestamp: 0,
+        };
+        let history = [
+            entry(3, TreasuryEntryKind::Inflow, Some("btc-100k"), 2),
+            entry(2, TreasuryEntryKind::Withdrawal, None, 50),
+            entry(1, TreasuryEntryKind::Inflow, Some("eth-10k"), 4),
+            entry(0, TreasuryEntryKind::Inflow, Some("btc-100k"), 3),
+        ];
+        let totals = inflows_by_market(&history);
+        assert_eq!(totals["btc-100k"], Amount::from_tokens(5));
+        assert_eq!(totals["eth-10k"], Amount::from_tokens(4));
+        assert_eq!(totals.len(), 2);
+    }
+}
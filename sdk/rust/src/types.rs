@@ -0,0 +1,501 @@
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+/// Which outcome an order buys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderSide {
+    Yes,
+    No,
+}
+
+/// A single order as submitted by the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketOrder {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub amount: String,
+    pub max_price: Option<String>,
+    /// Sub-account the order is paid from and the position attributed to; `None`
+    /// means the main account. Only honoured when placed through the user application.
+    #[serde(default)]
+    pub subaccount: Option<String>,
+    /// Referral code whose owner receives a share of the trading fee
+    #[serde(default)]
+    pub referral_code: Option<String>,
+}
+
+/// Side of an [`Order`] as the contracts encode it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WireSide {
+    BuyYes,
+    BuyNo,
+}
+
+impl From<OrderSide> for WireSide {
+    fn from(side: OrderSide) -> Self {
+        match side {
+            OrderSide::Yes => WireSide::BuyYes,
+            OrderSide::No => WireSide::BuyNo,
+        }
+    }
+}
+
+/// An order exactly as the contracts encode it: what `BatchedOrders` and
+/// `ReserveOrders` carry and what a batch signature covers. The market is
+/// the chain the batch is sent to, so it isn't part of the order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    /// Echoed back in `BatchConfirmed` and rejections; see [`order_id`]
+    pub id: u64,
+    pub side: WireSide,
+    pub amount: Amount,
+    pub max_price: Option<Amount>,
+    pub subaccount: Option<String>,
+    pub referral_code: Option<String>,
+}
+
+/// Orders one batch may carry, so that [`order_id`] stays unique per user chain
+pub const MAX_BATCH_ORDERS: usize = 1 << 16;
+
+/// Largest nonce [`order_id`] can number orders under; higher bits would be shifted out
+pub const MAX_ORDER_NONCE: u64 = (1 << 48) - 1;
+
+/// Id of the `index`th order of the batch sent with `nonce`. Fails rather than wrap
+/// into an id another order may already have.
+pub fn order_id(nonce: u64, index: usize) -> Result<u64, crate::SdkError> {
+    if nonce > MAX_ORDER_NONCE || index >= MAX_BATCH_ORDERS {
+        return Err(crate::SdkError::InvalidInput(format!(
+            "no order id for order {} of the batch with nonce {}",
+            index, nonce
+        )));
+    }
+    Ok((nonce << 16) | index as u64)
+}
+
+impl MarketOrder {
+    /// This order on the wire, with the given id
+    pub fn to_wire(&self, id: u64) -> Result<Order, crate::SdkError> {
+        let parse = |raw: &str| {
+            raw.parse::<Amount>()
+                .map_err(|e| crate::SdkError::InvalidInput(format!("invalid amount {}: {}", raw, e)))
+        };
+        Ok(Order {
+            id,
+            side: self.side.into(),
+            amount: parse(&self.amount)?,
+            max_price: self.max_price.as_deref().map(parse).transpose()?,
+            subaccount: self.subaccount.clone(),
+            referral_code: self.referral_code.clone(),
+        })
+    }
+}
+
+/// `orders` on the wire, numbered from `nonce`
+pub fn wire_orders(orders: &[MarketOrder], nonce: u64) -> Result<Vec<Order>, crate::SdkError> {
+    if orders.len() > MAX_BATCH_ORDERS {
+        return Err(crate::SdkError::InvalidInput(format!(
+            "a batch carries at most {} orders, got {}",
+            MAX_BATCH_ORDERS,
+            orders.len()
+        )));
+    }
+    orders
+        .iter()
+        .enumerate()
+        .map(|(index, order)| order.to_wire(order_id(nonce, index)?))
+        .collect()
+}
+
+/// Fee a user agrees to pay the chain relaying their signed batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayerFee {
+    pub relayer: ChainId,
+    pub amount: Amount,
+}
+
+/// Oracle that signs a market's resolution; mirrors the contracts' `OracleType`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OracleType {
+    FastTee { public_key: String },
+    Committee { member_count: u32 },
+    Hybrid,
+}
+
+/// Messages handled by market chains.
+///
+/// Mirrors the market contract's enum variant for variant, including the
+/// chain-to-chain messages the SDK never sends: the variant index is the BCS
+/// tag, so new variants are only ever appended. `wire::tests` pins the layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MarketMessage {
+    BatchedOrders {
+        user_chain_id: ChainId,
+        orders: Vec<Order>,
+        nonce: u64,
+        signature: Option<crate::OrderSignature>,
+        relayer_fee: Option<RelayerFee>,
+        cosignatures: Vec<crate::OrderSignature>,
+    },
+    SetMultisigPolicy {
+        user_chain_id: ChainId,
+        policy: Option<crate::MultisigPolicy>,
+    },
+    RegisterSigner {
+        user_chain_id: ChainId,
+        public_key: Vec<u8>,
+    },
+    GrantSessionKey {
+        user_chain_id: ChainId,
+        public_key: Vec<u8>,
+        spend_cap: Amount,
+        expires_at: u64,
+        markets: Vec<ChainId>,
+    },
+    RevokeSessionKey {
+        user_chain_id: ChainId,
+        public_key: Vec<u8>,
+    },
+    RegisterReferralCode {
+        code: String,
+        referrer: ChainId,
+    },
+    ClaimReferralEarnings {
+        referrer: ChainId,
+    },
+    AttachEvidence {
+        kind: crate::EvidenceKind,
+        content_hash: [u8; 32],
+        uri: String,
+    },
+    AddLiquidity {
+        provider: ChainId,
+        amount: Amount,
+    },
+    RemoveLiquidity {
+        provider: ChainId,
+        shares: Amount,
+    },
+    PlaceParlay {
+        user_chain_id: ChainId,
+        stake: Amount,
+        legs: Vec<crate::ParlayLeg>,
+    },
+    ParlayLegQuote {
+        parlay_id: u64,
+        leg_index: u64,
+        outcome: bool,
+    },
+    ParlayLegQuoted {
+        parlay_id: u64,
+        leg_index: u64,
+        odds: f64,
+    },
+    ParlayLegRejected {
+        parlay_id: u64,
+    },
+    ParlayLegResolved {
+        parlay_id: u64,
+        leg_index: u64,
+        won: bool,
+    },
+    UncrossAuction,
+    WatchResolution,
+    ParentResolved {
+        outcome: bool,
+    },
+    Resolution {
+        outcome: bool,
+        timestamp: u64,
+        signature: Vec<u8>,
+        oracle_type: OracleType,
+    },
+    Claim {
+        user_chain_id: ChainId,
+    },
+    Transfer {
+        from: ChainId,
+        to: ChainId,
+        amount: Amount,
+        token: Option<ApplicationId>,
+        /// The receiving chain applies each ID once; see `reconcile_transfers`
+        transfer_id: TransferId,
+    },
+    BatchConfirmed {
+        user_chain_id: ChainId,
+        order_ids: Vec<u64>,
+        total_cost: Amount,
+        rejections: Vec<crate::OrderRejection>,
+    },
+    StageResolution {
+        stage: u32,
+        outcome: bool,
+        timestamp: u64,
+        signature: Vec<u8>,
+        oracle_type: OracleType,
+    },
+    /// Replace the size and limit of an order queued in a call auction; it keeps its id
+    AmendOrder {
+        user_chain_id: ChainId,
+        order_id: u64,
+        amount: Amount,
+        max_price: Option<Amount>,
+        nonce: u64,
+        signature: Option<crate::OrderSignature>,
+    },
+    /// Collect the yield the market's idle funds have accrued; anyone may send it
+    HarvestYield,
+    ClaimYield {
+        provider: ChainId,
+    },
+    /// Send the market's accrued protocol fees to the registry treasury; anyone may send it
+    SweepProtocolFees,
+    /// Announce a FastTee oracle's next key; see `sign_key_rotation`
+    RotateOracleKey {
+        new_key: String,
+        announced_at: u64,
+        overlap: u64,
+        signature: Vec<u8>,
+    },
+    /// A confirmation or payout whose delivery the market tracks; see `decode_market_message`
+    Tracked {
+        outbox_id: u64,
+        payload: Vec<u8>,
+    },
+    /// Acknowledges a `Tracked` message; the market app sends it on the receiving chain
+    Delivered {
+        outbox_id: u64,
+    },
+    /// Resend an unacknowledged outbox message; see `retry_message`
+    RetryMessage {
+        outbox_id: u64,
+    },
+    /// Sent by a user chain to itself to start an atomic batch; see `submit_batched_orders_atomic`
+    SubmitAtomic {
+        legs: Vec<crate::AtomicLeg>,
+    },
+    ReserveOrders {
+        saga_id: u64,
+        user_chain_id: ChainId,
+        orders: Vec<Order>,
+    },
+    OrdersReserved {
+        saga_id: u64,
+        total_cost: Amount,
+    },
+    ReservationRejected {
+        saga_id: u64,
+        rejections: Vec<crate::OrderRejection>,
+    },
+    CommitReservation {
+        saga_id: u64,
+    },
+    AbortReservation {
+        saga_id: u64,
+    },
+    /// Bankroll a market's parlays; see `fund_parlay_reserve`
+    FundParlayReserve {
+        funder: ChainId,
+        amount: Amount,
+    },
+    /// Replace or remove an installed policy; see `propose_policy_change`
+    UpdateMultisigPolicy {
+        user_chain_id: ChainId,
+        policy: Option<crate::MultisigPolicy>,
+        nonce: u64,
+        signatures: Vec<crate::OrderSignature>,
+    },
+    /// Let `market_chain` charge the sending chain up to `amount` more; see `approve_payments`
+    ApprovePayments {
+        market_chain: ChainId,
+        amount: Amount,
+    },
+    /// Refuse every order from `user_chain_id` until `until`; sent by its user application
+    SelfExclude {
+        user_chain_id: ChainId,
+        until: u64,
+    },
+    /// Sent by the registry to move a market that hasn't settled to another oracle
+    UpdateOracle {
+        oracle_type: OracleType,
+    },
+    /// Tells a conditional market its parent was voided
+    ParentCancelled,
+    /// Sent by a market to the user chain when a session-signed batch is accepted
+    SessionSpent {
+        public_key: Vec<u8>,
+        amount: Amount,
+    },
+    /// Sent by the user chain to a session key's markets with its spend across all of them
+    SyncSessionSpend {
+        public_key: Vec<u8>,
+        spent: Amount,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResponse {
+    pub transaction_ids: Vec<String>,
+    pub total_orders: usize,
+}
+
+/// Filters accepted by `query_markets`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketFilters {
+    pub status: Option<String>,
+    pub min_volume: Option<f64>,
+    pub category: Option<String>,
+    pub limit: Option<usize>,
+    /// Also list markets whose chains have been retired
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketInfo {
+    pub id: String,
+    pub description: String,
+    pub yes_odds: f64,
+    pub no_odds: f64,
+    pub volume: f64,
+    pub liquidity: f64,
+    pub status: String,
+    pub oracle_type: String,
+    pub resolution_time: u64,
+    pub created_block: u64,
+    /// Computed metrics; absent when the service has no history for the market yet
+    #[serde(default)]
+    pub realized_volatility: Option<f64>,
+    #[serde(default)]
+    pub yes_depth: Option<f64>,
+    #[serde(default)]
+    pub no_depth: Option<f64>,
+    #[serde(default)]
+    pub average_trade_size: Option<f64>,
+    /// Micros timestamp of the most recent trade
+    #[serde(default)]
+    pub last_trade_at: Option<u64>,
+    /// Micros timestamp the market stops taking orders, ahead of `resolution_time`
+    #[serde(default)]
+    pub locks_at: Option<u64>,
+    /// Lifecycle transitions so far, oldest first
+    #[serde(default)]
+    pub status_history: Vec<crate::StatusChange>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketsData {
+    pub markets: Vec<MarketInfo>,
+}
+
+/// Raw GraphQL response envelope; `data` may be partial when `errors` is non-empty
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQLResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+}
+
+/// One entry of a GraphQL `errors` array
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default)]
+    pub path: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub extensions: Option<serde_json::Value>,
+}
+
+impl GraphQLError {
+    /// Dotted form of `path`, e.g. `markets.0.yesOdds`
+    pub fn path_string(&self) -> String {
+        self.path
+            .iter()
+            .map(|segment| match segment {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+impl From<GraphQLError> for crate::SdkError {
+    fn from(error: GraphQLError) -> Self {
+        crate::SdkError::GraphQL {
+            path: error.path_string(),
+            message: error.message,
+            extensions: error.extensions,
+        }
+    }
+}
+
+/// Real-time update pushed over the market subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketUpdate {
+    pub market_id: String,
+    pub yes_odds: f64,
+    pub no_odds: f64,
+    /// Cumulative traded volume
+    pub volume: f64,
+    pub status: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Per-market event counter, contiguous from 1; 0 from servers that predate it
+    #[serde(default)]
+    pub sequence: u64,
+}
+
+/// A market that resolved while the user held a position in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedMarket {
+    pub market_id: String,
+    pub outcome: bool,
+    pub resolved_at: u64,
+    /// Amount claimable by the user; zero for losing positions
+    pub payout: f64,
+    pub claimed: bool,
+}
+
+/// Result of a `claim_all` sweep
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimSummary {
+    pub claimed_markets: Vec<String>,
+    pub transaction_ids: Vec<String>,
+    pub total_payout: f64,
+    /// (market ID, error) for each claim that couldn't be sent; retrying `claim_all` picks them up
+    pub failed: Vec<(String, String)>,
+}
+
+/// Keeps a background subscription task alive
+pub struct SubscriptionHandle {
+    pub(crate) handle: JoinHandle<()>,
+}
+
+impl SubscriptionHandle {
+    /// Stop receiving updates
+    pub fn unsubscribe(self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_id_refuses_to_wrap() {
+        assert_eq!(order_id(MAX_ORDER_NONCE, MAX_BATCH_ORDERS - 1).unwrap(), u64::MAX);
+        assert!(order_id(MAX_ORDER_NONCE + 1, 0).is_err());
+        // Would otherwise carry into the nonce and collide with the next batch's first order
+        assert!(order_id(1, MAX_BATCH_ORDERS).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Value betting against the books: buy whichever side OddsStream prices
+//! further below the external consensus than a threshold
+
+use crate::{AgentContext, AgentDecision, Consensus, ConsensusBoard, MarketOrder, MarketUpdate, OrderSide, SdkError, TradingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tuning of a `ValueBettingStrategy`, read from `AgentConfig::strategy_params`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValueBettingParams {
+    /// Smallest gap between consensus probability and market price worth betting
+    pub edge_threshold: f64,
+    /// Shares bought per bet
+    pub stake: f64,
+    /// Skip markets where the feeds disagree by more than this
+    pub max_dispersion: f64,
+    /// Ignore a consensus whose oldest quote is older than this
+    pub max_quote_age_secs: u64,
+    /// Feeds that must quote a market before it is bet on
+    pub min_feeds: usize,
+    /// Wait this long after a bet before betting the same market again
+    pub cooldown_secs: u64,
+}
+
+impl Default for ValueBettingParams {
+    fn default() -> Self {
+        Self {
+            edge_threshold: 0.05,
+            stake: 10.0,
+            max_dispersion: 0.1,
+            max_quote_age_secs: 300,
+            min_feeds: 1,
+            cooldown_secs: 60,
+        }
+    }
+}
+
+impl ValueBettingParams {
+    pub fn validate(&self) -> Result<(), SdkError> {
+        let invalid = |what: &str| Err(SdkError::InvalidInput(format!("value betting {}", what)));
+        if !(self.edge_threshold > 0.0 && self.edge_threshold < 1.0) {
+            return invalid("edge threshold must be in (0, 1)");
+        }
+        if self.stake.is_nan() || self.stake <= 0.0 {
+            return invalid("stake must be positive");
+        }
+        if self.max_dispersion.is_nan() || self.max_dispersion < 0.0 {
+            return invalid("maximum dispersion must not be negative");
+        }
+        if self.min_feeds == 0 {
+            return invalid("needs at least one feed per market");
+        }
+        Ok(())
+    }
+}
+
+/// Bets on mispricings relative to a `ConsensusBoard` kept fresh by `ExternalOddsFeed::watch`
+#[derive(Debug, Clone)]
+pub struct ValueBettingStrategy {
+    params: ValueBettingParams,
+    board: ConsensusBoard,
+    /// Micros timestamp of the last bet per market
+    last_bet: HashMap<String, u64>,
+}
+
+impl ValueBettingStrategy {
+    pub fn new(params: ValueBettingParams, board: ConsensusBoard) -> Result<Self, SdkError> {
+        params.validate()?;
+        Ok(Self { params, board, last_bet: HashMap::new() })
+    }
+
+    pub fn params(&self) -> &ValueBettingParams {
+        &self.params
+    }
+
+    /// Whether `consensus` is fresh and agreed on enough to bet on at `now_micros`
+    fn is_usable(&self, consensus: &Consensus, now_micros: u64) -> bool {
+        let age = now_micros.saturating_sub(consensus.as_of);
+        consensus.quotes.len() >= self.params.min_feeds
+            && consensus.dispersion <= self.params.max_dispersion
+            && age <= self.params.max_quote_age_secs * 1_000_000
+    }
+
+    /// Side to buy and the most to pay for it, if either side is priced below consensus by the threshold
+    pub fn edge(&self, update: &MarketUpdate, consensus: &Consensus) -> Option<(OrderSide, f64)> {
+        let threshold = self.params.edge_threshold;
+        let yes_edge = consensus.yes_probability - update.yes_odds;
+        let no_edge = consensus.no_probability() - update.no_odds;
+        if yes_edge >= threshold && yes_edge >= no_edge {
+            Some((OrderSide::Yes, consensus.yes_probability - threshold))
+        } else if no_edge >= threshold {
+            Some((OrderSide::No, consensus.no_probability() - threshold))
+        } else {
+            None
+        }
+    }
+}
+
+impl TradingStrategy for ValueBettingStrategy {
+    fn name(&self) -> &str {
+        "value_betting"
+    }
+
+    fn on_update(&mut self, update: &MarketUpdate, context: &AgentContext) -> Vec<AgentDecision> {
+        if update.status != "Open" {
+            return Vec::new();
+        }
+        let cooldown = self.params.cooldown_secs * 1_000_000;
+        if let Some(last) = self.last_bet.get(&update.market_id) {
+            if context.now_micros.saturating_sub(*last) < cooldown {
+                return Vec::new();
+            }
+        }
+        let Some(consensus) = self.board.get(&update.market_id) else {
+            return Vec::new();
+        };
+        if !self.is_usable(&consensus, context.now_micros) {
+            return Vec::new();
+        }
+        let Some((side, max_price)) = self.edge(update, &consensus) else {
+            return Vec::new();
+        };
+
+        self.last_bet.insert(update.market_id.clone(), context.now_micros);
+        vec![AgentDecision::PlaceOrder(MarketOrder {
+            market_id: update.market_id.clone(),
+            side,
+            amount: self.params.stake.to_string(),
+            // Stop filling once the price has moved far enough to eat the edge
+            max_price: Some(format!("{:.4}", max_price)),
+            subaccount: None,
+            referral_code: None,
+        })]
+    }
+
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), SdkError> {
+        if params.is_null() {
+            return Ok(());
+        }
+        let params: ValueBettingParams = serde_json::from_value(params.clone())?;
+        params.validate()?;
+        self.params = params;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExternalQuote;
+
+    fn quote(feed: &str, yes_probability: f64) -> ExternalQuote {
+        ExternalQuote {
+            feed: feed.to_string(),
+            market_id: "m1".to_string(),
+            yes_probability,
+            overround: 0.0,
+            fetched_at: 0,
+            weight: 1.0,
+        }
+    }
+
+    fn update(yes_odds: f64) -> MarketUpdate {
+        MarketUpdate {
+            market_id: "m1".to_string(),
+            yes_odds,
+            no_odds: 1.0 - yes_odds,
+            volume: 0.0,
+            status: "Open".to_string(),
+            timestamp: 0,
+            sequence: 1,
+        }
+    }
+
+    #[test]
+    fn test_bets_underpriced_side_once_per_cooldown() {
+        let board = ConsensusBoard::default();
+        board.publish(Consensus::from_quotes("m1", vec![quote("a", 0.62), quote("b", 0.58)]).unwrap());
+        let mut strategy = ValueBettingStrategy::new(ValueBettingParams::default(), board.clone()).unwrap();
+        let context = AgentContext::default();
+
+        // Within the threshold of the 0.60 consensus: no bet
+        assert!(strategy.on_update(&update(0.57), &context).is_empty());
+
+        let decisions = strategy.on_update(&update(0.50), &context);
+        let [AgentDecision::PlaceOrder(order)] = decisions.as_slice() else {
+            panic!("expected one order, got {:?}", decisions);
+        };
+        assert_eq!(order.side, OrderSide::Yes);
+        assert_eq!(order.max_price.as_deref(), Some("0.5500"));
+        assert!(strategy.on_update(&update(0.50), &context).is_empty());
+
+        // The books disagreeing this much is no consensus at all
+        board.publish(Consensus::from_quotes("m1", vec![quote("a", 0.9), quote("b", 0.3)]).unwrap());
+        let mut fresh = ValueBettingStrategy::new(ValueBettingParams::default(), board).unwrap();
+        assert!(fresh.on_update(&update(0.30), &context).is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! Walk-forward analysis: tune parameters on one window of history, trade
+//! them on the next window the tuning never saw, and roll on. Out-of-sample
+//! results that hold up against in-sample ones are the check that an
+//! optimization found an edge rather than fit the noise.
+
+use crate::{
+    optimize, AgentConfig, BacktestReport, Backtester, MarketUpdate, OptimizationRun, ParamSpace, SdkError,
+    SearchMode, TradingStrategy,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// How history is cut into train and test windows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardConfig {
+    /// Length of each training window
+    pub train_secs: u64,
+    /// Length of each test window
+    pub test_secs: u64,
+    /// How far the windows move each fold; usually `test_secs`, so test windows tile the history
+    pub step_secs: u64,
+    /// Training windows all start at the beginning of the history and grow, instead of rolling
+    #[serde(default)]
+    pub anchored: bool,
+}
+
+/// One train/test split, in micros since the Unix epoch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardWindow {
+    pub train: Range<u64>,
+    pub test: Range<u64>,
+}
+
+/// Splits of `history` (micros), each test window right after its training window.
+/// Only whole test windows are used.
+pub fn walk_forward_windows(history: Range<u64>, config: &WalkForwardConfig) -> Vec<WalkForwardWindow> {
+    let (train, test, step) = (config.train_secs * 1_000_000, config.test_secs * 1_000_000, config.step_secs * 1_000_000);
+    if train == 0 || test == 0 || step == 0 {
+        return Vec::new();
+    }
+    let mut windows = Vec::new();
+    let mut train_end = history.start + train;
+    while train_end + test <= history.end {
+        let train_start = if config.anchored { history.start } else { train_end - train };
+        windows.push(WalkForwardWindow { train: train_start..train_end, test: train_end..train_end + test });
+        train_end += step;
+    }
+    windows
+}
+
+/// Which training run a fold trades out of sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Objective {
+    TotalReturn,
+    /// Return per unit of drawdown, so a run does not win by taking on risk
+    ReturnOverDrawdown,
+}
+
+impl Objective {
+    pub fn score(&self, run: &OptimizationRun) -> f64 {
+        match self {
+            Objective::TotalReturn => run.total_return,
+            // A floor keeps drawdown-free runs from scoring infinity
+            Objective::ReturnOverDrawdown => run.total_return / run.max_drawdown.max(0.01),
+        }
+    }
+}
+
+/// Headline results of one backtest
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleStats {
+    pub total_return: f64,
+    pub max_drawdown: f64,
+    pub trades: usize,
+}
+
+impl From<&BacktestReport> for SampleStats {
+    fn from(report: &BacktestReport) -> Self {
+        Self { total_return: report.total_return, max_drawdown: report.max_drawdown, trades: report.trades() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardFold {
+    pub window: WalkForwardWindow,
+    /// Parameters the training window chose
+    pub params: serde_json::Map<String, serde_json::Value>,
+    pub in_sample: SampleStats,
+    pub out_of_sample: SampleStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardReport {
+    pub folds: Vec<WalkForwardFold>,
+    /// Test-window returns compounded, as if traded back to back
+    pub out_of_sample_return: f64,
+    /// Worst drawdown of any test window
+    pub out_of_sample_max_drawdown: f64,
+    pub mean_in_sample_return: f64,
+    pub mean_out_of_sample_return: f64,
+    /// Mean out-of-sample return over mean in-sample return; near 1 the
+    /// tuning generalizes, near or below 0 it was overfit
+    pub efficiency: Option<f64>,
+    /// Share of test windows that made money
+    pub profitable_folds: f64,
+}
+
+impl WalkForwardReport {
+    pub fn from_folds(folds: Vec<WalkForwardFold>) -> Self {
+        let count = folds.len().max(1) as f64;
+        let mean_in_sample_return = folds.iter().map(|fold| fold.in_sample.total_return).sum::<f64>() / count;
+        let mean_out_of_sample_return = folds.iter().map(|fold| fold.out_of_sample.total_return).sum::<f64>() / count;
+        Self {
+            out_of_sample_return: folds.iter().fold(1.0, |growth, fold| growth * (1.0 + fold.out_of_sample.total_return)) - 1.0,
+            out_of_sample_max_drawdown: folds.iter().map(|fold| fold.out_of_sample.max_drawdown).fold(0.0, f64::max),
+            efficiency: (mean_in_sample_return != 0.0).then_some(mean_out_of_sample_return / mean_in_sample_return),
+            profitable_folds: folds.iter().filter(|fold| fold.out_of_sample.total_return > 0.0).count() as f64 / count,
+            mean_in_sample_return,
+            mean_out_of_sample_return,
+            folds,
+        }
+    }
+}
+
+/// Optimize over each training window and backtest the winner on the test window after it.
+///
+/// Outcomes in the backtester's config are only applied to a test window
+/// that reaches the end of `updates`; earlier windows mark positions to
+/// market, so no fold is scored on a result it could not have known.
+#[allow(clippy::too_many_arguments)]
+pub fn walk_forward<F>(
+    strategy: F,
+    base: &AgentConfig,
+    space: &ParamSpace,
+    mode: &SearchMode,
+    objective: Objective,
+    backtester: &Backtester,
+    updates: &[MarketUpdate],
+    config: &WalkForwardConfig,
+) -> Result<WalkForwardReport, SdkError>
+where
+    F: Fn() -> Box<dyn TradingStrategy> + Sync,
+{
+    let times = updates.iter().map(|update| update.timestamp * 1_000);
+    let (Some(start), Some(end)) = (times.clone().min(), times.max()) else {
+        return Err(SdkError::InvalidInput("no history to walk forward over".to_string()));
+    };
+    let windows = walk_forward_windows(start..end + 1, config);
+    if windows.is_empty() {
+        return Err(SdkError::InvalidInput("history is shorter than one train and test window".to_string()));
+    }
+
+    let mut unsettled = backtester.config().clone();
+    unsettled.outcomes.clear();
+    let marking = Backtester::new(unsettled);
+    let slice = |range: &Range<u64>| -> Vec<MarketUpdate> {
+        updates.iter().filter(|update| range.contains(&(update.timestamp * 1_000))).cloned().collect()
+    };
+
+    let mut folds = Vec::with_capacity(windows.len());
+    for window in windows {
+        let trained = optimize(&strategy, base, space, mode, &marking, &slice(&window.train));
+        let Some(best) = trained
+            .runs
+            .iter()
+            .filter(|run| run.error.is_none())
+            .max_by(|a, b| objective.score(a).total_cmp(&objective.score(b)))
+        else {
+            return Err(SdkError::InvalidInput("the strategy accepted none of the parameter sets".to_string()));
+        };
+
+        let mut tuned = base.clone();
+        let mut params = tuned.strategy_params.as_object().cloned().unwrap_or_default();
+        params.extend(best.params.clone());
+        tuned.strategy_params = serde_json::Value::Object(params);
+        let mut live = strategy();
+        live.configure(&tuned.strategy_params)?;
+        let tester = if window.test.end > end { backtester } else { &marking };
+        let report = tester.run(live.as_mut(), &tuned, &slice(&window.test));
+
+        folds.push(WalkForwardFold {
+            window,
+            params: best.params.clone(),
+            in_sample: SampleStats { total_return: best.total_return, max_drawdown: best.max_drawdown, trades: best.trades },
+            out_of_sample: SampleStats::from(&report),
+        });
+    }
+    Ok(WalkForwardReport::from_folds(folds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000;
+
+    #[test]
+    fn test_windows_roll_or_anchor() {
+        let rolling = WalkForwardConfig { train_secs: 10, test_secs: 5, step_secs: 5, anchored: false };
+        let windows = walk_forward_windows(0..26 * SEC, &rolling);
+        assert_eq!(
+            windows,
+            vec![
+                WalkForwardWindow { train: 0..10 * SEC, test: 10 * SEC..15 * SEC },
+                WalkForwardWindow { train: 5 * SEC..15 * SEC, test: 15 * SEC..20 * SEC },
+                WalkForwardWindow { train: 10 * SEC..20 * SEC, test: 20 * SEC..25 * SEC },
+            ]
+        );
+
+        let anchored = WalkForwardConfig { anchored: true, ..rolling };
+        let last = walk_forward_windows(0..26 * SEC, &anchored).pop().unwrap();
+        assert_eq!(last.train, 0..20 * SEC);
+    }
+
+    #[test]
+    fn test_efficiency_compares_test_to_train_returns() {
+        let stats = |total_return| SampleStats { total_return, max_drawdown: 0.0, trades: 1 };
+        let fold = |in_sample, out_of_sample| WalkForwardFold {
+            window: WalkForwardWindow { train: 0..1, test: 1..2 },
+            params: serde_json::Map::new(),
+            in_sample: stats(in_sample),
+            out_of_sample: stats(out_of_sample),
+        };
+        let report = WalkForwardReport::from_folds(vec![fold(0.2, 0.1), fold(0.2, -0.05)]);
+        assert!((report.efficiency.unwrap() - 0.125).abs() < 1e-12);
+        assert!((report.out_of_sample_return - (1.1 * 0.95 - 1.0)).abs() < 1e-12);
+        assert_eq!(report.profitable_folds, 0.5);
+    }
+}
@@ -0,0 +1,253 @@
+//! Wallet history: every movement of funds on the SDK's chain, rebuilt from
+//! the market messages in its blocks. Markets move money by sending the
+//! user chain `Transfer` messages, so each one is a receipt of a payment or
+//! a payout, and the confirmation arriving alongside tells an order's
+//! payment apart from a deposit or a stake. A transfer resent by the
+//! market's outbox shows up once, as the chain applied it.
+
+use crate::optimize::csv_field;
+use crate::{decode_market_message, ChainBlock, MarketMessage, OddsStreamSdk, SdkError, TransferId, CHAIN_LOG_PAGE};
+use linera_sdk::base::{Amount, ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReceiptKind {
+    /// Paid for filled orders
+    OrderPayment,
+    /// Paid to a market for anything else: a liquidity deposit or a parlay stake
+    Payment,
+    /// Paid to the chain that relayed a signed batch
+    RelayerFee,
+    /// Received from a market: winnings, refunds, withdrawn liquidity or referral earnings
+    Payout,
+    /// A claim sent to a market; the payout follows as its own receipt
+    Claim,
+    /// Sent from this chain to another
+    Transfer,
+}
+
+impl ReceiptKind {
+    /// Whether funds left the chain
+    pub fn is_debit(self) -> bool {
+        matches!(self, ReceiptKind::OrderPayment | ReceiptKind::Payment | ReceiptKind::RelayerFee | ReceiptKind::Transfer)
+    }
+}
+
+/// One movement of funds, or claim, recorded in a block of the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferReceipt {
+    pub height: u64,
+    /// Micros timestamp of the block
+    pub timestamp: u64,
+    pub kind: ReceiptKind,
+    /// Market, relayer or recipient on the other side
+    pub counterparty: ChainId,
+    /// Zero for claims
+    pub amount: Amount,
+    /// Fungible token moved; `None` is the native token
+    pub token: Option<ApplicationId>,
+    /// `None` for claims
+    #[serde(default)]
+    pub transfer_id: Option<TransferId>,
+}
+
+/// Receipts in one block of `chain_id`, in message order
+pub fn receipts_from_block(chain_id: ChainId, block: &ChainBlock) -> Vec<TransferReceipt> {
+    let decode = |payload: &[u8]| decode_market_message(payload).ok();
+    let incoming: Vec<(ChainId, MarketMessage)> = block
+        .incoming
+        .iter()
+        .filter_map(|message| decode(&message.payload).map(|decoded| (message.origin, decoded)))
+        .collect();
+    // A market settling fills sends the payment request and the confirmation together
+    let confirmed_cost = |market: ChainId, amount: Amount| {
+        incoming.iter().any(|(origin, message)| {
+            *origin == market
+                && matches!(message, MarketMessage::BatchConfirmed { total_cost, .. } if *total_cost == amount)
+        })
+    };
+    let receipt = |kind, counterparty, amount, token, transfer_id| TransferReceipt {
+        height: block.height,
+        timestamp: block.timestamp,
+        kind,
+        counterparty,
+        amount,
+        token,
+        transfer_id,
+    };
+
+    let mut receipts = Vec::new();
+    for (origin, message) in &incoming {
+        let MarketMessage::Transfer { from, to, amount, token, transfer_id } = message else {
+            continue;
+        };
+        let kind = if *to == chain_id {
+            ReceiptKind::Payout
+        } else if *from != chain_id {
+            continue;
+        } else if to != origin {
+            ReceiptKind::RelayerFee
+        } else if confirmed_cost(*origin, *amount) {
+            ReceiptKind::OrderPayment
+        } else {
+            ReceiptKind::Payment
+        };
+        let counterparty = if *to == chain_id { *from } else { *to };
+        receipts.push(receipt(kind, counterparty, *amount, *token, Some(*transfer_id)));
+    }
+    for message in &block.outgoing {
+        match decode(&message.payload) {
+            Some(MarketMessage::Claim { .. }) => {
+                receipts.push(receipt(ReceiptKind::Claim, message.destination, Amount::zero(), None, None));
+            }
+            Some(MarketMessage::Transfer { from, to, amount, token, transfer_id }) if from == chain_id => {
+                receipts.push(receipt(ReceiptKind::Transfer, to, amount, token, Some(transfer_id)));
+            }
+            _ => {}
+        }
+    }
+    receipts
+}
+
+/// One row per receipt, amounts signed: negative when funds left the chain
+pub fn write_history_csv(receipts: &[TransferReceipt], mut writer: impl Write) -> Result<(), SdkError> {
+    writeln!(writer, "height,timestamp,kind,counterparty,amount,token")?;
+    for receipt in receipts {
+        let sign = if receipt.kind.is_debit() && receipt.amount > Amount::zero() { "-" } else { "" };
+        let kind = serde_json::to_value(receipt.kind)?;
+        writeln!(
+            writer,
+            "{},{},{},{},{}{},{}",
+            receipt.height,
+            receipt.timestamp,
+            kind.as_str().unwrap_or_default(),
+            receipt.counterparty,
+            sign,
+            receipt.amount,
+            csv_field(&receipt.token.map(|token| token.to_string()).unwrap_or_default()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Micros since the Unix epoch for a `YYYY-MM-DD` date (midnight UTC) or a
+/// plain number of seconds
+pub fn parse_since(input: &str) -> Result<u64, SdkError> {
+    let input = input.trim();
+    let invalid = || SdkError::InvalidInput(format!("expected YYYY-MM-DD or Unix seconds, got {}", input));
+    if let Ok(seconds) = input.parse::<u64>() {
+        return Ok(seconds * 1_000_000);
+    }
+    let mut parts = input.splitn(3, '-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || year < 1970 {
+        return Err(invalid());
+    }
+    // Days from the civil calendar date, after Howard Hinnant's `days_from_civil`
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Ok(days as u64 * 86_400_000_000)
+}
+
+impl OddsStreamSdk {
+    /// Receipts on this SDK's chain from blocks at or after `since` (micros), oldest first
+    pub async fn wallet_history(&self, since: u64) -> Result<Vec<TransferReceipt>, SdkError> {
+        let mut receipts = Vec::new();
+        let mut applied = BTreeSet::new();
+        let mut height = 0;
+        loop {
+            let blocks = self.transport.chain_blocks(self.chain_id, height, CHAIN_LOG_PAGE).await?;
+            let Some(last) = blocks.last() else { break };
+            height = last.height + 1;
+            for block in blocks.iter().filter(|block| block.timestamp >= since) {
+                // Later deliveries of a transfer ID are retries the chain ignored
+                let fresh = receipts_from_block(self.chain_id, block)
+                    .into_iter()
+                    .filter(|receipt| receipt.transfer_id.map_or(true, |id| applied.insert(id)));
+                receipts.extend(fresh);
+            }
+            if (blocks.len() as u64) < CHAIN_LOG_PAGE {
+                break;
+            }
+        }
+        Ok(receipts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IncomingMessage, OutgoingMessage, WireFormat};
+
+    #[test]
+    fn test_block_messages_become_receipts() {
+        let (user, market, relayer) = (ChainId::from([1u8; 32]), ChainId::from([2u8; 32]), ChainId::from([3u8; 32]));
+        let incoming = |message: MarketMessage| IncomingMessage {
+            origin: market,
+            origin_height: 4,
+            kind: "Tracked".to_string(),
+            payload: WireFormat::Bcs.encode_untagged(&message).unwrap(),
+        };
+        let transfer = |from, to, tokens| MarketMessage::Transfer {
+            from,
+            to,
+            amount: Amount::from_tokens(tokens),
+            token: None,
+            transfer_id: TransferId { issuer: market, sequence: tokens as u64 },
+        };
+        let block = ChainBlock {
+            chain_id: user,
+            height: 12,
+            hash: String::new(),
+            timestamp: 5_000,
+            incoming: vec![
+                incoming(transfer(user, market, 4)),
+                incoming(transfer(user, relayer, 1)),
+                incoming(MarketMessage::BatchConfirmed {
+                    user_chain_id: user,
+                    order_ids: vec![1],
+                    total_cost: Amount::from_tokens(4),
+                    rejections: Vec::new(),
+                }),
+                incoming(transfer(user, market, 9)),
+                incoming(transfer(market, user, 20)),
+            ],
+            outgoing: vec![OutgoingMessage {
+                destination: market,
+                kind: "Tracked".to_string(),
+                payload: WireFormat::Bcs.encode_untagged(&MarketMessage::Claim { user_chain_id: user }).unwrap(),
+            }],
+            events: Vec::new(),
+        };
+
+        let kinds: Vec<ReceiptKind> = receipts_from_block(user, &block).iter().map(|receipt| receipt.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ReceiptKind::OrderPayment,
+                ReceiptKind::RelayerFee,
+                ReceiptKind::Payment,
+                ReceiptKind::Payout,
+                ReceiptKind::Claim
+            ]
+        );
+    }
+
+    #[test]
+    fn test_since_accepts_dates_and_seconds() {
+        assert_eq!(parse_since("1970-01-02").unwrap(), 86_400_000_000);
+        assert_eq!(parse_since("2024-03-01").unwrap(), 1_709_251_200_000_000);
+        assert_eq!(parse_since("60").unwrap(), 60_000_000);
+        assert!(parse_since("2024-13-01").is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Explicit wire format for contract messages and payloads.
+//!
+//! Linera itself moves messages as BCS. Tooling outside the Rust ecosystem
+//! usually has a CBOR library but no BCS one, so payloads handed to it can be
+//! CBOR instead. Every encoded payload starts with a one-byte format tag, so
+//! a reader never has to guess which encoding it holds.
+
+use crate::SdkError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How a payload after the tag byte is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum WireFormat {
+    /// Linera-native; what contracts send and receive. No floating point.
+    #[default]
+    Bcs,
+    /// Self-describing, for tooling without a BCS implementation
+    Cbor,
+}
+
+impl WireFormat {
+    pub const fn tag(self) -> u8 {
+        match self {
+            WireFormat::Bcs => 0x01,
+            WireFormat::Cbor => 0x02,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0x01 => Some(WireFormat::Bcs),
+            0x02 => Some(WireFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Encode `value` without the tag byte
+    pub fn encode_untagged<T: Serialize>(self, value: &T) -> Result<Vec<u8>, SdkError> {
+        match self {
+            WireFormat::Bcs => bcs::to_bytes(value).map_err(|e| SdkError::InvalidInput(format!("BCS encoding: {}", e))),
+            WireFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes)
+                    .map_err(|e| SdkError::InvalidInput(format!("CBOR encoding: {}", e)))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decode a payload without the tag byte
+    pub fn decode_untagged<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, SdkError> {
+        match self {
+            WireFormat::Bcs => bcs::from_bytes(bytes).map_err(|e| SdkError::InvalidInput(format!("BCS decoding: {}", e))),
+            WireFormat::Cbor => {
+                ciborium::from_reader(bytes).map_err(|e| SdkError::InvalidInput(format!("CBOR decoding: {}", e)))
+            }
+        }
+    }
+
+    /// Tag byte followed by `value` in this format
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, SdkError> {
+        let mut bytes = vec![self.tag()];
+        bytes.extend(self.encode_untagged(value)?);
+        Ok(bytes)
+    }
+}
+
+/// Decode a tagged payload in whichever format its tag names
+pub fn decode_tagged<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SdkError> {
+    let (&tag, payload) = bytes
+        .split_first()
+        .ok_or_else(|| SdkError::InvalidInput("empty wire payload".to_string()))?;
+    let format = WireFormat::from_tag(tag)
+        .ok_or_else(|| SdkError::InvalidInput(format!("unknown wire format tag {:#04x}", tag)))?;
+    format.decode_untagged(payload)
+}
+
+impl fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireFormat::Bcs => write!(f, "bcs"),
+            WireFormat::Cbor => write!(f, "cbor"),
+        }
+    }
+}
+
+impl FromStr for WireFormat {
+    type Err = SdkError;
+
+    fn from_str(s: &str) -> Result<Self, SdkError> {
+        match s.to_ascii_lowercase().as_str() {
+            "bcs" => Ok(WireFormat::Bcs),
+            "cbor" => Ok(WireFormat::Cbor),
+            other => Err(SdkError::InvalidInput(format!("unknown wire format {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MarketMessage, RelayerFee};
+    use linera_sdk::base::{Amount, ChainId};
+
+    // Byte layouts the market contract decodes; a change here is a protocol change
+    #[test]
+    fn test_bcs_layout_is_pinned() {
+        let chain = ChainId::from([7u8; 32]);
+
+        let claim = WireFormat::Bcs.encode(&MarketMessage::Claim { user_chain_id: chain }).unwrap();
+        let mut expected = vec![0x01, 19];
+        expected.extend([7u8; 32]);
+        assert_eq!(claim, expected);
+
+        let uncross = WireFormat::Bcs.encode(&MarketMessage::UncrossAuction).unwrap();
+        assert_eq!(uncross, vec![0x01, 15]);
+
+        let amend = MarketMessage::AmendOrder {
+            user_chain_id: chain,
+            order_id: 9,
+            amount: Amount::from_attos(1),
+            max_price: None,
+            nonce: 2,
+            signature: None,
+        };
+        let mut expected = vec![0x01, 23];
+        expected.extend([7u8; 32]);
+        expected.extend(9u64.to_le_bytes());
+        expected.extend(1u128.to_le_bytes());
+        expected.push(0);
+        expected.extend(2u64.to_le_bytes());
+        expected.push(0);
+        assert_eq!(WireFormat::Bcs.encode(&amend).unwrap(), expected);
+
+        let add = WireFormat::Bcs
+            .encode(&MarketMessage::AddLiquidity { provider: chain, amount: Amount::from_attos(1) })
+            .unwrap();
+        let mut expected = vec![0x01, 8];
+        expected.extend([7u8; 32]);
+        // Amounts are little-endian u128 attos
+        expected.extend(1u128.to_le_bytes());
+        assert_eq!(add, expected);
+    }
+
+    // Order batches carry nested structs and options, so they are pinned against the bytes
+    // the contract's own types produce rather than written out by hand
+    #[test]
+    fn test_order_batches_match_contract_encoding() {
+        use crate::{order_id, Order, OrderSignature, WireSide};
+        use oddsstream_market::signing;
+
+        let user = ChainId::from([1u8; 32]);
+        let relayer = ChainId::from([4u8; 32]);
+        let orders = vec![
+            Order {
+                id: order_id(5, 0).unwrap(),
+                side: WireSide::BuyYes,
+                amount: Amount::from_tokens(10),
+                max_price: Some(Amount::from_attos(600)),
+                subaccount: Some("hedge".to_string()),
+                referral_code: None,
+            },
+            Order {
+                id: order_id(5, 1).unwrap(),
+                side: WireSide::BuyNo,
+                amount: Amount::from_attos(3),
+                max_price: None,
+                subaccount: None,
+                referral_code: Some("ref".to_string()),
+            },
+        ];
+        let contract_orders: Vec<oddsstream_market::Order> = orders
+            .iter()
+            .map(|order| oddsstream_market::Order {
+                id: order.id,
+                side: match order.side {
+                    WireSide::BuyYes => oddsstream_market::OrderSide::BuyYes,
+                    WireSide::BuyNo => oddsstream_market::OrderSide::BuyNo,
+                },
+                amount: order.amount,
+                max_price: order.max_price,
+                subaccount: order.subaccount.clone(),
+                referral_code: order.referral_code.clone(),
+            })
+            .collect();
+        let signature = |seed: u8| OrderSignature { public_key: vec![seed; 32], signature: vec![seed; 64] };
+        let contract_signature =
+            |seed: u8| signing::OrderSignature { public_key: vec![seed; 32], signature: vec![seed; 64] };
+
+        let batch = MarketMessage::BatchedOrders {
+            user_chain_id: user,
+            orders: orders.clone(),
+            nonce: 5,
+            signature: Some(signature(8)),
+            relayer_fee: Some(RelayerFee { relayer, amount: Amount::from_tokens(1) }),
+            cosignatures: vec![signature(9)],
+        };
+        let contract_batch = oddsstream_market::MarketMessage::BatchedOrders {
+            user_chain_id: user,
+            orders: contract_orders.clone(),
+            nonce: 5,
+            signature: Some(contract_signature(8)),
+            relayer_fee: Some(signing::RelayerFee { relayer, amount: Amount::from_tokens(1) }),
+            cosignatures: vec![contract_signature(9)],
+        };
+        assert_eq!(
+            WireFormat::Bcs.encode_untagged(&batch).unwrap(),
+            bcs::to_bytes(&contract_batch).unwrap()
+        );
+
+        let reserve = MarketMessage::ReserveOrders { saga_id: 3, user_chain_id: user, orders };
+        let contract_reserve =
+            oddsstream_market::MarketMessage::ReserveOrders { saga_id: 3, user_chain_id: user, orders: contract_orders };
+        assert_eq!(
+            WireFormat::Bcs.encode_untagged(&reserve).unwrap(),
+            bcs::to_bytes(&contract_reserve).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip_in_both_formats() {
+        let fee = RelayerFee { relayer: ChainId::from([3u8; 32]), amount: Amount::from_tokens(2) };
+        for format in [WireFormat::Bcs, WireFormat::Cbor] {
+            let bytes = format.encode(&fee).unwrap();
+            assert_eq!(bytes[0], format.tag());
+            assert_eq!(decode_tagged::<RelayerFee>(&bytes).unwrap(), fee);
+        }
+        assert!(decode_tagged::<RelayerFee>(&[0x7f, 0]).is_err());
+        assert!(decode_tagged::<RelayerFee>(&[]).is_err());
+    }
+}